@@ -227,6 +227,10 @@ impl win32::Host for EnvRef {
         let gui = env.ensure_gui().unwrap();
         gui.init_audio(sample_rate)
     }
+
+    fn clone_box(&self) -> Box<dyn win32::Host> {
+        Box::new(self.clone())
+    }
 }
 
 pub fn new_host() -> EnvRef {
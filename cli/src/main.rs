@@ -51,6 +51,14 @@ struct Args {
     #[argh(switch)]
     audio: bool,
 
+    /// directory of trackNN.wav files to play as the audio CD (default "cdaudio")
+    #[argh(option)]
+    cd_audio: Option<String>,
+
+    /// registry file (.reg format) to load, and save changes to
+    #[argh(option)]
+    registry: Option<String>,
+
     /// command line to run
     #[argh(positional, greedy)]
     cmdline: Vec<String>,
@@ -151,6 +159,13 @@ fn main() -> anyhow::Result<ExitCode> {
     let mut machine = win32::Machine::new(Box::new(host.clone()));
     machine.set_external_dlls(&args.external_dll);
     machine.state.winmm.audio_enabled = args.audio;
+    if let Some(dir) = args.cd_audio {
+        machine.state.winmm.mci.cd_audio_dir = dir;
+    }
+    if let Some(registry) = &args.registry {
+        let path = cwd.join(registry).normalize();
+        win32::winapi::advapi32::load_registry(&mut machine, &path).map_err(|err| anyhow!(err))?;
+    }
 
     let addrs = machine
         .load_exe(&buf, cmdline, None)
@@ -12,6 +12,77 @@ fn map_button(b: sdl2::mouse::MouseButton) -> Option<win32::MouseButton> {
     })
 }
 
+/// Map an SDL key to a Windows virtual-key code.
+fn map_keycode(keycode: sdl2::keyboard::Keycode) -> Option<u8> {
+    use sdl2::keyboard::Keycode;
+    use win32::winapi::user32::*;
+    let code = keycode as i32;
+    Some(match keycode {
+        // SDL uses the ASCII values for these, but lowercase.
+        _ if (b'a' as i32..=b'z' as i32).contains(&code) => code as u8 - b'a' + b'A',
+        _ if (b'0' as i32..=b'9' as i32).contains(&code) => code as u8,
+        _ if (Keycode::F1 as i32..=Keycode::F12 as i32).contains(&code) => {
+            VK_F1 + (code - Keycode::F1 as i32) as u8
+        }
+        _ if (Keycode::Kp1 as i32..=Keycode::Kp9 as i32).contains(&code) => {
+            VK_NUMPAD0 + 1 + (code - Keycode::Kp1 as i32) as u8
+        }
+        Keycode::Kp0 => VK_NUMPAD0,
+        Keycode::KpPeriod => VK_DECIMAL,
+        Keycode::KpDivide => VK_DIVIDE,
+        Keycode::KpMultiply => VK_MULTIPLY,
+        Keycode::KpMinus => VK_SUBTRACT,
+        Keycode::KpPlus => VK_ADD,
+        Keycode::Return | Keycode::KpEnter => VK_RETURN,
+        Keycode::Backspace => VK_BACK,
+        Keycode::Tab => VK_TAB,
+        Keycode::Escape => VK_ESCAPE,
+        Keycode::Space => VK_SPACE,
+        Keycode::LShift | Keycode::RShift => VK_SHIFT,
+        Keycode::LCtrl | Keycode::RCtrl => VK_CONTROL,
+        Keycode::LAlt | Keycode::RAlt => VK_MENU,
+        Keycode::CapsLock => VK_CAPITAL,
+        Keycode::NumLockClear => VK_NUMLOCK,
+        Keycode::ScrollLock => VK_SCROLL,
+        Keycode::PageUp => VK_PRIOR,
+        Keycode::PageDown => VK_NEXT,
+        Keycode::End => VK_END,
+        Keycode::Home => VK_HOME,
+        Keycode::Left => VK_LEFT,
+        Keycode::Up => VK_UP,
+        Keycode::Right => VK_RIGHT,
+        Keycode::Down => VK_DOWN,
+        Keycode::Insert => VK_INSERT,
+        Keycode::Delete => VK_DELETE,
+        Keycode::Semicolon => VK_OEM_1,
+        Keycode::Equals => VK_OEM_PLUS,
+        Keycode::Comma => VK_OEM_COMMA,
+        Keycode::Minus => VK_OEM_MINUS,
+        Keycode::Period => VK_OEM_PERIOD,
+        Keycode::Slash => VK_OEM_2,
+        Keycode::Backquote => VK_OEM_3,
+        Keycode::LeftBracket => VK_OEM_4,
+        Keycode::Backslash => VK_OEM_5,
+        Keycode::RightBracket => VK_OEM_6,
+        Keycode::Quote => VK_OEM_7,
+        _ => return None,
+    })
+}
+
+fn map_key(
+    keycode: Option<sdl2::keyboard::Keycode>,
+    down: bool,
+    repeat: bool,
+) -> Option<win32::KeyMessage> {
+    let vk = map_keycode(keycode?)?;
+    Some(win32::KeyMessage {
+        vk,
+        scancode: win32::winapi::user32::vk_to_scancode(vk),
+        down,
+        repeat,
+    })
+}
+
 fn message_from_event(hwnd: u32, event: sdl2::event::Event) -> Option<win32::Message> {
     let (time, detail) = match event {
         sdl2::event::Event::Quit { timestamp } => (timestamp, win32::MessageDetail::Quit),
@@ -56,6 +127,24 @@ fn message_from_event(hwnd: u32, event: sdl2::event::Event) -> Option<win32::Mes
                 y: y as u32,
             }),
         ),
+        sdl2::event::Event::KeyDown {
+            timestamp,
+            keycode,
+            repeat,
+            ..
+        } => (
+            timestamp,
+            win32::MessageDetail::Key(map_key(keycode, true, repeat)?),
+        ),
+        sdl2::event::Event::KeyUp {
+            timestamp,
+            keycode,
+            repeat,
+            ..
+        } => (
+            timestamp,
+            win32::MessageDetail::Key(map_key(keycode, false, repeat)?),
+        ),
         _ => {
             // log::warn!("unhandled event: {:?}", event);
             return None;
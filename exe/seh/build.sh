@@ -0,0 +1,11 @@
+#!/bin/sh
+
+# Assembled with LLVM tools rather than built from C, to control the exact layout
+# of the SEH frames.
+
+set -e
+
+llvm-mc -triple i586-pc-windows-msvc -filetype=obj seh.s -o seh.obj
+llvm-dlltool -m i386 -k -d kernel32.def -l kernel32.lib
+exec lld-link /entry:start /subsystem:console /safeseh:no /nodefaultlib /out:seh.exe \
+  seh.obj kernel32.lib ../../win32/lib/retrowin32_test.lib
@@ -0,0 +1,6 @@
+#!/bin/bash
+
+set -eo pipefail
+
+out=$(cargo run -p retrowin32 -F x86-emu -- seh.exe | tr -d '\r')
+diff -u out.txt <(echo "$out")
@@ -0,0 +1,197 @@
+# Structured exception handling around callbacks from retrowin32 into x86 code.
+#
+# The equivalent of:
+#   void body(int arg) {
+#     __try { retrowin32_test_callback1(fault, 0); }
+#     __except (EXCEPTION_EXECUTE_HANDLER) { print("caught"); }
+#     result = arg * 2;
+#   }
+#   retrowin32_test_callback1(body, 21);  // must return normally
+# written in assembly to control the SEH frame layout exactly.
+# The outer callback matters: the inner callback's frames are unwound by the
+# handler, and must not be mistaken for the outer one when body returns.
+#
+# Then memory faults are resumed with ExceptionContinueExecution, after the
+# handler repoints the faulting address register; the instruction must rerun
+# against the registers it started with.
+
+.intel_syntax noprefix
+
+.data
+stdout: .long 0
+written: .long 0
+saved_esp: .long 0
+arg: .long 0
+result: .long 0
+msg_caught: .ascii "caught exception from callback\n"
+msg_caught_end:
+.set msg_caught_len, msg_caught_end - msg_caught
+msg_missed: .ascii "callback returned despite faulting\n"
+msg_missed_end:
+.set msg_missed_len, msg_missed_end - msg_missed
+msg_ok: .ascii "callback after unwind stored 42\n"
+msg_ok_end:
+.set msg_ok_len, msg_ok_end - msg_ok
+msg_bad_result: .ascii "callback after unwind returned wrong value\n"
+msg_bad_result_end:
+.set msg_bad_result_len, msg_bad_result_end - msg_bad_result
+msg_bad_esp: .ascii "stack pointer corrupted after unwind\n"
+msg_bad_esp_end:
+.set msg_bad_esp_len, msg_bad_esp_end - msg_bad_esp
+fixup: .long 10
+access: .long -1
+msg_resumed: .ascii "resumed read and write faults\n"
+msg_resumed_end:
+.set msg_resumed_len, msg_resumed_end - msg_resumed
+msg_bad_resume: .ascii "resumed fault saw clobbered state\n"
+msg_bad_resume_end:
+.set msg_bad_resume_len, msg_bad_resume_end - msg_bad_resume
+
+.text
+
+# WriteFile(stdout, msg, len, &written, NULL)
+.macro print msg
+  push 0
+  mov ecx, offset written
+  push ecx
+  mov ecx, offset \msg\()_len
+  push ecx
+  mov ecx, offset \msg
+  push ecx
+  push [stdout]
+  call _WriteFile@20
+.endm
+
+.globl _start
+_start:
+  push -11
+  call _GetStdHandle@4
+  mov [stdout], eax
+
+  mov ebx, esp
+  push 21
+  mov ecx, offset body
+  push ecx
+  call _retrowin32_test_callback1@8
+  cmp esp, ebx
+  jne 2f
+  cmp eax, 1
+  jne 3f
+  cmp dword ptr [result], 42
+  jne 3f
+  print msg_ok
+  jmp resume
+2:
+  print msg_bad_esp
+  push 1
+  call _ExitProcess@4
+3:
+  print msg_bad_result
+  push 1
+  call _ExitProcess@4
+
+resume:
+  mov ecx, offset resume_handler
+  push ecx
+  push dword ptr fs:[0]
+  mov dword ptr fs:[0], esp
+
+  # Read fault: eax must still be 5 when the imul reruns.
+  mov eax, 5
+  mov esi, 0xfffffff0
+  imul eax, [esi]
+  cmp eax, 50
+  jne 4f
+  cmp dword ptr [access], 0
+  jne 4f
+
+  # Write fault: the pop must not move esp until it reruns.
+  mov ebx, esp
+  push 7
+  mov edi, 0xfffffff0
+  pop dword ptr [edi]
+  cmp esp, ebx
+  jne 4f
+  cmp dword ptr [fixup], 7
+  jne 4f
+  cmp dword ptr [access], 1
+  jne 4f
+
+  pop dword ptr fs:[0]
+  add esp, 4
+  print msg_resumed
+  push 0
+  call _ExitProcess@4
+4:
+  print msg_bad_resume
+  push 1
+  call _ExitProcess@4
+
+# stdcall void body(u32)
+body:
+  mov eax, [esp+4]
+  mov [arg], eax
+  push ebx
+
+  # __try: register the handler on the SEH chain.
+  mov ecx, offset handler
+  push ecx
+  push dword ptr fs:[0]
+  mov dword ptr fs:[0], esp
+  mov [saved_esp], esp
+
+  push 0
+  mov ecx, offset fault
+  push ecx
+  call _retrowin32_test_callback1@8
+  print msg_missed
+  jmp 1f
+
+except:
+  # __except body: the handler jumps here after unwinding.
+  mov esp, [saved_esp]
+  print msg_caught
+
+1:
+  # Unregister the handler.
+  pop dword ptr fs:[0]
+  add esp, 4
+
+  mov eax, [arg]
+  add eax, eax
+  mov [result], eax
+  pop ebx
+  ret 4
+
+# stdcall u32 fault(u32): raises an illegal instruction exception.
+fault:
+  ud2
+
+# cdecl EXCEPTION_DISPOSITION handler(record, frame, context, dispatcher_context)
+handler:
+  mov eax, [esp+4]
+  # Called again while unwinding (EXCEPTION_UNWINDING | EXCEPTION_EXIT_UNWIND).
+  test dword ptr [eax+4], 6
+  jnz 1f
+  push 0
+  push eax
+  push 0
+  push [esp+20]
+  call _RtlUnwind@16
+  jmp except
+1:
+  mov eax, 1 # ExceptionContinueSearch
+  ret
+
+# cdecl EXCEPTION_DISPOSITION resume_handler(record, frame, context, dispatcher_context)
+resume_handler:
+  mov eax, [esp+4]
+  # ExceptionInformation[0]: 0 for a read, 1 for a write.
+  mov ecx, [eax+0x14]
+  mov [access], ecx
+  mov eax, [esp+12]
+  mov ecx, offset fixup
+  mov [eax+0xa0], ecx # Esi
+  mov [eax+0x9c], ecx # Edi
+  xor eax, eax # ExceptionContinueExecution
+  ret
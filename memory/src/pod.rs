@@ -28,5 +28,6 @@ unsafe impl Pod for u32 {}
 unsafe impl Pod for i32 {}
 unsafe impl Pod for u64 {}
 unsafe impl Pod for i64 {}
+unsafe impl Pod for u128 {}
 unsafe impl Pod for f32 {}
 unsafe impl Pod for f64 {}
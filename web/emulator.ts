@@ -31,6 +31,10 @@ class Window implements wasm.JsWindow {
     this.canvas.onmousedown = stashEvent;
     this.canvas.onmouseup = stashEvent;
     this.canvas.onmousemove = stashEvent;
+    // Make the canvas focusable so it receives keyboard events.
+    this.canvas.tabIndex = 0;
+    this.canvas.onkeydown = stashEvent;
+    this.canvas.onkeyup = stashEvent;
     this.canvas.oncontextmenu = (ev) => {
       return false;
     };
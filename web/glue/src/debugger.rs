@@ -46,7 +46,10 @@ impl Registers {
             ss: x86.regs.get16(x86::Register::SS),
             flags: x86.flags.bits(),
             flags_str: format!("{:?}", x86.flags),
-            st: x86.fpu.st[x86.fpu.st_top..].into(),
+            st: x86.fpu.st[x86.fpu.st_top..]
+                .iter()
+                .map(|f| x86::FpuFloat::to_f64(*f))
+                .collect(),
         }
     }
 }
@@ -1,7 +1,7 @@
 //! Implementations of the traits in win32/host.rs, providing the hosting API for the emulator.
 
 use anyhow::bail;
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::{prelude::*, JsCast};
 use win32::{Stat, StatKind, WindowsPath};
 
 struct WebSurface {
@@ -207,6 +207,24 @@ fn map_mousevent(event: web_sys::MouseEvent) -> anyhow::Result<win32::MouseMessa
     })
 }
 
+fn map_keyevent(event: web_sys::KeyboardEvent, down: bool) -> anyhow::Result<win32::KeyMessage> {
+    // DOM keyCodes mostly match Windows virtual-key codes, except for
+    // some punctuation in Firefox.
+    let vk = match event.key_code() {
+        59 => 0xBA,  // ;
+        61 => 0xBB,  // =
+        173 => 0xBD, // -
+        code @ 1..=0xFE => code as u8,
+        code => bail!("unhandled keyCode {code}"),
+    };
+    Ok(win32::KeyMessage {
+        vk,
+        scancode: win32::winapi::user32::vk_to_scancode(vk),
+        down,
+        repeat: event.repeat(),
+    })
+}
+
 fn message_from_event(event: web_sys::Event) -> anyhow::Result<win32::Message> {
     let hwnd = js_sys::Reflect::get(&event, &JsValue::from_str("hwnd"))
         .unwrap()
@@ -230,6 +248,14 @@ fn message_from_event(event: web_sys::Event) -> anyhow::Result<win32::Message> {
             event.down = false;
             win32::MessageDetail::Mouse(event)
         }
+        "keydown" => win32::MessageDetail::Key(map_keyevent(
+            event.unchecked_into::<web_sys::KeyboardEvent>(),
+            true,
+        )?),
+        "keyup" => win32::MessageDetail::Key(map_keyevent(
+            event.unchecked_into::<web_sys::KeyboardEvent>(),
+            false,
+        )?),
         ty => bail!("unhandled event type {ty}"),
     };
     log::info!("msg: {:?}", detail);
@@ -372,4 +398,9 @@ impl win32::Host for JsHost {
     fn init_audio(&mut self, _sample_rate: u32) -> Box<dyn win32::Audio> {
         todo!()
     }
+
+    fn clone_box(&self) -> Box<dyn win32::Host> {
+        let host: &JsValue = self;
+        Box::new(host.clone().unchecked_into::<JsHost>())
+    }
 }
@@ -107,10 +107,22 @@ pub struct MouseMessage {
     pub y: u32,
 }
 
+#[derive(Debug)]
+pub struct KeyMessage {
+    /// Windows virtual-key code, e.g. VK_RETURN.
+    pub vk: u8,
+    /// Set 1 scan code, with 0xE000 set for extended keys; see user32::vk_to_scancode.
+    pub scancode: u16,
+    pub down: bool,
+    /// True if this is an autorepeat of a held key.
+    pub repeat: bool,
+}
+
 #[derive(Debug)]
 pub enum MessageDetail {
     Quit,
     Mouse(MouseMessage),
+    Key(KeyMessage),
 }
 
 #[derive(Debug)]
@@ -162,4 +174,7 @@ pub trait Host: FileSystem {
     fn create_surface(&mut self, hwnd: u32, opts: &SurfaceOptions) -> Box<dyn Surface>;
 
     fn init_audio(&mut self, sample_rate: u32) -> Box<dyn Audio>;
+
+    /// Get another reference to this host, e.g. for a child process to share.
+    fn clone_box(&self) -> Box<dyn Host>;
 }
@@ -7,10 +7,15 @@ use crate::{
     shims::{Handler, Shims},
     winapi::{
         self,
-        kernel32::{create_thread, CommandLine, NewThread},
+        kernel32::{
+            create_thread, dispatch_exception, CommandLine, NewThread, CONTEXT, CONTEXT_CONTROL,
+            CONTEXT_EXTENDED_REGISTERS, CONTEXT_INTEGER, CONTEXT_SEGMENTS, EXCEPTION_POINTERS,
+            EXCEPTION_RECORD, STATUS_ACCESS_VIOLATION, STATUS_ILLEGAL_INSTRUCTION,
+            STATUS_INTEGER_DIVIDE_BY_ZERO,
+        },
     },
 };
-use memory::{Extensions, ExtensionsMut, Mem};
+use memory::{Extensions, ExtensionsMut, Mem, Pod};
 use std::collections::HashMap;
 
 pub struct BoxMem(Box<[u8]>);
@@ -148,16 +153,28 @@ impl MachineX<Emulator> {
     }
 
     pub fn run(&mut self) -> bool {
+        let children = winapi::kernel32::run_child_processes(self);
         self.emu.x86.schedule();
         match &self.emu.x86.cpu().state {
             x86::CPUState::Running => self.execute_block(),
             x86::CPUState::SysCall => self.syscall(),
             x86::CPUState::Blocked(wait) => {
                 let wait = *wait;
-                if self.host.block(wait) {
+                if let winapi::kernel32::ChildProgress::Blocked(until) = children {
+                    // Block the host until the parent or any child process is due.
+                    let wait = match (wait, until) {
+                        (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+                        (a, b) => a.or(b),
+                    };
+                    if self.host.block(wait) {
+                        self.unblock();
+                    } else {
+                        self.status = Status::Blocked;
+                    }
+                } else if wait.is_some_and(|until| self.host.ticks() >= until) {
+                    // Don't block the host while child processes need to run; just
+                    // check whether the wait has expired.
                     self.unblock();
-                } else {
-                    self.status = Status::Blocked;
                 }
             }
             x86::CPUState::Error(message) => {
@@ -165,6 +182,10 @@ impl MachineX<Emulator> {
                     message: message.clone(),
                 };
             }
+            x86::CPUState::Exception(exception) => {
+                let exception = *exception;
+                self.fault(exception);
+            }
             x86::CPUState::DebugBreak => {
                 self.status = Status::DebugBreak;
             }
@@ -219,6 +240,160 @@ impl MachineX<Emulator> {
         }
     }
 
+    /// Turn a CPU fault into a Windows exception, dispatched on the faulting thread.
+    fn fault(&mut self, exception: x86::Exception) {
+        self.emu.x86.cpu_mut().state = x86::CPUState::Running;
+        let context = self.get_context();
+        let record = match exception {
+            x86::Exception::DivideError => {
+                EXCEPTION_RECORD::new(STATUS_INTEGER_DIVIDE_BY_ZERO, 0, context.Eip, &[])
+            }
+            x86::Exception::InvalidOpcode => {
+                EXCEPTION_RECORD::new(STATUS_ILLEGAL_INSTRUCTION, 0, context.Eip, &[])
+            }
+            x86::Exception::AccessViolation { addr, write } => {
+                // Parameters are (read=0/write=1, address).
+                EXCEPTION_RECORD::new(
+                    STATUS_ACCESS_VIOLATION,
+                    0,
+                    context.Eip,
+                    &[write as u32, addr],
+                )
+            }
+        };
+        self.raise_exception(&record, &context);
+    }
+
+    /// Copy the current thread's registers into a CONTEXT.
+    pub fn get_context(&self) -> CONTEXT {
+        use x86::Register::*;
+        let cpu = self.emu.x86.cpu();
+        let regs = &cpu.regs;
+        let mut context = CONTEXT::zeroed();
+        context.ContextFlags =
+            CONTEXT_CONTROL | CONTEXT_INTEGER | CONTEXT_SEGMENTS | CONTEXT_EXTENDED_REGISTERS;
+        context.SegGs = regs.get16(GS) as u32;
+        context.SegFs = regs.get16(FS) as u32;
+        context.SegEs = regs.get16(ES) as u32;
+        context.SegDs = regs.get16(DS) as u32;
+        context.Edi = regs.get32(EDI);
+        context.Esi = regs.get32(ESI);
+        context.Ebx = regs.get32(EBX);
+        context.Edx = regs.get32(EDX);
+        context.Ecx = regs.get32(ECX);
+        context.Eax = regs.get32(EAX);
+        context.Ebp = regs.get32(EBP);
+        context.Eip = regs.eip;
+        context.SegCs = regs.get16(CS) as u32;
+        context.EFlags = cpu.flags.bits();
+        context.Esp = regs.get32(ESP);
+        context.SegSs = regs.get16(SS) as u32;
+        // FXSAVE layout: MXCSR at 24, XMM registers at 160.
+        let ext = &mut context.ExtendedRegisters;
+        ext[24..28].copy_from_slice(&regs.mxcsr.to_le_bytes());
+        for (i, reg) in [XMM0, XMM1, XMM2, XMM3, XMM4, XMM5, XMM6, XMM7]
+            .into_iter()
+            .enumerate()
+        {
+            let ofs = 160 + i * 16;
+            ext[ofs..ofs + 16].copy_from_slice(&regs.get128(reg).to_le_bytes());
+        }
+        context
+    }
+
+    /// Load the current thread's registers from a CONTEXT.
+    pub fn set_context(&mut self, context: &CONTEXT) {
+        use x86::Register::*;
+        let cpu = self.emu.x86.cpu_mut();
+        if context.ContextFlags & CONTEXT_SEGMENTS == CONTEXT_SEGMENTS {
+            cpu.regs.set16(GS, context.SegGs as u16);
+            cpu.regs.set16(ES, context.SegEs as u16);
+            cpu.regs.set16(DS, context.SegDs as u16);
+            // FS is left alone, as we rely on fs_addr pointing at the TEB.
+        }
+        if context.ContextFlags & CONTEXT_INTEGER == CONTEXT_INTEGER {
+            cpu.regs.set32(EDI, context.Edi);
+            cpu.regs.set32(ESI, context.Esi);
+            cpu.regs.set32(EBX, context.Ebx);
+            cpu.regs.set32(EDX, context.Edx);
+            cpu.regs.set32(ECX, context.Ecx);
+            cpu.regs.set32(EAX, context.Eax);
+        }
+        if context.ContextFlags & CONTEXT_CONTROL == CONTEXT_CONTROL {
+            cpu.regs.set32(EBP, context.Ebp);
+            cpu.regs.set32(ESP, context.Esp);
+            cpu.regs.eip = context.Eip;
+            cpu.flags = x86::Flags::from_bits_truncate(context.EFlags);
+        }
+        if context.ContextFlags & CONTEXT_EXTENDED_REGISTERS == CONTEXT_EXTENDED_REGISTERS {
+            let ext = &context.ExtendedRegisters;
+            cpu.regs.mxcsr = u32::from_le_bytes(ext[24..28].try_into().unwrap());
+            for (i, reg) in [XMM0, XMM1, XMM2, XMM3, XMM4, XMM5, XMM6, XMM7]
+                .into_iter()
+                .enumerate()
+            {
+                let ofs = 160 + i * 16;
+                cpu.regs.set128(
+                    reg,
+                    u128::from_le_bytes(ext[ofs..ofs + 16].try_into().unwrap()),
+                );
+            }
+        }
+    }
+
+    /// Begin dispatching an exception on the current thread.
+    /// The exception structures are copied onto the stack below the faulting context's esp,
+    /// where handlers expect to find them.  If the stack can't hold them, the machine stops.
+    pub fn raise_exception(&mut self, record: &EXCEPTION_RECORD, context: &CONTEXT) {
+        let mem = self.emu.memory.mem();
+        let Some(context_addr) = context
+            .Esp
+            .checked_sub(std::mem::size_of::<CONTEXT>() as u32)
+            .map(|addr| addr & !0xF)
+        else {
+            return self.exception_overflow(record);
+        };
+        let Some(pointers_addr) = context_addr.checked_sub(
+            (std::mem::size_of::<EXCEPTION_RECORD>() + std::mem::size_of::<EXCEPTION_POINTERS>())
+                as u32,
+        ) else {
+            return self.exception_overflow(record);
+        };
+        // The frame spans pointers_addr up to the end of the CONTEXT.
+        if mem.is_oob::<CONTEXT>(context_addr) {
+            return self.exception_overflow(record);
+        }
+        let record_addr = pointers_addr + std::mem::size_of::<EXCEPTION_POINTERS>() as u32;
+        mem.put_pod::<CONTEXT>(context_addr, *context);
+        mem.put_pod::<EXCEPTION_RECORD>(record_addr, *record);
+        mem.put_pod::<EXCEPTION_POINTERS>(
+            pointers_addr,
+            EXCEPTION_POINTERS {
+                ExceptionRecord: record_addr,
+                ContextRecord: context_addr,
+            },
+        );
+
+        let machine = self as *mut Machine;
+        let cpu = self.emu.x86.cpu_mut();
+        cpu.regs.set32(x86::Register::ESP, pointers_addr);
+        cpu.spawn_async(Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            dispatch_exception(machine, pointers_addr).await;
+        }));
+    }
+
+    /// Stop the machine on an exception whose record doesn't fit on the stack.
+    fn exception_overflow(&mut self, record: &EXCEPTION_RECORD) {
+        self.status = Status::Error {
+            message: format!(
+                "no stack space to raise exception {code:08x} at {addr:08x}",
+                code = record.ExceptionCode,
+                addr = record.ExceptionAddress
+            ),
+        };
+    }
+
     pub async fn call_x86(&mut self, func: u32, args: Vec<u32>) -> u32 {
         self.emu
             .x86
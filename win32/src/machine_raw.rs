@@ -4,7 +4,10 @@ use crate::{
     pe,
     shims::Shims,
     shims_raw::retrowin32_syscall,
-    winapi::{self, kernel32::CommandLine},
+    winapi::{
+        self,
+        kernel32::{CommandLine, CONTEXT, EXCEPTION_RECORD},
+    },
 };
 use memory::Mem;
 use std::collections::HashMap;
@@ -106,6 +109,18 @@ impl MachineX<Emulator> {
         todo!();
     }
 
+    pub fn get_context(&self) -> CONTEXT {
+        todo!();
+    }
+
+    pub fn set_context(&mut self, _context: &CONTEXT) {
+        todo!();
+    }
+
+    pub fn raise_exception(&mut self, _record: &EXCEPTION_RECORD, _context: &CONTEXT) {
+        todo!();
+    }
+
     pub fn exit(&mut self, exit_code: u32) {
         self.status = Status::Exit(exit_code);
     }
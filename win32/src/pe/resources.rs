@@ -53,7 +53,10 @@ pub enum RT {
     CURSOR = 1,
     BITMAP = 2,
     ICON = 3,
+    MENU = 4,
+    DIALOG = 5,
     STRING = 6,
+    ACCELERATOR = 9,
 }
 
 #[derive(Debug, Eq)]
@@ -36,7 +36,7 @@ mod wrappers {
         let mem = machine.mem().detach();
         let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
         let lpSubKey = <Option<&str>>::from_stack(mem, stack_args + 4u32);
-        let phkResult = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+        let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 8u32);
         let __trace_record = if crate::trace::enabled("advapi32") {
             crate::trace::Record::new(
                 winapi::advapi32::RegCreateKeyA_pos,
@@ -58,6 +58,55 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn RegCreateKeyExA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpSubKey = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+        let Reserved = <u32>::from_stack(mem, stack_args + 8u32);
+        let lpClass = <Option<&str>>::from_stack(mem, stack_args + 12u32);
+        let dwOptions = <u32>::from_stack(mem, stack_args + 16u32);
+        let samDesired = <u32>::from_stack(mem, stack_args + 20u32);
+        let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 24u32);
+        let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 28u32);
+        let lpdwDisposition = <Option<&mut u32>>::from_stack(mem, stack_args + 32u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegCreateKeyExA_pos,
+                "advapi32",
+                "RegCreateKeyExA",
+                &[
+                    ("hKey", &hKey),
+                    ("lpSubKey", &lpSubKey),
+                    ("Reserved", &Reserved),
+                    ("lpClass", &lpClass),
+                    ("dwOptions", &dwOptions),
+                    ("samDesired", &samDesired),
+                    ("lpSecurityAttributes", &lpSecurityAttributes),
+                    ("phkResult", &phkResult),
+                    ("lpdwDisposition", &lpdwDisposition),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegCreateKeyExA(
+            machine,
+            hKey,
+            lpSubKey,
+            Reserved,
+            lpClass,
+            dwOptions,
+            samDesired,
+            lpSecurityAttributes,
+            phkResult,
+            lpdwDisposition,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn RegCreateKeyExW(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
@@ -67,7 +116,7 @@ mod wrappers {
         let dwOptions = <u32>::from_stack(mem, stack_args + 16u32);
         let samDesired = <u32>::from_stack(mem, stack_args + 20u32);
         let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 24u32);
-        let phkResult = <Option<&mut u32>>::from_stack(mem, stack_args + 28u32);
+        let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 28u32);
         let lpdwDisposition = <Option<&mut u32>>::from_stack(mem, stack_args + 32u32);
         let __trace_record = if crate::trace::enabled("advapi32") {
             crate::trace::Record::new(
@@ -107,6 +156,402 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn RegCreateKeyW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpSubKey = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegCreateKeyW_pos,
+                "advapi32",
+                "RegCreateKeyW",
+                &[
+                    ("hKey", &hKey),
+                    ("lpSubKey", &lpSubKey),
+                    ("phkResult", &phkResult),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegCreateKeyW(machine, hKey, lpSubKey, phkResult);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegDeleteKeyA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpSubKey = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegDeleteKeyA_pos,
+                "advapi32",
+                "RegDeleteKeyA",
+                &[("hKey", &hKey), ("lpSubKey", &lpSubKey)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegDeleteKeyA(machine, hKey, lpSubKey);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegDeleteKeyW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpSubKey = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegDeleteKeyW_pos,
+                "advapi32",
+                "RegDeleteKeyW",
+                &[("hKey", &hKey), ("lpSubKey", &lpSubKey)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegDeleteKeyW(machine, hKey, lpSubKey);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegDeleteValueA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpValueName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegDeleteValueA_pos,
+                "advapi32",
+                "RegDeleteValueA",
+                &[("hKey", &hKey), ("lpValueName", &lpValueName)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegDeleteValueA(machine, hKey, lpValueName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegDeleteValueW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpValueName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegDeleteValueW_pos,
+                "advapi32",
+                "RegDeleteValueW",
+                &[("hKey", &hKey), ("lpValueName", &lpValueName)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegDeleteValueW(machine, hKey, lpValueName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegEnumKeyA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let dwIndex = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpName = <u32>::from_stack(mem, stack_args + 8u32);
+        let cchName = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegEnumKeyA_pos,
+                "advapi32",
+                "RegEnumKeyA",
+                &[
+                    ("hKey", &hKey),
+                    ("dwIndex", &dwIndex),
+                    ("lpName", &lpName),
+                    ("cchName", &cchName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegEnumKeyA(machine, hKey, dwIndex, lpName, cchName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegEnumKeyExA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let dwIndex = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpName = <u32>::from_stack(mem, stack_args + 8u32);
+        let lpcchName = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+        let lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
+        let lpClass = <u32>::from_stack(mem, stack_args + 20u32);
+        let lpcchClass = <Option<&mut u32>>::from_stack(mem, stack_args + 24u32);
+        let lpftLastWriteTime = <u32>::from_stack(mem, stack_args + 28u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegEnumKeyExA_pos,
+                "advapi32",
+                "RegEnumKeyExA",
+                &[
+                    ("hKey", &hKey),
+                    ("dwIndex", &dwIndex),
+                    ("lpName", &lpName),
+                    ("lpcchName", &lpcchName),
+                    ("lpReserved", &lpReserved),
+                    ("lpClass", &lpClass),
+                    ("lpcchClass", &lpcchClass),
+                    ("lpftLastWriteTime", &lpftLastWriteTime),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegEnumKeyExA(
+            machine,
+            hKey,
+            dwIndex,
+            lpName,
+            lpcchName,
+            lpReserved,
+            lpClass,
+            lpcchClass,
+            lpftLastWriteTime,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegEnumKeyExW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let dwIndex = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpName = <u32>::from_stack(mem, stack_args + 8u32);
+        let lpcchName = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+        let lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
+        let lpClass = <u32>::from_stack(mem, stack_args + 20u32);
+        let lpcchClass = <Option<&mut u32>>::from_stack(mem, stack_args + 24u32);
+        let lpftLastWriteTime = <u32>::from_stack(mem, stack_args + 28u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegEnumKeyExW_pos,
+                "advapi32",
+                "RegEnumKeyExW",
+                &[
+                    ("hKey", &hKey),
+                    ("dwIndex", &dwIndex),
+                    ("lpName", &lpName),
+                    ("lpcchName", &lpcchName),
+                    ("lpReserved", &lpReserved),
+                    ("lpClass", &lpClass),
+                    ("lpcchClass", &lpcchClass),
+                    ("lpftLastWriteTime", &lpftLastWriteTime),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegEnumKeyExW(
+            machine,
+            hKey,
+            dwIndex,
+            lpName,
+            lpcchName,
+            lpReserved,
+            lpClass,
+            lpcchClass,
+            lpftLastWriteTime,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegEnumKeyW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let dwIndex = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpName = <u32>::from_stack(mem, stack_args + 8u32);
+        let cchName = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegEnumKeyW_pos,
+                "advapi32",
+                "RegEnumKeyW",
+                &[
+                    ("hKey", &hKey),
+                    ("dwIndex", &dwIndex),
+                    ("lpName", &lpName),
+                    ("cchName", &cchName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegEnumKeyW(machine, hKey, dwIndex, lpName, cchName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegEnumValueA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let dwIndex = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpValueName = <u32>::from_stack(mem, stack_args + 8u32);
+        let lpcchValueName = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+        let lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
+        let lpType = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
+        let lpData = <u32>::from_stack(mem, stack_args + 24u32);
+        let lpcbData = <Option<&mut u32>>::from_stack(mem, stack_args + 28u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegEnumValueA_pos,
+                "advapi32",
+                "RegEnumValueA",
+                &[
+                    ("hKey", &hKey),
+                    ("dwIndex", &dwIndex),
+                    ("lpValueName", &lpValueName),
+                    ("lpcchValueName", &lpcchValueName),
+                    ("lpReserved", &lpReserved),
+                    ("lpType", &lpType),
+                    ("lpData", &lpData),
+                    ("lpcbData", &lpcbData),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegEnumValueA(
+            machine,
+            hKey,
+            dwIndex,
+            lpValueName,
+            lpcchValueName,
+            lpReserved,
+            lpType,
+            lpData,
+            lpcbData,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegEnumValueW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let dwIndex = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpValueName = <u32>::from_stack(mem, stack_args + 8u32);
+        let lpcchValueName = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+        let lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
+        let lpType = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
+        let lpData = <u32>::from_stack(mem, stack_args + 24u32);
+        let lpcbData = <Option<&mut u32>>::from_stack(mem, stack_args + 28u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegEnumValueW_pos,
+                "advapi32",
+                "RegEnumValueW",
+                &[
+                    ("hKey", &hKey),
+                    ("dwIndex", &dwIndex),
+                    ("lpValueName", &lpValueName),
+                    ("lpcchValueName", &lpcchValueName),
+                    ("lpReserved", &lpReserved),
+                    ("lpType", &lpType),
+                    ("lpData", &lpData),
+                    ("lpcbData", &lpcbData),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegEnumValueW(
+            machine,
+            hKey,
+            dwIndex,
+            lpValueName,
+            lpcchValueName,
+            lpReserved,
+            lpType,
+            lpData,
+            lpcbData,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegFlushKey(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegFlushKey_pos,
+                "advapi32",
+                "RegFlushKey",
+                &[("hKey", &hKey)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegFlushKey(machine, hKey);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegOpenKeyA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpSubKey = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+        let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegOpenKeyA_pos,
+                "advapi32",
+                "RegOpenKeyA",
+                &[
+                    ("hKey", &hKey),
+                    ("lpSubKey", &lpSubKey),
+                    ("phkResult", &phkResult),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegOpenKeyA(machine, hKey, lpSubKey, phkResult);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn RegOpenKeyExA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
@@ -139,6 +584,180 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn RegOpenKeyExW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpSubKey = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let ulOptions = <u32>::from_stack(mem, stack_args + 8u32);
+        let samDesired = <u32>::from_stack(mem, stack_args + 12u32);
+        let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegOpenKeyExW_pos,
+                "advapi32",
+                "RegOpenKeyExW",
+                &[
+                    ("hKey", &hKey),
+                    ("lpSubKey", &lpSubKey),
+                    ("ulOptions", &ulOptions),
+                    ("samDesired", &samDesired),
+                    ("phkResult", &phkResult),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegOpenKeyExW(
+            machine, hKey, lpSubKey, ulOptions, samDesired, phkResult,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegOpenKeyW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpSubKey = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegOpenKeyW_pos,
+                "advapi32",
+                "RegOpenKeyW",
+                &[
+                    ("hKey", &hKey),
+                    ("lpSubKey", &lpSubKey),
+                    ("phkResult", &phkResult),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegOpenKeyW(machine, hKey, lpSubKey, phkResult);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegQueryInfoKeyA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpClass = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpcchClass = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+        let lpReserved = <u32>::from_stack(mem, stack_args + 12u32);
+        let lpcSubKeys = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
+        let lpcbMaxSubKeyLen = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
+        let lpcbMaxClassLen = <Option<&mut u32>>::from_stack(mem, stack_args + 24u32);
+        let lpcValues = <Option<&mut u32>>::from_stack(mem, stack_args + 28u32);
+        let lpcbMaxValueNameLen = <Option<&mut u32>>::from_stack(mem, stack_args + 32u32);
+        let lpcbMaxValueLen = <Option<&mut u32>>::from_stack(mem, stack_args + 36u32);
+        let lpcbSecurityDescriptor = <Option<&mut u32>>::from_stack(mem, stack_args + 40u32);
+        let lpftLastWriteTime = <u32>::from_stack(mem, stack_args + 44u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegQueryInfoKeyA_pos,
+                "advapi32",
+                "RegQueryInfoKeyA",
+                &[
+                    ("hKey", &hKey),
+                    ("lpClass", &lpClass),
+                    ("lpcchClass", &lpcchClass),
+                    ("lpReserved", &lpReserved),
+                    ("lpcSubKeys", &lpcSubKeys),
+                    ("lpcbMaxSubKeyLen", &lpcbMaxSubKeyLen),
+                    ("lpcbMaxClassLen", &lpcbMaxClassLen),
+                    ("lpcValues", &lpcValues),
+                    ("lpcbMaxValueNameLen", &lpcbMaxValueNameLen),
+                    ("lpcbMaxValueLen", &lpcbMaxValueLen),
+                    ("lpcbSecurityDescriptor", &lpcbSecurityDescriptor),
+                    ("lpftLastWriteTime", &lpftLastWriteTime),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegQueryInfoKeyA(
+            machine,
+            hKey,
+            lpClass,
+            lpcchClass,
+            lpReserved,
+            lpcSubKeys,
+            lpcbMaxSubKeyLen,
+            lpcbMaxClassLen,
+            lpcValues,
+            lpcbMaxValueNameLen,
+            lpcbMaxValueLen,
+            lpcbSecurityDescriptor,
+            lpftLastWriteTime,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RegQueryInfoKeyW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+        let lpClass = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpcchClass = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+        let lpReserved = <u32>::from_stack(mem, stack_args + 12u32);
+        let lpcSubKeys = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
+        let lpcbMaxSubKeyLen = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
+        let lpcbMaxClassLen = <Option<&mut u32>>::from_stack(mem, stack_args + 24u32);
+        let lpcValues = <Option<&mut u32>>::from_stack(mem, stack_args + 28u32);
+        let lpcbMaxValueNameLen = <Option<&mut u32>>::from_stack(mem, stack_args + 32u32);
+        let lpcbMaxValueLen = <Option<&mut u32>>::from_stack(mem, stack_args + 36u32);
+        let lpcbSecurityDescriptor = <Option<&mut u32>>::from_stack(mem, stack_args + 40u32);
+        let lpftLastWriteTime = <u32>::from_stack(mem, stack_args + 44u32);
+        let __trace_record = if crate::trace::enabled("advapi32") {
+            crate::trace::Record::new(
+                winapi::advapi32::RegQueryInfoKeyW_pos,
+                "advapi32",
+                "RegQueryInfoKeyW",
+                &[
+                    ("hKey", &hKey),
+                    ("lpClass", &lpClass),
+                    ("lpcchClass", &lpcchClass),
+                    ("lpReserved", &lpReserved),
+                    ("lpcSubKeys", &lpcSubKeys),
+                    ("lpcbMaxSubKeyLen", &lpcbMaxSubKeyLen),
+                    ("lpcbMaxClassLen", &lpcbMaxClassLen),
+                    ("lpcValues", &lpcValues),
+                    ("lpcbMaxValueNameLen", &lpcbMaxValueNameLen),
+                    ("lpcbMaxValueLen", &lpcbMaxValueLen),
+                    ("lpcbSecurityDescriptor", &lpcbSecurityDescriptor),
+                    ("lpftLastWriteTime", &lpftLastWriteTime),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::advapi32::RegQueryInfoKeyW(
+            machine,
+            hKey,
+            lpClass,
+            lpcchClass,
+            lpReserved,
+            lpcSubKeys,
+            lpcbMaxSubKeyLen,
+            lpcbMaxClassLen,
+            lpcValues,
+            lpcbMaxValueNameLen,
+            lpcbMaxValueLen,
+            lpcbSecurityDescriptor,
+            lpftLastWriteTime,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn RegQueryValueExA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
@@ -300,7 +919,7 @@ mod wrappers {
         result.into_abireturn()
     }
 }
-const SHIMS: [Shim; 8usize] = [
+const SHIMS: [Shim; 26usize] = [
     Shim {
         name: "RegCloseKey",
         func: Handler::Sync(wrappers::RegCloseKey),
@@ -309,14 +928,86 @@ const SHIMS: [Shim; 8usize] = [
         name: "RegCreateKeyA",
         func: Handler::Sync(wrappers::RegCreateKeyA),
     },
+    Shim {
+        name: "RegCreateKeyExA",
+        func: Handler::Sync(wrappers::RegCreateKeyExA),
+    },
     Shim {
         name: "RegCreateKeyExW",
         func: Handler::Sync(wrappers::RegCreateKeyExW),
     },
+    Shim {
+        name: "RegCreateKeyW",
+        func: Handler::Sync(wrappers::RegCreateKeyW),
+    },
+    Shim {
+        name: "RegDeleteKeyA",
+        func: Handler::Sync(wrappers::RegDeleteKeyA),
+    },
+    Shim {
+        name: "RegDeleteKeyW",
+        func: Handler::Sync(wrappers::RegDeleteKeyW),
+    },
+    Shim {
+        name: "RegDeleteValueA",
+        func: Handler::Sync(wrappers::RegDeleteValueA),
+    },
+    Shim {
+        name: "RegDeleteValueW",
+        func: Handler::Sync(wrappers::RegDeleteValueW),
+    },
+    Shim {
+        name: "RegEnumKeyA",
+        func: Handler::Sync(wrappers::RegEnumKeyA),
+    },
+    Shim {
+        name: "RegEnumKeyExA",
+        func: Handler::Sync(wrappers::RegEnumKeyExA),
+    },
+    Shim {
+        name: "RegEnumKeyExW",
+        func: Handler::Sync(wrappers::RegEnumKeyExW),
+    },
+    Shim {
+        name: "RegEnumKeyW",
+        func: Handler::Sync(wrappers::RegEnumKeyW),
+    },
+    Shim {
+        name: "RegEnumValueA",
+        func: Handler::Sync(wrappers::RegEnumValueA),
+    },
+    Shim {
+        name: "RegEnumValueW",
+        func: Handler::Sync(wrappers::RegEnumValueW),
+    },
+    Shim {
+        name: "RegFlushKey",
+        func: Handler::Sync(wrappers::RegFlushKey),
+    },
+    Shim {
+        name: "RegOpenKeyA",
+        func: Handler::Sync(wrappers::RegOpenKeyA),
+    },
     Shim {
         name: "RegOpenKeyExA",
         func: Handler::Sync(wrappers::RegOpenKeyExA),
     },
+    Shim {
+        name: "RegOpenKeyExW",
+        func: Handler::Sync(wrappers::RegOpenKeyExW),
+    },
+    Shim {
+        name: "RegOpenKeyW",
+        func: Handler::Sync(wrappers::RegOpenKeyW),
+    },
+    Shim {
+        name: "RegQueryInfoKeyA",
+        func: Handler::Sync(wrappers::RegQueryInfoKeyA),
+    },
+    Shim {
+        name: "RegQueryInfoKeyW",
+        func: Handler::Sync(wrappers::RegQueryInfoKeyW),
+    },
     Shim {
         name: "RegQueryValueExA",
         func: Handler::Sync(wrappers::RegQueryValueExA),
@@ -1,27 +1,437 @@
 #![allow(non_snake_case)]
 
 mod builtin;
+mod registry;
 
 pub use builtin::DLL;
+pub use registry::Registry;
 
-use super::{types::Str16, ERROR};
-use crate::machine::Machine;
+use super::{
+    kernel32::KernelObject,
+    types::{Str16, HANDLE},
+    ERROR,
+};
+use crate::{host, machine::Machine};
+use memory::{Extensions, ExtensionsMut};
+use registry::{encode_sz, is_string_type, Key, ROOTS};
+use std::io::{Read, Write};
+use typed_path::{WindowsPath, WindowsPathBuf};
 
-pub type HKEY = u32;
+pub struct HKEYT;
+pub type HKEY = HANDLE<HKEYT>;
+
+#[derive(Default)]
+pub struct State {
+    pub registry: Registry,
+    /// .reg file that the registry is saved to whenever it changes.
+    path: Option<WindowsPathBuf>,
+}
+
+/// Load the registry from a .reg file, and save changes back to it.
+/// A missing file is treated as empty and will be created on first change.
+pub fn load_registry(machine: &mut Machine, path: &WindowsPath) -> Result<(), String> {
+    let state = &mut machine.state.advapi32;
+    state.path = Some(path.to_path_buf());
+    let mut file = match machine.host.open(path, host::FileOptions::read()) {
+        Ok(file) => file,
+        Err(ERROR::FILE_NOT_FOUND) => return Ok(()),
+        Err(err) => return Err(format!("{}: {:?}", path.display(), err)),
+    };
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).map_err(|err| err.to_string())?;
+    let text = if buf.starts_with(&[0xFF, 0xFE]) {
+        // regedit writes UTF-16 files.
+        let units: Vec<u16> = buf[2..]
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(&buf).into_owned()
+    };
+    state
+        .registry
+        .load(&text)
+        .map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+fn save_registry(machine: &mut Machine) {
+    let state = &machine.state.advapi32;
+    let Some(path) = &state.path else {
+        return;
+    };
+    let options = host::FileOptions {
+        write: true,
+        truncate: true,
+        create: true,
+        ..Default::default()
+    };
+    let result = machine
+        .host
+        .open(path, options)
+        .and_then(|mut file| Ok(file.write_all(state.registry.save().as_bytes())?));
+    if let Err(err) = result {
+        log::warn!("saving registry to {}: {:?}", path.display(), err);
+    }
+}
+
+/// Map a key handle to the full path of the key it refers to.
+fn key_path(machine: &Machine, hKey: HKEY) -> Option<String> {
+    match hKey.to_raw() {
+        raw @ 0x8000_0000..=0x8000_0003 => Some(ROOTS[(raw & 0xF) as usize].to_string()),
+        raw => match machine.state.kernel32.objects.get_raw(raw) {
+            Some(KernelObject::Key(path)) => Some(path.clone()),
+            _ => None,
+        },
+    }
+}
+
+fn join_path(mut path: String, sub: Option<&str>) -> String {
+    if let Some(sub) = sub.filter(|s| !s.is_empty()) {
+        path.push('\\');
+        path.push_str(sub);
+    }
+    path
+}
+
+fn get_key<'a>(machine: &'a Machine, hKey: HKEY) -> Result<&'a Key, ERROR> {
+    let path = key_path(machine, hKey).ok_or(ERROR::INVALID_HANDLE)?;
+    machine
+        .state
+        .advapi32
+        .registry
+        .key(&path)
+        .ok_or(ERROR::FILE_NOT_FOUND)
+}
+
+fn get_key_mut(machine: &mut Machine, hKey: HKEY) -> Result<&mut Key, ERROR> {
+    let path = key_path(machine, hKey).ok_or(ERROR::INVALID_HANDLE)?;
+    machine
+        .state
+        .advapi32
+        .registry
+        .key_mut(&path)
+        .ok_or(ERROR::FILE_NOT_FOUND)
+}
+
+/// Convert registry string data (UTF-16) to the ANSI form returned by the A-suffixed APIs.
+fn to_ansi(data: &[u8]) -> Vec<u8> {
+    data.chunks(2)
+        .map(|c| match c {
+            [lo, 0] => *lo,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Convert ANSI data passed to A-suffixed APIs into registry string data.
+fn from_ansi(data: &[u8]) -> Vec<u8> {
+    data.iter().flat_map(|&b| [b, 0]).collect()
+}
+
+/// Copy a key or value name out to a buffer of cch characters, following the
+/// RegEnum* convention of returning the length without the nul.
+fn write_name(machine: &Machine, buf: u32, cch: &mut u32, name: &str, ansi: bool) -> ERROR {
+    let data = if ansi {
+        to_ansi(&encode_sz(name))
+    } else {
+        encode_sz(name)
+    };
+    let char_size = if ansi { 1 } else { 2 };
+    let len = data.len() as u32 / char_size;
+    if *cch < len {
+        return ERROR::MORE_DATA;
+    }
+    machine
+        .mem()
+        .sub32_mut(buf, data.len() as u32)
+        .copy_from_slice(&data);
+    *cch = len - 1;
+    ERROR::SUCCESS
+}
+
+/// Copy value data out following the RegQueryValueEx conventions for lpData/lpcbData.
+fn write_data(machine: &Machine, data: &[u8], lpData: u32, lpcbData: Option<&mut u32>) -> ERROR {
+    let len = data.len() as u32;
+    let Some(cbData) = lpcbData else {
+        return if lpData != 0 {
+            ERROR::INVALID_PARAMETER
+        } else {
+            ERROR::SUCCESS
+        };
+    };
+    if lpData != 0 {
+        if *cbData < len {
+            *cbData = len;
+            return ERROR::MORE_DATA;
+        }
+        machine.mem().sub32_mut(lpData, len).copy_from_slice(data);
+    }
+    *cbData = len;
+    ERROR::SUCCESS
+}
+
+fn value_data(typ: u32, data: &[u8], ansi: bool) -> Vec<u8> {
+    if ansi && is_string_type(typ) {
+        to_ansi(data)
+    } else {
+        data.to_vec()
+    }
+}
+
+fn open_key(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&str>,
+    create: bool,
+    phkResult: Option<&mut HKEY>,
+    lpdwDisposition: Option<&mut u32>,
+) -> ERROR {
+    const REG_CREATED_NEW_KEY: u32 = 1;
+    const REG_OPENED_EXISTING_KEY: u32 = 2;
+
+    let Some(phkResult) = phkResult else {
+        return ERROR::INVALID_PARAMETER;
+    };
+    let Some(parent) = key_path(machine, hKey) else {
+        return ERROR::INVALID_HANDLE;
+    };
+    let path = join_path(parent, lpSubKey);
+    let registry = &mut machine.state.advapi32.registry;
+    let created = if create {
+        match registry.create(&path) {
+            Some(created) => created,
+            None => return ERROR::FILE_NOT_FOUND,
+        }
+    } else if registry.key(&path).is_some() {
+        false
+    } else {
+        return ERROR::FILE_NOT_FOUND;
+    };
+    if let Some(disposition) = lpdwDisposition {
+        *disposition = if created {
+            REG_CREATED_NEW_KEY
+        } else {
+            REG_OPENED_EXISTING_KEY
+        };
+    }
+    let handle = machine.state.kernel32.objects.add(KernelObject::Key(path));
+    *phkResult = HKEY::from_raw(handle.to_raw());
+    if created {
+        save_registry(machine);
+    }
+    ERROR::SUCCESS
+}
+
+fn query_value(
+    machine: &mut Machine,
+    hKey: HKEY,
+    name: Option<&str>,
+    lpType: Option<&mut u32>,
+    lpData: u32,
+    lpcbData: Option<&mut u32>,
+    ansi: bool,
+) -> ERROR {
+    let key = match get_key(machine, hKey) {
+        Ok(key) => key,
+        Err(err) => return err,
+    };
+    let Some(value) = key.value(name.unwrap_or("")) else {
+        return ERROR::FILE_NOT_FOUND;
+    };
+    if let Some(lpType) = lpType {
+        *lpType = value.typ;
+    }
+    let data = value_data(value.typ, &value.data, ansi);
+    write_data(machine, &data, lpData, lpcbData)
+}
+
+fn set_value(
+    machine: &mut Machine,
+    hKey: HKEY,
+    name: Option<&str>,
+    typ: u32,
+    lpData: u32,
+    cbData: u32,
+    ansi: bool,
+) -> ERROR {
+    let mut data = machine.mem().sub32(lpData, cbData).to_vec();
+    if ansi && is_string_type(typ) {
+        data = from_ansi(&data);
+    }
+    let key = match get_key_mut(machine, hKey) {
+        Ok(key) => key,
+        Err(err) => return err,
+    };
+    key.set_value(name.unwrap_or(""), typ, data);
+    save_registry(machine);
+    ERROR::SUCCESS
+}
+
+fn delete_key(machine: &mut Machine, hKey: HKEY, sub: &str) -> ERROR {
+    let Some(parent) = key_path(machine, hKey) else {
+        return ERROR::INVALID_HANDLE;
+    };
+    let path = join_path(parent, Some(sub));
+    let registry = &mut machine.state.advapi32.registry;
+    match registry.key(&path) {
+        None => return ERROR::FILE_NOT_FOUND,
+        // RegDeleteKey only deletes leaf keys.
+        Some(key) if key.subkey_count() > 0 => return ERROR::ACCESS_DENIED,
+        Some(_) => {}
+    }
+    if registry.delete(&path).is_none() {
+        return ERROR::ACCESS_DENIED; // root key
+    }
+    save_registry(machine);
+    ERROR::SUCCESS
+}
+
+fn delete_value(machine: &mut Machine, hKey: HKEY, name: Option<&str>) -> ERROR {
+    let key = match get_key_mut(machine, hKey) {
+        Ok(key) => key,
+        Err(err) => return err,
+    };
+    if !key.delete_value(name.unwrap_or("")) {
+        return ERROR::FILE_NOT_FOUND;
+    }
+    save_registry(machine);
+    ERROR::SUCCESS
+}
+
+fn enum_key(
+    machine: &mut Machine,
+    hKey: HKEY,
+    index: u32,
+    buf: u32,
+    cch: &mut u32,
+    ansi: bool,
+) -> ERROR {
+    let key = match get_key(machine, hKey) {
+        Ok(key) => key,
+        Err(err) => return err,
+    };
+    let Some(sub) = key.subkeys().nth(index as usize) else {
+        return ERROR::NO_MORE_ITEMS;
+    };
+    write_name(machine, buf, cch, &sub.name, ansi)
+}
+
+fn enum_value(
+    machine: &mut Machine,
+    hKey: HKEY,
+    index: u32,
+    lpValueName: u32,
+    lpcchValueName: Option<&mut u32>,
+    lpType: Option<&mut u32>,
+    lpData: u32,
+    lpcbData: Option<&mut u32>,
+    ansi: bool,
+) -> ERROR {
+    let key = match get_key(machine, hKey) {
+        Ok(key) => key,
+        Err(err) => return err,
+    };
+    let Some(value) = key.values().get(index as usize) else {
+        return ERROR::NO_MORE_ITEMS;
+    };
+    let Some(cch) = lpcchValueName else {
+        return ERROR::INVALID_PARAMETER;
+    };
+    let err = write_name(machine, lpValueName, cch, &value.name, ansi);
+    if !matches!(err, ERROR::SUCCESS) {
+        return err;
+    }
+    if let Some(lpType) = lpType {
+        *lpType = value.typ;
+    }
+    let data = value_data(value.typ, &value.data, ansi);
+    write_data(machine, &data, lpData, lpcbData)
+}
+
+fn query_info_key(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpcSubKeys: Option<&mut u32>,
+    lpcbMaxSubKeyLen: Option<&mut u32>,
+    lpcValues: Option<&mut u32>,
+    lpcbMaxValueNameLen: Option<&mut u32>,
+    lpcbMaxValueLen: Option<&mut u32>,
+) -> ERROR {
+    let key = match get_key(machine, hKey) {
+        Ok(key) => key,
+        Err(err) => return err,
+    };
+    // Name lengths are in characters, excluding the nul.
+    let name_len = |name: &str| name.encode_utf16().count() as u32;
+    if let Some(count) = lpcSubKeys {
+        *count = key.subkey_count() as u32;
+    }
+    if let Some(max) = lpcbMaxSubKeyLen {
+        *max = key.subkeys().map(|k| name_len(&k.name)).max().unwrap_or(0);
+    }
+    if let Some(count) = lpcValues {
+        *count = key.values().len() as u32;
+    }
+    if let Some(max) = lpcbMaxValueNameLen {
+        *max = key
+            .values()
+            .iter()
+            .map(|v| name_len(&v.name))
+            .max()
+            .unwrap_or(0);
+    }
+    if let Some(max) = lpcbMaxValueLen {
+        *max = key
+            .values()
+            .iter()
+            .map(|v| v.data.len() as u32)
+            .max()
+            .unwrap_or(0);
+    }
+    ERROR::SUCCESS
+}
 
 #[win32_derive::dllexport]
 pub fn RegCreateKeyA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hKey: HKEY,
     lpSubKey: Option<&str>,
-    phkResult: Option<&mut u32>,
-) -> u32 {
-    0
+    phkResult: Option<&mut HKEY>,
+) -> ERROR {
+    open_key(machine, hKey, lpSubKey, true, phkResult, None)
+}
+
+#[win32_derive::dllexport]
+pub fn RegCreateKeyW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&Str16>,
+    phkResult: Option<&mut HKEY>,
+) -> ERROR {
+    let sub = lpSubKey.map(|s| s.to_string());
+    open_key(machine, hKey, sub.as_deref(), true, phkResult, None)
+}
+
+#[win32_derive::dllexport]
+pub fn RegCreateKeyExA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&str>,
+    Reserved: u32,
+    lpClass: Option<&str>,
+    dwOptions: u32,
+    samDesired: u32,
+    lpSecurityAttributes: u32,
+    phkResult: Option<&mut HKEY>,
+    lpdwDisposition: Option<&mut u32>,
+) -> ERROR {
+    open_key(machine, hKey, lpSubKey, true, phkResult, lpdwDisposition)
 }
 
 #[win32_derive::dllexport]
 pub fn RegCreateKeyExW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hKey: HKEY,
     lpSubKey: Option<&Str16>,
     Reserved: u32,
@@ -29,77 +439,364 @@ pub fn RegCreateKeyExW(
     dwOptions: u32,
     samDesired: u32,
     lpSecurityAttributes: u32,
-    phkResult: Option<&mut u32>,
+    phkResult: Option<&mut HKEY>,
     lpdwDisposition: Option<&mut u32>,
-) -> u32 {
-    0 // success
+) -> ERROR {
+    let sub = lpSubKey.map(|s| s.to_string());
+    open_key(
+        machine,
+        hKey,
+        sub.as_deref(),
+        true,
+        phkResult,
+        lpdwDisposition,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn RegOpenKeyA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&str>,
+    phkResult: Option<&mut HKEY>,
+) -> ERROR {
+    open_key(machine, hKey, lpSubKey, false, phkResult, None)
+}
+
+#[win32_derive::dllexport]
+pub fn RegOpenKeyW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&Str16>,
+    phkResult: Option<&mut HKEY>,
+) -> ERROR {
+    let sub = lpSubKey.map(|s| s.to_string());
+    open_key(machine, hKey, sub.as_deref(), false, phkResult, None)
 }
 
 #[win32_derive::dllexport]
 pub fn RegOpenKeyExA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hKey: HKEY,
     lpSubKey: Option<&str>,
     ulOptions: u32,
     samDesired: u32,
     phkResult: Option<&mut HKEY>,
 ) -> ERROR {
-    ERROR::FILE_NOT_FOUND
+    open_key(machine, hKey, lpSubKey, false, phkResult, None)
 }
 
 #[win32_derive::dllexport]
-pub fn RegCloseKey(_machine: &mut Machine, hKey: HKEY) -> u32 {
-    0 // success
+pub fn RegOpenKeyExW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&Str16>,
+    ulOptions: u32,
+    samDesired: u32,
+    phkResult: Option<&mut HKEY>,
+) -> ERROR {
+    let sub = lpSubKey.map(|s| s.to_string());
+    open_key(machine, hKey, sub.as_deref(), false, phkResult, None)
+}
+
+#[win32_derive::dllexport]
+pub fn RegCloseKey(machine: &mut Machine, hKey: HKEY) -> ERROR {
+    if hKey.to_raw() & 0x8000_0000 != 0 {
+        return ERROR::SUCCESS; // predefined keys are never closed
+    }
+    match machine.state.kernel32.objects.get(hKey.untyped()) {
+        Some(KernelObject::Key(_)) => {
+            machine.state.kernel32.objects.remove(hKey.untyped());
+            ERROR::SUCCESS
+        }
+        _ => ERROR::INVALID_HANDLE,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn RegFlushKey(machine: &mut Machine, hKey: HKEY) -> ERROR {
+    // Changes are saved as they happen.
+    match get_key(machine, hKey) {
+        Ok(_) => ERROR::SUCCESS,
+        Err(err) => err,
+    }
 }
 
 #[win32_derive::dllexport]
 pub fn RegQueryValueExA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hKey: HKEY,
     lpValueName: Option<&str>,
     lpReserved: u32,
     lpType: Option<&mut u32>,
     lpData: u32,
     lpcbData: Option<&mut u32>,
-) -> u32 {
-    2 // ERROR_FILE_NOT_FOUND
+) -> ERROR {
+    query_value(machine, hKey, lpValueName, lpType, lpData, lpcbData, true)
 }
 
 #[win32_derive::dllexport]
 pub fn RegQueryValueExW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hKey: HKEY,
     lpValueName: Option<&Str16>,
     lpReserved: u32,
     lpType: Option<&mut u32>,
     lpData: u32,
     lpcbData: Option<&mut u32>,
-) -> u32 {
-    2 // ERROR_FILE_NOT_FOUND
+) -> ERROR {
+    let name = lpValueName.map(|s| s.to_string());
+    query_value(
+        machine,
+        hKey,
+        name.as_deref(),
+        lpType,
+        lpData,
+        lpcbData,
+        false,
+    )
 }
 
 #[win32_derive::dllexport]
 pub fn RegSetValueExA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hKey: HKEY,
     lpValueName: Option<&str>,
     Reserved: u32,
     dwType: u32,
     lpData: u32,
     cbData: u32,
-) -> u32 {
-    0 // success
+) -> ERROR {
+    set_value(machine, hKey, lpValueName, dwType, lpData, cbData, true)
 }
 
 #[win32_derive::dllexport]
 pub fn RegSetValueExW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hKey: HKEY,
     lpValueName: Option<&Str16>,
     Reserved: u32,
     dwType: u32,
     lpData: u32,
     cbData: u32,
-) -> u32 {
-    0 // success
+) -> ERROR {
+    let name = lpValueName.map(|s| s.to_string());
+    set_value(
+        machine,
+        hKey,
+        name.as_deref(),
+        dwType,
+        lpData,
+        cbData,
+        false,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn RegDeleteKeyA(machine: &mut Machine, hKey: HKEY, lpSubKey: Option<&str>) -> ERROR {
+    match lpSubKey {
+        Some(sub) => delete_key(machine, hKey, sub),
+        None => ERROR::INVALID_PARAMETER,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn RegDeleteKeyW(machine: &mut Machine, hKey: HKEY, lpSubKey: Option<&Str16>) -> ERROR {
+    match lpSubKey {
+        Some(sub) => delete_key(machine, hKey, &sub.to_string()),
+        None => ERROR::INVALID_PARAMETER,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn RegDeleteValueA(machine: &mut Machine, hKey: HKEY, lpValueName: Option<&str>) -> ERROR {
+    delete_value(machine, hKey, lpValueName)
+}
+
+#[win32_derive::dllexport]
+pub fn RegDeleteValueW(machine: &mut Machine, hKey: HKEY, lpValueName: Option<&Str16>) -> ERROR {
+    let name = lpValueName.map(|s| s.to_string());
+    delete_value(machine, hKey, name.as_deref())
+}
+
+#[win32_derive::dllexport]
+pub fn RegEnumKeyA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    dwIndex: u32,
+    lpName: u32,
+    cchName: u32,
+) -> ERROR {
+    let mut cch = cchName;
+    enum_key(machine, hKey, dwIndex, lpName, &mut cch, true)
+}
+
+#[win32_derive::dllexport]
+pub fn RegEnumKeyW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    dwIndex: u32,
+    lpName: u32,
+    cchName: u32,
+) -> ERROR {
+    let mut cch = cchName;
+    enum_key(machine, hKey, dwIndex, lpName, &mut cch, false)
+}
+
+#[win32_derive::dllexport]
+pub fn RegEnumKeyExA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    dwIndex: u32,
+    lpName: u32,
+    lpcchName: Option<&mut u32>,
+    lpReserved: u32,
+    lpClass: u32,
+    lpcchClass: Option<&mut u32>,
+    lpftLastWriteTime: u32,
+) -> ERROR {
+    let Some(cch) = lpcchName else {
+        return ERROR::INVALID_PARAMETER;
+    };
+    if let Some(cchClass) = lpcchClass {
+        *cchClass = 0;
+    }
+    enum_key(machine, hKey, dwIndex, lpName, cch, true)
+}
+
+#[win32_derive::dllexport]
+pub fn RegEnumKeyExW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    dwIndex: u32,
+    lpName: u32,
+    lpcchName: Option<&mut u32>,
+    lpReserved: u32,
+    lpClass: u32,
+    lpcchClass: Option<&mut u32>,
+    lpftLastWriteTime: u32,
+) -> ERROR {
+    let Some(cch) = lpcchName else {
+        return ERROR::INVALID_PARAMETER;
+    };
+    if let Some(cchClass) = lpcchClass {
+        *cchClass = 0;
+    }
+    enum_key(machine, hKey, dwIndex, lpName, cch, false)
+}
+
+#[win32_derive::dllexport]
+pub fn RegEnumValueA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    dwIndex: u32,
+    lpValueName: u32,
+    lpcchValueName: Option<&mut u32>,
+    lpReserved: u32,
+    lpType: Option<&mut u32>,
+    lpData: u32,
+    lpcbData: Option<&mut u32>,
+) -> ERROR {
+    enum_value(
+        machine,
+        hKey,
+        dwIndex,
+        lpValueName,
+        lpcchValueName,
+        lpType,
+        lpData,
+        lpcbData,
+        true,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn RegEnumValueW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    dwIndex: u32,
+    lpValueName: u32,
+    lpcchValueName: Option<&mut u32>,
+    lpReserved: u32,
+    lpType: Option<&mut u32>,
+    lpData: u32,
+    lpcbData: Option<&mut u32>,
+) -> ERROR {
+    enum_value(
+        machine,
+        hKey,
+        dwIndex,
+        lpValueName,
+        lpcchValueName,
+        lpType,
+        lpData,
+        lpcbData,
+        false,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn RegQueryInfoKeyA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpClass: u32,
+    lpcchClass: Option<&mut u32>,
+    lpReserved: u32,
+    lpcSubKeys: Option<&mut u32>,
+    lpcbMaxSubKeyLen: Option<&mut u32>,
+    lpcbMaxClassLen: Option<&mut u32>,
+    lpcValues: Option<&mut u32>,
+    lpcbMaxValueNameLen: Option<&mut u32>,
+    lpcbMaxValueLen: Option<&mut u32>,
+    lpcbSecurityDescriptor: Option<&mut u32>,
+    lpftLastWriteTime: u32,
+) -> ERROR {
+    for len in [lpcchClass, lpcbMaxClassLen, lpcbSecurityDescriptor]
+        .into_iter()
+        .flatten()
+    {
+        *len = 0;
+    }
+    query_info_key(
+        machine,
+        hKey,
+        lpcSubKeys,
+        lpcbMaxSubKeyLen,
+        lpcValues,
+        lpcbMaxValueNameLen,
+        lpcbMaxValueLen,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn RegQueryInfoKeyW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpClass: u32,
+    lpcchClass: Option<&mut u32>,
+    lpReserved: u32,
+    lpcSubKeys: Option<&mut u32>,
+    lpcbMaxSubKeyLen: Option<&mut u32>,
+    lpcbMaxClassLen: Option<&mut u32>,
+    lpcValues: Option<&mut u32>,
+    lpcbMaxValueNameLen: Option<&mut u32>,
+    lpcbMaxValueLen: Option<&mut u32>,
+    lpcbSecurityDescriptor: Option<&mut u32>,
+    lpftLastWriteTime: u32,
+) -> ERROR {
+    for len in [lpcchClass, lpcbMaxClassLen, lpcbSecurityDescriptor]
+        .into_iter()
+        .flatten()
+    {
+        *len = 0;
+    }
+    query_info_key(
+        machine,
+        hKey,
+        lpcSubKeys,
+        lpcbMaxSubKeyLen,
+        lpcValues,
+        lpcbMaxValueNameLen,
+        lpcbMaxValueLen,
+    )
 }
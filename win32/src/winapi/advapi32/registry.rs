@@ -0,0 +1,450 @@
+//! In-memory model of the registry, loaded from and saved to .reg files.
+//!
+//! Values are stored the way Windows stores them internally: string types hold
+//! nul-terminated UTF-16, and the A-suffixed APIs convert at the boundary.
+
+use std::collections::BTreeMap;
+
+pub const REG_SZ: u32 = 1;
+pub const REG_EXPAND_SZ: u32 = 2;
+pub const REG_BINARY: u32 = 3;
+pub const REG_DWORD: u32 = 4;
+pub const REG_MULTI_SZ: u32 = 7;
+
+/// Names of the root keys, in the order of their predefined HKEY values.
+pub const ROOTS: [&str; 4] = [
+    "HKEY_CLASSES_ROOT",
+    "HKEY_CURRENT_USER",
+    "HKEY_LOCAL_MACHINE",
+    "HKEY_USERS",
+];
+
+/// HKEY_CLASSES_ROOT is a view of this key.
+const CLASSES_ROOT: &str = "HKEY_LOCAL_MACHINE\\Software\\Classes";
+
+pub fn is_string_type(typ: u32) -> bool {
+    matches!(typ, REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Value {
+    /// Value name, with empty string for the default value.
+    pub name: String,
+    pub typ: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+pub struct Key {
+    /// Key name with its original casing.
+    pub name: String,
+    /// Subkeys, indexed by lowercased name.
+    subkeys: BTreeMap<String, Key>,
+    /// Values, in creation order.
+    values: Vec<Value>,
+}
+
+impl Key {
+    fn new(name: &str) -> Self {
+        Key {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn subkey(&self, name: &str) -> Option<&Key> {
+        self.subkeys.get(&name.to_ascii_lowercase())
+    }
+
+    pub fn subkeys(&self) -> impl Iterator<Item = &Key> {
+        self.subkeys.values()
+    }
+
+    pub fn subkey_count(&self) -> usize {
+        self.subkeys.len()
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.values
+            .iter()
+            .find(|v| v.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn set_value(&mut self, name: &str, typ: u32, data: Vec<u8>) {
+        match self
+            .values
+            .iter_mut()
+            .find(|v| v.name.eq_ignore_ascii_case(name))
+        {
+            Some(value) => {
+                value.typ = typ;
+                value.data = data;
+            }
+            None => self.values.push(Value {
+                name: name.to_string(),
+                typ,
+                data,
+            }),
+        }
+    }
+
+    /// Returns false if the value didn't exist.
+    pub fn delete_value(&mut self, name: &str) -> bool {
+        let len = self.values.len();
+        self.values.retain(|v| !v.name.eq_ignore_ascii_case(name));
+        self.values.len() != len
+    }
+}
+
+/// Split a path into components, resolving HKEY_CLASSES_ROOT and dropping empty parts.
+fn components(path: &str) -> Vec<&str> {
+    let mut parts: Vec<&str> = path.split('\\').filter(|p| !p.is_empty()).collect();
+    if parts
+        .first()
+        .map_or(false, |p| p.eq_ignore_ascii_case(ROOTS[0]))
+    {
+        parts.splice(0..1, CLASSES_ROOT.split('\\'));
+    }
+    parts
+}
+
+#[derive(Debug)]
+pub struct Registry {
+    /// Root keys, excluding HKEY_CLASSES_ROOT which is an alias.
+    roots: Key,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut roots = Key::default();
+        for name in &ROOTS[1..] {
+            roots
+                .subkeys
+                .insert(name.to_ascii_lowercase(), Key::new(name));
+        }
+        Registry { roots }
+    }
+}
+
+impl Registry {
+    /// Look up a key by full path, e.g. "HKEY_CURRENT_USER\Software".
+    pub fn key(&self, path: &str) -> Option<&Key> {
+        let mut key = &self.roots;
+        for part in components(path) {
+            key = key.subkey(part)?;
+        }
+        Some(key)
+    }
+
+    pub fn key_mut(&mut self, path: &str) -> Option<&mut Key> {
+        let mut key = &mut self.roots;
+        for part in components(path) {
+            key = key.subkeys.get_mut(&part.to_ascii_lowercase())?;
+        }
+        Some(key)
+    }
+
+    /// Open a key, creating it and any missing parents.
+    /// Returns whether the key was newly created.
+    /// Returns None if the path doesn't start with a known root.
+    pub fn create(&mut self, path: &str) -> Option<bool> {
+        let parts = components(path);
+        let root = parts.first()?.to_ascii_lowercase();
+        let mut key = self.roots.subkeys.get_mut(&root)?;
+        let mut created = false;
+        for part in &parts[1..] {
+            key = key
+                .subkeys
+                .entry(part.to_ascii_lowercase())
+                .or_insert_with(|| {
+                    created = true;
+                    Key::new(part)
+                });
+        }
+        Some(created)
+    }
+
+    /// Delete a key and everything beneath it.  Root keys cannot be deleted.
+    /// Returns the deleted key, if it existed.
+    pub fn delete(&mut self, path: &str) -> Option<Key> {
+        let parts = components(path);
+        let (last, parents) = parts.split_last()?;
+        if parents.is_empty() {
+            return None;
+        }
+        let mut key = &mut self.roots;
+        for part in parents {
+            key = key.subkeys.get_mut(&part.to_ascii_lowercase())?;
+        }
+        key.subkeys.remove(&last.to_ascii_lowercase())
+    }
+
+    /// Merge the contents of a .reg file into the registry.
+    pub fn load(&mut self, text: &str) -> Result<(), String> {
+        let mut lines = text.lines().enumerate();
+        let mut unicode = true;
+        let mut path: Option<String> = None;
+        while let Some((i, line)) = lines.next() {
+            let mut line = line.trim().to_string();
+            // Hex data may continue across lines with a trailing backslash.
+            while line.ends_with('\\') && !line.ends_with('"') {
+                line.pop();
+                match lines.next() {
+                    Some((_, next)) => line.push_str(next.trim()),
+                    None => break,
+                }
+            }
+            let err = |msg: &str| format!("line {}: {}: {:?}", i + 1, msg, line);
+
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            } else if line == "REGEDIT4" {
+                unicode = false;
+            } else if line.starts_with("Windows Registry Editor") {
+                unicode = true;
+            } else if let Some(inner) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                if let Some(del) = inner.strip_prefix('-') {
+                    self.delete(del);
+                    path = None;
+                } else {
+                    self.create(inner).ok_or_else(|| err("unknown root key"))?;
+                    path = Some(inner.to_string());
+                }
+            } else {
+                let path = path.as_ref().ok_or_else(|| err("value outside key"))?;
+                let (name, rest) = parse_name(&line).ok_or_else(|| err("bad value name"))?;
+                let key = self.key_mut(path).unwrap();
+                if rest == "-" {
+                    key.delete_value(&name);
+                    continue;
+                }
+                let (typ, data) = parse_data(rest, unicode).ok_or_else(|| err("bad value"))?;
+                key.set_value(&name, typ, data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize the registry in the .reg file format.
+    pub fn save(&self) -> String {
+        let mut out = String::from("Windows Registry Editor Version 5.00\r\n");
+        for root in self.roots.subkeys() {
+            save_key(&mut out, &root.name, root);
+        }
+        out
+    }
+}
+
+fn save_key(out: &mut String, path: &str, key: &Key) {
+    // Empty parent keys are implied by their children, except for leaves.
+    if !key.values.is_empty() || (key.subkeys.is_empty() && path.contains('\\')) {
+        out.push_str(&format!("\r\n[{}]\r\n", path));
+        for value in &key.values {
+            if value.name.is_empty() {
+                out.push('@');
+            } else {
+                out.push_str(&quote(&value.name));
+            }
+            out.push('=');
+            out.push_str(&format_data(value));
+            out.push_str("\r\n");
+        }
+    }
+    for sub in key.subkeys() {
+        save_key(out, &format!("{}\\{}", path, sub.name), sub);
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+/// Decode a nul-terminated UTF-16 string value, if it is well-formed.
+fn decode_sz(data: &[u8]) -> Option<String> {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    if data.len() % 2 != 0 {
+        return None;
+    }
+    let (&last, rest) = units.split_last()?;
+    if last != 0 || rest.contains(&0) {
+        return None;
+    }
+    String::from_utf16(rest).ok()
+}
+
+pub fn encode_sz(s: &str) -> Vec<u8> {
+    s.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|c| c.to_le_bytes())
+        .collect()
+}
+
+fn format_data(value: &Value) -> String {
+    match value.typ {
+        REG_SZ => {
+            if let Some(s) = decode_sz(&value.data) {
+                return quote(&s);
+            }
+        }
+        REG_DWORD if value.data.len() == 4 => {
+            let dword = u32::from_le_bytes(value.data[..].try_into().unwrap());
+            return format!("dword:{:08x}", dword);
+        }
+        _ => {}
+    }
+    let hex = value
+        .data
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(",");
+    match value.typ {
+        REG_BINARY => format!("hex:{}", hex),
+        typ => format!("hex({:x}):{}", typ, hex),
+    }
+}
+
+/// Parse a leading quoted string or "@", returning it and the text after the "=".
+fn parse_name(line: &str) -> Option<(String, &str)> {
+    let (name, rest) = if let Some(rest) = line.strip_prefix('@') {
+        (String::new(), rest)
+    } else {
+        parse_quoted(line)?
+    };
+    Some((name, rest.trim_start().strip_prefix('=')?.trim_start()))
+}
+
+/// Parse a leading quoted string with backslash escapes, returning it and the remaining text.
+fn parse_quoted(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut out = String::new();
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next()?.1),
+            '"' => return Some((out, chars.as_str())),
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+fn parse_data(text: &str, unicode: bool) -> Option<(u32, Vec<u8>)> {
+    if text.starts_with('"') {
+        let (s, rest) = parse_quoted(text)?;
+        if !rest.trim().is_empty() {
+            return None;
+        }
+        return Some((REG_SZ, encode_sz(&s)));
+    }
+    if let Some(hex) = text.strip_prefix("dword:") {
+        let dword = u32::from_str_radix(hex, 16).ok()?;
+        return Some((REG_DWORD, dword.to_le_bytes().to_vec()));
+    }
+    let (typ, hex) = if let Some(hex) = text.strip_prefix("hex:") {
+        (REG_BINARY, hex)
+    } else {
+        let rest = text.strip_prefix("hex(")?;
+        let (typ, hex) = rest.split_once("):")?;
+        (u32::from_str_radix(typ, 16).ok()?, hex)
+    };
+    let mut data = hex
+        .split(',')
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    if !unicode && is_string_type(typ) {
+        // REGEDIT4 files store these as ANSI.
+        let s: String = data.iter().map(|&b| b as char).collect();
+        data = s
+            .encode_utf16()
+            .flat_map(|c| c.to_le_bytes())
+            .collect::<Vec<u8>>();
+    }
+    Some((typ, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REG: &str = r#"Windows Registry Editor Version 5.00
+
+[HKEY_CURRENT_USER\Software\Test]
+@="default"
+"Path"="C:\\Games\\\"x\""
+"Count"=dword:0000002a
+"Blob"=hex:01,02,\
+  03
+"Multi"=hex(7):61,00,00,00,62,00,00,00,00,00
+
+[HKEY_LOCAL_MACHINE\Software\Empty]
+"#;
+
+    #[test]
+    fn load() {
+        let mut reg = Registry::default();
+        reg.load(REG).unwrap();
+        let key = reg.key("hkey_current_user\\software\\TEST").unwrap();
+        assert_eq!(key.value("").unwrap().data, encode_sz("default"));
+        assert_eq!(
+            key.value("path").unwrap().data,
+            encode_sz("C:\\Games\\\"x\"")
+        );
+        assert_eq!(key.value("Count").unwrap().data, 42u32.to_le_bytes());
+        assert_eq!(key.value("Blob").unwrap().data, [1, 2, 3]);
+        assert_eq!(key.value("Multi").unwrap().typ, REG_MULTI_SZ);
+        assert!(reg.key("HKEY_LOCAL_MACHINE\\Software\\Empty").is_some());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut reg = Registry::default();
+        reg.load(REG).unwrap();
+        let saved = reg.save();
+        let mut reg2 = Registry::default();
+        reg2.load(&saved).unwrap();
+        assert_eq!(saved, reg2.save());
+        assert!(reg2.key("HKEY_LOCAL_MACHINE\\Software\\Empty").is_some());
+    }
+
+    #[test]
+    fn deletions() {
+        let mut reg = Registry::default();
+        reg.load(REG).unwrap();
+        reg.load(
+            "[HKEY_CURRENT_USER\\Software\\Test]\n\"Count\"=-\n[-HKEY_LOCAL_MACHINE\\Software]\n",
+        )
+        .unwrap();
+        let key = reg.key("HKEY_CURRENT_USER\\Software\\Test").unwrap();
+        assert!(key.value("Count").is_none());
+        assert!(reg.key("HKEY_LOCAL_MACHINE\\Software").is_none());
+        assert!(reg.key("HKEY_LOCAL_MACHINE").is_some());
+    }
+
+    #[test]
+    fn classes_root() {
+        let mut reg = Registry::default();
+        assert_eq!(reg.create("HKEY_CLASSES_ROOT\\.txt"), Some(true));
+        assert_eq!(
+            reg.create("HKEY_LOCAL_MACHINE\\SOFTWARE\\Classes\\.TXT"),
+            Some(false)
+        );
+    }
+}
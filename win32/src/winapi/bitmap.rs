@@ -4,7 +4,10 @@
 
 use std::ops::Range;
 
-use super::{gdi32::COLORREF, types::*};
+use super::{
+    gdi32::{nearest_index, new_palette, Palette, COLORREF, PALETTEENTRY},
+    types::*,
+};
 use memory::{Extensions, ExtensionsMut, Mem};
 
 #[derive(Debug, Eq, PartialEq, win32_derive::TryFromEnum)]
@@ -41,7 +44,7 @@ unsafe impl memory::Pod for BITMAPCOREHEADER {}
 impl BITMAPCOREHEADER {
     pub fn stride(&self) -> usize {
         // Bitmap row stride is padded out to 4 bytes per row.
-        ((((self.bcWidth * self.bcBitCount) as usize) + 31) & !31) >> 3
+        (((self.bcWidth as usize * self.bcBitCount as usize) + 31) & !31) >> 3
     }
 }
 
@@ -79,47 +82,81 @@ impl BITMAPINFOHEADER {
     pub fn compression(&self) -> Result<BI, u32> {
         BI::try_from(self.biCompression)
     }
+
+    /// The channel masks of a 16 or 32bpp DIB, given the bytes following the header,
+    /// where BI_BITFIELDS bitmaps store their masks.
+    pub fn color_masks(&self, buf: &[u8]) -> Option<ColorMasks> {
+        match (self.compression(), self.biBitCount) {
+            (Ok(BI::BITFIELDS), 16 | 32) => Some(ColorMasks {
+                r: buf.get_pod::<u32>(0),
+                g: buf.get_pod::<u32>(4),
+                b: buf.get_pod::<u32>(8),
+            }),
+            (_, 16) => Some(ColorMasks::RGB555),
+            (_, 32) => Some(ColorMasks::BGRX32),
+            _ => None,
+        }
+    }
 }
 
-/// The parsed header of a bitmap, either v2 (BITMAPCOREHEADER) or v3 (BITMAPINFOHEADER).
-struct BitmapInfo<'a> {
+/// The parsed header of a DIB: a v2 BITMAPCOREHEADER, or a v3 BITMAPINFOHEADER or its
+/// v4/v5 extensions, along with the masks and color table that follow it.
+pub struct BitmapInfo {
     pub width: usize,
     pub height: usize,
     pub stride: usize,
     pub is_bottom_up: bool,
     pub bit_count: u8,
     pub compression: BI,
-    pub palette_entry_size: usize,
-    pub palette: &'a [u8],
+    /// Size of the pixel data; only meaningful for compressed bitmaps.
+    pub size_image: usize,
+    /// The color table, for bitmaps of 8bpp and below.
+    pub palette: Palette,
+    /// Channel masks, for 16 and 32bpp bitmaps.
+    pub masks: Option<ColorMasks>,
     /// The total size in memory of the underlying header+palette.
     pub header_length: usize,
 }
 
-impl<'a> BitmapInfo<'a> {
+impl BitmapInfo {
     // TODO: when parsing a bitmap from memory it's unclear how much memory we'll need
     // to read until we've read the bitmap header.  This means the caller cannot know how
     // big of a slice to provide.
 
-    fn parse(buf: &'a [u8]) -> Self {
+    pub fn parse(buf: &[u8]) -> Self {
         let header_size = buf.get_pod::<u32>(0);
         match header_size {
             12 => Self::parseBMPv2(&buf.get_pod::<BITMAPCOREHEADER>(0), &buf[12..]),
-            40 => Self::parseBMPv3(&buf.get_pod::<BITMAPINFOHEADER>(0), &buf[40..]),
+            // v4 (108) and v5 (124) headers extend v3 with the masks, color space etc.
+            40 | 108 | 124 => Self::parseBMPv3(
+                &buf.get_pod::<BITMAPINFOHEADER>(0),
+                header_size as usize,
+                &buf[40..],
+            ),
             _ => unimplemented!("unimplemented bitmap header size {}", header_size),
         }
     }
 
     /// buf is the bytes following the header.
-    fn parseBMPv2(header: &BITMAPCOREHEADER, buf: &'a [u8]) -> Self {
+    fn parseBMPv2(header: &BITMAPCOREHEADER, buf: &[u8]) -> Self {
         let palette_len = match header.bcBitCount {
+            24 => 0,
             8 => 256,
             4 => 16,
             1 => 2,
             _ => unimplemented!(),
         };
-        let palette_entry_size = 3usize;
-        let palette_size = palette_len * palette_entry_size;
-        let palette = buf.sub32(0, palette_size as u32);
+        // BMPv2 palette is BGR.
+        let palette = new_palette(
+            buf[..palette_len * 3]
+                .chunks_exact(3)
+                .map(|p| PALETTEENTRY {
+                    peRed: p[2],
+                    peGreen: p[1],
+                    peBlue: p[0],
+                    peFlags: 0,
+                }),
+        );
 
         BitmapInfo {
             width: header.bcWidth as usize,
@@ -128,25 +165,39 @@ impl<'a> BitmapInfo<'a> {
             is_bottom_up: true, // MSDN: "BITMAPCOREHEADER bitmaps cannot be top-down bitmaps"
             bit_count: header.bcBitCount as u8,
             compression: BI::RGB,
-            palette_entry_size,
+            size_image: 0,
             palette,
-            header_length: 12 + palette_size,
+            masks: None,
+            header_length: 12 + palette_len * 3,
         }
     }
 
-    /// buf is the bytes following the header.
-    fn parseBMPv3(header: &BITMAPINFOHEADER, buf: &'a [u8]) -> Self {
-        let palette_len = match header.biBitCount {
-            32 => 0,
-            8 => 256,
-            4 => 16,
-            1 => 2,
-            _ => unimplemented!(),
+    /// buf is the bytes following the first 40 bytes of the header, which for v4/v5
+    /// headers begins with the channel masks.
+    fn parseBMPv3(header: &BITMAPINFOHEADER, header_size: usize, buf: &[u8]) -> Self {
+        let compression = header
+            .compression()
+            .unwrap_or_else(|c| unimplemented!("bitmap compression {c:#x}"));
+        let palette_len = match (header.biBitCount, header.biClrUsed) {
+            (8 | 4 | 1, 0) => 1 << header.biBitCount,
+            // Higher depths may carry an optional palette, which we skip over.
+            (_, n) => n as usize,
+        };
+        // BITFIELDS masks follow a v3 header; v4/v5 headers contain them.
+        let masks_size = match compression {
+            BI::BITFIELDS if header_size == 40 => 12,
+            _ => header_size - 40,
         };
-        assert!(header.biClrUsed == 0 || header.biClrUsed == palette_len as u32);
-        let palette_entry_size = 4usize;
-        let palette_size = palette_len * palette_entry_size;
-        let palette = buf.sub32(0, palette_size as u32);
+        let palette = new_palette(
+            buf[masks_size..][..palette_len * 4]
+                .chunks_exact(4)
+                .map(|p| PALETTEENTRY {
+                    peRed: p[2],
+                    peGreen: p[1],
+                    peBlue: p[0],
+                    peFlags: 0,
+                }),
+        );
 
         BitmapInfo {
             width: header.biWidth as usize,
@@ -154,14 +205,100 @@ impl<'a> BitmapInfo<'a> {
             stride: header.stride(),
             is_bottom_up: header.is_bottom_up(),
             bit_count: header.biBitCount as u8,
-            compression: BI::RGB,
-            palette_entry_size,
+            compression,
+            size_image: header.biSizeImage as usize,
             palette,
-            header_length: 40 + palette_size,
+            masks: header.color_masks(buf),
+            header_length: 40 + masks_size + palette_len * 4,
+        }
+    }
+
+    /// The format of the uncompressed pixels.
+    pub fn format(&self) -> PixelFormat {
+        match self.bit_count {
+            32 | 16 => PixelFormat::from_masks(self.bit_count as u32, self.masks.unwrap()),
+            24 => PixelFormat::BGR24,
+            8 => PixelFormat::Indexed8(self.palette.clone()),
+            4 => PixelFormat::Indexed4(self.palette.clone()),
+            1 => PixelFormat::Indexed1(self.palette.clone()),
+            n => unimplemented!("{n}bpp bitmap"),
+        }
+    }
+
+    /// The number of bytes of pixel data following the header.
+    pub fn pixels_len(&self) -> usize {
+        match self.compression {
+            BI::RLE8 | BI::RLE4 => self.size_image,
+            _ => self.stride * self.height,
         }
     }
 }
 
+/// Expand RLE8 or RLE4 data into one palette index per pixel, as bottom-up rows of
+/// `width` bytes.  Pixels skipped over by deltas and line ends are left as index 0.
+fn decode_rle(src: &[u8], width: usize, height: usize, rle4: bool) -> Vec<u8> {
+    let mut dst = vec![0u8; width * height];
+    let mut put = |x: usize, y: usize, index: u8| {
+        if x < width && y < height {
+            dst[y * width + x] = index;
+        }
+    };
+    // In RLE4, runs alternate between the high and low nibbles.
+    let nibble = |byte: u8, n: usize| if n & 1 == 0 { byte >> 4 } else { byte & 0xF };
+
+    let (mut x, mut y) = (0, 0);
+    let mut i = 0;
+    while i + 1 < src.len() {
+        let (count, val) = (src[i] as usize, src[i + 1]);
+        i += 2;
+        if count > 0 {
+            for n in 0..count {
+                put(x, y, if rle4 { nibble(val, n) } else { val });
+                x += 1;
+            }
+            continue;
+        }
+        match val {
+            0 => {
+                // end of line
+                x = 0;
+                y += 1;
+            }
+            1 => break, // end of bitmap
+            2 => {
+                let Some(&[dx, dy]) = src.get(i..i + 2) else {
+                    break;
+                };
+                x += dx as usize;
+                y += dy as usize;
+                i += 2;
+            }
+            n => {
+                // Absolute mode: n literal pixels, padded to a word boundary.
+                let n = n as usize;
+                let len = if rle4 { n.div_ceil(2) } else { n };
+                let Some(data) = src.get(i..i + len) else {
+                    break;
+                };
+                for k in 0..n {
+                    put(
+                        x,
+                        y,
+                        if rle4 {
+                            nibble(data[k / 2], k)
+                        } else {
+                            data[k]
+                        },
+                    );
+                    x += 1;
+                }
+                i += (len + 1) & !1;
+            }
+        }
+    }
+    dst
+}
+
 pub enum PixelData {
     Owned(Box<[u8]>),
     Ptr(u32, u32),
@@ -214,10 +351,100 @@ impl PixelData {
     }
 }
 
+/// Bit masks locating the red, green and blue channels within a 16 or 32-bit pixel,
+/// as found in BI_BITFIELDS DIBs and DirectDraw pixel formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorMasks {
+    pub r: u32,
+    pub g: u32,
+    pub b: u32,
+}
+
+impl ColorMasks {
+    /// xrrr_rrgg_gggb_bbbb, the format of 16bpp BI_RGB DIBs.
+    pub const RGB555: ColorMasks = ColorMasks {
+        r: 0x7C00,
+        g: 0x03E0,
+        b: 0x001F,
+    };
+    /// rrrr_rggg_gggb_bbbb
+    pub const RGB565: ColorMasks = ColorMasks {
+        r: 0xF800,
+        g: 0x07E0,
+        b: 0x001F,
+    };
+    /// Blue in the low byte, the format of 32bpp BI_RGB DIBs.
+    pub const BGRX32: ColorMasks = ColorMasks {
+        r: 0xFF_0000,
+        g: 0x00_FF00,
+        b: 0x00_00FF,
+    };
+    /// Red in the low byte, the layout of PixelFormat::RGBA32.
+    pub const RGBX32: ColorMasks = ColorMasks {
+        r: 0x00_00FF,
+        g: 0x00_FF00,
+        b: 0xFF_0000,
+    };
+
+    fn decode_channel(mask: u32, val: u32) -> u8 {
+        if mask == 0 {
+            return 0;
+        }
+        let bits = mask.count_ones();
+        let val = (val & mask) >> mask.trailing_zeros();
+        if bits >= 8 {
+            (val >> (bits - 8)) as u8
+        } else {
+            (val << (8 - bits)) as u8
+        }
+    }
+
+    fn encode_channel(mask: u32, val: u8) -> u32 {
+        if mask == 0 {
+            return 0;
+        }
+        let bits = mask.count_ones();
+        let val = val as u32;
+        let val = if bits >= 8 {
+            val << (bits - 8)
+        } else {
+            val >> (8 - bits)
+        };
+        (val << mask.trailing_zeros()) & mask
+    }
+
+    pub fn decode(&self, val: u32) -> COLORREF {
+        COLORREF::from_rgb(
+            Self::decode_channel(self.r, val),
+            Self::decode_channel(self.g, val),
+            Self::decode_channel(self.b, val),
+        )
+    }
+
+    pub fn encode(&self, color: COLORREF) -> u32 {
+        let [r, g, b] = color.to_rgb();
+        Self::encode_channel(self.r, r)
+            | Self::encode_channel(self.g, g)
+            | Self::encode_channel(self.b, b)
+    }
+}
+
 #[derive(Debug)]
 pub enum PixelFormat {
     RGBA32,
-    RGB555,
+    /// 32 bits per pixel, with channels placed by the masks.
+    RGB32(ColorMasks),
+    /// 24 bits per pixel, stored as blue, green, red bytes.
+    BGR24,
+    /// 16 bits per pixel, with channels placed by the masks (555 or 565).
+    RGB16(ColorMasks),
+    /// One byte per pixel, indexing into a palette.
+    Indexed8(Palette),
+    /// Two pixels per byte, indexing into a palette.
+    Indexed4(Palette),
+    /// Eight pixels per byte, indexing into a palette, as in 1bpp DIBs.
+    Indexed1(Palette),
+    /// Eight pixels per byte, black or white, as in monochrome DDBs.
     Mono,
 }
 
@@ -237,45 +464,23 @@ impl PixelFormat {
 
     pub fn bits_per_pixel(&self) -> u32 {
         match self {
-            PixelFormat::RGBA32 => 32,
-            PixelFormat::RGB555 => 16,
-            PixelFormat::Mono => 1,
+            PixelFormat::RGBA32 | PixelFormat::RGB32(_) => 32,
+            PixelFormat::BGR24 => 24,
+            PixelFormat::RGB16(_) => 16,
+            PixelFormat::Indexed8(_) => 8,
+            PixelFormat::Indexed4(_) => 4,
+            PixelFormat::Indexed1(_) | PixelFormat::Mono => 1,
         }
     }
 
-    fn decode_rgb555(val: u16) -> COLORREF {
-        // xrrr_rrgg_gggb_bbbb
-        let r = ((val >> 10) & 0x1F) as u8;
-        let g = ((val >> 5) & 0x1F) as u8;
-        let b = (val & 0x1F) as u8;
-        COLORREF::from_rgb(r << 3, g << 3, b << 3)
-    }
-
-    fn encode_rgb555(val: COLORREF) -> u16 {
-        let [mut r, mut g, mut b] = val.to_rgb();
-        r = r >> 3;
-        g = g >> 3;
-        b = b >> 3;
-        let out = ((r as u16) << 10) | ((g as u16) << 5) | b as u16;
-        out
-    }
-
-    #[allow(dead_code)]
-    fn decode_rgb565(val: u16) -> COLORREF {
-        // rrrr_rggg_gggb_bbbb
-        let r = ((val >> 11) & 0x1F) as u8;
-        let g = ((val >> 5) & 0x3F) as u8;
-        let b = (val & 0x1F) as u8;
-        COLORREF::from_rgb(r << 3, g << 3, b << 3)
-    }
-
-    #[allow(dead_code)]
-    fn encode_rgb565(val: COLORREF) -> u16 {
-        let [mut r, mut g, mut b] = val.to_rgb();
-        r = r >> 3;
-        g = g >> 2;
-        b = b >> 3;
-        ((r as u16) << 11) | ((g as u16) << 5) | b as u16
+    /// The pixel format for 16 or 32bpp pixels with the given masks.
+    pub fn from_masks(bits_per_pixel: u32, masks: ColorMasks) -> PixelFormat {
+        match bits_per_pixel {
+            16 => PixelFormat::RGB16(masks),
+            32 if masks == ColorMasks::RGBX32 => PixelFormat::RGBA32,
+            32 => PixelFormat::RGB32(masks),
+            _ => todo!("{bits_per_pixel}bpp masked pixel format"),
+        }
     }
 }
 
@@ -283,6 +488,10 @@ pub struct Bitmap {
     pub width: u32,
     pub height: u32,
     pub format: PixelFormat,
+    /// Bytes per row; DIB rows are padded to 4 bytes, where DDB rows are padded to 2.
+    pub stride: u32,
+    /// Whether the first row in memory is the bottom of the image, as in most DIBs.
+    pub bottom_up: bool,
     pub pixels: PixelData,
 }
 
@@ -302,88 +511,69 @@ impl Bitmap {
         let header = BitmapInfo::parse(buf);
         let (pixels, lines) = match pixels {
             Some((pixels, lines)) => (pixels, Some(lines)),
-            _ => {
-                let len = header.height * header.stride;
-                (&buf[header.header_length..][..len], None)
-            }
-        };
-        Self::parse_pixels(&header, pixels, lines)
-    }
-
-    /// Parse a BITMAPINFO/HEADER and pixel data.
-    fn parse_pixels(header: &BitmapInfo, pixels: &[u8], lines: Option<usize>) -> Bitmap {
-        match header.compression {
-            BI::RGB => {}
-            BI::RLE8 => todo!(),
-            BI::RLE4 => todo!(),
-            BI::BITFIELDS => {}
-            BI::JPEG => todo!(),
-            BI::PNG => todo!(),
+            _ => (&buf[header.header_length..][..header.pixels_len()], None),
         };
+        Self::decode(&header, pixels, lines)
+    }
 
-        // TODO: remove all this; we should be able to use bitmaps in their native pixel format.
-
-        fn get_pixel(header: &BitmapInfo, val: u8) -> [u8; 4] {
-            // BMP palette is BGRx
-            let offset = val as usize * header.palette_entry_size as usize;
-            let slice = &header.palette[offset..][..3];
-            [slice[2], slice[1], slice[0], 0xFF]
-        }
+    /// Parse a .bmp file, which is a BITMAPFILEHEADER followed by a DIB.
+    pub fn parse_file(buf: &[u8]) -> Bitmap {
+        let file_header = buf.get_pod::<BITMAPFILEHEADER>(0);
+        assert!(file_header.bfType == 0x4D42); // "BM"
+        let header = BitmapInfo::parse(&buf[14..]);
+        let pixels = &buf[file_header.bfOffBits as usize..][..header.pixels_len()];
+        Self::decode(&header, pixels, None)
+    }
 
-        let src = pixels;
+    /// Decode DIB pixel data, as described by a parsed header, into an RGBA32 bitmap.
+    /// If lines is given, only that many lines of pixels are present.
+    pub fn decode(header: &BitmapInfo, pixels: &[u8], lines: Option<usize>) -> Bitmap {
         let width = header.width;
-        let stride = header.stride;
-        let height = lines.unwrap_or(header.height as usize);
-
-        let mut dst: Vec<[u8; 4]> = Vec::with_capacity(width * height);
-        for y in 0..height {
-            let y_src = if header.is_bottom_up {
-                height - y - 1
-            } else {
-                y
-            };
-            let row = &src[y_src * stride..][..stride];
-            match header.bit_count {
-                32 => {
-                    // TODO: might need to swizzle here, depending on BI::BITFIELDS.
-                    dst.extend_from_slice(transmute_pixels(&row[..width * 4]));
-                }
-                8 => {
-                    for &p in &row[..width] {
-                        dst.push(get_pixel(header, p));
-                    }
+        let src = match header.compression {
+            BI::RGB | BI::BITFIELDS => {
+                let height = lines.unwrap_or(header.height);
+                Bitmap {
+                    width: width as u32,
+                    height: height as u32,
+                    format: header.format(),
+                    stride: header.stride as u32,
+                    bottom_up: header.is_bottom_up,
+                    pixels: PixelData::new_with_owned(pixels[..height * header.stride].into()),
                 }
-                4 => {
-                    for i in 0..width {
-                        let p = row[i / 2];
-                        if i % 2 == 0 {
-                            dst.push(get_pixel(header, p >> 4));
-                        } else {
-                            dst.push(get_pixel(header, p & 0xF));
-                        }
-                    }
-                }
-                1 => {
-                    for i in 0..width {
-                        let p = row[i / 8];
-                        let bit = 7 - (i % 8);
-                        let p = (p >> bit) & 1;
-                        dst.push(get_pixel(header, p));
-                    }
+            }
+            BI::RLE8 | BI::RLE4 => {
+                let rle4 = header.compression == BI::RLE4;
+                let indices = decode_rle(pixels, width, header.height, rle4);
+                Bitmap {
+                    width: width as u32,
+                    height: header.height as u32,
+                    format: PixelFormat::Indexed8(header.palette.clone()),
+                    stride: width as u32,
+                    bottom_up: true,
+                    pixels: PixelData::new_with_owned(indices.into_boxed_slice()),
                 }
-                _ => unimplemented!(),
             }
-        }
-
-        let mut buf: Vec<u8> = Vec::with_capacity(dst.len() * 4);
-        unsafe { buf.set_len(buf.capacity()) };
-        buf.copy_from_slice(transmute_pixels(&dst));
+            // These only exist for passing images through to printers.
+            BI::JPEG | BI::PNG => todo!("{:?} bitmap", header.compression),
+        };
+        src.to_rgba32(Mem::from_slice(&[]))
+    }
 
+    /// Convert to an owned RGBA32 bitmap, the format of our device-dependent bitmaps.
+    pub fn to_rgba32(&self, mem: Mem) -> Bitmap {
+        let mut pixels = Vec::with_capacity((self.width * self.height * 4) as usize);
+        let mut row = vec![COLORREF::from_rgb(0, 0, 0); self.width as usize];
+        for y in 0..self.height {
+            self.read_row(mem, 0..self.width, y, &mut row);
+            pixels.extend(row.iter().flat_map(|c| c.to_pixel()));
+        }
         Bitmap {
-            width: header.width as u32,
-            height: height as u32,
+            width: self.width,
+            height: self.height,
             format: PixelFormat::RGBA32,
-            pixels: PixelData::new_with_owned(buf.into_boxed_slice()),
+            stride: self.width * 4,
+            bottom_up: false,
+            pixels: PixelData::new_with_owned(pixels.into_boxed_slice()),
         }
     }
 
@@ -399,31 +589,59 @@ impl Bitmap {
         transmute_pixels_mut(bytes)
     }
 
+    /// Byte offset of row y, counting from the top of the image.
+    fn row_offset(&self, y: u32) -> usize {
+        let row = if self.bottom_up {
+            self.height - 1 - y
+        } else {
+            y
+        };
+        (row * self.stride) as usize
+    }
+
     pub fn read_row(&self, mem: Mem, x: Range<u32>, y: u32, out: &mut [COLORREF]) {
-        let stride = self.format.stride(self.width);
         let pixels = self.pixels.bytes(mem);
+        let fullrow = &pixels[self.row_offset(y)..][..self.stride as usize];
         match self.format {
             PixelFormat::RGBA32 => {
-                let row = &pixels[((y * stride) + x.start * 4) as usize..]
-                    [..(x.end - x.start) as usize * 4];
+                let row = &fullrow[x.start as usize * 4..x.end as usize * 4];
                 for (dst, src) in out.iter_mut().zip(row.chunks_exact(4)) {
                     *dst = COLORREF::from_rgb(src[0], src[1], src[2]);
                 }
             }
-            PixelFormat::RGB555 => {
-                let row = &pixels[((y * stride) + x.start * 2) as usize..]
-                    [..(x.end - x.start) as usize * 2];
+            PixelFormat::RGB32(masks) => {
+                let row = &fullrow[x.start as usize * 4..x.end as usize * 4];
+                for (dst, src) in out.iter_mut().zip(row.chunks_exact(4)) {
+                    *dst = masks.decode(u32::from_le_bytes(src.try_into().unwrap()));
+                }
+            }
+            PixelFormat::BGR24 => {
+                let row = &fullrow[x.start as usize * 3..x.end as usize * 3];
+                for (dst, src) in out.iter_mut().zip(row.chunks_exact(3)) {
+                    *dst = COLORREF::from_rgb(src[2], src[1], src[0]);
+                }
+            }
+            PixelFormat::RGB16(masks) => {
+                let row = &fullrow[x.start as usize * 2..x.end as usize * 2];
                 for (dst, src) in out.iter_mut().zip(row.chunks_exact(2)) {
-                    *dst = PixelFormat::decode_rgb555(u16::from_le_bytes([src[0], src[1]]));
+                    *dst = masks.decode(u16::from_le_bytes([src[0], src[1]]) as u32);
+                }
+            }
+            PixelFormat::Indexed8(ref palette)
+            | PixelFormat::Indexed4(ref palette)
+            | PixelFormat::Indexed1(ref palette) => {
+                let palette = palette.borrow();
+                let bpp = self.format.bits_per_pixel() as usize;
+                for (i, dst) in out.iter_mut().enumerate() {
+                    let index = get_index(fullrow, x.start as usize + i, bpp);
+                    *dst = palette
+                        .get(index as usize)
+                        .map_or(COLORREF::from_rgb(0, 0, 0), |e| e.to_color());
                 }
             }
             PixelFormat::Mono => {
-                let fullrow = &pixels[(y * stride) as usize..][..stride as usize];
                 for (i, dst) in out.iter_mut().enumerate() {
-                    let x = x.start as usize + i;
-                    let byte = fullrow[x / 8];
-                    let bit = 7 - (x % 8);
-                    let val = ((byte >> bit) & 1) * 0xff;
+                    let val = get_index(fullrow, x.start as usize + i, 1) * 0xff;
                     *dst = COLORREF::from_rgb(val, val, val);
                 }
             }
@@ -431,34 +649,78 @@ impl Bitmap {
     }
 
     pub fn write_row(&mut self, mem: Mem, x: Range<u32>, y: u32, src: &[COLORREF]) {
-        let stride = self.format.stride(self.width);
+        let offset = self.row_offset(y);
+        let stride = self.stride as usize;
+        let bpp = self.format.bits_per_pixel() as usize;
         let pixels = self.pixels.bytes_mut(mem);
+        let fullrow = &mut pixels[offset..][..stride];
+        let dst_width = (x.end - x.start) as usize;
         match self.format {
             PixelFormat::RGBA32 => {
-                let fullrow = &mut pixels[(y * stride) as usize..][..stride as usize];
                 let row = &mut fullrow[x.start as usize * 4..x.end as usize * 4];
-                let dst_width = (x.end - x.start) as usize;
                 for (i, dst) in row.chunks_exact_mut(4).enumerate() {
                     let color = src[i * src.len() / dst_width];
                     dst.copy_from_slice(&color.to_pixel());
                 }
             }
-            PixelFormat::RGB555 => {
-                let fullrow = &mut pixels[(y * stride) as usize..][..stride as usize];
+            PixelFormat::RGB32(masks) => {
+                let row = &mut fullrow[x.start as usize * 4..x.end as usize * 4];
+                for (i, dst) in row.chunks_exact_mut(4).enumerate() {
+                    let color = src[i * src.len() / dst_width];
+                    dst.copy_from_slice(&masks.encode(color).to_le_bytes());
+                }
+            }
+            PixelFormat::BGR24 => {
+                let row = &mut fullrow[x.start as usize * 3..x.end as usize * 3];
+                for (i, dst) in row.chunks_exact_mut(3).enumerate() {
+                    let [r, g, b] = src[i * src.len() / dst_width].to_rgb();
+                    dst.copy_from_slice(&[b, g, r]);
+                }
+            }
+            PixelFormat::RGB16(masks) => {
                 let row = &mut fullrow[x.start as usize * 2..x.end as usize * 2];
-                let dst_width = (x.end - x.start) as usize;
                 for (i, dst) in row.chunks_exact_mut(2).enumerate() {
                     let color = src[i * src.len() / dst_width];
-                    dst.copy_from_slice(&PixelFormat::encode_rgb555(color).to_le_bytes());
+                    dst.copy_from_slice(&(masks.encode(color) as u16).to_le_bytes());
+                }
+            }
+            PixelFormat::Indexed8(ref palette)
+            | PixelFormat::Indexed4(ref palette)
+            | PixelFormat::Indexed1(ref palette) => {
+                let palette = palette.borrow();
+                for i in 0..dst_width {
+                    let color = src[i * src.len() / dst_width];
+                    let index = nearest_index(&palette, color) as u8;
+                    set_index(fullrow, x.start as usize + i, bpp, index);
                 }
             }
             PixelFormat::Mono => {
-                todo!();
+                for i in 0..dst_width {
+                    let [r, g, b] = src[i * src.len() / dst_width].to_rgb();
+                    let white = r as u32 + g as u32 + b as u32 >= 0x180;
+                    set_index(fullrow, x.start as usize + i, 1, white as u8);
+                }
             }
         }
     }
 }
 
+/// Read the x'th pixel of a row of 8, 4 or 1 bit pixels, which are packed starting with
+/// the high bits of each byte.
+fn get_index(row: &[u8], x: usize, bpp: usize) -> u8 {
+    let per_byte = 8 / bpp;
+    let shift = (per_byte - 1 - x % per_byte) * bpp;
+    (row[x / per_byte] >> shift) & ((1u16 << bpp) - 1) as u8
+}
+
+fn set_index(row: &mut [u8], x: usize, bpp: usize, index: u8) {
+    let per_byte = 8 / bpp;
+    let shift = (per_byte - 1 - x % per_byte) * bpp;
+    let mask = (((1u16 << bpp) - 1) as u8) << shift;
+    let byte = &mut row[x / per_byte];
+    *byte = (*byte & !mask) | ((index << shift) & mask);
+}
+
 impl std::fmt::Debug for Bitmap {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Bitmap")
@@ -476,22 +738,61 @@ mod tests {
     #[test]
     fn test_pixel_format_stride() {
         assert_eq!(PixelFormat::RGBA32.stride(11), 44);
-        assert_eq!(PixelFormat::RGB555.stride(11), 22);
+        assert_eq!(PixelFormat::RGB16(ColorMasks::RGB555).stride(11), 22);
         assert_eq!(PixelFormat::Mono.stride(11), 2);
     }
 
     #[test]
     fn test_pixel_format_rgb555() {
+        let masks = ColorMasks::RGB555;
         let red = COLORREF::from_rgb(0xff, 0, 0);
-        let enc = PixelFormat::encode_rgb555(red);
+        let enc = masks.encode(red);
         assert_eq!(enc, 0b0_11111_00000_00000);
-        let dec = PixelFormat::decode_rgb555(enc);
+        let dec = masks.decode(enc);
         assert_eq!(dec.to_rgb(), [0xf8, 0, 0]);
 
         let cyan = COLORREF::from_rgb(0x20, 0x10, 0);
-        let enc = PixelFormat::encode_rgb555(cyan);
+        let enc = masks.encode(cyan);
         assert_eq!(enc, 0b0_00100_00010_00000);
-        let dec = PixelFormat::decode_rgb555(enc);
+        let dec = masks.decode(enc);
         assert_eq!(dec.to_rgb(), [0x20, 0x10, 0]);
     }
+
+    #[test]
+    fn test_decode_rle8() {
+        let rle = [
+            3, 7, // run of three 7s
+            0, 3, 1, 2, 3, 0, // absolute run, padded
+            0, 0, // end of line
+            0, 2, 1, 0, // delta
+            1, 9, // run of one 9
+            0, 1, // end of bitmap
+        ];
+        let indices = decode_rle(&rle, 7, 2, false);
+        assert_eq!(indices, [7, 7, 7, 1, 2, 3, 0, 0, 9, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_decode_rle4() {
+        let rle = [
+            5, 0x12, // alternating run
+            0, 3, 0x45, 0x60, // absolute run
+        ];
+        let indices = decode_rle(&rle, 8, 1, true);
+        assert_eq!(indices, [1, 2, 1, 2, 1, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_pixel_format_masks() {
+        let masks = ColorMasks::RGB565;
+        let color = COLORREF::from_rgb(0xff, 0x84, 0x10);
+        let enc = masks.encode(color);
+        assert_eq!(enc, (0b11111 << 11) | (0b100001 << 5) | 0b00010);
+        assert_eq!(masks.decode(enc).to_rgb(), [0xf8, 0x84, 0x10]);
+
+        let masks = ColorMasks::BGRX32;
+        let enc = masks.encode(color);
+        assert_eq!(enc, 0x00ff_8410);
+        assert_eq!(masks.decode(enc), color);
+    }
 }
@@ -595,6 +595,38 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IDirectDrawPalette_GetEntries(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let unused = <u32>::from_stack(mem, stack_args + 4u32);
+        let start = <u32>::from_stack(mem, stack_args + 8u32);
+        let count = <u32>::from_stack(mem, stack_args + 12u32);
+        let entries = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("ddraw/palette") {
+            crate::trace::Record::new(
+                winapi::ddraw::IDirectDrawPalette::GetEntries_pos,
+                "ddraw/palette",
+                "IDirectDrawPalette::GetEntries",
+                &[
+                    ("this", &this),
+                    ("unused", &unused),
+                    ("start", &start),
+                    ("count", &count),
+                    ("entries", &entries),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::ddraw::IDirectDrawPalette::GetEntries(
+            machine, this, unused, start, count, entries,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IDirectDrawPalette_Release(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let this = <u32>::from_stack(mem, stack_args + 0u32);
@@ -1483,7 +1515,7 @@ mod wrappers {
         result.into_abireturn()
     }
 }
-const SHIMS: [Shim; 57usize] = [
+const SHIMS: [Shim; 58usize] = [
     Shim {
         name: "DirectDrawCreate",
         func: Handler::Sync(wrappers::DirectDrawCreate),
@@ -1568,6 +1600,10 @@ const SHIMS: [Shim; 57usize] = [
         name: "IDirectDrawClipper::SetHWnd",
         func: Handler::Sync(wrappers::IDirectDrawClipper_SetHWnd),
     },
+    Shim {
+        name: "IDirectDrawPalette::GetEntries",
+        func: Handler::Sync(wrappers::IDirectDrawPalette_GetEntries),
+    },
     Shim {
         name: "IDirectDrawPalette::Release",
         func: Handler::Sync(wrappers::IDirectDrawPalette_Release),
@@ -1,15 +1,15 @@
 //! DirectDraw shared API.  All the ddraw1 through ddraw7 interfaces back onto shared
 //! implementation defined here.
 
-use super::palette::Palette;
 pub use super::types::*;
 pub use crate::winapi::com::GUID;
 use crate::{
     host,
     machine::Machine,
     winapi::{
-        bitmap::{transmute_pixels_mut, Bitmap, PixelData, PixelFormat},
+        bitmap::{transmute_pixels_mut, Bitmap, ColorMasks, PixelData, PixelFormat},
         ddraw::{ddraw1, ddraw7},
+        gdi32::{Palette, COLORREF},
         heap::Heap,
         types::{HWND, RECT},
     },
@@ -26,13 +26,15 @@ pub struct Surface {
     /// x86 address to pixel buffer, or 0 if unused.
     pixels: u32,
     pub bytes_per_pixel: u32,
+    /// Channel layout of 16 and 32bpp pixels.
+    pub masks: ColorMasks,
     pub primary: bool,
     /// Address of attached surface, e.g. back buffer.
     pub attached: u32,
 }
 
 impl Surface {
-    fn new(machine: &mut Machine, hwnd: HWND, opts: &SurfaceOptions) -> Self {
+    fn new(machine: &mut Machine, hwnd: HWND, opts: &SurfaceOptions, masks: ColorMasks) -> Self {
         if opts.width == 0 || opts.height == 0 {
             panic!("cannot create 0-sized surface");
         }
@@ -40,10 +42,16 @@ impl Surface {
             host: machine.host.create_surface(hwnd.to_raw(), &opts),
             width: opts.width,
             height: opts.height,
-            palette: None,
+            // An 8bpp primary displays through the system palette until SetPalette.
+            palette: if opts.primary && opts.bytes_per_pixel == 1 {
+                Some(machine.state.gdi32.system_palette.clone())
+            } else {
+                None
+            },
             pixels: 0,
             primary: opts.primary,
             bytes_per_pixel: opts.bytes_per_pixel,
+            masks,
             attached: 0,
         }
     }
@@ -76,16 +84,23 @@ impl Surface {
             }
         }
 
+        let mut masks = None;
+        if desc.dwFlags.contains(DDSD::PIXELFORMAT) && desc.ddpfPixelFormat.dwRGBBitCount != 0 {
+            let format = &desc.ddpfPixelFormat;
+            opts.bytes_per_pixel = format.dwRGBBitCount / 8;
+            masks = format.masks();
+        }
         if opts.bytes_per_pixel == 0 {
             opts.bytes_per_pixel = machine.state.ddraw.bytes_per_pixel;
         }
+        let masks = masks.unwrap_or(machine.state.ddraw.masks(opts.bytes_per_pixel));
 
-        surfaces.push(Surface::new(machine, hwnd, &opts));
+        surfaces.push(Surface::new(machine, hwnd, &opts, masks));
 
         if let Some(count) = desc.back_buffer_count() {
             opts.primary = false;
             for _ in 0..count {
-                surfaces.push(Surface::new(machine, hwnd, &opts));
+                surfaces.push(Surface::new(machine, hwnd, &opts, masks));
             }
         }
 
@@ -102,7 +117,8 @@ impl Surface {
     }
 
     /// Create a Bitmap representing the backing pixel buffer.
-    pub fn to_bitmap(&self) -> Bitmap {
+    /// 8bpp surfaces without their own palette use the given system palette.
+    pub fn to_bitmap(&self, system_palette: &Palette) -> Bitmap {
         if self.pixels == 0 {
             todo!()
         }
@@ -110,10 +126,17 @@ impl Surface {
             width: self.width,
             height: self.height,
             format: match self.bytes_per_pixel {
-                4 => PixelFormat::RGBA32,
-                2 => PixelFormat::RGB555, // TODO: 565 etc.
+                4 => PixelFormat::from_masks(32, self.masks),
+                2 => PixelFormat::RGB16(self.masks),
+                1 => PixelFormat::Indexed8(
+                    self.palette
+                        .clone()
+                        .unwrap_or_else(|| system_palette.clone()),
+                ),
                 _ => todo!(),
             },
+            stride: self.width * self.bytes_per_pixel,
+            bottom_up: false,
             pixels: PixelData::Ptr(self.pixels, self.width * self.height * self.bytes_per_pixel),
         }
     }
@@ -183,11 +206,13 @@ impl Surface {
                 let pixels = mem.iter_pod::<u16>(self.pixels, self.width * self.height);
 
                 for (pSrc, pDst) in pixels.zip(pixels_quads) {
-                    // TODO: this depends on whether 16bpp is 555 or 565 etc.
-                    let r = ((pSrc & 0b0111_1100_0000_0000) >> 7) as u8;
-                    let g = ((pSrc & 0b0000_0011_1110_0000) >> 2) as u8;
-                    let b = ((pSrc & 0b0000_0000_0001_1111) << 3) as u8;
-                    *pDst = [r, g, b, 0xFF];
+                    *pDst = self.masks.decode(pSrc as u32).to_pixel();
+                }
+            }
+            4 if self.masks != ColorMasks::RGBX32 => {
+                let pixels = mem.iter_pod::<u32>(self.pixels, self.width * self.height);
+                for (pSrc, pDst) in pixels.zip(pixels_quads) {
+                    *pDst = self.masks.decode(pSrc).to_pixel();
                 }
             }
             4 => {
@@ -203,22 +228,107 @@ impl Surface {
     }
 }
 
+/// Copy pixels between surfaces' backing memory, converting between their pixel formats
+/// and stretching src_rect over dst_rect.  This keeps the memory of the destination in
+/// sync with the host-side blit; surfaces never locked have no memory to update.
+pub fn blit_pixels(machine: &Machine, dst: u32, dst_rect: &RECT, src: u32, src_rect: &RECT) {
+    let surfaces = &machine.state.ddraw.surfaces;
+    let (Some(dst), Some(src)) = (surfaces.get(&dst), surfaces.get(&src)) else {
+        return;
+    };
+    if dst.pixels == 0 || src.pixels == 0 {
+        return;
+    }
+    let (dst_w, dst_h) = (
+        dst_rect.right - dst_rect.left,
+        dst_rect.bottom - dst_rect.top,
+    );
+    let (src_w, src_h) = (
+        src_rect.right - src_rect.left,
+        src_rect.bottom - src_rect.top,
+    );
+    if dst_w <= 0 || dst_h <= 0 || src_w <= 0 || src_h <= 0 {
+        return;
+    }
+
+    let mem = machine.emu.memory.mem();
+    let system_palette = &machine.state.gdi32.system_palette;
+    let src_bitmap = src.to_bitmap(system_palette);
+    let mut dst_bitmap = dst.to_bitmap(system_palette);
+    let clip = dst_rect.clip(&dst.to_rect());
+    let src_x =
+        |x: i32| (src_rect.left + (x - dst_rect.left) * src_w / dst_w).clamp(0, src.width as i32);
+    let (x0, x1) = (src_x(clip.left), src_x(clip.right));
+    if x0 >= x1 {
+        return;
+    }
+    let mut row = vec![COLORREF::from_rgb(0, 0, 0); (x1 - x0) as usize];
+    for y in clip.top..clip.bottom {
+        let sy = src_rect.top + (y - dst_rect.top) * src_h / dst_h;
+        if sy < 0 || sy >= src.height as i32 {
+            continue;
+        }
+        src_bitmap.read_row(mem, x0 as u32..x1 as u32, sy as u32, &mut row);
+        dst_bitmap.write_row(mem, clip.left as u32..clip.right as u32, y as u32, &row);
+    }
+}
+
+/// Redraw 8bpp primary surfaces, and their back buffers, after a palette change.
+/// Palette animation relies on this, as the pixels themselves don't change.
+pub fn palette_changed(machine: &Machine) {
+    let mem = machine.emu.memory.mem();
+    let surfaces = &machine.state.ddraw.surfaces;
+    for surface in surfaces.values() {
+        if !surface.primary || surface.bytes_per_pixel != 1 {
+            continue;
+        }
+        if surface.pixels != 0 {
+            surface.flush(mem, None);
+        }
+        if let Some(back) = surfaces.get(&surface.attached) {
+            if back.pixels != 0 {
+                back.flush(mem, Some(surface));
+            }
+        }
+        surface.host.show();
+    }
+}
+
 pub struct State {
     // TODO: this is per-IDirectDraw state.
     pub hwnd: HWND,
     pub surfaces: HashMap<u32, Surface>,
 
     pub bytes_per_pixel: u32,
+    /// The channel layout of 16bpp display modes.  Hardware differs on whether this
+    /// is 555 or 565, and games probe for it via the surface pixel format.
+    pub masks16: ColorMasks,
 
     pub palettes: HashMap<u32, Palette>,
 }
 
+impl State {
+    /// The channel layout of display surfaces of a given depth.
+    pub fn masks(&self, bytes_per_pixel: u32) -> ColorMasks {
+        match bytes_per_pixel {
+            2 => self.masks16,
+            _ => ColorMasks::RGBX32,
+        }
+    }
+
+    /// The pixel format of the current display mode.
+    pub fn display_format(&self) -> DDPIXELFORMAT {
+        DDPIXELFORMAT::new(self.bytes_per_pixel, self.masks(self.bytes_per_pixel))
+    }
+}
+
 impl Default for State {
     fn default() -> Self {
         State {
             hwnd: HWND::null(),
             surfaces: HashMap::new(),
             bytes_per_pixel: 4,
+            masks16: ColorMasks::RGB565,
             palettes: HashMap::new(),
         }
     }
@@ -154,7 +154,7 @@ pub mod IDirectDraw2 {
 
     #[win32_derive::dllexport]
     pub fn GetDisplayMode(
-        _machine: &mut Machine,
+        machine: &mut Machine,
         this: u32,
         lpDDSurfaceDesc: Option<&mut DDSURFACEDESC>,
     ) -> DD {
@@ -167,8 +167,7 @@ pub mod IDirectDraw2 {
         desc.dwHeight = 768;
 
         desc.dwFlags.insert(DDSD::PIXELFORMAT);
-        // desc.ddpfPixelFormat.dwFlags = ...;
-        desc.ddpfPixelFormat.dwRGBBitCount = 32;
+        desc.ddpfPixelFormat = machine.state.ddraw.display_format();
 
         DD::OK
     }
@@ -239,13 +239,15 @@ pub mod IDirectDraw7 {
 
     #[win32_derive::dllexport]
     pub fn GetDisplayMode(
-        _machine: &mut Machine,
+        machine: &mut Machine,
         this: u32,
         lpDDSurfaceDesc: Option<&mut DDSURFACEDESC2>,
     ) -> DD {
         let desc = lpDDSurfaceDesc.unwrap();
         *desc = DDSURFACEDESC2::zeroed();
         desc.dwSize = std::mem::size_of::<DDSURFACEDESC2>() as u32;
+        desc.dwFlags.insert(DDSD::PIXELFORMAT);
+        desc.ddpfPixelFormat = machine.state.ddraw.display_format();
         DD::OK
     }
 
@@ -419,6 +421,7 @@ pub mod IDirectDrawSurface7 {
         if dst.primary {
             dst.host.show();
         }
+        ddraw::blit_pixels(machine, this, &dst_rect, lpSrc, &src_rect);
         DD::OK
     }
 
@@ -448,16 +451,17 @@ pub mod IDirectDrawSurface7 {
         });
 
         let dst_rect = RECT {
-            left: 0,
-            top: 0,
-            right: dst.width as i32,
-            bottom: dst.height as i32,
+            left: x as i32,
+            top: y as i32,
+            right: x as i32 + (src_rect.right - src_rect.left),
+            bottom: y as i32 + (src_rect.bottom - src_rect.top),
         };
 
         dst.host.bit_blt(&dst_rect, src.host.as_ref(), &src_rect);
         if dst.primary {
             dst.host.show();
         }
+        ddraw::blit_pixels(machine, this, &dst_rect, lpSrc, &src_rect);
         DD::OK
     }
 
@@ -509,25 +513,11 @@ pub mod IDirectDrawSurface7 {
     }
 
     #[win32_derive::dllexport]
-    pub fn GetPixelFormat(
-        _machine: &mut Machine,
-        this: u32,
-        fmt: Option<&mut DDPIXELFORMAT>,
-    ) -> DD {
+    pub fn GetPixelFormat(machine: &mut Machine, this: u32, fmt: Option<&mut DDPIXELFORMAT>) -> DD {
         let fmt = fmt.unwrap();
         assert!(fmt.dwSize == std::mem::size_of::<DDPIXELFORMAT>() as u32);
-        // The "RGBA32" we use has the R as the first byte, so due to little
-        // endian that means the *low* end of the bit mask here.
-        *fmt = DDPIXELFORMAT {
-            dwSize: std::mem::size_of::<DDPIXELFORMAT>() as u32,
-            dwFlags: 0x00000040,
-            dwFourCC: 0,
-            dwRGBBitCount: 32,
-            dwRBitMask: 0x0000_00FF,
-            dwGBitMask: 0x0000_FF00,
-            dwBBitMask: 0x00FF_0000,
-            dwRGBAlphaBitMask: 0xFF00_0000,
-        };
+        let surf = machine.state.ddraw.surfaces.get(&this).unwrap();
+        *fmt = DDPIXELFORMAT::new(surf.bytes_per_pixel, surf.masks);
         DD::OK
     }
 
@@ -546,12 +536,10 @@ pub mod IDirectDrawSurface7 {
         desc.dwFlags.insert(DDSD::WIDTH);
         desc.dwHeight = surf.height;
         desc.dwFlags.insert(DDSD::HEIGHT);
-        desc.lPitch_dwLinearSize = surf.width * 4;
+        desc.lPitch_dwLinearSize = surf.width * surf.bytes_per_pixel;
         desc.dwFlags.insert(DDSD::PITCH);
 
-        // TODO: a trace of a ddraw2 program had the result contain
-        // DDPF_RGB, r/g/b bitmasks
-        desc.ddpfPixelFormat.dwRGBBitCount = 32;
+        desc.ddpfPixelFormat = DDPIXELFORMAT::new(surf.bytes_per_pixel, surf.masks);
         desc.dwFlags.insert(DDSD::PIXELFORMAT);
         DD::OK
     }
@@ -585,6 +573,7 @@ pub mod IDirectDrawSurface7 {
         // and instead expect all fields to be included.
         desc.lpSurface = pixels;
         desc.lPitch_dwLinearSize = surf.width * surf.bytes_per_pixel;
+        desc.ddpfPixelFormat = DDPIXELFORMAT::new(surf.bytes_per_pixel, surf.masks);
         DD::OK
     }
 
@@ -619,8 +608,14 @@ pub mod IDirectDrawSurface7 {
 
     #[win32_derive::dllexport]
     pub fn SetPalette(machine: &mut Machine, this: u32, palette: u32) -> DD {
-        let palette = machine.state.ddraw.palettes.get(&palette).unwrap();
-        machine.state.ddraw.surfaces.get_mut(&this).unwrap().palette = Some(palette.clone());
+        let palette = machine.state.ddraw.palettes.get(&palette).unwrap().clone();
+        let surface = machine.state.ddraw.surfaces.get_mut(&this).unwrap();
+        surface.palette = Some(palette.clone());
+        if surface.primary {
+            // The primary's palette is the system palette, as seen by GDI too.
+            machine.state.gdi32.system_palette = palette;
+            ddraw::palette_changed(machine);
+        }
         DD::OK
     }
 
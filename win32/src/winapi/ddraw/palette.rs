@@ -1,13 +1,14 @@
-use std::{cell::RefCell, rc::Rc};
-
 use crate::{
-    winapi::{com::vtable, ddraw::DD, gdi32::PALETTEENTRY, kernel32::get_symbol},
+    winapi::{
+        com::vtable,
+        ddraw::{palette_changed, DD},
+        gdi32::PALETTEENTRY,
+        kernel32::get_symbol,
+    },
     Machine,
 };
 use memory::{Extensions, ExtensionsMut};
 
-pub type Palette = Rc<RefCell<Box<[PALETTEENTRY]>>>;
-
 #[win32_derive::dllexport]
 pub mod IDirectDrawPalette {
     use super::*;
@@ -17,7 +18,7 @@ pub mod IDirectDrawPalette {
         AddRef: todo,
         Release: ok,
         GetCaps: todo,
-        GetEntries: todo,
+        GetEntries: ok,
         Initialize: todo,
         SetEntries: ok,
     ];
@@ -67,6 +68,28 @@ pub mod IDirectDrawPalette {
         {
             *dst = src;
         }
+        drop(palette);
+        palette_changed(machine);
+        DD::OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn GetEntries(
+        machine: &mut Machine,
+        this: u32,
+        unused: u32,
+        start: u32,
+        count: u32,
+        entries: u32,
+    ) -> DD {
+        let palette = machine.state.ddraw.palettes.get(&this).unwrap().borrow();
+        let mem = machine.emu.memory.mem();
+        for (i, &entry) in palette[start as usize..][..count as usize]
+            .iter()
+            .enumerate()
+        {
+            mem.put_pod::<PALETTEENTRY>(entries + i as u32 * 4, entry);
+        }
         DD::OK
     }
 }
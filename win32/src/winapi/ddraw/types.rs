@@ -1,6 +1,6 @@
 //! Types defined in the DirectDraw API.
 
-use crate::winapi::types::*;
+use crate::winapi::{bitmap::ColorMasks, types::*};
 use bitflags::bitflags;
 use memory::Pod;
 
@@ -397,6 +397,50 @@ pub struct DDPIXELFORMAT {
 }
 unsafe impl memory::Pod for DDPIXELFORMAT {}
 
+const DDPF_PALETTEINDEXED8: u32 = 0x20;
+const DDPF_RGB: u32 = 0x40;
+
+impl DDPIXELFORMAT {
+    /// The pixel format of a surface of a given depth and channel layout.
+    pub fn new(bytes_per_pixel: u32, masks: ColorMasks) -> Self {
+        let (dwFlags, masks, alpha) = match bytes_per_pixel {
+            1 => (
+                DDPF_RGB | DDPF_PALETTEINDEXED8,
+                ColorMasks { r: 0, g: 0, b: 0 },
+                0,
+            ),
+            2 => (DDPF_RGB, masks, 0),
+            // The "RGBA32" we use has the R as the first byte, so due to little
+            // endian that means the *low* end of the bit mask here.
+            4 if masks == ColorMasks::RGBX32 => (DDPF_RGB, masks, 0xFF00_0000),
+            4 => (DDPF_RGB, masks, 0),
+            _ => todo!("{bytes_per_pixel} bytes per pixel"),
+        };
+        DDPIXELFORMAT {
+            dwSize: std::mem::size_of::<DDPIXELFORMAT>() as u32,
+            dwFlags,
+            dwFourCC: 0,
+            dwRGBBitCount: bytes_per_pixel * 8,
+            dwRBitMask: masks.r,
+            dwGBitMask: masks.g,
+            dwBBitMask: masks.b,
+            dwRGBAlphaBitMask: alpha,
+        }
+    }
+
+    /// The channel masks of an RGB format, or None for palettized and other formats.
+    pub fn masks(&self) -> Option<ColorMasks> {
+        if self.dwFlags & DDPF_RGB == 0 || self.dwFlags & DDPF_PALETTEINDEXED8 != 0 {
+            return None;
+        }
+        Some(ColorMasks {
+            r: self.dwRBitMask,
+            g: self.dwGBitMask,
+            b: self.dwBBitMask,
+        })
+    }
+}
+
 bitflags! {
     #[derive(win32_derive::TryFromBitflags)]
     pub struct DDBLT: u32 {
@@ -127,6 +127,29 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IDirectInputDevice_GetDeviceState(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let cbData = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpvData = <u32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("dinput/dinput") {
+            crate::trace::Record::new(
+                winapi::dinput::IDirectInputDevice::GetDeviceState_pos,
+                "dinput/dinput",
+                "IDirectInputDevice::GetDeviceState",
+                &[("this", &this), ("cbData", &cbData), ("lpvData", &lpvData)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::dinput::IDirectInputDevice::GetDeviceState(machine, this, cbData, lpvData);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IDirectInputDevice_SetCooperativeLevel(
         machine: &mut Machine,
         stack_args: u32,
@@ -333,7 +356,7 @@ mod wrappers {
         result.into_abireturn()
     }
 }
-const SHIMS: [Shim; 12usize] = [
+const SHIMS: [Shim; 13usize] = [
     Shim {
         name: "DirectInputCreateA",
         func: Handler::Sync(wrappers::DirectInputCreateA),
@@ -350,6 +373,10 @@ const SHIMS: [Shim; 12usize] = [
         name: "IDirectInputDevice::GetDeviceData",
         func: Handler::Sync(wrappers::IDirectInputDevice_GetDeviceData),
     },
+    Shim {
+        name: "IDirectInputDevice::GetDeviceState",
+        func: Handler::Sync(wrappers::IDirectInputDevice_GetDeviceState),
+    },
     Shim {
         name: "IDirectInputDevice::SetCooperativeLevel",
         func: Handler::Sync(wrappers::IDirectInputDevice_SetCooperativeLevel),
@@ -1,6 +1,7 @@
-use crate::winapi::{calling_convention, com::vtable, kernel32};
+use crate::winapi::{calling_convention, com::vtable, kernel32, user32};
 use crate::Machine;
 use memory::ExtensionsMut;
+use std::collections::HashMap;
 
 pub use crate::winapi::com::GUID;
 
@@ -20,6 +21,18 @@ const GUID_SysKeyboard: GUID = GUID((
     [0xBF, 0xC7, 0x44, 0x45, 0x53, 0x54, 0x00, 0x00],
 ));
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Device {
+    Mouse,
+    Keyboard,
+}
+
+#[derive(Default)]
+pub struct State {
+    /// Maps IDirectInputDevice pointer to the kind of device.
+    devices: HashMap<u32, Device>,
+}
+
 #[derive(Debug, Copy, Clone)]
 #[repr(u32)]
 pub enum DI {
@@ -83,12 +96,18 @@ pub mod IDirectInput {
         if !ENABLE {
             return DI::ERR_DEVICENOTREG;
         }
-        match lpGUID.unwrap() {
-            &GUID_SysMouse => {}
-            &GUID_SysKeyboard => {}
+        let device = match lpGUID.unwrap() {
+            &GUID_SysMouse => Device::Mouse,
+            &GUID_SysKeyboard => Device::Keyboard,
             _ => return DI::ERR_DEVICENOTREG,
-        }
-        *lplpDirectInputDevice.unwrap() = IDirectInputDevice::new(machine);
+        };
+        let lpDirectInputDevice = IDirectInputDevice::new(machine);
+        machine
+            .state
+            .dinput
+            .devices
+            .insert(lpDirectInputDevice, device);
+        *lplpDirectInputDevice.unwrap() = lpDirectInputDevice;
         DI::OK
     }
 
@@ -143,7 +162,7 @@ pub mod IDirectInputDevice {
         SetProperty: ok,
         Acquire: ok,
         Unacquire: todo,
-        GetDeviceState: todo,
+        GetDeviceState: ok,
         GetDeviceData: ok,
         SetDataFormat: ok,
         SetEventNotification: ok,
@@ -189,6 +208,33 @@ pub mod IDirectInputDevice {
         pdwInOut: Option<&mut u32>,
         dwFlags: u32,
     ) -> DI {
+        // TODO: buffered input; for now report no events.
+        *pdwInOut.unwrap() = 0;
+        DI::OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn GetDeviceState(machine: &mut Machine, this: u32, cbData: u32, lpvData: u32) -> DI {
+        let buf = machine.mem().sub32_mut(lpvData, cbData);
+        buf.fill(0);
+        match machine.state.dinput.devices.get(&this) {
+            Some(Device::Keyboard) => {
+                user32::fetch_host_messages(machine);
+                let buf = machine.mem().sub32_mut(lpvData, cbData);
+                for vk in machine.state.user32.keyboard.iter_down() {
+                    // DIK_* codes are scan codes, with the high bit set for extended keys.
+                    let scancode = user32::vk_to_scancode(vk);
+                    let dik = (scancode & 0x7F) as usize | ((scancode >> 8) as usize & 0x80);
+                    if dik != 0 && dik < buf.len() {
+                        buf[dik] = 0x80;
+                    }
+                }
+            }
+            Some(Device::Mouse) => {
+                // TODO: mouse state; report no movement and no buttons.
+            }
+            None => unreachable!(),
+        }
         DI::OK
     }
 
@@ -96,6 +96,82 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IDirectSoundBuffer_GetFormat(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let lpwfxFormat = <u32>::from_stack(mem, stack_args + 4u32);
+        let dwSizeAllocated = <u32>::from_stack(mem, stack_args + 8u32);
+        let lpdwSizeWritten = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::GetFormat_pos,
+                "dsound",
+                "IDirectSoundBuffer::GetFormat",
+                &[
+                    ("this", &this),
+                    ("lpwfxFormat", &lpwfxFormat),
+                    ("dwSizeAllocated", &dwSizeAllocated),
+                    ("lpdwSizeWritten", &lpdwSizeWritten),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundBuffer::GetFormat(
+            machine,
+            this,
+            lpwfxFormat,
+            dwSizeAllocated,
+            lpdwSizeWritten,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn IDirectSoundBuffer_GetFrequency(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let pdwFrequency = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::GetFrequency_pos,
+                "dsound",
+                "IDirectSoundBuffer::GetFrequency",
+                &[("this", &this), ("pdwFrequency", &pdwFrequency)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundBuffer::GetFrequency(machine, this, pdwFrequency);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn IDirectSoundBuffer_GetPan(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let plPan = <Option<&mut i32>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::GetPan_pos,
+                "dsound",
+                "IDirectSoundBuffer::GetPan",
+                &[("this", &this), ("plPan", &plPan)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundBuffer::GetPan(machine, this, plPan);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IDirectSoundBuffer_GetStatus(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let this = <u32>::from_stack(mem, stack_args + 0u32);
@@ -117,6 +193,27 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IDirectSoundBuffer_GetVolume(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let plVolume = <Option<&mut i32>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::GetVolume_pos,
+                "dsound",
+                "IDirectSoundBuffer::GetVolume",
+                &[("this", &this), ("plVolume", &plVolume)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundBuffer::GetVolume(machine, this, plVolume);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IDirectSoundBuffer_Lock(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let this = <u32>::from_stack(mem, stack_args + 0u32);
@@ -197,6 +294,29 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IDirectSoundBuffer_QueryInterface(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let riid = <Option<&GUID>>::from_stack(mem, stack_args + 4u32);
+        let ppvObject = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::QueryInterface_pos,
+                "dsound",
+                "IDirectSoundBuffer::QueryInterface",
+                &[("this", &this), ("riid", &riid), ("ppvObject", &ppvObject)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::dsound::IDirectSoundBuffer::QueryInterface(machine, this, riid, ppvObject);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IDirectSoundBuffer_Release(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let this = <u32>::from_stack(mem, stack_args + 0u32);
@@ -217,6 +337,31 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IDirectSoundBuffer_SetCurrentPosition(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let dwNewPosition = <u32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::SetCurrentPosition_pos,
+                "dsound",
+                "IDirectSoundBuffer::SetCurrentPosition",
+                &[("this", &this), ("dwNewPosition", &dwNewPosition)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::dsound::IDirectSoundBuffer::SetCurrentPosition(machine, this, dwNewPosition);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IDirectSoundBuffer_SetFormat(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let this = <u32>::from_stack(mem, stack_args + 0u32);
@@ -238,6 +383,89 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IDirectSoundBuffer_SetFrequency(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let dwFrequency = <u32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::SetFrequency_pos,
+                "dsound",
+                "IDirectSoundBuffer::SetFrequency",
+                &[("this", &this), ("dwFrequency", &dwFrequency)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundBuffer::SetFrequency(machine, this, dwFrequency);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn IDirectSoundBuffer_SetPan(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let lPan = <i32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::SetPan_pos,
+                "dsound",
+                "IDirectSoundBuffer::SetPan",
+                &[("this", &this), ("lPan", &lPan)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundBuffer::SetPan(machine, this, lPan);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn IDirectSoundBuffer_SetVolume(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let lVolume = <i32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::SetVolume_pos,
+                "dsound",
+                "IDirectSoundBuffer::SetVolume",
+                &[("this", &this), ("lVolume", &lVolume)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundBuffer::SetVolume(machine, this, lVolume);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn IDirectSoundBuffer_Stop(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundBuffer::Stop_pos,
+                "dsound",
+                "IDirectSoundBuffer::Stop",
+                &[("this", &this)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundBuffer::Stop(machine, this);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IDirectSoundBuffer_Unlock(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let this = <u32>::from_stack(mem, stack_args + 0u32);
@@ -275,6 +503,60 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IDirectSoundNotify_Release(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundNotify::Release_pos,
+                "dsound",
+                "IDirectSoundNotify::Release",
+                &[("this", &this)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundNotify::Release(machine, this);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn IDirectSoundNotify_SetNotificationPositions(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let dwPositionNotifies = <u32>::from_stack(mem, stack_args + 4u32);
+        let pcPositionNotifies = <u32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSoundNotify::SetNotificationPositions_pos,
+                "dsound",
+                "IDirectSoundNotify::SetNotificationPositions",
+                &[
+                    ("this", &this),
+                    ("dwPositionNotifies", &dwPositionNotifies),
+                    ("pcPositionNotifies", &pcPositionNotifies),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSoundNotify::SetNotificationPositions(
+            machine,
+            this,
+            dwPositionNotifies,
+            pcPositionNotifies,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IDirectSound_CreateSoundBuffer(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let this = <u32>::from_stack(mem, stack_args + 0u32);
@@ -309,6 +591,37 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IDirectSound_DuplicateSoundBuffer(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let this = <u32>::from_stack(mem, stack_args + 0u32);
+        let lpDsbOriginal = <u32>::from_stack(mem, stack_args + 4u32);
+        let lplpDsbDuplicate = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("dsound") {
+            crate::trace::Record::new(
+                winapi::dsound::IDirectSound::DuplicateSoundBuffer_pos,
+                "dsound",
+                "IDirectSound::DuplicateSoundBuffer",
+                &[
+                    ("this", &this),
+                    ("lpDsbOriginal", &lpDsbOriginal),
+                    ("lplpDsbDuplicate", &lplpDsbDuplicate),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::dsound::IDirectSound::DuplicateSoundBuffer(
+            machine,
+            this,
+            lpDsbOriginal,
+            lplpDsbDuplicate,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IDirectSound_Release(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let this = <u32>::from_stack(mem, stack_args + 0u32);
@@ -353,7 +666,7 @@ mod wrappers {
         result.into_abireturn()
     }
 }
-const SHIMS: [Shim; 12usize] = [
+const SHIMS: [Shim; 25usize] = [
     Shim {
         name: "DirectSoundCreate",
         func: Handler::Sync(wrappers::DirectSoundCreate),
@@ -366,10 +679,26 @@ const SHIMS: [Shim; 12usize] = [
         name: "IDirectSoundBuffer::GetCurrentPosition",
         func: Handler::Sync(wrappers::IDirectSoundBuffer_GetCurrentPosition),
     },
+    Shim {
+        name: "IDirectSoundBuffer::GetFormat",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_GetFormat),
+    },
+    Shim {
+        name: "IDirectSoundBuffer::GetFrequency",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_GetFrequency),
+    },
+    Shim {
+        name: "IDirectSoundBuffer::GetPan",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_GetPan),
+    },
     Shim {
         name: "IDirectSoundBuffer::GetStatus",
         func: Handler::Sync(wrappers::IDirectSoundBuffer_GetStatus),
     },
+    Shim {
+        name: "IDirectSoundBuffer::GetVolume",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_GetVolume),
+    },
     Shim {
         name: "IDirectSoundBuffer::Lock",
         func: Handler::Sync(wrappers::IDirectSoundBuffer_Lock),
@@ -378,22 +707,58 @@ const SHIMS: [Shim; 12usize] = [
         name: "IDirectSoundBuffer::Play",
         func: Handler::Sync(wrappers::IDirectSoundBuffer_Play),
     },
+    Shim {
+        name: "IDirectSoundBuffer::QueryInterface",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_QueryInterface),
+    },
     Shim {
         name: "IDirectSoundBuffer::Release",
         func: Handler::Sync(wrappers::IDirectSoundBuffer_Release),
     },
+    Shim {
+        name: "IDirectSoundBuffer::SetCurrentPosition",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_SetCurrentPosition),
+    },
     Shim {
         name: "IDirectSoundBuffer::SetFormat",
         func: Handler::Sync(wrappers::IDirectSoundBuffer_SetFormat),
     },
+    Shim {
+        name: "IDirectSoundBuffer::SetFrequency",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_SetFrequency),
+    },
+    Shim {
+        name: "IDirectSoundBuffer::SetPan",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_SetPan),
+    },
+    Shim {
+        name: "IDirectSoundBuffer::SetVolume",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_SetVolume),
+    },
+    Shim {
+        name: "IDirectSoundBuffer::Stop",
+        func: Handler::Sync(wrappers::IDirectSoundBuffer_Stop),
+    },
     Shim {
         name: "IDirectSoundBuffer::Unlock",
         func: Handler::Sync(wrappers::IDirectSoundBuffer_Unlock),
     },
+    Shim {
+        name: "IDirectSoundNotify::Release",
+        func: Handler::Sync(wrappers::IDirectSoundNotify_Release),
+    },
+    Shim {
+        name: "IDirectSoundNotify::SetNotificationPositions",
+        func: Handler::Sync(wrappers::IDirectSoundNotify_SetNotificationPositions),
+    },
     Shim {
         name: "IDirectSound::CreateSoundBuffer",
         func: Handler::Sync(wrappers::IDirectSound_CreateSoundBuffer),
     },
+    Shim {
+        name: "IDirectSound::DuplicateSoundBuffer",
+        func: Handler::Sync(wrappers::IDirectSound_DuplicateSoundBuffer),
+    },
     Shim {
         name: "IDirectSound::Release",
         func: Handler::Sync(wrappers::IDirectSound_Release),
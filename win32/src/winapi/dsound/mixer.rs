@@ -0,0 +1,243 @@
+//! Software mixing of secondary buffers into the stream we send to the host.
+//!
+//! Samples are mixed as stereo f32 frames at the primary buffer's sample rate;
+//! each buffer is resampled by linear interpolation as it is mixed in.
+
+use super::WAVEFORMATEX;
+
+/// DSBVOLUME_MIN: volumes at or below this are silent.
+pub const DSBVOLUME_MIN: i32 = -10000;
+pub const DSBVOLUME_MAX: i32 = 0;
+pub const DSBPAN_LEFT: i32 = -10000;
+pub const DSBPAN_RIGHT: i32 = 10000;
+
+const WAVE_FORMAT_PCM: u16 = 1;
+
+/// The PCM format of a buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Format {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+}
+
+impl Format {
+    /// The format of the primary buffer until SetFormat is called.
+    pub const DEFAULT_PRIMARY: Format = Format {
+        channels: 2,
+        sample_rate: 22050,
+        bits_per_sample: 8,
+    };
+
+    /// Returns None for formats the mixer can't play: anything but 8/16-bit mono/stereo PCM.
+    pub fn from_wave(fmt: &WAVEFORMATEX) -> Option<Self> {
+        if fmt.wFormatTag != WAVE_FORMAT_PCM
+            || !matches!(fmt.nChannels, 1 | 2)
+            || !matches!(fmt.wBitsPerSample, 8 | 16)
+            || fmt.nSamplesPerSec == 0
+        {
+            log::warn!(
+                "unsupported wave format {tag:#x}, {channels} channels, {bits} bits, {rate} Hz",
+                tag = fmt.wFormatTag,
+                channels = fmt.nChannels,
+                bits = fmt.wBitsPerSample,
+                rate = fmt.nSamplesPerSec
+            );
+            return None;
+        }
+        Some(Format {
+            channels: fmt.nChannels,
+            sample_rate: fmt.nSamplesPerSec,
+            bits_per_sample: fmt.wBitsPerSample,
+        })
+    }
+
+    pub fn to_wave(self) -> WAVEFORMATEX {
+        WAVEFORMATEX {
+            wFormatTag: WAVE_FORMAT_PCM,
+            nChannels: self.channels,
+            nSamplesPerSec: self.sample_rate,
+            nAvgBytesPerSec: self.sample_rate * self.block_align(),
+            nBlockAlign: self.block_align() as u16,
+            wBitsPerSample: self.bits_per_sample,
+            cbSize: 0,
+        }
+    }
+
+    /// Bytes per frame.
+    pub fn block_align(&self) -> u32 {
+        self.channels as u32 * self.bits_per_sample as u32 / 8
+    }
+
+    /// Read a frame as left/right samples in [-1, 1].
+    fn read_frame(&self, data: &[u8], frame: usize) -> [f32; 2] {
+        let bytes = &data[frame * self.block_align() as usize..];
+        let sample = |i: usize| match self.bits_per_sample {
+            // 8-bit samples are unsigned.
+            8 => (bytes[i] as f32 - 128.0) / 128.0,
+            16 => i16::from_le_bytes([bytes[i * 2], bytes[i * 2 + 1]]) as f32 / 32768.0,
+            bits => unreachable!("{bits}-bit samples rejected by from_wave"),
+        };
+        match self.channels {
+            1 => {
+                let s = sample(0);
+                [s, s]
+            }
+            2 => [sample(0), sample(1)],
+            n => unreachable!("{n} channels rejected by from_wave"),
+        }
+    }
+}
+
+/// Convert an attenuation in hundredths of a decibel to a linear gain.
+pub fn gain(hundredths_db: i32) -> f32 {
+    if hundredths_db <= DSBVOLUME_MIN {
+        return 0.0;
+    }
+    10f32.powf(hundredths_db as f32 / 2000.0)
+}
+
+/// Left and right gains for a volume and pan, both in hundredths of a decibel.
+/// Panning attenuates the opposite channel.
+pub fn channel_gains(volume: i32, pan: i32) -> [f32; 2] {
+    let volume = gain(volume);
+    [volume * gain(-pan.max(0)), volume * gain(pan.min(0))]
+}
+
+/// A buffer's contents and how to play them.
+pub struct Voice<'a> {
+    pub data: &'a [u8],
+    pub format: Format,
+    /// Source frames to advance per output frame.
+    pub step: f64,
+    pub gains: [f32; 2],
+    pub looping: bool,
+}
+
+/// Mix a voice into out, starting at the play cursor (in source frames) and advancing it.
+/// Returns false if a non-looping voice reached its end.
+pub fn mix_voice(out: &mut [[f32; 2]], voice: &Voice, cursor: &mut f64) -> bool {
+    let len = voice.data.len() / voice.format.block_align() as usize;
+    if len == 0 {
+        return false;
+    }
+    for frame in out.iter_mut() {
+        let i = *cursor as usize;
+        let t = (*cursor - i as f64) as f32;
+        let next = if i + 1 < len {
+            Some(i + 1)
+        } else if voice.looping {
+            Some(0)
+        } else {
+            None
+        };
+        let a = voice.format.read_frame(voice.data, i);
+        let b = next.map_or([0.0; 2], |n| voice.format.read_frame(voice.data, n));
+        for c in 0..2 {
+            frame[c] += (a[c] + (b[c] - a[c]) * t) * voice.gains[c];
+        }
+
+        *cursor += voice.step;
+        if *cursor >= len as f64 {
+            if !voice.looping {
+                return false;
+            }
+            *cursor %= len as f64;
+        }
+    }
+    true
+}
+
+/// Downmix stereo frames to the mono 16-bit samples the host plays.
+pub fn to_host_samples(frames: &[[f32; 2]]) -> Vec<u8> {
+    frames
+        .iter()
+        .flat_map(|[l, r]| {
+            let s = ((l + r) / 2.0).clamp(-1.0, 1.0);
+            ((s * i16::MAX as f32) as i16).to_le_bytes()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_gains() {
+        assert_eq!(channel_gains(0, 0), [1.0, 1.0]);
+        assert_eq!(channel_gains(DSBVOLUME_MIN, 0), [0.0, 0.0]);
+        let [l, r] = channel_gains(-600, 0);
+        assert!((l - 0.501).abs() < 0.001 && l == r);
+        assert_eq!(channel_gains(0, DSBPAN_RIGHT), [0.0, 1.0]);
+        let [l, r] = channel_gains(0, -2000);
+        assert!(l == 1.0 && (r - 0.1).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_from_wave() {
+        let wave = Format::DEFAULT_PRIMARY.to_wave();
+        assert_eq!(Format::from_wave(&wave), Some(Format::DEFAULT_PRIMARY));
+        let rejected = [
+            WAVEFORMATEX {
+                wFormatTag: 3, // WAVE_FORMAT_IEEE_FLOAT
+                ..wave
+            },
+            WAVEFORMATEX {
+                nChannels: 0,
+                ..wave
+            },
+            WAVEFORMATEX {
+                nChannels: 6,
+                ..wave
+            },
+            WAVEFORMATEX {
+                wBitsPerSample: 4,
+                ..wave
+            },
+            WAVEFORMATEX {
+                wBitsPerSample: 24,
+                ..wave
+            },
+            WAVEFORMATEX {
+                nSamplesPerSec: 0,
+                ..wave
+            },
+        ];
+        for wave in rejected {
+            assert_eq!(Format::from_wave(&wave), None);
+        }
+    }
+
+    #[test]
+    fn test_mix_voice() {
+        let format = Format {
+            channels: 1,
+            sample_rate: 11025,
+            bits_per_sample: 8,
+        };
+        let data = [128 + 64, 128 - 64];
+        let mut voice = Voice {
+            data: &data,
+            format,
+            step: 0.5, // upsampling by 2
+            gains: [1.0, 0.5],
+            looping: true,
+        };
+
+        let mut out = [[0.0; 2]; 5];
+        let mut cursor = 0.0;
+        assert!(mix_voice(&mut out, &voice, &mut cursor));
+        let left: Vec<f32> = out.iter().map(|f| f[0]).collect();
+        assert_eq!(left, [0.5, 0.0, -0.5, 0.0, 0.5]);
+        assert_eq!(out[0][1], 0.25);
+        assert_eq!(cursor, 0.5);
+
+        voice.looping = false;
+        let mut out = [[0.0; 2]; 5];
+        let mut cursor = 0.0;
+        assert!(!mix_voice(&mut out, &voice, &mut cursor));
+        assert_eq!(out[3], [-0.25, -0.125]);
+        assert_eq!(out[4], [0.0, 0.0]);
+    }
+}
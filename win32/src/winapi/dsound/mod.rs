@@ -2,38 +2,60 @@
 #![allow(non_upper_case_globals)]
 
 mod builtin;
+pub mod mixer;
 
 pub use builtin::DLL;
 
 use super::heap::Heap;
 pub use crate::winapi::com::GUID;
 use crate::{
+    host,
     machine::Machine,
-    winapi::{com::vtable, kernel32::get_symbol},
+    winapi::{
+        com::vtable,
+        kernel32::{get_symbol, SetEvent, HEVENT},
+    },
 };
-use memory::ExtensionsMut;
+use memory::{Extensions, ExtensionsMut};
+use mixer::Format;
 use std::collections::HashMap;
 
-/// Set to true to make DirectSoundCreate report no sound device available.
-/// Doing this from the beginning would have been a better idea than trying to implement stubs here...
-const DISABLE: bool = true;
-
 pub const DS_OK: u32 = 0;
+const E_NOINTERFACE: u32 = 0x80004002;
 #[allow(unused)]
 const E_FAIL: u32 = 0x80004005;
 #[allow(unused)]
 pub const DSERR_GENERIC: u32 = E_FAIL;
-#[allow(unused)]
+pub const DSERR_INVALIDPARAM: u32 = 0x80070057;
+pub const DSERR_BADFORMAT: u32 = make_dhsresult(100);
 pub const DSERR_NODRIVER: u32 = make_dhsresult(120);
 
 const fn make_dhsresult(code: u32) -> u32 {
     (1 << 31) | (0x878 << 16) | code
 }
 
+pub const IID_IDirectSoundNotify: GUID = GUID((
+    0xb0210783,
+    0x89cd,
+    0x11d0,
+    [0xaf, 0x08, 0x00, 0xa0, 0xc9, 0x25, 0xcd, 0x16],
+));
+
+/// Mix at least this often while anything is playing, so that position notifications
+/// reach threads blocked waiting for them.
+const MIX_INTERVAL_MS: u32 = 10;
+/// If we haven't mixed for longer than this, drop the backlog rather than catching up.
+const MAX_MIX_MS: u32 = 250;
+
 #[derive(Default)]
 pub struct State {
     heap: Heap,
     buffers: HashMap<u32, Buffer>,
+    /// The primary buffer's format, which is what we mix to.
+    primary_format: Option<Format>,
+    audio: Option<Box<dyn host::Audio>>,
+    /// Host tick count up to which we've mixed.
+    mixed_until: u32,
 }
 
 impl State {
@@ -46,18 +68,159 @@ impl State {
         );
         dsound
     }
+
+    fn set_primary_format(&mut self, host: &mut dyn host::Host, format: Format) {
+        if self.primary_format != Some(format) {
+            self.primary_format = Some(format);
+            self.audio = Some(host.init_audio(format.sample_rate));
+            self.mixed_until = host.ticks();
+        }
+    }
 }
 
-#[derive(Default)]
 struct Buffer {
+    primary: bool,
+    /// Sample memory, which duplicated buffers share.
     addr: u32,
     size: u32,
-    lock: Option<Lock>,
+    format: Format,
+    playing: bool,
+    looping: bool,
+    /// Play cursor, in frames; fractional because of resampling.
+    cursor: f64,
+    /// Attenuation, in hundredths of a decibel.
+    volume: i32,
+    /// Relative volume of the channels, in hundredths of a decibel.
+    pan: i32,
+    /// Playback sample rate, or 0 for the rate of the format.
+    frequency: u32,
+    notify: Vec<DSBPOSITIONNOTIFY>,
 }
 
-struct Lock {
-    addr: u32,
-    size: u32,
+impl Buffer {
+    fn new(format: Format) -> Self {
+        Buffer {
+            primary: false,
+            addr: 0,
+            size: 0,
+            format,
+            playing: false,
+            looping: false,
+            cursor: 0.0,
+            volume: mixer::DSBVOLUME_MAX,
+            pan: 0,
+            frequency: 0,
+            notify: Vec::new(),
+        }
+    }
+
+    /// Play cursor, in bytes.
+    fn play_pos(&self) -> u32 {
+        self.cursor as u32 * self.format.block_align()
+    }
+
+    /// Where it's safe to write: a little ahead of the play cursor, past what the next
+    /// mix() will consume.
+    fn write_pos(&self) -> u32 {
+        if !self.playing || self.size == 0 {
+            return self.play_pos();
+        }
+        let ahead = self.format.sample_rate * MIX_INTERVAL_MS / 1000 * self.format.block_align();
+        (self.play_pos() + ahead) % self.size
+    }
+
+    /// Events for notification positions in the byte range played from `from` to the
+    /// current position, which may have wrapped around the end of the buffer.
+    fn crossed_notifications(&self, from: u32, wrapped: bool) -> impl Iterator<Item = u32> + '_ {
+        let to = self.play_pos();
+        self.notify
+            .iter()
+            .filter(move |n| {
+                let ofs = n.dwOffset;
+                if ofs == DSBPN_OFFSETSTOP {
+                    return false;
+                }
+                if wrapped {
+                    ofs >= from || ofs < to
+                } else {
+                    ofs >= from && ofs < to
+                }
+            })
+            .map(|n| n.hEventNotify)
+    }
+
+    fn stop_notifications(&self) -> impl Iterator<Item = u32> + '_ {
+        self.notify
+            .iter()
+            .filter(|n| n.dwOffset == DSBPN_OFFSETSTOP)
+            .map(|n| n.hEventNotify)
+    }
+}
+
+fn signal(machine: &mut Machine, events: Vec<u32>) {
+    for event in events {
+        SetEvent(machine, HEVENT::from_raw(event));
+    }
+}
+
+/// Mix playing buffers up to the current time, send the result to the host, and signal
+/// any position notifications that playback passed.
+pub fn mix(machine: &mut Machine) {
+    let now = machine.host.ticks();
+    let state = &mut machine.state.dsound;
+    let (Some(format), Some(audio)) = (state.primary_format, state.audio.as_mut()) else {
+        return;
+    };
+    let elapsed = now.wrapping_sub(state.mixed_until).min(MAX_MIX_MS);
+    let frames = (elapsed as u64 * format.sample_rate as u64 / 1000) as usize;
+    if frames == 0 {
+        return;
+    }
+    state.mixed_until = now;
+
+    let mem = machine.emu.memory.mem();
+    let mut out = vec![[0.0f32; 2]; frames];
+    let mut events = Vec::new();
+    for buf in state.buffers.values_mut() {
+        if buf.primary || !buf.playing {
+            continue;
+        }
+        let rate = match buf.frequency {
+            0 => buf.format.sample_rate,
+            rate => rate,
+        };
+        let voice = mixer::Voice {
+            data: mem.sub32(buf.addr, buf.size),
+            format: buf.format,
+            step: rate as f64 / format.sample_rate as f64,
+            gains: mixer::channel_gains(buf.volume, buf.pan),
+            looping: buf.looping,
+        };
+        let from = buf.play_pos();
+        let start = buf.cursor;
+        if mixer::mix_voice(&mut out, &voice, &mut buf.cursor) {
+            events.extend(buf.crossed_notifications(from, buf.cursor < start));
+        } else {
+            // Ran off the end: notify everything to the end, then stop and rewind.
+            buf.cursor = (buf.size / buf.format.block_align()) as f64;
+            events.extend(buf.crossed_notifications(from, false));
+            buf.playing = false;
+            buf.cursor = 0.0;
+            events.extend(buf.stop_notifications());
+        }
+    }
+    audio.write(&mixer::to_host_samples(&out));
+    signal(machine, events);
+}
+
+/// Tick count by which mix() should next run, if anything is playing.
+pub fn mix_due(machine: &Machine) -> Option<u32> {
+    let state = &machine.state.dsound;
+    if state.buffers.values().any(|b| b.playing) {
+        Some(state.mixed_until + MIX_INTERVAL_MS)
+    } else {
+        None
+    }
 }
 
 bitflags::bitflags! {
@@ -102,7 +265,7 @@ pub struct DSBUFFERDESC {
 unsafe impl memory::Pod for DSBUFFERDESC {}
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct WAVEFORMATEX {
     pub wFormatTag: u16,
     pub nChannels: u16,
@@ -114,6 +277,21 @@ pub struct WAVEFORMATEX {
 }
 unsafe impl memory::Pod for WAVEFORMATEX {}
 
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct DSBPOSITIONNOTIFY {
+    pub dwOffset: u32,
+    pub hEventNotify: u32,
+}
+unsafe impl memory::Pod for DSBPOSITIONNOTIFY {}
+
+/// Notification offset meaning "when the buffer stops".
+const DSBPN_OFFSETSTOP: u32 = 0xFFFF_FFFF;
+
+const DSBPLAY_LOOPING: u32 = 1;
+const DSBSTATUS_PLAYING: u32 = 1;
+const DSBSTATUS_LOOPING: u32 = 4;
+
 #[win32_derive::dllexport]
 pub mod IDirectSound {
     use super::*;
@@ -139,14 +317,24 @@ pub mod IDirectSound {
         lplpDirectSoundBuffer: Option<&mut u32>,
         pUnkOuter: u32,
     ) -> u32 {
-        let x86_buffer = IDirectSoundBuffer::new(machine);
         let desc = lpcDSBufferDesc.unwrap();
         assert!(desc.dwSize == std::mem::size_of::<DSBUFFERDESC>() as u32);
-        *lplpDirectSoundBuffer.unwrap() = x86_buffer;
-        log::info!("=> {x86_buffer:x}");
 
-        let mut buffer = Buffer::default();
-        if !desc.dwFlags.contains(DSBCAPS::PRIMARYBUFFER) {
+        let mut buffer = if desc.dwFlags.contains(DSBCAPS::PRIMARYBUFFER) {
+            let mut buffer = Buffer::new(machine.state.dsound.primary_format.unwrap());
+            buffer.primary = true;
+            buffer
+        } else {
+            if desc.lpwfxFormat == 0 {
+                return DSERR_INVALIDPARAM;
+            }
+            let wave = machine.mem().get_pod::<WAVEFORMATEX>(desc.lpwfxFormat);
+            let Some(format) = Format::from_wave(&wave) else {
+                return DSERR_BADFORMAT;
+            };
+            Buffer::new(format)
+        };
+        if !buffer.primary {
             buffer.addr = machine
                 .state
                 .dsound
@@ -155,6 +343,33 @@ pub mod IDirectSound {
             buffer.size = desc.dwBufferBytes;
         }
 
+        let x86_buffer = IDirectSoundBuffer::new(machine);
+        *lplpDirectSoundBuffer.unwrap() = x86_buffer;
+        log::info!("=> {x86_buffer:x}");
+        machine.state.dsound.buffers.insert(x86_buffer, buffer);
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn DuplicateSoundBuffer(
+        machine: &mut Machine,
+        this: u32,
+        lpDsbOriginal: u32,
+        lplpDsbDuplicate: Option<&mut u32>,
+    ) -> u32 {
+        let Some(original) = machine.state.dsound.buffers.get(&lpDsbOriginal) else {
+            return DSERR_INVALIDPARAM;
+        };
+        if original.primary {
+            return DSERR_INVALIDPARAM;
+        }
+        // The duplicate shares the sample memory but has its own cursor and settings.
+        let mut buffer = Buffer::new(original.format);
+        buffer.addr = original.addr;
+        buffer.size = original.size;
+
+        let x86_buffer = IDirectSoundBuffer::new(machine);
+        *lplpDsbDuplicate.unwrap() = x86_buffer;
         machine.state.dsound.buffers.insert(x86_buffer, buffer);
         DS_OK
     }
@@ -170,7 +385,7 @@ pub mod IDirectSound {
         Release: ok,
         CreateSoundBuffer: ok,
         GetCaps: todo,
-        DuplicateSoundBuffer: todo,
+        DuplicateSoundBuffer: ok,
         SetCooperativeLevel: ok,
         Compact: todo,
         GetSpeakerConfig: todo,
@@ -191,6 +406,22 @@ pub mod IDirectSoundBuffer {
         lpDirectSoundBuffer
     }
 
+    #[win32_derive::dllexport]
+    pub fn QueryInterface(
+        machine: &mut Machine,
+        this: u32,
+        riid: Option<&GUID>,
+        ppvObject: Option<&mut u32>,
+    ) -> u32 {
+        match riid.unwrap() {
+            &IID_IDirectSoundNotify => {
+                *ppvObject.unwrap() = IDirectSoundNotify::new(machine, this);
+                DS_OK
+            }
+            _ => E_NOINTERFACE,
+        }
+    }
+
     #[win32_derive::dllexport]
     pub fn Release(_machine: &mut Machine, this: u32) -> u32 {
         0
@@ -198,26 +429,78 @@ pub mod IDirectSoundBuffer {
 
     #[win32_derive::dllexport]
     pub fn GetCurrentPosition(
-        _machine: &mut Machine,
+        machine: &mut Machine,
         this: u32,
         lpdwCurrentPlayCursor: Option<&mut u32>,
         lpdwCurrentWriteCursor: Option<&mut u32>,
     ) -> u32 {
-        match lpdwCurrentPlayCursor {
-            Some(play) => *play = 0,
-            None => {}
+        mix(machine);
+        let buf = &machine.state.dsound.buffers[&this];
+        if let Some(play) = lpdwCurrentPlayCursor {
+            *play = buf.play_pos();
+        }
+        if let Some(write) = lpdwCurrentWriteCursor {
+            *write = buf.write_pos();
+        }
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn GetFormat(
+        machine: &mut Machine,
+        this: u32,
+        lpwfxFormat: u32,
+        dwSizeAllocated: u32,
+        lpdwSizeWritten: Option<&mut u32>,
+    ) -> u32 {
+        let wave = machine.state.dsound.buffers[&this].format.to_wave();
+        let size = std::mem::size_of::<WAVEFORMATEX>() as u32;
+        if lpwfxFormat != 0 {
+            if dwSizeAllocated < size {
+                return DSERR_INVALIDPARAM;
+            }
+            machine.mem().put_pod::<WAVEFORMATEX>(lpwfxFormat, wave);
         }
-        match lpdwCurrentWriteCursor {
-            Some(write) => *write = 0,
-            None => {}
+        if let Some(written) = lpdwSizeWritten {
+            *written = size;
         }
         DS_OK
     }
 
     #[win32_derive::dllexport]
-    pub fn GetStatus(_machine: &mut Machine, this: u32, lpdwStatus: Option<&mut u32>) -> u32 {
-        let status = lpdwStatus.unwrap();
-        *status = 0;
+    pub fn GetVolume(machine: &mut Machine, this: u32, plVolume: Option<&mut i32>) -> u32 {
+        *plVolume.unwrap() = machine.state.dsound.buffers[&this].volume;
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn GetPan(machine: &mut Machine, this: u32, plPan: Option<&mut i32>) -> u32 {
+        *plPan.unwrap() = machine.state.dsound.buffers[&this].pan;
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn GetFrequency(machine: &mut Machine, this: u32, pdwFrequency: Option<&mut u32>) -> u32 {
+        let buf = &machine.state.dsound.buffers[&this];
+        *pdwFrequency.unwrap() = match buf.frequency {
+            0 => buf.format.sample_rate,
+            freq => freq,
+        };
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn GetStatus(machine: &mut Machine, this: u32, lpdwStatus: Option<&mut u32>) -> u32 {
+        mix(machine);
+        let buf = &machine.state.dsound.buffers[&this];
+        let mut status = 0;
+        if buf.playing {
+            status |= DSBSTATUS_PLAYING;
+            if buf.looping {
+                status |= DSBSTATUS_LOOPING;
+            }
+        }
+        *lpdwStatus.unwrap() = status;
         DS_OK
     }
 
@@ -233,101 +516,220 @@ pub mod IDirectSoundBuffer {
         lpdwAudioBytes2: Option<&mut u32>,
         dwFlags: Result<DSBLOCK, u32>,
     ) -> u32 {
+        mix(machine);
         let flags = dwFlags.unwrap();
-        if flags.contains(DSBLOCK::ENTIREBUFFER) {
-            let buf = machine.state.dsound.buffers.get_mut(&this).unwrap();
-            assert!(buf.lock.is_none());
-            *lplpvAudioPtr1.unwrap() = buf.addr;
-            *lpdwAudioBytes1.unwrap() = buf.size;
-            buf.lock = Some(Lock {
-                addr: buf.addr,
-                size: buf.size,
-            });
+        let buf = &machine.state.dsound.buffers[&this];
+        if buf.primary {
+            todo!("lock primary buffer");
+        }
+        let (start, len) = if flags.contains(DSBLOCK::ENTIREBUFFER) {
+            (0, buf.size)
+        } else if flags.contains(DSBLOCK::FROMWRITECURSOR) {
+            (buf.write_pos(), dwWriteBytes)
         } else {
-            todo!();
+            (dwWriteCursor, dwWriteBytes)
+        };
+        if start >= buf.size || len > buf.size {
+            return DSERR_INVALIDPARAM;
+        }
+
+        // A region running off the end of the buffer wraps around to the start.
+        let len1 = len.min(buf.size - start);
+        *lplpvAudioPtr1.unwrap() = buf.addr + start;
+        *lpdwAudioBytes1.unwrap() = len1;
+        let (ptr2, len2) = match len - len1 {
+            0 => (0, 0),
+            rest => (buf.addr, rest),
+        };
+        if let Some(ptr) = lplpvAudioPtr2 {
+            *ptr = ptr2;
+        }
+        if let Some(bytes) = lpdwAudioBytes2 {
+            *bytes = len2;
         }
         DS_OK
     }
 
     #[win32_derive::dllexport]
     pub fn Play(
-        _machine: &mut Machine,
+        machine: &mut Machine,
         this: u32,
         dwReserved1: u32,
         dwReserved2: u32,
         dwFlags: u32,
     ) -> u32 {
-        assert_eq!(dwFlags, 1); // LOOPING
+        mix(machine);
+        let buf = machine.state.dsound.buffers.get_mut(&this).unwrap();
+        buf.playing = true;
+        buf.looping = dwFlags & DSBPLAY_LOOPING != 0;
         DS_OK
     }
 
     #[win32_derive::dllexport]
-    pub fn SetFormat(_machine: &mut Machine, this: u32, lpcfxFormat: Option<&WAVEFORMATEX>) -> u32 {
-        let fmt = lpcfxFormat.unwrap();
-        // Just check fmt is the one we support..
-        assert!(matches!(
-            fmt,
-            WAVEFORMATEX {
-                wFormatTag: 1, // PCM
-                nChannels: 2,
-                nSamplesPerSec: 44100,
-                nAvgBytesPerSec: 176400, // 4*44100
-                nBlockAlign: 4,
-                wBitsPerSample: 16,
-                cbSize: 0,
-            }
-        ));
+    pub fn SetCurrentPosition(machine: &mut Machine, this: u32, dwNewPosition: u32) -> u32 {
+        mix(machine);
+        let buf = machine.state.dsound.buffers.get_mut(&this).unwrap();
+        if dwNewPosition >= buf.size {
+            return DSERR_INVALIDPARAM;
+        }
+        buf.cursor = (dwNewPosition / buf.format.block_align()) as f64;
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn SetFormat(machine: &mut Machine, this: u32, lpcfxFormat: Option<&WAVEFORMATEX>) -> u32 {
+        let Some(wave) = lpcfxFormat else {
+            return DSERR_INVALIDPARAM;
+        };
+        let Some(format) = Format::from_wave(wave) else {
+            return DSERR_BADFORMAT;
+        };
+        let buf = machine.state.dsound.buffers.get_mut(&this).unwrap();
+        if !buf.primary {
+            // Secondary buffer formats are fixed at creation.
+            return DSERR_INVALIDPARAM;
+        }
+        buf.format = format;
+        mix(machine);
+        machine
+            .state
+            .dsound
+            .set_primary_format(&mut *machine.host, format);
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn SetVolume(machine: &mut Machine, this: u32, lVolume: i32) -> u32 {
+        if !(mixer::DSBVOLUME_MIN..=mixer::DSBVOLUME_MAX).contains(&lVolume) {
+            return DSERR_INVALIDPARAM;
+        }
+        mix(machine);
+        machine.state.dsound.buffers.get_mut(&this).unwrap().volume = lVolume;
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn SetPan(machine: &mut Machine, this: u32, lPan: i32) -> u32 {
+        if !(mixer::DSBPAN_LEFT..=mixer::DSBPAN_RIGHT).contains(&lPan) {
+            return DSERR_INVALIDPARAM;
+        }
+        mix(machine);
+        machine.state.dsound.buffers.get_mut(&this).unwrap().pan = lPan;
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn SetFrequency(machine: &mut Machine, this: u32, dwFrequency: u32) -> u32 {
+        mix(machine);
+        // 0 (DSBFREQUENCY_ORIGINAL) resets to the rate of the buffer's format.
+        machine
+            .state
+            .dsound
+            .buffers
+            .get_mut(&this)
+            .unwrap()
+            .frequency = dwFrequency;
+        DS_OK
+    }
+
+    #[win32_derive::dllexport]
+    pub fn Stop(machine: &mut Machine, this: u32) -> u32 {
+        mix(machine);
+        let buf = machine.state.dsound.buffers.get_mut(&this).unwrap();
+        if !buf.playing {
+            return DS_OK;
+        }
+        buf.playing = false;
+        let events = buf.stop_notifications().collect();
+        signal(machine, events);
         DS_OK
     }
 
     #[win32_derive::dllexport]
     pub fn Unlock(
-        machine: &mut Machine,
+        _machine: &mut Machine,
         this: u32,
         lpvAudioPtr1: u32,
         dwAudioBytes1: u32,
         lpvAudioPtr2: u32,
         dwAudioBytes2: u32,
     ) -> u32 {
-        let buf = machine.state.dsound.buffers.get_mut(&this).unwrap();
-        let lock = buf.lock.take().unwrap();
-
-        assert_eq!(lpvAudioPtr1, lock.addr);
-        assert_eq!(dwAudioBytes1, lock.size);
-        // TODO: handle case where these don't match
-
-        // Secondary lock not used.
-        assert_eq!(lpvAudioPtr2, 0);
-        assert_eq!(dwAudioBytes2, 0);
-
+        // The mixer reads buffer memory directly, so there's nothing to commit.
         DS_OK
     }
 
     vtable![
-        QueryInterface: todo,
+        QueryInterface: ok,
         AddRef: todo,
         Release: ok,
         GetCaps: todo,
         GetCurrentPosition: ok,
-        GetFormat: todo,
-        GetVolume: todo,
-        GetPan: todo,
-        GetFrequency: todo,
+        GetFormat: ok,
+        GetVolume: ok,
+        GetPan: ok,
+        GetFrequency: ok,
         GetStatus: ok,
         Initialize: todo,
         Lock: ok,
         Play: ok,
-        SetCurrentPosition: todo,
+        SetCurrentPosition: ok,
         SetFormat: ok,
-        SetVolume: todo,
-        SetPan: todo,
-        SetFrequency: todo,
-        Stop: todo,
+        SetVolume: ok,
+        SetPan: ok,
+        SetFrequency: ok,
+        Stop: ok,
         Unlock: ok,
         Restore: todo,
     ];
 }
 
+#[win32_derive::dllexport]
+pub mod IDirectSoundNotify {
+    use super::*;
+
+    /// The notify object is the vtable followed by the buffer it belongs to.
+    pub fn new(machine: &mut Machine, buffer: u32) -> u32 {
+        let dsound = &mut machine.state.dsound;
+        let lpNotify = dsound.heap.alloc(machine.emu.memory.mem(), 8);
+        let vtable = get_symbol(machine, "dsound.dll", "IDirectSoundNotify");
+        machine.mem().put_pod::<u32>(lpNotify, vtable);
+        machine.mem().put_pod::<u32>(lpNotify + 4, buffer);
+        lpNotify
+    }
+
+    #[win32_derive::dllexport]
+    pub fn Release(_machine: &mut Machine, this: u32) -> u32 {
+        0
+    }
+
+    #[win32_derive::dllexport]
+    pub fn SetNotificationPositions(
+        machine: &mut Machine,
+        this: u32,
+        dwPositionNotifies: u32,
+        pcPositionNotifies: u32,
+    ) -> u32 {
+        let buffer = machine.mem().get_pod::<u32>(this + 4);
+        let notify = machine
+            .mem()
+            .iter_pod::<DSBPOSITIONNOTIFY>(pcPositionNotifies, dwPositionNotifies)
+            .collect::<Vec<_>>();
+        let buf = machine.state.dsound.buffers.get_mut(&buffer).unwrap();
+        if buf.playing {
+            return DSERR_INVALIDPARAM;
+        }
+        buf.notify = notify;
+        DS_OK
+    }
+
+    vtable![
+        QueryInterface: todo,
+        AddRef: todo,
+        Release: ok,
+        SetNotificationPositions: ok,
+    ];
+}
+
 #[win32_derive::dllexport(ordinal = 1)]
 pub fn DirectSoundCreate(
     machine: &mut Machine,
@@ -335,11 +737,15 @@ pub fn DirectSoundCreate(
     ppDS: Option<&mut u32>,
     pUnkOuter: u32,
 ) -> u32 {
-    if DISABLE {
+    if !machine.state.winmm.audio_enabled {
         return DSERR_NODRIVER;
     }
     if machine.state.dsound.heap.addr == 0 {
         machine.state.dsound = State::new_init(machine);
+        machine
+            .state
+            .dsound
+            .set_primary_format(&mut *machine.host, Format::DEFAULT_PRIMARY);
     }
     let lpDirectSound = IDirectSound::new(machine);
     *ppDS.unwrap() = lpDirectSound;
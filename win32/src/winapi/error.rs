@@ -14,9 +14,20 @@ pub enum ERROR {
     INVALID_DATA = 13,
     OUT_OF_PAPER = 28,
     FILE_EXISTS = 80,
+    BROKEN_PIPE = 109,
     OPEN_FAILED = 110,
+    INVALID_PARAMETER = 87,
     MOD_NOT_FOUND = 126,
     ALREADY_EXISTS = 183,
+    BAD_EXE_FORMAT = 193,
+    NO_DATA = 232,
+    MORE_DATA = 234,
+    NO_MORE_ITEMS = 259,
+    NOT_OWNER = 288,
+    TOO_MANY_POSTS = 298,
+    INVALID_WINDOW_HANDLE = 1400,
+    INVALID_THREAD_ID = 1444,
+    TIMEOUT = 1460,
 }
 
 impl From<std::io::Error> for ERROR {
@@ -1,13 +1,16 @@
-use super::{DCTarget, Object, BITMAPINFOHEADER, COLORREF, HDC, HGDIOBJ};
+use super::{
+    dc_palette, new_palette, Brush, DCTarget, Object, BITMAPINFOHEADER, COLORREF, DC, HDC, HGDIOBJ,
+};
 use crate::{
     machine::Machine,
     winapi::{
-        bitmap::{Bitmap, PixelData, PixelFormat, BI},
+        bitmap::{Bitmap, BitmapInfo, ColorMasks, PixelData, PixelFormat, BI},
         kernel32,
-        types::{POINT, RECT},
+        types::RECT,
     },
 };
-use memory::{Extensions, Mem};
+use memory::{Extensions, ExtensionsMut, Mem};
+use std::cell::RefCell;
 
 pub type HBITMAP = HGDIOBJ;
 
@@ -23,53 +26,320 @@ pub struct BITMAP {
 }
 unsafe impl memory::Pod for BITMAP {}
 
-/// For the subset of dst within r, write pixels from op(x, y).
-/// Performs no clipping; caller must have already clipped.
-// TODO: remove me in favor of pattern used in StretchBlt
-fn fill_pixels(
-    mem: Mem,
-    dst: &mut Bitmap,
-    r: &RECT,
-    mut op: impl FnMut(POINT, [u8; 4]) -> [u8; 4],
-) {
-    let stride = dst.width as i32;
-    let pixels = dst.as_rgba_mut(mem);
-    for y in r.top..r.bottom {
-        for x in r.left..r.right {
-            let p = &mut pixels[(y * stride + x) as usize];
-            *p = op(POINT { x, y }, *p);
+/// A ternary raster operation, as passed to BitBlt and friends.  The high word's low
+/// byte is a truth table giving the result bit for each combination of pattern, source
+/// and destination bits; the low word is a hint for drivers that we don't need.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RasterOp(u32);
+
+impl RasterOp {
+    pub const SRCCOPY: RasterOp = RasterOp(0xcc0020);
+    pub const SRCPAINT: RasterOp = RasterOp(0xee0086);
+    pub const SRCAND: RasterOp = RasterOp(0x8800c6);
+    pub const SRCINVERT: RasterOp = RasterOp(0x660046);
+    pub const SRCERASE: RasterOp = RasterOp(0x440328);
+    pub const NOTSRCCOPY: RasterOp = RasterOp(0x330008);
+    pub const NOTSRCERASE: RasterOp = RasterOp(0x1100a6);
+    pub const MERGECOPY: RasterOp = RasterOp(0xc000ca);
+    pub const MERGEPAINT: RasterOp = RasterOp(0xbb0226);
+    pub const PATCOPY: RasterOp = RasterOp(0xf00021);
+    pub const PATPAINT: RasterOp = RasterOp(0xfb0a09);
+    pub const PATINVERT: RasterOp = RasterOp(0x5a0049);
+    pub const DSTINVERT: RasterOp = RasterOp(0x550009);
+    pub const BLACKNESS: RasterOp = RasterOp(0x000042);
+    pub const WHITENESS: RasterOp = RasterOp(0xff0062);
+
+    fn code(&self) -> u8 {
+        (self.0 >> 16) as u8
+    }
+
+    /// Return true if the result depends on the source, i.e. flipping the source bit
+    /// (bit 1 of the truth table index) changes some entry of the table.
+    pub fn uses_src(&self) -> bool {
+        let code = self.code();
+        ((code >> 2) ^ code) & 0x33 != 0
+    }
+
+    pub fn uses_pat(&self) -> bool {
+        let code = self.code();
+        ((code >> 4) ^ code) & 0x0f != 0
+    }
+
+    /// Combine pattern, source and destination pixels.
+    pub fn apply(&self, pat: COLORREF, src: COLORREF, dst: COLORREF) -> COLORREF {
+        let [p, s, d] = [pat, src, dst].map(|c| {
+            let [r, g, b] = c.to_rgb();
+            u32::from_le_bytes([r, g, b, 0])
+        });
+        let code = self.code();
+        let mut out = 0;
+        for i in 0..8 {
+            if code & (1 << i) == 0 {
+                continue;
+            }
+            let p = if i & 4 != 0 { p } else { !p };
+            let s = if i & 2 != 0 { s } else { !s };
+            let d = if i & 1 != 0 { d } else { !d };
+            out |= p & s & d;
         }
+        let [r, g, b, _] = out.to_le_bytes();
+        COLORREF::from_rgb(r, g, b)
     }
 }
 
-#[derive(Debug, win32_derive::TryFromEnum, PartialEq, Eq)]
-pub enum RasterOp {
-    SRCCOPY = 0xcc0020,
-    NOTSRCCOPY = 0x330008,
-    SRCAND = 0x8800c6,
-    PATCOPY = 0xf00021,
-    BLACKNESS = 0x000042,
+impl std::fmt::Debug for RasterOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RasterOp({:#x})", self.0)
+    }
 }
 
-impl RasterOp {
-    /// Return true if this uses the src surface at all.
-    fn uses_src(&self) -> bool {
-        match *self {
-            RasterOp::PATCOPY | RasterOp::BLACKNESS => false,
-            RasterOp::SRCCOPY | RasterOp::NOTSRCCOPY | RasterOp::SRCAND => true,
+impl<'a> crate::winapi::calling_convention::FromArg<'a> for RasterOp {
+    unsafe fn from_arg(_mem: Mem<'a>, arg: u32) -> Self {
+        RasterOp(arg)
+    }
+}
+
+/// How StretchBlt combines source pixels when shrinking, as set by SetStretchBltMode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, win32_derive::TryFromEnum)]
+pub enum StretchMode {
+    /// AND pixels together, preserving black pixels in monochrome images.
+    #[default]
+    BLACKONWHITE = 1,
+    /// OR pixels together, preserving white pixels.
+    WHITEONBLACK = 2,
+    /// Drop pixels.
+    COLORONCOLOR = 3,
+    /// Average pixels.
+    HALFTONE = 4,
+}
+
+#[win32_derive::dllexport]
+pub fn SetStretchBltMode(machine: &mut Machine, hdc: HDC, mode: Result<StretchMode, u32>) -> u32 {
+    let (Some(dc), Ok(mode)) = (machine.state.gdi32.dcs.get(hdc), mode) else {
+        return 0; // fail
+    };
+    std::mem::replace(&mut dc.borrow_mut().stretch_mode, mode) as u32
+}
+
+/// Normalize a rectangle with negative extents, which blits use to request mirroring.
+/// Returns the rectangle and whether it was flipped horizontally and vertically.
+fn normalize(r: RECT) -> (RECT, bool, bool) {
+    let flip_x = r.right < r.left;
+    let flip_y = r.bottom < r.top;
+    let rect = RECT {
+        left: r.left.min(r.right),
+        top: r.top.min(r.bottom),
+        right: r.left.max(r.right),
+        bottom: r.top.max(r.bottom),
+    };
+    (rect, flip_x, flip_y)
+}
+
+/// The source of a blit: a region of a bitmap, which gets stretched over the destination.
+pub(super) struct BlitSource<'a> {
+    bitmap: &'a RefCell<Bitmap>,
+    rect: RECT,
+    /// The source DC's background color, which maps to white when converting color
+    /// to monochrome.
+    bk_color: COLORREF,
+}
+
+impl BlitSource<'_> {
+    /// Compute the source pixel for each pixel of clip, a subset of dst_rect.
+    /// Pixels that map outside of the source bitmap are None.
+    fn sample(
+        &self,
+        mem: Mem,
+        dc: &DC,
+        dst_rect: &RECT,
+        clip: &RECT,
+        dst_mono: bool,
+    ) -> Vec<Option<COLORREF>> {
+        let (dst_rect, dst_flip_x, dst_flip_y) = normalize(*dst_rect);
+        let (rect, flip_x, flip_y) = normalize(self.rect);
+        let (flip_x, flip_y) = (flip_x != dst_flip_x, flip_y != dst_flip_y);
+
+        let bitmap = self.bitmap.borrow();
+        let src_mono = matches!(bitmap.format, PixelFormat::Mono);
+        let bounds = rect.clip(&bitmap.to_rect());
+        let bounds_width = (bounds.right - bounds.left).max(0);
+        let mut pixels = Vec::new();
+        if bounds_width > 0 {
+            let mut row = vec![COLORREF::white(); bounds_width as usize];
+            for y in bounds.top..bounds.bottom {
+                bitmap.read_row(
+                    mem,
+                    bounds.left as u32..bounds.right as u32,
+                    y as u32,
+                    &mut row,
+                );
+                pixels.extend_from_slice(&row);
+            }
         }
+        drop(bitmap);
+        let get = |x: i32, y: i32| -> Option<COLORREF> {
+            if x < bounds.left || x >= bounds.right || y < bounds.top || y >= bounds.bottom {
+                return None;
+            }
+            Some(pixels[((y - bounds.top) * bounds_width + (x - bounds.left)) as usize])
+        };
+
+        // The range of source coordinates that map to a destination coordinate.
+        fn span(
+            rel: i32,
+            flip: bool,
+            dst_len: i32,
+            src_start: i32,
+            src_len: i32,
+        ) -> std::ops::Range<i32> {
+            let rel = if flip { dst_len - 1 - rel } else { rel };
+            let a = src_start + rel * src_len / dst_len;
+            let b = (src_start + (rel + 1) * src_len / dst_len).max(a + 1);
+            a..b
+        }
+
+        let dst_w = dst_rect.right - dst_rect.left;
+        let dst_h = dst_rect.bottom - dst_rect.top;
+        let src_w = rect.right - rect.left;
+        let src_h = rect.bottom - rect.top;
+        let mut out =
+            Vec::with_capacity(((clip.right - clip.left) * (clip.bottom - clip.top)) as usize);
+        for y in clip.top..clip.bottom {
+            let ys = span(y - dst_rect.top, flip_y, dst_h, rect.top, src_h);
+            for x in clip.left..clip.right {
+                let xs = span(x - dst_rect.left, flip_x, dst_w, rect.left, src_w);
+                let pixel = if dc.stretch_mode == StretchMode::COLORONCOLOR
+                    || (xs.len() == 1 && ys.len() == 1)
+                {
+                    get(xs.start, ys.start)
+                } else {
+                    combine(
+                        dc.stretch_mode,
+                        ys.clone()
+                            .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                            .filter_map(|(x, y)| get(x, y)),
+                    )
+                };
+                out.push(pixel.map(|pixel| {
+                    if src_mono && !dst_mono {
+                        // Monochrome sources expand into the destination's colors.
+                        if pixel == COLORREF::white() {
+                            dc.bk_color
+                        } else {
+                            dc.text_color
+                        }
+                    } else if !src_mono && dst_mono {
+                        if pixel == self.bk_color {
+                            COLORREF::white()
+                        } else {
+                            COLORREF::from_rgb(0, 0, 0)
+                        }
+                    } else {
+                        pixel
+                    }
+                }));
+            }
+        }
+        out
     }
+}
 
-    /// Return a function that combines a dst and src pixel.
-    fn to_binop(&self) -> fn([u8; 4], [u8; 4]) -> [u8; 4] {
-        match self {
-            RasterOp::SRCCOPY => |_, src| src,
-            RasterOp::SRCAND => |dst, src| {
-                // https://godbolt.org/z/aT1qc7EKo
-                (u32::from_le_bytes(dst) & u32::from_le_bytes(src)).to_le_bytes()
-            },
-            _ => todo!(),
+/// Merge the source pixels that shrink down to a single destination pixel.
+fn combine(mode: StretchMode, pixels: impl Iterator<Item = COLORREF>) -> Option<COLORREF> {
+    let mut count = 0;
+    let mut acc = [0u32; 3];
+    for pixel in pixels {
+        let rgb = pixel.to_rgb().map(|c| c as u32);
+        if count == 0 {
+            acc = rgb;
+        } else {
+            for (a, c) in acc.iter_mut().zip(rgb) {
+                *a = match mode {
+                    StretchMode::BLACKONWHITE => *a & c,
+                    StretchMode::WHITEONBLACK => *a | c,
+                    StretchMode::HALFTONE => *a + c,
+                    StretchMode::COLORONCOLOR => *a,
+                };
+            }
         }
+        count += 1;
+    }
+    if count == 0 {
+        return None;
+    }
+    if mode == StretchMode::HALFTONE {
+        acc = acc.map(|a| a / count);
+    }
+    Some(COLORREF::from_rgb(acc[0] as u8, acc[1] as u8, acc[2] as u8))
+}
+
+/// Combine source, pattern and destination pixels within dst_rect according to rop.
+/// The core of all the blit functions.
+fn blit(machine: &Machine, dc: &DC, dst_rect: RECT, src: Option<BlitSource>, rop: RasterOp) {
+    let default_brush = Brush::new(Some(COLORREF::white()));
+    let brush = match machine.state.gdi32.objects.get(dc.brush) {
+        Some(Object::Brush(brush)) => brush,
+        _ => &default_brush,
+    };
+    blit_with_brush(machine, dc, dst_rect, src, brush, rop);
+}
+
+/// Like blit, but with an explicit brush in place of the DC's selected brush.
+pub(super) fn blit_with_brush(
+    machine: &Machine,
+    dc: &DC,
+    dst_rect: RECT,
+    src: Option<BlitSource>,
+    brush: &Brush,
+    rop: RasterOp,
+) {
+    let mem = machine.emu.memory.mem();
+    let dst_bitmap = dc.target.get_bitmap(machine);
+    let (region, dst_mono) = {
+        let dst = dst_bitmap.borrow();
+        let region = dc.clip_region(dst.to_rect());
+        (region, matches!(dst.format, PixelFormat::Mono))
+    };
+    let clip = normalize(dst_rect).0.clip(&region.bounds());
+    if clip.left >= clip.right || clip.top >= clip.bottom {
+        return;
+    }
+    if rop.uses_pat() && brush.color.is_none() && brush.pattern.is_none() {
+        return; // null brush
+    }
+
+    // Sample the source before borrowing the destination, which might be the same bitmap.
+    let src_pixels = src.map(|src| src.sample(mem, dc, &dst_rect, &clip, dst_mono));
+
+    let black = COLORREF::from_rgb(0, 0, 0);
+    let width = (clip.right - clip.left) as usize;
+    let mut dst = dst_bitmap.borrow_mut();
+    let mut row = vec![black; width];
+    for y in clip.top..clip.bottom {
+        let xs = clip.left as u32..clip.right as u32;
+        dst.read_row(mem, xs.clone(), y as u32, &mut row);
+        for (left, right) in region.row_spans(y) {
+            for x in left.max(clip.left)..right.min(clip.right) {
+                let i = (x - clip.left) as usize;
+                let s = match &src_pixels {
+                    Some(pixels) => match pixels[(y - clip.top) as usize * width + i] {
+                        Some(s) => s,
+                        None => continue,
+                    },
+                    None => black,
+                };
+                let p = if rop.uses_pat() {
+                    match brush.pixel(dc, x, y) {
+                        Some(p) => p,
+                        None => continue,
+                    }
+                } else {
+                    black
+                };
+                row[i] = rop.apply(p, s, row[i]);
+            }
+        }
+        dst.write_row(mem, xs, y as u32, &row);
     }
 }
 
@@ -86,13 +356,22 @@ pub fn StretchBlt(
     ySrc: i32,
     wSrc: i32,
     hSrc: i32,
-    rop: Result<RasterOp, u32>,
+    rop: RasterOp,
 ) -> bool {
-    let rop = rop.unwrap();
     if !rop.uses_src() {
-        return PatBlt(machine, hdcDst, xDst, yDst, wDst, hDst, Ok(rop));
+        return PatBlt(machine, hdcDst, xDst, yDst, wDst, hDst, rop);
     }
-    let op = rop.to_binop();
+    let Some(dst_dc) = machine.state.gdi32.dcs.get(hdcDst) else {
+        return false;
+    };
+    let Some(src_dc) = machine.state.gdi32.dcs.get(hdcSrc) else {
+        return false;
+    };
+    let dst_dc = dst_dc.borrow();
+    let (src_bitmap, src_bk_color) = {
+        let src_dc = src_dc.borrow();
+        (src_dc.target.get_bitmap(machine), src_dc.bk_color)
+    };
 
     let dst_rect = RECT {
         left: xDst,
@@ -100,54 +379,17 @@ pub fn StretchBlt(
         right: xDst + wDst,
         bottom: yDst + hDst,
     };
-
-    let src_rect = RECT {
-        left: xSrc,
-        top: ySrc,
-        right: xSrc + wSrc,
-        bottom: ySrc + hSrc,
+    let src = BlitSource {
+        bitmap: &src_bitmap,
+        rect: RECT {
+            left: xSrc,
+            top: ySrc,
+            right: xSrc + wSrc,
+            bottom: ySrc + hSrc,
+        },
+        bk_color: src_bk_color,
     };
-
-    let src_dc = machine.state.gdi32.dcs.get(hdcSrc).unwrap().borrow();
-    let src_bitmap = src_dc.target.get_bitmap(machine);
-    let src_bitmap = src_bitmap.borrow();
-
-    let dst_dc = machine.state.gdi32.dcs.get(hdcDst).unwrap().borrow();
-    let dst_bitmap = dst_dc.target.get_bitmap(machine);
-    let mut dst_bitmap = dst_bitmap.borrow_mut();
-
-    // What does it mean when the src_rect isn't within the src bitmap?
-    // sol.exe does this when you drag a card off screen.
-    // Clipping here means the xform below will stretch slightly, hrmm.
-    // log::info!("src_rect={:x?}", src_rect);
-    let src_rect = src_rect.clip(&src_bitmap.to_rect());
-    // log::info!("src_rect clipped={:x?}", src_rect);
-
-    let copy_rect = dst_rect.clip(&dst_bitmap.to_rect());
-    // log::info!("dst_rect={:x?}", dst_rect);
-    // log::info!("dst_bitmap.to_rect()={:x?}", dst_bitmap.to_rect());
-    // log::info!("copy_rect={:x?}", copy_rect);
-
-    let mem = machine.emu.memory.mem();
-
-    let mut row: Vec<COLORREF> = Vec::with_capacity((src_rect.right - src_rect.left) as usize);
-    row.resize_with(row.capacity(), || COLORREF::from_rgb(0xff, 0, 0));
-    for dy in copy_rect.top..copy_rect.bottom {
-        let sy = (dy - yDst) * hSrc / hDst + ySrc;
-        src_bitmap.read_row(
-            mem,
-            src_rect.left as u32..src_rect.right as u32,
-            sy as u32,
-            &mut row,
-        );
-        dst_bitmap.write_row(
-            mem,
-            copy_rect.left as u32..copy_rect.right as u32,
-            dy as u32,
-            &row,
-        );
-    }
-    drop(dst_bitmap);
+    blit(machine, &dst_dc, dst_rect, Some(src), rop);
 
     dst_dc.target.flush(machine);
     true
@@ -164,7 +406,7 @@ pub fn BitBlt(
     hdcSrc: HDC,
     xSrc: i32,
     ySrc: i32,
-    rop: Result<RasterOp, u32>,
+    rop: RasterOp,
 ) -> bool {
     StretchBlt(
         machine, hdcDst, xDst, yDst, w, h, hdcSrc, xSrc, ySrc, w, h, rop,
@@ -179,45 +421,23 @@ pub fn PatBlt(
     y: i32,
     w: i32,
     h: i32,
-    rop: Result<RasterOp, u32>,
+    rop: RasterOp,
 ) -> bool {
     if hdc.is_null() {
         log::warn!("PatBlt: ignoring null hdc, possibly child window");
         return false;
     }
-    let rop = rop.unwrap();
+    if rop.uses_src() {
+        return false; // invalid
+    }
     let dc = machine.state.gdi32.dcs.get(hdc).unwrap().borrow();
-
-    const DEFAULT_COLOR: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
-
-    let color = match rop {
-        RasterOp::PATCOPY => {
-            // get brush color
-            match machine.state.gdi32.objects.get(dc.brush) {
-                Some(Object::Brush(brush)) => match brush.color {
-                    Some(color) => color.to_pixel(),
-                    None => DEFAULT_COLOR,
-                },
-                _ => DEFAULT_COLOR,
-            }
-        }
-        RasterOp::BLACKNESS => [0, 0, 0, 0xFF],
-        _ => todo!("unimplemented PatBlt with rop={rop:?}"),
-    };
-
-    let dst_bitmap = dc.target.get_bitmap(machine);
-    let mut dst_bitmap = dst_bitmap.borrow_mut();
-    dst_bitmap.format.expect_rgba32();
     let dst_rect = RECT {
         left: x,
         top: y,
         right: x + w,
         bottom: y + h,
-    }
-    .clip(&dst_bitmap.to_rect());
-
-    fill_pixels(machine.mem(), &mut *dst_bitmap, &dst_rect, |_, _| color);
-    drop(dst_bitmap);
+    };
+    blit(machine, &dc, dst_rect, None, rop);
     dc.target.flush(machine);
     true
 }
@@ -232,65 +452,83 @@ pub fn CreateBitmap(
     lpBits: u32,
 ) -> HGDIOBJ {
     assert_eq!(nPlanes, 1);
-    let bitmap = match nBitCount {
-        1 => {
-            let format = PixelFormat::Mono;
-            let stride = format.stride(nWidth);
-            let len = (nHeight * stride) as usize;
-            let mut pixels = Vec::with_capacity(len);
-            pixels.resize(len, 0);
-            pixels.copy_from_slice(machine.mem().sub32(lpBits, len as u32));
-            Bitmap {
-                width: nWidth,
-                height: nHeight,
-                format,
-                pixels: PixelData::new_with_owned(pixels.into_boxed_slice()),
-            }
-        }
-        _ => unimplemented!(),
+    // The initial bits are laid out like a top-down DIB, but with rows padded to 2 bytes.
+    let format = match nBitCount {
+        1 => PixelFormat::Mono,
+        8 => PixelFormat::Indexed8(machine.state.gdi32.system_palette.clone()),
+        16 => PixelFormat::RGB16(ColorMasks::RGB555),
+        24 => PixelFormat::BGR24,
+        32 => PixelFormat::RGB32(ColorMasks::BGRX32),
+        n => todo!("CreateBitmap({n}bpp)"),
+    };
+    let stride = format.stride(nWidth);
+    let len = nHeight * stride;
+    let pixels = if lpBits == 0 {
+        vec![0; len as usize]
+    } else {
+        machine.mem().sub32(lpBits, len).to_vec()
+    };
+    let bitmap = Bitmap {
+        width: nWidth,
+        height: nHeight,
+        format,
+        stride,
+        bottom_up: false,
+        pixels: PixelData::new_with_owned(pixels.into_boxed_slice()),
+    };
+    // Color bitmaps are stored in the screen's format.
+    let bitmap = match bitmap.format {
+        PixelFormat::Mono => bitmap,
+        _ => bitmap.to_rgba32(machine.mem()),
     };
     machine.state.gdi32.objects.add_bitmap(bitmap)
 }
 
 const DIB_RGB_COLORS: u32 = 0;
-// const DIB_PAL_COLORS: u32 = 1;
+const DIB_PAL_COLORS: u32 = 1;
+
+/// Parse the BITMAPINFO at pbmi.  With DIB_PAL_COLORS the color table holds indices
+/// into the DC's selected palette rather than colors.
+fn dib_info(machine: &Machine, hdc: HDC, pbmi: u32, usage: u32) -> BitmapInfo {
+    let mem = machine.mem();
+    let mut info = BitmapInfo::parse(mem.slice(pbmi..));
+    match usage {
+        DIB_RGB_COLORS => {}
+        DIB_PAL_COLORS => {
+            let count = info.palette.borrow().len() as u32;
+            let table = pbmi + mem.get_pod::<u32>(pbmi);
+            let palette = dc_palette(machine, hdc);
+            let palette = palette.borrow();
+            info.palette = new_palette(
+                mem.iter_pod::<u16>(table, count)
+                    .map(|index| palette.get(index as usize).copied().unwrap_or_default()),
+            );
+        }
+        _ => todo!("DIB usage {usage}"),
+    }
+    info
+}
 
 #[win32_derive::dllexport]
 pub fn CreateDIBSection(
     machine: &mut Machine,
     hdc: HDC,
-    pbmi: Option<&BITMAPINFOHEADER>,
+    pbmi: u32,
     usage: u32,
     ppvBits: Option<&mut u32>, // **void
     hSection: u32,
     offset: u32,
 ) -> HGDIOBJ {
-    if usage != DIB_RGB_COLORS {
-        todo!()
-    }
     if hSection != 0 || offset != 0 {
         todo!()
     }
 
-    let bi = &pbmi.unwrap();
-    if bi.biSize != std::mem::size_of::<BITMAPINFOHEADER>() as u32 {
-        todo!()
-    }
-    if bi.is_bottom_up() {
-        log::warn!("CreateDIBSection: bottom-up bitmap will need flipping");
-    }
-    if bi.biBitCount != 32 {
-        todo!()
-    }
-    match bi.compression().unwrap() {
-        BI::BITFIELDS => {
-            // TODO: ought to check that .bmiColors masks are the RGBX we expect.
-        }
-        BI::RGB => {} // ok
-        _ => todo!(),
+    let info = dib_info(machine, hdc, pbmi, usage);
+    match info.compression {
+        BI::RGB | BI::BITFIELDS => {}
+        _ => todo!("{:?} DIB section", info.compression),
     };
-
-    let byte_count = bi.stride() as u32 * bi.height();
+    let byte_count = (info.stride * info.height) as u32;
     let heap = kernel32::GetProcessHeap(machine);
     let pixels = kernel32::HeapAlloc(
         machine,
@@ -302,9 +540,11 @@ pub fn CreateDIBSection(
     *ppvBits.unwrap() = pixels;
 
     let bitmap = Bitmap {
-        width: bi.width(),
-        height: bi.height(),
-        format: PixelFormat::RGBA32,
+        width: info.width as u32,
+        height: info.height as u32,
+        format: info.format(),
+        stride: info.stride as u32,
+        bottom_up: info.is_bottom_up,
         pixels: PixelData::Ptr(pixels, byte_count),
     };
     machine.state.gdi32.objects.add_bitmap(bitmap)
@@ -330,6 +570,8 @@ pub fn CreateCompatibleBitmap(machine: &mut Machine, hdc: HDC, cx: u32, cy: u32)
         width: cx,
         height: cy,
         format: PixelFormat::RGBA32,
+        stride: cx * 4,
+        bottom_up: false,
         pixels: PixelData::new_owned((cx * cy * 4) as usize),
     };
     machine.state.gdi32.objects.add_bitmap(bitmap)
@@ -354,49 +596,27 @@ pub fn SetDIBitsToDevice(
     if StartScan as i32 != ySrc || cLines as i32 != h {
         todo!()
     }
-    if iUsage != DIB_RGB_COLORS {
-        todo!();
-    }
-    let src_bitmap = Bitmap::parse(
-        machine.mem().slice(lpBmi..),
-        Some((machine.mem().slice(lpBits..), cLines as usize)),
-    );
+    let info = dib_info(machine, hdc, lpBmi, iUsage);
+    let src_bitmap = Bitmap::decode(&info, machine.mem().slice(lpBits..), Some(cLines as usize));
 
     let dc = machine.state.gdi32.dcs.get(hdc).unwrap().borrow();
-    let dst_bitmap = dc.target.get_bitmap(machine);
-    let mut dst_bitmap = dst_bitmap.borrow_mut();
-    dst_bitmap.format.expect_rgba32();
-
-    let src_rect = RECT {
-        left: xSrc,
-        top: ySrc,
-        right: xSrc + w,
-        bottom: ySrc + h,
+    let src = BlitSource {
+        bitmap: &RefCell::new(src_bitmap),
+        rect: RECT {
+            left: xSrc,
+            top: ySrc,
+            right: xSrc + w,
+            bottom: ySrc + h,
+        },
+        bk_color: dc.bk_color,
     };
-
     let dst_rect = RECT {
         left: xDst,
         top: yDst,
         right: xDst + w,
         bottom: yDst + h,
     };
-
-    // Clip copy region to the relevant bitmap bounds.
-    let src_copy_rect = src_rect.clip(&src_bitmap.to_rect());
-    let dst_copy_rect = dst_rect.clip(&dst_bitmap.to_rect());
-    let copy_rect =
-        dst_copy_rect.clip(&src_copy_rect.add(dst_rect.origin().sub(src_rect.origin())));
-    let dst_to_src = src_rect.origin().sub(dst_rect.origin());
-
-    let mem = machine.mem();
-    let src = src_bitmap.as_rgba(mem);
-    fill_pixels(mem, &mut *dst_bitmap, &copy_rect, |p, _| {
-        let p = p.add(dst_to_src);
-        let mut pixel = src[(p.y * src_bitmap.width as i32 + p.x) as usize];
-        pixel[3] = 0xFF;
-        pixel
-    });
-    drop(dst_bitmap);
+    blit(machine, &dc, dst_rect, Some(src), RasterOp::SRCCOPY);
     dc.target.flush(machine);
 
     cLines
@@ -417,87 +637,169 @@ pub fn StretchDIBits(
     lpBits: u32,
     lpBmi: u32,
     iUsage: u32,
-    rop: Result<RasterOp, u32>,
+    rop: RasterOp,
 ) -> i32 {
-    match rop.unwrap() {
-        RasterOp::SRCCOPY => {}
-        _ => todo!(),
-    }
-
-    let src_bitmap = Bitmap::parse(
-        machine.mem().slice(lpBmi..),
-        Some((machine.mem().slice(lpBits..), hSrc as usize)),
-    );
+    let info = dib_info(machine, hdc, lpBmi, iUsage);
+    let src_bitmap = Bitmap::decode(&info, machine.mem().slice(lpBits..), None);
 
     let dc = machine.state.gdi32.dcs.get(hdc).unwrap().borrow();
-    let dst_bitmap = dc.target.get_bitmap(machine);
-    let mut dst_bitmap = dst_bitmap.borrow_mut();
-    dst_bitmap.format.expect_rgba32();
-
-    let src_rect = RECT {
-        left: xSrc,
-        top: ySrc,
-        right: xSrc + wSrc,
-        bottom: ySrc + hSrc,
+    let src = BlitSource {
+        bitmap: &RefCell::new(src_bitmap),
+        rect: RECT {
+            left: xSrc,
+            top: ySrc,
+            right: xSrc + wSrc,
+            bottom: ySrc + hSrc,
+        },
+        bk_color: dc.bk_color,
     };
-
     let dst_rect = RECT {
         left: xDst,
         top: yDst,
         right: xDst + wDst,
         bottom: yDst + hDst,
     };
-
-    // TODO: do we need to clip src or dst rect?
-
-    let copy_rect = dst_rect;
-
-    let mem = machine.mem();
-    let src = src_bitmap.as_rgba(mem);
-    fill_pixels(mem, &mut *dst_bitmap, &dst_rect, |p, _| {
-        // Translate p from dst to src space.
-        // Because we're stretching, we scale to src space rather than clipping.
-        let p = p
-            .sub(dst_rect.origin())
-            .mul(src_rect.size())
-            .div(dst_rect.size())
-            .add(src_rect.origin());
-        let mut pixel = src[(p.y * src_bitmap.width as i32 + p.x) as usize];
-        pixel[3] = 0xFF;
-        pixel
-    });
-    drop(dst_bitmap);
-
+    blit(machine, &dc, dst_rect, Some(src), rop);
     dc.target.flush(machine);
 
     hSrc
 }
 
-pub type BITMAPINFO = u32; // TODO
-
+/// Copy scanlines out of a bitmap, converted to the DIB format described by lpbmi.
 #[win32_derive::dllexport]
 pub fn GetDIBits(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hdc: HDC,
     hbm: HBITMAP,
     start: u32,
     cLines: u32,
-    lpvBits: Option<&mut u8>,
-    lpbmi: Option<&mut BITMAPINFO>,
+    lpvBits: u32,
+    lpbmi: u32,
     usage: u32, /* DIB_USAGE */
 ) -> i32 {
-    todo!()
+    let bitmap = match machine.state.gdi32.objects.get(hbm) {
+        Some(Object::Bitmap(bitmap)) => bitmap.clone(),
+        _ => return 0,
+    };
+    let bitmap = bitmap.borrow();
+    let mem = machine.mem();
+    let mut bi = mem.get_pod::<BITMAPINFOHEADER>(lpbmi);
+    if bi.biBitCount == 0 {
+        // Caller is asking for the bitmap's dimensions and format.
+        bi.biWidth = bitmap.width;
+        bi.biHeight = bitmap.height;
+        bi.biPlanes = 1;
+        bi.biBitCount = bitmap.format.bits_per_pixel() as u16;
+        bi.biCompression = BI::RGB as u32;
+        bi.biSizeImage = bi.stride() as u32 * bitmap.height;
+        mem.put_pod::<BITMAPINFOHEADER>(lpbmi, bi);
+        return 1;
+    }
+    if lpvBits == 0 {
+        bi.biSizeImage = bi.stride() as u32 * bi.height();
+        mem.put_pod::<BITMAPINFOHEADER>(lpbmi, bi);
+        return 1;
+    }
+    if usage != DIB_RGB_COLORS {
+        todo!("GetDIBits usage {usage}");
+    }
+
+    let format = match bi.biBitCount {
+        32 | 16 => {
+            let masks = bi.color_masks(mem.slice(lpbmi + bi.biSize..));
+            PixelFormat::from_masks(bi.biBitCount as u32, masks.unwrap())
+        }
+        24 => PixelFormat::BGR24,
+        n => todo!("GetDIBits to {n}bpp"),
+    };
+    let stride = bi.stride() as u32;
+    let lines = cLines.min(bitmap.height.saturating_sub(start));
+    let mut row = vec![COLORREF::from_rgb(0, 0, 0); bitmap.width as usize];
+    let mut dst = Bitmap {
+        width: bitmap.width,
+        height: 1,
+        format,
+        stride,
+        bottom_up: false,
+        pixels: PixelData::Ptr(lpvBits, stride),
+    };
+    for i in 0..lines {
+        // Scanline 0 is the bottom row of a bottom-up DIB.
+        let scanline = start + i;
+        let y = if bi.is_bottom_up() {
+            bitmap.height - 1 - scanline
+        } else {
+            scanline
+        };
+        bitmap.read_row(mem, 0..bitmap.width, y, &mut row);
+        dst.pixels = PixelData::Ptr(lpvBits + i * stride, stride);
+        dst.write_row(mem, 0..bitmap.width, 0, &row);
+    }
+    lines as i32
 }
 
+const CBM_INIT: u32 = 4;
+
 #[win32_derive::dllexport]
 pub fn CreateDIBitmap(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hdc: HDC,
-    pbmih: Option<&mut BITMAPINFOHEADER>,
+    pbmih: u32,
     flInit: u32,
-    pjBits: Option<&mut u8>,
-    pbmi: Option<&mut BITMAPINFO>,
+    pjBits: u32,
+    pbmi: u32,
     iUsage: u32, /* DIB_USAGE */
 ) -> HBITMAP {
-    todo!()
+    let bitmap = if flInit & CBM_INIT != 0 {
+        let info = dib_info(machine, hdc, pbmi, iUsage);
+        Bitmap::decode(&info, machine.mem().slice(pjBits..), None)
+    } else {
+        let header = machine.mem().get_pod::<BITMAPINFOHEADER>(pbmih);
+        let (width, height) = (header.width(), header.height());
+        Bitmap {
+            width,
+            height,
+            format: PixelFormat::RGBA32,
+            stride: width * 4,
+            bottom_up: false,
+            pixels: PixelData::new_owned((width * height * 4) as usize),
+        }
+    };
+    machine.state.gdi32.objects.add_bitmap(bitmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raster_op() {
+        let black = COLORREF::from_rgb(0, 0, 0);
+        let white = COLORREF::white();
+        let (p, s, d) = (
+            COLORREF::from_rgb(0xf0, 0x0f, 0x55),
+            COLORREF::from_rgb(0xcc, 0xcc, 0x00),
+            COLORREF::from_rgb(0xaa, 0x0f, 0xff),
+        );
+        assert_eq!(RasterOp::SRCCOPY.apply(p, s, d), s);
+        assert_eq!(RasterOp::PATCOPY.apply(p, s, d), p);
+        assert_eq!(RasterOp::BLACKNESS.apply(p, s, d), black);
+        assert_eq!(RasterOp::WHITENESS.apply(p, s, d), white);
+        assert_eq!(
+            RasterOp::SRCAND.apply(p, s, d),
+            COLORREF::from_rgb(0x88, 0x0c, 0x00)
+        );
+        assert_eq!(
+            RasterOp::SRCPAINT.apply(p, s, d),
+            COLORREF::from_rgb(0xee, 0xcf, 0xff)
+        );
+        assert_eq!(
+            RasterOp::DSTINVERT.apply(p, s, d),
+            COLORREF::from_rgb(0x55, 0xf0, 0x00)
+        );
+
+        assert!(RasterOp::SRCAND.uses_src() && !RasterOp::SRCAND.uses_pat());
+        assert!(!RasterOp::PATINVERT.uses_src() && RasterOp::PATINVERT.uses_pat());
+        assert!(RasterOp::MERGECOPY.uses_src() && RasterOp::MERGECOPY.uses_pat());
+    }
 }
@@ -12,6 +12,34 @@ mod wrappers {
     };
     use ::memory::Extensions;
     use winapi::gdi32::*;
+    pub unsafe fn AnimatePalette(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hPal = <HPALETTE>::from_stack(mem, stack_args + 0u32);
+        let iStartIndex = <u32>::from_stack(mem, stack_args + 4u32);
+        let cEntries = <u32>::from_stack(mem, stack_args + 8u32);
+        let ppe = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("gdi32/palette") {
+            crate::trace::Record::new(
+                winapi::gdi32::AnimatePalette_pos,
+                "gdi32/palette",
+                "AnimatePalette",
+                &[
+                    ("hPal", &hPal),
+                    ("iStartIndex", &iStartIndex),
+                    ("cEntries", &cEntries),
+                    ("ppe", &ppe),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::AnimatePalette(machine, hPal, iStartIndex, cEntries, ppe);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn BitBlt(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdcDst = <HDC>::from_stack(mem, stack_args + 0u32);
@@ -22,7 +50,7 @@ mod wrappers {
         let hdcSrc = <HDC>::from_stack(mem, stack_args + 20u32);
         let xSrc = <i32>::from_stack(mem, stack_args + 24u32);
         let ySrc = <i32>::from_stack(mem, stack_args + 28u32);
-        let rop = <Result<RasterOp, u32>>::from_stack(mem, stack_args + 32u32);
+        let rop = <RasterOp>::from_stack(mem, stack_args + 32u32);
         let __trace_record = if crate::trace::enabled("gdi32/bitmap") {
             crate::trace::Record::new(
                 winapi::gdi32::BitBlt_pos,
@@ -51,6 +79,34 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn CombineRgn(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hrgnDst = <HRGN>::from_stack(mem, stack_args + 0u32);
+        let hrgnSrc1 = <HRGN>::from_stack(mem, stack_args + 4u32);
+        let hrgnSrc2 = <HRGN>::from_stack(mem, stack_args + 8u32);
+        let iMode = <Result<RGN, u32>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::CombineRgn_pos,
+                "gdi32/region",
+                "CombineRgn",
+                &[
+                    ("hrgnDst", &hrgnDst),
+                    ("hrgnSrc1", &hrgnSrc1),
+                    ("hrgnSrc2", &hrgnSrc2),
+                    ("iMode", &iMode),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::CombineRgn(machine, hrgnDst, hrgnSrc1, hrgnSrc2, iMode);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn CreateBitmap(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let nWidth = <u32>::from_stack(mem, stack_args + 0u32);
@@ -127,7 +183,7 @@ mod wrappers {
     pub unsafe fn CreateDIBSection(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
-        let pbmi = <Option<&BITMAPINFOHEADER>>::from_stack(mem, stack_args + 4u32);
+        let pbmi = <u32>::from_stack(mem, stack_args + 4u32);
         let usage = <u32>::from_stack(mem, stack_args + 8u32);
         let ppvBits = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
         let hSection = <u32>::from_stack(mem, stack_args + 16u32);
@@ -160,10 +216,10 @@ mod wrappers {
     pub unsafe fn CreateDIBitmap(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
-        let pbmih = <Option<&mut BITMAPINFOHEADER>>::from_stack(mem, stack_args + 4u32);
+        let pbmih = <u32>::from_stack(mem, stack_args + 4u32);
         let flInit = <u32>::from_stack(mem, stack_args + 8u32);
-        let pjBits = <Option<&mut u8>>::from_stack(mem, stack_args + 12u32);
-        let pbmi = <Option<&mut BITMAPINFO>>::from_stack(mem, stack_args + 16u32);
+        let pjBits = <u32>::from_stack(mem, stack_args + 12u32);
+        let pbmi = <u32>::from_stack(mem, stack_args + 16u32);
         let iUsage = <u32>::from_stack(mem, stack_args + 20u32);
         let __trace_record = if crate::trace::enabled("gdi32/bitmap") {
             crate::trace::Record::new(
@@ -254,6 +310,131 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn CreateFontIndirectA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lplf = <Option<&LOGFONTA>>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("gdi32/text") {
+            crate::trace::Record::new(
+                winapi::gdi32::CreateFontIndirectA_pos,
+                "gdi32/text",
+                "CreateFontIndirectA",
+                &[("lplf", &lplf)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::CreateFontIndirectA(machine, lplf);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateFontIndirectW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lplf = <Option<&LOGFONTW>>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("gdi32/text") {
+            crate::trace::Record::new(
+                winapi::gdi32::CreateFontIndirectW_pos,
+                "gdi32/text",
+                "CreateFontIndirectW",
+                &[("lplf", &lplf)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::CreateFontIndirectW(machine, lplf);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateFontW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let cHeight = <i32>::from_stack(mem, stack_args + 0u32);
+        let cWidth = <i32>::from_stack(mem, stack_args + 4u32);
+        let cEscapement = <i32>::from_stack(mem, stack_args + 8u32);
+        let cOrientation = <i32>::from_stack(mem, stack_args + 12u32);
+        let cWeight = <u32>::from_stack(mem, stack_args + 16u32);
+        let bItalic = <u32>::from_stack(mem, stack_args + 20u32);
+        let bUnderline = <u32>::from_stack(mem, stack_args + 24u32);
+        let bStrikeOut = <u32>::from_stack(mem, stack_args + 28u32);
+        let iCharSet = <u32>::from_stack(mem, stack_args + 32u32);
+        let iOutPrecision = <u32>::from_stack(mem, stack_args + 36u32);
+        let iClipPrecision = <u32>::from_stack(mem, stack_args + 40u32);
+        let iQuality = <u32>::from_stack(mem, stack_args + 44u32);
+        let iPitchAndFamily = <u32>::from_stack(mem, stack_args + 48u32);
+        let pszFaceName = <Option<&Str16>>::from_stack(mem, stack_args + 52u32);
+        let __trace_record = if crate::trace::enabled("gdi32/text") {
+            crate::trace::Record::new(
+                winapi::gdi32::CreateFontW_pos,
+                "gdi32/text",
+                "CreateFontW",
+                &[
+                    ("cHeight", &cHeight),
+                    ("cWidth", &cWidth),
+                    ("cEscapement", &cEscapement),
+                    ("cOrientation", &cOrientation),
+                    ("cWeight", &cWeight),
+                    ("bItalic", &bItalic),
+                    ("bUnderline", &bUnderline),
+                    ("bStrikeOut", &bStrikeOut),
+                    ("iCharSet", &iCharSet),
+                    ("iOutPrecision", &iOutPrecision),
+                    ("iClipPrecision", &iClipPrecision),
+                    ("iQuality", &iQuality),
+                    ("iPitchAndFamily", &iPitchAndFamily),
+                    ("pszFaceName", &pszFaceName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::CreateFontW(
+            machine,
+            cHeight,
+            cWidth,
+            cEscapement,
+            cOrientation,
+            cWeight,
+            bItalic,
+            bUnderline,
+            bStrikeOut,
+            iCharSet,
+            iOutPrecision,
+            iClipPrecision,
+            iQuality,
+            iPitchAndFamily,
+            pszFaceName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateHatchBrush(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let iHatch = <Result<HS, u32>>::from_stack(mem, stack_args + 0u32);
+        let color = <COLORREF>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("gdi32/draw") {
+            crate::trace::Record::new(
+                winapi::gdi32::CreateHatchBrush_pos,
+                "gdi32/draw",
+                "CreateHatchBrush",
+                &[("iHatch", &iHatch), ("color", &color)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::CreateHatchBrush(machine, iHatch, color);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn CreatePalette(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let plpal = <u32>::from_stack(mem, stack_args + 0u32);
@@ -274,6 +455,26 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn CreatePatternBrush(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hbm = <HGDIOBJ>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("gdi32/draw") {
+            crate::trace::Record::new(
+                winapi::gdi32::CreatePatternBrush_pos,
+                "gdi32/draw",
+                "CreatePatternBrush",
+                &[("hbm", &hbm)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::CreatePatternBrush(machine, hbm);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn CreatePen(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let iStyle = <Result<PS, u32>>::from_stack(mem, stack_args + 0u32);
@@ -296,6 +497,49 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn CreateRectRgn(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let x1 = <i32>::from_stack(mem, stack_args + 0u32);
+        let y1 = <i32>::from_stack(mem, stack_args + 4u32);
+        let x2 = <i32>::from_stack(mem, stack_args + 8u32);
+        let y2 = <i32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::CreateRectRgn_pos,
+                "gdi32/region",
+                "CreateRectRgn",
+                &[("x1", &x1), ("y1", &y1), ("x2", &x2), ("y2", &y2)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::CreateRectRgn(machine, x1, y1, x2, y2);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateRectRgnIndirect(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lprect = <Option<&RECT>>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::CreateRectRgnIndirect_pos,
+                "gdi32/region",
+                "CreateRectRgnIndirect",
+                &[("lprect", &lprect)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::CreateRectRgnIndirect(machine, lprect);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn CreateSolidBrush(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let color = <COLORREF>::from_stack(mem, stack_args + 0u32);
@@ -387,6 +631,78 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn EqualRgn(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hrgn1 = <HRGN>::from_stack(mem, stack_args + 0u32);
+        let hrgn2 = <HRGN>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::EqualRgn_pos,
+                "gdi32/region",
+                "EqualRgn",
+                &[("hrgn1", &hrgn1), ("hrgn2", &hrgn2)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::EqualRgn(machine, hrgn1, hrgn2);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn ExcludeClipRect(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
+        let left = <i32>::from_stack(mem, stack_args + 4u32);
+        let top = <i32>::from_stack(mem, stack_args + 8u32);
+        let right = <i32>::from_stack(mem, stack_args + 12u32);
+        let bottom = <i32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::ExcludeClipRect_pos,
+                "gdi32/region",
+                "ExcludeClipRect",
+                &[
+                    ("hdc", &hdc),
+                    ("left", &left),
+                    ("top", &top),
+                    ("right", &right),
+                    ("bottom", &bottom),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::ExcludeClipRect(machine, hdc, left, top, right, bottom);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetClipBox(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
+        let lprect = <Option<&mut RECT>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::GetClipBox_pos,
+                "gdi32/region",
+                "GetClipBox",
+                &[("hdc", &hdc), ("lprect", &lprect)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::GetClipBox(machine, hdc, lprect);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetDCOrgEx(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
@@ -414,8 +730,8 @@ mod wrappers {
         let hbm = <HBITMAP>::from_stack(mem, stack_args + 4u32);
         let start = <u32>::from_stack(mem, stack_args + 8u32);
         let cLines = <u32>::from_stack(mem, stack_args + 12u32);
-        let lpvBits = <Option<&mut u8>>::from_stack(mem, stack_args + 16u32);
-        let lpbmi = <Option<&mut BITMAPINFO>>::from_stack(mem, stack_args + 20u32);
+        let lpvBits = <u32>::from_stack(mem, stack_args + 16u32);
+        let lpbmi = <u32>::from_stack(mem, stack_args + 20u32);
         let usage = <u32>::from_stack(mem, stack_args + 24u32);
         let __trace_record = if crate::trace::enabled("gdi32/bitmap") {
             crate::trace::Record::new(
@@ -484,6 +800,27 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetNearestPaletteIndex(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let h = <HPALETTE>::from_stack(mem, stack_args + 0u32);
+        let color = <COLORREF>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("gdi32/palette") {
+            crate::trace::Record::new(
+                winapi::gdi32::GetNearestPaletteIndex_pos,
+                "gdi32/palette",
+                "GetNearestPaletteIndex",
+                &[("h", &h), ("color", &color)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::GetNearestPaletteIndex(machine, h, color);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetObjectA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let handle = <HGDIOBJ>::from_stack(mem, stack_args + 0u32);
@@ -511,7 +848,7 @@ mod wrappers {
         let hpal = <HPALETTE>::from_stack(mem, stack_args + 0u32);
         let iStart = <u32>::from_stack(mem, stack_args + 4u32);
         let cEntries = <u32>::from_stack(mem, stack_args + 8u32);
-        let pPalEntries = <Option<&mut PALETTEENTRY>>::from_stack(mem, stack_args + 12u32);
+        let pPalEntries = <u32>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("gdi32/palette") {
             crate::trace::Record::new(
                 winapi::gdi32::GetPaletteEntries_pos,
@@ -556,6 +893,27 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetRgnBox(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hrgn = <HRGN>::from_stack(mem, stack_args + 0u32);
+        let lprc = <Option<&mut RECT>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::GetRgnBox_pos,
+                "gdi32/region",
+                "GetRgnBox",
+                &[("hrgn", &hrgn), ("lprc", &lprc)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::GetRgnBox(machine, hrgn, lprc);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetStockObject(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let i = <Result<GetStockObjectArg, u32>>::from_stack(mem, stack_args + 0u32);
@@ -581,7 +939,7 @@ mod wrappers {
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
         let iStart = <u32>::from_stack(mem, stack_args + 4u32);
         let cEntries = <u32>::from_stack(mem, stack_args + 8u32);
-        let pPalEntries = <Option<&mut PALETTEENTRY>>::from_stack(mem, stack_args + 12u32);
+        let pPalEntries = <u32>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("gdi32/palette") {
             crate::trace::Record::new(
                 winapi::gdi32::GetSystemPaletteEntries_pos,
@@ -608,26 +966,20 @@ mod wrappers {
     pub unsafe fn GetTextExtentPoint32A(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
-        let lpString = <Option<&str>>::from_stack(mem, stack_args + 4u32);
-        let c = <i32>::from_stack(mem, stack_args + 8u32);
+        let lpString = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
         let psizl = <Option<&mut SIZE>>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("gdi32/text") {
             crate::trace::Record::new(
                 winapi::gdi32::GetTextExtentPoint32A_pos,
                 "gdi32/text",
                 "GetTextExtentPoint32A",
-                &[
-                    ("hdc", &hdc),
-                    ("lpString", &lpString),
-                    ("c", &c),
-                    ("psizl", &psizl),
-                ],
+                &[("hdc", &hdc), ("lpString", &lpString), ("psizl", &psizl)],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::gdi32::GetTextExtentPoint32A(machine, hdc, lpString, c, psizl);
+        let result = winapi::gdi32::GetTextExtentPoint32A(machine, hdc, lpString, psizl);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
@@ -636,26 +988,20 @@ mod wrappers {
     pub unsafe fn GetTextExtentPoint32W(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
-        let lpString = <Option<&str>>::from_stack(mem, stack_args + 4u32);
-        let c = <i32>::from_stack(mem, stack_args + 8u32);
+        let lpString = <ArrayWithSize<u16>>::from_stack(mem, stack_args + 4u32);
         let psizl = <Option<&mut SIZE>>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("gdi32/text") {
             crate::trace::Record::new(
                 winapi::gdi32::GetTextExtentPoint32W_pos,
                 "gdi32/text",
                 "GetTextExtentPoint32W",
-                &[
-                    ("hdc", &hdc),
-                    ("lpString", &lpString),
-                    ("c", &c),
-                    ("psizl", &psizl),
-                ],
+                &[("hdc", &hdc), ("lpString", &lpString), ("psizl", &psizl)],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::gdi32::GetTextExtentPoint32W(machine, hdc, lpString, c, psizl);
+        let result = winapi::gdi32::GetTextExtentPoint32W(machine, hdc, lpString, psizl);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
@@ -703,6 +1049,36 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IntersectClipRect(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
+        let left = <i32>::from_stack(mem, stack_args + 4u32);
+        let top = <i32>::from_stack(mem, stack_args + 8u32);
+        let right = <i32>::from_stack(mem, stack_args + 12u32);
+        let bottom = <i32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::IntersectClipRect_pos,
+                "gdi32/region",
+                "IntersectClipRect",
+                &[
+                    ("hdc", &hdc),
+                    ("left", &left),
+                    ("top", &top),
+                    ("right", &right),
+                    ("bottom", &bottom),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::IntersectClipRect(machine, hdc, left, top, right, bottom);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn LineDDA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let xStart = <i32>::from_stack(mem, stack_args + 0u32);
@@ -780,6 +1156,28 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn OffsetRgn(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hrgn = <HRGN>::from_stack(mem, stack_args + 0u32);
+        let x = <i32>::from_stack(mem, stack_args + 4u32);
+        let y = <i32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::OffsetRgn_pos,
+                "gdi32/region",
+                "OffsetRgn",
+                &[("hrgn", &hrgn), ("x", &x), ("y", &y)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::OffsetRgn(machine, hrgn, x, y);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn PatBlt(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
@@ -787,7 +1185,7 @@ mod wrappers {
         let y = <i32>::from_stack(mem, stack_args + 8u32);
         let w = <i32>::from_stack(mem, stack_args + 12u32);
         let h = <i32>::from_stack(mem, stack_args + 16u32);
-        let rop = <Result<RasterOp, u32>>::from_stack(mem, stack_args + 20u32);
+        let rop = <RasterOp>::from_stack(mem, stack_args + 20u32);
         let __trace_record = if crate::trace::enabled("gdi32/bitmap") {
             crate::trace::Record::new(
                 winapi::gdi32::PatBlt_pos,
@@ -812,15 +1210,37 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn PtInRegion(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hrgn = <HRGN>::from_stack(mem, stack_args + 0u32);
+        let x = <i32>::from_stack(mem, stack_args + 4u32);
+        let y = <i32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::PtInRegion_pos,
+                "gdi32/region",
+                "PtInRegion",
+                &[("hrgn", &hrgn), ("x", &x), ("y", &y)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::PtInRegion(machine, hrgn, x, y);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn PtVisible(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
         let x = <i32>::from_stack(mem, stack_args + 4u32);
         let y = <i32>::from_stack(mem, stack_args + 8u32);
-        let __trace_record = if crate::trace::enabled("gdi32/draw") {
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
             crate::trace::Record::new(
                 winapi::gdi32::PtVisible_pos,
-                "gdi32/draw",
+                "gdi32/region",
                 "PtVisible",
                 &[("hdc", &hdc), ("x", &x), ("y", &y)],
             )
@@ -854,6 +1274,27 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn SelectClipRgn(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
+        let hrgn = <HRGN>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::SelectClipRgn_pos,
+                "gdi32/region",
+                "SelectClipRgn",
+                &[("hdc", &hdc), ("hrgn", &hrgn)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::SelectClipRgn(machine, hdc, hrgn);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn SelectObject(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
@@ -921,7 +1362,7 @@ mod wrappers {
     pub unsafe fn SetBkMode(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
-        let mode = <i32>::from_stack(mem, stack_args + 4u32);
+        let mode = <Result<BkMode, u32>>::from_stack(mem, stack_args + 4u32);
         let __trace_record = if crate::trace::enabled("gdi32/draw") {
             crate::trace::Record::new(
                 winapi::gdi32::SetBkMode_pos,
@@ -1034,7 +1475,7 @@ mod wrappers {
         let hpal = <HPALETTE>::from_stack(mem, stack_args + 0u32);
         let iStart = <u32>::from_stack(mem, stack_args + 4u32);
         let cEntries = <u32>::from_stack(mem, stack_args + 8u32);
-        let pPalEntries = <Option<&mut PALETTEENTRY>>::from_stack(mem, stack_args + 12u32);
+        let pPalEntries = <u32>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("gdi32/palette") {
             crate::trace::Record::new(
                 winapi::gdi32::SetPaletteEntries_pos,
@@ -1060,8 +1501,8 @@ mod wrappers {
     pub unsafe fn SetPixel(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
-        let x = <u32>::from_stack(mem, stack_args + 4u32);
-        let y = <u32>::from_stack(mem, stack_args + 8u32);
+        let x = <i32>::from_stack(mem, stack_args + 4u32);
+        let y = <i32>::from_stack(mem, stack_args + 8u32);
         let color = <COLORREF>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("gdi32/draw") {
             crate::trace::Record::new(
@@ -1101,6 +1542,57 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn SetRectRgn(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hrgn = <HRGN>::from_stack(mem, stack_args + 0u32);
+        let left = <i32>::from_stack(mem, stack_args + 4u32);
+        let top = <i32>::from_stack(mem, stack_args + 8u32);
+        let right = <i32>::from_stack(mem, stack_args + 12u32);
+        let bottom = <i32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("gdi32/region") {
+            crate::trace::Record::new(
+                winapi::gdi32::SetRectRgn_pos,
+                "gdi32/region",
+                "SetRectRgn",
+                &[
+                    ("hrgn", &hrgn),
+                    ("left", &left),
+                    ("top", &top),
+                    ("right", &right),
+                    ("bottom", &bottom),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::SetRectRgn(machine, hrgn, left, top, right, bottom);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn SetStretchBltMode(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
+        let mode = <Result<StretchMode, u32>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("gdi32/bitmap") {
+            crate::trace::Record::new(
+                winapi::gdi32::SetStretchBltMode_pos,
+                "gdi32/bitmap",
+                "SetStretchBltMode",
+                &[("hdc", &hdc), ("mode", &mode)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::gdi32::SetStretchBltMode(machine, hdc, mode);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn SetTextAlign(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
@@ -1155,7 +1647,7 @@ mod wrappers {
         let ySrc = <i32>::from_stack(mem, stack_args + 28u32);
         let wSrc = <i32>::from_stack(mem, stack_args + 32u32);
         let hSrc = <i32>::from_stack(mem, stack_args + 36u32);
-        let rop = <Result<RasterOp, u32>>::from_stack(mem, stack_args + 40u32);
+        let rop = <RasterOp>::from_stack(mem, stack_args + 40u32);
         let __trace_record = if crate::trace::enabled("gdi32/bitmap") {
             crate::trace::Record::new(
                 winapi::gdi32::StretchBlt_pos,
@@ -1201,7 +1693,7 @@ mod wrappers {
         let lpBits = <u32>::from_stack(mem, stack_args + 36u32);
         let lpBmi = <u32>::from_stack(mem, stack_args + 40u32);
         let iUsage = <u32>::from_stack(mem, stack_args + 44u32);
-        let rop = <Result<RasterOp, u32>>::from_stack(mem, stack_args + 48u32);
+        let rop = <RasterOp>::from_stack(mem, stack_args + 48u32);
         let __trace_record = if crate::trace::enabled("gdi32/bitmap") {
             crate::trace::Record::new(
                 winapi::gdi32::StretchDIBits_pos,
@@ -1239,8 +1731,8 @@ mod wrappers {
     pub unsafe fn TextOutA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
-        let x = <u32>::from_stack(mem, stack_args + 4u32);
-        let y = <u32>::from_stack(mem, stack_args + 8u32);
+        let x = <i32>::from_stack(mem, stack_args + 4u32);
+        let y = <i32>::from_stack(mem, stack_args + 8u32);
         let lpString = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("gdi32/text") {
             crate::trace::Record::new(
@@ -1262,8 +1754,8 @@ mod wrappers {
     pub unsafe fn TextOutW(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
-        let x = <u32>::from_stack(mem, stack_args + 4u32);
-        let y = <u32>::from_stack(mem, stack_args + 8u32);
+        let x = <i32>::from_stack(mem, stack_args + 4u32);
+        let y = <i32>::from_stack(mem, stack_args + 8u32);
         let lpString = <ArrayWithSize<u16>>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("gdi32/text") {
             crate::trace::Record::new(
@@ -1283,11 +1775,19 @@ mod wrappers {
         result.into_abireturn()
     }
 }
-const SHIMS: [Shim; 48usize] = [
+const SHIMS: [Shim; 68usize] = [
+    Shim {
+        name: "AnimatePalette",
+        func: Handler::Sync(wrappers::AnimatePalette),
+    },
     Shim {
         name: "BitBlt",
         func: Handler::Sync(wrappers::BitBlt),
     },
+    Shim {
+        name: "CombineRgn",
+        func: Handler::Sync(wrappers::CombineRgn),
+    },
     Shim {
         name: "CreateBitmap",
         func: Handler::Sync(wrappers::CreateBitmap),
@@ -1312,14 +1812,42 @@ const SHIMS: [Shim; 48usize] = [
         name: "CreateFontA",
         func: Handler::Sync(wrappers::CreateFontA),
     },
+    Shim {
+        name: "CreateFontIndirectA",
+        func: Handler::Sync(wrappers::CreateFontIndirectA),
+    },
+    Shim {
+        name: "CreateFontIndirectW",
+        func: Handler::Sync(wrappers::CreateFontIndirectW),
+    },
+    Shim {
+        name: "CreateFontW",
+        func: Handler::Sync(wrappers::CreateFontW),
+    },
+    Shim {
+        name: "CreateHatchBrush",
+        func: Handler::Sync(wrappers::CreateHatchBrush),
+    },
     Shim {
         name: "CreatePalette",
         func: Handler::Sync(wrappers::CreatePalette),
     },
+    Shim {
+        name: "CreatePatternBrush",
+        func: Handler::Sync(wrappers::CreatePatternBrush),
+    },
     Shim {
         name: "CreatePen",
         func: Handler::Sync(wrappers::CreatePen),
     },
+    Shim {
+        name: "CreateRectRgn",
+        func: Handler::Sync(wrappers::CreateRectRgn),
+    },
+    Shim {
+        name: "CreateRectRgnIndirect",
+        func: Handler::Sync(wrappers::CreateRectRgnIndirect),
+    },
     Shim {
         name: "CreateSolidBrush",
         func: Handler::Sync(wrappers::CreateSolidBrush),
@@ -1336,6 +1864,18 @@ const SHIMS: [Shim; 48usize] = [
         name: "EnumFontFamiliesExA",
         func: Handler::Sync(wrappers::EnumFontFamiliesExA),
     },
+    Shim {
+        name: "EqualRgn",
+        func: Handler::Sync(wrappers::EqualRgn),
+    },
+    Shim {
+        name: "ExcludeClipRect",
+        func: Handler::Sync(wrappers::ExcludeClipRect),
+    },
+    Shim {
+        name: "GetClipBox",
+        func: Handler::Sync(wrappers::GetClipBox),
+    },
     Shim {
         name: "GetDCOrgEx",
         func: Handler::Sync(wrappers::GetDCOrgEx),
@@ -1352,6 +1892,10 @@ const SHIMS: [Shim; 48usize] = [
         name: "GetLayout",
         func: Handler::Sync(wrappers::GetLayout),
     },
+    Shim {
+        name: "GetNearestPaletteIndex",
+        func: Handler::Sync(wrappers::GetNearestPaletteIndex),
+    },
     Shim {
         name: "GetObjectA",
         func: Handler::Sync(wrappers::GetObjectA),
@@ -1364,6 +1908,10 @@ const SHIMS: [Shim; 48usize] = [
         name: "GetPixel",
         func: Handler::Sync(wrappers::GetPixel),
     },
+    Shim {
+        name: "GetRgnBox",
+        func: Handler::Sync(wrappers::GetRgnBox),
+    },
     Shim {
         name: "GetStockObject",
         func: Handler::Sync(wrappers::GetStockObject),
@@ -1388,6 +1936,10 @@ const SHIMS: [Shim; 48usize] = [
         name: "GetTextMetricsW",
         func: Handler::Sync(wrappers::GetTextMetricsW),
     },
+    Shim {
+        name: "IntersectClipRect",
+        func: Handler::Sync(wrappers::IntersectClipRect),
+    },
     Shim {
         name: "LineDDA",
         func: Handler::Sync(wrappers::LineDDA),
@@ -1400,10 +1952,18 @@ const SHIMS: [Shim; 48usize] = [
         name: "MoveToEx",
         func: Handler::Sync(wrappers::MoveToEx),
     },
+    Shim {
+        name: "OffsetRgn",
+        func: Handler::Sync(wrappers::OffsetRgn),
+    },
     Shim {
         name: "PatBlt",
         func: Handler::Sync(wrappers::PatBlt),
     },
+    Shim {
+        name: "PtInRegion",
+        func: Handler::Sync(wrappers::PtInRegion),
+    },
     Shim {
         name: "PtVisible",
         func: Handler::Sync(wrappers::PtVisible),
@@ -1412,6 +1972,10 @@ const SHIMS: [Shim; 48usize] = [
         name: "RealizePalette",
         func: Handler::Sync(wrappers::RealizePalette),
     },
+    Shim {
+        name: "SelectClipRgn",
+        func: Handler::Sync(wrappers::SelectClipRgn),
+    },
     Shim {
         name: "SelectObject",
         func: Handler::Sync(wrappers::SelectObject),
@@ -1452,6 +2016,14 @@ const SHIMS: [Shim; 48usize] = [
         name: "SetROP2",
         func: Handler::Sync(wrappers::SetROP2),
     },
+    Shim {
+        name: "SetRectRgn",
+        func: Handler::Sync(wrappers::SetRectRgn),
+    },
+    Shim {
+        name: "SetStretchBltMode",
+        func: Handler::Sync(wrappers::SetStretchBltMode),
+    },
     Shim {
         name: "SetTextAlign",
         func: Handler::Sync(wrappers::SetTextAlign),
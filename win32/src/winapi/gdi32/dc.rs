@@ -1,10 +1,10 @@
-use super::{HGDIOBJ, R2};
+use super::{BkMode, Region, StretchMode, COLORREF, HGDIOBJ, R2};
 use crate::{
     machine::Machine,
     winapi::{
         bitmap::{Bitmap, PixelData, PixelFormat},
-        types::{HANDLE, POINT},
-        user32::Window,
+        types::{HANDLE, HWND, POINT},
+        user32::{self, Window},
     },
 };
 use std::{cell::RefCell, rc::Rc};
@@ -31,7 +31,9 @@ impl DCTarget {
             }
             DCTarget::DirectDrawSurface(addr) => {
                 let surface = machine.state.ddraw.surfaces.get(&addr).unwrap();
-                Rc::new(RefCell::new(surface.to_bitmap()))
+                Rc::new(RefCell::new(
+                    surface.to_bitmap(&machine.state.gdi32.system_palette),
+                ))
             }
             _ => todo!(),
         }
@@ -40,8 +42,8 @@ impl DCTarget {
     pub fn flush(&self, machine: &Machine) {
         match self {
             DCTarget::Window(window) => {
-                let mut window = window.borrow_mut();
-                window.flush_backing_store(machine.emu.memory.mem());
+                let hwnd = HWND::from_raw(window.borrow().id);
+                user32::flush_window(machine, hwnd);
             }
             DCTarget::DirectDrawSurface(addr) => {
                 let surface = machine.state.ddraw.surfaces.get(addr).unwrap();
@@ -62,12 +64,26 @@ pub struct DC {
     pub rop2: R2,
     pub pos: POINT,
 
+    pub text_color: COLORREF,
+    pub bk_color: COLORREF,
+    pub bk_mode: BkMode,
+    /// TA_* flags, as set by SetTextAlign.
+    pub text_align: u32,
+    pub brush_org: POINT,
+    pub stretch_mode: StretchMode,
+    /// Clip region set by SelectClipRgn etc., in device coordinates.
+    pub clip: Option<Region>,
+    /// The area a window DC may draw in, e.g. the update region within BeginPaint.
+    pub visible: Option<Region>,
+
     // The SelectObject() API sets a drawing-related field on the DC and returns the
     // previously selected object of a given type, which means we need a storage field
     // per object type.
     pub bitmap: HGDIOBJ,
     pub brush: HGDIOBJ,
     pub pen: HGDIOBJ,
+    pub font: HGDIOBJ,
+    pub palette: HGDIOBJ,
 }
 
 impl DC {
@@ -76,9 +92,19 @@ impl DC {
             target,
             rop2: R2::default(),
             pos: Default::default(),
+            text_color: COLORREF::from_rgb(0, 0, 0),
+            bk_color: COLORREF::white(),
+            bk_mode: BkMode::OPAQUE,
+            text_align: 0,
+            brush_org: Default::default(),
+            stretch_mode: StretchMode::default(),
+            clip: None,
+            visible: None,
             bitmap: Default::default(),
             brush: Default::default(),
             pen: Default::default(),
+            font: Default::default(),
+            palette: Default::default(),
         }
     }
 
@@ -89,6 +115,8 @@ impl DC {
             width: 1,
             height: 1,
             format: PixelFormat::Mono,
+            stride: 2,
+            bottom_up: false,
             pixels: PixelData::Ptr(0, 0),
         };
         let hobj = machine.state.gdi32.objects.add_bitmap(bitmap);
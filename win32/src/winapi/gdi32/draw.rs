@@ -1,9 +1,12 @@
 //! Pens, brushes, color.
 
-use super::{DCTarget, Object, HDC, HGDIOBJ};
+use super::{bitmap::blit_with_brush, DCTarget, Object, RasterOp, DC, HDC, HGDIOBJ};
 use crate::{
     machine::Machine,
-    winapi::types::{POINT, RECT},
+    winapi::{
+        bitmap::PixelFormat,
+        types::{POINT, RECT},
+    },
 };
 
 /// COLORREF is a u32 containing RGB0, modeled specially here because there is the
@@ -62,16 +65,82 @@ pub struct Pen {
 #[derive(Debug)]
 pub struct Brush {
     pub color: Option<COLORREF>,
+    /// For hatch and pattern brushes, an 8x8 pattern drawn instead of the plain color.
+    pub pattern: Option<Pattern>,
+}
+
+#[derive(Debug)]
+pub enum Pattern {
+    /// Set bits are drawn in the brush color and clear bits in the DC background color,
+    /// or not at all in transparent background mode.
+    Hatch([u8; 8]),
+    /// From a monochrome bitmap: clear bits are drawn in the DC text color and set bits
+    /// in the DC background color.
+    Mono([u8; 8]),
+    Color(Box<[COLORREF; 64]>),
+}
+
+impl Brush {
+    pub fn new(color: Option<COLORREF>) -> Self {
+        Brush {
+            color,
+            pattern: None,
+        }
+    }
+
+    /// The brush color at a given point in a DC, or None where the brush leaves the
+    /// destination unchanged.
+    pub fn pixel(&self, dc: &DC, x: i32, y: i32) -> Option<COLORREF> {
+        let Some(pattern) = &self.pattern else {
+            return self.color;
+        };
+        let px = (x - dc.brush_org.x).rem_euclid(8) as usize;
+        let py = (y - dc.brush_org.y).rem_euclid(8) as usize;
+        let bit = |rows: &[u8; 8]| rows[py] & (0x80 >> px) != 0;
+        match pattern {
+            Pattern::Hatch(rows) => {
+                if bit(rows) {
+                    self.color
+                } else if dc.bk_mode == BkMode::OPAQUE {
+                    Some(dc.bk_color)
+                } else {
+                    None
+                }
+            }
+            Pattern::Mono(rows) => Some(if bit(rows) {
+                dc.bk_color
+            } else {
+                dc.text_color
+            }),
+            Pattern::Color(pixels) => Some(pixels[py * 8 + px]),
+        }
+    }
+}
+
+/// Background mode, as found in SetBkMode: whether text and hatches fill their background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, win32_derive::TryFromEnum)]
+pub enum BkMode {
+    TRANSPARENT = 1,
+    OPAQUE = 2,
 }
 
 #[win32_derive::dllexport]
-pub fn SetBkMode(_machine: &mut Machine, hdc: HDC, mode: i32) -> i32 {
-    0 // fail
+pub fn SetBkMode(machine: &mut Machine, hdc: HDC, mode: Result<BkMode, u32>) -> i32 {
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return 0; // fail
+    };
+    let Ok(mode) = mode else {
+        return 0; // fail
+    };
+    std::mem::replace(&mut dc.borrow_mut().bk_mode, mode) as i32
 }
 
 #[win32_derive::dllexport]
-pub fn SetBkColor(_machine: &mut Machine, hdc: HDC, color: COLORREF) -> COLORREF {
-    CLR_INVALID // fail
+pub fn SetBkColor(machine: &mut Machine, hdc: HDC, color: COLORREF) -> COLORREF {
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return CLR_INVALID; // fail
+    };
+    std::mem::replace(&mut dc.borrow_mut().bk_color, color)
 }
 
 #[derive(Debug, win32_derive::TryFromEnum)]
@@ -134,21 +203,27 @@ pub fn LineTo(machine: &mut Machine, hdc: HDC, x: i32, y: i32) -> bool {
     let bitmap = window.bitmap().clone();
     drop(window);
     let mut bitmap = bitmap.borrow_mut();
+    let region = dc.clip_region(bitmap.to_rect());
     let stride = bitmap.width;
     let pixels = bitmap.as_rgba_mut(machine.emu.memory.mem());
+    let mut plot = |x: u32, y: u32| {
+        if region.contains(x as i32, y as i32) {
+            pixels[((y * stride) + x) as usize] = color;
+        }
+    };
     let (dstX, dstY) = (x, y);
     if dstX == dc.pos.x {
         let x = x.max(0) as u32;
         let (y0, y1) = ascending(dstY, dc.pos.y);
         for y in y0..=y1 {
-            pixels[((y * stride) + x) as usize] = color;
+            plot(x, y);
         }
         dc.pos.y = dstY;
     } else if dstY == dc.pos.y {
         let (x0, x1) = ascending(dstX, dc.pos.x);
         let y = y.max(0) as u32;
         for x in x0..=x1 {
-            pixels[((y * stride) + x) as usize] = color;
+            plot(x, y);
         }
         dc.pos.x = dstX;
     } else {
@@ -171,39 +246,32 @@ pub fn SetROP2(machine: &mut Machine, hdc: HDC, rop2: Result<R2, u32>) -> u32 {
     std::mem::replace(&mut dc.rop2, rop2.unwrap()) as u32
 }
 
-pub fn fill_rect(machine: &mut Machine, hdc: HDC, _rect: &RECT, color: COLORREF) {
+pub fn fill_rect(machine: &mut Machine, hdc: HDC, rect: &RECT, color: COLORREF) {
     let dc = machine.state.gdi32.dcs.get(hdc).unwrap().borrow();
-    match &dc.target {
-        DCTarget::Window(window) => {
-            let window = window.borrow();
-            // TODO: obey rect
-            let mut bitmap = window.bitmap().borrow_mut();
-            let pixels = bitmap.as_rgba_mut(machine.emu.memory.mem());
-            pixels.fill(color.to_pixel());
-        }
-        _ => todo!(),
+    if let DCTarget::DesktopWindow = dc.target {
+        return;
     }
+    let brush = Brush::new(Some(color));
+    blit_with_brush(machine, &dc, *rect, None, &brush, RasterOp::PATCOPY);
     dc.target.clone().flush(machine);
 }
 
 #[win32_derive::dllexport]
-pub fn SetPixel(machine: &mut Machine, hdc: HDC, x: u32, y: u32, color: COLORREF) -> COLORREF {
+pub fn SetPixel(machine: &mut Machine, hdc: HDC, x: i32, y: i32, color: COLORREF) -> COLORREF {
     let dc = machine.state.gdi32.dcs.get(hdc).unwrap().borrow();
-    match &dc.target {
-        DCTarget::Window(window) => {
-            let window = window.borrow();
-            if x >= window.width || y >= window.height {
-                return CLR_INVALID;
-            }
-            let stride = window.width;
-            let mut bitmap = window.bitmap().borrow_mut();
-            let pixels = bitmap.as_rgba_mut(machine.emu.memory.mem());
-            pixels[((y * stride) + x) as usize] = color.to_pixel();
-        }
-        _ => {
-            log::warn!("TODO: SetPixel unimplemented");
-        }
+    if let DCTarget::DesktopWindow = dc.target {
+        log::warn!("TODO: SetPixel on desktop unimplemented");
+        return color;
+    }
+    let bitmap = dc.target.get_bitmap(machine);
+    let region = dc.clip_region(bitmap.borrow().to_rect());
+    if !region.contains(x, y) {
+        return CLR_INVALID;
     }
+    let (x, y) = (x as u32, y as u32);
+    bitmap
+        .borrow_mut()
+        .write_row(machine.emu.memory.mem(), x..x + 1, y, &[color]);
 
     // TODO: don't need to flush whole window for just one pixel
     dc.target.clone().flush(machine);
@@ -214,20 +282,18 @@ pub fn SetPixel(machine: &mut Machine, hdc: HDC, x: u32, y: u32, color: COLORREF
 #[win32_derive::dllexport]
 pub fn GetPixel(machine: &mut Machine, hdc: HDC, x: u32, y: u32) -> COLORREF {
     let dc = machine.state.gdi32.dcs.get(hdc).unwrap().borrow();
-    match &dc.target {
-        DCTarget::Window(window) => {
-            let window = window.borrow();
-            let stride = window.width;
-            let bitmap = window.bitmap().borrow();
-            let pixels = bitmap.as_rgba(machine.emu.memory.mem());
-            let pixel = pixels[((y * stride) + x) as usize];
-            COLORREF::from_rgb(pixel[0], pixel[1], pixel[2])
-        }
-        _ => {
-            // TODO: actually read
-            COLORREF::from_rgb(0, 0, 0)
-        }
+    if let DCTarget::DesktopWindow = dc.target {
+        // TODO: actually read
+        return COLORREF::from_rgb(0, 0, 0);
+    }
+    let bitmap = dc.target.get_bitmap(machine);
+    let bitmap = bitmap.borrow();
+    if x >= bitmap.width || y >= bitmap.height {
+        return CLR_INVALID;
     }
+    let mut pixel = [CLR_INVALID];
+    bitmap.read_row(machine.emu.memory.mem(), x..x + 1, y, &mut pixel);
+    pixel[0]
 }
 
 #[win32_derive::dllexport]
@@ -236,7 +302,75 @@ pub fn CreateSolidBrush(machine: &mut Machine, color: COLORREF) -> HGDIOBJ {
         .state
         .gdi32
         .objects
-        .add(Object::Brush(Brush { color: Some(color) }))
+        .add(Object::Brush(Brush::new(Some(color))))
+}
+
+#[derive(Debug, win32_derive::TryFromEnum)]
+pub enum HS {
+    HORIZONTAL = 0,
+    VERTICAL = 1,
+    FDIAGONAL = 2,
+    BDIAGONAL = 3,
+    CROSS = 4,
+    DIAGCROSS = 5,
+}
+
+#[win32_derive::dllexport]
+pub fn CreateHatchBrush(
+    machine: &mut Machine,
+    iHatch: Result<HS, u32>,
+    color: COLORREF,
+) -> HGDIOBJ {
+    let Ok(hatch) = iHatch else {
+        return HGDIOBJ::null();
+    };
+    let rows = match hatch {
+        HS::HORIZONTAL => [0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00, 0x00],
+        HS::VERTICAL => [0x08; 8],
+        HS::FDIAGONAL => [0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x01],
+        HS::BDIAGONAL => [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80],
+        HS::CROSS => [0x08, 0x08, 0x08, 0xff, 0x08, 0x08, 0x08, 0x08],
+        HS::DIAGCROSS => [0x81, 0x42, 0x24, 0x18, 0x18, 0x24, 0x42, 0x81],
+    };
+    machine.state.gdi32.objects.add(Object::Brush(Brush {
+        color: Some(color),
+        pattern: Some(Pattern::Hatch(rows)),
+    }))
+}
+
+#[win32_derive::dllexport]
+pub fn CreatePatternBrush(machine: &mut Machine, hbm: HGDIOBJ) -> HGDIOBJ {
+    let Some(bitmap) = machine.state.gdi32.objects.get_bitmap(hbm) else {
+        return HGDIOBJ::null();
+    };
+    let bitmap = bitmap.borrow();
+    let mem = machine.emu.memory.mem();
+    // Patterns are 8x8; smaller bitmaps are tiled.
+    let mut pixels = Box::new([COLORREF::white(); 64]);
+    let mut row = vec![COLORREF::white(); bitmap.width as usize];
+    for y in 0..8 {
+        bitmap.read_row(mem, 0..bitmap.width, y % bitmap.height, &mut row);
+        for x in 0..8 {
+            pixels[(y * 8 + x) as usize] = row[(x % bitmap.width) as usize];
+        }
+    }
+    let pattern = match bitmap.format {
+        PixelFormat::Mono => {
+            let mut rows = [0u8; 8];
+            for (i, &pixel) in pixels.iter().enumerate() {
+                if pixel == COLORREF::white() {
+                    rows[i / 8] |= 0x80 >> (i % 8);
+                }
+            }
+            Pattern::Mono(rows)
+        }
+        _ => Pattern::Color(pixels),
+    };
+    drop(bitmap);
+    machine.state.gdi32.objects.add(Object::Brush(Brush {
+        color: None,
+        pattern: Some(pattern),
+    }))
 }
 
 #[win32_derive::dllexport]
@@ -247,12 +381,14 @@ pub fn SetBrushOrgEx(
     y: i32,
     lppt: Option<&mut POINT>,
 ) -> bool {
-    true // stub
-}
-
-#[win32_derive::dllexport]
-pub fn PtVisible(_machine: &mut Machine, hdc: HDC, x: i32, y: i32) -> bool {
-    true // stub
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return false;
+    };
+    let prev = std::mem::replace(&mut dc.borrow_mut().brush_org, POINT { x, y });
+    if let Some(pt) = lppt {
+        *pt = prev;
+    }
+    true
 }
 
 #[win32_derive::dllexport]
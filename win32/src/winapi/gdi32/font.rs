@@ -0,0 +1,425 @@
+//! Bitmap font engine, standing in for the .fon files of a real Windows install.
+//!
+//! All faces are derived from a single embedded 8x8 glyph set (the public domain
+//! font8x8 "basic" set): the proportional faces trim each glyph to its inked columns,
+//! the tall faces double up glyph rows, and bold is synthesized by smearing each row
+//! one pixel to the right.  Larger requested sizes are integer multiples of a face.
+
+/// A bitmap typeface, as selectable by name in a LOGFONT.
+#[derive(Debug)]
+pub struct Face {
+    pub name: &'static str,
+    /// Cell height in pixels, before scaling.
+    height: u32,
+    /// Blank rows above the glyph data, reported as tmInternalLeading.
+    leading: u32,
+    /// Each glyph row is drawn this many times.
+    row_repeat: u32,
+    pub fixed: bool,
+    bold: bool,
+    pub charset: u8,
+}
+
+pub const ANSI_CHARSET: u8 = 0;
+pub const OEM_CHARSET: u8 = 255;
+
+pub const SYSTEM: Face = Face {
+    name: "System",
+    height: 16,
+    leading: 0,
+    row_repeat: 2,
+    fixed: false,
+    bold: true,
+    charset: ANSI_CHARSET,
+};
+
+pub const SANS_SERIF: Face = Face {
+    name: "MS Sans Serif",
+    height: 13,
+    leading: 4,
+    row_repeat: 1,
+    fixed: false,
+    bold: false,
+    charset: ANSI_CHARSET,
+};
+
+pub const FIXEDSYS: Face = Face {
+    name: "Fixedsys",
+    height: 16,
+    leading: 0,
+    row_repeat: 2,
+    fixed: true,
+    bold: false,
+    charset: ANSI_CHARSET,
+};
+
+pub const TERMINAL: Face = Face {
+    name: "Terminal",
+    height: 12,
+    leading: 2,
+    row_repeat: 1,
+    fixed: true,
+    bold: false,
+    charset: OEM_CHARSET,
+};
+
+/// Find the face to use for a LOGFONT's face name, falling back on its pitch and charset.
+pub fn find_face(name: &str, charset: u8, pitch_and_family: u8) -> &'static Face {
+    const FIXED_PITCH: u8 = 1;
+    const FF_MODERN: u8 = 0x30;
+    match name.to_ascii_lowercase().as_str() {
+        "system" => &SYSTEM,
+        "fixedsys" | "courier" | "courier new" | "lucida console" => &FIXEDSYS,
+        "terminal" => &TERMINAL,
+        "ms sans serif" | "ms shell dlg" | "ms shell dlg 2" | "helv" | "arial" | "tahoma" => {
+            &SANS_SERIF
+        }
+        _ if charset == OEM_CHARSET => &TERMINAL,
+        _ if pitch_and_family & 3 == FIXED_PITCH || pitch_and_family & 0xF0 == FF_MODERN => {
+            &FIXEDSYS
+        }
+        _ => &SANS_SERIF,
+    }
+}
+
+/// A face at a particular size and style, as referenced by an HFONT.
+#[derive(Clone, Debug)]
+pub struct Font {
+    pub face: &'static Face,
+    pub scale: u32,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikeout: bool,
+}
+
+/// Pixels covered by a run of text; true where text color should be drawn.
+pub struct TextMask {
+    pub width: u32,
+    pub height: u32,
+    pub bits: Vec<bool>,
+}
+
+impl Font {
+    pub fn new(face: &'static Face) -> Self {
+        Font {
+            face,
+            scale: 1,
+            bold: face.bold,
+            italic: false,
+            underline: false,
+            strikeout: false,
+        }
+    }
+
+    /// Pick a scale for a LOGFONT lfHeight: positive values request a cell height,
+    /// negative values a character height (cell height minus internal leading).
+    pub fn with_height(mut self, height: i32) -> Self {
+        let (want, native) = if height < 0 {
+            (-height as u32, self.face.height - self.face.leading)
+        } else {
+            (height as u32, self.face.height)
+        };
+        self.scale = if want == 0 {
+            1
+        } else {
+            ((want + native / 2) / native).max(1)
+        };
+        self
+    }
+
+    pub fn height(&self) -> u32 {
+        self.face.height * self.scale
+    }
+
+    pub fn ascent(&self) -> u32 {
+        (self.face.leading + 7 * self.face.row_repeat) * self.scale
+    }
+
+    pub fn descent(&self) -> u32 {
+        self.height() - self.ascent()
+    }
+
+    pub fn internal_leading(&self) -> u32 {
+        self.face.leading * self.scale
+    }
+
+    pub fn weight(&self) -> u32 {
+        if self.bold {
+            700
+        } else {
+            400
+        }
+    }
+
+    /// Extra width beyond the advance that italic text hangs over, as in tmOverhang.
+    pub fn overhang(&self) -> u32 {
+        if self.italic {
+            self.ascent() / 4
+        } else {
+            0
+        }
+    }
+
+    /// Glyph rows for a character, along with the column the inked part starts at
+    /// and the unscaled advance width.
+    fn glyph(&self, c: char) -> (&'static [u8; 8], u32, u32) {
+        let index = match c as u32 {
+            0x20..=0x7e => c as u32 - 0x20,
+            0xa0 => 0, // non-breaking space
+            _ => '?' as u32 - 0x20,
+        };
+        let rows = &FONT8X8[index as usize];
+        if self.face.fixed {
+            return (rows, 0, 8);
+        }
+        let ink = rows.iter().fold(0u8, |acc, &row| acc | row);
+        if ink == 0 {
+            return (rows, 0, 4);
+        }
+        let left = ink.trailing_zeros();
+        let right = 8 - ink.leading_zeros();
+        let bold = if self.bold { 1 } else { 0 };
+        (rows, left, right - left + 1 + bold)
+    }
+
+    /// Width in pixels of a single character.
+    pub fn char_width(&self, c: char) -> u32 {
+        self.glyph(c).2 * self.scale
+    }
+
+    /// Width in pixels of a run of text, as reported by GetTextExtentPoint32.
+    pub fn text_width(&self, text: &[char]) -> u32 {
+        text.iter().map(|&c| self.char_width(c)).sum()
+    }
+
+    pub fn max_char_width(&self) -> u32 {
+        (0x20u8..=0x7e)
+            .map(|c| self.char_width(c as char))
+            .max()
+            .unwrap()
+    }
+
+    /// Rasterize text, optionally underlining just the character at the given index
+    /// (for DrawText's '&' prefixes).
+    pub fn render(&self, text: &[char], underline_at: Option<usize>) -> TextMask {
+        let scale = self.scale;
+        let width = self.text_width(text) + self.overhang();
+        let height = self.height();
+        let mut bits = vec![false; (width * height) as usize];
+        let mut fill = |x: u32, y: u32, w: u32, h: u32| {
+            for y in y..(y + h).min(height) {
+                for x in x..(x + w).min(width) {
+                    bits[(y * width + x) as usize] = true;
+                }
+            }
+        };
+
+        let underline_y = (self.ascent() / scale + 1).min(self.face.height - 1) * scale;
+        let strikeout_y = (self.face.leading + 4 * self.face.row_repeat) * scale;
+        let mut x = 0;
+        for (i, &c) in text.iter().enumerate() {
+            let (rows, left, advance) = self.glyph(c);
+            for cell_y in 0..self.face.height {
+                let Some(row) = cell_y
+                    .checked_sub(self.face.leading)
+                    .map(|y| y / self.face.row_repeat)
+                    .and_then(|y| rows.get(y as usize))
+                else {
+                    continue;
+                };
+                let mut row = (*row as u16) >> left;
+                if self.bold {
+                    row |= row << 1;
+                }
+                for col in 0..16 {
+                    if row & (1 << col) != 0 {
+                        fill(x + col * scale, cell_y * scale, scale, scale);
+                    }
+                }
+            }
+            let advance = advance * scale;
+            if self.underline || underline_at == Some(i) {
+                fill(x, underline_y, advance, scale);
+            }
+            if self.strikeout {
+                fill(x, strikeout_y, advance, scale);
+            }
+            x += advance;
+        }
+
+        if self.italic {
+            // Shear rows above the baseline rightwards.
+            let ascent = self.ascent();
+            for y in 0..height {
+                let shift = (ascent.saturating_sub(y + 1) / 4) as usize;
+                if shift == 0 {
+                    continue;
+                }
+                let row = &mut bits[(y * width) as usize..][..width as usize];
+                row.rotate_right(shift);
+                row[..shift].fill(false);
+            }
+        }
+
+        TextMask {
+            width,
+            height,
+            bits,
+        }
+    }
+}
+
+/// Glyphs for U+0020 through U+007E, one byte per row, with the leftmost pixel in bit 0.
+/// Row 6 is the baseline; row 7 holds descenders.
+#[rustfmt::skip]
+const FONT8X8: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // '#'
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // '$'
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // '%'
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // '&'
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // '\''
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // '('
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // ')'
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // '*'
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ','
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // '/'
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // '0'
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // '1'
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // '2'
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // '3'
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // '4'
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // '5'
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // '6'
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // '7'
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // '8'
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ';'
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // '<'
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // '='
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // '>'
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // '?'
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // '@'
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // 'A'
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // 'B'
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // 'C'
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // 'D'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // 'E'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // 'F'
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // 'G'
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // 'H'
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'I'
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // 'J'
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // 'K'
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // 'L'
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // 'M'
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // 'N'
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // 'O'
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // 'P'
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // 'Q'
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // 'R'
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // 'S'
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'T'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // 'U'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'V'
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // 'W'
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // 'X'
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // 'Y'
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // 'Z'
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // '['
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // '\\'
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ']'
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // '_'
+    [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // 'a'
+    [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // 'b'
+    [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // 'c'
+    [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // 'd'
+    [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // 'e'
+    [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // 'f'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'g'
+    [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // 'h'
+    [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'i'
+    [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // 'j'
+    [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // 'k'
+    [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'l'
+    [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // 'm'
+    [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // 'n'
+    [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // 'o'
+    [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // 'p'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // 'q'
+    [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // 'r'
+    [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // 's'
+    [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // 't'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // 'u'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'v'
+    [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // 'w'
+    [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // 'x'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'y'
+    [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // 'z'
+    [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // '{'
+    [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // '|'
+    [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // '}'
+    [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_find_face() {
+        assert_eq!(find_face("MS Shell Dlg", 0, 0).name, "MS Sans Serif");
+        assert_eq!(find_face("COURIER", 0, 0).name, "Fixedsys");
+        assert_eq!(find_face("", OEM_CHARSET, 0).name, "Terminal");
+        assert_eq!(find_face("Mystery", 0, 1).name, "Fixedsys");
+        assert_eq!(find_face("Mystery", 0, 0).name, "MS Sans Serif");
+    }
+
+    #[test]
+    fn test_metrics() {
+        let font = Font::new(&TERMINAL);
+        assert_eq!(font.text_width(&chars("abc")), 24);
+        assert_eq!((font.ascent(), font.descent()), (9, 3));
+
+        let font = Font::new(&SANS_SERIF);
+        assert!(font.char_width('i') < font.char_width('W'));
+        assert_eq!(font.char_width(' '), 4);
+
+        let font = Font::new(&SANS_SERIF).with_height(-18);
+        assert_eq!(font.scale, 2);
+        assert_eq!(font.height(), 26);
+    }
+
+    #[test]
+    fn test_render() {
+        let font = Font::new(&TERMINAL);
+        let mask = font.render(&chars("-"), None);
+        assert_eq!((mask.width, mask.height), (8, 12));
+        // The '-' glyph is columns 0-5 of glyph row 3, which is cell row 5.
+        let row = |y: u32| &mask.bits[(y * 8) as usize..][..8];
+        assert_eq!(row(5), &[true, true, true, true, true, true, false, false]);
+        assert!(row(4).iter().all(|&b| !b));
+
+        let mask = font.render(&chars(" "), Some(0));
+        assert!(row_has_ink(&mask, font.ascent() + 1));
+    }
+
+    fn row_has_ink(mask: &TextMask, y: u32) -> bool {
+        mask.bits[(y * mask.width) as usize..][..mask.width as usize]
+            .iter()
+            .any(|&b| b)
+    }
+}
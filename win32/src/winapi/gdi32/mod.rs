@@ -8,15 +8,19 @@ pub use builtin::DLL;
 mod bitmap;
 mod dc;
 mod draw;
+pub mod font;
 mod object;
 mod palette;
+mod region;
 mod state;
 mod text;
 pub use bitmap::*;
 pub use dc::*;
 pub use draw::*;
+pub use font::Font;
 pub use object::*;
 pub use palette::*;
+pub use region::*;
 pub use state::*;
 pub use text::*;
 
@@ -1,4 +1,7 @@
-use super::{Brush, DCTarget, Pen, BITMAP, COLORREF, HDC};
+use super::{
+    default_palette_entries, font, new_palette, Brush, DCTarget, Font, Palette, Pen, Region,
+    BITMAP, COLORREF, HDC, LOGFONTA,
+};
 use crate::{
     winapi::{bitmap::Bitmap, types::HANDLE},
     Machine,
@@ -12,6 +15,9 @@ pub enum Object {
     Brush(Brush),
     Bitmap(Rc<RefCell<Bitmap>>),
     Pen(Pen),
+    Font(Font),
+    Region(Region),
+    Palette(Palette),
 }
 
 pub type HGDIOBJ = HANDLE<Object>;
@@ -50,32 +56,52 @@ pub enum GetStockObjectArg {
 pub fn GetStockObject(machine: &mut Machine, i: Result<GetStockObjectArg, u32>) -> HGDIOBJ {
     use GetStockObjectArg::*;
     let obj = match i.unwrap() {
-        WHITE_BRUSH => machine.state.gdi32.objects.add(Object::Brush(Brush {
-            color: Some(COLORREF::white()),
-        })),
-        LTGRAY_BRUSH => machine.state.gdi32.objects.add(Object::Brush(Brush {
-            color: Some(COLORREF::from_rgb(0xc0, 0xc0, 0xc0)),
-        })),
+        WHITE_BRUSH => machine
+            .state
+            .gdi32
+            .objects
+            .add(Object::Brush(Brush::new(Some(COLORREF::white())))),
+        LTGRAY_BRUSH => machine
+            .state
+            .gdi32
+            .objects
+            .add(Object::Brush(Brush::new(Some(COLORREF::from_rgb(
+                0xc0, 0xc0, 0xc0,
+            ))))),
         GRAY_BRUSH => todo!(),
-        DKGRAY_BRUSH => machine.state.gdi32.objects.add(Object::Brush(Brush {
-            color: Some(COLORREF::from_rgb(0x40, 0x40, 0x40)),
-        })),
-        BLACK_BRUSH => machine.state.gdi32.objects.add(Object::Brush(Brush {
-            color: Some(COLORREF::from_rgb(0x00, 0x00, 0x00)),
-        })),
+        DKGRAY_BRUSH => machine
+            .state
+            .gdi32
+            .objects
+            .add(Object::Brush(Brush::new(Some(COLORREF::from_rgb(
+                0x40, 0x40, 0x40,
+            ))))),
+        BLACK_BRUSH => machine
+            .state
+            .gdi32
+            .objects
+            .add(Object::Brush(Brush::new(Some(COLORREF::from_rgb(
+                0x00, 0x00, 0x00,
+            ))))),
         NULL_BRUSH => machine
             .state
             .gdi32
             .objects
-            .add(Object::Brush(Brush { color: None })),
+            .add(Object::Brush(Brush::new(None))),
         DC_BRUSH => todo!(),
 
         WHITE_PEN | BLACK_PEN | NULL_PEN | DC_PEN => HGDIOBJ::null(),
 
-        OEM_FIXED_FONT | ANSI_FIXED_FONT | ANSI_VAR_FONT | SYSTEM_FONT | DEVICE_DEFAULT_FONT
-        | SYSTEM_FIXED_FONT | DEFAULT_GUI_FONT => HGDIOBJ::null(),
+        OEM_FIXED_FONT => add_font(machine, &font::TERMINAL),
+        ANSI_FIXED_FONT | SYSTEM_FIXED_FONT => add_font(machine, &font::FIXEDSYS),
+        ANSI_VAR_FONT | DEFAULT_GUI_FONT => add_font(machine, &font::SANS_SERIF),
+        SYSTEM_FONT | DEVICE_DEFAULT_FONT => add_font(machine, &font::SYSTEM),
 
-        DEFAULT_PALETTE => todo!(),
+        DEFAULT_PALETTE => machine
+            .state
+            .gdi32
+            .objects
+            .add(Object::Palette(new_palette(default_palette_entries()))),
     };
     if obj.is_null() {
         // TODO: once all of these are implemented, null is not a possible return.
@@ -84,6 +110,14 @@ pub fn GetStockObject(machine: &mut Machine, i: Result<GetStockObjectArg, u32>)
     obj
 }
 
+fn add_font(machine: &mut Machine, face: &'static font::Face) -> HGDIOBJ {
+    machine
+        .state
+        .gdi32
+        .objects
+        .add(Object::Font(Font::new(face)))
+}
+
 #[win32_derive::dllexport]
 pub fn SelectObject(machine: &mut Machine, hdc: HDC, hGdiObj: HGDIOBJ) -> HGDIOBJ {
     let mut dc = match machine.state.gdi32.dcs.get(hdc) {
@@ -106,6 +140,13 @@ pub fn SelectObject(machine: &mut Machine, hdc: HDC, hGdiObj: HGDIOBJ) -> HGDIOB
         },
         Object::Brush(_) => std::mem::replace(&mut dc.brush, hGdiObj),
         Object::Pen(_) => std::mem::replace(&mut dc.pen, hGdiObj),
+        Object::Font(_) => std::mem::replace(&mut dc.font, hGdiObj),
+        Object::Palette(_) => HGDIOBJ::null(), // must use SelectPalette
+        Object::Region(region) => {
+            // Selecting a region sets the clip region, returning the region complexity.
+            dc.clip = Some(region.clone());
+            HGDIOBJ::from_raw(region.complexity() as u32)
+        }
     }
 }
 
@@ -136,6 +177,25 @@ pub fn GetObjectA(machine: &mut Machine, handle: HGDIOBJ, bytes: u32, out: u32)
             bytes
         }
         Object::Pen(_) => todo!(),
+        Object::Font(font) => {
+            let size = std::mem::size_of::<LOGFONTA>() as u32;
+            if bytes < size {
+                return 0; // fail
+            }
+            machine.mem().put_pod::<LOGFONTA>(out, font.to_logfont());
+            size
+        }
+        Object::Region(_) => 0,
+        Object::Palette(palette) => {
+            // A palette's object is its entry count.
+            if bytes < 2 {
+                return 0;
+            }
+            machine
+                .mem()
+                .put_pod::<u16>(out, palette.borrow().len() as u16);
+            2
+        }
     }
 }
 
@@ -1,10 +1,19 @@
-use super::{HDC, HGDIOBJ};
+//! Logical palettes and the system palette.
+//!
+//! We model a single 256-entry system palette, shared with DirectDraw: an 8bpp primary
+//! surface draws through it, so realizing or animating a logical palette is visible on
+//! the display.  Logical palette entry i is realized at system palette index i, without
+//! the collapsing onto static colors that Windows does.
+
+use super::{Object, COLORREF, GDI_ERROR, HDC, HGDIOBJ};
 use crate::Machine;
+use memory::{Extensions, ExtensionsMut};
+use std::{cell::RefCell, rc::Rc};
 
-pub type HPALETTE = u32; // TODO
+pub type HPALETTE = HGDIOBJ;
 
 #[repr(C)]
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct PALETTEENTRY {
     pub peRed: u8,
     pub peGreen: u8,
@@ -13,55 +22,292 @@ pub struct PALETTEENTRY {
 }
 unsafe impl memory::Pod for PALETTEENTRY {}
 
-#[win32_derive::dllexport]
-pub fn CreatePalette(machine: &mut Machine, plpal: u32) -> HGDIOBJ {
-    HGDIOBJ::null()
+impl PALETTEENTRY {
+    pub fn to_color(&self) -> COLORREF {
+        COLORREF::from_rgb(self.peRed, self.peGreen, self.peBlue)
+    }
+
+    pub fn from_color(color: COLORREF) -> Self {
+        let [peRed, peGreen, peBlue] = color.to_rgb();
+        PALETTEENTRY {
+            peRed,
+            peGreen,
+            peBlue,
+            peFlags: 0,
+        }
+    }
+}
+
+/// Palette entries, shared between the objects that draw through them.
+pub type Palette = Rc<RefCell<Box<[PALETTEENTRY]>>>;
+
+pub fn new_palette(entries: impl IntoIterator<Item = PALETTEENTRY>) -> Palette {
+    Rc::new(RefCell::new(entries.into_iter().collect()))
+}
+
+/// The 20 colors Windows reserves in the system palette: the first ten at the start and
+/// the last ten at the end.
+const STATIC_COLORS: [[u8; 3]; 20] = [
+    [0x00, 0x00, 0x00],
+    [0x80, 0x00, 0x00],
+    [0x00, 0x80, 0x00],
+    [0x80, 0x80, 0x00],
+    [0x00, 0x00, 0x80],
+    [0x80, 0x00, 0x80],
+    [0x00, 0x80, 0x80],
+    [0xc0, 0xc0, 0xc0],
+    [0xc0, 0xdc, 0xc0],
+    [0xa6, 0xca, 0xf0],
+    [0xff, 0xfb, 0xf0],
+    [0xa0, 0xa0, 0xa4],
+    [0x80, 0x80, 0x80],
+    [0xff, 0x00, 0x00],
+    [0x00, 0xff, 0x00],
+    [0xff, 0xff, 0x00],
+    [0x00, 0x00, 0xff],
+    [0xff, 0x00, 0xff],
+    [0x00, 0xff, 0xff],
+    [0xff, 0xff, 0xff],
+];
+
+fn static_entry(i: usize) -> PALETTEENTRY {
+    let [peRed, peGreen, peBlue] = STATIC_COLORS[i];
+    PALETTEENTRY {
+        peRed,
+        peGreen,
+        peBlue,
+        peFlags: 0,
+    }
+}
+
+/// The entries of DEFAULT_PALETTE, which holds just the static colors.
+pub fn default_palette_entries() -> impl Iterator<Item = PALETTEENTRY> {
+    (0..STATIC_COLORS.len()).map(static_entry)
+}
+
+/// The system palette at startup: static colors at either end, black in between.
+pub fn default_system_palette() -> Palette {
+    new_palette((0..256).map(|i| match i {
+        0..10 => static_entry(i),
+        246..256 => static_entry(i - 236),
+        _ => PALETTEENTRY::default(),
+    }))
+}
+
+/// Index of the palette entry closest to a color.
+pub fn nearest_index(entries: &[PALETTEENTRY], color: COLORREF) -> usize {
+    let [r, g, b] = color.to_rgb();
+    let distance = |e: &PALETTEENTRY| {
+        let dr = e.peRed as i32 - r as i32;
+        let dg = e.peGreen as i32 - g as i32;
+        let db = e.peBlue as i32 - b as i32;
+        dr * dr + dg * dg + db * db
+    };
+    entries
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, e)| distance(e))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// The logical palette selected into a DC, defaulting to DEFAULT_PALETTE.
+pub fn dc_palette(machine: &Machine, hdc: HDC) -> Palette {
+    let hpal = match machine.state.gdi32.dcs.get(hdc) {
+        Some(dc) => dc.borrow().palette,
+        None => HPALETTE::null(),
+    };
+    match machine.state.gdi32.objects.get(hpal) {
+        Some(Object::Palette(palette)) => palette.clone(),
+        _ => new_palette(default_palette_entries()),
+    }
+}
+
+fn get_palette(machine: &Machine, hpal: HPALETTE) -> Option<Palette> {
+    match machine.state.gdi32.objects.get(hpal) {
+        Some(Object::Palette(palette)) => Some(palette.clone()),
+        _ => None,
+    }
+}
+
+/// Copy entries from a realized logical palette into the system palette, and redraw
+/// anything that draws through it.
+fn update_system_palette(machine: &mut Machine, palette: &Palette, start: usize, count: usize) {
+    {
+        let src = palette.borrow();
+        let mut system = machine.state.gdi32.system_palette.borrow_mut();
+        let count = count.min(src.len().saturating_sub(start));
+        let end = (start + count).min(system.len());
+        if start < end {
+            system[start..end].copy_from_slice(&src[start..end]);
+        }
+    }
+    crate::winapi::ddraw::palette_changed(machine);
 }
 
 #[win32_derive::dllexport]
-pub fn RealizePalette(_machine: &mut Machine, hdc: HDC) -> u32 {
-    todo!()
+pub fn CreatePalette(machine: &mut Machine, plpal: u32) -> HPALETTE {
+    if plpal == 0 {
+        return HPALETTE::null();
+    }
+    // LOGPALETTE: palVersion: u16, palNumEntries: u16, palPalEntry: [PALETTEENTRY]
+    let mem = machine.mem();
+    let count = mem.get_pod::<u16>(plpal + 2) as u32;
+    let entries = mem.iter_pod::<PALETTEENTRY>(plpal + 4, count);
+    let palette = new_palette(entries);
+    machine.state.gdi32.objects.add(Object::Palette(palette))
 }
 
 #[win32_derive::dllexport]
 pub fn SelectPalette(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hdc: HDC,
     hPal: HPALETTE,
     bForceBkgd: bool,
 ) -> HPALETTE {
-    todo!()
+    if get_palette(machine, hPal).is_none() {
+        return HPALETTE::null();
+    }
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return HPALETTE::null();
+    };
+    std::mem::replace(&mut dc.borrow_mut().palette, hPal)
+}
+
+#[win32_derive::dllexport]
+pub fn RealizePalette(machine: &mut Machine, hdc: HDC) -> u32 {
+    let hpal = match machine.state.gdi32.dcs.get(hdc) {
+        Some(dc) => dc.borrow().palette,
+        None => return GDI_ERROR,
+    };
+    let Some(palette) = get_palette(machine, hpal) else {
+        return 0; // default palette, nothing to realize
+    };
+    let count = palette.borrow().len();
+    machine.state.gdi32.realized_palette = hpal;
+    update_system_palette(machine, &palette, 0, count);
+    count as u32
+}
+
+#[win32_derive::dllexport]
+pub fn AnimatePalette(
+    machine: &mut Machine,
+    hPal: HPALETTE,
+    iStartIndex: u32,
+    cEntries: u32,
+    ppe: u32,
+) -> bool {
+    let Some(palette) = get_palette(machine, hPal) else {
+        return false;
+    };
+    {
+        let mut dst = palette.borrow_mut();
+        let start = (iStartIndex as usize).min(dst.len());
+        let src = machine.mem().iter_pod::<PALETTEENTRY>(ppe, cEntries);
+        for (dst, src) in dst[start..].iter_mut().zip(src) {
+            *dst = src;
+        }
+    }
+    // Animation takes effect immediately if the palette is realized.
+    if machine.state.gdi32.realized_palette.to_raw() == hPal.to_raw() {
+        update_system_palette(machine, &palette, iStartIndex as usize, cEntries as usize);
+    }
+    true
 }
 
 #[win32_derive::dllexport]
 pub fn SetPaletteEntries(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hpal: HPALETTE,
     iStart: u32,
     cEntries: u32,
-    pPalEntries: Option<&mut PALETTEENTRY>,
+    pPalEntries: u32,
 ) -> u32 {
-    todo!()
+    let Some(palette) = get_palette(machine, hpal) else {
+        return 0;
+    };
+    let mut dst = palette.borrow_mut();
+    let start = (iStart as usize).min(dst.len());
+    let src = machine
+        .mem()
+        .iter_pod::<PALETTEENTRY>(pPalEntries, cEntries);
+    let mut count = 0;
+    for (dst, src) in dst[start..].iter_mut().zip(src) {
+        *dst = src;
+        count += 1;
+    }
+    count
+}
+
+/// Copy entries out of a palette, or return its size if there is nowhere to copy to.
+fn get_entries(machine: &Machine, palette: &Palette, start: u32, count: u32, out: u32) -> u32 {
+    let palette = palette.borrow();
+    if out == 0 {
+        return palette.len() as u32;
+    }
+    let start = (start as usize).min(palette.len());
+    let entries = &palette[start..][..(count as usize).min(palette.len() - start)];
+    let mem = machine.mem();
+    for (i, &entry) in entries.iter().enumerate() {
+        mem.put_pod::<PALETTEENTRY>(
+            out + (i * std::mem::size_of::<PALETTEENTRY>()) as u32,
+            entry,
+        );
+    }
+    entries.len() as u32
 }
 
 #[win32_derive::dllexport]
 pub fn GetPaletteEntries(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hpal: HPALETTE,
     iStart: u32,
     cEntries: u32,
-    pPalEntries: Option<&mut PALETTEENTRY>,
+    pPalEntries: u32,
 ) -> u32 {
-    todo!()
+    let Some(palette) = get_palette(machine, hpal) else {
+        return 0;
+    };
+    get_entries(machine, &palette, iStart, cEntries, pPalEntries)
 }
 
 #[win32_derive::dllexport]
 pub fn GetSystemPaletteEntries(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hdc: HDC,
     iStart: u32,
     cEntries: u32,
-    pPalEntries: Option<&mut PALETTEENTRY>,
+    pPalEntries: u32,
 ) -> u32 {
-    todo!()
+    let palette = machine.state.gdi32.system_palette.clone();
+    get_entries(machine, &palette, iStart, cEntries, pPalEntries)
+}
+
+#[win32_derive::dllexport]
+pub fn GetNearestPaletteIndex(machine: &mut Machine, h: HPALETTE, color: COLORREF) -> u32 {
+    let Some(palette) = get_palette(machine, h) else {
+        return GDI_ERROR; // CLR_INVALID
+    };
+    let entries = palette.borrow();
+    nearest_index(&entries, color) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_index() {
+        let palette = default_system_palette();
+        let entries = palette.borrow();
+        assert_eq!(nearest_index(&entries, COLORREF::white()), 255);
+        assert_eq!(
+            nearest_index(&entries, COLORREF::from_rgb(0xf0, 0x10, 0)),
+            249
+        );
+        assert_eq!(
+            nearest_index(&entries, COLORREF::from_rgb(0xc0, 0xc0, 0xc0)),
+            7
+        );
+    }
 }
@@ -0,0 +1,469 @@
+//! Regions, and clipping of DCs to them.
+
+use super::{Object, DC, HDC, HGDIOBJ};
+use crate::{
+    winapi::types::{POINT, RECT},
+    Machine,
+};
+
+pub type HRGN = HGDIOBJ;
+
+/// An area made of non-overlapping rectangles, sorted top to bottom and left to right.
+/// Rectangles are grouped into horizontal bands that share a top and bottom, with
+/// vertically adjacent identical bands merged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Region {
+    rects: Vec<RECT>,
+}
+
+/// A horizontal strip of a region, as used while building one.
+struct Band {
+    top: i32,
+    bottom: i32,
+    spans: Vec<(i32, i32)>,
+}
+
+/// Region combination modes, as in CombineRgn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, win32_derive::TryFromEnum)]
+pub enum RGN {
+    AND = 1,
+    OR = 2,
+    XOR = 3,
+    DIFF = 4,
+    COPY = 5,
+}
+
+/// Region complexity, as returned by many region functions.
+pub const RGN_ERROR: i32 = 0;
+pub const NULLREGION: i32 = 1;
+pub const SIMPLEREGION: i32 = 2;
+pub const COMPLEXREGION: i32 = 3;
+
+fn rect_is_empty(r: &RECT) -> bool {
+    r.left >= r.right || r.top >= r.bottom
+}
+
+impl Region {
+    pub fn from_rect(rect: RECT) -> Self {
+        // Windows accepts reversed coordinates in region rectangles.
+        let rect = RECT {
+            left: rect.left.min(rect.right),
+            top: rect.top.min(rect.bottom),
+            right: rect.left.max(rect.right),
+            bottom: rect.top.max(rect.bottom),
+        };
+        if rect_is_empty(&rect) {
+            return Region::default();
+        }
+        Region { rects: vec![rect] }
+    }
+
+    pub fn rects(&self) -> &[RECT] {
+        &self.rects
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// NULLREGION, SIMPLEREGION or COMPLEXREGION.
+    pub fn complexity(&self) -> i32 {
+        match self.rects.len() {
+            0 => NULLREGION,
+            1 => SIMPLEREGION,
+            _ => COMPLEXREGION,
+        }
+    }
+
+    /// Smallest rectangle containing the region; all zeros if empty.
+    pub fn bounds(&self) -> RECT {
+        let mut rects = self.rects.iter();
+        let Some(first) = rects.next() else {
+            return RECT::default();
+        };
+        rects.fold(*first, |acc, r| RECT {
+            left: acc.left.min(r.left),
+            top: acc.top.min(r.top),
+            right: acc.right.max(r.right),
+            bottom: acc.bottom.max(r.bottom),
+        })
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.rects
+            .iter()
+            .any(|r| r.left <= x && x < r.right && r.top <= y && y < r.bottom)
+    }
+
+    /// The horizontal spans (left, right) of the region in row y, left to right.
+    pub fn row_spans(&self, y: i32) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.rects
+            .iter()
+            .filter(move |r| r.top <= y && y < r.bottom)
+            .map(|r| (r.left, r.right))
+    }
+
+    pub fn offset(&mut self, dx: i32, dy: i32) {
+        for r in self.rects.iter_mut() {
+            *r = r.add(POINT { x: dx, y: dy });
+        }
+    }
+
+    pub fn intersect_rect(&self, rect: RECT) -> Region {
+        self.combine(&Region::from_rect(rect), RGN::AND)
+    }
+
+    /// Combine two regions with a boolean operation.
+    pub fn combine(&self, other: &Region, op: RGN) -> Region {
+        // Split the plane into a grid along every rectangle edge, decide each grid cell,
+        // then merge cells back into bands of rectangles.
+        let all = || self.rects.iter().chain(other.rects.iter());
+        let mut xs: Vec<i32> = all().flat_map(|r| [r.left, r.right]).collect();
+        xs.sort();
+        xs.dedup();
+        let mut ys: Vec<i32> = all().flat_map(|r| [r.top, r.bottom]).collect();
+        ys.sort();
+        ys.dedup();
+
+        let mut bands: Vec<Band> = Vec::new();
+        for row in ys.windows(2) {
+            let (top, bottom) = (row[0], row[1]);
+            let mut spans: Vec<(i32, i32)> = Vec::new();
+            for col in xs.windows(2) {
+                let (left, right) = (col[0], col[1]);
+                let a = self.contains(left, top);
+                let b = other.contains(left, top);
+                let inside = match op {
+                    RGN::AND => a && b,
+                    RGN::OR => a || b,
+                    RGN::XOR => a != b,
+                    RGN::DIFF => a && !b,
+                    RGN::COPY => a,
+                };
+                if !inside {
+                    continue;
+                }
+                match spans.last_mut() {
+                    Some(last) if last.1 == left => last.1 = right,
+                    _ => spans.push((left, right)),
+                }
+            }
+            match bands.last_mut() {
+                Some(prev) if prev.bottom == top && prev.spans == spans => prev.bottom = bottom,
+                _ => bands.push(Band { top, bottom, spans }),
+            }
+        }
+
+        let rects = bands
+            .into_iter()
+            .flat_map(|band| {
+                band.spans.into_iter().map(move |(left, right)| RECT {
+                    left,
+                    top: band.top,
+                    right,
+                    bottom: band.bottom,
+                })
+            })
+            .collect();
+        Region { rects }
+    }
+}
+
+impl DC {
+    /// The region that drawing is limited to, combining the DC's clip region with the
+    /// visible region from BeginPaint and the bounds of the target surface.
+    pub fn clip_region(&self, bounds: RECT) -> Region {
+        let mut region = Region::from_rect(bounds);
+        if let Some(clip) = &self.clip {
+            region = region.combine(clip, RGN::AND);
+        }
+        if let Some(visible) = &self.visible {
+            region = region.combine(visible, RGN::AND);
+        }
+        region
+    }
+}
+
+/// The bounds of a DC's drawing surface.
+fn dc_bounds(machine: &Machine, dc: &DC) -> RECT {
+    match dc.target {
+        super::DCTarget::DesktopWindow => RECT {
+            left: 0,
+            top: 0,
+            right: 640,
+            bottom: 480,
+        },
+        _ => dc.target.get_bitmap(machine).borrow().to_rect(),
+    }
+}
+
+/// A copy of the region referred to by a handle, if any.
+pub fn get_region(machine: &Machine, hrgn: HRGN) -> Option<Region> {
+    match machine.state.gdi32.objects.get(hrgn) {
+        Some(Object::Region(region)) => Some(region.clone()),
+        _ => None,
+    }
+}
+
+fn region_mut(machine: &mut Machine, hrgn: HRGN) -> Option<&mut Region> {
+    match machine.state.gdi32.objects.get_mut(hrgn) {
+        Some(Object::Region(region)) => Some(region),
+        _ => None,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn CreateRectRgn(machine: &mut Machine, x1: i32, y1: i32, x2: i32, y2: i32) -> HRGN {
+    let region = Region::from_rect(RECT {
+        left: x1,
+        top: y1,
+        right: x2,
+        bottom: y2,
+    });
+    machine.state.gdi32.objects.add(Object::Region(region))
+}
+
+#[win32_derive::dllexport]
+pub fn CreateRectRgnIndirect(machine: &mut Machine, lprect: Option<&RECT>) -> HRGN {
+    let Some(rect) = lprect else {
+        return HRGN::null();
+    };
+    machine
+        .state
+        .gdi32
+        .objects
+        .add(Object::Region(Region::from_rect(*rect)))
+}
+
+#[win32_derive::dllexport]
+pub fn SetRectRgn(
+    machine: &mut Machine,
+    hrgn: HRGN,
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+) -> bool {
+    let Some(region) = region_mut(machine, hrgn) else {
+        return false;
+    };
+    *region = Region::from_rect(RECT {
+        left,
+        top,
+        right,
+        bottom,
+    });
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn CombineRgn(
+    machine: &mut Machine,
+    hrgnDst: HRGN,
+    hrgnSrc1: HRGN,
+    hrgnSrc2: HRGN,
+    iMode: Result<RGN, u32>,
+) -> i32 {
+    let Ok(mode) = iMode else {
+        return RGN_ERROR;
+    };
+    let Some(src1) = get_region(machine, hrgnSrc1) else {
+        return RGN_ERROR;
+    };
+    let src2 = if mode == RGN::COPY {
+        Region::default()
+    } else {
+        match get_region(machine, hrgnSrc2) {
+            Some(region) => region,
+            None => return RGN_ERROR,
+        }
+    };
+    let Some(dst) = region_mut(machine, hrgnDst) else {
+        return RGN_ERROR;
+    };
+    *dst = src1.combine(&src2, mode);
+    dst.complexity()
+}
+
+#[win32_derive::dllexport]
+pub fn OffsetRgn(machine: &mut Machine, hrgn: HRGN, x: i32, y: i32) -> i32 {
+    let Some(region) = region_mut(machine, hrgn) else {
+        return RGN_ERROR;
+    };
+    region.offset(x, y);
+    region.complexity()
+}
+
+#[win32_derive::dllexport]
+pub fn GetRgnBox(machine: &mut Machine, hrgn: HRGN, lprc: Option<&mut RECT>) -> i32 {
+    let Some(region) = region_mut(machine, hrgn) else {
+        return RGN_ERROR;
+    };
+    if let Some(rect) = lprc {
+        *rect = region.bounds();
+    }
+    region.complexity()
+}
+
+#[win32_derive::dllexport]
+pub fn PtInRegion(machine: &mut Machine, hrgn: HRGN, x: i32, y: i32) -> bool {
+    get_region(machine, hrgn).is_some_and(|region| region.contains(x, y))
+}
+
+#[win32_derive::dllexport]
+pub fn EqualRgn(machine: &mut Machine, hrgn1: HRGN, hrgn2: HRGN) -> bool {
+    let Some(region1) = get_region(machine, hrgn1) else {
+        return false;
+    };
+    get_region(machine, hrgn2).is_some_and(|region2| region2 == region1)
+}
+
+#[win32_derive::dllexport]
+pub fn SelectClipRgn(machine: &mut Machine, hdc: HDC, hrgn: HRGN) -> i32 {
+    let region = if hrgn.is_null() {
+        None
+    } else {
+        match get_region(machine, hrgn) {
+            Some(region) => Some(region),
+            None => return RGN_ERROR,
+        }
+    };
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return RGN_ERROR;
+    };
+    let mut dc = dc.borrow_mut();
+    dc.clip = region;
+    let bounds = dc_bounds(machine, &dc);
+    dc.clip_region(bounds).complexity()
+}
+
+/// Shared implementation of IntersectClipRect and ExcludeClipRect.
+fn combine_clip(machine: &mut Machine, hdc: HDC, rect: RECT, mode: RGN) -> i32 {
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return RGN_ERROR;
+    };
+    let mut dc = dc.borrow_mut();
+    let bounds = dc_bounds(machine, &dc);
+    let clip = dc.clip.take().unwrap_or_else(|| Region::from_rect(bounds));
+    dc.clip = Some(clip.combine(&Region::from_rect(rect), mode));
+    dc.clip_region(bounds).complexity()
+}
+
+#[win32_derive::dllexport]
+pub fn IntersectClipRect(
+    machine: &mut Machine,
+    hdc: HDC,
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+) -> i32 {
+    let rect = RECT {
+        left,
+        top,
+        right,
+        bottom,
+    };
+    combine_clip(machine, hdc, rect, RGN::AND)
+}
+
+#[win32_derive::dllexport]
+pub fn ExcludeClipRect(
+    machine: &mut Machine,
+    hdc: HDC,
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+) -> i32 {
+    let rect = RECT {
+        left,
+        top,
+        right,
+        bottom,
+    };
+    combine_clip(machine, hdc, rect, RGN::DIFF)
+}
+
+#[win32_derive::dllexport]
+pub fn GetClipBox(machine: &mut Machine, hdc: HDC, lprect: Option<&mut RECT>) -> i32 {
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return RGN_ERROR;
+    };
+    let dc = dc.borrow();
+    let region = dc.clip_region(dc_bounds(machine, &dc));
+    if let Some(rect) = lprect {
+        *rect = region.bounds();
+    }
+    region.complexity()
+}
+
+#[win32_derive::dllexport]
+pub fn PtVisible(machine: &mut Machine, hdc: HDC, x: i32, y: i32) -> bool {
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return false;
+    };
+    let dc = dc.borrow();
+    dc.clip_region(dc_bounds(machine, &dc)).contains(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> RECT {
+        RECT {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    fn area(region: &Region) -> i32 {
+        region
+            .rects()
+            .iter()
+            .map(|r| (r.right - r.left) * (r.bottom - r.top))
+            .sum()
+    }
+
+    #[test]
+    fn test_combine() {
+        let a = Region::from_rect(rect(0, 0, 10, 10));
+        let b = Region::from_rect(rect(5, 5, 15, 15));
+
+        let and = a.combine(&b, RGN::AND);
+        assert_eq!(and.rects(), &[rect(5, 5, 10, 10)]);
+
+        let or = a.combine(&b, RGN::OR);
+        assert_eq!(area(&or), 175);
+        assert_eq!(or.complexity(), COMPLEXREGION);
+        assert_eq!(or.bounds(), rect(0, 0, 15, 15));
+        // Top band, middle band, bottom band.
+        assert_eq!(
+            or.rects(),
+            &[rect(0, 0, 10, 5), rect(0, 5, 15, 10), rect(5, 10, 15, 15)]
+        );
+
+        let diff = a.combine(&b, RGN::DIFF);
+        assert_eq!(area(&diff), 75);
+        assert!(diff.contains(2, 7) && !diff.contains(7, 7));
+
+        let xor = a.combine(&b, RGN::XOR);
+        assert_eq!(area(&xor), 150);
+
+        assert!(a.combine(&a, RGN::DIFF).is_empty());
+        // Merging pieces back together yields the original rectangle.
+        assert_eq!(diff.combine(&and, RGN::OR), a);
+    }
+
+    #[test]
+    fn test_row_spans() {
+        let a = Region::from_rect(rect(0, 0, 10, 10));
+        let hole = a.combine(&Region::from_rect(rect(3, 3, 6, 6)), RGN::DIFF);
+        assert_eq!(hole.row_spans(4).collect::<Vec<_>>(), &[(0, 3), (6, 10)]);
+        assert_eq!(hole.row_spans(1).collect::<Vec<_>>(), &[(0, 10)]);
+        assert_eq!(hole.row_spans(10).count(), 0);
+    }
+}
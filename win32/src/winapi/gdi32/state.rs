@@ -1,4 +1,4 @@
-use super::{DCTarget, Object, DC, HDC, HGDIOBJ};
+use super::{default_system_palette, DCTarget, Object, Palette, DC, HDC, HGDIOBJ};
 use crate::winapi::{bitmap::Bitmap, handle::Handles, user32::Window};
 use std::{cell::RefCell, rc::Rc};
 
@@ -6,6 +6,10 @@ pub struct State {
     pub dcs: Handles<HDC, Rc<RefCell<DC>>>,
     pub screen_dc: HDC,
     pub objects: Handles<HGDIOBJ, Object>,
+    /// Shared with DirectDraw; see palette.rs.
+    pub system_palette: Palette,
+    /// The logical palette most recently realized into the system palette.
+    pub realized_palette: HGDIOBJ,
 }
 
 impl Handles<HDC, Rc<RefCell<DC>>> {
@@ -36,6 +40,8 @@ impl Default for State {
             dcs,
             screen_dc,
             objects: Handles::new(HGDIOBJ::lowest_value()),
+            system_palette: default_system_palette(),
+            realized_palette: HGDIOBJ::null(),
         }
     }
 }
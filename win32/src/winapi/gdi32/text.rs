@@ -1,20 +1,114 @@
-use super::{CLR_INVALID, HDC};
+use super::{font, BkMode, DCTarget, Font, Object, CLR_INVALID, HDC, HGDIOBJ};
 use crate::{
+    str16::Str16,
     winapi::{
         calling_convention::ArrayWithSize,
         gdi32::COLORREF,
-        types::{HANDLE, LPARAM},
+        types::{LPARAM, RECT},
     },
     Machine,
 };
 use memory::Pod;
 
-pub struct FONT {}
-pub type HFONT = HANDLE<FONT>;
+pub type HFONT = HGDIOBJ;
+
+pub const LF_FACESIZE: usize = 32;
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct LOGFONTA {
+    pub lfHeight: i32,
+    pub lfWidth: i32,
+    pub lfEscapement: i32,
+    pub lfOrientation: i32,
+    pub lfWeight: u32,
+    pub lfItalic: u8,
+    pub lfUnderline: u8,
+    pub lfStrikeOut: u8,
+    pub lfCharSet: u8,
+    pub lfOutPrecision: u8,
+    pub lfClipPrecision: u8,
+    pub lfQuality: u8,
+    pub lfPitchAndFamily: u8,
+    pub lfFaceName: [u8; LF_FACESIZE],
+}
+unsafe impl memory::Pod for LOGFONTA {}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct LOGFONTW {
+    pub lfHeight: i32,
+    pub lfWidth: i32,
+    pub lfEscapement: i32,
+    pub lfOrientation: i32,
+    pub lfWeight: u32,
+    pub lfItalic: u8,
+    pub lfUnderline: u8,
+    pub lfStrikeOut: u8,
+    pub lfCharSet: u8,
+    pub lfOutPrecision: u8,
+    pub lfClipPrecision: u8,
+    pub lfQuality: u8,
+    pub lfPitchAndFamily: u8,
+    pub lfFaceName: [u16; LF_FACESIZE],
+}
+unsafe impl memory::Pod for LOGFONTW {}
+
+impl Font {
+    pub fn to_logfont(&self) -> LOGFONTA {
+        let mut lf = LOGFONTA::zeroed();
+        lf.lfHeight = self.height() as i32;
+        lf.lfWeight = self.weight();
+        lf.lfItalic = self.italic as u8;
+        lf.lfUnderline = self.underline as u8;
+        lf.lfStrikeOut = self.strikeout as u8;
+        lf.lfCharSet = self.face.charset;
+        let name = self.face.name.as_bytes();
+        lf.lfFaceName[..name.len()].copy_from_slice(name);
+        lf
+    }
+}
+
+/// Decode ANSI text for drawing.
+pub fn chars_a(text: &[u8]) -> Vec<char> {
+    text.iter().map(|&b| b as char).collect()
+}
+
+/// Decode UTF-16 text for drawing.
+pub fn chars_w(text: &[u16]) -> Vec<char> {
+    char::decode_utf16(text.iter().copied())
+        .map(|c| c.unwrap_or('?'))
+        .collect()
+}
+
+/// The fields of a LOGFONT that matter for selecting one of our fonts.
+struct FontRequest<'a> {
+    height: i32,
+    weight: u32,
+    italic: bool,
+    underline: bool,
+    strikeout: bool,
+    charset: u8,
+    pitch_and_family: u8,
+    face_name: &'a str,
+}
+
+fn create_font(machine: &mut Machine, req: FontRequest) -> HFONT {
+    const FW_SEMIBOLD: u32 = 600;
+    let face = font::find_face(req.face_name, req.charset, req.pitch_and_family);
+    let mut font = Font::new(face).with_height(req.height);
+    if req.weight != 0 {
+        font.bold = req.weight >= FW_SEMIBOLD;
+    }
+    font.italic = req.italic;
+    font.underline = req.underline;
+    font.strikeout = req.strikeout;
+    machine.state.gdi32.objects.add(Object::Font(font))
+}
 
 #[win32_derive::dllexport]
 pub fn CreateFontA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     cHeight: i32,
     cWidth: i32,
     cEscapement: i32,
@@ -30,29 +124,106 @@ pub fn CreateFontA(
     iPitchAndFamily: u32,
     pszFaceName: Option<&str>,
 ) -> HFONT {
-    HFONT::null()
+    create_font(
+        machine,
+        FontRequest {
+            height: cHeight,
+            weight: cWeight,
+            italic: bItalic != 0,
+            underline: bUnderline != 0,
+            strikeout: bStrikeOut != 0,
+            charset: iCharSet as u8,
+            pitch_and_family: iPitchAndFamily as u8,
+            face_name: pszFaceName.unwrap_or(""),
+        },
+    )
 }
 
-pub const LF_FACESIZE: usize = 32;
-#[repr(C)]
-#[derive(Debug)]
-pub struct LOGFONTA {
-    pub lfHeight: u32,
-    pub lfWidth: u32,
-    pub lfEscapement: u32,
-    pub lfOrientation: u32,
-    pub lfWeight: u32,
-    pub lfItalic: u8,
-    pub lfUnderline: u8,
-    pub lfStrikeOut: u8,
-    pub lfCharSet: u8,
-    pub lfOutPrecision: u8,
-    pub lfClipPrecision: u8,
-    pub lfQuality: u8,
-    pub lfPitchAndFamily: u8,
-    pub lfFaceName: [u8; LF_FACESIZE],
+#[win32_derive::dllexport]
+pub fn CreateFontW(
+    machine: &mut Machine,
+    cHeight: i32,
+    cWidth: i32,
+    cEscapement: i32,
+    cOrientation: i32,
+    cWeight: u32,
+    bItalic: u32,
+    bUnderline: u32,
+    bStrikeOut: u32,
+    iCharSet: u32,
+    iOutPrecision: u32,
+    iClipPrecision: u32,
+    iQuality: u32,
+    iPitchAndFamily: u32,
+    pszFaceName: Option<&Str16>,
+) -> HFONT {
+    let face_name = pszFaceName.map(|name| String::from_iter(chars_w(name.buf())));
+    create_font(
+        machine,
+        FontRequest {
+            height: cHeight,
+            weight: cWeight,
+            italic: bItalic != 0,
+            underline: bUnderline != 0,
+            strikeout: bStrikeOut != 0,
+            charset: iCharSet as u8,
+            pitch_and_family: iPitchAndFamily as u8,
+            face_name: face_name.as_deref().unwrap_or(""),
+        },
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn CreateFontIndirectA(machine: &mut Machine, lplf: Option<&LOGFONTA>) -> HFONT {
+    let Some(lf) = lplf else {
+        return HFONT::null();
+    };
+    let len = lf
+        .lfFaceName
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(LF_FACESIZE);
+    let face_name = String::from_iter(chars_a(&lf.lfFaceName[..len]));
+    create_font(
+        machine,
+        FontRequest {
+            height: lf.lfHeight,
+            weight: lf.lfWeight,
+            italic: lf.lfItalic != 0,
+            underline: lf.lfUnderline != 0,
+            strikeout: lf.lfStrikeOut != 0,
+            charset: lf.lfCharSet,
+            pitch_and_family: lf.lfPitchAndFamily,
+            face_name: &face_name,
+        },
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn CreateFontIndirectW(machine: &mut Machine, lplf: Option<&LOGFONTW>) -> HFONT {
+    let Some(lf) = lplf else {
+        return HFONT::null();
+    };
+    let len = lf
+        .lfFaceName
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(LF_FACESIZE);
+    let face_name = String::from_iter(chars_w(&lf.lfFaceName[..len]));
+    create_font(
+        machine,
+        FontRequest {
+            height: lf.lfHeight,
+            weight: lf.lfWeight,
+            italic: lf.lfItalic != 0,
+            underline: lf.lfUnderline != 0,
+            strikeout: lf.lfStrikeOut != 0,
+            charset: lf.lfCharSet,
+            pitch_and_family: lf.lfPitchAndFamily,
+            face_name: &face_name,
+        },
+    )
 }
-unsafe impl memory::Pod for LOGFONTA {}
 
 #[win32_derive::dllexport]
 pub fn EnumFontFamiliesExA(
@@ -67,37 +238,165 @@ pub fn EnumFontFamiliesExA(
     0
 }
 
+/// The font selected into a DC, which defaults to the System font.
+pub fn dc_font(machine: &Machine, hdc: HDC) -> Font {
+    let hfont = match machine.state.gdi32.dcs.get(hdc) {
+        Some(dc) => dc.borrow().font,
+        None => HFONT::null(),
+    };
+    match machine.state.gdi32.objects.get(hfont) {
+        Some(Object::Font(font)) => font.clone(),
+        _ => Font::new(&font::SYSTEM),
+    }
+}
+
+/// Draw text with its top left corner at the given point, in the DC's text and
+/// background colors.  Optionally underlines one character (for DrawText's '&' prefixes)
+/// and clips to a rectangle.
+pub fn draw_text(
+    machine: &Machine,
+    hdc: HDC,
+    x: i32,
+    y: i32,
+    text: &[char],
+    underline_at: Option<usize>,
+    clip: Option<&RECT>,
+) -> bool {
+    let font = dc_font(machine, hdc);
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return false;
+    };
+    let dc = dc.borrow();
+    if let DCTarget::DesktopWindow = dc.target {
+        log::warn!("TODO: draw text to desktop");
+        return true;
+    }
+
+    let mask = font.render(text, underline_at);
+    let opaque = dc.bk_mode == BkMode::OPAQUE;
+    let bitmap = dc.target.get_bitmap(machine);
+    let mut bitmap = bitmap.borrow_mut();
+    let mut region = dc.clip_region(bitmap.to_rect());
+    if let Some(clip) = clip {
+        region = region.intersect_rect(*clip);
+    }
+    let rect = RECT {
+        left: x,
+        top: y,
+        right: x + mask.width as i32,
+        bottom: y + mask.height as i32,
+    }
+    .clip(&region.bounds());
+
+    if rect.left < rect.right && rect.top < rect.bottom {
+        let mem = machine.emu.memory.mem();
+        let mut row = vec![dc.bk_color; (rect.right - rect.left) as usize];
+        for py in rect.top..rect.bottom {
+            let xs = rect.left as u32..rect.right as u32;
+            bitmap.read_row(mem, xs.clone(), py as u32, &mut row);
+            let mask_row =
+                &mask.bits[((py - y) as u32 * mask.width) as usize..][..mask.width as usize];
+            for (px, color) in xs.clone().zip(row.iter_mut()) {
+                let px = px as i32;
+                if !region.row_spans(py).any(|(l, r)| l <= px && px < r) {
+                    continue;
+                }
+                if mask_row[(px - x) as usize] {
+                    *color = dc.text_color;
+                } else if opaque {
+                    *color = dc.bk_color;
+                }
+            }
+            bitmap.write_row(mem, xs, py as u32, &row);
+        }
+    }
+    drop(bitmap);
+    dc.target.flush(machine);
+    true
+}
+
+const TA_UPDATECP: u32 = 1;
+const TA_RIGHT: u32 = 2;
+const TA_CENTER: u32 = 6;
+const TA_BOTTOM: u32 = 8;
+const TA_BASELINE: u32 = 24;
+pub const GDI_ERROR: u32 = 0xffff_ffff;
+
 #[win32_derive::dllexport]
-pub fn SetTextAlign(_machine: &mut Machine, hdc: HDC, fMode: u32) -> u32 {
-    0 // TA_LEFT | TA_TOP | TA_NOUPDATECP
+pub fn SetTextAlign(machine: &mut Machine, hdc: HDC, fMode: u32) -> u32 {
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return GDI_ERROR;
+    };
+    std::mem::replace(&mut dc.borrow_mut().text_align, fMode)
 }
 
 #[win32_derive::dllexport]
-pub fn SetTextColor(_machine: &mut Machine, hdc: HDC, color: COLORREF) -> COLORREF {
-    CLR_INVALID // fail
+pub fn SetTextColor(machine: &mut Machine, hdc: HDC, color: COLORREF) -> COLORREF {
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return CLR_INVALID; // fail
+    };
+    std::mem::replace(&mut dc.borrow_mut().text_color, color)
+}
+
+/// Draw text relative to a reference point, according to the DC's text alignment.
+fn text_out(machine: &mut Machine, hdc: HDC, x: i32, y: i32, text: &[char]) -> bool {
+    let font = dc_font(machine, hdc);
+    let Some(dc) = machine.state.gdi32.dcs.get(hdc) else {
+        return false;
+    };
+    let mut dc = dc.borrow_mut();
+    let align = dc.text_align;
+    let width = font.text_width(text) as i32;
+
+    let (x, y) = if align & TA_UPDATECP != 0 {
+        (dc.pos.x, dc.pos.y)
+    } else {
+        (x, y)
+    };
+    let left = match align & TA_CENTER {
+        TA_CENTER => x - width / 2,
+        TA_RIGHT => x - width,
+        _ => x,
+    };
+    let top = match align & TA_BASELINE {
+        TA_BASELINE => y - font.ascent() as i32,
+        TA_BOTTOM => y - font.height() as i32,
+        _ => y,
+    };
+    if align & TA_UPDATECP != 0 {
+        match align & TA_CENTER {
+            TA_CENTER => {}
+            TA_RIGHT => dc.pos.x -= width,
+            _ => dc.pos.x += width,
+        }
+    }
+    drop(dc);
+
+    draw_text(machine, hdc, left, top, text, None, None)
 }
 
 #[win32_derive::dllexport]
 pub fn TextOutA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hdc: HDC,
-    x: u32,
-    y: u32,
+    x: i32,
+    y: i32,
     lpString: ArrayWithSize<u8>,
 ) -> bool {
-    let _text = std::str::from_utf8(lpString.unwrap()).unwrap();
-    true
+    let text = chars_a(lpString.unwrap());
+    text_out(machine, hdc, x, y, &text)
 }
 
 #[win32_derive::dllexport]
 pub fn TextOutW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hdc: HDC,
-    x: u32,
-    y: u32,
+    x: i32,
+    y: i32,
     lpString: ArrayWithSize<u16>,
 ) -> bool {
-    true
+    let text = chars_w(lpString.unwrap());
+    text_out(machine, hdc, x, y, &text)
 }
 
 #[repr(C)]
@@ -152,23 +451,77 @@ pub struct TEXTMETRICW {
 }
 unsafe impl memory::Pod for TEXTMETRICW {}
 
-#[win32_derive::dllexport]
-pub fn GetTextMetricsA(_machine: &mut Machine, hdc: HDC, lptm: Option<&mut TEXTMETRICA>) -> bool {
-    let tm = lptm.unwrap();
-    *tm = TEXTMETRICA::zeroed();
+impl Font {
+    fn text_metrics(&self) -> TEXTMETRICW {
+        const TMPF_FIXED_PITCH: u8 = 1; // confusingly, set for variable pitch fonts
+        const FF_SWISS: u8 = 0x20;
+        const FF_MODERN: u8 = 0x30;
+        TEXTMETRICW {
+            tmHeight: self.height(),
+            tmAscent: self.ascent(),
+            tmDescent: self.descent(),
+            tmInternalLeading: self.internal_leading(),
+            tmExternalLeading: 0,
+            tmAveCharWidth: self.char_width('x'),
+            tmMaxCharWidth: self.max_char_width(),
+            tmWeight: self.weight(),
+            tmOverhang: self.overhang(),
+            tmDigitizedAspectX: 96,
+            tmDigitizedAspectY: 96,
+            tmFirstChar: 0x20,
+            tmLastChar: 0xff,
+            tmDefaultChar: '?' as u16,
+            tmBreakChar: ' ' as u16,
+            tmItalic: self.italic as u8,
+            tmUnderlined: self.underline as u8,
+            tmStruckOut: self.strikeout as u8,
+            tmPitchAndFamily: if self.face.fixed {
+                FF_MODERN
+            } else {
+                FF_SWISS | TMPF_FIXED_PITCH
+            },
+            tmCharSet: self.face.charset,
+        }
+    }
+}
 
-    // SkiFree only cares about the height, just make something up for now.
-    tm.tmHeight = 12;
+#[win32_derive::dllexport]
+pub fn GetTextMetricsA(machine: &mut Machine, hdc: HDC, lptm: Option<&mut TEXTMETRICA>) -> bool {
+    let Some(tm) = lptm else {
+        return false;
+    };
+    let w = dc_font(machine, hdc).text_metrics();
+    *tm = TEXTMETRICA {
+        tmHeight: w.tmHeight,
+        tmAscent: w.tmAscent,
+        tmDescent: w.tmDescent,
+        tmInternalLeading: w.tmInternalLeading,
+        tmExternalLeading: w.tmExternalLeading,
+        tmAveCharWidth: w.tmAveCharWidth,
+        tmMaxCharWidth: w.tmMaxCharWidth,
+        tmWeight: w.tmWeight,
+        tmOverhang: w.tmOverhang,
+        tmDigitizedAspectX: w.tmDigitizedAspectX,
+        tmDigitizedAspectY: w.tmDigitizedAspectY,
+        tmFirstChar: w.tmFirstChar as u8,
+        tmLastChar: w.tmLastChar as u8,
+        tmDefaultChar: w.tmDefaultChar as u8,
+        tmBreakChar: w.tmBreakChar as u8,
+        tmItalic: w.tmItalic,
+        tmUnderlined: w.tmUnderlined,
+        tmStruckOut: w.tmStruckOut,
+        tmPitchAndFamily: w.tmPitchAndFamily,
+        tmCharSet: w.tmCharSet,
+    };
     true
 }
 
 #[win32_derive::dllexport]
-pub fn GetTextMetricsW(_machine: &mut Machine, hdc: HDC, lptm: Option<&mut TEXTMETRICW>) -> bool {
-    let tm = lptm.unwrap();
-    *tm = TEXTMETRICW::zeroed();
-
-    // SkiFree only cares about the height, just make something up for now.
-    tm.tmHeight = 12;
+pub fn GetTextMetricsW(machine: &mut Machine, hdc: HDC, lptm: Option<&mut TEXTMETRICW>) -> bool {
+    let Some(tm) = lptm else {
+        return false;
+    };
+    *tm = dc_font(machine, hdc).text_metrics();
     true
 }
 
@@ -180,32 +533,38 @@ pub struct SIZE {
 }
 unsafe impl memory::Pod for SIZE {}
 
+fn text_extent(machine: &Machine, hdc: HDC, text: &[char]) -> SIZE {
+    let font = dc_font(machine, hdc);
+    SIZE {
+        cx: font.text_width(text) as i32,
+        cy: font.height() as i32,
+    }
+}
+
 #[win32_derive::dllexport]
 pub fn GetTextExtentPoint32A(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hdc: HDC,
-    lpString: Option<&str>,
-    c: i32,
+    lpString: ArrayWithSize<u8>,
     psizl: Option<&mut SIZE>,
 ) -> bool {
-    *psizl.unwrap() = SIZE {
-        cx: lpString.unwrap().len() as i32 * 10,
-        cy: 12,
+    let Some(size) = psizl else {
+        return false;
     };
+    *size = text_extent(machine, hdc, &chars_a(lpString.unwrap()));
     true
 }
 
 #[win32_derive::dllexport]
 pub fn GetTextExtentPoint32W(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hdc: HDC,
-    lpString: Option<&str>,
-    c: i32,
+    lpString: ArrayWithSize<u16>,
     psizl: Option<&mut SIZE>,
 ) -> bool {
-    *psizl.unwrap() = SIZE {
-        cx: lpString.unwrap().len() as i32 * 10,
-        cy: 12,
+    let Some(size) = psizl else {
+        return false;
     };
+    *size = text_extent(machine, hdc, &chars_w(lpString.unwrap()));
     true
 }
@@ -56,10 +56,10 @@ mod wrappers {
         let mem = machine.mem().detach();
         let first = <u32>::from_stack(mem, stack_args + 0u32);
         let handler = <u32>::from_stack(mem, stack_args + 4u32);
-        let __trace_record = if crate::trace::enabled("kernel32/misc") {
+        let __trace_record = if crate::trace::enabled("kernel32/exception") {
             crate::trace::Record::new(
                 winapi::kernel32::AddVectoredExceptionHandler_pos,
-                "kernel32/misc",
+                "kernel32/exception",
                 "AddVectoredExceptionHandler",
                 &[("first", &first), ("handler", &handler)],
             )
@@ -94,6 +94,26 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn CancelWaitableTimer(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hTimer = <HTIMER>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/timer") {
+            crate::trace::Record::new(
+                winapi::kernel32::CancelWaitableTimer_pos,
+                "kernel32/sync/timer",
+                "CancelWaitableTimer",
+                &[("hTimer", &hTimer)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::CancelWaitableTimer(machine, hTimer);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn CloseHandle(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hObject = <HFILE>::from_stack(mem, stack_args + 0u32);
@@ -383,6 +403,34 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn CreateMutexW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpMutexAttributes =
+            <Option<&mut SECURITY_ATTRIBUTES>>::from_stack(mem, stack_args + 0u32);
+        let bInitialOwner = <bool>::from_stack(mem, stack_args + 4u32);
+        let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/mutex") {
+            crate::trace::Record::new(
+                winapi::kernel32::CreateMutexW_pos,
+                "kernel32/sync/mutex",
+                "CreateMutexW",
+                &[
+                    ("lpMutexAttributes", &lpMutexAttributes),
+                    ("bInitialOwner", &bInitialOwner),
+                    ("lpName", &lpName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::kernel32::CreateMutexW(machine, lpMutexAttributes, bInitialOwner, lpName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn CreatePipe(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hReadPipe = <Option<&mut HFILE>>::from_stack(mem, stack_args + 0u32);
@@ -523,6 +571,76 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn CreateSemaphoreA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpSemaphoreAttributes =
+            <Option<&mut SECURITY_ATTRIBUTES>>::from_stack(mem, stack_args + 0u32);
+        let lInitialCount = <i32>::from_stack(mem, stack_args + 4u32);
+        let lMaximumCount = <i32>::from_stack(mem, stack_args + 8u32);
+        let lpName = <Option<&str>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/semaphore") {
+            crate::trace::Record::new(
+                winapi::kernel32::CreateSemaphoreA_pos,
+                "kernel32/sync/semaphore",
+                "CreateSemaphoreA",
+                &[
+                    ("lpSemaphoreAttributes", &lpSemaphoreAttributes),
+                    ("lInitialCount", &lInitialCount),
+                    ("lMaximumCount", &lMaximumCount),
+                    ("lpName", &lpName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::CreateSemaphoreA(
+            machine,
+            lpSemaphoreAttributes,
+            lInitialCount,
+            lMaximumCount,
+            lpName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateSemaphoreW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpSemaphoreAttributes =
+            <Option<&mut SECURITY_ATTRIBUTES>>::from_stack(mem, stack_args + 0u32);
+        let lInitialCount = <i32>::from_stack(mem, stack_args + 4u32);
+        let lMaximumCount = <i32>::from_stack(mem, stack_args + 8u32);
+        let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/semaphore") {
+            crate::trace::Record::new(
+                winapi::kernel32::CreateSemaphoreW_pos,
+                "kernel32/sync/semaphore",
+                "CreateSemaphoreW",
+                &[
+                    ("lpSemaphoreAttributes", &lpSemaphoreAttributes),
+                    ("lInitialCount", &lInitialCount),
+                    ("lMaximumCount", &lMaximumCount),
+                    ("lpName", &lpName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::CreateSemaphoreW(
+            machine,
+            lpSemaphoreAttributes,
+            lInitialCount,
+            lMaximumCount,
+            lpName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn CreateThread(
         machine: &mut Machine,
         stack_args: u32,
@@ -571,6 +689,70 @@ mod wrappers {
             result.into_abireturn()
         })
     }
+    pub unsafe fn CreateWaitableTimerA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpTimerAttributes =
+            <Option<&mut SECURITY_ATTRIBUTES>>::from_stack(mem, stack_args + 0u32);
+        let bManualReset = <bool>::from_stack(mem, stack_args + 4u32);
+        let lpTimerName = <Option<&str>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/timer") {
+            crate::trace::Record::new(
+                winapi::kernel32::CreateWaitableTimerA_pos,
+                "kernel32/sync/timer",
+                "CreateWaitableTimerA",
+                &[
+                    ("lpTimerAttributes", &lpTimerAttributes),
+                    ("bManualReset", &bManualReset),
+                    ("lpTimerName", &lpTimerName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::CreateWaitableTimerA(
+            machine,
+            lpTimerAttributes,
+            bManualReset,
+            lpTimerName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateWaitableTimerW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpTimerAttributes =
+            <Option<&mut SECURITY_ATTRIBUTES>>::from_stack(mem, stack_args + 0u32);
+        let bManualReset = <bool>::from_stack(mem, stack_args + 4u32);
+        let lpTimerName = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/timer") {
+            crate::trace::Record::new(
+                winapi::kernel32::CreateWaitableTimerW_pos,
+                "kernel32/sync/timer",
+                "CreateWaitableTimerW",
+                &[
+                    ("lpTimerAttributes", &lpTimerAttributes),
+                    ("bManualReset", &bManualReset),
+                    ("lpTimerName", &lpTimerName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::CreateWaitableTimerW(
+            machine,
+            lpTimerAttributes,
+            bManualReset,
+            lpTimerName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn DebugBreak(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let __trace_record = if crate::trace::enabled("kernel32/misc") {
@@ -713,7 +895,10 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn EnterCriticalSection(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn EnterCriticalSection(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let lpCriticalSection = <u32>::from_stack(mem, stack_args + 0u32);
         let __trace_record = if crate::trace::enabled("kernel32/sync/critical_section") {
@@ -727,11 +912,15 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::kernel32::EnterCriticalSection(machine, lpCriticalSection);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::kernel32::EnterCriticalSection(machine, lpCriticalSection).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn EnumSystemLocalesA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
@@ -2102,6 +2291,36 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetPrivateProfileIntA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+        let lpKeyName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+        let nDefault = <u32>::from_stack(mem, stack_args + 8u32);
+        let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::GetPrivateProfileIntA_pos,
+                "kernel32/ini",
+                "GetPrivateProfileIntA",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpKeyName", &lpKeyName),
+                    ("nDefault", &nDefault),
+                    ("lpFileName", &lpFileName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::GetPrivateProfileIntA(
+            machine, lpAppName, lpKeyName, nDefault, lpFileName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetPrivateProfileIntW(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
@@ -2132,13 +2351,124 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetPrivateProfileSectionA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+        let lpReturnedString = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+        let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::GetPrivateProfileSectionA_pos,
+                "kernel32/ini",
+                "GetPrivateProfileSectionA",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpReturnedString", &lpReturnedString),
+                    ("lpFileName", &lpFileName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::GetPrivateProfileSectionA(
+            machine,
+            lpAppName,
+            lpReturnedString,
+            lpFileName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetPrivateProfileSectionNamesA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpszReturnBuffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 0u32);
+        let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::GetPrivateProfileSectionNamesA_pos,
+                "kernel32/ini",
+                "GetPrivateProfileSectionNamesA",
+                &[
+                    ("lpszReturnBuffer", &lpszReturnBuffer),
+                    ("lpFileName", &lpFileName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::kernel32::GetPrivateProfileSectionNamesA(machine, lpszReturnBuffer, lpFileName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetPrivateProfileSectionNamesW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpszReturnBuffer = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 0u32);
+        let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::GetPrivateProfileSectionNamesW_pos,
+                "kernel32/ini",
+                "GetPrivateProfileSectionNamesW",
+                &[
+                    ("lpszReturnBuffer", &lpszReturnBuffer),
+                    ("lpFileName", &lpFileName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::kernel32::GetPrivateProfileSectionNamesW(machine, lpszReturnBuffer, lpFileName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetPrivateProfileSectionW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+        let lpReturnedString = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 4u32);
+        let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::GetPrivateProfileSectionW_pos,
+                "kernel32/ini",
+                "GetPrivateProfileSectionW",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpReturnedString", &lpReturnedString),
+                    ("lpFileName", &lpFileName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::GetPrivateProfileSectionW(
+            machine,
+            lpAppName,
+            lpReturnedString,
+            lpFileName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetPrivateProfileStringA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lpAppName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
         let lpKeyName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
         let lpDefault = <Option<&str>>::from_stack(mem, stack_args + 8u32);
-        let lpReturnedString = <Option<&str>>::from_stack(mem, stack_args + 12u32);
-        let nSize = <u32>::from_stack(mem, stack_args + 16u32);
+        let lpReturnedString = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 12u32);
         let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 20u32);
         let __trace_record = if crate::trace::enabled("kernel32/ini") {
             crate::trace::Record::new(
@@ -2150,7 +2480,6 @@ mod wrappers {
                     ("lpKeyName", &lpKeyName),
                     ("lpDefault", &lpDefault),
                     ("lpReturnedString", &lpReturnedString),
-                    ("nSize", &nSize),
                     ("lpFileName", &lpFileName),
                 ],
             )
@@ -2164,7 +2493,6 @@ mod wrappers {
             lpKeyName,
             lpDefault,
             lpReturnedString,
-            nSize,
             lpFileName,
         );
         if let Some(mut __trace_record) = __trace_record {
@@ -2249,16 +2577,16 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn GetProfileIntW(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn GetProfileIntA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-        let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let lpAppName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+        let lpKeyName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
         let nDefault = <i32>::from_stack(mem, stack_args + 8u32);
         let __trace_record = if crate::trace::enabled("kernel32/ini") {
             crate::trace::Record::new(
-                winapi::kernel32::GetProfileIntW_pos,
+                winapi::kernel32::GetProfileIntA_pos,
                 "kernel32/ini",
-                "GetProfileIntW",
+                "GetProfileIntA",
                 &[
                     ("lpAppName", &lpAppName),
                     ("lpKeyName", &lpKeyName),
@@ -2269,13 +2597,121 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::kernel32::GetProfileIntW(machine, lpAppName, lpKeyName, nDefault);
+        let result = winapi::kernel32::GetProfileIntA(machine, lpAppName, lpKeyName, nDefault);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
         result.into_abireturn()
     }
-    pub unsafe fn GetProfileStringW(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn GetProfileIntW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+        let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let nDefault = <i32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::GetProfileIntW_pos,
+                "kernel32/ini",
+                "GetProfileIntW",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpKeyName", &lpKeyName),
+                    ("nDefault", &nDefault),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::GetProfileIntW(machine, lpAppName, lpKeyName, nDefault);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetProfileSectionA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+        let lpReturnedString = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::GetProfileSectionA_pos,
+                "kernel32/ini",
+                "GetProfileSectionA",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpReturnedString", &lpReturnedString),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::GetProfileSectionA(machine, lpAppName, lpReturnedString);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetProfileSectionW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+        let lpReturnedString = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::GetProfileSectionW_pos,
+                "kernel32/ini",
+                "GetProfileSectionW",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpReturnedString", &lpReturnedString),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::GetProfileSectionW(machine, lpAppName, lpReturnedString);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetProfileStringA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+        let lpKeyName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+        let lpDefault = <Option<&str>>::from_stack(mem, stack_args + 8u32);
+        let lpReturnedString = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::GetProfileStringA_pos,
+                "kernel32/ini",
+                "GetProfileStringA",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpKeyName", &lpKeyName),
+                    ("lpDefault", &lpDefault),
+                    ("lpReturnedString", &lpReturnedString),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::GetProfileStringA(
+            machine,
+            lpAppName,
+            lpKeyName,
+            lpDefault,
+            lpReturnedString,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetProfileStringW(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
         let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
@@ -3846,6 +4282,148 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn OpenMutexW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 0u32);
+        let bInheritHandle = <bool>::from_stack(mem, stack_args + 4u32);
+        let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/mutex") {
+            crate::trace::Record::new(
+                winapi::kernel32::OpenMutexW_pos,
+                "kernel32/sync/mutex",
+                "OpenMutexW",
+                &[
+                    ("dwDesiredAccess", &dwDesiredAccess),
+                    ("bInheritHandle", &bInheritHandle),
+                    ("lpName", &lpName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::OpenMutexW(machine, dwDesiredAccess, bInheritHandle, lpName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn OpenSemaphoreA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 0u32);
+        let bInheritHandle = <bool>::from_stack(mem, stack_args + 4u32);
+        let lpName = <Option<&str>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/semaphore") {
+            crate::trace::Record::new(
+                winapi::kernel32::OpenSemaphoreA_pos,
+                "kernel32/sync/semaphore",
+                "OpenSemaphoreA",
+                &[
+                    ("dwDesiredAccess", &dwDesiredAccess),
+                    ("bInheritHandle", &bInheritHandle),
+                    ("lpName", &lpName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::kernel32::OpenSemaphoreA(machine, dwDesiredAccess, bInheritHandle, lpName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn OpenSemaphoreW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 0u32);
+        let bInheritHandle = <bool>::from_stack(mem, stack_args + 4u32);
+        let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/semaphore") {
+            crate::trace::Record::new(
+                winapi::kernel32::OpenSemaphoreW_pos,
+                "kernel32/sync/semaphore",
+                "OpenSemaphoreW",
+                &[
+                    ("dwDesiredAccess", &dwDesiredAccess),
+                    ("bInheritHandle", &bInheritHandle),
+                    ("lpName", &lpName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::kernel32::OpenSemaphoreW(machine, dwDesiredAccess, bInheritHandle, lpName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn OpenWaitableTimerA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 0u32);
+        let bInheritHandle = <bool>::from_stack(mem, stack_args + 4u32);
+        let lpTimerName = <Option<&str>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/timer") {
+            crate::trace::Record::new(
+                winapi::kernel32::OpenWaitableTimerA_pos,
+                "kernel32/sync/timer",
+                "OpenWaitableTimerA",
+                &[
+                    ("dwDesiredAccess", &dwDesiredAccess),
+                    ("bInheritHandle", &bInheritHandle),
+                    ("lpTimerName", &lpTimerName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::OpenWaitableTimerA(
+            machine,
+            dwDesiredAccess,
+            bInheritHandle,
+            lpTimerName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn OpenWaitableTimerW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 0u32);
+        let bInheritHandle = <bool>::from_stack(mem, stack_args + 4u32);
+        let lpTimerName = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/timer") {
+            crate::trace::Record::new(
+                winapi::kernel32::OpenWaitableTimerW_pos,
+                "kernel32/sync/timer",
+                "OpenWaitableTimerW",
+                &[
+                    ("dwDesiredAccess", &dwDesiredAccess),
+                    ("bInheritHandle", &bInheritHandle),
+                    ("lpTimerName", &lpTimerName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::OpenWaitableTimerW(
+            machine,
+            dwDesiredAccess,
+            bInheritHandle,
+            lpTimerName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn OutputDebugStringA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let msg = <Option<&str>>::from_stack(mem, stack_args + 0u32);
@@ -3903,8 +4481,7 @@ mod wrappers {
     pub unsafe fn PeekNamedPipe(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hNamedPipe = <HFILE>::from_stack(mem, stack_args + 0u32);
-        let lpBuffer = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
-        let nBufferSize = <u32>::from_stack(mem, stack_args + 8u32);
+        let lpBuffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
         let lpBytesRead = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
         let lpTotalBytesAvail = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
         let lpBytesLeftThisMessage = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
@@ -3916,7 +4493,6 @@ mod wrappers {
                 &[
                     ("hNamedPipe", &hNamedPipe),
                     ("lpBuffer", &lpBuffer),
-                    ("nBufferSize", &nBufferSize),
                     ("lpBytesRead", &lpBytesRead),
                     ("lpTotalBytesAvail", &lpTotalBytesAvail),
                     ("lpBytesLeftThisMessage", &lpBytesLeftThisMessage),
@@ -3930,7 +4506,6 @@ mod wrappers {
             machine,
             hNamedPipe,
             lpBuffer,
-            nBufferSize,
             lpBytesRead,
             lpTotalBytesAvail,
             lpBytesLeftThisMessage,
@@ -4006,10 +4581,10 @@ mod wrappers {
         let dwExceptionFlags = <u32>::from_stack(mem, stack_args + 4u32);
         let nNumberOfArguments = <u32>::from_stack(mem, stack_args + 8u32);
         let lpArguments = <u32>::from_stack(mem, stack_args + 12u32);
-        let __trace_record = if crate::trace::enabled("kernel32/misc") {
+        let __trace_record = if crate::trace::enabled("kernel32/exception") {
             crate::trace::Record::new(
                 winapi::kernel32::RaiseException_pos,
-                "kernel32/misc",
+                "kernel32/exception",
                 "RaiseException",
                 &[
                     ("dwExceptionCode", &dwExceptionCode),
@@ -4106,10 +4681,13 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn ReadFile(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn ReadFile(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-        let lpBuffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+        let lpBuffer = <ArrayWithSizeMut<'_, u8>>::from_stack(mem, stack_args + 4u32);
         let lpNumberOfBytesRead = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
         let lpOverlapped = <u32>::from_stack(mem, stack_args + 16u32);
         let __trace_record = if crate::trace::enabled("kernel32/file") {
@@ -4128,8 +4706,38 @@ mod wrappers {
         } else {
             None
         };
-        let result =
-            winapi::kernel32::ReadFile(machine, hFile, lpBuffer, lpNumberOfBytesRead, lpOverlapped);
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::kernel32::ReadFile(
+                machine,
+                hFile,
+                lpBuffer,
+                lpNumberOfBytesRead,
+                lpOverlapped,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn ReleaseMutex(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMutex = <HMUTEX>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/mutex") {
+            crate::trace::Record::new(
+                winapi::kernel32::ReleaseMutex_pos,
+                "kernel32/sync/mutex",
+                "ReleaseMutex",
+                &[("hMutex", &hMutex)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::ReleaseMutex(machine, hMutex);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
@@ -4175,6 +4783,33 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn ReleaseSemaphore(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hSemaphore = <HSEMAPHORE>::from_stack(mem, stack_args + 0u32);
+        let lReleaseCount = <i32>::from_stack(mem, stack_args + 4u32);
+        let lpPreviousCount = <Option<&mut i32>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/semaphore") {
+            crate::trace::Record::new(
+                winapi::kernel32::ReleaseSemaphore_pos,
+                "kernel32/sync/semaphore",
+                "ReleaseSemaphore",
+                &[
+                    ("hSemaphore", &hSemaphore),
+                    ("lReleaseCount", &lReleaseCount),
+                    ("lpPreviousCount", &lpPreviousCount),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::kernel32::ReleaseSemaphore(machine, hSemaphore, lReleaseCount, lpPreviousCount);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn RemoveDirectoryA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lpPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
@@ -4215,6 +4850,26 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn RemoveVectoredExceptionHandler(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let Handle = <u32>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("kernel32/exception") {
+            crate::trace::Record::new(
+                winapi::kernel32::RemoveVectoredExceptionHandler_pos,
+                "kernel32/exception",
+                "RemoveVectoredExceptionHandler",
+                &[("Handle", &Handle)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::RemoveVectoredExceptionHandler(machine, Handle);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn ResetEvent(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hEvent = <HEVENT>::from_stack(mem, stack_args + 0u32);
@@ -4255,16 +4910,19 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn RtlUnwind(machine: &mut Machine, stack_args: u32) -> u64 {
-        let mem = machine.mem().detach();
+    pub unsafe fn RtlUnwind(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
         let TargetFrame = <u32>::from_stack(mem, stack_args + 0u32);
         let TargetIp = <u32>::from_stack(mem, stack_args + 4u32);
         let ExceptionRecord = <u32>::from_stack(mem, stack_args + 8u32);
         let ReturnValue = <u32>::from_stack(mem, stack_args + 12u32);
-        let __trace_record = if crate::trace::enabled("kernel32/misc") {
+        let __trace_record = if crate::trace::enabled("kernel32/exception") {
             crate::trace::Record::new(
                 winapi::kernel32::RtlUnwind_pos,
-                "kernel32/misc",
+                "kernel32/exception",
                 "RtlUnwind",
                 &[
                     ("TargetFrame", &TargetFrame),
@@ -4277,17 +4935,22 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::kernel32::RtlUnwind(
-            machine,
-            TargetFrame,
-            TargetIp,
-            ExceptionRecord,
-            ReturnValue,
-        );
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::kernel32::RtlUnwind(
+                machine,
+                TargetFrame,
+                TargetIp,
+                ExceptionRecord,
+                ReturnValue,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn SetConsoleCtrlHandler(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
@@ -4676,7 +5339,7 @@ mod wrappers {
     pub unsafe fn SetStdHandle(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let nStdHandle = <Result<STD, u32>>::from_stack(mem, stack_args + 0u32);
-        let hHandle = <u32>::from_stack(mem, stack_args + 4u32);
+        let hHandle = <HFILE>::from_stack(mem, stack_args + 4u32);
         let __trace_record = if crate::trace::enabled("kernel32/file") {
             crate::trace::Record::new(
                 winapi::kernel32::SetStdHandle_pos,
@@ -4761,25 +5424,107 @@ mod wrappers {
     }
     pub unsafe fn SetUnhandledExceptionFilter(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let _lpTopLevelExceptionFilter = <u32>::from_stack(mem, stack_args + 0u32);
-        let __trace_record = if crate::trace::enabled("kernel32/misc") {
+        let lpTopLevelExceptionFilter = <u32>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("kernel32/exception") {
             crate::trace::Record::new(
                 winapi::kernel32::SetUnhandledExceptionFilter_pos,
-                "kernel32/misc",
+                "kernel32/exception",
                 "SetUnhandledExceptionFilter",
-                &[("lpTopLevelExceptionFilter", &_lpTopLevelExceptionFilter)],
+                &[("lpTopLevelExceptionFilter", &lpTopLevelExceptionFilter)],
             )
             .enter()
         } else {
             None
         };
         let result =
-            winapi::kernel32::SetUnhandledExceptionFilter(machine, _lpTopLevelExceptionFilter);
+            winapi::kernel32::SetUnhandledExceptionFilter(machine, lpTopLevelExceptionFilter);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn SetWaitableTimer(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hTimer = <HTIMER>::from_stack(mem, stack_args + 0u32);
+        let lpDueTime = <Option<&LARGE_INTEGER>>::from_stack(mem, stack_args + 4u32);
+        let lPeriod = <i32>::from_stack(mem, stack_args + 8u32);
+        let pfnCompletionRoutine = <u32>::from_stack(mem, stack_args + 12u32);
+        let lpArgToCompletionRoutine = <u32>::from_stack(mem, stack_args + 16u32);
+        let fResume = <bool>::from_stack(mem, stack_args + 20u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/timer") {
+            crate::trace::Record::new(
+                winapi::kernel32::SetWaitableTimer_pos,
+                "kernel32/sync/timer",
+                "SetWaitableTimer",
+                &[
+                    ("hTimer", &hTimer),
+                    ("lpDueTime", &lpDueTime),
+                    ("lPeriod", &lPeriod),
+                    ("pfnCompletionRoutine", &pfnCompletionRoutine),
+                    ("lpArgToCompletionRoutine", &lpArgToCompletionRoutine),
+                    ("fResume", &fResume),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::SetWaitableTimer(
+            machine,
+            hTimer,
+            lpDueTime,
+            lPeriod,
+            pfnCompletionRoutine,
+            lpArgToCompletionRoutine,
+            fResume,
+        );
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
         result.into_abireturn()
     }
+    pub unsafe fn SignalObjectAndWait(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hObjectToSignal = <HANDLE<()>>::from_stack(mem, stack_args + 0u32);
+        let hObjectToWaitOn = <HANDLE<()>>::from_stack(mem, stack_args + 4u32);
+        let dwMilliseconds = <u32>::from_stack(mem, stack_args + 8u32);
+        let bAlertable = <bool>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/wait") {
+            crate::trace::Record::new(
+                winapi::kernel32::SignalObjectAndWait_pos,
+                "kernel32/sync/wait",
+                "SignalObjectAndWait",
+                &[
+                    ("hObjectToSignal", &hObjectToSignal),
+                    ("hObjectToWaitOn", &hObjectToWaitOn),
+                    ("dwMilliseconds", &dwMilliseconds),
+                    ("bAlertable", &bAlertable),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::kernel32::SignalObjectAndWait(
+                machine,
+                hObjectToSignal,
+                hObjectToWaitOn,
+                dwMilliseconds,
+                bAlertable,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
     pub unsafe fn SizeofResource(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
@@ -4851,12 +5596,12 @@ mod wrappers {
     }
     pub unsafe fn TerminateProcess(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let hProcess = <u32>::from_stack(mem, stack_args + 0u32);
+        let hProcess = <HPROCESS>::from_stack(mem, stack_args + 0u32);
         let uExitCode = <u32>::from_stack(mem, stack_args + 4u32);
-        let __trace_record = if crate::trace::enabled("kernel32/misc") {
+        let __trace_record = if crate::trace::enabled("kernel32/process") {
             crate::trace::Record::new(
                 winapi::kernel32::TerminateProcess_pos,
-                "kernel32/misc",
+                "kernel32/process",
                 "TerminateProcess",
                 &[("hProcess", &hProcess), ("uExitCode", &uExitCode)],
             )
@@ -4970,26 +5715,53 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn UnhandledExceptionFilter(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn TryEnterCriticalSection(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let _exceptionInfo = <u32>::from_stack(mem, stack_args + 0u32);
-        let __trace_record = if crate::trace::enabled("kernel32/misc") {
+        let lpCriticalSection = <u32>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("kernel32/sync/critical_section") {
             crate::trace::Record::new(
-                winapi::kernel32::UnhandledExceptionFilter_pos,
-                "kernel32/misc",
-                "UnhandledExceptionFilter",
-                &[("exceptionInfo", &_exceptionInfo)],
+                winapi::kernel32::TryEnterCriticalSection_pos,
+                "kernel32/sync/critical_section",
+                "TryEnterCriticalSection",
+                &[("lpCriticalSection", &lpCriticalSection)],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::kernel32::UnhandledExceptionFilter(machine, _exceptionInfo);
+        let result = winapi::kernel32::TryEnterCriticalSection(machine, lpCriticalSection);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
         result.into_abireturn()
     }
+    pub unsafe fn UnhandledExceptionFilter(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let ExceptionInfo = <u32>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("kernel32/exception") {
+            crate::trace::Record::new(
+                winapi::kernel32::UnhandledExceptionFilter_pos,
+                "kernel32/exception",
+                "UnhandledExceptionFilter",
+                &[("ExceptionInfo", &ExceptionInfo)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::kernel32::UnhandledExceptionFilter(machine, ExceptionInfo).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
     pub unsafe fn UnlockFile(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
@@ -5290,10 +6062,13 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn WriteConsoleW(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn WriteConsoleW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hConsoleOutput = <HFILE>::from_stack(mem, stack_args + 0u32);
-        let lpBuffer = <ArrayWithSize<u16>>::from_stack(mem, stack_args + 4u32);
+        let lpBuffer = <ArrayWithSize<'_, u16>>::from_stack(mem, stack_args + 4u32);
         let lpNumberOfCharsWritten = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
         let _lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
         let __trace_record = if crate::trace::enabled("kernel32/console") {
@@ -5312,22 +6087,30 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::kernel32::WriteConsoleW(
-            machine,
-            hConsoleOutput,
-            lpBuffer,
-            lpNumberOfCharsWritten,
-            _lpReserved,
-        );
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::kernel32::WriteConsoleW(
+                machine,
+                hConsoleOutput,
+                lpBuffer,
+                lpNumberOfCharsWritten,
+                _lpReserved,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn WriteFile(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn WriteFile(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-        let lpBuffer = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
+        let lpBuffer = <ArrayWithSize<'_, u8>>::from_stack(mem, stack_args + 4u32);
         let lpNumberOfBytesWritten = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
         let lpOverlapped = <u32>::from_stack(mem, stack_args + 16u32);
         let __trace_record = if crate::trace::enabled("kernel32/file") {
@@ -5346,13 +6129,72 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::kernel32::WriteFile(
-            machine,
-            hFile,
-            lpBuffer,
-            lpNumberOfBytesWritten,
-            lpOverlapped,
-        );
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::kernel32::WriteFile(
+                machine,
+                hFile,
+                lpBuffer,
+                lpNumberOfBytesWritten,
+                lpOverlapped,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn WritePrivateProfileSectionA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+        let lpString = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::WritePrivateProfileSectionA_pos,
+                "kernel32/ini",
+                "WritePrivateProfileSectionA",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpString", &lpString),
+                    ("lpFileName", &lpFileName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::kernel32::WritePrivateProfileSectionA(machine, lpAppName, lpString, lpFileName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn WritePrivateProfileSectionW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+        let lpString = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::WritePrivateProfileSectionW_pos,
+                "kernel32/ini",
+                "WritePrivateProfileSectionW",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpString", &lpString),
+                    ("lpFileName", &lpFileName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::kernel32::WritePrivateProfileSectionW(machine, lpAppName, lpString, lpFileName);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
@@ -5388,6 +6230,104 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn WritePrivateProfileStringW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+        let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let lpString = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+        let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::WritePrivateProfileStringW_pos,
+                "kernel32/ini",
+                "WritePrivateProfileStringW",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpKeyName", &lpKeyName),
+                    ("lpString", &lpString),
+                    ("lpFileName", &lpFileName),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::WritePrivateProfileStringW(
+            machine, lpAppName, lpKeyName, lpString, lpFileName,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn WriteProfileSectionA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+        let lpString = <u32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::WriteProfileSectionA_pos,
+                "kernel32/ini",
+                "WriteProfileSectionA",
+                &[("lpAppName", &lpAppName), ("lpString", &lpString)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::WriteProfileSectionA(machine, lpAppName, lpString);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn WriteProfileSectionW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+        let lpString = <u32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::WriteProfileSectionW_pos,
+                "kernel32/ini",
+                "WriteProfileSectionW",
+                &[("lpAppName", &lpAppName), ("lpString", &lpString)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::WriteProfileSectionW(machine, lpAppName, lpString);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn WriteProfileStringA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let lpAppName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+        let lpKeyName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+        let lpString = <Option<&str>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("kernel32/ini") {
+            crate::trace::Record::new(
+                winapi::kernel32::WriteProfileStringA_pos,
+                "kernel32/ini",
+                "WriteProfileStringA",
+                &[
+                    ("lpAppName", &lpAppName),
+                    ("lpKeyName", &lpKeyName),
+                    ("lpString", &lpString),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::kernel32::WriteProfileStringA(machine, lpAppName, lpKeyName, lpString);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn WriteProfileStringW(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
@@ -5662,7 +6602,7 @@ mod wrappers {
         })
     }
 }
-const SHIMS: [Shim; 230usize] = [
+const SHIMS: [Shim; 262usize] = [
     Shim {
         name: "AcquireSRWLockExclusive",
         func: Handler::Sync(wrappers::AcquireSRWLockExclusive),
@@ -5679,6 +6619,10 @@ const SHIMS: [Shim; 230usize] = [
         name: "Beep",
         func: Handler::Sync(wrappers::Beep),
     },
+    Shim {
+        name: "CancelWaitableTimer",
+        func: Handler::Sync(wrappers::CancelWaitableTimer),
+    },
     Shim {
         name: "CloseHandle",
         func: Handler::Sync(wrappers::CloseHandle),
@@ -5715,6 +6659,10 @@ const SHIMS: [Shim; 230usize] = [
         name: "CreateMutexA",
         func: Handler::Sync(wrappers::CreateMutexA),
     },
+    Shim {
+        name: "CreateMutexW",
+        func: Handler::Sync(wrappers::CreateMutexW),
+    },
     Shim {
         name: "CreatePipe",
         func: Handler::Sync(wrappers::CreatePipe),
@@ -5727,10 +6675,26 @@ const SHIMS: [Shim; 230usize] = [
         name: "CreateProcessW",
         func: Handler::Sync(wrappers::CreateProcessW),
     },
+    Shim {
+        name: "CreateSemaphoreA",
+        func: Handler::Sync(wrappers::CreateSemaphoreA),
+    },
+    Shim {
+        name: "CreateSemaphoreW",
+        func: Handler::Sync(wrappers::CreateSemaphoreW),
+    },
     Shim {
         name: "CreateThread",
         func: Handler::Async(wrappers::CreateThread),
     },
+    Shim {
+        name: "CreateWaitableTimerA",
+        func: Handler::Sync(wrappers::CreateWaitableTimerA),
+    },
+    Shim {
+        name: "CreateWaitableTimerW",
+        func: Handler::Sync(wrappers::CreateWaitableTimerW),
+    },
     Shim {
         name: "DebugBreak",
         func: Handler::Sync(wrappers::DebugBreak),
@@ -5757,7 +6721,7 @@ const SHIMS: [Shim; 230usize] = [
     },
     Shim {
         name: "EnterCriticalSection",
-        func: Handler::Sync(wrappers::EnterCriticalSection),
+        func: Handler::Async(wrappers::EnterCriticalSection),
     },
     Shim {
         name: "EnumSystemLocalesA",
@@ -5995,10 +6959,30 @@ const SHIMS: [Shim; 230usize] = [
         name: "GetOEMCP",
         func: Handler::Sync(wrappers::GetOEMCP),
     },
+    Shim {
+        name: "GetPrivateProfileIntA",
+        func: Handler::Sync(wrappers::GetPrivateProfileIntA),
+    },
     Shim {
         name: "GetPrivateProfileIntW",
         func: Handler::Sync(wrappers::GetPrivateProfileIntW),
     },
+    Shim {
+        name: "GetPrivateProfileSectionA",
+        func: Handler::Sync(wrappers::GetPrivateProfileSectionA),
+    },
+    Shim {
+        name: "GetPrivateProfileSectionNamesA",
+        func: Handler::Sync(wrappers::GetPrivateProfileSectionNamesA),
+    },
+    Shim {
+        name: "GetPrivateProfileSectionNamesW",
+        func: Handler::Sync(wrappers::GetPrivateProfileSectionNamesW),
+    },
+    Shim {
+        name: "GetPrivateProfileSectionW",
+        func: Handler::Sync(wrappers::GetPrivateProfileSectionW),
+    },
     Shim {
         name: "GetPrivateProfileStringA",
         func: Handler::Sync(wrappers::GetPrivateProfileStringA),
@@ -6015,10 +6999,26 @@ const SHIMS: [Shim; 230usize] = [
         name: "GetProcessHeap",
         func: Handler::Sync(wrappers::GetProcessHeap),
     },
+    Shim {
+        name: "GetProfileIntA",
+        func: Handler::Sync(wrappers::GetProfileIntA),
+    },
     Shim {
         name: "GetProfileIntW",
         func: Handler::Sync(wrappers::GetProfileIntW),
     },
+    Shim {
+        name: "GetProfileSectionA",
+        func: Handler::Sync(wrappers::GetProfileSectionA),
+    },
+    Shim {
+        name: "GetProfileSectionW",
+        func: Handler::Sync(wrappers::GetProfileSectionW),
+    },
+    Shim {
+        name: "GetProfileStringA",
+        func: Handler::Sync(wrappers::GetProfileStringA),
+    },
     Shim {
         name: "GetProfileStringW",
         func: Handler::Sync(wrappers::GetProfileStringW),
@@ -6291,6 +7291,26 @@ const SHIMS: [Shim; 230usize] = [
         name: "OpenMutexA",
         func: Handler::Sync(wrappers::OpenMutexA),
     },
+    Shim {
+        name: "OpenMutexW",
+        func: Handler::Sync(wrappers::OpenMutexW),
+    },
+    Shim {
+        name: "OpenSemaphoreA",
+        func: Handler::Sync(wrappers::OpenSemaphoreA),
+    },
+    Shim {
+        name: "OpenSemaphoreW",
+        func: Handler::Sync(wrappers::OpenSemaphoreW),
+    },
+    Shim {
+        name: "OpenWaitableTimerA",
+        func: Handler::Sync(wrappers::OpenWaitableTimerA),
+    },
+    Shim {
+        name: "OpenWaitableTimerW",
+        func: Handler::Sync(wrappers::OpenWaitableTimerW),
+    },
     Shim {
         name: "OutputDebugStringA",
         func: Handler::Sync(wrappers::OutputDebugStringA),
@@ -6329,7 +7349,11 @@ const SHIMS: [Shim; 230usize] = [
     },
     Shim {
         name: "ReadFile",
-        func: Handler::Sync(wrappers::ReadFile),
+        func: Handler::Async(wrappers::ReadFile),
+    },
+    Shim {
+        name: "ReleaseMutex",
+        func: Handler::Sync(wrappers::ReleaseMutex),
     },
     Shim {
         name: "ReleaseSRWLockExclusive",
@@ -6339,6 +7363,10 @@ const SHIMS: [Shim; 230usize] = [
         name: "ReleaseSRWLockShared",
         func: Handler::Sync(wrappers::ReleaseSRWLockShared),
     },
+    Shim {
+        name: "ReleaseSemaphore",
+        func: Handler::Sync(wrappers::ReleaseSemaphore),
+    },
     Shim {
         name: "RemoveDirectoryA",
         func: Handler::Sync(wrappers::RemoveDirectoryA),
@@ -6347,6 +7375,10 @@ const SHIMS: [Shim; 230usize] = [
         name: "RemoveDirectoryW",
         func: Handler::Sync(wrappers::RemoveDirectoryW),
     },
+    Shim {
+        name: "RemoveVectoredExceptionHandler",
+        func: Handler::Sync(wrappers::RemoveVectoredExceptionHandler),
+    },
     Shim {
         name: "ResetEvent",
         func: Handler::Sync(wrappers::ResetEvent),
@@ -6357,7 +7389,7 @@ const SHIMS: [Shim; 230usize] = [
     },
     Shim {
         name: "RtlUnwind",
-        func: Handler::Sync(wrappers::RtlUnwind),
+        func: Handler::Async(wrappers::RtlUnwind),
     },
     Shim {
         name: "SetConsoleCtrlHandler",
@@ -6447,6 +7479,14 @@ const SHIMS: [Shim; 230usize] = [
         name: "SetUnhandledExceptionFilter",
         func: Handler::Sync(wrappers::SetUnhandledExceptionFilter),
     },
+    Shim {
+        name: "SetWaitableTimer",
+        func: Handler::Sync(wrappers::SetWaitableTimer),
+    },
+    Shim {
+        name: "SignalObjectAndWait",
+        func: Handler::Async(wrappers::SignalObjectAndWait),
+    },
     Shim {
         name: "SizeofResource",
         func: Handler::Sync(wrappers::SizeofResource),
@@ -6483,9 +7523,13 @@ const SHIMS: [Shim; 230usize] = [
         name: "TryAcquireSRWLockExclusive",
         func: Handler::Sync(wrappers::TryAcquireSRWLockExclusive),
     },
+    Shim {
+        name: "TryEnterCriticalSection",
+        func: Handler::Sync(wrappers::TryEnterCriticalSection),
+    },
     Shim {
         name: "UnhandledExceptionFilter",
-        func: Handler::Sync(wrappers::UnhandledExceptionFilter),
+        func: Handler::Async(wrappers::UnhandledExceptionFilter),
     },
     Shim {
         name: "UnlockFile",
@@ -6525,16 +7569,40 @@ const SHIMS: [Shim; 230usize] = [
     },
     Shim {
         name: "WriteConsoleW",
-        func: Handler::Sync(wrappers::WriteConsoleW),
+        func: Handler::Async(wrappers::WriteConsoleW),
     },
     Shim {
         name: "WriteFile",
-        func: Handler::Sync(wrappers::WriteFile),
+        func: Handler::Async(wrappers::WriteFile),
+    },
+    Shim {
+        name: "WritePrivateProfileSectionA",
+        func: Handler::Sync(wrappers::WritePrivateProfileSectionA),
+    },
+    Shim {
+        name: "WritePrivateProfileSectionW",
+        func: Handler::Sync(wrappers::WritePrivateProfileSectionW),
     },
     Shim {
         name: "WritePrivateProfileStringA",
         func: Handler::Sync(wrappers::WritePrivateProfileStringA),
     },
+    Shim {
+        name: "WritePrivateProfileStringW",
+        func: Handler::Sync(wrappers::WritePrivateProfileStringW),
+    },
+    Shim {
+        name: "WriteProfileSectionA",
+        func: Handler::Sync(wrappers::WriteProfileSectionA),
+    },
+    Shim {
+        name: "WriteProfileSectionW",
+        func: Handler::Sync(wrappers::WriteProfileSectionW),
+    },
+    Shim {
+        name: "WriteProfileStringA",
+        func: Handler::Sync(wrappers::WriteProfileStringA),
+    },
     Shim {
         name: "WriteProfileStringW",
         func: Handler::Sync(wrappers::WriteProfileStringW),
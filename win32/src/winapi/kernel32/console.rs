@@ -77,10 +77,10 @@ pub fn WriteConsoleA(
 }
 
 #[win32_derive::dllexport]
-pub fn WriteConsoleW(
+pub async fn WriteConsoleW(
     machine: &mut Machine,
     hConsoleOutput: HFILE,
-    lpBuffer: ArrayWithSize<u16>,
+    lpBuffer: ArrayWithSize<'_, u16>,
     lpNumberOfCharsWritten: Option<&mut u32>,
     _lpReserved: u32,
 ) -> bool {
@@ -92,7 +92,9 @@ pub fn WriteConsoleW(
         Some(buf.as_bytes()),
         Some(&mut bytes_written),
         0,
-    ) {
+    )
+    .await
+    {
         return false;
     }
     if let Some(chars_written) = lpNumberOfCharsWritten {
@@ -191,6 +191,7 @@ pub fn load_library(machine: &mut Machine, filename: &str) -> HMODULE {
     }
 
     let hmodule = HMODULE::from_raw(dll.base);
+    let is_user32 = builtin.is_some() && filename == "user32.dll";
     machine.state.kernel32.dlls.insert(
         hmodule,
         DLL {
@@ -198,6 +199,10 @@ pub fn load_library(machine: &mut Machine, filename: &str) -> HMODULE {
             dll,
         },
     );
+    if is_user32 {
+        // The predefined window classes point at user32's exports, so must wait until now.
+        winapi::user32::register_system_classes(machine);
+    }
     hmodule
 }
 
@@ -276,13 +281,13 @@ pub struct STARTUPINFOA {
     dwXCountChars: DWORD,
     dwYCountChars: DWORD,
     dwFillAttribute: DWORD,
-    dwFlags: DWORD,
+    pub dwFlags: DWORD,
     wShowWindow: u16,
     cbReserved2: u16,
     lpReserved2: DWORD,
-    hStdInput: DWORD,
-    hStdOutput: DWORD,
-    hStdError: DWORD,
+    pub hStdInput: DWORD,
+    pub hStdOutput: DWORD,
+    pub hStdError: DWORD,
 }
 unsafe impl ::memory::Pod for STARTUPINFOA {}
 
@@ -0,0 +1,389 @@
+//! Structured exception handling (SEH).
+//!
+//! Exceptions come from either CPU faults (see Machine::run) or explicit calls like
+//! RaiseException.  Either way they end up in dispatch_exception, which runs on the
+//! faulting thread's stack, and which calls (in order) vectored handlers, the frame-based
+//! handlers found by walking the FS:[0] chain, and finally the unhandled exception filter.
+
+use super::{teb, teb_mut};
+use crate::{machine::Status, winapi::types::DWORD, Machine};
+use memory::{Extensions, ExtensionsMut, Pod};
+
+pub const STATUS_ACCESS_VIOLATION: u32 = 0xC000_0005;
+pub const STATUS_ILLEGAL_INSTRUCTION: u32 = 0xC000_001D;
+pub const STATUS_NONCONTINUABLE_EXCEPTION: u32 = 0xC000_0025;
+pub const STATUS_UNWIND: u32 = 0xC000_0027;
+pub const STATUS_INTEGER_DIVIDE_BY_ZERO: u32 = 0xC000_0094;
+/// The exception code used by MSVC for C++ exceptions, "\xE0msc".
+pub const STATUS_CXX_EXCEPTION: u32 = 0xE06D_7363;
+
+// EXCEPTION_RECORD.ExceptionFlags
+pub const EXCEPTION_NONCONTINUABLE: u32 = 0x1;
+pub const EXCEPTION_UNWINDING: u32 = 0x2;
+pub const EXCEPTION_EXIT_UNWIND: u32 = 0x4;
+
+// Return values of exception filters and vectored handlers.
+pub const EXCEPTION_EXECUTE_HANDLER: i32 = 1;
+pub const EXCEPTION_CONTINUE_SEARCH: i32 = 0;
+pub const EXCEPTION_CONTINUE_EXECUTION: i32 = -1;
+
+/// Return values of frame-based handlers.
+#[derive(Debug, win32_derive::TryFromEnum)]
+pub enum EXCEPTION_DISPOSITION {
+    ContinueExecution = 0,
+    ContinueSearch = 1,
+    NestedException = 2,
+    CollidedUnwind = 3,
+}
+
+/// Marks the end of the FS:[0] chain.
+pub const SEH_CHAIN_END: u32 = 0xFFFF_FFFF;
+/// Handler of the record at the bottom of every thread's SEH chain.
+/// It's never called; hitting it means the exception was unhandled.
+pub const SEH_SENTINEL_HANDLER: u32 = 0xFF5E_5EFF; // Hopefully easier to spot.
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EXCEPTION_REGISTRATION_RECORD {
+    pub Prev: u32,
+    pub Handler: u32,
+}
+unsafe impl Pod for EXCEPTION_REGISTRATION_RECORD {}
+
+const EXCEPTION_MAXIMUM_PARAMETERS: usize = 15;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EXCEPTION_RECORD {
+    pub ExceptionCode: DWORD,
+    pub ExceptionFlags: DWORD,
+    pub ExceptionRecord: DWORD,
+    pub ExceptionAddress: DWORD,
+    pub NumberParameters: DWORD,
+    pub ExceptionInformation: [DWORD; EXCEPTION_MAXIMUM_PARAMETERS],
+}
+unsafe impl Pod for EXCEPTION_RECORD {}
+
+impl EXCEPTION_RECORD {
+    pub fn new(code: u32, flags: u32, address: u32, params: &[u32]) -> Self {
+        let mut record = EXCEPTION_RECORD {
+            ExceptionCode: code,
+            ExceptionFlags: flags,
+            ExceptionAddress: address,
+            ..Default::default()
+        };
+        let count = params.len().min(EXCEPTION_MAXIMUM_PARAMETERS);
+        record.NumberParameters = count as u32;
+        record.ExceptionInformation[..count].copy_from_slice(&params[..count]);
+        record
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FLOATING_SAVE_AREA {
+    pub ControlWord: DWORD,
+    pub StatusWord: DWORD,
+    pub TagWord: DWORD,
+    pub ErrorOffset: DWORD,
+    pub ErrorSelector: DWORD,
+    pub DataOffset: DWORD,
+    pub DataSelector: DWORD,
+    pub RegisterArea: [u8; 80],
+    pub Cr0NpxState: DWORD,
+}
+
+// CONTEXT.ContextFlags
+#[allow(non_upper_case_globals)]
+pub const CONTEXT_i386: u32 = 0x0001_0000;
+pub const CONTEXT_CONTROL: u32 = CONTEXT_i386 | 0x1;
+pub const CONTEXT_INTEGER: u32 = CONTEXT_i386 | 0x2;
+pub const CONTEXT_SEGMENTS: u32 = CONTEXT_i386 | 0x4;
+/// ExtendedRegisters holds an FXSAVE image, which is where the SSE state lives.
+pub const CONTEXT_EXTENDED_REGISTERS: u32 = CONTEXT_i386 | 0x20;
+
+/// x86 register state, as seen by exception handlers.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CONTEXT {
+    pub ContextFlags: DWORD,
+    pub Dr0: DWORD,
+    pub Dr1: DWORD,
+    pub Dr2: DWORD,
+    pub Dr3: DWORD,
+    pub Dr6: DWORD,
+    pub Dr7: DWORD,
+    pub FloatSave: FLOATING_SAVE_AREA,
+    pub SegGs: DWORD,
+    pub SegFs: DWORD,
+    pub SegEs: DWORD,
+    pub SegDs: DWORD,
+    pub Edi: DWORD,
+    pub Esi: DWORD,
+    pub Ebx: DWORD,
+    pub Edx: DWORD,
+    pub Ecx: DWORD,
+    pub Eax: DWORD,
+    pub Ebp: DWORD,
+    pub Eip: DWORD,
+    pub SegCs: DWORD,
+    pub EFlags: DWORD,
+    pub Esp: DWORD,
+    pub SegSs: DWORD,
+    pub ExtendedRegisters: [u8; 512],
+}
+unsafe impl Pod for CONTEXT {}
+const _: () = assert!(std::mem::size_of::<CONTEXT>() == 0x2cc);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct EXCEPTION_POINTERS {
+    pub ExceptionRecord: u32,
+    pub ContextRecord: u32,
+}
+unsafe impl Pod for EXCEPTION_POINTERS {}
+
+/// Resume execution at a (possibly handler-modified) context.
+fn continue_execution(machine: &mut Machine, context_addr: u32) {
+    let context = machine.mem().get_pod::<CONTEXT>(context_addr);
+    machine.set_context(&context);
+}
+
+/// Give up on an exception that nothing handled.
+fn unhandled_exception(machine: &mut Machine, record: &EXCEPTION_RECORD, context_addr: u32) {
+    // Restore the faulting state, so that state dumps point at the problem.
+    continue_execution(machine, context_addr);
+    machine.status = Status::Error {
+        message: format!(
+            "unhandled exception {code:08x} at {addr:08x}",
+            code = record.ExceptionCode,
+            addr = record.ExceptionAddress
+        ),
+    };
+}
+
+/// The primitive beneath all exception delivery, the equivalent of ntdll's
+/// KiUserExceptionDispatcher.  Expects an EXCEPTION_POINTERS (and the structures it
+/// points to) to already be set up on the stack.  On completion either the CPU is
+/// set up to continue at the exception context, or the process has stopped.
+///
+/// If a handler decides to handle the exception it typically calls RtlUnwind and
+/// then jumps directly into the function that installed it, in which case this
+/// never completes; the CPU discards this future (along with those of any async
+/// shims whose x86 frames were unwound past) once it sees esp above them.
+pub async fn dispatch_exception(machine: &mut Machine, pointers_addr: u32) {
+    let pointers = machine.mem().get_pod::<EXCEPTION_POINTERS>(pointers_addr);
+    let record_addr = pointers.ExceptionRecord;
+    let context_addr = pointers.ContextRecord;
+    let record = machine.mem().get_pod::<EXCEPTION_RECORD>(record_addr);
+    log::warn!(
+        "exception {code:08x} at {addr:08x}",
+        code = record.ExceptionCode,
+        addr = record.ExceptionAddress
+    );
+
+    let handlers = machine.state.kernel32.vectored_exception_handlers.clone();
+    for handler in handlers {
+        let ret = machine.call_x86(handler, vec![pointers_addr]).await as i32;
+        if ret == EXCEPTION_CONTINUE_EXECUTION {
+            continue_execution(machine, context_addr);
+            return;
+        }
+    }
+
+    let mut frame = teb(machine).Tib.ExceptionList;
+    while frame != SEH_CHAIN_END && frame != 0 {
+        let registration = machine
+            .mem()
+            .get_pod::<EXCEPTION_REGISTRATION_RECORD>(frame);
+        if registration.Handler != SEH_SENTINEL_HANDLER {
+            let ret = machine
+                .call_x86(
+                    registration.Handler,
+                    vec![record_addr, frame, context_addr, 0],
+                )
+                .await;
+            match EXCEPTION_DISPOSITION::try_from(ret) {
+                Ok(EXCEPTION_DISPOSITION::ContinueExecution) => {
+                    if record.ExceptionFlags & EXCEPTION_NONCONTINUABLE != 0 {
+                        let record = EXCEPTION_RECORD::new(
+                            STATUS_NONCONTINUABLE_EXCEPTION,
+                            EXCEPTION_NONCONTINUABLE,
+                            record.ExceptionAddress,
+                            &[],
+                        );
+                        unhandled_exception(machine, &record, context_addr);
+                        return;
+                    }
+                    continue_execution(machine, context_addr);
+                    return;
+                }
+                Ok(EXCEPTION_DISPOSITION::ContinueSearch) => {}
+                disposition => {
+                    // Nested exceptions only arise from exceptions raised within handlers,
+                    // which we don't track.
+                    log::warn!("exception handler returned {disposition:?}, ignoring");
+                }
+            }
+        }
+        frame = registration.Prev;
+    }
+
+    let filter = machine.state.kernel32.unhandled_exception_filter;
+    if filter != 0 {
+        match machine.call_x86(filter, vec![pointers_addr]).await as i32 {
+            EXCEPTION_CONTINUE_EXECUTION => {
+                continue_execution(machine, context_addr);
+                return;
+            }
+            EXCEPTION_EXECUTE_HANDLER => {
+                // The default handler terminates the process with the exception code.
+                machine.exit(record.ExceptionCode);
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    unhandled_exception(machine, &record, context_addr);
+}
+
+/// Raise an exception from within a shim, as if the shim's caller had raised it
+/// at its return address.  `stack_consumed` is the size of the shim's stack arguments
+/// that the callee pops (i.e. 0 for cdecl).
+pub fn raise_from_shim(
+    machine: &mut Machine,
+    code: u32,
+    flags: u32,
+    params: &[u32],
+    stack_consumed: u32,
+) {
+    let mut context = machine.get_context();
+    // See doc/shims.md: the stack holds the return address within the shim dll,
+    // followed by the return address in the caller, followed by the args.
+    let return_address = machine.mem().get_pod::<u32>(context.Esp + 4);
+    context.Eip = return_address;
+    context.Esp += 8 + stack_consumed;
+    let record = EXCEPTION_RECORD::new(code, flags, return_address, params);
+    machine.raise_exception(&record, &context);
+}
+
+#[win32_derive::dllexport]
+pub fn RaiseException(
+    machine: &mut Machine,
+    dwExceptionCode: u32,
+    dwExceptionFlags: u32,
+    nNumberOfArguments: u32,
+    lpArguments: u32,
+) {
+    let params = machine
+        .mem()
+        .iter_pod::<u32>(lpArguments, nNumberOfArguments)
+        .collect::<Vec<_>>();
+    raise_from_shim(
+        machine,
+        dwExceptionCode,
+        dwExceptionFlags & EXCEPTION_NONCONTINUABLE,
+        &params,
+        16,
+    );
+}
+
+#[win32_derive::dllexport]
+pub async fn RtlUnwind(
+    machine: &mut Machine,
+    TargetFrame: u32,
+    TargetIp: u32,
+    ExceptionRecord: u32,
+    ReturnValue: u32,
+) -> u32 {
+    // TargetIp is typically the address just past the call to RtlUnwind, so returning
+    // to our caller gets us to the same place.
+    _ = TargetIp;
+
+    let mem = machine.emu.memory.mem();
+    let size = (std::mem::size_of::<EXCEPTION_RECORD>() + std::mem::size_of::<CONTEXT>()) as u32;
+    let scratch = machine.state.kernel32.process_heap.alloc(mem, size);
+    let record_addr = if ExceptionRecord != 0 {
+        ExceptionRecord
+    } else {
+        let addr = scratch;
+        mem.put_pod::<EXCEPTION_RECORD>(addr, EXCEPTION_RECORD::new(STATUS_UNWIND, 0, 0, &[]));
+        addr
+    };
+    let context_addr = scratch + std::mem::size_of::<EXCEPTION_RECORD>() as u32;
+    mem.put_pod::<CONTEXT>(context_addr, machine.get_context());
+
+    let record = mem.get_aligned_ref_mut::<EXCEPTION_RECORD>(record_addr);
+    record.ExceptionFlags |= EXCEPTION_UNWINDING;
+    if TargetFrame == 0 {
+        record.ExceptionFlags |= EXCEPTION_EXIT_UNWIND;
+    }
+
+    loop {
+        let frame = teb(machine).Tib.ExceptionList;
+        if frame == TargetFrame || frame == SEH_CHAIN_END || frame == 0 {
+            break;
+        }
+        let registration = machine
+            .mem()
+            .get_pod::<EXCEPTION_REGISTRATION_RECORD>(frame);
+        if registration.Handler != SEH_SENTINEL_HANDLER {
+            machine
+                .call_x86(
+                    registration.Handler,
+                    vec![record_addr, frame, context_addr, 0],
+                )
+                .await;
+        }
+        // Pop the frame.
+        teb_mut(machine).Tib.ExceptionList = registration.Prev;
+    }
+
+    machine
+        .state
+        .kernel32
+        .process_heap
+        .free(machine.emu.memory.mem(), scratch);
+    ReturnValue
+}
+
+#[win32_derive::dllexport]
+pub fn SetUnhandledExceptionFilter(machine: &mut Machine, lpTopLevelExceptionFilter: u32) -> u32 {
+    std::mem::replace(
+        &mut machine.state.kernel32.unhandled_exception_filter,
+        lpTopLevelExceptionFilter,
+    )
+}
+
+#[win32_derive::dllexport]
+pub async fn UnhandledExceptionFilter(machine: &mut Machine, ExceptionInfo: u32) -> i32 {
+    let filter = machine.state.kernel32.unhandled_exception_filter;
+    if filter == 0 {
+        return EXCEPTION_CONTINUE_SEARCH;
+    }
+    machine.call_x86(filter, vec![ExceptionInfo]).await as i32
+}
+
+#[win32_derive::dllexport]
+pub fn AddVectoredExceptionHandler(machine: &mut Machine, first: u32, handler: u32) -> u32 {
+    let handlers = &mut machine.state.kernel32.vectored_exception_handlers;
+    if first != 0 {
+        handlers.insert(0, handler);
+    } else {
+        handlers.push(handler);
+    }
+    handler // Handle used to remove it later.
+}
+
+#[win32_derive::dllexport]
+pub fn RemoveVectoredExceptionHandler(machine: &mut Machine, Handle: u32) -> u32 {
+    let handlers = &mut machine.state.kernel32.vectored_exception_handlers;
+    match handlers.iter().position(|&h| h == Handle) {
+        Some(index) => {
+            handlers.remove(index);
+            1
+        }
+        None => 0,
+    }
+}
@@ -1,4 +1,4 @@
-use super::{FILETIME, SECURITY_ATTRIBUTES};
+use super::{get_pipe, read_pipe, write_pipe, FILETIME, SECURITY_ATTRIBUTES};
 use crate::str16::String16;
 use crate::winapi::calling_convention::ABIReturn;
 use crate::winapi::kernel32::set_last_error;
@@ -28,19 +28,33 @@ pub const STDIN_HFILE: HFILE = HFILE::from_raw(0xF11E_0100);
 pub const STDOUT_HFILE: HFILE = HFILE::from_raw(0xF11E_0101);
 pub const STDERR_HFILE: HFILE = HFILE::from_raw(0xF11E_0102);
 
+impl STD {
+    /// Index into kernel32 State::std_handles.
+    pub fn index(&self) -> usize {
+        match self {
+            STD::INPUT_HANDLE => 0,
+            STD::OUTPUT_HANDLE => 1,
+            STD::ERROR_HANDLE => 2,
+        }
+    }
+}
+
 #[win32_derive::dllexport]
-pub fn GetStdHandle(_machine: &mut Machine, nStdHandle: Result<STD, u32>) -> HFILE {
+pub fn GetStdHandle(machine: &mut Machine, nStdHandle: Result<STD, u32>) -> HFILE {
     match nStdHandle {
-        Ok(STD::INPUT_HANDLE) => STDIN_HFILE,
-        Ok(STD::OUTPUT_HANDLE) => STDOUT_HFILE,
-        Ok(STD::ERROR_HANDLE) => STDERR_HFILE,
+        Ok(std) => machine.state.kernel32.std_handles[std.index()],
         _ => HFILE::invalid(),
     }
 }
 
 #[win32_derive::dllexport]
-pub fn SetStdHandle(_machine: &mut Machine, nStdHandle: Result<STD, u32>, hHandle: u32) -> bool {
-    true // succees
+pub fn SetStdHandle(machine: &mut Machine, nStdHandle: Result<STD, u32>, hHandle: HFILE) -> bool {
+    let Ok(std) = nStdHandle else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    machine.state.kernel32.std_handles[std.index()] = hHandle;
+    true
 }
 
 // https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-createfilea
@@ -218,7 +232,11 @@ pub fn CreateFileW(
 #[win32_derive::dllexport]
 pub fn GetFileType(machine: &mut Machine, hFile: HFILE) -> u32 {
     let FILE_TYPE_CHAR = 0x2;
+    let FILE_TYPE_PIPE = 0x3;
     let FILE_TYPE_UNKNOWN = 0x8;
+    if get_pipe(machine, hFile).is_some() {
+        return FILE_TYPE_PIPE;
+    }
     match hFile {
         STDIN_HFILE | STDOUT_HFILE | STDERR_HFILE => return FILE_TYPE_CHAR,
         _ => {}
@@ -344,10 +362,10 @@ pub fn SetFilePointer(
 }
 
 #[win32_derive::dllexport]
-pub fn ReadFile(
+pub async fn ReadFile(
     machine: &mut Machine,
     hFile: HFILE,
-    lpBuffer: ArrayWithSizeMut<u8>,
+    lpBuffer: ArrayWithSizeMut<'_, u8>,
     mut lpNumberOfBytesRead: Option<&mut u32>,
     lpOverlapped: u32,
 ) -> bool {
@@ -355,6 +373,25 @@ pub fn ReadFile(
     if let Some(bytes) = lpNumberOfBytesRead.as_deref_mut() {
         *bytes = 0;
     }
+    if let Some(pipe) = get_pipe(machine, hFile) {
+        let Some(buf) = lpBuffer.to_option() else {
+            set_last_error(machine, ERROR::INVALID_DATA);
+            return false;
+        };
+        return match read_pipe(machine, hFile, pipe, buf).await {
+            Ok(n) => {
+                set_last_error(machine, ERROR::SUCCESS);
+                if let Some(bytes) = lpNumberOfBytesRead {
+                    *bytes = n as u32;
+                }
+                true
+            }
+            Err(err) => {
+                set_last_error(machine, err);
+                false
+            }
+        };
+    }
     let Some(file) = (match hFile {
         STDIN_HFILE => unimplemented!("ReadFile(stdin)"),
         _ => machine.state.kernel32.files.get_mut(hFile),
@@ -396,10 +433,10 @@ pub fn ReadFile(
 }
 
 #[win32_derive::dllexport]
-pub fn WriteFile(
+pub async fn WriteFile(
     machine: &mut Machine,
     hFile: HFILE,
-    lpBuffer: ArrayWithSize<u8>,
+    lpBuffer: ArrayWithSize<'_, u8>,
     mut lpNumberOfBytesWritten: Option<&mut u32>,
     lpOverlapped: u32,
 ) -> bool {
@@ -416,6 +453,22 @@ pub fn WriteFile(
         return false;
     };
 
+    if let Some(pipe) = get_pipe(machine, hFile) {
+        return match write_pipe(machine, hFile, pipe, buf).await {
+            Ok(n) => {
+                set_last_error(machine, ERROR::SUCCESS);
+                if let Some(written) = lpNumberOfBytesWritten {
+                    *written = n as u32;
+                }
+                true
+            }
+            Err(err) => {
+                set_last_error(machine, err);
+                false
+            }
+        };
+    }
+
     let n = match hFile {
         STDOUT_HFILE | STDERR_HFILE => {
             machine.host.stdout(buf);
@@ -1,94 +1,778 @@
 //! Functions that work with .ini files.
 
 use crate::{
+    host,
     winapi::{calling_convention::ArrayWithSizeMut, types::Str16},
     Machine,
 };
+use memory::Extensions;
+use std::io::{Read, Write};
+use typed_path::WindowsPath;
+
+enum Line {
+    Pair {
+        key: String,
+        value: String,
+    },
+    /// Comments, blank lines, and anything else we preserve but ignore.
+    Other(String),
+}
+
+struct Section {
+    /// None for any lines that precede the first section header.
+    name: Option<String>,
+    lines: Vec<Line>,
+}
+
+impl Section {
+    fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Pair { key, value } => Some((key.as_str(), value.as_str())),
+            Line::Other(_) => None,
+        })
+    }
+
+    fn is_named(&self, name: &str) -> bool {
+        self.name
+            .as_deref()
+            .map_or(false, |n| n.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Parsed contents of an .ini file, preserving layout and comments for rewriting.
+#[derive(Default)]
+struct Ini {
+    sections: Vec<Section>,
+}
+
+impl Ini {
+    fn parse(text: &str) -> Self {
+        let mut sections = vec![Section {
+            name: None,
+            lines: Vec::new(),
+        }];
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if let Some(header) = trimmed.strip_prefix('[') {
+                let name = header.split(']').next().unwrap().trim();
+                sections.push(Section {
+                    name: Some(name.to_string()),
+                    lines: Vec::new(),
+                });
+                continue;
+            }
+            let parsed = match trimmed.split_once('=') {
+                Some((key, value)) if !trimmed.starts_with(';') => Line::Pair {
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                },
+                _ => Line::Other(line.to_string()),
+            };
+            sections.last_mut().unwrap().lines.push(parsed);
+        }
+        Ini { sections }
+    }
+
+    fn serialize(&self) -> String {
+        let mut out = String::new();
+        for section in &self.sections {
+            if let Some(name) = &section.name {
+                out.push_str(&format!("[{}]\r\n", name));
+            }
+            for line in &section.lines {
+                match line {
+                    Line::Pair { key, value } => out.push_str(&format!("{}={}\r\n", key, value)),
+                    Line::Other(text) => out.push_str(&format!("{}\r\n", text)),
+                }
+            }
+        }
+        out
+    }
+
+    fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.is_named(name))
+    }
+
+    fn section_names(&self) -> Vec<&str> {
+        self.sections
+            .iter()
+            .filter_map(|s| s.name.as_deref())
+            .collect()
+    }
+
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.section(section)?
+            .pairs()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    fn section_mut(&mut self, name: &str) -> &mut Section {
+        match self.sections.iter().position(|s| s.is_named(name)) {
+            Some(i) => &mut self.sections[i],
+            None => {
+                self.sections.push(Section {
+                    name: Some(name.to_string()),
+                    lines: Vec::new(),
+                });
+                self.sections.last_mut().unwrap()
+            }
+        }
+    }
+
+    fn set(&mut self, section: &str, key: &str, new_value: &str) {
+        let section = self.section_mut(section);
+        for line in section.lines.iter_mut() {
+            if let Line::Pair { key: k, value } = line {
+                if k.eq_ignore_ascii_case(key) {
+                    *value = new_value.to_string();
+                    return;
+                }
+            }
+        }
+        // Insert after the last entry, keeping any trailing blank lines at the end.
+        let pos = section
+            .lines
+            .iter()
+            .rposition(|line| matches!(line, Line::Pair { .. }))
+            .map_or(0, |i| i + 1);
+        section.lines.insert(
+            pos,
+            Line::Pair {
+                key: key.to_string(),
+                value: new_value.to_string(),
+            },
+        );
+    }
+
+    fn delete_key(&mut self, section: &str, key: &str) {
+        if let Some(section) = self.sections.iter_mut().find(|s| s.is_named(section)) {
+            section.lines.retain(
+                |line| !matches!(line, Line::Pair { key: k, .. } if k.eq_ignore_ascii_case(key)),
+            );
+        }
+    }
+
+    fn delete_section(&mut self, section: &str) {
+        self.sections.retain(|s| !s.is_named(section));
+    }
+
+    /// Replace the contents of a section with the given "key=value" lines.
+    fn set_section(&mut self, section: &str, entries: &[String]) {
+        let section = self.section_mut(section);
+        section.lines = Ini::parse(&entries.join("\n")).sections.remove(0).lines;
+    }
+}
+
+/// Resolve an .ini file name the way Windows does: bare names live in the Windows directory.
+fn ini_path(file_name: Option<&str>) -> String {
+    match file_name {
+        None => "C:\\Windows\\win.ini".into(),
+        Some(name) if !name.contains(['\\', '/', ':']) => format!("C:\\Windows\\{}", name),
+        Some(name) => name.into(),
+    }
+}
+
+fn read_ini(machine: &Machine, path: &str) -> Ini {
+    let Ok(mut file) = machine
+        .host
+        .open(WindowsPath::new(path), host::FileOptions::read())
+    else {
+        return Ini::default();
+    };
+    let mut buf = Vec::new();
+    if file.read_to_end(&mut buf).is_err() {
+        return Ini::default();
+    }
+    // .ini files are in the ANSI code page, which we treat as Latin-1.
+    Ini::parse(&buf.iter().map(|&b| b as char).collect::<String>())
+}
+
+fn write_ini(machine: &Machine, path: &str, ini: &Ini) -> bool {
+    let options = host::FileOptions {
+        write: true,
+        truncate: true,
+        create: true,
+        ..Default::default()
+    };
+    let bytes = ini
+        .serialize()
+        .chars()
+        .map(|c| if (c as u32) < 0x100 { c as u8 } else { b'?' })
+        .collect::<Vec<_>>();
+    let result = machine
+        .host
+        .open(WindowsPath::new(path), options)
+        .and_then(|mut file| Ok(file.write_all(&bytes)?));
+    if let Err(err) = result {
+        log::warn!("writing {}: {:?}", path, err);
+        return false;
+    }
+    true
+}
+
+/// Character types for the A and W variants of the profile functions.
+trait Char: Copy + From<u8> {
+    fn encode(s: &str) -> Vec<Self>;
+}
+
+impl Char for u8 {
+    fn encode(s: &str) -> Vec<Self> {
+        s.chars()
+            .map(|c| if (c as u32) < 0x100 { c as u8 } else { b'?' })
+            .collect()
+    }
+}
+
+impl Char for u16 {
+    fn encode(s: &str) -> Vec<Self> {
+        s.encode_utf16().collect()
+    }
+}
+
+/// Copy a string to dst, truncating as needed, returning the length copied excluding the nul.
+fn copy_string<C: Char>(dst: &mut [C], src: &str) -> u32 {
+    if dst.is_empty() {
+        return 0;
+    }
+    let src = C::encode(src);
+    let len = std::cmp::min(src.len(), dst.len() - 1);
+    dst[..len].copy_from_slice(&src[..len]);
+    dst[len] = C::from(0);
+    len as u32
+}
+
+/// Copy a list of strings to dst as a sequence of nul-terminated strings with an extra
+/// nul at the end.  Returns the length copied excluding the final nul; when truncated,
+/// the result is still double-nul terminated and the return value is dst.len() - 2.
+fn copy_list<'a, C: Char>(dst: &mut [C], items: impl Iterator<Item = &'a str>) -> u32 {
+    let mut out = Vec::new();
+    for item in items {
+        out.extend(C::encode(item));
+        out.push(C::from(0));
+    }
+    out.push(C::from(0));
+    if out.len() <= dst.len() {
+        dst[..out.len()].copy_from_slice(&out);
+        return out.len() as u32 - 1;
+    }
+    if dst.len() < 2 {
+        dst.fill(C::from(0));
+        return 0;
+    }
+    let len = dst.len() - 2;
+    dst[..len].copy_from_slice(&out[..len]);
+    dst[len] = C::from(0);
+    dst[len + 1] = C::from(0);
+    len as u32
+}
+
+fn get_string<C: Char>(
+    machine: &Machine,
+    app_name: Option<&str>,
+    key_name: Option<&str>,
+    default: Option<&str>,
+    dst: Option<&mut [C]>,
+    file_name: Option<&str>,
+) -> u32 {
+    let Some(dst) = dst else {
+        return 0;
+    };
+    let ini = read_ini(machine, &ini_path(file_name));
+    let Some(app_name) = app_name else {
+        return copy_list(dst, ini.section_names().into_iter());
+    };
+    let Some(key_name) = key_name else {
+        let keys = ini.section(app_name).into_iter().flat_map(|s| s.pairs());
+        return copy_list(dst, keys.map(|(k, _)| k));
+    };
+    match ini.get(app_name, key_name) {
+        Some(value) => {
+            // Matching quotes around a value are stripped.
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            copy_string(dst, value)
+        }
+        None => copy_string(dst, default.unwrap_or("").trim_end_matches(' ')),
+    }
+}
+
+fn get_int(
+    machine: &Machine,
+    app_name: Option<&str>,
+    key_name: Option<&str>,
+    default: u32,
+    file_name: Option<&str>,
+) -> u32 {
+    let (Some(app_name), Some(key_name)) = (app_name, key_name) else {
+        return default;
+    };
+    let ini = read_ini(machine, &ini_path(file_name));
+    let Some(value) = ini.get(app_name, key_name) else {
+        return default;
+    };
+    // Like strtol: an optional sign, then a hex or decimal prefix of the string.
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    let (radix, digits) = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => (16, hex),
+        None => (10, digits),
+    };
+    let end = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    let n = u32::from_str_radix(&digits[..end], radix).unwrap_or(0);
+    if negative {
+        n.wrapping_neg()
+    } else {
+        n
+    }
+}
+
+fn get_section<C: Char>(
+    machine: &Machine,
+    app_name: Option<&str>,
+    dst: Option<&mut [C]>,
+    file_name: Option<&str>,
+) -> u32 {
+    let (Some(app_name), Some(dst)) = (app_name, dst) else {
+        return 0;
+    };
+    let ini = read_ini(machine, &ini_path(file_name));
+    let lines = ini
+        .section(app_name)
+        .into_iter()
+        .flat_map(|s| s.pairs())
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>();
+    copy_list(dst, lines.iter().map(|l| l.as_str()))
+}
+
+fn write_string(
+    machine: &Machine,
+    app_name: Option<&str>,
+    key_name: Option<&str>,
+    string: Option<&str>,
+    file_name: Option<&str>,
+) -> bool {
+    let Some(app_name) = app_name else {
+        return false;
+    };
+    let path = ini_path(file_name);
+    let mut ini = read_ini(machine, &path);
+    match (key_name, string) {
+        (None, _) => ini.delete_section(app_name),
+        (Some(key_name), None) => ini.delete_key(app_name, key_name),
+        (Some(key_name), Some(string)) => ini.set(app_name, key_name, string),
+    }
+    write_ini(machine, &path, &ini)
+}
+
+fn write_section(
+    machine: &Machine,
+    app_name: Option<&str>,
+    entries: Option<Vec<String>>,
+    file_name: Option<&str>,
+) -> bool {
+    let Some(app_name) = app_name else {
+        return false;
+    };
+    let path = ini_path(file_name);
+    let mut ini = read_ini(machine, &path);
+    match entries {
+        None => ini.delete_section(app_name),
+        Some(entries) => ini.set_section(app_name, &entries),
+    }
+    write_ini(machine, &path, &ini)
+}
+
+/// Read a double-nul-terminated list of ANSI strings.
+fn read_list_a(machine: &Machine, mut addr: u32) -> Option<Vec<String>> {
+    if addr == 0 {
+        return None;
+    }
+    let mem = machine.mem();
+    let mut list = Vec::new();
+    loop {
+        let str = mem.slicez(addr);
+        if str.is_empty() {
+            return Some(list);
+        }
+        list.push(str.iter().map(|&b| b as char).collect());
+        addr += str.len() as u32 + 1;
+    }
+}
+
+/// Read a double-nul-terminated list of wide strings.
+fn read_list_w(machine: &Machine, mut addr: u32) -> Option<Vec<String>> {
+    if addr == 0 {
+        return None;
+    }
+    let mem = machine.mem();
+    let mut list = Vec::new();
+    loop {
+        let str = unsafe { Str16::from_nul_term_ptr(mem, addr) }.unwrap();
+        if str.len() == 0 {
+            return Some(list);
+        }
+        list.push(str.to_string());
+        addr += (str.len() as u32 + 1) * 2;
+    }
+}
+
+fn to_string(str: Option<&Str16>) -> Option<String> {
+    str.map(|s| s.to_string())
+}
+
+#[win32_derive::dllexport]
+pub fn GetPrivateProfileIntA(
+    machine: &mut Machine,
+    lpAppName: Option<&str>,
+    lpKeyName: Option<&str>,
+    nDefault: u32,
+    lpFileName: Option<&str>,
+) -> u32 {
+    get_int(machine, lpAppName, lpKeyName, nDefault, lpFileName)
+}
 
 #[win32_derive::dllexport]
 pub fn GetPrivateProfileIntW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpAppName: Option<&Str16>,
     lpKeyName: Option<&Str16>,
     nDefault: u32,
     lpFileName: Option<&Str16>,
 ) -> u32 {
-    nDefault // not found
+    get_int(
+        machine,
+        to_string(lpAppName).as_deref(),
+        to_string(lpKeyName).as_deref(),
+        nDefault,
+        to_string(lpFileName).as_deref(),
+    )
 }
 
 #[win32_derive::dllexport]
 pub fn GetPrivateProfileStringA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpAppName: Option<&str>,
     lpKeyName: Option<&str>,
     lpDefault: Option<&str>,
-    lpReturnedString: Option<&str>,
-    nSize: u32,
+    lpReturnedString: ArrayWithSizeMut<u8>,
     lpFileName: Option<&str>,
 ) -> u32 {
-    todo!()
+    get_string(
+        machine,
+        lpAppName,
+        lpKeyName,
+        lpDefault,
+        lpReturnedString.to_option(),
+        lpFileName,
+    )
 }
 
 #[win32_derive::dllexport]
 pub fn GetPrivateProfileStringW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpAppName: Option<&Str16>,
     lpKeyName: Option<&Str16>,
     lpDefault: Option<&Str16>,
     lpReturnedString: ArrayWithSizeMut<u16>,
     lpFileName: Option<&Str16>,
 ) -> u32 {
-    let dst = lpReturnedString.unwrap();
-    let src = lpDefault.unwrap();
-    let copy_len = std::cmp::min(dst.len() - 1, src.len());
-    dst[..copy_len].copy_from_slice(&src.buf()[..copy_len]);
-    dst[copy_len] = 0;
-    copy_len as u32
+    get_string(
+        machine,
+        to_string(lpAppName).as_deref(),
+        to_string(lpKeyName).as_deref(),
+        to_string(lpDefault).as_deref(),
+        lpReturnedString.to_option(),
+        to_string(lpFileName).as_deref(),
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn GetPrivateProfileSectionA(
+    machine: &mut Machine,
+    lpAppName: Option<&str>,
+    lpReturnedString: ArrayWithSizeMut<u8>,
+    lpFileName: Option<&str>,
+) -> u32 {
+    get_section(machine, lpAppName, lpReturnedString.to_option(), lpFileName)
+}
+
+#[win32_derive::dllexport]
+pub fn GetPrivateProfileSectionW(
+    machine: &mut Machine,
+    lpAppName: Option<&Str16>,
+    lpReturnedString: ArrayWithSizeMut<u16>,
+    lpFileName: Option<&Str16>,
+) -> u32 {
+    get_section(
+        machine,
+        to_string(lpAppName).as_deref(),
+        lpReturnedString.to_option(),
+        to_string(lpFileName).as_deref(),
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn GetPrivateProfileSectionNamesA(
+    machine: &mut Machine,
+    lpszReturnBuffer: ArrayWithSizeMut<u8>,
+    lpFileName: Option<&str>,
+) -> u32 {
+    get_string(
+        machine,
+        None,
+        None,
+        None,
+        lpszReturnBuffer.to_option(),
+        lpFileName,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn GetPrivateProfileSectionNamesW(
+    machine: &mut Machine,
+    lpszReturnBuffer: ArrayWithSizeMut<u16>,
+    lpFileName: Option<&Str16>,
+) -> u32 {
+    get_string(
+        machine,
+        None,
+        None,
+        None,
+        lpszReturnBuffer.to_option(),
+        to_string(lpFileName).as_deref(),
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn WritePrivateProfileStringA(
+    machine: &mut Machine,
+    lpAppName: Option<&str>,
+    lpKeyName: Option<&str>,
+    lpString: Option<&str>,
+    lpFileName: Option<&str>,
+) -> bool {
+    write_string(machine, lpAppName, lpKeyName, lpString, lpFileName)
+}
+
+#[win32_derive::dllexport]
+pub fn WritePrivateProfileStringW(
+    machine: &mut Machine,
+    lpAppName: Option<&Str16>,
+    lpKeyName: Option<&Str16>,
+    lpString: Option<&Str16>,
+    lpFileName: Option<&Str16>,
+) -> bool {
+    write_string(
+        machine,
+        to_string(lpAppName).as_deref(),
+        to_string(lpKeyName).as_deref(),
+        to_string(lpString).as_deref(),
+        to_string(lpFileName).as_deref(),
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn WritePrivateProfileSectionA(
+    machine: &mut Machine,
+    lpAppName: Option<&str>,
+    lpString: u32,
+    lpFileName: Option<&str>,
+) -> bool {
+    let entries = read_list_a(machine, lpString);
+    write_section(machine, lpAppName, entries, lpFileName)
+}
+
+#[win32_derive::dllexport]
+pub fn WritePrivateProfileSectionW(
+    machine: &mut Machine,
+    lpAppName: Option<&Str16>,
+    lpString: u32,
+    lpFileName: Option<&Str16>,
+) -> bool {
+    let entries = read_list_w(machine, lpString);
+    write_section(
+        machine,
+        to_string(lpAppName).as_deref(),
+        entries,
+        to_string(lpFileName).as_deref(),
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn GetProfileIntA(
+    machine: &mut Machine,
+    lpAppName: Option<&str>,
+    lpKeyName: Option<&str>,
+    nDefault: i32,
+) -> u32 {
+    get_int(machine, lpAppName, lpKeyName, nDefault as u32, None)
 }
 
 #[win32_derive::dllexport]
 pub fn GetProfileIntW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpAppName: Option<&Str16>,
     lpKeyName: Option<&Str16>,
     nDefault: i32,
 ) -> u32 {
-    nDefault as u32
+    get_int(
+        machine,
+        to_string(lpAppName).as_deref(),
+        to_string(lpKeyName).as_deref(),
+        nDefault as u32,
+        None,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn GetProfileStringA(
+    machine: &mut Machine,
+    lpAppName: Option<&str>,
+    lpKeyName: Option<&str>,
+    lpDefault: Option<&str>,
+    lpReturnedString: ArrayWithSizeMut<u8>,
+) -> u32 {
+    get_string(
+        machine,
+        lpAppName,
+        lpKeyName,
+        lpDefault,
+        lpReturnedString.to_option(),
+        None,
+    )
 }
 
 #[win32_derive::dllexport]
 pub fn GetProfileStringW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpAppName: Option<&Str16>,
     lpKeyName: Option<&Str16>,
     lpDefault: Option<&Str16>,
     lpReturnedString: ArrayWithSizeMut<u16>,
 ) -> u32 {
-    let dst = lpReturnedString.unwrap();
-    let src = lpDefault.unwrap();
-    let copy_len = std::cmp::min(dst.len() - 1, src.len());
-    dst[..copy_len].copy_from_slice(&src.buf()[..copy_len]);
-    dst[copy_len] = 0;
-    copy_len as u32
+    get_string(
+        machine,
+        to_string(lpAppName).as_deref(),
+        to_string(lpKeyName).as_deref(),
+        to_string(lpDefault).as_deref(),
+        lpReturnedString.to_option(),
+        None,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn GetProfileSectionA(
+    machine: &mut Machine,
+    lpAppName: Option<&str>,
+    lpReturnedString: ArrayWithSizeMut<u8>,
+) -> u32 {
+    get_section(machine, lpAppName, lpReturnedString.to_option(), None)
+}
+
+#[win32_derive::dllexport]
+pub fn GetProfileSectionW(
+    machine: &mut Machine,
+    lpAppName: Option<&Str16>,
+    lpReturnedString: ArrayWithSizeMut<u16>,
+) -> u32 {
+    get_section(
+        machine,
+        to_string(lpAppName).as_deref(),
+        lpReturnedString.to_option(),
+        None,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn WriteProfileStringA(
+    machine: &mut Machine,
+    lpAppName: Option<&str>,
+    lpKeyName: Option<&str>,
+    lpString: Option<&str>,
+) -> bool {
+    write_string(machine, lpAppName, lpKeyName, lpString, None)
 }
 
 #[win32_derive::dllexport]
 pub fn WriteProfileStringW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpAppName: Option<&Str16>,
     lpKeyName: Option<&Str16>,
     lpString: Option<&Str16>,
 ) -> bool {
-    todo!();
+    write_string(
+        machine,
+        to_string(lpAppName).as_deref(),
+        to_string(lpKeyName).as_deref(),
+        to_string(lpString).as_deref(),
+        None,
+    )
 }
 
 #[win32_derive::dllexport]
-pub fn WritePrivateProfileStringA(
-    _machine: &mut Machine,
-    lpAppName: Option<&str>,
-    lpKeyName: Option<&str>,
-    lpString: Option<&str>,
-    lpFileName: Option<&str>,
+pub fn WriteProfileSectionA(machine: &mut Machine, lpAppName: Option<&str>, lpString: u32) -> bool {
+    let entries = read_list_a(machine, lpString);
+    write_section(machine, lpAppName, entries, None)
+}
+
+#[win32_derive::dllexport]
+pub fn WriteProfileSectionW(
+    machine: &mut Machine,
+    lpAppName: Option<&Str16>,
+    lpString: u32,
 ) -> bool {
-    todo!()
+    let entries = read_list_w(machine, lpString);
+    write_section(machine, to_string(lpAppName).as_deref(), entries, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INI: &str =
+        "; comment\r\n[Video]\r\nWidth=640\r\nHeight = 480\r\n\r\n[Sound]\r\nVolume=\"loud\"\r\n";
+
+    #[test]
+    fn lookup() {
+        let ini = Ini::parse(INI);
+        assert_eq!(ini.get("video", "HEIGHT"), Some("480"));
+        assert_eq!(ini.get("Sound", "Volume"), Some("\"loud\""));
+        assert_eq!(ini.get("Sound", "Width"), None);
+        assert_eq!(ini.section_names(), vec!["Video", "Sound"]);
+    }
+
+    #[test]
+    fn rewrite() {
+        let mut ini = Ini::parse(INI);
+        ini.set("VIDEO", "Depth", "16");
+        ini.set("Video", "width", "800");
+        ini.delete_key("Sound", "Volume");
+        ini.set("Input", "Joystick", "1");
+        assert_eq!(
+            ini.serialize(),
+            "; comment\r\n[Video]\r\nWidth=800\r\nHeight=480\r\nDepth=16\r\n\r\n[Sound]\r\n[Input]\r\nJoystick=1\r\n"
+        );
+    }
+
+    #[test]
+    fn lists() {
+        let mut buf = [0xFFu8; 10];
+        assert_eq!(copy_list(&mut buf, ["ab", "cd"].into_iter()), 6);
+        assert_eq!(&buf[..7], b"ab\0cd\0\0");
+        let mut buf = [0xFFu8; 5];
+        assert_eq!(copy_list(&mut buf, ["ab", "cd"].into_iter()), 3);
+        assert_eq!(&buf, b"ab\0\0\0");
+    }
 }
@@ -1,8 +1,9 @@
 //! Process initialization and startup.
 
 use super::{
-    command_line::CommandLine, EventObject, FindHandle, Mappings, ResourceHandle, Thread, DLL,
-    HEVENT, HMODULE, STDERR_HFILE, STDOUT_HFILE,
+    command_line::CommandLine, exit_current_thread, ChildWindows, EventObject, FindHandle,
+    Mappings, MutexObject, PipeEnd, Process, ResourceHandle, SemaphoreObject, Thread, TimerObject,
+    Waiter, DLL, HEVENT, HMODULE, STDERR_HFILE, STDIN_HFILE, STDOUT_HFILE,
 };
 use crate::{
     machine::MemImpl,
@@ -13,7 +14,7 @@ use crate::{
 };
 use ::memory::Mem;
 use memory::{Extensions, ExtensionsMut};
-use std::{collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 #[repr(C)]
 pub struct UNICODE_STRING {
@@ -68,6 +69,14 @@ pub struct GDTEntries {
 pub enum KernelObject {
     Event(EventObject),
     Thread(Rc<Thread>),
+    Mutex(Rc<MutexObject>),
+    Semaphore(Rc<SemaphoreObject>),
+    Timer(Rc<TimerObject>),
+    Process(Rc<Process>),
+    /// One end of an anonymous pipe; these handles are used as HFILEs.
+    Pipe(PipeEnd),
+    /// Open registry key, identified by its full path.
+    Key(String),
 }
 
 type KernelObjects = Handles<HANDLE<()>, KernelObject>;
@@ -118,6 +127,24 @@ pub struct State {
     pub(super) env: u32,
 
     pub cmdline: CommandLine,
+
+    /// Set by SetUnhandledExceptionFilter, called when no SEH handler handles an exception.
+    pub unhandled_exception_filter: u32,
+    /// Handlers registered by AddVectoredExceptionHandler, in call order.
+    pub vectored_exception_handlers: Vec<u32>,
+
+    /// Threads blocked in wait_for_objects, in the order they started waiting.
+    pub waiters: Vec<Rc<Waiter>>,
+
+    /// ID of this process, as returned by GetCurrentProcessId.
+    pub process_id: u32,
+    /// Child processes that are still running.
+    pub processes: Vec<Rc<Process>>,
+    /// Host windows created by child processes, for routing host messages to them.
+    pub child_windows: Rc<RefCell<ChildWindows>>,
+
+    /// Handles returned by GetStdHandle, indexed by STD::index().
+    pub std_handles: [HFILE; 3],
 }
 
 impl State {
@@ -163,13 +190,21 @@ impl State {
             mappings,
             heaps: HashMap::new(),
             dlls,
-            objects: Default::default(),
+            // Distinct from the range used by files, so CloseHandle etc. can tell them apart.
+            objects: Handles::new(0x1000),
             files: Default::default(),
             find_handles: Default::default(),
             env: env_addr,
             cmdline: CommandLine::default(),
             resources: Default::default(),
             resource_handles: Default::default(),
+            unhandled_exception_filter: 0,
+            vectored_exception_handlers: Vec::new(),
+            waiters: Vec::new(),
+            process_id: 1,
+            processes: Vec::new(),
+            child_windows: Default::default(),
+            std_handles: [STDIN_HFILE, STDOUT_HFILE, STDERR_HFILE],
         }
     }
 
@@ -377,5 +412,5 @@ pub async fn retrowin32_main(machine: &mut Machine, entry_point: u32) {
 #[win32_derive::dllexport]
 pub async fn retrowin32_thread_main(machine: &mut Machine, entry_point: u32, param: u32) {
     machine.call_x86(entry_point, vec![param]).await;
-    machine.exit_thread();
+    exit_current_thread(machine);
 }
@@ -30,11 +30,6 @@ pub fn ExitProcess(machine: &mut Machine, uExitCode: u32) {
     machine.exit(uExitCode);
 }
 
-#[win32_derive::dllexport]
-pub fn TerminateProcess(_machine: &mut Machine, hProcess: u32, uExitCode: u32) -> bool {
-    todo!();
-}
-
 #[derive(Debug, win32_derive::TryFromEnum)]
 pub enum ProcessorFeature {
     FLOATING_POINT_PRECISION_ERRATA = 0,
@@ -100,8 +95,8 @@ pub fn DebugBreak(_machine: &mut Machine) {
 }
 
 #[win32_derive::dllexport]
-pub fn GetCurrentProcessId(_machine: &mut Machine) -> u32 {
-    1
+pub fn GetCurrentProcessId(machine: &mut Machine) -> u32 {
+    machine.state.kernel32.process_id
 }
 
 #[win32_derive::dllexport]
@@ -152,28 +147,6 @@ pub fn OutputDebugStringA(_machine: &mut Machine, msg: Option<&str>) -> u32 {
     0
 }
 
-#[win32_derive::dllexport]
-pub fn SetUnhandledExceptionFilter(_machine: &mut Machine, _lpTopLevelExceptionFilter: u32) -> u32 {
-    0 // No current handler.
-}
-
-#[win32_derive::dllexport]
-pub fn UnhandledExceptionFilter(_machine: &mut Machine, _exceptionInfo: u32) -> u32 {
-    // "The process is being debugged, so the exception should be passed (as second chance) to the application's debugger."
-    0 // EXCEPTION_CONTINUE_SEARCH
-}
-
-#[win32_derive::dllexport]
-pub fn RaiseException(
-    _machine: &mut Machine,
-    dwExceptionCode: u32,
-    dwExceptionFlags: u32,
-    nNumberOfArguments: u32,
-    lpArguments: u32,
-) {
-    todo!();
-}
-
 // TODO: this has a bunch of synchronization magic that I haven't implemented,
 // but I did at least make this struct the right size (128 bits).
 #[repr(C)]
@@ -199,11 +172,6 @@ pub fn SetPriorityClass(
     true // success
 }
 
-#[win32_derive::dllexport]
-pub fn AddVectoredExceptionHandler(_machine: &mut Machine, first: u32, handler: u32) -> u32 {
-    handler // success
-}
-
 bitflags! {
     #[derive(win32_derive::TryFromBitflags)]
     pub struct FormatMessageFlags: u32 {
@@ -259,7 +227,14 @@ pub fn FormatMessageW(
 
 #[win32_derive::dllexport]
 pub fn CloseHandle(machine: &mut Machine, hObject: HFILE) -> bool {
-    if machine.state.kernel32.files.remove(hObject).is_none() {
+    if machine.state.kernel32.files.remove(hObject).is_none()
+        && machine
+            .state
+            .kernel32
+            .objects
+            .remove(hObject.untyped())
+            .is_none()
+    {
         log::debug!("CloseHandle({hObject:?}): unknown handle");
         set_last_error(machine, ERROR::INVALID_HANDLE);
         return false;
@@ -350,17 +325,6 @@ pub fn MulDiv(_machine: &mut Machine, nNumber: i32, nNumerator: i32, nDenominato
     result as i32
 }
 
-#[win32_derive::dllexport]
-pub fn RtlUnwind(
-    _machine: &mut Machine,
-    TargetFrame: u32,
-    TargetIp: u32,
-    ExceptionRecord: u32,
-    ReturnValue: u32,
-) {
-    todo!();
-}
-
 #[win32_derive::dllexport]
 pub fn CompareStringA(
     _machine: &mut Machine,
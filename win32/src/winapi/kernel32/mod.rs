@@ -6,6 +6,7 @@ mod command_line;
 mod console;
 mod dll;
 mod env;
+mod exception;
 mod file;
 mod file16;
 mod ini;
@@ -28,6 +29,7 @@ pub use command_line::*;
 pub use console::*;
 pub use dll::*;
 pub use env::*;
+pub use exception::*;
 pub use file::*;
 pub use file16::*;
 pub use ini::*;
@@ -1,26 +1,253 @@
-use super::SECURITY_ATTRIBUTES;
-use crate::{winapi::types::HFILE, Machine};
+//! Anonymous pipes: a bounded in-memory buffer with a read end and a write end.
+//! Reads block while the pipe is empty and writes block while it's full, via the
+//! same wait machinery as WaitForSingleObject.
+
+use super::{
+    set_last_error, wait_for_objects, wake_waiters, KernelObject, SECURITY_ATTRIBUTES, WAIT_FAILED,
+    WAIT_OBJECT_0,
+};
+use crate::{
+    winapi::{calling_convention::ArrayWithSizeMut, types::HFILE, ERROR},
+    Machine,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    rc::Rc,
+};
+
+/// Buffer size used when CreatePipe is passed 0.
+const DEFAULT_PIPE_SIZE: usize = 4096;
+
+const INFINITE: u32 = 0xFFFF_FFFF;
+
+/// An anonymous pipe, shared between the read and write ends.
+pub struct Pipe {
+    buf: RefCell<VecDeque<u8>>,
+    capacity: usize,
+    /// Number of open handles to each end; when one side drops to zero, operations on the
+    /// other side fail rather than blocking forever.
+    readers: Cell<u32>,
+    writers: Cell<u32>,
+}
+
+impl Pipe {
+    fn ends(&self, write: bool) -> &Cell<u32> {
+        if write {
+            &self.writers
+        } else {
+            &self.readers
+        }
+    }
+}
+
+/// One end of a pipe, as found in the kernel object table.
+/// Pipes can be shared with child processes, so the same pipe may appear in multiple
+/// machines' object tables.  Each PipeEnd counts as an open handle to its end.
+pub struct PipeEnd {
+    pipe: Rc<Pipe>,
+    write: bool,
+}
+
+impl PipeEnd {
+    fn new(pipe: Rc<Pipe>, write: bool) -> Self {
+        let ends = pipe.ends(write);
+        ends.set(ends.get() + 1);
+        PipeEnd { pipe, write }
+    }
+
+    /// Whether the other end of the pipe has been closed.
+    fn is_broken(&self) -> bool {
+        self.pipe.ends(!self.write).get() == 0
+    }
+
+    /// Whether a read or write would make progress (or fail) without blocking.
+    pub fn is_ready(&self) -> bool {
+        let len = self.pipe.buf.borrow().len();
+        let progress = if self.write {
+            len < self.pipe.capacity
+        } else {
+            len > 0
+        };
+        progress || self.is_broken()
+    }
+
+    /// Number of bytes available to read.
+    fn available(&self) -> usize {
+        self.pipe.buf.borrow().len()
+    }
+
+    /// Read whatever data is available, up to the size of buf.
+    fn read(&self, buf: &mut [u8]) -> usize {
+        let mut data = self.pipe.buf.borrow_mut();
+        let n = std::cmp::min(buf.len(), data.len());
+        for (dst, src) in buf.iter_mut().zip(data.drain(..n)) {
+            *dst = src;
+        }
+        n
+    }
+
+    /// Copy out data without consuming it.
+    fn peek(&self, buf: &mut [u8]) -> usize {
+        let data = self.pipe.buf.borrow();
+        let n = std::cmp::min(buf.len(), data.len());
+        for (dst, src) in buf.iter_mut().zip(data.iter()) {
+            *dst = *src;
+        }
+        n
+    }
+
+    /// Write as much of buf as fits.
+    fn write(&self, buf: &[u8]) -> usize {
+        let mut data = self.pipe.buf.borrow_mut();
+        let n = std::cmp::min(buf.len(), self.pipe.capacity - data.len());
+        data.extend(&buf[..n]);
+        n
+    }
+}
+
+impl Clone for PipeEnd {
+    fn clone(&self) -> Self {
+        PipeEnd::new(self.pipe.clone(), self.write)
+    }
+}
+
+impl Drop for PipeEnd {
+    fn drop(&mut self) {
+        let ends = self.pipe.ends(self.write);
+        ends.set(ends.get() - 1);
+    }
+}
+
+/// Look up a file handle that refers to a pipe.
+pub fn get_pipe(machine: &Machine, handle: HFILE) -> Option<PipeEnd> {
+    match machine.state.kernel32.objects.get(handle.untyped()) {
+        Some(KernelObject::Pipe(end)) => Some(end.clone()),
+        _ => None,
+    }
+}
+
+/// Block until the pipe end is ready for reading/writing, returning the wait result.
+/// The wait fails if the handle is closed while blocked.
+async fn wait_ready(machine: &mut Machine, handle: HFILE, pipe: &PipeEnd) -> u32 {
+    if pipe.is_ready() {
+        return WAIT_OBJECT_0;
+    }
+    wait_for_objects(machine, &[handle.untyped()], false, INFINITE).await
+}
+
+/// ReadFile on a pipe: wait for some data, then return what's available.
+/// Returns Ok(0) only for zero-length reads; once the pipe is empty and the write end
+/// is closed, returns ERROR::BROKEN_PIPE like Windows does.
+pub async fn read_pipe(
+    machine: &mut Machine,
+    handle: HFILE,
+    pipe: PipeEnd,
+    buf: &mut [u8],
+) -> Result<usize, ERROR> {
+    if pipe.write {
+        return Err(ERROR::ACCESS_DENIED);
+    }
+    if buf.is_empty() {
+        return Ok(0);
+    }
+    if wait_ready(machine, handle, &pipe).await == WAIT_FAILED {
+        return Err(ERROR::INVALID_HANDLE);
+    }
+    let n = pipe.read(buf);
+    if n == 0 {
+        return Err(ERROR::BROKEN_PIPE);
+    }
+    // A writer may be waiting for space.
+    wake_waiters(machine);
+    Ok(n)
+}
+
+/// WriteFile on a pipe: block until all of buf has been written.
+pub async fn write_pipe(
+    machine: &mut Machine,
+    handle: HFILE,
+    pipe: PipeEnd,
+    buf: &[u8],
+) -> Result<usize, ERROR> {
+    if !pipe.write {
+        return Err(ERROR::ACCESS_DENIED);
+    }
+    let mut written = 0;
+    while written < buf.len() {
+        if wait_ready(machine, handle, &pipe).await == WAIT_FAILED {
+            return Err(ERROR::INVALID_HANDLE);
+        }
+        if pipe.is_broken() {
+            return Err(ERROR::NO_DATA);
+        }
+        written += pipe.write(&buf[written..]);
+        // A reader may be waiting for data.
+        wake_waiters(machine);
+    }
+    Ok(written)
+}
 
 #[win32_derive::dllexport]
 pub fn CreatePipe(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hReadPipe: Option<&mut HFILE>,
     hWritePipe: Option<&mut HFILE>,
     lpPipeAttributes: Option<&mut SECURITY_ATTRIBUTES>,
     nSize: u32,
 ) -> bool {
-    todo!()
+    let (Some(hReadPipe), Some(hWritePipe)) = (hReadPipe, hWritePipe) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let pipe = Rc::new(Pipe {
+        buf: Default::default(),
+        capacity: if nSize == 0 {
+            DEFAULT_PIPE_SIZE
+        } else {
+            nSize as usize
+        },
+        readers: Cell::new(0),
+        writers: Cell::new(0),
+    });
+    let objects = &mut machine.state.kernel32.objects;
+    let read = objects.add(KernelObject::Pipe(PipeEnd::new(pipe.clone(), false)));
+    let write = objects.add(KernelObject::Pipe(PipeEnd::new(pipe, true)));
+    *hReadPipe = HFILE::from_raw(read.to_raw());
+    *hWritePipe = HFILE::from_raw(write.to_raw());
+    set_last_error(machine, ERROR::SUCCESS);
+    true
 }
 
 #[win32_derive::dllexport]
 pub fn PeekNamedPipe(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hNamedPipe: HFILE,
-    lpBuffer: Option<&mut u32>, // TODO
-    nBufferSize: u32,
+    lpBuffer: ArrayWithSizeMut<u8>,
     lpBytesRead: Option<&mut u32>,
     lpTotalBytesAvail: Option<&mut u32>,
     lpBytesLeftThisMessage: Option<&mut u32>,
 ) -> bool {
-    todo!()
+    let Some(pipe) = get_pipe(machine, hNamedPipe) else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    let available = pipe.available();
+    if available == 0 && pipe.is_broken() {
+        set_last_error(machine, ERROR::BROKEN_PIPE);
+        return false;
+    }
+    let read = lpBuffer.to_option().map_or(0, |buf| pipe.peek(buf));
+    if let Some(bytes) = lpBytesRead {
+        *bytes = read as u32;
+    }
+    if let Some(bytes) = lpTotalBytesAvail {
+        *bytes = available as u32;
+    }
+    // Anonymous pipes are byte streams, without messages.
+    if let Some(bytes) = lpBytesLeftThisMessage {
+        *bytes = 0;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
 }
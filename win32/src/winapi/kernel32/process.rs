@@ -1,11 +1,368 @@
-use super::STARTUPINFOA;
-use crate::{str16::Str16, winapi::types::HANDLE, Machine};
+//! Child processes, each run in its own nested Machine that shares the parent's host.
+
+use super::{
+    current_thread, get_pipe, set_last_error, wake_waiters, CommandLine, KernelObject,
+    STARTUPINFOA, STD,
+};
+use crate::{
+    host::{self, Host},
+    str16::Str16,
+    winapi::{
+        types::{HANDLE, HFILE},
+        ERROR,
+    },
+    Machine, Status,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    rc::{Rc, Weak},
+};
+use typed_path::{WindowsPath, WindowsPathBuf};
 
 pub type HPROCESS = HANDLE<()>;
 
 // MSDN: "A pseudo handle is a special constant, currently (HANDLE)-1, that is interpreted as the current process handle."
 pub const CURRENT_PROCESS_HANDLE: HPROCESS = HPROCESS::from_raw(-1i32 as u32);
 
+const STILL_ACTIVE: u32 = 259;
+const STARTF_USESTDHANDLES: u32 = 0x100;
+const CREATE_SUSPENDED: u32 = 0x4;
+
+/// A child process started by CreateProcess.
+pub struct Process {
+    pub id: u32,
+    /// The child's machine, while it's still running.
+    machine: RefCell<Option<Box<Machine>>>,
+    exit_code: Cell<Option<u32>>,
+}
+
+impl Process {
+    /// Whether the process has exited, which is when it becomes signaled.
+    pub fn is_exited(&self) -> bool {
+        self.exit_code.get().is_some()
+    }
+
+    fn exit(&self, exit_code: u32) {
+        self.exit_code.set(Some(exit_code));
+        // Drop the child machine and its memory.
+        self.machine.borrow_mut().take();
+    }
+}
+
+/// Host messages received by the parent but destined for a child's windows.
+type Inbox = Rc<RefCell<VecDeque<host::Message>>>;
+type WeakInbox = Weak<RefCell<VecDeque<host::Message>>>;
+
+/// The host windows created by a machine's child processes.
+/// Each machine numbers its windows from the same start, so child windows are given
+/// host-side ids from a separate range, which also identify the child to route to.
+#[derive(Default)]
+pub struct ChildWindows {
+    next: u32,
+    /// Host-side id => the child's inbox and its own hwnd.
+    routes: HashMap<u32, (WeakInbox, u32)>,
+}
+
+impl ChildWindows {
+    /// Start of the range of host-side ids, above any hwnd the parent allocates.
+    const BASE: u32 = 0x4000_0000;
+
+    fn add(&mut self, inbox: &Inbox, hwnd: u32) -> u32 {
+        let id = Self::BASE + self.next;
+        self.next += 1;
+        self.routes.insert(id, (Rc::downgrade(inbox), hwnd));
+        id
+    }
+}
+
+/// Pass a host message on to the child process that owns its window, if any.
+/// Returns the message if it is for the machine itself.
+pub fn route_host_message(machine: &Machine, msg: host::Message) -> Option<host::Message> {
+    let mut child_windows = machine.state.kernel32.child_windows.borrow_mut();
+    let Some((inbox, hwnd)) = child_windows.routes.get(&msg.hwnd) else {
+        return Some(msg);
+    };
+    match inbox.upgrade() {
+        Some(inbox) => inbox
+            .borrow_mut()
+            .push_back(host::Message { hwnd: *hwnd, ..msg }),
+        // The child has exited.
+        None => _ = child_windows.routes.remove(&msg.hwnd),
+    }
+    None
+}
+
+/// Host for a child process, which forwards to the parent's host but never blocks on it,
+/// because the parent and any other children must keep running.
+/// The child only sees host messages for its own windows, which the parent routes to it.
+struct ChildHost {
+    host: Box<dyn Host>,
+    child_windows: Rc<RefCell<ChildWindows>>,
+    inbox: Inbox,
+}
+
+impl ChildHost {
+    /// The host-side id for one of the child's windows.
+    fn host_hwnd(&self, hwnd: u32) -> u32 {
+        let mut child_windows = self.child_windows.borrow_mut();
+        let existing = child_windows
+            .routes
+            .iter()
+            .find(|(_, (inbox, h))| *h == hwnd && inbox.as_ptr() == Rc::as_ptr(&self.inbox))
+            .map(|(&id, _)| id);
+        existing.unwrap_or_else(|| child_windows.add(&self.inbox, hwnd))
+    }
+}
+
+impl host::FileSystem for ChildHost {
+    fn current_dir(&self) -> Result<WindowsPathBuf, ERROR> {
+        self.host.current_dir()
+    }
+    fn open(
+        &self,
+        path: &WindowsPath,
+        options: host::FileOptions,
+    ) -> Result<Box<dyn host::File>, ERROR> {
+        self.host.open(path, options)
+    }
+    fn stat(&self, path: &WindowsPath) -> Result<host::Stat, ERROR> {
+        self.host.stat(path)
+    }
+    fn read_dir(&self, path: &WindowsPath) -> Result<Box<dyn host::ReadDir>, ERROR> {
+        self.host.read_dir(path)
+    }
+    fn create_dir(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        self.host.create_dir(path)
+    }
+    fn remove_file(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        self.host.remove_file(path)
+    }
+    fn remove_dir(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        self.host.remove_dir(path)
+    }
+}
+
+impl Host for ChildHost {
+    fn ticks(&self) -> u32 {
+        self.host.ticks()
+    }
+    fn system_time(&self) -> chrono::DateTime<chrono::Local> {
+        self.host.system_time()
+    }
+    fn get_message(&self) -> Option<host::Message> {
+        self.inbox.borrow_mut().pop_front()
+    }
+    fn block(&self, wait: Option<u32>) -> bool {
+        // Report whether the wait is already over; otherwise the child is left Blocked and
+        // run_child_processes retries it later.
+        wait.is_some_and(|until| self.host.ticks() >= until)
+    }
+    fn stdout(&self, buf: &[u8]) {
+        self.host.stdout(buf)
+    }
+    fn create_window(&mut self, hwnd: u32) -> Box<dyn host::Window> {
+        let hwnd = self.host_hwnd(hwnd);
+        self.host.create_window(hwnd)
+    }
+    fn create_surface(&mut self, hwnd: u32, opts: &host::SurfaceOptions) -> Box<dyn host::Surface> {
+        let hwnd = self.host_hwnd(hwnd);
+        self.host.create_surface(hwnd, opts)
+    }
+    fn init_audio(&mut self, sample_rate: u32) -> Box<dyn host::Audio> {
+        self.host.init_audio(sample_rate)
+    }
+    fn clone_box(&self) -> Box<dyn Host> {
+        Box::new(ChildHost {
+            host: self.host.clone_box(),
+            child_windows: self.child_windows.clone(),
+            inbox: self.inbox.clone(),
+        })
+    }
+}
+
+/// The outcome of a step of run_child_processes.
+pub enum ChildProgress {
+    /// Some child ran, so the parent shouldn't block the host.
+    Running,
+    /// Every child (if any) is blocked, until the given time or forever if None.
+    Blocked(Option<u32>),
+}
+
+/// Run each child process for a step.  Called from the parent's run loop.
+#[cfg(feature = "x86-emu")]
+pub fn run_child_processes(machine: &mut Machine) -> ChildProgress {
+    if machine.state.kernel32.processes.is_empty() {
+        return ChildProgress::Blocked(None);
+    }
+    let mut progress = ChildProgress::Blocked(None);
+    for process in machine.state.kernel32.processes.clone() {
+        let exit_code = {
+            let mut child = process.machine.borrow_mut();
+            let child = child.as_mut().unwrap();
+            if matches!(child.status, Status::Blocked) {
+                // Objects the child is waiting on may have been signaled from outside,
+                // e.g. by the parent writing to a pipe or routing it input, and its
+                // timeouts may have passed.
+                crate::winapi::user32::fetch_host_messages(child);
+                wake_waiters(child);
+                child.status = Status::Running;
+            }
+            if child.run() {
+                progress = ChildProgress::Running;
+                continue;
+            }
+            match &child.status {
+                Status::Running => {
+                    progress = ChildProgress::Running;
+                    continue;
+                }
+                Status::Blocked => {
+                    if let (ChildProgress::Blocked(until), x86::CPUState::Blocked(wait)) =
+                        (&mut progress, &child.emu.x86.cpu().state)
+                    {
+                        *until = match (*until, *wait) {
+                            (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+                            (a, b) => a.or(b),
+                        };
+                    }
+                    continue;
+                }
+                Status::Exit(code) => *code,
+                Status::Error { message } => {
+                    log::error!("process {:x}: {message}", process.id);
+                    1
+                }
+                Status::DebugBreak => {
+                    log::error!("process {:x}: unexpected breakpoint", process.id);
+                    1
+                }
+            }
+        };
+        process.exit(exit_code);
+        progress = ChildProgress::Running;
+        machine
+            .state
+            .kernel32
+            .processes
+            .retain(|p| !Rc::ptr_eq(p, &process));
+        wake_waiters(machine);
+    }
+    if !machine.state.kernel32.waiters.is_empty() {
+        // The children may have signaled something shared with us, like a pipe.
+        wake_waiters(machine);
+    }
+    progress
+}
+
+fn read_exe(machine: &Machine, exe: &str) -> Option<Vec<u8>> {
+    let mut paths = vec![exe.to_string()];
+    let file_name = exe.rsplit('\\').next().unwrap();
+    if !file_name.contains('.') {
+        paths.push(format!("{exe}.exe"));
+    }
+    for path in paths {
+        let Ok(mut file) = machine
+            .host
+            .open(WindowsPath::new(&path), host::FileOptions::read())
+        else {
+            continue;
+        };
+        let mut buf = Vec::new();
+        if file.read_to_end(&mut buf).is_ok() {
+            return Some(buf);
+        }
+    }
+    None
+}
+
+/// The std handles of the parent that should be given to the child.
+struct StdHandles {
+    input: HFILE,
+    output: HFILE,
+    error: HFILE,
+}
+
+fn create_process(
+    machine: &mut Machine,
+    app_name: Option<String>,
+    cmdline: Option<String>,
+    creation_flags: u32,
+    std_handles: Option<StdHandles>,
+    info: Option<&mut PROCESS_INFORMATION>,
+) -> bool {
+    let Some(cmdline) = cmdline.or_else(|| app_name.clone()) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let exe = app_name.unwrap_or_else(|| CommandLine::new(cmdline.clone()).exe_name());
+    let Some(buf) = read_exe(machine, &exe) else {
+        log::warn!("CreateProcess({exe:?}): not found");
+        set_last_error(machine, ERROR::FILE_NOT_FOUND);
+        return false;
+    };
+    if creation_flags & CREATE_SUSPENDED != 0 {
+        log::warn!("CreateProcess: CREATE_SUSPENDED unimplemented, running immediately");
+    }
+
+    let host = Box::new(ChildHost {
+        host: machine.host.clone_box(),
+        child_windows: machine.state.kernel32.child_windows.clone(),
+        inbox: Default::default(),
+    });
+    let mut child = Box::new(Machine::new(host));
+    child.external_dlls = machine.external_dlls.clone();
+    child.state.winmm.audio_enabled = machine.state.winmm.audio_enabled;
+    child.state.winmm.mci.cd_audio_dir = machine.state.winmm.mci.cd_audio_dir.clone();
+    if let Err(err) = child.load_exe(&buf, cmdline, None) {
+        log::warn!("CreateProcess({exe:?}): {err}");
+        set_last_error(machine, ERROR::BAD_EXE_FORMAT);
+        return false;
+    }
+
+    // Pipes are the only handles worth passing on; anything else means the console,
+    // which the child shares anyway.
+    if let Some(std_handles) = std_handles {
+        for (std, handle) in [
+            (STD::INPUT_HANDLE, std_handles.input),
+            (STD::OUTPUT_HANDLE, std_handles.output),
+            (STD::ERROR_HANDLE, std_handles.error),
+        ] {
+            if let Some(pipe) = get_pipe(machine, handle) {
+                let handle = child.state.kernel32.objects.add(KernelObject::Pipe(pipe));
+                child.state.kernel32.std_handles[std.index()] = HFILE::from_raw(handle.to_raw());
+            }
+        }
+    }
+
+    let thread_id = current_thread(&child).to_raw();
+    let objects = &mut machine.state.kernel32.objects;
+    let hProcess = objects.reserve();
+    // We don't model the child's threads in the parent, so the thread handle refers to
+    // the process too; it's signaled when the process exits.
+    let hThread = objects.reserve();
+    child.state.kernel32.process_id = hProcess.to_raw();
+    let process = Rc::new(Process {
+        id: hProcess.to_raw(),
+        machine: RefCell::new(Some(child)),
+        exit_code: Cell::new(None),
+    });
+    objects.set(hProcess, KernelObject::Process(process.clone()));
+    objects.set(hThread, KernelObject::Process(process.clone()));
+    machine.state.kernel32.processes.push(process.clone());
+
+    if let Some(info) = info {
+        *info = PROCESS_INFORMATION {
+            hProcess,
+            hThread,
+            dwProcessId: process.id,
+            dwThreadId: thread_id,
+        };
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
 #[win32_derive::dllexport]
 pub fn GetCurrentProcess(_machine: &mut Machine) -> HPROCESS {
     CURRENT_PROCESS_HANDLE
@@ -13,21 +370,79 @@ pub fn GetCurrentProcess(_machine: &mut Machine) -> HPROCESS {
 
 #[win32_derive::dllexport]
 pub fn GetExitCodeProcess(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hProcess: HPROCESS,
     lpExitCode: Option<&mut u32>,
 ) -> bool {
-    todo!()
+    let exit_code = if hProcess == CURRENT_PROCESS_HANDLE {
+        STILL_ACTIVE
+    } else {
+        match machine.state.kernel32.objects.get(hProcess) {
+            Some(KernelObject::Process(process)) => process.exit_code.get().unwrap_or(STILL_ACTIVE),
+            _ => {
+                set_last_error(machine, ERROR::INVALID_HANDLE);
+                return false;
+            }
+        }
+    };
+    *lpExitCode.unwrap() = exit_code;
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn TerminateProcess(machine: &mut Machine, hProcess: HPROCESS, uExitCode: u32) -> bool {
+    if hProcess == CURRENT_PROCESS_HANDLE {
+        machine.exit(uExitCode);
+        return true;
+    }
+    let process = match machine.state.kernel32.objects.get(hProcess) {
+        Some(KernelObject::Process(process)) => process.clone(),
+        _ => {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            return false;
+        }
+    };
+    if !process.is_exited() {
+        process.exit(uExitCode);
+        machine
+            .state
+            .kernel32
+            .processes
+            .retain(|p| !Rc::ptr_eq(p, &process));
+        wake_waiters(machine);
+    }
+    true
 }
 
 pub type SECURITY_ATTRIBUTES = u32; // TODO
-pub type PROCESS_INFORMATION = u32; // TODO
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct PROCESS_INFORMATION {
+    pub hProcess: HPROCESS,
+    pub hThread: HANDLE<()>,
+    pub dwProcessId: u32,
+    pub dwThreadId: u32,
+}
+unsafe impl ::memory::Pod for PROCESS_INFORMATION {}
 
 pub type STARTUPINFOW = STARTUPINFOA; // TODO: same layout, different strings
 
+fn std_handles(info: Option<&STARTUPINFOA>) -> Option<StdHandles> {
+    let info = info?;
+    if info.dwFlags & STARTF_USESTDHANDLES == 0 {
+        return None;
+    }
+    Some(StdHandles {
+        input: HFILE::from_raw(info.hStdInput),
+        output: HFILE::from_raw(info.hStdOutput),
+        error: HFILE::from_raw(info.hStdError),
+    })
+}
+
 #[win32_derive::dllexport]
 pub fn CreateProcessW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpApplicationName: Option<&Str16>,
     lpCommandLine: Option<&Str16>,
     lpProcessAttributes: Option<&mut SECURITY_ATTRIBUTES>,
@@ -39,12 +454,22 @@ pub fn CreateProcessW(
     lpStartupInfo: Option<&mut STARTUPINFOW>,
     lpProcessInformation: Option<&mut PROCESS_INFORMATION>,
 ) -> bool {
-    todo!()
+    if lpCurrentDirectory.is_some() {
+        log::warn!("CreateProcessW: ignoring lpCurrentDirectory");
+    }
+    create_process(
+        machine,
+        lpApplicationName.map(|s| s.to_string()),
+        lpCommandLine.map(|s| s.to_string()),
+        dwCreationFlags,
+        std_handles(lpStartupInfo.as_deref()),
+        lpProcessInformation,
+    )
 }
 
 #[win32_derive::dllexport]
 pub fn CreateProcessA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpApplicationName: Option<&str>,
     lpCommandLine: Option<&str>,
     lpProcessAttributes: Option<&mut SECURITY_ATTRIBUTES>,
@@ -56,5 +481,15 @@ pub fn CreateProcessA(
     lpStartupInfo: Option<&mut STARTUPINFOA>,
     lpProcessInformation: Option<&mut PROCESS_INFORMATION>,
 ) -> bool {
-    todo!()
+    if lpCurrentDirectory.is_some() {
+        log::warn!("CreateProcessA: ignoring lpCurrentDirectory");
+    }
+    create_process(
+        machine,
+        lpApplicationName.map(|s| s.to_string()),
+        lpCommandLine.map(|s| s.to_string()),
+        dwCreationFlags,
+        std_handles(lpStartupInfo.as_deref()),
+        lpProcessInformation,
+    )
 }
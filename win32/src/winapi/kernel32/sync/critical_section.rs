@@ -1,42 +1,166 @@
-use crate::Machine;
+//! Critical sections, with their state kept in the guest's CRITICAL_SECTION struct
+//! following the layout (and LockCount convention) of RTL_CRITICAL_SECTION.
+
+use super::{wait_for_objects, EventObject, HEVENT};
+use crate::{
+    winapi::kernel32::{current_thread, KernelObject},
+    Machine,
+};
+use memory::Extensions;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct CRITICAL_SECTION {
+    pub DebugInfo: u32,
+    /// -1 when free, otherwise the number of Enter calls (owner recursion plus
+    /// waiting threads) minus one.
+    pub LockCount: i32,
+    pub RecursionCount: i32,
+    pub OwningThread: u32,
+    /// Auto-reset event that waiting threads block on, created on first contention.
+    pub LockSemaphore: HEVENT,
+    pub SpinCount: u32,
+}
+unsafe impl ::memory::Pod for CRITICAL_SECTION {}
+
+const INFINITE: u32 = 0xFFFF_FFFF;
+
+fn get(machine: &mut Machine, addr: u32) -> &mut CRITICAL_SECTION {
+    machine.mem().get_aligned_ref_mut::<CRITICAL_SECTION>(addr)
+}
+
+fn init(machine: &mut Machine, addr: u32, spin_count: u32) {
+    *get(machine, addr) = CRITICAL_SECTION {
+        DebugInfo: 0,
+        LockCount: -1,
+        RecursionCount: 0,
+        OwningThread: 0,
+        LockSemaphore: HEVENT::null(),
+        SpinCount: spin_count,
+    };
+}
 
 #[win32_derive::dllexport]
-pub fn InitializeCriticalSection(_machine: &mut Machine, lpCriticalSection: u32) -> bool {
+pub fn InitializeCriticalSection(machine: &mut Machine, lpCriticalSection: u32) -> bool {
+    init(machine, lpCriticalSection, 0);
     true
 }
 
 #[win32_derive::dllexport]
 pub fn InitializeCriticalSectionEx(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpCriticalSection: u32,
     dwSpinCount: u32,
     flags: u32,
 ) -> bool {
+    init(machine, lpCriticalSection, dwSpinCount);
     true
 }
 
 #[win32_derive::dllexport]
 pub fn InitializeCriticalSectionAndSpinCount(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpCriticalSection: u32,
     dwSpinCount: u32,
 ) -> bool {
     // "On single-processor systems, the spin count is ignored and the critical section spin count is set to 0 (zero)."
     // "This function always succeeds and returns a nonzero value."
+    init(machine, lpCriticalSection, 0);
     true
 }
 
 #[win32_derive::dllexport]
-pub fn DeleteCriticalSection(_machine: &mut Machine, lpCriticalSection: u32) -> u32 {
+pub fn DeleteCriticalSection(machine: &mut Machine, lpCriticalSection: u32) -> u32 {
+    let event = get(machine, lpCriticalSection).LockSemaphore;
+    if !event.is_null() {
+        machine.state.kernel32.objects.remove(event.untyped());
+    }
     0
 }
 
 #[win32_derive::dllexport]
-pub fn EnterCriticalSection(_machine: &mut Machine, lpCriticalSection: u32) -> u32 {
-    0
+pub async fn EnterCriticalSection(machine: &mut Machine, lpCriticalSection: u32) -> u32 {
+    let thread = current_thread(machine).to_raw();
+    let cs = get(machine, lpCriticalSection);
+    cs.LockCount += 1;
+    if cs.LockCount == 0 {
+        cs.OwningThread = thread;
+        cs.RecursionCount = 1;
+        return 0;
+    }
+    if cs.OwningThread == thread {
+        cs.RecursionCount += 1;
+        return 0;
+    }
+
+    // Contended: block until the owner hands off via the event.
+    let mut event = cs.LockSemaphore;
+    if event.is_null() {
+        event = HEVENT::from_raw(
+            machine
+                .state
+                .kernel32
+                .objects
+                .add(KernelObject::Event(EventObject::new(None, false, false)))
+                .to_raw(),
+        );
+        get(machine, lpCriticalSection).LockSemaphore = event;
+    }
+    loop {
+        wait_for_objects(machine, &[event.untyped()], false, INFINITE).await;
+        let cs = get(machine, lpCriticalSection);
+        if cs.OwningThread == 0 {
+            cs.OwningThread = thread;
+            cs.RecursionCount = 1;
+            return 0;
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn TryEnterCriticalSection(machine: &mut Machine, lpCriticalSection: u32) -> bool {
+    let thread = current_thread(machine).to_raw();
+    let cs = get(machine, lpCriticalSection);
+    if cs.LockCount == -1 {
+        cs.LockCount = 0;
+        cs.OwningThread = thread;
+        cs.RecursionCount = 1;
+        true
+    } else if cs.OwningThread == thread {
+        cs.LockCount += 1;
+        cs.RecursionCount += 1;
+        true
+    } else {
+        false
+    }
 }
 
 #[win32_derive::dllexport]
-pub fn LeaveCriticalSection(_machine: &mut Machine, lpCriticalSection: u32) -> u32 {
+pub fn LeaveCriticalSection(machine: &mut Machine, lpCriticalSection: u32) -> u32 {
+    let thread = current_thread(machine).to_raw();
+    let cs = get(machine, lpCriticalSection);
+    if cs.OwningThread != thread {
+        log::warn!("LeaveCriticalSection({lpCriticalSection:x}) by non-owner thread");
+        return 0;
+    }
+    cs.LockCount -= 1;
+    cs.RecursionCount -= 1;
+    if cs.RecursionCount > 0 {
+        return 0;
+    }
+    cs.OwningThread = 0;
+    if cs.LockCount >= 0 {
+        // Another thread is waiting; wake one of them.
+        let event = cs.LockSemaphore;
+        machine
+            .state
+            .kernel32
+            .objects
+            .get_event(event)
+            .unwrap()
+            .signaled
+            .set(true);
+        super::wake_waiters(machine);
+    }
     0
 }
@@ -22,6 +22,13 @@ impl EventObject {
             signaled: Cell::new(signaled),
         }
     }
+
+    /// Consume the signal on behalf of a waiter; auto-reset events only release one.
+    pub fn acquire(&self) {
+        if !self.manual_reset {
+            self.signaled.set(false);
+        }
+    }
 }
 
 #[win32_derive::dllexport]
@@ -72,15 +79,26 @@ pub fn SetEvent(machine: &mut Machine, hEvent: HEVENT) -> bool {
         .unwrap()
         .signaled
         .set(true);
+    super::wake_waiters(machine);
     true
 }
 
 #[win32_derive::dllexport]
-pub fn ResetEvent(_machine: &mut Machine, hEvent: HEVENT) -> bool {
-    todo!()
+pub fn ResetEvent(machine: &mut Machine, hEvent: HEVENT) -> bool {
+    machine
+        .state
+        .kernel32
+        .objects
+        .get_event(hEvent)
+        .unwrap()
+        .signaled
+        .set(false);
+    true
 }
 
 #[win32_derive::dllexport]
-pub fn PulseEvent(_machine: &mut Machine, hEvent: HEVENT) -> bool {
-    todo!()
+pub fn PulseEvent(machine: &mut Machine, hEvent: HEVENT) -> bool {
+    // Release the current waiters (all of them, or one for auto-reset), then reset.
+    SetEvent(machine, hEvent);
+    ResetEvent(machine, hEvent)
 }
@@ -3,7 +3,9 @@ mod event;
 mod interlocked;
 mod mutex;
 mod once;
+mod semaphore;
 mod srw_lock;
+mod timer;
 mod wait;
 
 pub use critical_section::*;
@@ -11,5 +13,7 @@ pub use event::*;
 pub use interlocked::*;
 pub use mutex::*;
 pub use once::*;
+pub use semaphore::*;
 pub use srw_lock::*;
+pub use timer::*;
 pub use wait::*;
@@ -1,24 +1,188 @@
 use crate::{
-    winapi::{kernel32::SECURITY_ATTRIBUTES, types::HANDLE},
+    winapi::{
+        kernel32::{current_thread, set_last_error, KernelObject, HTHREAD, SECURITY_ATTRIBUTES},
+        types::{Str16, HANDLE},
+        ERROR,
+    },
     Machine,
 };
+use std::{cell::Cell, rc::Rc};
+
+pub struct HMUTEXT;
+pub type HMUTEX = HANDLE<HMUTEXT>;
+
+pub struct MutexObject {
+    name: Option<String>,
+    /// Owning thread, if any.  The mutex is signaled when unowned.
+    owner: Cell<Option<HTHREAD>>,
+    /// Number of times the owner has acquired the mutex.
+    count: Cell<u32>,
+    /// Set when the owner exited without releasing; reported to the next acquirer.
+    abandoned: Cell<bool>,
+}
+
+impl MutexObject {
+    pub fn new(name: Option<String>, owner: Option<HTHREAD>) -> Self {
+        MutexObject {
+            name,
+            owner: Cell::new(owner),
+            count: Cell::new(if owner.is_some() { 1 } else { 0 }),
+            abandoned: Cell::new(false),
+        }
+    }
+
+    /// Whether the given thread could take ownership: the mutex is unowned or already
+    /// owned by that thread.
+    pub fn is_signaled(&self, thread: HTHREAD) -> bool {
+        match self.owner.get() {
+            Some(owner) => owner == thread,
+            None => true,
+        }
+    }
+
+    /// Take ownership on behalf of a thread, which must be is_signaled() for it.
+    /// Returns whether the mutex had been abandoned.
+    pub fn acquire(&self, thread: HTHREAD) -> bool {
+        self.owner.set(Some(thread));
+        self.count.set(self.count.get() + 1);
+        self.abandoned.replace(false)
+    }
+
+    /// Returns false if the thread is not the owner.
+    pub fn release(&self, thread: HTHREAD) -> bool {
+        if self.owner.get() != Some(thread) {
+            return false;
+        }
+        let count = self.count.get() - 1;
+        self.count.set(count);
+        if count == 0 {
+            self.owner.set(None);
+        }
+        true
+    }
+}
+
+/// Release any mutexes owned by an exiting thread, marking them abandoned.
+pub fn abandon_mutexes(machine: &mut Machine, thread: HTHREAD) {
+    for (_, obj) in machine.state.kernel32.objects.iter() {
+        if let KernelObject::Mutex(mutex) = obj {
+            if mutex.owner.get() == Some(thread) {
+                mutex.owner.set(None);
+                mutex.count.set(0);
+                mutex.abandoned.set(true);
+            }
+        }
+    }
+}
+
+fn find_mutex(machine: &Machine, name: &str) -> Option<Rc<MutexObject>> {
+    machine
+        .state
+        .kernel32
+        .objects
+        .iter()
+        .find_map(|(_, obj)| match obj {
+            KernelObject::Mutex(mutex) if mutex.name.as_deref() == Some(name) => {
+                Some(mutex.clone())
+            }
+            _ => None,
+        })
+}
+
+fn add_mutex(machine: &mut Machine, mutex: Rc<MutexObject>) -> HMUTEX {
+    HMUTEX::from_raw(
+        machine
+            .state
+            .kernel32
+            .objects
+            .add(KernelObject::Mutex(mutex))
+            .to_raw(),
+    )
+}
+
+fn create_mutex(machine: &mut Machine, initial_owner: bool, name: Option<String>) -> HMUTEX {
+    if let Some(name) = &name {
+        if let Some(mutex) = find_mutex(machine, name) {
+            // The initial owner flag is ignored for existing mutexes.
+            let handle = add_mutex(machine, mutex);
+            set_last_error(machine, ERROR::ALREADY_EXISTS);
+            return handle;
+        }
+    }
+    let owner = if initial_owner {
+        Some(current_thread(machine))
+    } else {
+        None
+    };
+    let handle = add_mutex(machine, Rc::new(MutexObject::new(name, owner)));
+    set_last_error(machine, ERROR::SUCCESS);
+    handle
+}
+
+fn open_mutex(machine: &mut Machine, name: Option<String>) -> HMUTEX {
+    match name.and_then(|name| find_mutex(machine, &name)) {
+        Some(mutex) => add_mutex(machine, mutex),
+        None => {
+            set_last_error(machine, ERROR::FILE_NOT_FOUND);
+            HMUTEX::null()
+        }
+    }
+}
 
 #[win32_derive::dllexport]
 pub fn CreateMutexA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpMutexAttributes: Option<&mut SECURITY_ATTRIBUTES>,
     bInitialOwner: bool,
     lpName: Option<&str>,
-) -> HANDLE<()> {
-    HANDLE::null() // fail
+) -> HMUTEX {
+    create_mutex(machine, bInitialOwner, lpName.map(|s| s.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn CreateMutexW(
+    machine: &mut Machine,
+    lpMutexAttributes: Option<&mut SECURITY_ATTRIBUTES>,
+    bInitialOwner: bool,
+    lpName: Option<&Str16>,
+) -> HMUTEX {
+    create_mutex(machine, bInitialOwner, lpName.map(|s| s.to_string()))
 }
 
 #[win32_derive::dllexport]
 pub fn OpenMutexA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     dwDesiredAccess: u32,
     bInheritHandle: bool,
     lpName: Option<&str>,
-) -> HANDLE<()> {
-    HANDLE::null() // fail
+) -> HMUTEX {
+    open_mutex(machine, lpName.map(|s| s.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn OpenMutexW(
+    machine: &mut Machine,
+    dwDesiredAccess: u32,
+    bInheritHandle: bool,
+    lpName: Option<&Str16>,
+) -> HMUTEX {
+    open_mutex(machine, lpName.map(|s| s.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn ReleaseMutex(machine: &mut Machine, hMutex: HMUTEX) -> bool {
+    let thread = current_thread(machine);
+    let released = match machine.state.kernel32.objects.get(hMutex.untyped()) {
+        Some(KernelObject::Mutex(mutex)) => mutex.release(thread),
+        _ => {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            return false;
+        }
+    };
+    if released {
+        super::wake_waiters(machine);
+    } else {
+        set_last_error(machine, ERROR::NOT_OWNER);
+    }
+    released
 }
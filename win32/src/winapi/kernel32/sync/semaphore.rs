@@ -0,0 +1,170 @@
+use crate::{
+    winapi::{
+        kernel32::{set_last_error, KernelObject, SECURITY_ATTRIBUTES},
+        types::{Str16, HANDLE},
+        ERROR,
+    },
+    Machine,
+};
+use std::{cell::Cell, rc::Rc};
+
+pub struct HSEMAPHORET;
+pub type HSEMAPHORE = HANDLE<HSEMAPHORET>;
+
+pub struct SemaphoreObject {
+    name: Option<String>,
+    /// The semaphore is signaled while the count is nonzero; each wait decrements it.
+    pub count: Cell<u32>,
+    max: u32,
+}
+
+impl SemaphoreObject {
+    /// Add to the count, returning the previous count, or None if it would exceed the max.
+    pub fn release(&self, count: u32) -> Option<u32> {
+        let prev = self.count.get();
+        let next = prev.checked_add(count).filter(|&next| next <= self.max)?;
+        self.count.set(next);
+        Some(prev)
+    }
+}
+
+fn find_semaphore(machine: &Machine, name: &str) -> Option<Rc<SemaphoreObject>> {
+    machine
+        .state
+        .kernel32
+        .objects
+        .iter()
+        .find_map(|(_, obj)| match obj {
+            KernelObject::Semaphore(sem) if sem.name.as_deref() == Some(name) => Some(sem.clone()),
+            _ => None,
+        })
+}
+
+fn add_semaphore(machine: &mut Machine, sem: Rc<SemaphoreObject>) -> HSEMAPHORE {
+    HSEMAPHORE::from_raw(
+        machine
+            .state
+            .kernel32
+            .objects
+            .add(KernelObject::Semaphore(sem))
+            .to_raw(),
+    )
+}
+
+fn create_semaphore(
+    machine: &mut Machine,
+    initial_count: i32,
+    maximum_count: i32,
+    name: Option<String>,
+) -> HSEMAPHORE {
+    if maximum_count <= 0 || initial_count < 0 || initial_count > maximum_count {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return HSEMAPHORE::null();
+    }
+    if let Some(name) = &name {
+        if let Some(sem) = find_semaphore(machine, name) {
+            // The counts are ignored for existing semaphores.
+            let handle = add_semaphore(machine, sem);
+            set_last_error(machine, ERROR::ALREADY_EXISTS);
+            return handle;
+        }
+    }
+    let sem = SemaphoreObject {
+        name,
+        count: Cell::new(initial_count as u32),
+        max: maximum_count as u32,
+    };
+    let handle = add_semaphore(machine, Rc::new(sem));
+    set_last_error(machine, ERROR::SUCCESS);
+    handle
+}
+
+fn open_semaphore(machine: &mut Machine, name: Option<String>) -> HSEMAPHORE {
+    match name.and_then(|name| find_semaphore(machine, &name)) {
+        Some(sem) => add_semaphore(machine, sem),
+        None => {
+            set_last_error(machine, ERROR::FILE_NOT_FOUND);
+            HSEMAPHORE::null()
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn CreateSemaphoreA(
+    machine: &mut Machine,
+    lpSemaphoreAttributes: Option<&mut SECURITY_ATTRIBUTES>,
+    lInitialCount: i32,
+    lMaximumCount: i32,
+    lpName: Option<&str>,
+) -> HSEMAPHORE {
+    create_semaphore(
+        machine,
+        lInitialCount,
+        lMaximumCount,
+        lpName.map(|s| s.to_string()),
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn CreateSemaphoreW(
+    machine: &mut Machine,
+    lpSemaphoreAttributes: Option<&mut SECURITY_ATTRIBUTES>,
+    lInitialCount: i32,
+    lMaximumCount: i32,
+    lpName: Option<&Str16>,
+) -> HSEMAPHORE {
+    create_semaphore(
+        machine,
+        lInitialCount,
+        lMaximumCount,
+        lpName.map(|s| s.to_string()),
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn OpenSemaphoreA(
+    machine: &mut Machine,
+    dwDesiredAccess: u32,
+    bInheritHandle: bool,
+    lpName: Option<&str>,
+) -> HSEMAPHORE {
+    open_semaphore(machine, lpName.map(|s| s.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn OpenSemaphoreW(
+    machine: &mut Machine,
+    dwDesiredAccess: u32,
+    bInheritHandle: bool,
+    lpName: Option<&Str16>,
+) -> HSEMAPHORE {
+    open_semaphore(machine, lpName.map(|s| s.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn ReleaseSemaphore(
+    machine: &mut Machine,
+    hSemaphore: HSEMAPHORE,
+    lReleaseCount: i32,
+    lpPreviousCount: Option<&mut i32>,
+) -> bool {
+    let prev = match machine.state.kernel32.objects.get(hSemaphore.untyped()) {
+        Some(KernelObject::Semaphore(sem)) if lReleaseCount > 0 => {
+            sem.release(lReleaseCount as u32)
+        }
+        Some(KernelObject::Semaphore(_)) => None,
+        _ => {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            return false;
+        }
+    };
+    let Some(prev) = prev else {
+        set_last_error(machine, ERROR::TOO_MANY_POSTS);
+        return false;
+    };
+    if let Some(out) = lpPreviousCount {
+        *out = prev as i32;
+    }
+    super::wake_waiters(machine);
+    true
+}
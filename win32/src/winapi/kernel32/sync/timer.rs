@@ -0,0 +1,205 @@
+use crate::{
+    winapi::{
+        kernel32::{set_last_error, KernelObject, FILETIME, LARGE_INTEGER, SECURITY_ATTRIBUTES},
+        types::{Str16, HANDLE},
+        ERROR,
+    },
+    Machine,
+};
+use std::{cell::Cell, rc::Rc};
+
+pub struct HTIMERT;
+pub type HTIMER = HANDLE<HTIMERT>;
+
+/// A waitable timer, which becomes signaled when its due time (in host ticks) passes.
+pub struct TimerObject {
+    name: Option<String>,
+    manual_reset: bool,
+    signaled: Cell<bool>,
+    /// Tick count at which the timer next fires, if active.
+    due: Cell<Option<u32>>,
+    /// Period in milliseconds for periodic timers, or 0 for one-shot.
+    period: Cell<u32>,
+}
+
+impl TimerObject {
+    /// Fire the timer if its due time has passed, rescheduling periodic timers.
+    fn update(&self, now: u32) {
+        let Some(due) = self.due.get() else {
+            return;
+        };
+        if now < due {
+            return;
+        }
+        self.signaled.set(true);
+        let period = self.period.get();
+        self.due.set(if period == 0 {
+            None
+        } else {
+            // If we fell behind by more than one period, don't try to catch up.
+            Some(std::cmp::max(due + period, now + 1))
+        });
+    }
+
+    pub fn is_signaled(&self, now: u32) -> bool {
+        self.update(now);
+        self.signaled.get()
+    }
+
+    pub fn acquire(&self) {
+        if !self.manual_reset {
+            self.signaled.set(false);
+        }
+    }
+
+    /// Tick count at which the timer next fires, if active.
+    pub fn due(&self) -> Option<u32> {
+        self.due.get()
+    }
+}
+
+fn find_timer(machine: &Machine, name: &str) -> Option<Rc<TimerObject>> {
+    machine
+        .state
+        .kernel32
+        .objects
+        .iter()
+        .find_map(|(_, obj)| match obj {
+            KernelObject::Timer(timer) if timer.name.as_deref() == Some(name) => {
+                Some(timer.clone())
+            }
+            _ => None,
+        })
+}
+
+fn add_timer(machine: &mut Machine, timer: Rc<TimerObject>) -> HTIMER {
+    HTIMER::from_raw(
+        machine
+            .state
+            .kernel32
+            .objects
+            .add(KernelObject::Timer(timer))
+            .to_raw(),
+    )
+}
+
+fn create_timer(machine: &mut Machine, manual_reset: bool, name: Option<String>) -> HTIMER {
+    if let Some(name) = &name {
+        if let Some(timer) = find_timer(machine, name) {
+            let handle = add_timer(machine, timer);
+            set_last_error(machine, ERROR::ALREADY_EXISTS);
+            return handle;
+        }
+    }
+    let timer = TimerObject {
+        name,
+        manual_reset,
+        signaled: Cell::new(false),
+        due: Cell::new(None),
+        period: Cell::new(0),
+    };
+    let handle = add_timer(machine, Rc::new(timer));
+    set_last_error(machine, ERROR::SUCCESS);
+    handle
+}
+
+fn open_timer(machine: &mut Machine, name: Option<String>) -> HTIMER {
+    match name.and_then(|name| find_timer(machine, &name)) {
+        Some(timer) => add_timer(machine, timer),
+        None => {
+            set_last_error(machine, ERROR::FILE_NOT_FOUND);
+            HTIMER::null()
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn CreateWaitableTimerA(
+    machine: &mut Machine,
+    lpTimerAttributes: Option<&mut SECURITY_ATTRIBUTES>,
+    bManualReset: bool,
+    lpTimerName: Option<&str>,
+) -> HTIMER {
+    create_timer(machine, bManualReset, lpTimerName.map(|s| s.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn CreateWaitableTimerW(
+    machine: &mut Machine,
+    lpTimerAttributes: Option<&mut SECURITY_ATTRIBUTES>,
+    bManualReset: bool,
+    lpTimerName: Option<&Str16>,
+) -> HTIMER {
+    create_timer(machine, bManualReset, lpTimerName.map(|s| s.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn OpenWaitableTimerA(
+    machine: &mut Machine,
+    dwDesiredAccess: u32,
+    bInheritHandle: bool,
+    lpTimerName: Option<&str>,
+) -> HTIMER {
+    open_timer(machine, lpTimerName.map(|s| s.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn OpenWaitableTimerW(
+    machine: &mut Machine,
+    dwDesiredAccess: u32,
+    bInheritHandle: bool,
+    lpTimerName: Option<&Str16>,
+) -> HTIMER {
+    open_timer(machine, lpTimerName.map(|s| s.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn SetWaitableTimer(
+    machine: &mut Machine,
+    hTimer: HTIMER,
+    lpDueTime: Option<&LARGE_INTEGER>,
+    lPeriod: i32,
+    pfnCompletionRoutine: u32, /* PTIMERAPCROUTINE */
+    lpArgToCompletionRoutine: u32,
+    fResume: bool,
+) -> bool {
+    let Some(due_time) = lpDueTime else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    if pfnCompletionRoutine != 0 {
+        log::warn!("SetWaitableTimer: ignoring completion routine");
+    }
+
+    // Negative due times are relative, positive are absolute FILETIMEs; both in 100ns units.
+    let due_time = due_time.to_i64();
+    let delay_100ns = if due_time < 0 {
+        -due_time
+    } else {
+        let now = FILETIME::from_chrono(machine.host.system_time().to_utc()).to_u64() as i64;
+        std::cmp::max(due_time - now, 0)
+    };
+    let due = machine.host.ticks() + ((delay_100ns + 9_999) / 10_000) as u32;
+
+    let Some(KernelObject::Timer(timer)) = machine.state.kernel32.objects.get(hTimer.untyped())
+    else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    timer.signaled.set(false);
+    timer.due.set(Some(due));
+    timer.period.set(std::cmp::max(lPeriod, 0) as u32);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn CancelWaitableTimer(machine: &mut Machine, hTimer: HTIMER) -> bool {
+    let Some(KernelObject::Timer(timer)) = machine.state.kernel32.objects.get(hTimer.untyped())
+    else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    // Cancelling doesn't change the signaled state.
+    timer.due.set(None);
+    true
+}
@@ -1,22 +1,134 @@
 //! WaitFor* functions that can block on various types of kernel objects.
+//!
+//! Blocked threads are recorded as Waiters.  When an object is signaled, the signaler
+//! calls wake_waiters(), which satisfies waits in the order they began, acquiring objects
+//! on behalf of the waiting thread.  This way an auto-reset event or a semaphore count
+//! releases exactly one waiter, and a wait-all acquires all of its objects at once.
 
-use super::EventObject;
 use crate::{
-    winapi::{kernel32::KernelObject, types::HANDLE},
+    winapi::{
+        kernel32::{current_thread, set_last_error, KernelObject, HTHREAD},
+        types::HANDLE,
+        user32, winmm, ERROR,
+    },
     Machine,
 };
 use memory::Extensions;
+use std::{cell::Cell, rc::Rc};
 
 pub const WAIT_OBJECT_0: u32 = 0;
-//const WAIT_ABANDONED_0: u32 = 0x80;
-const WAIT_TIMEOUT: u32 = 0x102;
-const INFINITE: u32 = 0xffff_ffff;
+const WAIT_ABANDONED_0: u32 = 0x80;
+pub const WAIT_TIMEOUT: u32 = 0x102;
+pub const WAIT_FAILED: u32 = 0xffff_ffff;
+pub const INFINITE: u32 = 0xffff_ffff;
 
 impl KernelObject {
-    pub fn get_event(&self) -> &EventObject {
+    /// Whether a wait by the given thread on this object would succeed now.
+    fn is_signaled(&self, thread: HTHREAD, now: u32) -> bool {
         match self {
-            KernelObject::Event(event) => event,
-            KernelObject::Thread(thread) => &thread.terminated,
+            KernelObject::Event(event) => event.signaled.get(),
+            KernelObject::Thread(thread) => thread.terminated.signaled.get(),
+            KernelObject::Mutex(mutex) => mutex.is_signaled(thread),
+            KernelObject::Semaphore(sem) => sem.count.get() > 0,
+            KernelObject::Timer(timer) => timer.is_signaled(now),
+            KernelObject::Process(process) => process.is_exited(),
+            KernelObject::Pipe(pipe) => pipe.is_ready(),
+            KernelObject::Key(_) => unreachable!("wait on registry key"),
+        }
+    }
+
+    /// Acquire a signaled object on behalf of the given thread: resetting auto-reset
+    /// events, taking ownership of mutexes, etc.
+    /// Returns true if the object was an abandoned mutex.
+    fn acquire(&self, thread: HTHREAD) -> bool {
+        match self {
+            KernelObject::Event(event) => event.acquire(),
+            KernelObject::Thread(_) => {}
+            KernelObject::Mutex(mutex) => return mutex.acquire(thread),
+            KernelObject::Semaphore(sem) => sem.count.set(sem.count.get() - 1),
+            KernelObject::Timer(timer) => timer.acquire(),
+            KernelObject::Process(_) | KernelObject::Pipe(_) => {}
+            KernelObject::Key(_) => unreachable!(),
+        }
+        false
+    }
+}
+
+/// A thread blocked in wait_for_objects.
+pub struct Waiter {
+    thread: HTHREAD,
+    #[allow(unused)]
+    cpu: usize,
+    handles: Vec<HANDLE<()>>,
+    wait_all: bool,
+    /// Set by wake_waiters once the wait has been satisfied.
+    result: Cell<Option<u32>>,
+}
+
+/// If the wait can be satisfied now, acquire the objects and return the wait result.
+fn try_satisfy(
+    machine: &Machine,
+    thread: HTHREAD,
+    handles: &[HANDLE<()>],
+    wait_all: bool,
+    now: u32,
+) -> Option<u32> {
+    let objects = &machine.state.kernel32.objects;
+    // Handles were validated when the wait began, but may have since been closed.
+    let signaled = |handle| {
+        objects
+            .get(handle)
+            .is_some_and(|obj| obj.is_signaled(thread, now))
+    };
+    if wait_all {
+        if !handles.iter().all(|&handle| signaled(handle)) {
+            return None;
+        }
+        let mut result = WAIT_OBJECT_0;
+        for (i, &handle) in handles.iter().enumerate() {
+            if objects.get(handle).unwrap().acquire(thread) && result == WAIT_OBJECT_0 {
+                result = WAIT_ABANDONED_0 + i as u32;
+            }
+        }
+        Some(result)
+    } else {
+        let i = handles.iter().position(|&handle| signaled(handle))?;
+        let abandoned = objects.get(handles[i]).unwrap().acquire(thread);
+        Some(
+            if abandoned {
+                WAIT_ABANDONED_0
+            } else {
+                WAIT_OBJECT_0
+            } + i as u32,
+        )
+    }
+}
+
+/// Satisfy whichever blocked waits can now complete, in the order they began, and wake
+/// their threads.  Called whenever an object may have become signaled.
+pub fn wake_waiters(machine: &mut Machine) {
+    let now = machine.host.ticks();
+    let waiters = machine.state.kernel32.waiters.clone();
+    for waiter in waiters {
+        if waiter.result.get().is_some() {
+            continue;
+        }
+        let Some(result) = try_satisfy(
+            machine,
+            waiter.thread,
+            &waiter.handles,
+            waiter.wait_all,
+            now,
+        ) else {
+            continue;
+        };
+        waiter.result.set(Some(result));
+        #[cfg(feature = "x86-emu")]
+        {
+            let cpu = &mut machine.emu.x86.cpus[waiter.cpu];
+            if matches!(cpu.state, x86::CPUState::Blocked(_)) {
+                cpu.state = x86::CPUState::Running;
+            }
         }
     }
 }
@@ -25,7 +137,7 @@ impl KernelObject {
 /// Returns:
 /// - None => no waiting requested
 /// - Some(until) => wait until "until", which is None in the case of forever
-fn wait_from_milliseconds(machine: &mut Machine, dwMilliseconds: u32) -> Option<Option<u32>> {
+pub fn wait_from_milliseconds(machine: &mut Machine, dwMilliseconds: u32) -> Option<Option<u32>> {
     if dwMilliseconds == 0 {
         None
     } else if dwMilliseconds == INFINITE {
@@ -35,6 +147,15 @@ fn wait_from_milliseconds(machine: &mut Machine, dwMilliseconds: u32) -> Option<
     }
 }
 
+/// Whether a handle refers to an object that can be waited on.
+fn is_waitable(machine: &Machine, handle: HANDLE<()>) -> bool {
+    // Registry keys share the handle table but aren't waitable.
+    !matches!(
+        machine.state.kernel32.objects.get(handle),
+        None | Some(KernelObject::Key(_))
+    )
+}
+
 /// The primitive beneath WaitForMultipleObjects etc.
 pub async fn wait_for_objects(
     machine: &mut Machine,
@@ -42,49 +163,121 @@ pub async fn wait_for_objects(
     bWaitAll: bool,
     dwMilliseconds: u32,
 ) -> u32 {
-    if bWaitAll {
-        todo!("WaitForMultipleObjects: bWaitAll");
+    if !handles.iter().all(|&handle| is_waitable(machine, handle)) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return WAIT_FAILED;
     }
 
     let until = wait_from_milliseconds(machine, dwMilliseconds);
+    let thread = current_thread(machine);
     loop {
-        for (i, &handle) in handles.iter().enumerate() {
-            let event = machine
-                .state
-                .kernel32
-                .objects
-                .get(handle)
-                .unwrap()
-                .get_event();
-            if event.signaled.get() {
-                if !event.manual_reset {
-                    // TODO: this should wake up exactly one waiting thread
-                    event.signaled.set(false);
-                }
-                return WAIT_OBJECT_0 + i as u32;
-            }
+        // Playing sound may signal position notification events.
+        winmm::pump_audio(machine);
+        // Host input may have woken this thread rather than the one that reads it, so
+        // pass it along to the message queues, waking their threads.
+        user32::fetch_host_messages(machine);
+        winmm::deliver_callbacks(machine).await;
+        let now = machine.host.ticks();
+        if let Some(result) = try_satisfy(machine, thread, handles, bWaitAll, now) {
+            return result;
         }
         let Some(until) = until else {
             return WAIT_TIMEOUT; // no waiting at all
         };
 
         if let Some(until) = until {
-            if machine.host.ticks() >= until {
+            if now >= until {
                 return WAIT_TIMEOUT;
             }
         }
-        // log::info!(
-        //     "{:?}: waiting for {:?}",
-        //     crate::winapi::kernel32::current_thread(machine),
-        //     handles
-        // );
 
-        #[cfg(feature = "x86-emu")]
-        {
-            machine.emu.x86.cpu_mut().block(until).await;
+        // Timers become signaled by the passage of time rather than by a call to
+        // wake_waiters, so wake up to check on them when they're due, and likewise
+        // to keep sound mixing.
+        let timer_due = handles
+            .iter()
+            .filter_map(|&handle| match machine.state.kernel32.objects.get(handle) {
+                Some(KernelObject::Timer(timer)) => timer.due(),
+                _ => None,
+            })
+            .chain(winmm::audio_due(machine))
+            .min();
+        let block_until = match (until, timer_due) {
+            (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+            (a, b) => a.or(b),
+        };
+
+        if let Some(result) = block_on_objects(machine, handles, bWaitAll, block_until).await {
+            return result;
         }
-        #[cfg(not(feature = "x86-emu"))]
-        todo!();
+    }
+}
+
+/// Block the current thread once, until the wait on the handles is satisfied or until
+/// the given time, returning the wait result if it was satisfied.  Returns None if the
+/// thread woke for another reason, in which case the caller should check for whatever
+/// else it is interested in and block again.
+pub async fn block_on_objects(
+    machine: &mut Machine,
+    handles: &[HANDLE<()>],
+    wait_all: bool,
+    until: Option<u32>,
+) -> Option<u32> {
+    block_on_waits(machine, &[(handles, wait_all)], until).await[0]
+}
+
+/// Like block_on_objects for a wait-all, but also waking (with no result) when the
+/// `wake` object is signaled.
+pub async fn block_on_all_objects_or(
+    machine: &mut Machine,
+    handles: &[HANDLE<()>],
+    wake: HANDLE<()>,
+    until: Option<u32>,
+) -> Option<u32> {
+    block_on_waits(machine, &[(handles, true), (&[wake], false)], until).await[0]
+}
+
+/// Block the current thread once, until any of the waits (handles, wait_all) is
+/// satisfied or until the given time, returning each wait's result.
+async fn block_on_waits(
+    machine: &mut Machine,
+    waits: &[(&[HANDLE<()>], bool)],
+    until: Option<u32>,
+) -> Vec<Option<u32>> {
+    #[cfg(feature = "x86-emu")]
+    {
+        let thread = current_thread(machine);
+        let cpu = machine.emu.x86.cur_cpu;
+        let waiters = waits
+            .iter()
+            .map(|&(handles, wait_all)| {
+                Rc::new(Waiter {
+                    thread,
+                    cpu,
+                    handles: handles.to_vec(),
+                    wait_all,
+                    result: Cell::new(None),
+                })
+            })
+            .collect::<Vec<_>>();
+        machine
+            .state
+            .kernel32
+            .waiters
+            .extend(waiters.iter().cloned());
+        machine.emu.x86.cpu_mut().block(until).await;
+        machine
+            .state
+            .kernel32
+            .waiters
+            .retain(|w| !waiters.iter().any(|waiter| Rc::ptr_eq(w, waiter)));
+        waiters.iter().map(|waiter| waiter.result.get()).collect()
+    }
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        // There's only one thread, so nothing can signal the objects while it waits.
+        machine.host.block(until);
+        vec![None; waits.len()]
     }
 }
 
@@ -109,5 +302,40 @@ pub async fn WaitForMultipleObjects(
         .mem()
         .iter_pod::<HANDLE<()>>(lpHandles, nCount)
         .collect::<Vec<_>>();
-    wait_for_objects(machine, &handles, false, dwMilliseconds).await
+    wait_for_objects(machine, &handles, bWaitAll, dwMilliseconds).await
+}
+
+#[win32_derive::dllexport]
+pub async fn SignalObjectAndWait(
+    machine: &mut Machine,
+    hObjectToSignal: HANDLE<()>,
+    hObjectToWaitOn: HANDLE<()>,
+    dwMilliseconds: u32,
+    bAlertable: bool,
+) -> u32 {
+    // Validate the wait before signaling, so that a failed call has no effect.
+    if !is_waitable(machine, hObjectToWaitOn) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return WAIT_FAILED;
+    }
+    let thread = current_thread(machine);
+    let ok = match machine.state.kernel32.objects.get(hObjectToSignal) {
+        Some(KernelObject::Event(event)) => {
+            event.signaled.set(true);
+            Ok(())
+        }
+        Some(KernelObject::Mutex(mutex)) => {
+            mutex.release(thread).then_some(()).ok_or(ERROR::NOT_OWNER)
+        }
+        Some(KernelObject::Semaphore(sem)) => {
+            sem.release(1).map(|_| ()).ok_or(ERROR::TOO_MANY_POSTS)
+        }
+        _ => Err(ERROR::INVALID_HANDLE),
+    };
+    if let Err(err) = ok {
+        set_last_error(machine, err);
+        return WAIT_FAILED;
+    }
+    wake_waiters(machine);
+    wait_for_objects(machine, &[hObjectToWaitOn], false, dwMilliseconds).await
 }
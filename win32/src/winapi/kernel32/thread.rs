@@ -1,4 +1,7 @@
-use super::{peb_mut, EventObject, KernelObject};
+use super::{
+    peb_mut, EventObject, KernelObject, EXCEPTION_REGISTRATION_RECORD, SEH_CHAIN_END,
+    SEH_SENTINEL_HANDLER,
+};
 use crate::{
     machine::Machine,
     winapi::{
@@ -15,16 +18,9 @@ pub struct HTHREADT;
 /// state.objects[HTHREAD] maps to a Thread object.
 pub type HTHREAD = HANDLE<HTHREADT>;
 
-#[repr(C)]
-struct _EXCEPTION_REGISTRATION_RECORD {
-    Prev: u32,
-    Handler: u32,
-}
-unsafe impl ::memory::Pod for _EXCEPTION_REGISTRATION_RECORD {}
-
 #[repr(C)]
 pub struct NT_TIB {
-    ExceptionList: u32,
+    pub ExceptionList: u32,
     StackBase: u32,
     StackLimit: u32,
     SubSystemTib: u32,
@@ -73,12 +69,12 @@ pub struct Thread {
 fn init_teb(peb_addr: u32, thread_id: u32, arena: &mut Arena, mem: Mem) -> u32 {
     // SEH chain
     let seh_addr = arena.alloc(
-        std::mem::size_of::<_EXCEPTION_REGISTRATION_RECORD>() as u32,
+        std::mem::size_of::<EXCEPTION_REGISTRATION_RECORD>() as u32,
         4,
     );
-    let seh = mem.get_aligned_ref_mut::<_EXCEPTION_REGISTRATION_RECORD>(seh_addr);
-    seh.Prev = 0xFFFF_FFFF;
-    seh.Handler = 0xFF5E_5EFF; // Hopefully easier to spot.
+    let seh = mem.get_aligned_ref_mut::<EXCEPTION_REGISTRATION_RECORD>(seh_addr);
+    seh.Prev = SEH_CHAIN_END;
+    seh.Handler = SEH_SENTINEL_HANDLER;
 
     // TEB
     let teb_addr = arena.alloc(std::cmp::max(std::mem::size_of::<TEB>() as u32, 0x100), 4);
@@ -120,7 +116,7 @@ pub fn create_thread(machine: &mut Machine, stack_size: u32) -> NewThread {
     let thread = Rc::new(Thread {
         handle: HTHREAD::from_raw(handle.to_raw()),
         teb,
-        terminated: EventObject::new(None, false, false),
+        terminated: EventObject::new(None, true, false),
     });
     machine
         .state
@@ -138,6 +134,17 @@ pub fn current_thread(machine: &Machine) -> HTHREAD {
     HTHREAD::from_raw(teb(machine).ClientId_UniqueThread)
 }
 
+/// Release the current thread's mutexes, signal anyone waiting on it, and free its CPU.
+pub fn exit_current_thread(machine: &mut Machine) {
+    let thread = current_thread(machine);
+    super::abandon_mutexes(machine, thread);
+    if let Some(KernelObject::Thread(t)) = machine.state.kernel32.objects.get(thread.untyped()) {
+        t.terminated.signaled.set(true);
+    }
+    super::wake_waiters(machine);
+    machine.exit_thread();
+}
+
 #[win32_derive::dllexport]
 pub fn GetCurrentThread(machine: &mut Machine) -> HTHREAD {
     current_thread(machine)
@@ -258,8 +265,8 @@ pub fn ExitThread(machine: &mut Machine, dwExitCode: u32) {
             code = dwExitCode,
             id = machine.emu.x86.cur_cpu
         );
-        // TODO: free stack, other thread cleanup, set event to signal waiters, etc.
-        machine.exit_thread();
+        // TODO: free stack, other thread cleanup, etc.
+        exit_current_thread(machine);
     }
     #[cfg(not(feature = "x86-emu"))]
     todo!();
@@ -21,6 +21,12 @@ pub struct LARGE_INTEGER {
 }
 unsafe impl memory::Pod for LARGE_INTEGER {}
 
+impl LARGE_INTEGER {
+    pub fn to_i64(&self) -> i64 {
+        ((self.HighPart as i64) << 32) | self.LowPart as i64
+    }
+}
+
 #[win32_derive::dllexport]
 pub fn QueryPerformanceCounter(
     machine: &mut Machine,
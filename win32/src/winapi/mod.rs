@@ -1,6 +1,6 @@
 use crate::machine::MemImpl;
 
-mod advapi32;
+pub mod advapi32;
 mod alloc;
 mod bass;
 mod bitmap;
@@ -47,7 +47,9 @@ impl<'a> std::fmt::Display for ImportSymbol<'a> {
 pub struct State {
     scratch: heap::Heap,
 
+    pub advapi32: advapi32::State,
     pub ddraw: ddraw::State,
+    pub dinput: dinput::State,
     pub dsound: dsound::State,
     pub gdi32: gdi32::State,
     pub kernel32: kernel32::State,
@@ -61,7 +63,9 @@ impl State {
 
         State {
             scratch,
+            advapi32: advapi32::State::default(),
             ddraw: ddraw::State::default(),
+            dinput: dinput::State::default(),
             dsound: dsound::State::default(),
             gdi32: gdi32::State::default(),
             kernel32,
@@ -17,7 +17,7 @@ pub fn _ftol(machine: &mut Machine) -> u64 {
     #[cfg(feature = "x86-emu")]
     {
         let cpu = machine.emu.x86.cpu_mut();
-        let value = *cpu.fpu.st0() as i64 as u64;
+        let value = x86::FpuFloat::to_f64(*cpu.fpu.st0()) as i64 as u64;
         cpu.fpu.pop();
         value
     }
@@ -69,9 +69,9 @@ mod wrappers {
     pub unsafe fn AppendMenuA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
-        let uFlags = <u32>::from_stack(mem, stack_args + 4u32);
+        let uFlags = <Result<MF, u32>>::from_stack(mem, stack_args + 4u32);
         let uIDNewItem = <u32>::from_stack(mem, stack_args + 8u32);
-        let lpNewItem = <Option<&str>>::from_stack(mem, stack_args + 12u32);
+        let lpNewItem = <u32>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
                 winapi::user32::AppendMenuA_pos,
@@ -94,7 +94,38 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn BeginPaint(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn AppendMenuW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uFlags = <Result<MF, u32>>::from_stack(mem, stack_args + 4u32);
+        let uIDNewItem = <u32>::from_stack(mem, stack_args + 8u32);
+        let lpNewItem = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::AppendMenuW_pos,
+                "user32/menu",
+                "AppendMenuW",
+                &[
+                    ("hMenu", &hMenu),
+                    ("uFlags", &uFlags),
+                    ("uIDNewItem", &uIDNewItem),
+                    ("lpNewItem", &lpNewItem),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::AppendMenuW(machine, hMenu, uFlags, uIDNewItem, lpNewItem);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn BeginPaint(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
         let lpPaint = <Option<&mut PAINTSTRUCT>>::from_stack(mem, stack_args + 4u32);
@@ -109,13 +140,75 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::BeginPaint(machine, hWnd, lpPaint);
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::BeginPaint(machine, hWnd, lpPaint).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn BringWindowToTop(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::BringWindowToTop_pos,
+                "user32/window",
+                "BringWindowToTop",
+                &[("hWnd", &hWnd)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::BringWindowToTop(machine, hWnd);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
         result.into_abireturn()
     }
-    pub unsafe fn CallWindowProcA(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn ButtonWndProc(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let msg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/controls/button") {
+            crate::trace::Record::new(
+                winapi::user32::ButtonWndProc_pos,
+                "user32/controls/button",
+                "ButtonWndProc",
+                &[
+                    ("hWnd", &hWnd),
+                    ("msg", &msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::ButtonWndProc(machine, hWnd, msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn CallWindowProcA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let lpPrevWndFunc = <u32>::from_stack(mem, stack_args + 0u32);
         let hWnd = <HWND>::from_stack(mem, stack_args + 4u32);
@@ -139,12 +232,17 @@ mod wrappers {
         } else {
             None
         };
-        let result =
-            winapi::user32::CallWindowProcA(machine, lpPrevWndFunc, hWnd, Msg, wParam, lParam);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result =
+                winapi::user32::CallWindowProcA(machine, lpPrevWndFunc, hWnd, Msg, wParam, lParam)
+                    .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn CharLowerA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
@@ -187,7 +285,10 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn CheckDlgButton(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn CheckDlgButton(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
         let nIDButton = <i32>::from_stack(mem, stack_args + 4u32);
@@ -207,17 +308,21 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::CheckDlgButton(machine, hDlg, nIDButton, uCheck);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::CheckDlgButton(machine, hDlg, nIDButton, uCheck).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn CheckMenuItem(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
         let uIDCheckItem = <u32>::from_stack(mem, stack_args + 4u32);
-        let uCheck = <u32>::from_stack(mem, stack_args + 8u32);
+        let uCheck = <Result<MF, u32>>::from_stack(mem, stack_args + 8u32);
         let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
                 winapi::user32::CheckMenuItem_pos,
@@ -239,7 +344,40 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn CheckRadioButton(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn CheckMenuRadioItem(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hmenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let first = <u32>::from_stack(mem, stack_args + 4u32);
+        let last = <u32>::from_stack(mem, stack_args + 8u32);
+        let check = <u32>::from_stack(mem, stack_args + 12u32);
+        let flags = <Result<MF, u32>>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::CheckMenuRadioItem_pos,
+                "user32/menu",
+                "CheckMenuRadioItem",
+                &[
+                    ("hmenu", &hmenu),
+                    ("first", &first),
+                    ("last", &last),
+                    ("check", &check),
+                    ("flags", &flags),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::CheckMenuRadioItem(machine, hmenu, first, last, check, flags);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CheckRadioButton(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
         let nIDFirstButton = <i32>::from_stack(mem, stack_args + 4u32);
@@ -261,13 +399,39 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::CheckRadioButton(
-            machine,
-            hDlg,
-            nIDFirstButton,
-            nIDLastButton,
-            nIDCheckButton,
-        );
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::CheckRadioButton(
+                machine,
+                hDlg,
+                nIDFirstButton,
+                nIDLastButton,
+                nIDCheckButton,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn ChildWindowFromPoint(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWndParent = <HWND>::from_stack(mem, stack_args + 0u32);
+        let Point = <POINT>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::ChildWindowFromPoint_pos,
+                "user32/window",
+                "ChildWindowFromPoint",
+                &[("hWndParent", &hWndParent), ("Point", &Point)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::ChildWindowFromPoint(machine, hWndParent, Point);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
@@ -294,6 +458,41 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn ComboBoxWndProc(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let msg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/controls/combobox") {
+            crate::trace::Record::new(
+                winapi::user32::ComboBoxWndProc_pos,
+                "user32/controls/combobox",
+                "ComboBoxWndProc",
+                &[
+                    ("hWnd", &hWnd),
+                    ("msg", &msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::ComboBoxWndProc(machine, hWnd, msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
     pub unsafe fn CopyRect(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lprcDst = <Option<&mut RECT>>::from_stack(mem, stack_args + 0u32);
@@ -309,43 +508,284 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::CopyRect(machine, lprcDst, lprcSrc);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let result = winapi::user32::CopyRect(machine, lprcDst, lprcSrc);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateAcceleratorTableA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let paccel = <u32>::from_stack(mem, stack_args + 0u32);
+        let cAccel = <i32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::CreateAcceleratorTableA_pos,
+                "user32/menu",
+                "CreateAcceleratorTableA",
+                &[("paccel", &paccel), ("cAccel", &cAccel)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::CreateAcceleratorTableA(machine, paccel, cAccel);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateAcceleratorTableW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let paccel = <u32>::from_stack(mem, stack_args + 0u32);
+        let cAccel = <i32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::CreateAcceleratorTableW_pos,
+                "user32/menu",
+                "CreateAcceleratorTableW",
+                &[("paccel", &paccel), ("cAccel", &cAccel)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::CreateAcceleratorTableW(machine, paccel, cAccel);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateCursor(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hInst = <u32>::from_stack(mem, stack_args + 0u32);
+        let xHotSpot = <u32>::from_stack(mem, stack_args + 4u32);
+        let yHotSpot = <u32>::from_stack(mem, stack_args + 8u32);
+        let nWidth = <u32>::from_stack(mem, stack_args + 12u32);
+        let nHeight = <u32>::from_stack(mem, stack_args + 16u32);
+        let pvANDPlane = <u32>::from_stack(mem, stack_args + 20u32);
+        let pvXORPlane = <u32>::from_stack(mem, stack_args + 24u32);
+        let __trace_record = if crate::trace::enabled("user32/resource") {
+            crate::trace::Record::new(
+                winapi::user32::CreateCursor_pos,
+                "user32/resource",
+                "CreateCursor",
+                &[
+                    ("hInst", &hInst),
+                    ("xHotSpot", &xHotSpot),
+                    ("yHotSpot", &yHotSpot),
+                    ("nWidth", &nWidth),
+                    ("nHeight", &nHeight),
+                    ("pvANDPlane", &pvANDPlane),
+                    ("pvXORPlane", &pvXORPlane),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::CreateCursor(
+            machine, hInst, xHotSpot, yHotSpot, nWidth, nHeight, pvANDPlane, pvXORPlane,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn CreateDialogIndirectParamA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpTemplate = <u32>::from_stack(mem, stack_args + 4u32);
+        let hWndParent = <HWND>::from_stack(mem, stack_args + 8u32);
+        let lpDialogFunc = <u32>::from_stack(mem, stack_args + 12u32);
+        let dwInitParam = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::CreateDialogIndirectParamA_pos,
+                "user32/dialog",
+                "CreateDialogIndirectParamA",
+                &[
+                    ("hInstance", &hInstance),
+                    ("lpTemplate", &lpTemplate),
+                    ("hWndParent", &hWndParent),
+                    ("lpDialogFunc", &lpDialogFunc),
+                    ("dwInitParam", &dwInitParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::CreateDialogIndirectParamA(
+                machine,
+                hInstance,
+                lpTemplate,
+                hWndParent,
+                lpDialogFunc,
+                dwInitParam,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn CreateDialogIndirectParamW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpTemplate = <u32>::from_stack(mem, stack_args + 4u32);
+        let hWndParent = <HWND>::from_stack(mem, stack_args + 8u32);
+        let lpDialogFunc = <u32>::from_stack(mem, stack_args + 12u32);
+        let dwInitParam = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::CreateDialogIndirectParamW_pos,
+                "user32/dialog",
+                "CreateDialogIndirectParamW",
+                &[
+                    ("hInstance", &hInstance),
+                    ("lpTemplate", &lpTemplate),
+                    ("hWndParent", &hWndParent),
+                    ("lpDialogFunc", &lpDialogFunc),
+                    ("dwInitParam", &dwInitParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::CreateDialogIndirectParamW(
+                machine,
+                hInstance,
+                lpTemplate,
+                hWndParent,
+                lpDialogFunc,
+                dwInitParam,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn CreateDialogParamA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpTemplateName = <ResourceKey<&str>>::from_stack(mem, stack_args + 4u32);
+        let hWndParent = <HWND>::from_stack(mem, stack_args + 8u32);
+        let lpDialogFunc = <u32>::from_stack(mem, stack_args + 12u32);
+        let dwInitParam = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::CreateDialogParamA_pos,
+                "user32/dialog",
+                "CreateDialogParamA",
+                &[
+                    ("hInstance", &hInstance),
+                    ("lpTemplateName", &lpTemplateName),
+                    ("hWndParent", &hWndParent),
+                    ("lpDialogFunc", &lpDialogFunc),
+                    ("dwInitParam", &dwInitParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::CreateDialogParamA(
+                machine,
+                hInstance,
+                lpTemplateName,
+                hWndParent,
+                lpDialogFunc,
+                dwInitParam,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn CreateDialogParamW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpTemplateName = <ResourceKey<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let hWndParent = <HWND>::from_stack(mem, stack_args + 8u32);
+        let lpDialogFunc = <u32>::from_stack(mem, stack_args + 12u32);
+        let dwInitParam = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::CreateDialogParamW_pos,
+                "user32/dialog",
+                "CreateDialogParamW",
+                &[
+                    ("hInstance", &hInstance),
+                    ("lpTemplateName", &lpTemplateName),
+                    ("hWndParent", &hWndParent),
+                    ("lpDialogFunc", &lpDialogFunc),
+                    ("dwInitParam", &dwInitParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::CreateDialogParamW(
+                machine,
+                hInstance,
+                lpTemplateName,
+                hWndParent,
+                lpDialogFunc,
+                dwInitParam,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn CreateCursor(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn CreateMenu(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let hInst = <u32>::from_stack(mem, stack_args + 0u32);
-        let xHotSpot = <u32>::from_stack(mem, stack_args + 4u32);
-        let yHotSpot = <u32>::from_stack(mem, stack_args + 8u32);
-        let nWidth = <u32>::from_stack(mem, stack_args + 12u32);
-        let nHeight = <u32>::from_stack(mem, stack_args + 16u32);
-        let pvANDPlane = <u32>::from_stack(mem, stack_args + 20u32);
-        let pvXORPlane = <u32>::from_stack(mem, stack_args + 24u32);
-        let __trace_record = if crate::trace::enabled("user32/resource") {
+        let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
-                winapi::user32::CreateCursor_pos,
-                "user32/resource",
-                "CreateCursor",
-                &[
-                    ("hInst", &hInst),
-                    ("xHotSpot", &xHotSpot),
-                    ("yHotSpot", &yHotSpot),
-                    ("nWidth", &nWidth),
-                    ("nHeight", &nHeight),
-                    ("pvANDPlane", &pvANDPlane),
-                    ("pvXORPlane", &pvXORPlane),
-                ],
+                winapi::user32::CreateMenu_pos,
+                "user32/menu",
+                "CreateMenu",
+                &[],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::user32::CreateCursor(
-            machine, hInst, xHotSpot, yHotSpot, nWidth, nHeight, pvANDPlane, pvXORPlane,
-        );
+        let result = winapi::user32::CreateMenu(machine);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
@@ -502,6 +942,76 @@ mod wrappers {
             result.into_abireturn()
         })
     }
+    pub unsafe fn DefDlgProcA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::DefDlgProcA_pos,
+                "user32/dialog",
+                "DefDlgProcA",
+                &[
+                    ("hDlg", &hDlg),
+                    ("Msg", &Msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::DefDlgProcA(machine, hDlg, Msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn DefDlgProcW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::DefDlgProcW_pos,
+                "user32/dialog",
+                "DefDlgProcW",
+                &[
+                    ("hDlg", &hDlg),
+                    ("Msg", &Msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::DefDlgProcW(machine, hDlg, Msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
     pub unsafe fn DefWindowProcA(
         machine: &mut Machine,
         stack_args: u32,
@@ -576,7 +1086,7 @@ mod wrappers {
         let mem = machine.mem().detach();
         let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
         let uPosition = <u32>::from_stack(mem, stack_args + 4u32);
-        let uFlags = <u32>::from_stack(mem, stack_args + 8u32);
+        let uFlags = <Result<MF, u32>>::from_stack(mem, stack_args + 8u32);
         let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
                 winapi::user32::DeleteMenu_pos,
@@ -598,7 +1108,50 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn DestroyWindow(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn DestroyAcceleratorTable(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hAccel = <HACCEL>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::DestroyAcceleratorTable_pos,
+                "user32/menu",
+                "DestroyAcceleratorTable",
+                &[("hAccel", &hAccel)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::DestroyAcceleratorTable(machine, hAccel);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn DestroyMenu(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::DestroyMenu_pos,
+                "user32/menu",
+                "DestroyMenu",
+                &[("hMenu", &hMenu)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::DestroyMenu(machine, hMenu);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn DestroyWindow(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
         let __trace_record = if crate::trace::enabled("user32/window") {
@@ -612,15 +1165,22 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::DestroyWindow(machine, hWnd);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::DestroyWindow(machine, hWnd).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn DialogBoxIndirectParamA(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn DialogBoxIndirectParamA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
-        let hInstance = <u32>::from_stack(mem, stack_args + 0u32);
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
         let hDialogTemplate = <u32>::from_stack(mem, stack_args + 4u32);
         let hWndParent = <HWND>::from_stack(mem, stack_args + 8u32);
         let lpDialogFunc = <u32>::from_stack(mem, stack_args + 12u32);
@@ -642,23 +1202,76 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::DialogBoxIndirectParamA(
-            machine,
-            hInstance,
-            hDialogTemplate,
-            hWndParent,
-            lpDialogFunc,
-            dwInitParam,
-        );
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::DialogBoxIndirectParamA(
+                machine,
+                hInstance,
+                hDialogTemplate,
+                hWndParent,
+                lpDialogFunc,
+                dwInitParam,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn DialogBoxParamA(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn DialogBoxIndirectParamW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
-        let hInstance = <u32>::from_stack(mem, stack_args + 0u32);
-        let lpTemplateName = <u32>::from_stack(mem, stack_args + 4u32);
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let hDialogTemplate = <u32>::from_stack(mem, stack_args + 4u32);
+        let hWndParent = <HWND>::from_stack(mem, stack_args + 8u32);
+        let lpDialogFunc = <u32>::from_stack(mem, stack_args + 12u32);
+        let dwInitParam = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::DialogBoxIndirectParamW_pos,
+                "user32/dialog",
+                "DialogBoxIndirectParamW",
+                &[
+                    ("hInstance", &hInstance),
+                    ("hDialogTemplate", &hDialogTemplate),
+                    ("hWndParent", &hWndParent),
+                    ("lpDialogFunc", &lpDialogFunc),
+                    ("dwInitParam", &dwInitParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::DialogBoxIndirectParamW(
+                machine,
+                hInstance,
+                hDialogTemplate,
+                hWndParent,
+                lpDialogFunc,
+                dwInitParam,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn DialogBoxParamA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpTemplateName = <ResourceKey<&str>>::from_stack(mem, stack_args + 4u32);
         let hWndParent = <HWND>::from_stack(mem, stack_args + 8u32);
         let lpDialogFunc = <u32>::from_stack(mem, stack_args + 12u32);
         let dwInitParam = <u32>::from_stack(mem, stack_args + 16u32);
@@ -679,23 +1292,31 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::DialogBoxParamA(
-            machine,
-            hInstance,
-            lpTemplateName,
-            hWndParent,
-            lpDialogFunc,
-            dwInitParam,
-        );
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::DialogBoxParamA(
+                machine,
+                hInstance,
+                lpTemplateName,
+                hWndParent,
+                lpDialogFunc,
+                dwInitParam,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn DialogBoxParamW(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn DialogBoxParamW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
-        let hInstance = <u32>::from_stack(mem, stack_args + 0u32);
-        let lpTemplateName = <u32>::from_stack(mem, stack_args + 4u32);
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpTemplateName = <ResourceKey<&Str16>>::from_stack(mem, stack_args + 4u32);
         let hWndParent = <HWND>::from_stack(mem, stack_args + 8u32);
         let lpDialogFunc = <u32>::from_stack(mem, stack_args + 12u32);
         let dwInitParam = <u32>::from_stack(mem, stack_args + 16u32);
@@ -716,18 +1337,23 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::DialogBoxParamW(
-            machine,
-            hInstance,
-            lpTemplateName,
-            hWndParent,
-            lpDialogFunc,
-            dwInitParam,
-        );
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::DialogBoxParamW(
+                machine,
+                hInstance,
+                lpTemplateName,
+                hWndParent,
+                lpDialogFunc,
+                dwInitParam,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn DispatchMessageA(
         machine: &mut Machine,
@@ -803,12 +1429,42 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn DrawTextA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hDC = <HDC>::from_stack(mem, stack_args + 0u32);
+        let lpString = <Option<&CStr>>::from_stack(mem, stack_args + 4u32);
+        let nCount = <i32>::from_stack(mem, stack_args + 8u32);
+        let lpRect = <Option<&mut RECT>>::from_stack(mem, stack_args + 12u32);
+        let uFormat = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/paint") {
+            crate::trace::Record::new(
+                winapi::user32::DrawTextA_pos,
+                "user32/paint",
+                "DrawTextA",
+                &[
+                    ("hDC", &hDC),
+                    ("lpString", &lpString),
+                    ("nCount", &nCount),
+                    ("lpRect", &lpRect),
+                    ("uFormat", &uFormat),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::DrawTextA(machine, hDC, lpString, nCount, lpRect, uFormat);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn DrawTextW(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hDC = <HDC>::from_stack(mem, stack_args + 0u32);
         let lpString = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
         let nCount = <i32>::from_stack(mem, stack_args + 8u32);
-        let lpRect = <Option<&RECT>>::from_stack(mem, stack_args + 12u32);
+        let lpRect = <Option<&mut RECT>>::from_stack(mem, stack_args + 12u32);
         let uFormat = <u32>::from_stack(mem, stack_args + 16u32);
         let __trace_record = if crate::trace::enabled("user32/paint") {
             crate::trace::Record::new(
@@ -833,6 +1489,41 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn EditWndProc(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let msg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/controls/edit") {
+            crate::trace::Record::new(
+                winapi::user32::EditWndProc_pos,
+                "user32/controls/edit",
+                "EditWndProc",
+                &[
+                    ("hWnd", &hWnd),
+                    ("msg", &msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::EditWndProc(machine, hWnd, msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
     pub unsafe fn EnableMenuItem(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
@@ -883,7 +1574,7 @@ mod wrappers {
     pub unsafe fn EndDialog(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
-        let nResult = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+        let nResult = <i32>::from_stack(mem, stack_args + 4u32);
         let __trace_record = if crate::trace::enabled("user32/dialog") {
             crate::trace::Record::new(
                 winapi::user32::EndDialog_pos,
@@ -922,6 +1613,68 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn EnumChildWindows(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWndParent = <HWND>::from_stack(mem, stack_args + 0u32);
+        let lpEnumFunc = <u32>::from_stack(mem, stack_args + 4u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::EnumChildWindows_pos,
+                "user32/window",
+                "EnumChildWindows",
+                &[
+                    ("hWndParent", &hWndParent),
+                    ("lpEnumFunc", &lpEnumFunc),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result =
+                winapi::user32::EnumChildWindows(machine, hWndParent, lpEnumFunc, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn EnumWindows(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let lpEnumFunc = <u32>::from_stack(mem, stack_args + 0u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::EnumWindows_pos,
+                "user32/window",
+                "EnumWindows",
+                &[("lpEnumFunc", &lpEnumFunc), ("lParam", &lParam)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::EnumWindows(machine, lpEnumFunc, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
     pub unsafe fn FillRect(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hDC = <HDC>::from_stack(mem, stack_args + 0u32);
@@ -1009,6 +1762,26 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetAsyncKeyState(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let vKey = <u32>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/keyboard") {
+            crate::trace::Record::new(
+                winapi::user32::GetAsyncKeyState_pos,
+                "user32/keyboard",
+                "GetAsyncKeyState",
+                &[("vKey", &vKey)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetAsyncKeyState(machine, vKey);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetCapture(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let __trace_record = if crate::trace::enabled("user32/window") {
@@ -1129,6 +1902,26 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetDlgCtrlID(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::GetDlgCtrlID_pos,
+                "user32/dialog",
+                "GetDlgCtrlID",
+                &[("hWnd", &hWnd)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetDlgCtrlID(machine, hWnd);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetDlgItem(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
@@ -1179,6 +1972,32 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetDlgItemTextA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
+        let nIDDlgItem = <i32>::from_stack(mem, stack_args + 4u32);
+        let lpString = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::GetDlgItemTextA_pos,
+                "user32/dialog",
+                "GetDlgItemTextA",
+                &[
+                    ("hDlg", &hDlg),
+                    ("nIDDlgItem", &nIDDlgItem),
+                    ("lpString", &lpString),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetDlgItemTextA(machine, hDlg, nIDDlgItem, lpString);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetDlgItemTextW(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
@@ -1246,10 +2065,10 @@ mod wrappers {
     pub unsafe fn GetKeyState(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let nVirtKey = <u32>::from_stack(mem, stack_args + 0u32);
-        let __trace_record = if crate::trace::enabled("user32/misc") {
+        let __trace_record = if crate::trace::enabled("user32/keyboard") {
             crate::trace::Record::new(
                 winapi::user32::GetKeyState_pos,
-                "user32/misc",
+                "user32/keyboard",
                 "GetKeyState",
                 &[("nVirtKey", &nVirtKey)],
             )
@@ -1306,11 +2125,11 @@ mod wrappers {
     }
     pub unsafe fn GetKeyboardState(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let lpKeyState = <Option<&mut u8>>::from_stack(mem, stack_args + 0u32);
-        let __trace_record = if crate::trace::enabled("user32/misc") {
+        let lpKeyState = <u32>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/keyboard") {
             crate::trace::Record::new(
                 winapi::user32::GetKeyboardState_pos,
-                "user32/misc",
+                "user32/keyboard",
                 "GetKeyboardState",
                 &[("lpKeyState", &lpKeyState)],
             )
@@ -1318,94 +2137,216 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::GetKeyboardState(machine, lpKeyState);
+        let result = winapi::user32::GetKeyboardState(machine, lpKeyState);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetKeyboardType(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let nTypeFlag = <i32>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/misc") {
+            crate::trace::Record::new(
+                winapi::user32::GetKeyboardType_pos,
+                "user32/misc",
+                "GetKeyboardType",
+                &[("nTypeFlag", &nTypeFlag)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetKeyboardType(machine, nTypeFlag);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetLastActivePopup(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::GetLastActivePopup_pos,
+                "user32/window",
+                "GetLastActivePopup",
+                &[],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetLastActivePopup(machine);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetMenu(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::GetMenu_pos,
+                "user32/menu",
+                "GetMenu",
+                &[("hWnd", &hWnd)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetMenu(machine, hWnd);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetMenuItemCount(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::GetMenuItemCount_pos,
+                "user32/menu",
+                "GetMenuItemCount",
+                &[("hMenu", &hMenu)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetMenuItemCount(machine, hMenu);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetMenuItemID(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let nPos = <i32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::GetMenuItemID_pos,
+                "user32/menu",
+                "GetMenuItemID",
+                &[("hMenu", &hMenu), ("nPos", &nPos)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetMenuItemID(machine, hMenu, nPos);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
         result.into_abireturn()
     }
-    pub unsafe fn GetKeyboardType(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn GetMenuItemInfoA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let nTypeFlag = <i32>::from_stack(mem, stack_args + 0u32);
-        let __trace_record = if crate::trace::enabled("user32/misc") {
+        let hmenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let item = <u32>::from_stack(mem, stack_args + 4u32);
+        let fByPosition = <bool>::from_stack(mem, stack_args + 8u32);
+        let lpmii = <Option<&mut MENUITEMINFOA>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
-                winapi::user32::GetKeyboardType_pos,
-                "user32/misc",
-                "GetKeyboardType",
-                &[("nTypeFlag", &nTypeFlag)],
+                winapi::user32::GetMenuItemInfoA_pos,
+                "user32/menu",
+                "GetMenuItemInfoA",
+                &[
+                    ("hmenu", &hmenu),
+                    ("item", &item),
+                    ("fByPosition", &fByPosition),
+                    ("lpmii", &lpmii),
+                ],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::user32::GetKeyboardType(machine, nTypeFlag);
+        let result = winapi::user32::GetMenuItemInfoA(machine, hmenu, item, fByPosition, lpmii);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
         result.into_abireturn()
     }
-    pub unsafe fn GetLastActivePopup(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn GetMenuItemRect(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let __trace_record = if crate::trace::enabled("user32/window") {
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 4u32);
+        let uItem = <u32>::from_stack(mem, stack_args + 8u32);
+        let lprcItem = <Option<&mut RECT>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
-                winapi::user32::GetLastActivePopup_pos,
-                "user32/window",
-                "GetLastActivePopup",
-                &[],
+                winapi::user32::GetMenuItemRect_pos,
+                "user32/menu",
+                "GetMenuItemRect",
+                &[
+                    ("hWnd", &hWnd),
+                    ("hMenu", &hMenu),
+                    ("uItem", &uItem),
+                    ("lprcItem", &lprcItem),
+                ],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::user32::GetLastActivePopup(machine);
+        let result = winapi::user32::GetMenuItemRect(machine, hWnd, hMenu, uItem, lprcItem);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
         result.into_abireturn()
     }
-    pub unsafe fn GetMenu(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn GetMenuState(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uId = <u32>::from_stack(mem, stack_args + 4u32);
+        let uFlags = <Result<MF, u32>>::from_stack(mem, stack_args + 8u32);
         let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
-                winapi::user32::GetMenu_pos,
+                winapi::user32::GetMenuState_pos,
                 "user32/menu",
-                "GetMenu",
-                &[("hWnd", &hWnd)],
+                "GetMenuState",
+                &[("hMenu", &hMenu), ("uId", &uId), ("uFlags", &uFlags)],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::user32::GetMenu(machine, hWnd);
+        let result = winapi::user32::GetMenuState(machine, hMenu, uId, uFlags);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
         result.into_abireturn()
     }
-    pub unsafe fn GetMenuItemRect(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn GetMenuStringA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-        let hMenu = <HMENU>::from_stack(mem, stack_args + 4u32);
-        let uItem = <u32>::from_stack(mem, stack_args + 8u32);
-        let lprcItem = <Option<&mut RECT>>::from_stack(mem, stack_args + 12u32);
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uIDItem = <u32>::from_stack(mem, stack_args + 4u32);
+        let lpString = <u32>::from_stack(mem, stack_args + 8u32);
+        let cchMax = <i32>::from_stack(mem, stack_args + 12u32);
+        let flags = <Result<MF, u32>>::from_stack(mem, stack_args + 16u32);
         let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
-                winapi::user32::GetMenuItemRect_pos,
+                winapi::user32::GetMenuStringA_pos,
                 "user32/menu",
-                "GetMenuItemRect",
+                "GetMenuStringA",
                 &[
-                    ("hWnd", &hWnd),
                     ("hMenu", &hMenu),
-                    ("uItem", &uItem),
-                    ("lprcItem", &lprcItem),
+                    ("uIDItem", &uIDItem),
+                    ("lpString", &lpString),
+                    ("cchMax", &cchMax),
+                    ("flags", &flags),
                 ],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::user32::GetMenuItemRect(machine, hWnd, hMenu, uItem, lprcItem);
+        let result =
+            winapi::user32::GetMenuStringA(machine, hMenu, uIDItem, lpString, cchMax, flags);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
@@ -1506,6 +2447,26 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetParent(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::GetParent_pos,
+                "user32/window",
+                "GetParent",
+                &[("hWnd", &hWnd)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetParent(machine, hWnd);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetQueueStatus(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let flags = <Result<QS, u32>>::from_stack(mem, stack_args + 0u32);
@@ -1549,7 +2510,7 @@ mod wrappers {
     }
     pub unsafe fn GetSysColor(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let nIndex = <i32>::from_stack(mem, stack_args + 0u32);
+        let nIndex = <Result<COLOR, u32>>::from_stack(mem, stack_args + 0u32);
         let __trace_record = if crate::trace::enabled("user32/misc") {
             crate::trace::Record::new(
                 winapi::user32::GetSysColor_pos,
@@ -1608,6 +2569,26 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetTopWindow(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::GetTopWindow_pos,
+                "user32/window",
+                "GetTopWindow",
+                &[("hWnd", &hWnd)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetTopWindow(machine, hWnd);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetUpdateRect(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
@@ -1630,6 +2611,49 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetUpdateRgn(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let hRgn = <HRGN>::from_stack(mem, stack_args + 4u32);
+        let bErase = <bool>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("user32/paint") {
+            crate::trace::Record::new(
+                winapi::user32::GetUpdateRgn_pos,
+                "user32/paint",
+                "GetUpdateRgn",
+                &[("hWnd", &hWnd), ("hRgn", &hRgn), ("bErase", &bErase)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetUpdateRgn(machine, hWnd, hRgn, bErase);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetWindow(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let uCmd = <Result<GW, u32>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::GetWindow_pos,
+                "user32/window",
+                "GetWindow",
+                &[("hWnd", &hWnd), ("uCmd", &uCmd)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetWindow(machine, hWnd, uCmd);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetWindowDC(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
@@ -1671,6 +2695,27 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetWindowLongW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let nIndex = <i32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::GetWindowLongW_pos,
+                "user32/window",
+                "GetWindowLongW",
+                &[("hWnd", &hWnd), ("nIndex", &nIndex)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetWindowLongW(machine, hWnd, nIndex);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn GetWindowPlacement(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
@@ -1713,6 +2758,69 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn GetWindowTextA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let lpString = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::GetWindowTextA_pos,
+                "user32/window",
+                "GetWindowTextA",
+                &[("hWnd", &hWnd), ("lpString", &lpString)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetWindowTextA(machine, hWnd, lpString);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetWindowTextW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let lpString = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::GetWindowTextW_pos,
+                "user32/window",
+                "GetWindowTextW",
+                &[("hWnd", &hWnd), ("lpString", &lpString)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetWindowTextW(machine, hWnd, lpString);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn GetWindowThreadProcessId(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let lpdwProcessId = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::GetWindowThreadProcessId_pos,
+                "user32/window",
+                "GetWindowThreadProcessId",
+                &[("hWnd", &hWnd), ("lpdwProcessId", &lpdwProcessId)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::GetWindowThreadProcessId(machine, hWnd, lpdwProcessId);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn InflateRect(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lprc = <Option<&mut RECT>>::from_stack(mem, stack_args + 0u32);
@@ -1729,7 +2837,97 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::InflateRect(machine, lprc, dx, dy);
+        let result = winapi::user32::InflateRect(machine, lprc, dx, dy);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn InsertMenuA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uPosition = <u32>::from_stack(mem, stack_args + 4u32);
+        let uFlags = <Result<MF, u32>>::from_stack(mem, stack_args + 8u32);
+        let uIDNewItem = <u32>::from_stack(mem, stack_args + 12u32);
+        let lpNewItem = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::InsertMenuA_pos,
+                "user32/menu",
+                "InsertMenuA",
+                &[
+                    ("hMenu", &hMenu),
+                    ("uPosition", &uPosition),
+                    ("uFlags", &uFlags),
+                    ("uIDNewItem", &uIDNewItem),
+                    ("lpNewItem", &lpNewItem),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::user32::InsertMenuA(machine, hMenu, uPosition, uFlags, uIDNewItem, lpNewItem);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn InsertMenuItemA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hmenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let item = <u32>::from_stack(mem, stack_args + 4u32);
+        let fByPosition = <bool>::from_stack(mem, stack_args + 8u32);
+        let lpmi = <Option<&MENUITEMINFOA>>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::InsertMenuItemA_pos,
+                "user32/menu",
+                "InsertMenuItemA",
+                &[
+                    ("hmenu", &hmenu),
+                    ("item", &item),
+                    ("fByPosition", &fByPosition),
+                    ("lpmi", &lpmi),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::InsertMenuItemA(machine, hmenu, item, fByPosition, lpmi);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn InsertMenuW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uPosition = <u32>::from_stack(mem, stack_args + 4u32);
+        let uFlags = <Result<MF, u32>>::from_stack(mem, stack_args + 8u32);
+        let uIDNewItem = <u32>::from_stack(mem, stack_args + 12u32);
+        let lpNewItem = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::InsertMenuW_pos,
+                "user32/menu",
+                "InsertMenuW",
+                &[
+                    ("hMenu", &hMenu),
+                    ("uPosition", &uPosition),
+                    ("uFlags", &uFlags),
+                    ("uIDNewItem", &uIDNewItem),
+                    ("lpNewItem", &lpNewItem),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::user32::InsertMenuW(machine, hMenu, uPosition, uFlags, uIDNewItem, lpNewItem);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
@@ -1826,7 +3024,87 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn IsDlgButtonChecked(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn IsChild(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWndParent = <HWND>::from_stack(mem, stack_args + 0u32);
+        let hWnd = <HWND>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::IsChild_pos,
+                "user32/window",
+                "IsChild",
+                &[("hWndParent", &hWndParent), ("hWnd", &hWnd)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::IsChild(machine, hWndParent, hWnd);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn IsDialogMessageA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
+        let lpMsg = <Option<&MSG>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::IsDialogMessageA_pos,
+                "user32/dialog",
+                "IsDialogMessageA",
+                &[("hDlg", &hDlg), ("lpMsg", &lpMsg)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::IsDialogMessageA(machine, hDlg, lpMsg).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn IsDialogMessageW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
+        let lpMsg = <Option<&MSG>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::IsDialogMessageW_pos,
+                "user32/dialog",
+                "IsDialogMessageW",
+                &[("hDlg", &hDlg), ("lpMsg", &lpMsg)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::IsDialogMessageW(machine, hDlg, lpMsg).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn IsDlgButtonChecked(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
         let nIDButton = <i32>::from_stack(mem, stack_args + 4u32);
@@ -1841,11 +3119,15 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::IsDlgButtonChecked(machine, hDlg, nIDButton);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::IsDlgButtonChecked(machine, hDlg, nIDButton).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn IsIconic(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
@@ -1867,6 +3149,26 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IsMenu(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::IsMenu_pos,
+                "user32/menu",
+                "IsMenu",
+                &[("hMenu", &hMenu)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::IsMenu(machine, hMenu);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IsRectEmpty(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lprc = <Option<&RECT>>::from_stack(mem, stack_args + 0u32);
@@ -1887,6 +3189,26 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn IsWindow(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::IsWindow_pos,
+                "user32/window",
+                "IsWindow",
+                &[("hWnd", &hWnd)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::IsWindow(machine, hWnd);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn IsWindowVisible(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
@@ -1928,14 +3250,70 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn ListBoxWndProc(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let msg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/controls/listbox") {
+            crate::trace::Record::new(
+                winapi::user32::ListBoxWndProc_pos,
+                "user32/controls/listbox",
+                "ListBoxWndProc",
+                &[
+                    ("hWnd", &hWnd),
+                    ("msg", &msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::ListBoxWndProc(machine, hWnd, msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn LoadAcceleratorsA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpTableName = <ResourceKey<&str>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::LoadAcceleratorsA_pos,
+                "user32/menu",
+                "LoadAcceleratorsA",
+                &[("hInstance", &hInstance), ("lpTableName", &lpTableName)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::LoadAcceleratorsA(machine, hInstance, lpTableName);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn LoadAcceleratorsW(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let hInstance = <u32>::from_stack(mem, stack_args + 0u32);
-        let lpTableName = <u32>::from_stack(mem, stack_args + 4u32);
-        let __trace_record = if crate::trace::enabled("user32/resource") {
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpTableName = <ResourceKey<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
                 winapi::user32::LoadAcceleratorsW_pos,
-                "user32/resource",
+                "user32/menu",
                 "LoadAcceleratorsW",
                 &[("hInstance", &hInstance), ("lpTableName", &lpTableName)],
             )
@@ -2120,8 +3498,8 @@ mod wrappers {
     }
     pub unsafe fn LoadMenuA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let hInstance = <u32>::from_stack(mem, stack_args + 0u32);
-        let lpMenuName = <u32>::from_stack(mem, stack_args + 4u32);
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpMenuName = <ResourceKey<&str>>::from_stack(mem, stack_args + 4u32);
         let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
                 winapi::user32::LoadMenuA_pos,
@@ -2141,12 +3519,12 @@ mod wrappers {
     }
     pub unsafe fn LoadMenuW(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
-        let hInstance = <u32>::from_stack(mem, stack_args + 0u32);
-        let lpMenuName = <u32>::from_stack(mem, stack_args + 4u32);
-        let __trace_record = if crate::trace::enabled("user32/resource") {
+        let hInstance = <HINSTANCE>::from_stack(mem, stack_args + 0u32);
+        let lpMenuName = <ResourceKey<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
                 winapi::user32::LoadMenuW_pos,
-                "user32/resource",
+                "user32/menu",
                 "LoadMenuW",
                 &[("hInstance", &hInstance), ("lpMenuName", &lpMenuName)],
             )
@@ -2216,11 +3594,53 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn MapDialogRect(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
+        let lpRect = <Option<&mut RECT>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/dialog") {
+            crate::trace::Record::new(
+                winapi::user32::MapDialogRect_pos,
+                "user32/dialog",
+                "MapDialogRect",
+                &[("hDlg", &hDlg), ("lpRect", &lpRect)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::MapDialogRect(machine, hDlg, lpRect);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn MapVirtualKeyA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let uCode = <u32>::from_stack(mem, stack_args + 0u32);
+        let uMapType = <u32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/keyboard") {
+            crate::trace::Record::new(
+                winapi::user32::MapVirtualKeyA_pos,
+                "user32/keyboard",
+                "MapVirtualKeyA",
+                &[("uCode", &uCode), ("uMapType", &uMapType)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::MapVirtualKeyA(machine, uCode, uMapType);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn MapWindowPoints(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hWndFrom = <HWND>::from_stack(mem, stack_args + 0u32);
         let hWndTo = <HWND>::from_stack(mem, stack_args + 4u32);
-        let lpPoints = <ArrayWithSize<POINT>>::from_stack(mem, stack_args + 8u32);
+        let lpPoints = <ArrayWithSizeMut<POINT>>::from_stack(mem, stack_args + 8u32);
         let __trace_record = if crate::trace::enabled("user32/window") {
             crate::trace::Record::new(
                 winapi::user32::MapWindowPoints_pos,
@@ -2298,13 +3718,78 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn MoveWindow(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn ModifyMenuA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMnu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uPosition = <u32>::from_stack(mem, stack_args + 4u32);
+        let uFlags = <Result<MF, u32>>::from_stack(mem, stack_args + 8u32);
+        let uIDNewItem = <u32>::from_stack(mem, stack_args + 12u32);
+        let lpNewItem = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::ModifyMenuA_pos,
+                "user32/menu",
+                "ModifyMenuA",
+                &[
+                    ("hMnu", &hMnu),
+                    ("uPosition", &uPosition),
+                    ("uFlags", &uFlags),
+                    ("uIDNewItem", &uIDNewItem),
+                    ("lpNewItem", &lpNewItem),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::user32::ModifyMenuA(machine, hMnu, uPosition, uFlags, uIDNewItem, lpNewItem);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn ModifyMenuW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMnu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uPosition = <u32>::from_stack(mem, stack_args + 4u32);
+        let uFlags = <Result<MF, u32>>::from_stack(mem, stack_args + 8u32);
+        let uIDNewItem = <u32>::from_stack(mem, stack_args + 12u32);
+        let lpNewItem = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::ModifyMenuW_pos,
+                "user32/menu",
+                "ModifyMenuW",
+                &[
+                    ("hMnu", &hMnu),
+                    ("uPosition", &uPosition),
+                    ("uFlags", &uFlags),
+                    ("uIDNewItem", &uIDNewItem),
+                    ("lpNewItem", &lpNewItem),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::user32::ModifyMenuW(machine, hMnu, uPosition, uFlags, uIDNewItem, lpNewItem);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn MoveWindow(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-        let X = <u32>::from_stack(mem, stack_args + 4u32);
-        let Y = <u32>::from_stack(mem, stack_args + 8u32);
-        let nWidth = <u32>::from_stack(mem, stack_args + 12u32);
-        let nHeight = <u32>::from_stack(mem, stack_args + 16u32);
+        let X = <i32>::from_stack(mem, stack_args + 4u32);
+        let Y = <i32>::from_stack(mem, stack_args + 8u32);
+        let nWidth = <i32>::from_stack(mem, stack_args + 12u32);
+        let nHeight = <i32>::from_stack(mem, stack_args + 16u32);
         let bRepaint = <bool>::from_stack(mem, stack_args + 20u32);
         let __trace_record = if crate::trace::enabled("user32/window") {
             crate::trace::Record::new(
@@ -2324,11 +3809,16 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::MoveWindow(machine, hWnd, X, Y, nWidth, nHeight, bRepaint);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result =
+                winapi::user32::MoveWindow(machine, hWnd, X, Y, nWidth, nHeight, bRepaint).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn MsgWaitForMultipleObjects(
         machine: &mut Machine,
@@ -2396,7 +3886,10 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn PeekMessageA(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn PeekMessageA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let lpMsg = <Option<&mut MSG>>::from_stack(mem, stack_args + 0u32);
         let hWnd = <HWND>::from_stack(mem, stack_args + 4u32);
@@ -2420,20 +3913,28 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::PeekMessageA(
-            machine,
-            lpMsg,
-            hWnd,
-            wMsgFilterMin,
-            wMsgFilterMax,
-            wRemoveMsg,
-        );
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::PeekMessageA(
+                machine,
+                lpMsg,
+                hWnd,
+                wMsgFilterMin,
+                wMsgFilterMax,
+                wRemoveMsg,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn PeekMessageW(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn PeekMessageW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let lpMsg = <Option<&mut MSG>>::from_stack(mem, stack_args + 0u32);
         let hWnd = <HWND>::from_stack(mem, stack_args + 4u32);
@@ -2457,18 +3958,23 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::PeekMessageW(
-            machine,
-            lpMsg,
-            hWnd,
-            wMsgFilterMin,
-            wMsgFilterMax,
-            wRemoveMsg,
-        );
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::PeekMessageW(
+                machine,
+                lpMsg,
+                hWnd,
+                wMsgFilterMin,
+                wMsgFilterMax,
+                wRemoveMsg,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn PostMessageA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
@@ -2574,6 +4080,34 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn PostThreadMessageW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let idThread = <u32>::from_stack(mem, stack_args + 0u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/message") {
+            crate::trace::Record::new(
+                winapi::user32::PostThreadMessageW_pos,
+                "user32/message",
+                "PostThreadMessageW",
+                &[
+                    ("idThread", &idThread),
+                    ("Msg", &Msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::PostThreadMessageW(machine, idThread, Msg, wParam, lParam);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn PtInRect(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lprc = <Option<&RECT>>::from_stack(mem, stack_args + 0u32);
@@ -2796,36 +4330,281 @@ mod wrappers {
         let hdc = <HDC>::from_stack(mem, stack_args + 4u32);
         let __trace_record = if crate::trace::enabled("user32/window") {
             crate::trace::Record::new(
-                winapi::user32::ReleaseDC_pos,
-                "user32/window",
-                "ReleaseDC",
-                &[("hwnd", &hwnd), ("hdc", &hdc)],
+                winapi::user32::ReleaseDC_pos,
+                "user32/window",
+                "ReleaseDC",
+                &[("hwnd", &hwnd), ("hdc", &hdc)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::ReleaseDC(machine, hwnd, hdc);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn RemoveMenu(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uPosition = <u32>::from_stack(mem, stack_args + 4u32);
+        let uFlags = <Result<MF, u32>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::RemoveMenu_pos,
+                "user32/menu",
+                "RemoveMenu",
+                &[
+                    ("hMenu", &hMenu),
+                    ("uPosition", &uPosition),
+                    ("uFlags", &uFlags),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::RemoveMenu(machine, hMenu, uPosition, uFlags);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn ScreenToClient(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let lpPoint = <Option<&mut POINT>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::ScreenToClient_pos,
+                "user32/window",
+                "ScreenToClient",
+                &[("hWnd", &hWnd), ("lpPoint", &lpPoint)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::ScreenToClient(machine, hWnd, lpPoint);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn ScrollBarWndProc(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let msg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/controls/scrollbar") {
+            crate::trace::Record::new(
+                winapi::user32::ScrollBarWndProc_pos,
+                "user32/controls/scrollbar",
+                "ScrollBarWndProc",
+                &[
+                    ("hWnd", &hWnd),
+                    ("msg", &msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::ScrollBarWndProc(machine, hWnd, msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn SendDlgItemMessageA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
+        let nIDDlgItem = <i32>::from_stack(mem, stack_args + 4u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 8u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 16u32);
+        let __trace_record = if crate::trace::enabled("user32/message") {
+            crate::trace::Record::new(
+                winapi::user32::SendDlgItemMessageA_pos,
+                "user32/message",
+                "SendDlgItemMessageA",
+                &[
+                    ("hDlg", &hDlg),
+                    ("nIDDlgItem", &nIDDlgItem),
+                    ("Msg", &Msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result =
+                winapi::user32::SendDlgItemMessageA(machine, hDlg, nIDDlgItem, Msg, wParam, lParam)
+                    .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn SendMessageA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/message") {
+            crate::trace::Record::new(
+                winapi::user32::SendMessageA_pos,
+                "user32/message",
+                "SendMessageA",
+                &[
+                    ("hWnd", &hWnd),
+                    ("Msg", &Msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::SendMessageA(machine, hWnd, Msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn SendMessageTimeoutA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let fuFlags = <Result<SMTO, u32>>::from_stack(mem, stack_args + 16u32);
+        let uTimeout = <u32>::from_stack(mem, stack_args + 20u32);
+        let lpdwResult = <Option<&mut u32>>::from_stack(mem, stack_args + 24u32);
+        let __trace_record = if crate::trace::enabled("user32/message") {
+            crate::trace::Record::new(
+                winapi::user32::SendMessageTimeoutA_pos,
+                "user32/message",
+                "SendMessageTimeoutA",
+                &[
+                    ("hWnd", &hWnd),
+                    ("Msg", &Msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                    ("fuFlags", &fuFlags),
+                    ("uTimeout", &uTimeout),
+                    ("lpdwResult", &lpdwResult),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::SendMessageTimeoutA(
+                machine, hWnd, Msg, wParam, lParam, fuFlags, uTimeout, lpdwResult,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn SendMessageTimeoutW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let fuFlags = <Result<SMTO, u32>>::from_stack(mem, stack_args + 16u32);
+        let uTimeout = <u32>::from_stack(mem, stack_args + 20u32);
+        let lpdwResult = <Option<&mut u32>>::from_stack(mem, stack_args + 24u32);
+        let __trace_record = if crate::trace::enabled("user32/message") {
+            crate::trace::Record::new(
+                winapi::user32::SendMessageTimeoutW_pos,
+                "user32/message",
+                "SendMessageTimeoutW",
+                &[
+                    ("hWnd", &hWnd),
+                    ("Msg", &Msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                    ("fuFlags", &fuFlags),
+                    ("uTimeout", &uTimeout),
+                    ("lpdwResult", &lpdwResult),
+                ],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::user32::ReleaseDC(machine, hwnd, hdc);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::SendMessageTimeoutW(
+                machine, hWnd, Msg, wParam, lParam, fuFlags, uTimeout, lpdwResult,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn SendDlgItemMessageA(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn SendMessageW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
-        let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
-        let nIDDlgItem = <i32>::from_stack(mem, stack_args + 4u32);
-        let Msg = <u32>::from_stack(mem, stack_args + 8u32);
-        let wParam = <u32>::from_stack(mem, stack_args + 12u32);
-        let lParam = <u32>::from_stack(mem, stack_args + 16u32);
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("user32/message") {
             crate::trace::Record::new(
-                winapi::user32::SendDlgItemMessageA_pos,
+                winapi::user32::SendMessageW_pos,
                 "user32/message",
-                "SendDlgItemMessageA",
+                "SendMessageW",
                 &[
-                    ("hDlg", &hDlg),
-                    ("nIDDlgItem", &nIDDlgItem),
+                    ("hWnd", &hWnd),
                     ("Msg", &Msg),
                     ("wParam", &wParam),
                     ("lParam", &lParam),
@@ -2835,27 +4614,30 @@ mod wrappers {
         } else {
             None
         };
-        let result =
-            winapi::user32::SendDlgItemMessageA(machine, hDlg, nIDDlgItem, Msg, wParam, lParam);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::SendMessageW(machine, hWnd, Msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn SendMessageA(
+    pub unsafe fn SendNotifyMessageA(
         machine: &mut Machine,
         stack_args: u32,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-        let Msg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 4u32);
         let wParam = <u32>::from_stack(mem, stack_args + 8u32);
         let lParam = <u32>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("user32/message") {
             crate::trace::Record::new(
-                winapi::user32::SendMessageA_pos,
+                winapi::user32::SendNotifyMessageA_pos,
                 "user32/message",
-                "SendMessageA",
+                "SendNotifyMessageA",
                 &[
                     ("hWnd", &hWnd),
                     ("Msg", &Msg),
@@ -2870,27 +4652,28 @@ mod wrappers {
         let machine: *mut Machine = machine;
         Box::pin(async move {
             let machine = unsafe { &mut *machine };
-            let result = winapi::user32::SendMessageA(machine, hWnd, Msg, wParam, lParam).await;
+            let result =
+                winapi::user32::SendNotifyMessageA(machine, hWnd, Msg, wParam, lParam).await;
             if let Some(mut __trace_record) = __trace_record {
                 __trace_record.exit(&result);
             }
             result.into_abireturn()
         })
     }
-    pub unsafe fn SendMessageW(
+    pub unsafe fn SendNotifyMessageW(
         machine: &mut Machine,
         stack_args: u32,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-        let Msg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+        let Msg = <u32>::from_stack(mem, stack_args + 4u32);
         let wParam = <u32>::from_stack(mem, stack_args + 8u32);
         let lParam = <u32>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("user32/message") {
             crate::trace::Record::new(
-                winapi::user32::SendMessageW_pos,
+                winapi::user32::SendNotifyMessageW_pos,
                 "user32/message",
-                "SendMessageW",
+                "SendNotifyMessageW",
                 &[
                     ("hWnd", &hWnd),
                     ("Msg", &Msg),
@@ -2905,7 +4688,8 @@ mod wrappers {
         let machine: *mut Machine = machine;
         Box::pin(async move {
             let machine = unsafe { &mut *machine };
-            let result = winapi::user32::SendMessageW(machine, hWnd, Msg, wParam, lParam).await;
+            let result =
+                winapi::user32::SendNotifyMessageW(machine, hWnd, Msg, wParam, lParam).await;
             if let Some(mut __trace_record) = __trace_record {
                 __trace_record.exit(&result);
             }
@@ -3004,7 +4788,7 @@ mod wrappers {
         let hDlg = <HWND>::from_stack(mem, stack_args + 0u32);
         let nIDDlgItem = <i32>::from_stack(mem, stack_args + 4u32);
         let uValue = <u32>::from_stack(mem, stack_args + 8u32);
-        let _bSigned = <bool>::from_stack(mem, stack_args + 12u32);
+        let bSigned = <bool>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("user32/dialog") {
             crate::trace::Record::new(
                 winapi::user32::SetDlgItemInt_pos,
@@ -3014,14 +4798,14 @@ mod wrappers {
                     ("hDlg", &hDlg),
                     ("nIDDlgItem", &nIDDlgItem),
                     ("uValue", &uValue),
-                    ("bSigned", &_bSigned),
+                    ("bSigned", &bSigned),
                 ],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::user32::SetDlgItemInt(machine, hDlg, nIDDlgItem, uValue, _bSigned);
+        let result = winapi::user32::SetDlgItemInt(machine, hDlg, nIDDlgItem, uValue, bSigned);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
@@ -3079,7 +4863,10 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn SetFocus(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn SetFocus(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
         let __trace_record = if crate::trace::enabled("user32/window") {
@@ -3093,11 +4880,15 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::SetFocus(machine, hWnd);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::SetFocus(machine, hWnd).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn SetForegroundWindow(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
@@ -3145,7 +4936,7 @@ mod wrappers {
         let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
         let item = <u32>::from_stack(mem, stack_args + 4u32);
         let fByPosition = <bool>::from_stack(mem, stack_args + 8u32);
-        let lpmii = <u32>::from_stack(mem, stack_args + 12u32);
+        let lpmii = <Option<&MENUITEMINFOA>>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
                 winapi::user32::SetMenuItemInfoA_pos,
@@ -3168,6 +4959,27 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn SetParent(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWndChild = <HWND>::from_stack(mem, stack_args + 0u32);
+        let hWndNewParent = <HWND>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::SetParent_pos,
+                "user32/window",
+                "SetParent",
+                &[("hWndChild", &hWndChild), ("hWndNewParent", &hWndNewParent)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::SetParent(machine, hWndChild, hWndNewParent);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn SetRect(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let lprc = <Option<&mut RECT>>::from_stack(mem, stack_args + 0u32);
@@ -3249,7 +5061,7 @@ mod wrappers {
     pub unsafe fn SetWindowLongA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-        let nIndex = <u32>::from_stack(mem, stack_args + 4u32);
+        let nIndex = <i32>::from_stack(mem, stack_args + 4u32);
         let dwNewLong = <i32>::from_stack(mem, stack_args + 8u32);
         let __trace_record = if crate::trace::enabled("user32/window") {
             crate::trace::Record::new(
@@ -3272,6 +5084,32 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn SetWindowLongW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let nIndex = <i32>::from_stack(mem, stack_args + 4u32);
+        let dwNewLong = <i32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::SetWindowLongW_pos,
+                "user32/window",
+                "SetWindowLongW",
+                &[
+                    ("hWnd", &hWnd),
+                    ("nIndex", &nIndex),
+                    ("dwNewLong", &dwNewLong),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::SetWindowLongW(machine, hWnd, nIndex, dwNewLong);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn SetWindowPos(
         machine: &mut Machine,
         stack_args: u32,
@@ -3336,6 +5174,27 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn SetWindowTextW(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let lpString = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::SetWindowTextW_pos,
+                "user32/window",
+                "SetWindowTextW",
+                &[("hWnd", &hWnd), ("lpString", &lpString)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::SetWindowTextW(machine, hWnd, lpString);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn SetWindowsHookExA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let idHook = <u32>::from_stack(mem, stack_args + 0u32);
@@ -3384,19 +5243,166 @@ mod wrappers {
         }
         result.into_abireturn()
     }
-    pub unsafe fn ShowWindow(
+    pub unsafe fn ShowWindow(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let nCmdShow = <Result<SW, u32>>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::ShowWindow_pos,
+                "user32/window",
+                "ShowWindow",
+                &[("hWnd", &hWnd), ("nCmdShow", &nCmdShow)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::ShowWindow(machine, hWnd, nCmdShow).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn StaticWndProc(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let msg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+        let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/controls/static_") {
+            crate::trace::Record::new(
+                winapi::user32::StaticWndProc_pos,
+                "user32/controls/static_",
+                "StaticWndProc",
+                &[
+                    ("hWnd", &hWnd),
+                    ("msg", &msg),
+                    ("wParam", &wParam),
+                    ("lParam", &lParam),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::StaticWndProc(machine, hWnd, msg, wParam, lParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn SystemParametersInfoA(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let uiAction = <Result<SPI, u32>>::from_stack(mem, stack_args + 0u32);
+        let uiParam = <u32>::from_stack(mem, stack_args + 4u32);
+        let pvParam = <u32>::from_stack(mem, stack_args + 8u32);
+        let fWinIni = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("user32/misc") {
+            crate::trace::Record::new(
+                winapi::user32::SystemParametersInfoA_pos,
+                "user32/misc",
+                "SystemParametersInfoA",
+                &[
+                    ("uiAction", &uiAction),
+                    ("uiParam", &uiParam),
+                    ("pvParam", &pvParam),
+                    ("fWinIni", &fWinIni),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result =
+            winapi::user32::SystemParametersInfoA(machine, uiAction, uiParam, pvParam, fWinIni);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn TrackPopupMenu(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uFlags = <u32>::from_stack(mem, stack_args + 4u32);
+        let x = <i32>::from_stack(mem, stack_args + 8u32);
+        let y = <i32>::from_stack(mem, stack_args + 12u32);
+        let nReserved = <i32>::from_stack(mem, stack_args + 16u32);
+        let hWnd = <HWND>::from_stack(mem, stack_args + 20u32);
+        let prcRect = <Option<&RECT>>::from_stack(mem, stack_args + 24u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
+            crate::trace::Record::new(
+                winapi::user32::TrackPopupMenu_pos,
+                "user32/menu",
+                "TrackPopupMenu",
+                &[
+                    ("hMenu", &hMenu),
+                    ("uFlags", &uFlags),
+                    ("x", &x),
+                    ("y", &y),
+                    ("nReserved", &nReserved),
+                    ("hWnd", &hWnd),
+                    ("prcRect", &prcRect),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::user32::TrackPopupMenu(
+                machine, hMenu, uFlags, x, y, nReserved, hWnd, prcRect,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn TrackPopupMenuEx(
         machine: &mut Machine,
         stack_args: u32,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
-        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-        let nCmdShow = <Result<SW, u32>>::from_stack(mem, stack_args + 4u32);
-        let __trace_record = if crate::trace::enabled("user32/window") {
+        let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+        let uFlags = <u32>::from_stack(mem, stack_args + 4u32);
+        let x = <i32>::from_stack(mem, stack_args + 8u32);
+        let y = <i32>::from_stack(mem, stack_args + 12u32);
+        let hwnd = <HWND>::from_stack(mem, stack_args + 16u32);
+        let lptpm = <u32>::from_stack(mem, stack_args + 20u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
-                winapi::user32::ShowWindow_pos,
-                "user32/window",
-                "ShowWindow",
-                &[("hWnd", &hWnd), ("nCmdShow", &nCmdShow)],
+                winapi::user32::TrackPopupMenuEx_pos,
+                "user32/menu",
+                "TrackPopupMenuEx",
+                &[
+                    ("hMenu", &hMenu),
+                    ("uFlags", &uFlags),
+                    ("x", &x),
+                    ("y", &y),
+                    ("hwnd", &hwnd),
+                    ("lptpm", &lptpm),
+                ],
             )
             .enter()
         } else {
@@ -3405,51 +5411,60 @@ mod wrappers {
         let machine: *mut Machine = machine;
         Box::pin(async move {
             let machine = unsafe { &mut *machine };
-            let result = winapi::user32::ShowWindow(machine, hWnd, nCmdShow).await;
+            let result =
+                winapi::user32::TrackPopupMenuEx(machine, hMenu, uFlags, x, y, hwnd, lptpm).await;
             if let Some(mut __trace_record) = __trace_record {
                 __trace_record.exit(&result);
             }
             result.into_abireturn()
         })
     }
-    pub unsafe fn SystemParametersInfoA(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn TranslateAcceleratorA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
-        let uiAction = <Result<SPI, u32>>::from_stack(mem, stack_args + 0u32);
-        let uiParam = <u32>::from_stack(mem, stack_args + 4u32);
-        let pvParam = <u32>::from_stack(mem, stack_args + 8u32);
-        let fWinIni = <u32>::from_stack(mem, stack_args + 12u32);
-        let __trace_record = if crate::trace::enabled("user32/misc") {
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let hAccTable = <HACCEL>::from_stack(mem, stack_args + 4u32);
+        let lpMsg = <Option<&MSG>>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
-                winapi::user32::SystemParametersInfoA_pos,
-                "user32/misc",
-                "SystemParametersInfoA",
+                winapi::user32::TranslateAcceleratorA_pos,
+                "user32/menu",
+                "TranslateAcceleratorA",
                 &[
-                    ("uiAction", &uiAction),
-                    ("uiParam", &uiParam),
-                    ("pvParam", &pvParam),
-                    ("fWinIni", &fWinIni),
+                    ("hWnd", &hWnd),
+                    ("hAccTable", &hAccTable),
+                    ("lpMsg", &lpMsg),
                 ],
             )
             .enter()
         } else {
             None
         };
-        let result =
-            winapi::user32::SystemParametersInfoA(machine, uiAction, uiParam, pvParam, fWinIni);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result =
+                winapi::user32::TranslateAcceleratorA(machine, hWnd, hAccTable, lpMsg).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn TranslateAcceleratorW(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn TranslateAcceleratorW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-        let hAccTable = <u32>::from_stack(mem, stack_args + 4u32);
+        let hAccTable = <HACCEL>::from_stack(mem, stack_args + 4u32);
         let lpMsg = <Option<&MSG>>::from_stack(mem, stack_args + 8u32);
-        let __trace_record = if crate::trace::enabled("user32/message") {
+        let __trace_record = if crate::trace::enabled("user32/menu") {
             crate::trace::Record::new(
                 winapi::user32::TranslateAcceleratorW_pos,
-                "user32/message",
+                "user32/menu",
                 "TranslateAcceleratorW",
                 &[
                     ("hWnd", &hWnd),
@@ -3461,11 +5476,16 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::user32::TranslateAcceleratorW(machine, hWnd, hAccTable, lpMsg);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result =
+                winapi::user32::TranslateAcceleratorW(machine, hWnd, hAccTable, lpMsg).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn TranslateMessage(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
@@ -3535,6 +5555,27 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn ValidateRgn(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+        let hRgn = <HRGN>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("user32/paint") {
+            crate::trace::Record::new(
+                winapi::user32::ValidateRgn_pos,
+                "user32/paint",
+                "ValidateRgn",
+                &[("hWnd", &hWnd), ("hRgn", &hRgn)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::ValidateRgn(machine, hWnd, hRgn);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn WaitMessage(
         machine: &mut Machine,
         stack_args: u32,
@@ -3589,16 +5630,36 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn WindowFromPoint(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let Point = <POINT>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("user32/window") {
+            crate::trace::Record::new(
+                winapi::user32::WindowFromPoint_pos,
+                "user32/window",
+                "WindowFromPoint",
+                &[("Point", &Point)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::user32::WindowFromPoint(machine, Point);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn keybd_event(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let bVk = <u8>::from_stack(mem, stack_args + 0u32);
         let bScan = <u8>::from_stack(mem, stack_args + 4u32);
         let dwFlags = <u32>::from_stack(mem, stack_args + 8u32);
         let dwExtraInfo = <u32>::from_stack(mem, stack_args + 12u32);
-        let __trace_record = if crate::trace::enabled("user32/misc") {
+        let __trace_record = if crate::trace::enabled("user32/keyboard") {
             crate::trace::Record::new(
                 winapi::user32::keybd_event_pos,
-                "user32/misc",
+                "user32/keyboard",
                 "keybd_event",
                 &[
                     ("bVk", &bVk),
@@ -3662,7 +5723,7 @@ mod wrappers {
         result.into_abireturn()
     }
 }
-const SHIMS: [Shim; 145usize] = [
+const SHIMS: [Shim; 214usize] = [
     Shim {
         name: "AdjustWindowRect",
         func: Handler::Sync(wrappers::AdjustWindowRect),
@@ -3675,13 +5736,25 @@ const SHIMS: [Shim; 145usize] = [
         name: "AppendMenuA",
         func: Handler::Sync(wrappers::AppendMenuA),
     },
+    Shim {
+        name: "AppendMenuW",
+        func: Handler::Sync(wrappers::AppendMenuW),
+    },
     Shim {
         name: "BeginPaint",
-        func: Handler::Sync(wrappers::BeginPaint),
+        func: Handler::Async(wrappers::BeginPaint),
+    },
+    Shim {
+        name: "BringWindowToTop",
+        func: Handler::Sync(wrappers::BringWindowToTop),
+    },
+    Shim {
+        name: "ButtonWndProc",
+        func: Handler::Async(wrappers::ButtonWndProc),
     },
     Shim {
         name: "CallWindowProcA",
-        func: Handler::Sync(wrappers::CallWindowProcA),
+        func: Handler::Async(wrappers::CallWindowProcA),
     },
     Shim {
         name: "CharLowerA",
@@ -3693,28 +5766,68 @@ const SHIMS: [Shim; 145usize] = [
     },
     Shim {
         name: "CheckDlgButton",
-        func: Handler::Sync(wrappers::CheckDlgButton),
+        func: Handler::Async(wrappers::CheckDlgButton),
     },
     Shim {
         name: "CheckMenuItem",
         func: Handler::Sync(wrappers::CheckMenuItem),
     },
+    Shim {
+        name: "CheckMenuRadioItem",
+        func: Handler::Sync(wrappers::CheckMenuRadioItem),
+    },
     Shim {
         name: "CheckRadioButton",
-        func: Handler::Sync(wrappers::CheckRadioButton),
+        func: Handler::Async(wrappers::CheckRadioButton),
+    },
+    Shim {
+        name: "ChildWindowFromPoint",
+        func: Handler::Sync(wrappers::ChildWindowFromPoint),
     },
     Shim {
         name: "ClientToScreen",
         func: Handler::Sync(wrappers::ClientToScreen),
     },
+    Shim {
+        name: "ComboBoxWndProc",
+        func: Handler::Async(wrappers::ComboBoxWndProc),
+    },
     Shim {
         name: "CopyRect",
         func: Handler::Sync(wrappers::CopyRect),
     },
+    Shim {
+        name: "CreateAcceleratorTableA",
+        func: Handler::Sync(wrappers::CreateAcceleratorTableA),
+    },
+    Shim {
+        name: "CreateAcceleratorTableW",
+        func: Handler::Sync(wrappers::CreateAcceleratorTableW),
+    },
     Shim {
         name: "CreateCursor",
         func: Handler::Sync(wrappers::CreateCursor),
     },
+    Shim {
+        name: "CreateDialogIndirectParamA",
+        func: Handler::Async(wrappers::CreateDialogIndirectParamA),
+    },
+    Shim {
+        name: "CreateDialogIndirectParamW",
+        func: Handler::Async(wrappers::CreateDialogIndirectParamW),
+    },
+    Shim {
+        name: "CreateDialogParamA",
+        func: Handler::Async(wrappers::CreateDialogParamA),
+    },
+    Shim {
+        name: "CreateDialogParamW",
+        func: Handler::Async(wrappers::CreateDialogParamW),
+    },
+    Shim {
+        name: "CreateMenu",
+        func: Handler::Sync(wrappers::CreateMenu),
+    },
     Shim {
         name: "CreatePopupMenu",
         func: Handler::Sync(wrappers::CreatePopupMenu),
@@ -3727,6 +5840,14 @@ const SHIMS: [Shim; 145usize] = [
         name: "CreateWindowExW",
         func: Handler::Async(wrappers::CreateWindowExW),
     },
+    Shim {
+        name: "DefDlgProcA",
+        func: Handler::Async(wrappers::DefDlgProcA),
+    },
+    Shim {
+        name: "DefDlgProcW",
+        func: Handler::Async(wrappers::DefDlgProcW),
+    },
     Shim {
         name: "DefWindowProcA",
         func: Handler::Async(wrappers::DefWindowProcA),
@@ -3739,21 +5860,33 @@ const SHIMS: [Shim; 145usize] = [
         name: "DeleteMenu",
         func: Handler::Sync(wrappers::DeleteMenu),
     },
+    Shim {
+        name: "DestroyAcceleratorTable",
+        func: Handler::Sync(wrappers::DestroyAcceleratorTable),
+    },
+    Shim {
+        name: "DestroyMenu",
+        func: Handler::Sync(wrappers::DestroyMenu),
+    },
     Shim {
         name: "DestroyWindow",
-        func: Handler::Sync(wrappers::DestroyWindow),
+        func: Handler::Async(wrappers::DestroyWindow),
     },
     Shim {
         name: "DialogBoxIndirectParamA",
-        func: Handler::Sync(wrappers::DialogBoxIndirectParamA),
+        func: Handler::Async(wrappers::DialogBoxIndirectParamA),
+    },
+    Shim {
+        name: "DialogBoxIndirectParamW",
+        func: Handler::Async(wrappers::DialogBoxIndirectParamW),
     },
     Shim {
         name: "DialogBoxParamA",
-        func: Handler::Sync(wrappers::DialogBoxParamA),
+        func: Handler::Async(wrappers::DialogBoxParamA),
     },
     Shim {
         name: "DialogBoxParamW",
-        func: Handler::Sync(wrappers::DialogBoxParamW),
+        func: Handler::Async(wrappers::DialogBoxParamW),
     },
     Shim {
         name: "DispatchMessageA",
@@ -3767,10 +5900,18 @@ const SHIMS: [Shim; 145usize] = [
         name: "DrawMenuBar",
         func: Handler::Sync(wrappers::DrawMenuBar),
     },
+    Shim {
+        name: "DrawTextA",
+        func: Handler::Sync(wrappers::DrawTextA),
+    },
     Shim {
         name: "DrawTextW",
         func: Handler::Sync(wrappers::DrawTextW),
     },
+    Shim {
+        name: "EditWndProc",
+        func: Handler::Async(wrappers::EditWndProc),
+    },
     Shim {
         name: "EnableMenuItem",
         func: Handler::Sync(wrappers::EnableMenuItem),
@@ -3787,6 +5928,14 @@ const SHIMS: [Shim; 145usize] = [
         name: "EndPaint",
         func: Handler::Sync(wrappers::EndPaint),
     },
+    Shim {
+        name: "EnumChildWindows",
+        func: Handler::Async(wrappers::EnumChildWindows),
+    },
+    Shim {
+        name: "EnumWindows",
+        func: Handler::Async(wrappers::EnumWindows),
+    },
     Shim {
         name: "FillRect",
         func: Handler::Sync(wrappers::FillRect),
@@ -3803,6 +5952,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "GetActiveWindow",
         func: Handler::Sync(wrappers::GetActiveWindow),
     },
+    Shim {
+        name: "GetAsyncKeyState",
+        func: Handler::Sync(wrappers::GetAsyncKeyState),
+    },
     Shim {
         name: "GetCapture",
         func: Handler::Sync(wrappers::GetCapture),
@@ -3827,6 +5980,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "GetDesktopWindow",
         func: Handler::Sync(wrappers::GetDesktopWindow),
     },
+    Shim {
+        name: "GetDlgCtrlID",
+        func: Handler::Sync(wrappers::GetDlgCtrlID),
+    },
     Shim {
         name: "GetDlgItem",
         func: Handler::Sync(wrappers::GetDlgItem),
@@ -3835,6 +5992,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "GetDlgItemInt",
         func: Handler::Sync(wrappers::GetDlgItemInt),
     },
+    Shim {
+        name: "GetDlgItemTextA",
+        func: Handler::Sync(wrappers::GetDlgItemTextA),
+    },
     Shim {
         name: "GetDlgItemTextW",
         func: Handler::Sync(wrappers::GetDlgItemTextW),
@@ -3875,10 +6036,30 @@ const SHIMS: [Shim; 145usize] = [
         name: "GetMenu",
         func: Handler::Sync(wrappers::GetMenu),
     },
+    Shim {
+        name: "GetMenuItemCount",
+        func: Handler::Sync(wrappers::GetMenuItemCount),
+    },
+    Shim {
+        name: "GetMenuItemID",
+        func: Handler::Sync(wrappers::GetMenuItemID),
+    },
+    Shim {
+        name: "GetMenuItemInfoA",
+        func: Handler::Sync(wrappers::GetMenuItemInfoA),
+    },
     Shim {
         name: "GetMenuItemRect",
         func: Handler::Sync(wrappers::GetMenuItemRect),
     },
+    Shim {
+        name: "GetMenuState",
+        func: Handler::Sync(wrappers::GetMenuState),
+    },
+    Shim {
+        name: "GetMenuStringA",
+        func: Handler::Sync(wrappers::GetMenuStringA),
+    },
     Shim {
         name: "GetMessageA",
         func: Handler::Async(wrappers::GetMessageA),
@@ -3891,6 +6072,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "GetMonitorInfoA",
         func: Handler::Sync(wrappers::GetMonitorInfoA),
     },
+    Shim {
+        name: "GetParent",
+        func: Handler::Sync(wrappers::GetParent),
+    },
     Shim {
         name: "GetQueueStatus",
         func: Handler::Sync(wrappers::GetQueueStatus),
@@ -3911,10 +6096,22 @@ const SHIMS: [Shim; 145usize] = [
         name: "GetSystemMetrics",
         func: Handler::Sync(wrappers::GetSystemMetrics),
     },
+    Shim {
+        name: "GetTopWindow",
+        func: Handler::Sync(wrappers::GetTopWindow),
+    },
     Shim {
         name: "GetUpdateRect",
         func: Handler::Sync(wrappers::GetUpdateRect),
     },
+    Shim {
+        name: "GetUpdateRgn",
+        func: Handler::Sync(wrappers::GetUpdateRgn),
+    },
+    Shim {
+        name: "GetWindow",
+        func: Handler::Sync(wrappers::GetWindow),
+    },
     Shim {
         name: "GetWindowDC",
         func: Handler::Sync(wrappers::GetWindowDC),
@@ -3923,6 +6120,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "GetWindowLongA",
         func: Handler::Sync(wrappers::GetWindowLongA),
     },
+    Shim {
+        name: "GetWindowLongW",
+        func: Handler::Sync(wrappers::GetWindowLongW),
+    },
     Shim {
         name: "GetWindowPlacement",
         func: Handler::Sync(wrappers::GetWindowPlacement),
@@ -3931,10 +6132,34 @@ const SHIMS: [Shim; 145usize] = [
         name: "GetWindowRect",
         func: Handler::Sync(wrappers::GetWindowRect),
     },
+    Shim {
+        name: "GetWindowTextA",
+        func: Handler::Sync(wrappers::GetWindowTextA),
+    },
+    Shim {
+        name: "GetWindowTextW",
+        func: Handler::Sync(wrappers::GetWindowTextW),
+    },
+    Shim {
+        name: "GetWindowThreadProcessId",
+        func: Handler::Sync(wrappers::GetWindowThreadProcessId),
+    },
     Shim {
         name: "InflateRect",
         func: Handler::Sync(wrappers::InflateRect),
     },
+    Shim {
+        name: "InsertMenuA",
+        func: Handler::Sync(wrappers::InsertMenuA),
+    },
+    Shim {
+        name: "InsertMenuItemA",
+        func: Handler::Sync(wrappers::InsertMenuItemA),
+    },
+    Shim {
+        name: "InsertMenuW",
+        func: Handler::Sync(wrappers::InsertMenuW),
+    },
     Shim {
         name: "IntersectRect",
         func: Handler::Sync(wrappers::IntersectRect),
@@ -3951,18 +6176,38 @@ const SHIMS: [Shim; 145usize] = [
         name: "InvertRect",
         func: Handler::Sync(wrappers::InvertRect),
     },
+    Shim {
+        name: "IsChild",
+        func: Handler::Sync(wrappers::IsChild),
+    },
+    Shim {
+        name: "IsDialogMessageA",
+        func: Handler::Async(wrappers::IsDialogMessageA),
+    },
+    Shim {
+        name: "IsDialogMessageW",
+        func: Handler::Async(wrappers::IsDialogMessageW),
+    },
     Shim {
         name: "IsDlgButtonChecked",
-        func: Handler::Sync(wrappers::IsDlgButtonChecked),
+        func: Handler::Async(wrappers::IsDlgButtonChecked),
     },
     Shim {
         name: "IsIconic",
         func: Handler::Sync(wrappers::IsIconic),
     },
+    Shim {
+        name: "IsMenu",
+        func: Handler::Sync(wrappers::IsMenu),
+    },
     Shim {
         name: "IsRectEmpty",
         func: Handler::Sync(wrappers::IsRectEmpty),
     },
+    Shim {
+        name: "IsWindow",
+        func: Handler::Sync(wrappers::IsWindow),
+    },
     Shim {
         name: "IsWindowVisible",
         func: Handler::Sync(wrappers::IsWindowVisible),
@@ -3971,6 +6216,14 @@ const SHIMS: [Shim; 145usize] = [
         name: "KillTimer",
         func: Handler::Sync(wrappers::KillTimer),
     },
+    Shim {
+        name: "ListBoxWndProc",
+        func: Handler::Async(wrappers::ListBoxWndProc),
+    },
+    Shim {
+        name: "LoadAcceleratorsA",
+        func: Handler::Sync(wrappers::LoadAcceleratorsA),
+    },
     Shim {
         name: "LoadAcceleratorsW",
         func: Handler::Sync(wrappers::LoadAcceleratorsW),
@@ -4019,6 +6272,14 @@ const SHIMS: [Shim; 145usize] = [
         name: "LoadStringW",
         func: Handler::Sync(wrappers::LoadStringW),
     },
+    Shim {
+        name: "MapDialogRect",
+        func: Handler::Sync(wrappers::MapDialogRect),
+    },
+    Shim {
+        name: "MapVirtualKeyA",
+        func: Handler::Sync(wrappers::MapVirtualKeyA),
+    },
     Shim {
         name: "MapWindowPoints",
         func: Handler::Sync(wrappers::MapWindowPoints),
@@ -4031,9 +6292,17 @@ const SHIMS: [Shim; 145usize] = [
         name: "MessageBoxW",
         func: Handler::Sync(wrappers::MessageBoxW),
     },
+    Shim {
+        name: "ModifyMenuA",
+        func: Handler::Sync(wrappers::ModifyMenuA),
+    },
+    Shim {
+        name: "ModifyMenuW",
+        func: Handler::Sync(wrappers::ModifyMenuW),
+    },
     Shim {
         name: "MoveWindow",
-        func: Handler::Sync(wrappers::MoveWindow),
+        func: Handler::Async(wrappers::MoveWindow),
     },
     Shim {
         name: "MsgWaitForMultipleObjects",
@@ -4045,11 +6314,11 @@ const SHIMS: [Shim; 145usize] = [
     },
     Shim {
         name: "PeekMessageA",
-        func: Handler::Sync(wrappers::PeekMessageA),
+        func: Handler::Async(wrappers::PeekMessageA),
     },
     Shim {
         name: "PeekMessageW",
-        func: Handler::Sync(wrappers::PeekMessageW),
+        func: Handler::Async(wrappers::PeekMessageW),
     },
     Shim {
         name: "PostMessageA",
@@ -4067,6 +6336,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "PostThreadMessageA",
         func: Handler::Sync(wrappers::PostThreadMessageA),
     },
+    Shim {
+        name: "PostThreadMessageW",
+        func: Handler::Sync(wrappers::PostThreadMessageW),
+    },
     Shim {
         name: "PtInRect",
         func: Handler::Sync(wrappers::PtInRect),
@@ -4111,18 +6384,46 @@ const SHIMS: [Shim; 145usize] = [
         name: "ReleaseDC",
         func: Handler::Sync(wrappers::ReleaseDC),
     },
+    Shim {
+        name: "RemoveMenu",
+        func: Handler::Sync(wrappers::RemoveMenu),
+    },
+    Shim {
+        name: "ScreenToClient",
+        func: Handler::Sync(wrappers::ScreenToClient),
+    },
+    Shim {
+        name: "ScrollBarWndProc",
+        func: Handler::Async(wrappers::ScrollBarWndProc),
+    },
     Shim {
         name: "SendDlgItemMessageA",
-        func: Handler::Sync(wrappers::SendDlgItemMessageA),
+        func: Handler::Async(wrappers::SendDlgItemMessageA),
     },
     Shim {
         name: "SendMessageA",
         func: Handler::Async(wrappers::SendMessageA),
     },
+    Shim {
+        name: "SendMessageTimeoutA",
+        func: Handler::Async(wrappers::SendMessageTimeoutA),
+    },
+    Shim {
+        name: "SendMessageTimeoutW",
+        func: Handler::Async(wrappers::SendMessageTimeoutW),
+    },
     Shim {
         name: "SendMessageW",
         func: Handler::Async(wrappers::SendMessageW),
     },
+    Shim {
+        name: "SendNotifyMessageA",
+        func: Handler::Async(wrappers::SendNotifyMessageA),
+    },
+    Shim {
+        name: "SendNotifyMessageW",
+        func: Handler::Async(wrappers::SendNotifyMessageW),
+    },
     Shim {
         name: "SetCapture",
         func: Handler::Sync(wrappers::SetCapture),
@@ -4153,7 +6454,7 @@ const SHIMS: [Shim; 145usize] = [
     },
     Shim {
         name: "SetFocus",
-        func: Handler::Sync(wrappers::SetFocus),
+        func: Handler::Async(wrappers::SetFocus),
     },
     Shim {
         name: "SetForegroundWindow",
@@ -4167,6 +6468,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "SetMenuItemInfoA",
         func: Handler::Sync(wrappers::SetMenuItemInfoA),
     },
+    Shim {
+        name: "SetParent",
+        func: Handler::Sync(wrappers::SetParent),
+    },
     Shim {
         name: "SetRect",
         func: Handler::Sync(wrappers::SetRect),
@@ -4183,6 +6488,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "SetWindowLongA",
         func: Handler::Sync(wrappers::SetWindowLongA),
     },
+    Shim {
+        name: "SetWindowLongW",
+        func: Handler::Sync(wrappers::SetWindowLongW),
+    },
     Shim {
         name: "SetWindowPos",
         func: Handler::Async(wrappers::SetWindowPos),
@@ -4191,6 +6500,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "SetWindowTextA",
         func: Handler::Sync(wrappers::SetWindowTextA),
     },
+    Shim {
+        name: "SetWindowTextW",
+        func: Handler::Sync(wrappers::SetWindowTextW),
+    },
     Shim {
         name: "SetWindowsHookExA",
         func: Handler::Sync(wrappers::SetWindowsHookExA),
@@ -4203,13 +6516,29 @@ const SHIMS: [Shim; 145usize] = [
         name: "ShowWindow",
         func: Handler::Async(wrappers::ShowWindow),
     },
+    Shim {
+        name: "StaticWndProc",
+        func: Handler::Async(wrappers::StaticWndProc),
+    },
     Shim {
         name: "SystemParametersInfoA",
         func: Handler::Sync(wrappers::SystemParametersInfoA),
     },
+    Shim {
+        name: "TrackPopupMenu",
+        func: Handler::Async(wrappers::TrackPopupMenu),
+    },
+    Shim {
+        name: "TrackPopupMenuEx",
+        func: Handler::Async(wrappers::TrackPopupMenuEx),
+    },
+    Shim {
+        name: "TranslateAcceleratorA",
+        func: Handler::Async(wrappers::TranslateAcceleratorA),
+    },
     Shim {
         name: "TranslateAcceleratorW",
-        func: Handler::Sync(wrappers::TranslateAcceleratorW),
+        func: Handler::Async(wrappers::TranslateAcceleratorW),
     },
     Shim {
         name: "TranslateMessage",
@@ -4223,6 +6552,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "ValidateRect",
         func: Handler::Sync(wrappers::ValidateRect),
     },
+    Shim {
+        name: "ValidateRgn",
+        func: Handler::Sync(wrappers::ValidateRgn),
+    },
     Shim {
         name: "WaitMessage",
         func: Handler::Async(wrappers::WaitMessage),
@@ -4231,6 +6564,10 @@ const SHIMS: [Shim; 145usize] = [
         name: "WinHelpW",
         func: Handler::Sync(wrappers::WinHelpW),
     },
+    Shim {
+        name: "WindowFromPoint",
+        func: Handler::Sync(wrappers::WindowFromPoint),
+    },
     Shim {
         name: "keybd_event",
         func: Handler::Sync(wrappers::keybd_event),
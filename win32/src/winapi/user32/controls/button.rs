@@ -0,0 +1,468 @@
+//! The "Button" class: push buttons, check boxes, radio buttons and group boxes.
+
+use super::{
+    class_style, def_control_proc, draw_item, init_control, invalidate, mouse_pos, notify_parent,
+    ControlState, Edge, Paint, DLGC_BUTTON, DLGC_DEFPUSHBUTTON, DLGC_RADIOBUTTON,
+    DLGC_UNDEFPUSHBUTTON, ODS_CHECKED, ODS_FOCUS, ODS_SELECTED, ODT_BUTTON,
+};
+use crate::{
+    winapi::{
+        types::{HWND, RECT},
+        user32::{
+            ReleaseCapture, SetCapture, SetFocus, Window, COLOR, DT_CENTER, DT_SINGLELINE,
+            DT_VCENTER, VK_SPACE, WM, WS,
+        },
+    },
+    Machine,
+};
+
+pub const BS_PUSHBUTTON: u32 = 0x0;
+pub const BS_DEFPUSHBUTTON: u32 = 0x1;
+pub const BS_CHECKBOX: u32 = 0x2;
+pub const BS_AUTOCHECKBOX: u32 = 0x3;
+pub const BS_RADIOBUTTON: u32 = 0x4;
+pub const BS_3STATE: u32 = 0x5;
+pub const BS_AUTO3STATE: u32 = 0x6;
+pub const BS_GROUPBOX: u32 = 0x7;
+pub const BS_USERBUTTON: u32 = 0x8;
+pub const BS_AUTORADIOBUTTON: u32 = 0x9;
+pub const BS_PUSHBOX: u32 = 0xA;
+pub const BS_OWNERDRAW: u32 = 0xB;
+pub const BS_TYPEMASK: u32 = 0xF;
+pub const BS_LEFTTEXT: u32 = 0x20;
+pub const BS_NOTIFY: u32 = 0x4000;
+
+pub const BM_GETCHECK: u32 = 0x00F0;
+pub const BM_SETCHECK: u32 = 0x00F1;
+pub const BM_GETSTATE: u32 = 0x00F2;
+pub const BM_SETSTATE: u32 = 0x00F3;
+pub const BM_SETSTYLE: u32 = 0x00F4;
+pub const BM_CLICK: u32 = 0x00F5;
+
+pub const BST_UNCHECKED: u32 = 0x0;
+pub const BST_CHECKED: u32 = 0x1;
+pub const BST_INDETERMINATE: u32 = 0x2;
+pub const BST_PUSHED: u32 = 0x4;
+pub const BST_FOCUS: u32 = 0x8;
+
+pub const BN_CLICKED: u32 = 0;
+pub const BN_SETFOCUS: u32 = 6;
+pub const BN_KILLFOCUS: u32 = 7;
+
+#[derive(Default)]
+pub struct Button {
+    /// BST_UNCHECKED etc.
+    check: u32,
+    /// Drawn pressed, because the mouse or space bar is down on it.
+    pushed: bool,
+    focused: bool,
+    /// The mouse went down on the button and has been captured.
+    tracking: bool,
+}
+
+fn with_button<T>(machine: &Machine, hwnd: HWND, f: impl FnOnce(&mut Button) -> T) -> T {
+    let mut window = machine.state.user32.windows.get(hwnd).unwrap().borrow_mut();
+    match &mut window.control.as_mut().unwrap().state {
+        ControlState::Button(button) => f(button),
+        _ => unreachable!("not a button"),
+    }
+}
+
+/// Whether a window is a push button, which Enter in a dialog activates.
+pub fn is_push_button(window: &Window) -> bool {
+    matches!(&window.control, Some(c) if matches!(c.state, ControlState::Button(_)))
+        && matches!(
+            window.style.bits() & BS_TYPEMASK,
+            BS_PUSHBUTTON | BS_DEFPUSHBUTTON
+        )
+}
+
+fn set_check(machine: &Machine, hwnd: HWND, check: u32) {
+    let changed = with_button(machine, hwnd, |b| {
+        std::mem::replace(&mut b.check, check) != check
+    });
+    if changed {
+        invalidate(machine, hwnd);
+    }
+}
+
+/// Check an auto radio button, and uncheck the others in its group.
+fn check_radio(machine: &Machine, hwnd: HWND) {
+    let parent = machine
+        .state
+        .user32
+        .windows
+        .get(hwnd)
+        .unwrap()
+        .borrow()
+        .parent;
+    let mut siblings = machine
+        .state
+        .user32
+        .windows
+        .iter()
+        .filter(|(_, w)| w.borrow().parent == parent)
+        .map(|(hwnd, w)| (hwnd, w.borrow().style))
+        .collect::<Vec<_>>();
+    siblings.sort_by_key(|(hwnd, _)| hwnd.to_raw());
+
+    // A group runs from a WS_GROUP window up to the next one.
+    let index = siblings.iter().position(|&(h, _)| h == hwnd).unwrap();
+    let start = siblings[..=index]
+        .iter()
+        .rposition(|(_, style)| style.contains(WS::GROUP))
+        .unwrap_or(0);
+    let end = siblings[index + 1..]
+        .iter()
+        .position(|(_, style)| style.contains(WS::GROUP))
+        .map_or(siblings.len(), |i| index + 1 + i);
+    for &(sibling, style) in &siblings[start..end] {
+        if style.bits() & BS_TYPEMASK != BS_AUTORADIOBUTTON {
+            continue;
+        }
+        let is_button = matches!(
+            &machine.state.user32.windows.get(sibling).unwrap().borrow().control,
+            Some(c) if matches!(c.state, ControlState::Button(_))
+        );
+        if is_button {
+            let check = if sibling == hwnd {
+                BST_CHECKED
+            } else {
+                BST_UNCHECKED
+            };
+            set_check(machine, sibling, check);
+        }
+    }
+}
+
+/// Toggle the button per its style and tell the parent.
+async fn click(machine: &mut Machine, hwnd: HWND) {
+    match class_style(machine, hwnd) & BS_TYPEMASK {
+        BS_AUTOCHECKBOX => {
+            let check = with_button(machine, hwnd, |b| b.check);
+            set_check(machine, hwnd, (check == BST_UNCHECKED) as u32);
+        }
+        BS_AUTO3STATE => {
+            let check = with_button(machine, hwnd, |b| b.check);
+            set_check(machine, hwnd, (check + 1) % 3);
+        }
+        BS_AUTORADIOBUTTON => check_radio(machine, hwnd),
+        _ => {}
+    }
+    notify_parent(machine, hwnd, BN_CLICKED).await;
+}
+
+fn set_pushed(machine: &Machine, hwnd: HWND, pushed: bool) {
+    let changed = with_button(machine, hwnd, |b| {
+        std::mem::replace(&mut b.pushed, pushed) != pushed
+    });
+    if changed {
+        invalidate(machine, hwnd);
+    }
+}
+
+/// The window procedure of the "Button" class.
+#[win32_derive::dllexport]
+pub async fn ButtonWndProc(
+    machine: &mut Machine,
+    hWnd: HWND,
+    msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    if !init_control(machine, hWnd, |_| ControlState::Button(Button::default())) {
+        return 0;
+    }
+    let typ = class_style(machine, hWnd) & BS_TYPEMASK;
+    match msg {
+        Ok(WM::PAINT) => {
+            paint(machine, hWnd, typ).await;
+            0
+        }
+        Ok(WM::LBUTTONDOWN | WM::LBUTTONDBLCLK) if typ != BS_GROUPBOX => {
+            with_button(machine, hWnd, |b| b.tracking = true);
+            SetCapture(machine, hWnd);
+            SetFocus(machine, hWnd).await;
+            set_pushed(machine, hWnd, true);
+            0
+        }
+        Ok(WM::MOUSEMOVE) if with_button(machine, hWnd, |b| b.tracking) => {
+            let rect = machine
+                .state
+                .user32
+                .windows
+                .get(hWnd)
+                .unwrap()
+                .borrow()
+                .client_rect();
+            set_pushed(machine, hWnd, rect.contains(mouse_pos(lParam)));
+            0
+        }
+        Ok(WM::LBUTTONUP) if with_button(machine, hWnd, |b| b.tracking) => {
+            with_button(machine, hWnd, |b| b.tracking = false);
+            ReleaseCapture(machine);
+            let pushed = with_button(machine, hWnd, |b| b.pushed);
+            set_pushed(machine, hWnd, false);
+            if pushed {
+                click(machine, hWnd).await;
+            }
+            0
+        }
+        Ok(WM::KEYDOWN) if wParam == VK_SPACE as u32 => {
+            set_pushed(machine, hWnd, true);
+            0
+        }
+        Ok(WM::KEYUP) if wParam == VK_SPACE as u32 => {
+            if with_button(machine, hWnd, |b| b.pushed) {
+                set_pushed(machine, hWnd, false);
+                click(machine, hWnd).await;
+            }
+            0
+        }
+        Ok(WM::SETFOCUS) => {
+            with_button(machine, hWnd, |b| b.focused = true);
+            invalidate(machine, hWnd);
+            if class_style(machine, hWnd) & BS_NOTIFY != 0 {
+                notify_parent(machine, hWnd, BN_SETFOCUS).await;
+            }
+            0
+        }
+        Ok(WM::KILLFOCUS) => {
+            with_button(machine, hWnd, |b| {
+                b.focused = false;
+                b.pushed = false;
+            });
+            invalidate(machine, hWnd);
+            if class_style(machine, hWnd) & BS_NOTIFY != 0 {
+                notify_parent(machine, hWnd, BN_KILLFOCUS).await;
+            }
+            0
+        }
+        Ok(WM::GETDLGCODE) => match typ {
+            BS_DEFPUSHBUTTON => DLGC_BUTTON | DLGC_DEFPUSHBUTTON,
+            BS_PUSHBUTTON => DLGC_BUTTON | DLGC_UNDEFPUSHBUTTON,
+            BS_RADIOBUTTON | BS_AUTORADIOBUTTON => DLGC_BUTTON | DLGC_RADIOBUTTON,
+            _ => DLGC_BUTTON,
+        },
+        Err(BM_GETCHECK) => with_button(machine, hWnd, |b| b.check),
+        Err(BM_SETCHECK) => {
+            set_check(machine, hWnd, wParam & (BST_CHECKED | BST_INDETERMINATE));
+            0
+        }
+        Err(BM_GETSTATE) => with_button(machine, hWnd, |b| {
+            b.check | if b.pushed { BST_PUSHED } else { 0 } | if b.focused { BST_FOCUS } else { 0 }
+        }),
+        Err(BM_SETSTATE) => {
+            set_pushed(machine, hWnd, wParam != 0);
+            0
+        }
+        Err(BM_SETSTYLE) => {
+            let window = machine.state.user32.windows.get(hWnd).unwrap();
+            let mut window = window.borrow_mut();
+            window.style =
+                WS::from_bits_retain((window.style.bits() & !0xFFFF) | (wParam & 0xFFFF));
+            if lParam != 0 {
+                window.add_dirty(true, None);
+            }
+            0
+        }
+        Err(BM_CLICK) => {
+            click(machine, hWnd).await;
+            0
+        }
+        msg => def_control_proc(machine, hWnd, msg, wParam, lParam).await,
+    }
+}
+
+const CHECK_SIZE: i32 = 13;
+
+/// The check mark, as rows of pixels drawn from the left.
+const CHECK_MARK: [u8; 7] = [
+    0b0000001, 0b0000011, 0b1000111, 0b1101110, 0b1111100, 0b0111000, 0b0010000,
+];
+
+async fn paint(machine: &mut Machine, hwnd: HWND, typ: u32) {
+    let (check, pushed, focused) = with_button(machine, hwnd, |b| (b.check, b.pushed, b.focused));
+    let style = class_style(machine, hwnd);
+    let text = machine
+        .state
+        .user32
+        .windows
+        .get(hwnd)
+        .unwrap()
+        .borrow()
+        .text
+        .chars()
+        .collect::<Vec<_>>();
+    let push = matches!(
+        typ,
+        BS_PUSHBUTTON | BS_DEFPUSHBUTTON | BS_PUSHBOX | BS_USERBUTTON
+    );
+    let ctlcolor = if push || typ == BS_OWNERDRAW {
+        WM::CTLCOLORBTN
+    } else {
+        WM::CTLCOLORSTATIC
+    };
+    let paint = super::begin_paint(machine, hwnd, ctlcolor, COLOR::BTNFACE, COLOR::BTNTEXT).await;
+    let rect = paint.rect;
+    if !paint.enabled {
+        paint.text_color(machine, COLOR::GRAYTEXT.to_colorref());
+    }
+    paint.bk_color(machine, paint.background);
+
+    match typ {
+        BS_OWNERDRAW => {
+            let state = if pushed { ODS_SELECTED } else { 0 }
+                | if focused { ODS_FOCUS } else { 0 }
+                | if check != 0 { ODS_CHECKED } else { 0 };
+            draw_item(machine, hwnd, &paint, ODT_BUTTON, 0, state, rect, 0).await;
+        }
+        BS_GROUPBOX => {
+            let font = super::control_font(machine, hwnd);
+            let half = font.height() as i32 / 2;
+            let frame = RECT {
+                top: rect.top + half,
+                ..rect
+            };
+            paint.edge(machine, &frame, Edge::Etched);
+            if !text.is_empty() {
+                let width = font.text_width(&text) as i32;
+                let label = RECT {
+                    left: rect.left + 8,
+                    top: rect.top,
+                    right: std::cmp::min(rect.left + 8 + width + 4, rect.right),
+                    bottom: rect.top + font.height() as i32,
+                };
+                paint.fill(machine, &label, paint.background);
+                paint.text(machine, &text, &label, DT_CENTER | DT_SINGLELINE);
+            }
+        }
+        BS_CHECKBOX | BS_AUTOCHECKBOX | BS_3STATE | BS_AUTO3STATE | BS_RADIOBUTTON
+        | BS_AUTORADIOBUTTON => {
+            let left_text = style & BS_LEFTTEXT != 0;
+            let box_left = if left_text {
+                rect.right - CHECK_SIZE
+            } else {
+                rect.left
+            };
+            let top = (rect.top + rect.bottom - CHECK_SIZE) / 2;
+            let mark = RECT {
+                left: box_left,
+                top,
+                right: box_left + CHECK_SIZE,
+                bottom: top + CHECK_SIZE,
+            };
+            if matches!(typ, BS_RADIOBUTTON | BS_AUTORADIOBUTTON) {
+                paint_radio(machine, &paint, &mark, check != 0, pushed);
+            } else {
+                paint_check(machine, &paint, &mark, check, pushed);
+            }
+            let label = if left_text {
+                RECT {
+                    right: mark.left - 4,
+                    ..rect
+                }
+            } else {
+                RECT {
+                    left: mark.right + 4,
+                    ..rect
+                }
+            };
+            paint.text(machine, &text, &label, DT_SINGLELINE | DT_VCENTER);
+            if focused {
+                paint.focus_rect(machine, &label, &text);
+            }
+        }
+        _ => {
+            let mut face = rect;
+            if typ == BS_DEFPUSHBUTTON || focused {
+                paint.frame(machine, &face, COLOR::WINDOWFRAME.to_colorref());
+                face = super::inset(&face, 1);
+            }
+            let mut label = if pushed {
+                paint.frame(machine, &face, COLOR::BTNSHADOW.to_colorref());
+                super::inset(&face, 1)
+            } else {
+                paint.edge(machine, &face, Edge::Raised)
+            };
+            if pushed {
+                label = label.add(crate::winapi::types::POINT { x: 1, y: 1 });
+            }
+            paint.text(
+                machine,
+                &text,
+                &label,
+                DT_CENTER | DT_VCENTER | DT_SINGLELINE,
+            );
+            if focused {
+                paint.focus_rect(machine, &super::inset(&label, 1), &[]);
+            }
+        }
+    }
+    super::end_paint(machine, hwnd, paint);
+}
+
+fn paint_check(machine: &mut Machine, paint: &Paint, rect: &RECT, check: u32, pushed: bool) {
+    let inner = paint.edge(machine, rect, Edge::Sunken);
+    let (face, mark) = if pushed || check == BST_INDETERMINATE {
+        (COLOR::BTNFACE, COLOR::BTNSHADOW)
+    } else {
+        (COLOR::WINDOW, COLOR::WINDOWTEXT)
+    };
+    paint.fill(machine, &inner, face.to_colorref());
+    if check == BST_UNCHECKED {
+        return;
+    }
+    let mark = mark.to_colorref();
+    for (y, row) in CHECK_MARK.iter().enumerate() {
+        for x in 0..7 {
+            if row & (1 << x) != 0 {
+                let px = inner.left + 1 + x;
+                let py = inner.top + 1 + y as i32;
+                let pixel = RECT {
+                    left: px,
+                    top: py,
+                    right: px + 1,
+                    bottom: py + 1,
+                };
+                paint.fill(machine, &pixel, mark);
+            }
+        }
+    }
+}
+
+/// Fill a circle, one row at a time.
+fn fill_circle(machine: &mut Machine, paint: &Paint, rect: &RECT, color: COLOR) {
+    let size = rect.right - rect.left;
+    let r = size as f32 / 2.0;
+    let color = color.to_colorref();
+    for y in 0..size {
+        let dy = y as f32 + 0.5 - r;
+        let half = (r * r - dy * dy).max(0.0).sqrt().round() as i32;
+        let row = RECT {
+            left: rect.left + size / 2 - half,
+            top: rect.top + y,
+            right: rect.left + (size + 1) / 2 + half,
+            bottom: rect.top + y + 1,
+        };
+        paint.fill(machine, &row, color);
+    }
+}
+
+fn paint_radio(machine: &mut Machine, paint: &Paint, rect: &RECT, checked: bool, pushed: bool) {
+    let rect = RECT {
+        right: rect.right - 1,
+        bottom: rect.bottom - 1,
+        ..*rect
+    };
+    fill_circle(machine, paint, &rect, COLOR::BTNSHADOW);
+    let face = if pushed {
+        COLOR::BTNFACE
+    } else {
+        COLOR::WINDOW
+    };
+    fill_circle(machine, paint, &super::inset(&rect, 1), face);
+    if checked {
+        fill_circle(machine, paint, &super::inset(&rect, 4), COLOR::WINDOWTEXT);
+    }
+}
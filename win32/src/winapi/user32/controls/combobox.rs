@@ -0,0 +1,754 @@
+//! The "ComboBox" class: a text field with a list of choices.
+
+use super::{
+    class_style, def_control_proc, draw_item, init_control, invalidate, mouse_pos, new_item,
+    notify_parent, paint_items, read_str, write_str, Arrow, ControlState, Edge, List,
+    DLGC_WANTARROWS, DLGC_WANTCHARS, ODS_FOCUS, ODS_SELECTED, ODT_COMBOBOX,
+};
+use crate::{
+    winapi::{
+        types::{HWND, RECT},
+        user32::{
+            flush_window, ReleaseCapture, SetCapture, SetFocus, COLOR, DT_NOPREFIX, DT_SINGLELINE,
+            DT_VCENTER, VK_DOWN, VK_END, VK_ESCAPE, VK_HOME, VK_MENU, VK_RETURN, VK_UP, WM,
+        },
+    },
+    Machine,
+};
+use memory::ExtensionsMut;
+
+pub const CBS_SIMPLE: u32 = 0x0001;
+pub const CBS_DROPDOWN: u32 = 0x0002;
+pub const CBS_DROPDOWNLIST: u32 = 0x0003;
+pub const CBS_OWNERDRAWFIXED: u32 = 0x0010;
+pub const CBS_OWNERDRAWVARIABLE: u32 = 0x0020;
+pub const CBS_SORT: u32 = 0x0100;
+pub const CBS_HASSTRINGS: u32 = 0x0200;
+
+pub const CB_GETEDITSEL: u32 = 0x0140;
+pub const CB_LIMITTEXT: u32 = 0x0141;
+pub const CB_SETEDITSEL: u32 = 0x0142;
+pub const CB_ADDSTRING: u32 = 0x0143;
+pub const CB_DELETESTRING: u32 = 0x0144;
+pub const CB_DIR: u32 = 0x0145;
+pub const CB_GETCOUNT: u32 = 0x0146;
+pub const CB_GETCURSEL: u32 = 0x0147;
+pub const CB_GETLBTEXT: u32 = 0x0148;
+pub const CB_GETLBTEXTLEN: u32 = 0x0149;
+pub const CB_INSERTSTRING: u32 = 0x014A;
+pub const CB_RESETCONTENT: u32 = 0x014B;
+pub const CB_FINDSTRING: u32 = 0x014C;
+pub const CB_SELECTSTRING: u32 = 0x014D;
+pub const CB_SETCURSEL: u32 = 0x014E;
+pub const CB_SHOWDROPDOWN: u32 = 0x014F;
+pub const CB_GETITEMDATA: u32 = 0x0150;
+pub const CB_SETITEMDATA: u32 = 0x0151;
+pub const CB_GETDROPPEDCONTROLRECT: u32 = 0x0152;
+pub const CB_SETITEMHEIGHT: u32 = 0x0153;
+pub const CB_GETITEMHEIGHT: u32 = 0x0154;
+pub const CB_SETEXTENDEDUI: u32 = 0x0155;
+pub const CB_GETEXTENDEDUI: u32 = 0x0156;
+pub const CB_GETDROPPEDSTATE: u32 = 0x0157;
+pub const CB_FINDSTRINGEXACT: u32 = 0x0158;
+
+pub const CB_ERR: u32 = -1i32 as u32;
+
+pub const CBN_SELCHANGE: u32 = 1;
+pub const CBN_DBLCLK: u32 = 2;
+pub const CBN_SETFOCUS: u32 = 3;
+pub const CBN_KILLFOCUS: u32 = 4;
+pub const CBN_EDITCHANGE: u32 = 5;
+pub const CBN_EDITUPDATE: u32 = 6;
+pub const CBN_DROPDOWN: u32 = 7;
+pub const CBN_CLOSEUP: u32 = 8;
+pub const CBN_SELENDOK: u32 = 9;
+pub const CBN_SELENDCANCEL: u32 = 10;
+
+const ODS_COMBOBOXEDIT: u32 = 0x1000;
+
+/// The width of the drop down button.
+const BUTTON_WIDTH: i32 = 16;
+
+pub struct ComboBox {
+    list: List,
+    /// The height the window was created with, which includes the list.
+    full_height: u32,
+    dropped: bool,
+    /// The selection when the list dropped down, restored if it's cancelled.
+    orig_cur: Option<usize>,
+    /// Set by CB_SETITEMHEIGHT; otherwise the font height.
+    item_height: Option<i32>,
+    /// Maximum length of the edit field, per CB_LIMITTEXT.
+    limit: usize,
+    focused: bool,
+}
+
+fn with_combo<T>(machine: &Machine, hwnd: HWND, f: impl FnOnce(&mut ComboBox) -> T) -> T {
+    let mut window = machine.state.user32.windows.get(hwnd).unwrap().borrow_mut();
+    match &mut window.control.as_mut().unwrap().state {
+        ControlState::ComboBox(combo) => f(combo),
+        _ => unreachable!("not a combo box"),
+    }
+}
+
+fn combo_type(style: u32) -> u32 {
+    style & 0x3
+}
+
+fn item_height(machine: &Machine, hwnd: HWND) -> i32 {
+    with_combo(machine, hwnd, |cb| cb.item_height)
+        .unwrap_or_else(|| super::control_font(machine, hwnd).height() as i32)
+}
+
+/// The height of the text field at the top.
+fn field_height(machine: &Machine, hwnd: HWND) -> i32 {
+    super::control_font(machine, hwnd).height() as i32 + 6
+}
+
+/// Size the window to show the list only when it should be visible.
+// TODO: the list should be a separate popup, so it can extend past the parent and draw
+// above later siblings.
+fn resize(machine: &mut Machine, hwnd: HWND) {
+    let style = class_style(machine, hwnd);
+    let field = field_height(machine, hwnd) as u32;
+    let (dropped, full) = with_combo(machine, hwnd, |cb| (cb.dropped, cb.full_height));
+    let height = if dropped || combo_type(style) == CBS_SIMPLE {
+        std::cmp::max(full, field)
+    } else {
+        field
+    };
+    let window = machine.state.user32.windows.get(hwnd).unwrap();
+    let mut window = window.borrow_mut();
+    if window.height == height {
+        return;
+    }
+    let width = window.width;
+    window.set_client_size(&mut *machine.host, width, height);
+    let parent = window.parent;
+    drop(window);
+    flush_window(machine, parent);
+}
+
+/// Set the field text to match the selection, for drop down lists.
+fn sync_text(machine: &Machine, hwnd: HWND) {
+    let text = with_combo(machine, hwnd, |cb| {
+        cb.list
+            .cur
+            .map(|cur| cb.list.items[cur].text.clone())
+            .unwrap_or_default()
+    });
+    machine
+        .state
+        .user32
+        .windows
+        .get(hwnd)
+        .unwrap()
+        .borrow_mut()
+        .text = text;
+    invalidate(machine, hwnd);
+}
+
+async fn drop_down(machine: &mut Machine, hwnd: HWND) {
+    let style = class_style(machine, hwnd);
+    if combo_type(style) == CBS_SIMPLE || with_combo(machine, hwnd, |cb| cb.dropped) {
+        return;
+    }
+    notify_parent(machine, hwnd, CBN_DROPDOWN).await;
+    with_combo(machine, hwnd, |cb| {
+        cb.dropped = true;
+        cb.orig_cur = cb.list.cur;
+    });
+    resize(machine, hwnd);
+    SetCapture(machine, hwnd);
+}
+
+/// Close the list, keeping the highlighted item if ok.
+async fn close_up(machine: &mut Machine, hwnd: HWND, ok: bool) {
+    let Some((orig, cur)) = with_combo(machine, hwnd, |cb| {
+        if !cb.dropped {
+            return None;
+        }
+        cb.dropped = false;
+        if !ok {
+            cb.list.cur = cb.orig_cur;
+        }
+        Some((cb.orig_cur, cb.list.cur))
+    }) else {
+        return;
+    };
+    ReleaseCapture(machine);
+    resize(machine, hwnd);
+    if ok {
+        sync_text(machine, hwnd);
+        if orig != cur {
+            notify_parent(machine, hwnd, CBN_SELCHANGE).await;
+        }
+        notify_parent(machine, hwnd, CBN_SELENDOK).await;
+    } else {
+        notify_parent(machine, hwnd, CBN_SELENDCANCEL).await;
+    }
+    notify_parent(machine, hwnd, CBN_CLOSEUP).await;
+}
+
+/// Select an item outside of the drop down list, e.g. by arrow keys.
+async fn select(machine: &mut Machine, hwnd: HWND, index: usize) {
+    let changed = with_combo(machine, hwnd, |cb| {
+        if index >= cb.list.items.len() || cb.list.cur == Some(index) {
+            return false;
+        }
+        cb.list.cur = Some(index);
+        true
+    });
+    if changed {
+        sync_text(machine, hwnd);
+        notify_parent(machine, hwnd, CBN_SELCHANGE).await;
+    }
+}
+
+/// The list item under a point, if the list is showing.
+fn item_at(machine: &Machine, hwnd: HWND, x: i32, y: i32) -> Option<usize> {
+    let field = field_height(machine, hwnd);
+    let height = item_height(machine, hwnd);
+    let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+    if x < 0 || x >= window.width as i32 || y < field || y >= window.height as i32 {
+        return None;
+    }
+    drop(window);
+    with_combo(machine, hwnd, |cb| {
+        let index = cb.list.top + ((y - field - 1) / height) as usize;
+        (index < cb.list.items.len()).then_some(index)
+    })
+}
+
+/// The number of items that fit in the list.
+fn page_size(machine: &Machine, hwnd: HWND) -> usize {
+    let field = field_height(machine, hwnd);
+    let full = with_combo(machine, hwnd, |cb| cb.full_height) as i32;
+    std::cmp::max(1, (full - field - 2) / item_height(machine, hwnd)) as usize
+}
+
+/// The window procedure of the "ComboBox" class.
+#[win32_derive::dllexport]
+pub async fn ComboBoxWndProc(
+    machine: &mut Machine,
+    hWnd: HWND,
+    msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    let created = init_control(machine, hWnd, |style| {
+        ControlState::ComboBox(ComboBox {
+            list: List {
+                sorted: style & CBS_SORT != 0,
+                ..Default::default()
+            },
+            full_height: 0,
+            dropped: false,
+            orig_cur: None,
+            item_height: None,
+            limit: 30000,
+            focused: false,
+        })
+    });
+    if !created {
+        return 0;
+    }
+    let style = class_style(machine, hWnd);
+    let typ = combo_type(style);
+    match msg {
+        Ok(WM::CREATE) => {
+            let height = machine
+                .state
+                .user32
+                .windows
+                .get(hWnd)
+                .unwrap()
+                .borrow()
+                .height;
+            with_combo(machine, hWnd, |cb| cb.full_height = height);
+            resize(machine, hWnd);
+            0
+        }
+        Ok(WM::SETFONT) => {
+            let result = def_control_proc(machine, hWnd, msg, wParam, lParam).await;
+            resize(machine, hWnd);
+            result
+        }
+        Ok(WM::PAINT) => {
+            paint(machine, hWnd).await;
+            0
+        }
+        Ok(WM::LBUTTONDOWN) => {
+            let pt = mouse_pos(lParam);
+            if with_combo(machine, hWnd, |cb| cb.dropped) {
+                match item_at(machine, hWnd, pt.x, pt.y) {
+                    Some(index) => {
+                        with_combo(machine, hWnd, |cb| cb.list.cur = Some(index));
+                        close_up(machine, hWnd, true).await;
+                    }
+                    None => close_up(machine, hWnd, false).await,
+                }
+                return 0;
+            }
+            SetFocus(machine, hWnd).await;
+            if typ == CBS_SIMPLE {
+                if let Some(index) = item_at(machine, hWnd, pt.x, pt.y) {
+                    select(machine, hWnd, index).await;
+                }
+            } else {
+                drop_down(machine, hWnd).await;
+            }
+            0
+        }
+        Ok(WM::MOUSEMOVE) if with_combo(machine, hWnd, |cb| cb.dropped) => {
+            // Track the mouse with the highlight.
+            let pt = mouse_pos(lParam);
+            if let Some(index) = item_at(machine, hWnd, pt.x, pt.y) {
+                let moved = with_combo(machine, hWnd, |cb| {
+                    cb.list.cur.replace(index) != Some(index)
+                });
+                if moved {
+                    invalidate(machine, hWnd);
+                }
+            }
+            0
+        }
+        Ok(WM::LBUTTONUP) if with_combo(machine, hWnd, |cb| cb.dropped) => {
+            // Releasing over an item after dragging from the field picks it.
+            let pt = mouse_pos(lParam);
+            if let Some(index) = item_at(machine, hWnd, pt.x, pt.y) {
+                with_combo(machine, hWnd, |cb| cb.list.cur = Some(index));
+                close_up(machine, hWnd, true).await;
+            }
+            0
+        }
+        Ok(WM::LBUTTONDBLCLK) if typ == CBS_SIMPLE => {
+            notify_parent(machine, hWnd, CBN_DBLCLK).await;
+            0
+        }
+        Ok(WM::KEYDOWN | WM::SYSKEYDOWN) => {
+            let dropped = with_combo(machine, hWnd, |cb| cb.dropped);
+            let alt = machine.state.user32.keyboard.is_down(VK_MENU);
+            const VK_F4: u8 = 0x73;
+            match wParam as u8 {
+                VK_F4 => {
+                    if dropped {
+                        close_up(machine, hWnd, true).await;
+                    } else {
+                        drop_down(machine, hWnd).await;
+                    }
+                }
+                VK_DOWN | VK_UP if alt => {
+                    if dropped {
+                        close_up(machine, hWnd, true).await;
+                    } else {
+                        drop_down(machine, hWnd).await;
+                    }
+                }
+                VK_RETURN if dropped => close_up(machine, hWnd, true).await,
+                VK_ESCAPE if dropped => close_up(machine, hWnd, false).await,
+                vk @ (VK_DOWN | VK_UP | VK_HOME | VK_END) => {
+                    let (cur, len) =
+                        with_combo(machine, hWnd, |cb| (cb.list.cur, cb.list.items.len()));
+                    if len == 0 {
+                        return 0;
+                    }
+                    let index = match (vk, cur) {
+                        (VK_HOME, _) => 0,
+                        (VK_END, _) => len - 1,
+                        (_, None) => 0,
+                        (VK_DOWN, Some(cur)) => std::cmp::min(cur + 1, len - 1),
+                        (_, Some(cur)) => cur.saturating_sub(1),
+                    };
+                    if dropped {
+                        // Only highlight; the selection happens on closing.
+                        let page = page_size(machine, hWnd);
+                        with_combo(machine, hWnd, |cb| {
+                            cb.list.cur = Some(index);
+                            cb.list.show(index, page);
+                        });
+                        invalidate(machine, hWnd);
+                    } else {
+                        select(machine, hWnd, index).await;
+                    }
+                }
+                _ => {}
+            }
+            0
+        }
+        Ok(WM::CHAR) if typ == CBS_DROPDOWNLIST => {
+            let Some(c) = char::from_u32(wParam).filter(|c| !c.is_control()) else {
+                return 0;
+            };
+            let found = with_combo(machine, hWnd, |cb| {
+                cb.list.find(cb.list.cur, &c.to_string(), false)
+            });
+            if let Some(index) = found {
+                select(machine, hWnd, index).await;
+            }
+            0
+        }
+        Ok(WM::CHAR) => {
+            // A minimal edit field: typing appends, and backspace deletes.
+            // TODO: this should be an Edit child window.
+            let limit = with_combo(machine, hWnd, |cb| cb.limit);
+            let changed = {
+                let window = machine.state.user32.windows.get(hWnd).unwrap();
+                let mut window = window.borrow_mut();
+                match char::from_u32(wParam) {
+                    Some('\x08') => window.text.pop().is_some(),
+                    Some(c) if !c.is_control() && window.text.chars().count() < limit => {
+                        window.text.push(c);
+                        true
+                    }
+                    _ => false,
+                }
+            };
+            if changed {
+                invalidate(machine, hWnd);
+                notify_parent(machine, hWnd, CBN_EDITUPDATE).await;
+                notify_parent(machine, hWnd, CBN_EDITCHANGE).await;
+            }
+            0
+        }
+        Ok(WM::SETFOCUS) => {
+            with_combo(machine, hWnd, |cb| cb.focused = true);
+            invalidate(machine, hWnd);
+            notify_parent(machine, hWnd, CBN_SETFOCUS).await;
+            0
+        }
+        Ok(WM::KILLFOCUS) => {
+            close_up(machine, hWnd, false).await;
+            with_combo(machine, hWnd, |cb| cb.focused = false);
+            invalidate(machine, hWnd);
+            notify_parent(machine, hWnd, CBN_KILLFOCUS).await;
+            0
+        }
+        Ok(WM::GETDLGCODE) => DLGC_WANTARROWS | DLGC_WANTCHARS,
+        Err(CB_SHOWDROPDOWN) => {
+            if wParam != 0 {
+                drop_down(machine, hWnd).await;
+            } else {
+                close_up(machine, hWnd, false).await;
+            }
+            1
+        }
+        Err(msg) => match list_message(machine, hWnd, style, msg, wParam, lParam) {
+            Some(result) => result,
+            None => def_control_proc(machine, hWnd, Err(msg), wParam, lParam).await,
+        },
+        msg => def_control_proc(machine, hWnd, msg, wParam, lParam).await,
+    }
+}
+
+/// Handle the CB_* messages, returning None for other messages.
+fn list_message(
+    machine: &mut Machine,
+    hwnd: HWND,
+    style: u32,
+    msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> Option<u32> {
+    let data_only =
+        style & (CBS_OWNERDRAWFIXED | CBS_OWNERDRAWVARIABLE) != 0 && style & CBS_HASSTRINGS == 0;
+    let index = wParam as usize;
+    Some(match msg {
+        CB_ADDSTRING => {
+            let item = new_item(machine, data_only, lParam);
+            let index = with_combo(machine, hwnd, |cb| cb.list.add(item));
+            invalidate(machine, hwnd);
+            index as u32
+        }
+        CB_INSERTSTRING => {
+            let item = new_item(machine, data_only, lParam);
+            let result = with_combo(machine, hwnd, |cb| {
+                let index = match wParam as i32 {
+                    -1 => cb.list.items.len(),
+                    i if i as usize > cb.list.items.len() => return CB_ERR,
+                    i => i as usize,
+                };
+                cb.list.insert(index, item);
+                index as u32
+            });
+            invalidate(machine, hwnd);
+            result
+        }
+        CB_DELETESTRING => {
+            let result = with_combo(machine, hwnd, |cb| {
+                if index >= cb.list.items.len() {
+                    return CB_ERR;
+                }
+                cb.list.delete(index);
+                cb.list.items.len() as u32
+            });
+            invalidate(machine, hwnd);
+            result
+        }
+        CB_RESETCONTENT => {
+            with_combo(machine, hwnd, |cb| cb.list.clear());
+            if combo_type(style) == CBS_DROPDOWNLIST {
+                sync_text(machine, hwnd);
+            }
+            invalidate(machine, hwnd);
+            1
+        }
+        CB_GETCOUNT => with_combo(machine, hwnd, |cb| cb.list.items.len() as u32),
+        CB_GETCURSEL => with_combo(machine, hwnd, |cb| cb.list.cur.map_or(CB_ERR, |i| i as u32)),
+        CB_SETCURSEL => {
+            let result = with_combo(machine, hwnd, |cb| {
+                if index < cb.list.items.len() {
+                    cb.list.cur = Some(index);
+                    index as u32
+                } else {
+                    cb.list.cur = None;
+                    CB_ERR
+                }
+            });
+            sync_text(machine, hwnd);
+            result
+        }
+        CB_GETLBTEXT => {
+            let item = with_combo(machine, hwnd, |cb| cb.list.items.get(index).cloned());
+            match item {
+                Some(item) if data_only => {
+                    machine.mem().put_pod::<u32>(lParam, item.data);
+                    4
+                }
+                Some(item) => write_str(machine, lParam, &item.text),
+                None => CB_ERR,
+            }
+        }
+        CB_GETLBTEXTLEN => with_combo(machine, hwnd, |cb| {
+            cb.list
+                .items
+                .get(index)
+                .map_or(CB_ERR, |item| item.text.len() as u32)
+        }),
+        CB_GETITEMDATA => with_combo(machine, hwnd, |cb| {
+            cb.list.items.get(index).map_or(CB_ERR, |item| item.data)
+        }),
+        CB_SETITEMDATA => with_combo(machine, hwnd, |cb| match cb.list.items.get_mut(index) {
+            Some(item) => {
+                item.data = lParam;
+                0
+            }
+            None => CB_ERR,
+        }),
+        CB_FINDSTRING | CB_FINDSTRINGEXACT | CB_SELECTSTRING => {
+            let text = read_str(machine, lParam);
+            let after = match wParam as i32 {
+                -1 => None,
+                i => Some(i as usize),
+            };
+            let found = with_combo(machine, hwnd, |cb| {
+                cb.list.find(after, &text, msg == CB_FINDSTRINGEXACT)
+            });
+            if msg == CB_SELECTSTRING {
+                if let Some(index) = found {
+                    with_combo(machine, hwnd, |cb| cb.list.cur = Some(index));
+                    sync_text(machine, hwnd);
+                }
+            }
+            found.map_or(CB_ERR, |i| i as u32)
+        }
+        CB_GETDROPPEDSTATE => with_combo(machine, hwnd, |cb| cb.dropped) as u32,
+        CB_GETDROPPEDCONTROLRECT => {
+            let field = field_height(machine, hwnd);
+            let (x, y, width) = {
+                let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+                (window.x, window.y, window.width as i32)
+            };
+            let full = with_combo(machine, hwnd, |cb| cb.full_height) as i32;
+            machine.mem().put_pod::<RECT>(
+                lParam,
+                RECT {
+                    left: x,
+                    top: y + field,
+                    right: x + width,
+                    bottom: y + full,
+                },
+            );
+            1
+        }
+        CB_GETITEMHEIGHT => {
+            if wParam as i32 == -1 {
+                field_height(machine, hwnd) as u32
+            } else {
+                item_height(machine, hwnd) as u32
+            }
+        }
+        CB_SETITEMHEIGHT => {
+            if wParam as i32 != -1 {
+                with_combo(machine, hwnd, |cb| {
+                    cb.item_height = Some(lParam as i32 & 0xFFFF)
+                });
+            }
+            0
+        }
+        CB_LIMITTEXT => {
+            let limit = if wParam == 0 {
+                0x7FFF_FFFE
+            } else {
+                wParam as usize
+            };
+            with_combo(machine, hwnd, |cb| cb.limit = limit);
+            1
+        }
+        CB_GETEDITSEL => {
+            // The edit field has no selection; the caret is at the end.
+            let len = machine
+                .state
+                .user32
+                .windows
+                .get(hwnd)
+                .unwrap()
+                .borrow()
+                .text
+                .len() as u32;
+            (len << 16) | len
+        }
+        CB_SETEDITSEL | CB_SETEXTENDEDUI => 1,
+        CB_GETEXTENDEDUI => 0,
+        CB_DIR => {
+            log::warn!("CB_DIR unimplemented");
+            CB_ERR
+        }
+        _ => return None,
+    })
+}
+
+async fn paint(machine: &mut Machine, hwnd: HWND) {
+    let style = class_style(machine, hwnd);
+    let typ = combo_type(style);
+    let field = field_height(machine, hwnd);
+    let height = item_height(machine, hwnd);
+    let owner_draw = style & (CBS_OWNERDRAWFIXED | CBS_OWNERDRAWVARIABLE) != 0;
+    let text = machine
+        .state
+        .user32
+        .windows
+        .get(hwnd)
+        .unwrap()
+        .borrow()
+        .text
+        .chars()
+        .collect::<Vec<_>>();
+    let (list, dropped, focused) = with_combo(machine, hwnd, |cb| {
+        (cb.list.clone(), cb.dropped, cb.focused)
+    });
+    let ctlcolor = if typ == CBS_DROPDOWNLIST {
+        WM::CTLCOLORLISTBOX
+    } else {
+        WM::CTLCOLOREDIT
+    };
+    let paint = super::begin_paint(machine, hwnd, ctlcolor, COLOR::WINDOW, COLOR::WINDOWTEXT).await;
+    let rect = paint.rect;
+    if !paint.enabled {
+        paint.text_color(machine, COLOR::GRAYTEXT.to_colorref());
+    }
+    paint.bk_color(machine, paint.background);
+
+    // The field, with the drop down button at its right.
+    let field_rect = RECT {
+        bottom: rect.top + field,
+        ..rect
+    };
+    let inner = paint.edge(machine, &field_rect, Edge::Sunken);
+    let mut text_rect = inner;
+    if typ != CBS_SIMPLE {
+        let button = RECT {
+            left: inner.right - BUTTON_WIDTH,
+            ..inner
+        };
+        paint.fill(machine, &button, COLOR::BTNFACE.to_colorref());
+        let face = if dropped {
+            paint.frame(machine, &button, COLOR::BTNSHADOW.to_colorref());
+            button
+        } else {
+            paint.edge(machine, &button, Edge::Raised)
+        };
+        let arrow = if paint.enabled {
+            COLOR::BTNTEXT
+        } else {
+            COLOR::GRAYTEXT
+        };
+        paint.arrow(machine, &face, Arrow::Down, arrow.to_colorref());
+        text_rect.right = button.left;
+    }
+
+    if owner_draw && typ == CBS_DROPDOWNLIST {
+        let state = ODS_COMBOBOXEDIT | if focused { ODS_SELECTED | ODS_FOCUS } else { 0 };
+        let (item, data) = match list.cur {
+            Some(cur) => (cur as u32, list.items[cur].data),
+            None => (-1i32 as u32, 0),
+        };
+        draw_item(
+            machine,
+            hwnd,
+            &paint,
+            ODT_COMBOBOX,
+            item,
+            state,
+            text_rect,
+            data,
+        )
+        .await;
+    } else {
+        let label = RECT {
+            left: text_rect.left + 2,
+            ..text_rect
+        };
+        let highlight = focused && !dropped && typ == CBS_DROPDOWNLIST;
+        if highlight {
+            paint.fill(machine, &text_rect, COLOR::HIGHLIGHT.to_colorref());
+            paint.text_color(machine, COLOR::HIGHLIGHTTEXT.to_colorref());
+            paint.bk_color(machine, COLOR::HIGHLIGHT.to_colorref());
+        }
+        paint.text(
+            machine,
+            &text,
+            &label,
+            DT_SINGLELINE | DT_VCENTER | DT_NOPREFIX,
+        );
+        if highlight {
+            paint.text_color(machine, COLOR::WINDOWTEXT.to_colorref());
+            paint.bk_color(machine, paint.background);
+        } else if focused && typ != CBS_DROPDOWNLIST {
+            // The edit field's caret, after the text.
+            let font = super::control_font(machine, hwnd);
+            let x = label.left + font.text_width(&text) as i32;
+            let y = (label.top + label.bottom - font.height() as i32) / 2;
+            let caret = RECT {
+                left: x,
+                top: y,
+                right: x + 1,
+                bottom: y + font.height() as i32,
+            }
+            .clip(&label);
+            paint.fill(machine, &caret, COLOR::WINDOWTEXT.to_colorref());
+        }
+    }
+
+    if dropped || typ == CBS_SIMPLE {
+        let list_rect = RECT {
+            top: field_rect.bottom,
+            ..rect
+        };
+        paint.frame(machine, &list_rect, COLOR::WINDOWFRAME.to_colorref());
+        let items = super::inset(&list_rect, 1);
+        paint_items(
+            machine,
+            hwnd,
+            &paint,
+            &list,
+            &items,
+            height,
+            false,
+            false,
+            owner_draw.then_some(ODT_COMBOBOX),
+        )
+        .await;
+    }
+    super::end_paint(machine, hwnd, paint);
+}
@@ -0,0 +1,869 @@
+//! The "Edit" class: single- and multi-line text entry.
+
+use super::{
+    class_style, def_control_proc, init_control, invalidate, mouse_pos, notify_parent,
+    window_style, ControlState, DLGC_HASSETSEL, DLGC_WANTARROWS, DLGC_WANTCHARS, SB_LINEDOWN,
+    SB_LINEUP, SB_PAGEDOWN, SB_PAGEUP,
+};
+use crate::{
+    winapi::{
+        gdi32,
+        types::{HWND, POINT, RECT},
+        user32::{
+            ReleaseCapture, SetCapture, SetFocus, COLOR, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END,
+            VK_HOME, VK_LEFT, VK_RIGHT, VK_SHIFT, VK_UP, WM, WS,
+        },
+    },
+    Machine,
+};
+use memory::{Extensions, ExtensionsMut};
+use std::ops::Range;
+
+pub const ES_CENTER: u32 = 0x0001;
+pub const ES_RIGHT: u32 = 0x0002;
+pub const ES_MULTILINE: u32 = 0x0004;
+pub const ES_UPPERCASE: u32 = 0x0008;
+pub const ES_LOWERCASE: u32 = 0x0010;
+pub const ES_PASSWORD: u32 = 0x0020;
+pub const ES_NOHIDESEL: u32 = 0x0100;
+pub const ES_READONLY: u32 = 0x0800;
+pub const ES_NUMBER: u32 = 0x2000;
+
+pub const EM_GETSEL: u32 = 0x00B0;
+pub const EM_SETSEL: u32 = 0x00B1;
+pub const EM_GETRECT: u32 = 0x00B2;
+pub const EM_SCROLL: u32 = 0x00B5;
+pub const EM_LINESCROLL: u32 = 0x00B6;
+pub const EM_SCROLLCARET: u32 = 0x00B7;
+pub const EM_GETMODIFY: u32 = 0x00B8;
+pub const EM_SETMODIFY: u32 = 0x00B9;
+pub const EM_GETLINECOUNT: u32 = 0x00BA;
+pub const EM_LINEINDEX: u32 = 0x00BB;
+pub const EM_SETHANDLE: u32 = 0x00BC;
+pub const EM_GETHANDLE: u32 = 0x00BD;
+pub const EM_LINELENGTH: u32 = 0x00C1;
+pub const EM_REPLACESEL: u32 = 0x00C2;
+pub const EM_GETLINE: u32 = 0x00C4;
+pub const EM_LIMITTEXT: u32 = 0x00C5;
+pub const EM_CANUNDO: u32 = 0x00C6;
+pub const EM_UNDO: u32 = 0x00C7;
+pub const EM_FMTLINES: u32 = 0x00C8;
+pub const EM_LINEFROMCHAR: u32 = 0x00C9;
+pub const EM_SETTABSTOPS: u32 = 0x00CB;
+pub const EM_SETPASSWORDCHAR: u32 = 0x00CC;
+pub const EM_EMPTYUNDOBUFFER: u32 = 0x00CD;
+pub const EM_GETFIRSTVISIBLELINE: u32 = 0x00CE;
+pub const EM_SETREADONLY: u32 = 0x00CF;
+pub const EM_GETPASSWORDCHAR: u32 = 0x00D2;
+pub const EM_SETMARGINS: u32 = 0x00D3;
+pub const EM_GETMARGINS: u32 = 0x00D4;
+pub const EM_GETLIMITTEXT: u32 = 0x00D5;
+pub const EM_POSFROMCHAR: u32 = 0x00D6;
+pub const EM_CHARFROMPOS: u32 = 0x00D7;
+
+const WM_CLEAR: u32 = 0x0303;
+const WM_UNDO: u32 = 0x0304;
+
+pub const EN_SETFOCUS: u32 = 0x0100;
+pub const EN_KILLFOCUS: u32 = 0x0200;
+pub const EN_CHANGE: u32 = 0x0300;
+pub const EN_UPDATE: u32 = 0x0400;
+pub const EN_MAXTEXT: u32 = 0x0501;
+
+pub struct Edit {
+    /// The selection runs from the anchor to the caret, in chars.
+    anchor: usize,
+    caret: usize,
+    modified: bool,
+    /// Maximum length, per EM_LIMITTEXT.
+    limit: usize,
+    /// Shown in place of every char, for ES_PASSWORD.
+    password: Option<char>,
+    /// Horizontal scroll, in pixels.
+    scroll_x: i32,
+    /// The first visible line, for multi-line edits.
+    first_line: usize,
+    /// The text and caret before the last edit.
+    undo: Option<(String, usize)>,
+    focused: bool,
+    tracking: bool,
+}
+
+/// Access the edit state along with the window text, which is the edit buffer.
+fn with_edit<T>(machine: &Machine, hwnd: HWND, f: impl FnOnce(&mut Edit, &mut String) -> T) -> T {
+    let mut window = machine.state.user32.windows.get(hwnd).unwrap().borrow_mut();
+    let window = &mut *window;
+    match &mut window.control.as_mut().unwrap().state {
+        ControlState::Edit(edit) => {
+            // The text may have been changed out from under us, e.g. by SetWindowText.
+            let len = window.text.chars().count();
+            edit.anchor = std::cmp::min(edit.anchor, len);
+            edit.caret = std::cmp::min(edit.caret, len);
+            f(edit, &mut window.text)
+        }
+        _ => unreachable!("not an edit"),
+    }
+}
+
+impl Edit {
+    fn selection(&self) -> Range<usize> {
+        std::cmp::min(self.anchor, self.caret)..std::cmp::max(self.anchor, self.caret)
+    }
+
+    fn set_caret(&mut self, pos: usize, extend: bool) {
+        self.caret = pos;
+        if !extend {
+            self.anchor = pos;
+        }
+    }
+}
+
+/// The ranges of the lines of text, not including their line breaks.
+fn lines(text: &[char], multiline: bool) -> Vec<Range<usize>> {
+    if !multiline {
+        return std::iter::once(0..text.len()).collect();
+    }
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (i, &c) in text.iter().enumerate() {
+        if c == '\n' {
+            let end = if i > start && text[i - 1] == '\r' {
+                i - 1
+            } else {
+                i
+            };
+            lines.push(start..end);
+            start = i + 1;
+        }
+    }
+    lines.push(start..text.len());
+    lines
+}
+
+/// The index of the line containing a char position.
+fn line_of(lines: &[Range<usize>], pos: usize) -> usize {
+    lines.iter().rposition(|l| l.start <= pos).unwrap_or(0)
+}
+
+/// The position in a line closest to an x offset from its start.
+fn char_at_x(font: &gdi32::Font, text: &[char], line: &Range<usize>, x: i32) -> usize {
+    let mut left = 0;
+    for i in line.clone() {
+        let width = font.char_width(text[i]) as i32;
+        if x < left + width / 2 {
+            return i;
+        }
+        left += width;
+    }
+    line.end
+}
+
+/// Layout shared by painting and hit testing.
+struct Layout {
+    font: gdi32::Font,
+    /// The formatting rectangle, inside the border.
+    rect: RECT,
+    multiline: bool,
+    password: Option<char>,
+    scroll_x: i32,
+    first_line: usize,
+}
+
+impl Layout {
+    fn new(machine: &Machine, hwnd: HWND) -> Self {
+        let (style, ex_style) = window_style(machine, hwnd);
+        let client = machine
+            .state
+            .user32
+            .windows
+            .get(hwnd)
+            .unwrap()
+            .borrow()
+            .client_rect();
+        let border = super::border_width(style, ex_style);
+        let rect = super::inset(&client, border);
+        let rect = RECT {
+            left: rect.left + 1,
+            right: rect.right - 1,
+            ..rect
+        };
+        let (password, scroll_x, first_line) =
+            with_edit(machine, hwnd, |e, _| (e.password, e.scroll_x, e.first_line));
+        Layout {
+            font: super::control_font(machine, hwnd),
+            rect,
+            multiline: style.bits() & ES_MULTILINE != 0,
+            password,
+            scroll_x,
+            first_line,
+        }
+    }
+
+    fn line_height(&self) -> i32 {
+        self.font.height() as i32
+    }
+
+    fn visible_lines(&self) -> usize {
+        std::cmp::max(1, (self.rect.bottom - self.rect.top) / self.line_height()) as usize
+    }
+
+    /// The chars as displayed, i.e. masked for passwords.
+    fn display(&self, text: &[char]) -> Vec<char> {
+        match self.password {
+            Some(c) => vec![c; text.len()],
+            None => text.to_vec(),
+        }
+    }
+
+    /// The client position of a char position.
+    fn pos_from_char(&self, text: &[char], pos: usize) -> POINT {
+        let text = self.display(text);
+        let lines = lines(&text, self.multiline);
+        let line = line_of(&lines, pos);
+        let x = self
+            .font
+            .text_width(&text[lines[line].start..pos.min(lines[line].end)]) as i32;
+        POINT {
+            x: self.rect.left + x - self.scroll_x,
+            y: self.rect.top + (line as i32 - self.first_line as i32) * self.line_height(),
+        }
+    }
+
+    /// The char position nearest a client position.
+    fn char_from_pos(&self, text: &[char], pt: POINT) -> usize {
+        let text = self.display(text);
+        let lines = lines(&text, self.multiline);
+        let line = (self.first_line as i32 + (pt.y - self.rect.top).div_euclid(self.line_height()))
+            .clamp(0, lines.len() as i32 - 1) as usize;
+        char_at_x(
+            &self.font,
+            &text,
+            &lines[line],
+            pt.x - self.rect.left + self.scroll_x,
+        )
+    }
+}
+
+/// Scroll so the caret is visible.
+fn scroll_to_caret(machine: &Machine, hwnd: HWND) {
+    let layout = Layout::new(machine, hwnd);
+    with_edit(machine, hwnd, |edit, text| {
+        let text = layout.display(&text.chars().collect::<Vec<_>>());
+        let lines = lines(&text, layout.multiline);
+        let line = line_of(&lines, edit.caret);
+        let x = layout
+            .font
+            .text_width(&text[lines[line].start..edit.caret.min(lines[line].end)])
+            as i32;
+        let width = layout.rect.right - layout.rect.left;
+        if x < edit.scroll_x {
+            edit.scroll_x = std::cmp::max(0, x - width / 4);
+        } else if x >= edit.scroll_x + width {
+            edit.scroll_x = x - width + width / 4;
+        }
+        let page = layout.visible_lines();
+        if line < edit.first_line {
+            edit.first_line = line;
+        } else if line >= edit.first_line + page {
+            edit.first_line = line + 1 - page;
+        }
+    });
+}
+
+/// Replace the selection, as typing does, notifying the parent of the change.
+async fn replace_sel(machine: &mut Machine, hwnd: HWND, insert: &str) {
+    let style = class_style(machine, hwnd);
+    let (changed, truncated) = with_edit(machine, hwnd, |edit, text| {
+        let mut chars = text.chars().collect::<Vec<_>>();
+        let sel = edit.selection();
+        let room = edit.limit.saturating_sub(chars.len() - sel.len());
+        let mut insert = insert.chars().collect::<Vec<_>>();
+        if style & ES_MULTILINE == 0 {
+            // Single-line edits stop at the first line break.
+            if let Some(end) = insert.iter().position(|&c| c == '\r' || c == '\n') {
+                insert.truncate(end);
+            }
+        }
+        let truncated = insert.len() > room;
+        insert.truncate(room);
+        if sel.is_empty() && insert.is_empty() {
+            return (false, truncated);
+        }
+        edit.undo = Some((text.clone(), edit.caret));
+        let pos = sel.start + insert.len();
+        chars.splice(sel, insert);
+        *text = chars.into_iter().collect();
+        edit.set_caret(pos, false);
+        edit.modified = true;
+        (true, truncated)
+    });
+    if changed {
+        changed_text(machine, hwnd).await;
+    }
+    if truncated {
+        notify_parent(machine, hwnd, EN_MAXTEXT).await;
+    }
+}
+
+async fn changed_text(machine: &mut Machine, hwnd: HWND) {
+    scroll_to_caret(machine, hwnd);
+    invalidate(machine, hwnd);
+    notify_parent(machine, hwnd, EN_UPDATE).await;
+    notify_parent(machine, hwnd, EN_CHANGE).await;
+}
+
+fn move_caret(machine: &Machine, hwnd: HWND, pos: usize, extend: bool) {
+    with_edit(machine, hwnd, |edit, _| edit.set_caret(pos, extend));
+    scroll_to_caret(machine, hwnd);
+    invalidate(machine, hwnd);
+}
+
+/// The position a cursor key moves the caret to.
+fn key_target(machine: &Machine, hwnd: HWND, vk: u8) -> Option<usize> {
+    let layout = Layout::new(machine, hwnd);
+    let (text, caret) = with_edit(machine, hwnd, |edit, text| {
+        (text.chars().collect::<Vec<_>>(), edit.caret)
+    });
+    let lines = lines(&text, layout.multiline);
+    let line = line_of(&lines, caret);
+    Some(match vk {
+        VK_LEFT if caret > 0 => {
+            // Step over line breaks as a unit.
+            if layout.multiline && caret == lines[line].start && line > 0 {
+                lines[line - 1].end
+            } else {
+                caret - 1
+            }
+        }
+        VK_RIGHT if caret < text.len() => {
+            if layout.multiline && caret == lines[line].end && line + 1 < lines.len() {
+                lines[line + 1].start
+            } else {
+                caret + 1
+            }
+        }
+        VK_HOME => lines[line].start,
+        VK_END => lines[line].end,
+        VK_UP | VK_DOWN if layout.multiline => {
+            let target = if vk == VK_UP {
+                line.checked_sub(1)?
+            } else if line + 1 < lines.len() {
+                line + 1
+            } else {
+                return None;
+            };
+            let display = layout.display(&text);
+            let x = layout
+                .font
+                .text_width(&display[lines[line].start..caret.min(lines[line].end)])
+                as i32;
+            char_at_x(&layout.font, &display, &lines[target], x)
+        }
+        _ => return None,
+    })
+}
+
+/// The window procedure of the "Edit" class.
+#[win32_derive::dllexport]
+pub async fn EditWndProc(
+    machine: &mut Machine,
+    hWnd: HWND,
+    msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    let created = init_control(machine, hWnd, |style| {
+        ControlState::Edit(Edit {
+            anchor: 0,
+            caret: 0,
+            modified: false,
+            limit: 30000,
+            password: (style & ES_PASSWORD != 0).then_some('*'),
+            scroll_x: 0,
+            first_line: 0,
+            undo: None,
+            focused: false,
+            tracking: false,
+        })
+    });
+    if !created {
+        return 0;
+    }
+    let style = class_style(machine, hWnd);
+    let multiline = style & ES_MULTILINE != 0;
+    let readonly = style & ES_READONLY != 0;
+    let shift = machine.state.user32.keyboard.is_down(VK_SHIFT);
+    match msg {
+        Ok(WM::PAINT) => {
+            paint(machine, hWnd).await;
+            0
+        }
+        Ok(WM::LBUTTONDOWN) => {
+            SetFocus(machine, hWnd).await;
+            SetCapture(machine, hWnd);
+            let text = with_edit(machine, hWnd, |edit, text| {
+                edit.tracking = true;
+                text.chars().collect::<Vec<_>>()
+            });
+            let pos = Layout::new(machine, hWnd).char_from_pos(&text, mouse_pos(lParam));
+            move_caret(machine, hWnd, pos, shift);
+            0
+        }
+        Ok(WM::MOUSEMOVE) if with_edit(machine, hWnd, |edit, _| edit.tracking) => {
+            let text = with_edit(machine, hWnd, |_, text| text.chars().collect::<Vec<_>>());
+            let pos = Layout::new(machine, hWnd).char_from_pos(&text, mouse_pos(lParam));
+            move_caret(machine, hWnd, pos, true);
+            0
+        }
+        Ok(WM::LBUTTONUP) if with_edit(machine, hWnd, |edit, _| edit.tracking) => {
+            with_edit(machine, hWnd, |edit, _| edit.tracking = false);
+            ReleaseCapture(machine);
+            0
+        }
+        Ok(WM::LBUTTONDBLCLK) => {
+            // Select the word under the caret.
+            with_edit(machine, hWnd, |edit, text| {
+                let chars = text.chars().collect::<Vec<_>>();
+                let is_word = |i: usize| chars.get(i).is_some_and(|c| !c.is_whitespace());
+                let mut start = edit.caret;
+                while start > 0 && is_word(start - 1) {
+                    start -= 1;
+                }
+                let mut end = edit.caret;
+                while is_word(end) {
+                    end += 1;
+                }
+                edit.anchor = start;
+                edit.caret = end;
+            });
+            invalidate(machine, hWnd);
+            0
+        }
+        Ok(WM::KEYDOWN) => {
+            let vk = wParam as u8;
+            if vk == VK_DELETE {
+                if !readonly {
+                    let empty = with_edit(machine, hWnd, |edit, text| {
+                        if edit.selection().is_empty() && edit.caret < text.chars().count() {
+                            // Delete the next char, or line break.
+                            let next = text.chars().skip(edit.caret).take(2).collect::<String>();
+                            edit.caret += if next == "\r\n" { 2 } else { 1 };
+                        }
+                        edit.selection().is_empty()
+                    });
+                    if !empty {
+                        replace_sel(machine, hWnd, "").await;
+                    }
+                }
+            } else if let Some(pos) = key_target(machine, hWnd, vk) {
+                move_caret(machine, hWnd, pos, shift);
+            }
+            0
+        }
+        Ok(WM::CHAR) => {
+            let ctrl = machine.state.user32.keyboard.is_down(VK_CONTROL);
+            match wParam {
+                0x01 if ctrl => {
+                    // Ctrl-A
+                    with_edit(machine, hWnd, |edit, text| {
+                        edit.anchor = 0;
+                        edit.caret = text.chars().count();
+                    });
+                    invalidate(machine, hWnd);
+                }
+                0x1A if ctrl => {
+                    // Ctrl-Z
+                    undo(machine, hWnd).await;
+                }
+                0x08 if !readonly => {
+                    let empty = with_edit(machine, hWnd, |edit, text| {
+                        if edit.selection().is_empty() && edit.caret > 0 {
+                            let prev = text
+                                .chars()
+                                .skip(edit.caret.saturating_sub(2))
+                                .take(std::cmp::min(edit.caret, 2))
+                                .collect::<String>();
+                            edit.anchor = edit.caret - if prev == "\r\n" { 2 } else { 1 };
+                        }
+                        edit.selection().is_empty()
+                    });
+                    if !empty {
+                        replace_sel(machine, hWnd, "").await;
+                    }
+                }
+                0x0D if multiline && !readonly => replace_sel(machine, hWnd, "\r\n").await,
+                0x09 if multiline && !readonly => replace_sel(machine, hWnd, "\t").await,
+                _ if readonly => {}
+                c => {
+                    let Some(mut c) = char::from_u32(c).filter(|c| !c.is_control()) else {
+                        return 0;
+                    };
+                    if style & ES_NUMBER != 0 && !c.is_ascii_digit() {
+                        return 0;
+                    }
+                    if style & ES_UPPERCASE != 0 {
+                        c = c.to_ascii_uppercase();
+                    } else if style & ES_LOWERCASE != 0 {
+                        c = c.to_ascii_lowercase();
+                    }
+                    replace_sel(machine, hWnd, &c.to_string()).await;
+                }
+            }
+            0
+        }
+        Ok(WM::SETFOCUS) => {
+            with_edit(machine, hWnd, |edit, _| edit.focused = true);
+            invalidate(machine, hWnd);
+            notify_parent(machine, hWnd, EN_SETFOCUS).await;
+            0
+        }
+        Ok(WM::KILLFOCUS) => {
+            with_edit(machine, hWnd, |edit, _| edit.focused = false);
+            invalidate(machine, hWnd);
+            notify_parent(machine, hWnd, EN_KILLFOCUS).await;
+            0
+        }
+        Ok(WM::SETTEXT) => {
+            let text = super::read_str(machine, lParam);
+            with_edit(machine, hWnd, |edit, buf| {
+                *buf = text;
+                edit.anchor = 0;
+                edit.caret = 0;
+                edit.scroll_x = 0;
+                edit.first_line = 0;
+                edit.modified = false;
+                edit.undo = None;
+            });
+            invalidate(machine, hWnd);
+            if !multiline {
+                notify_parent(machine, hWnd, EN_UPDATE).await;
+                notify_parent(machine, hWnd, EN_CHANGE).await;
+            }
+            1
+        }
+        Ok(WM::GETDLGCODE) => {
+            DLGC_WANTCHARS
+                | DLGC_HASSETSEL
+                | DLGC_WANTARROWS
+                | if multiline { DLGC_WANTALLKEYS } else { 0 }
+        }
+        Err(WM_CLEAR) if !readonly => {
+            replace_sel(machine, hWnd, "").await;
+            0
+        }
+        Err(WM_UNDO | EM_UNDO) => undo(machine, hWnd).await as u32,
+        Err(msg) => match edit_message(machine, hWnd, style, msg, wParam, lParam).await {
+            Some(result) => result,
+            None => def_control_proc(machine, hWnd, Err(msg), wParam, lParam).await,
+        },
+        msg => def_control_proc(machine, hWnd, msg, wParam, lParam).await,
+    }
+}
+
+const DLGC_WANTALLKEYS: u32 = 0x0004;
+
+/// Swap the text with the undo buffer, so a second undo redoes.
+async fn undo(machine: &mut Machine, hwnd: HWND) -> bool {
+    let undone = with_edit(machine, hwnd, |edit, text| {
+        let Some((prev, caret)) = edit.undo.take() else {
+            return false;
+        };
+        edit.undo = Some((std::mem::replace(text, prev), edit.caret));
+        edit.set_caret(caret, false);
+        true
+    });
+    if undone {
+        changed_text(machine, hwnd).await;
+    }
+    undone
+}
+
+/// Handle the EM_* messages, returning None for other messages.
+async fn edit_message(
+    machine: &mut Machine,
+    hwnd: HWND,
+    style: u32,
+    msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> Option<u32> {
+    let multiline = style & ES_MULTILINE != 0;
+    let text = with_edit(machine, hwnd, |_, text| text.chars().collect::<Vec<_>>());
+    let lines = lines(&text, multiline);
+    let caret = with_edit(machine, hwnd, |edit, _| edit.caret);
+    // Many messages take a char position or line, with -1 meaning the caret's.
+    let line_arg = |arg: u32| match arg as i32 {
+        -1 => Some(line_of(&lines, caret)),
+        i => Some(i as usize).filter(|&i| i < lines.len()),
+    };
+    Some(match msg {
+        EM_GETSEL => {
+            let sel = with_edit(machine, hwnd, |edit, _| edit.selection());
+            if wParam != 0 {
+                machine.mem().put_pod::<u32>(wParam, sel.start as u32);
+            }
+            if lParam != 0 {
+                machine.mem().put_pod::<u32>(lParam, sel.end as u32);
+            }
+            ((sel.end as u32) << 16) | (sel.start as u32 & 0xFFFF)
+        }
+        EM_SETSEL => {
+            with_edit(machine, hwnd, |edit, _| {
+                let len = text.len();
+                let (start, end) = (wParam as i32, lParam as i32);
+                if start == -1 {
+                    // Remove the selection.
+                    edit.anchor = edit.caret;
+                } else {
+                    let clamp = |i: i32| {
+                        if i < 0 {
+                            len
+                        } else {
+                            std::cmp::min(i as usize, len)
+                        }
+                    };
+                    edit.anchor = clamp(start);
+                    edit.caret = clamp(end);
+                }
+            });
+            scroll_to_caret(machine, hwnd);
+            invalidate(machine, hwnd);
+            1
+        }
+        EM_REPLACESEL => {
+            let insert = super::read_str(machine, lParam);
+            replace_sel(machine, hwnd, &insert).await;
+            // wParam is whether the replacement can be undone.
+            if wParam == 0 {
+                with_edit(machine, hwnd, |edit, _| edit.undo = None);
+            }
+            0
+        }
+        EM_GETRECT => {
+            let rect = Layout::new(machine, hwnd).rect;
+            machine.mem().put_pod::<RECT>(lParam, rect);
+            0
+        }
+        EM_GETMODIFY => with_edit(machine, hwnd, |edit, _| edit.modified) as u32,
+        EM_SETMODIFY => {
+            with_edit(machine, hwnd, |edit, _| edit.modified = wParam != 0);
+            0
+        }
+        EM_GETLINECOUNT => lines.len() as u32,
+        EM_LINEINDEX => line_arg(wParam).map_or(-1i32 as u32, |l| lines[l].start as u32),
+        EM_LINEFROMCHAR => match wParam as i32 {
+            -1 => line_of(&lines, caret) as u32,
+            i => line_of(&lines, i as usize) as u32,
+        },
+        EM_LINELENGTH => {
+            let line = match wParam as i32 {
+                -1 => line_of(&lines, caret),
+                i => line_of(&lines, i as usize),
+            };
+            lines[line].len() as u32
+        }
+        EM_GETLINE => {
+            let Some(line) = line_arg(wParam) else {
+                return Some(0);
+            };
+            // The buffer's size is passed in its first word.
+            let size = machine.mem().get_pod::<u16>(lParam) as usize;
+            let chars = &text[lines[line].clone()];
+            let line = chars[..std::cmp::min(chars.len(), size)]
+                .iter()
+                .collect::<String>();
+            let buf = machine.mem().sub32_mut(lParam, line.len() as u32);
+            buf.copy_from_slice(line.as_bytes());
+            line.len() as u32
+        }
+        EM_LIMITTEXT => {
+            let limit = if wParam == 0 {
+                0x7FFF_FFFE
+            } else {
+                wParam as usize
+            };
+            with_edit(machine, hwnd, |edit, _| edit.limit = limit);
+            0
+        }
+        EM_GETLIMITTEXT => with_edit(machine, hwnd, |edit, _| edit.limit as u32),
+        EM_CANUNDO => with_edit(machine, hwnd, |edit, _| edit.undo.is_some()) as u32,
+        EM_EMPTYUNDOBUFFER => {
+            with_edit(machine, hwnd, |edit, _| edit.undo = None);
+            0
+        }
+        EM_SETPASSWORDCHAR => {
+            with_edit(machine, hwnd, |edit, _| {
+                edit.password = char::from_u32(wParam).filter(|&c| c != '\0')
+            });
+            invalidate(machine, hwnd);
+            0
+        }
+        EM_GETPASSWORDCHAR => with_edit(machine, hwnd, |edit, _| {
+            edit.password.map_or(0, |c| c as u32)
+        }),
+        EM_SETREADONLY => {
+            let window = machine.state.user32.windows.get(hwnd).unwrap();
+            let mut window = window.borrow_mut();
+            let bits = window.style.bits();
+            window.style = WS::from_bits_retain(if wParam != 0 {
+                bits | ES_READONLY
+            } else {
+                bits & !ES_READONLY
+            });
+            window.add_dirty(true, None);
+            1
+        }
+        EM_GETFIRSTVISIBLELINE => {
+            if multiline {
+                with_edit(machine, hwnd, |edit, _| edit.first_line as u32)
+            } else {
+                // The first visible char, for single-line edits.
+                let layout = Layout::new(machine, hwnd);
+                char_at_x(
+                    &layout.font,
+                    &layout.display(&text),
+                    &lines[0],
+                    layout.scroll_x,
+                ) as u32
+            }
+        }
+        EM_LINESCROLL if multiline => {
+            with_edit(machine, hwnd, |edit, _| {
+                let line = edit.first_line as i32 + lParam as i32;
+                edit.first_line = line.clamp(0, lines.len() as i32 - 1) as usize;
+                edit.scroll_x = std::cmp::max(0, edit.scroll_x + wParam as i32);
+            });
+            invalidate(machine, hwnd);
+            1
+        }
+        EM_SCROLL if multiline => {
+            let page = Layout::new(machine, hwnd).visible_lines() as i32;
+            let delta = match wParam {
+                SB_LINEUP => -1,
+                SB_LINEDOWN => 1,
+                SB_PAGEUP => -page,
+                SB_PAGEDOWN => page,
+                _ => return Some(0),
+            };
+            let moved = with_edit(machine, hwnd, |edit, _| {
+                let prev = edit.first_line as i32;
+                edit.first_line = (prev + delta).clamp(0, lines.len() as i32 - 1) as usize;
+                edit.first_line as i32 - prev
+            });
+            invalidate(machine, hwnd);
+            // TRUE in the high word, and the lines scrolled in the low.
+            (1 << 16) | (moved as u32 & 0xFFFF)
+        }
+        EM_SCROLLCARET => {
+            scroll_to_caret(machine, hwnd);
+            invalidate(machine, hwnd);
+            1
+        }
+        EM_POSFROMCHAR => {
+            let index = wParam as usize;
+            if index > text.len() {
+                return Some(-1i32 as u32);
+            }
+            let pt = Layout::new(machine, hwnd).pos_from_char(&text, index);
+            ((pt.y as u32) << 16) | (pt.x as u32 & 0xFFFF)
+        }
+        EM_CHARFROMPOS => {
+            let pos = Layout::new(machine, hwnd).char_from_pos(&text, mouse_pos(lParam));
+            ((line_of(&lines, pos) as u32) << 16) | (pos as u32 & 0xFFFF)
+        }
+        EM_SETTABSTOPS | EM_FMTLINES => 1,
+        EM_SETMARGINS => 0,
+        EM_GETMARGINS => 0,
+        EM_SETHANDLE | EM_GETHANDLE => {
+            log::warn!("edit control local memory handles unimplemented");
+            0
+        }
+        _ => return None,
+    })
+}
+
+async fn paint(machine: &mut Machine, hwnd: HWND) {
+    let style = class_style(machine, hwnd);
+    let (window_style, ex_style) = window_style(machine, hwnd);
+    let readonly = style & ES_READONLY != 0;
+    let (ctlcolor, background) = if readonly || window_style.contains(WS::DISABLED) {
+        (WM::CTLCOLORSTATIC, COLOR::BTNFACE)
+    } else {
+        (WM::CTLCOLOREDIT, COLOR::WINDOW)
+    };
+    let paint = super::begin_paint(machine, hwnd, ctlcolor, background, COLOR::WINDOWTEXT).await;
+    paint.border(machine, &paint.rect, window_style, ex_style);
+    if !paint.enabled {
+        paint.text_color(machine, COLOR::GRAYTEXT.to_colorref());
+    }
+    paint.bk_color(machine, paint.background);
+
+    let layout = Layout::new(machine, hwnd);
+    let (text, sel, caret, focused) = with_edit(machine, hwnd, |edit, text| {
+        (
+            text.chars().collect::<Vec<_>>(),
+            edit.selection(),
+            edit.caret,
+            edit.focused,
+        )
+    });
+    let display = layout.display(&text);
+    let lines = lines(&display, layout.multiline);
+    let show_sel = (focused || style & ES_NOHIDESEL != 0) && !sel.is_empty();
+    let clip = layout.rect;
+    let height = layout.line_height();
+    for (i, line) in lines.iter().enumerate().skip(layout.first_line) {
+        let y = clip.top + (i - layout.first_line) as i32 * height;
+        if y >= clip.bottom {
+            break;
+        }
+        let chars = &display[line.clone()];
+        let width = layout.font.text_width(chars) as i32;
+        let x = if style & ES_CENTER != 0 {
+            clip.left + (clip.right - clip.left - width) / 2
+        } else if style & ES_RIGHT != 0 {
+            clip.right - width
+        } else {
+            clip.left
+        } - layout.scroll_x;
+
+        // Draw the line in up to three runs: before, within, and after the selection.
+        let sel_start = sel.start.clamp(line.start, line.end);
+        let sel_end = sel.end.clamp(line.start, line.end);
+        let runs = [
+            (line.start..sel_start, false),
+            (sel_start..sel_end, show_sel),
+            (sel_end..line.end, false),
+        ];
+        let mut run_x = x;
+        for (run, selected) in runs {
+            if run.is_empty() {
+                continue;
+            }
+            let chars = &display[run];
+            if selected {
+                paint.text_color(machine, COLOR::HIGHLIGHTTEXT.to_colorref());
+                paint.bk_color(machine, COLOR::HIGHLIGHT.to_colorref());
+            }
+            gdi32::draw_text(machine, paint.hdc, run_x, y, chars, None, Some(&clip));
+            if selected {
+                paint.text_color(machine, COLOR::WINDOWTEXT.to_colorref());
+                paint.bk_color(machine, paint.background);
+            }
+            run_x += layout.font.text_width(chars) as i32;
+        }
+    }
+
+    if focused {
+        let pt = layout.pos_from_char(&text, caret);
+        let caret = RECT {
+            left: pt.x,
+            top: pt.y,
+            right: pt.x + 1,
+            bottom: pt.y + height,
+        }
+        .clip(&clip);
+        paint.fill(machine, &caret, COLOR::WINDOWTEXT.to_colorref());
+    }
+    super::end_paint(machine, hwnd, paint);
+}
@@ -0,0 +1,802 @@
+//! The "ListBox" class, and the list model shared with combo boxes.
+
+use super::{
+    class_style, def_control_proc, draw_item, init_control, invalidate, mouse_pos, notify_parent,
+    read_str, window_style, write_str, ControlState, Paint, DLGC_WANTARROWS, DLGC_WANTCHARS,
+    ODS_FOCUS, ODS_SELECTED, ODT_LISTBOX,
+};
+use crate::{
+    winapi::{
+        types::{HWND, RECT},
+        user32::{
+            ReleaseCapture, SetCapture, SetFocus, COLOR, DT_NOPREFIX, DT_SINGLELINE, DT_VCENTER,
+            VK_DOWN, VK_END, VK_HOME, VK_NEXT, VK_PRIOR, VK_SHIFT, VK_SPACE, VK_UP, WM,
+        },
+    },
+    Machine,
+};
+use memory::ExtensionsMut;
+use std::cmp::Ordering;
+
+pub const LBS_NOTIFY: u32 = 0x0001;
+pub const LBS_SORT: u32 = 0x0002;
+pub const LBS_MULTIPLESEL: u32 = 0x0008;
+pub const LBS_OWNERDRAWFIXED: u32 = 0x0010;
+pub const LBS_OWNERDRAWVARIABLE: u32 = 0x0020;
+pub const LBS_HASSTRINGS: u32 = 0x0040;
+pub const LBS_EXTENDEDSEL: u32 = 0x0800;
+
+pub const LB_ADDSTRING: u32 = 0x0180;
+pub const LB_INSERTSTRING: u32 = 0x0181;
+pub const LB_DELETESTRING: u32 = 0x0182;
+pub const LB_RESETCONTENT: u32 = 0x0184;
+pub const LB_SETSEL: u32 = 0x0185;
+pub const LB_SETCURSEL: u32 = 0x0186;
+pub const LB_GETSEL: u32 = 0x0187;
+pub const LB_GETCURSEL: u32 = 0x0188;
+pub const LB_GETTEXT: u32 = 0x0189;
+pub const LB_GETTEXTLEN: u32 = 0x018A;
+pub const LB_GETCOUNT: u32 = 0x018B;
+pub const LB_SELECTSTRING: u32 = 0x018C;
+pub const LB_DIR: u32 = 0x018D;
+pub const LB_GETTOPINDEX: u32 = 0x018E;
+pub const LB_FINDSTRING: u32 = 0x018F;
+pub const LB_GETSELCOUNT: u32 = 0x0190;
+pub const LB_GETSELITEMS: u32 = 0x0191;
+pub const LB_SETTABSTOPS: u32 = 0x0192;
+pub const LB_SETHORIZONTALEXTENT: u32 = 0x0194;
+pub const LB_SETTOPINDEX: u32 = 0x0197;
+pub const LB_GETITEMRECT: u32 = 0x0198;
+pub const LB_GETITEMDATA: u32 = 0x0199;
+pub const LB_SETITEMDATA: u32 = 0x019A;
+pub const LB_SELITEMRANGE: u32 = 0x019B;
+pub const LB_SETANCHORINDEX: u32 = 0x019C;
+pub const LB_GETANCHORINDEX: u32 = 0x019D;
+pub const LB_SETCARETINDEX: u32 = 0x019E;
+pub const LB_GETCARETINDEX: u32 = 0x019F;
+pub const LB_SETITEMHEIGHT: u32 = 0x01A0;
+pub const LB_GETITEMHEIGHT: u32 = 0x01A1;
+pub const LB_FINDSTRINGEXACT: u32 = 0x01A2;
+
+pub const LB_ERR: u32 = -1i32 as u32;
+
+pub const LBN_SELCHANGE: u32 = 1;
+pub const LBN_DBLCLK: u32 = 2;
+pub const LBN_SETFOCUS: u32 = 4;
+pub const LBN_KILLFOCUS: u32 = 5;
+
+#[derive(Clone, Default)]
+pub struct ListItem {
+    pub text: String,
+    /// LB_SETITEMDATA; for owner-drawn lists without LBS_HASSTRINGS, the item itself.
+    pub data: u32,
+    /// Only used in multiple selection lists.
+    pub selected: bool,
+}
+
+/// The items of a list box or a combo box's list.
+#[derive(Clone, Default)]
+pub struct List {
+    pub items: Vec<ListItem>,
+    pub sorted: bool,
+    /// The current selection, or in multiple selection lists the caret.
+    pub cur: Option<usize>,
+    /// The first visible item.
+    pub top: usize,
+}
+
+fn cmp_ignore_case(a: &str, b: &str) -> Ordering {
+    a.chars()
+        .map(|c| c.to_ascii_lowercase())
+        .cmp(b.chars().map(|c| c.to_ascii_lowercase()))
+}
+
+impl List {
+    /// Append an item, or insert it in order in sorted lists.  Returns its index.
+    pub fn add(&mut self, item: ListItem) -> usize {
+        let index = if self.sorted {
+            self.items
+                .partition_point(|i| cmp_ignore_case(&i.text, &item.text) != Ordering::Greater)
+        } else {
+            self.items.len()
+        };
+        self.insert(index, item);
+        index
+    }
+
+    pub fn insert(&mut self, index: usize, item: ListItem) {
+        self.items.insert(index, item);
+        if let Some(cur) = &mut self.cur {
+            if *cur >= index {
+                *cur += 1;
+            }
+        }
+    }
+
+    pub fn delete(&mut self, index: usize) {
+        self.items.remove(index);
+        self.cur = match self.cur {
+            Some(cur) if cur == index => None,
+            Some(cur) if cur > index => Some(cur - 1),
+            cur => cur,
+        };
+        self.top = std::cmp::min(self.top, self.items.len().saturating_sub(1));
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.cur = None;
+        self.top = 0;
+    }
+
+    /// Search for an item by case-insensitive prefix or exact match, starting after
+    /// the given index and wrapping around, as LB_FINDSTRING does.
+    pub fn find(&self, after: Option<usize>, text: &str, exact: bool) -> Option<usize> {
+        let len = self.items.len();
+        let start = after.map_or(0, |i| i + 1);
+        (0..len).map(|i| (start + i) % len).find(|&i| {
+            let item = &self.items[i].text;
+            if exact {
+                item.eq_ignore_ascii_case(text)
+            } else {
+                item.len() >= text.len()
+                    && item.is_char_boundary(text.len())
+                    && item[..text.len()].eq_ignore_ascii_case(text)
+            }
+        })
+    }
+
+    /// Scroll so an item is within a page of visible items.
+    pub fn show(&mut self, index: usize, page: usize) {
+        if index < self.top {
+            self.top = index;
+        } else if index >= self.top + page {
+            self.top = index + 1 - page;
+        }
+    }
+}
+
+pub struct ListBox {
+    list: List,
+    /// Where shift-extended selections start, in LBS_EXTENDEDSEL lists.
+    anchor: usize,
+    /// Set by LB_SETITEMHEIGHT; otherwise the font height.
+    item_height: Option<i32>,
+    focused: bool,
+    tracking: bool,
+}
+
+fn with_listbox<T>(machine: &Machine, hwnd: HWND, f: impl FnOnce(&mut ListBox) -> T) -> T {
+    let mut window = machine.state.user32.windows.get(hwnd).unwrap().borrow_mut();
+    match &mut window.control.as_mut().unwrap().state {
+        ControlState::ListBox(listbox) => f(listbox),
+        _ => unreachable!("not a listbox"),
+    }
+}
+
+/// The message argument of LB_ADDSTRING etc., which is just data in owner-drawn
+/// lists without LBS_HASSTRINGS.
+pub(super) fn new_item(machine: &Machine, data_only: bool, lParam: u32) -> ListItem {
+    if data_only {
+        ListItem {
+            text: String::new(),
+            data: lParam,
+            selected: false,
+        }
+    } else {
+        ListItem {
+            text: read_str(machine, lParam),
+            data: 0,
+            selected: false,
+        }
+    }
+}
+
+fn item_height(machine: &Machine, hwnd: HWND) -> i32 {
+    with_listbox(machine, hwnd, |lb| lb.item_height)
+        .unwrap_or_else(|| super::control_font(machine, hwnd).height() as i32)
+}
+
+/// The area items are drawn in, inside any border.
+fn items_rect(machine: &Machine, hwnd: HWND) -> RECT {
+    let (style, ex_style) = window_style(machine, hwnd);
+    let rect = machine
+        .state
+        .user32
+        .windows
+        .get(hwnd)
+        .unwrap()
+        .borrow()
+        .client_rect();
+    super::inset(&rect, super::border_width(style, ex_style))
+}
+
+/// The number of items that fit in the list.
+fn page_size(machine: &Machine, hwnd: HWND) -> usize {
+    let rect = items_rect(machine, hwnd);
+    std::cmp::max(1, (rect.bottom - rect.top) / item_height(machine, hwnd)) as usize
+}
+
+fn is_multi(style: u32) -> bool {
+    style & (LBS_MULTIPLESEL | LBS_EXTENDEDSEL) != 0
+}
+
+/// Move the caret, updating the selection per the selection mode.
+/// Returns whether the selection changed.
+fn select(machine: &Machine, hwnd: HWND, index: usize, toggle: bool, extend: bool) -> bool {
+    let style = class_style(machine, hwnd);
+    let page = page_size(machine, hwnd);
+    let changed = with_listbox(machine, hwnd, |lb| {
+        let list = &mut lb.list;
+        if index >= list.items.len() {
+            return false;
+        }
+        let prev = list.cur;
+        list.cur = Some(index);
+        list.show(index, page);
+        if style & LBS_EXTENDEDSEL != 0 && extend {
+            let (lo, hi) = if lb.anchor < index {
+                (lb.anchor, index)
+            } else {
+                (index, lb.anchor)
+            };
+            for (i, item) in list.items.iter_mut().enumerate() {
+                item.selected = (lo..=hi).contains(&i);
+            }
+            true
+        } else if style & LBS_EXTENDEDSEL != 0 {
+            lb.anchor = index;
+            for (i, item) in list.items.iter_mut().enumerate() {
+                item.selected = i == index;
+            }
+            true
+        } else if style & LBS_MULTIPLESEL != 0 {
+            if toggle {
+                list.items[index].selected = !list.items[index].selected;
+            }
+            toggle
+        } else {
+            prev != Some(index)
+        }
+    });
+    invalidate(machine, hwnd);
+    changed
+}
+
+async fn sel_change(machine: &mut Machine, hwnd: HWND) {
+    if class_style(machine, hwnd) & LBS_NOTIFY != 0 {
+        notify_parent(machine, hwnd, LBN_SELCHANGE).await;
+    }
+}
+
+/// The window procedure of the "ListBox" class.
+#[win32_derive::dllexport]
+pub async fn ListBoxWndProc(
+    machine: &mut Machine,
+    hWnd: HWND,
+    msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    let created = init_control(machine, hWnd, |style| {
+        ControlState::ListBox(ListBox {
+            list: List {
+                sorted: style & LBS_SORT != 0,
+                ..Default::default()
+            },
+            anchor: 0,
+            item_height: None,
+            focused: false,
+            tracking: false,
+        })
+    });
+    if !created {
+        return 0;
+    }
+    let style = class_style(machine, hWnd);
+    let owner_draw = LBS_OWNERDRAWFIXED | LBS_OWNERDRAWVARIABLE;
+    match msg {
+        Ok(WM::PAINT) => {
+            paint(machine, hWnd).await;
+            0
+        }
+        Ok(WM::LBUTTONDOWN) => {
+            SetFocus(machine, hWnd).await;
+            SetCapture(machine, hWnd);
+            with_listbox(machine, hWnd, |lb| lb.tracking = true);
+            let shift = machine.state.user32.keyboard.is_down(VK_SHIFT);
+            if let Some(index) = item_at(machine, hWnd, mouse_pos(lParam).y) {
+                if select(machine, hWnd, index, true, shift) {
+                    sel_change(machine, hWnd).await;
+                }
+            }
+            0
+        }
+        Ok(WM::MOUSEMOVE) if with_listbox(machine, hWnd, |lb| lb.tracking) => {
+            // Dragging moves a single selection.
+            if !is_multi(style) {
+                if let Some(index) = item_at(machine, hWnd, mouse_pos(lParam).y) {
+                    if select(machine, hWnd, index, false, false) {
+                        sel_change(machine, hWnd).await;
+                    }
+                }
+            }
+            0
+        }
+        Ok(WM::LBUTTONUP) if with_listbox(machine, hWnd, |lb| lb.tracking) => {
+            with_listbox(machine, hWnd, |lb| lb.tracking = false);
+            ReleaseCapture(machine);
+            0
+        }
+        Ok(WM::LBUTTONDBLCLK) => {
+            if style & LBS_NOTIFY != 0 {
+                notify_parent(machine, hWnd, LBN_DBLCLK).await;
+            }
+            0
+        }
+        Ok(WM::KEYDOWN) => {
+            let (cur, len) = with_listbox(machine, hWnd, |lb| (lb.list.cur, lb.list.items.len()));
+            if len == 0 {
+                return 0;
+            }
+            let page = page_size(machine, hWnd);
+            let cur = cur.unwrap_or(0);
+            let index = match wParam as u8 {
+                VK_UP => cur.saturating_sub(1),
+                VK_DOWN => cur + 1,
+                VK_PRIOR => cur.saturating_sub(page - 1),
+                VK_NEXT => cur + page - 1,
+                VK_HOME => 0,
+                VK_END => len - 1,
+                VK_SPACE if style & LBS_MULTIPLESEL != 0 => {
+                    if select(machine, hWnd, cur, true, false) {
+                        sel_change(machine, hWnd).await;
+                    }
+                    return 0;
+                }
+                _ => return 0,
+            };
+            let index = std::cmp::min(index, len - 1);
+            let shift = machine.state.user32.keyboard.is_down(VK_SHIFT);
+            if style & LBS_MULTIPLESEL != 0 {
+                // The caret moves without changing the selection.
+                with_listbox(machine, hWnd, |lb| {
+                    lb.list.cur = Some(index);
+                    lb.list.show(index, page);
+                });
+                invalidate(machine, hWnd);
+            } else if select(machine, hWnd, index, false, shift) {
+                sel_change(machine, hWnd).await;
+            }
+            0
+        }
+        Ok(WM::CHAR) if style & owner_draw == 0 || style & LBS_HASSTRINGS != 0 => {
+            let Some(c) = char::from_u32(wParam).filter(|c| !c.is_control()) else {
+                return 0;
+            };
+            let found = with_listbox(machine, hWnd, |lb| {
+                lb.list.find(lb.list.cur, &c.to_string(), false)
+            });
+            if let Some(index) = found {
+                if select(machine, hWnd, index, false, false) {
+                    sel_change(machine, hWnd).await;
+                }
+            }
+            0
+        }
+        Ok(WM::SETFOCUS) => {
+            with_listbox(machine, hWnd, |lb| lb.focused = true);
+            invalidate(machine, hWnd);
+            if style & LBS_NOTIFY != 0 {
+                notify_parent(machine, hWnd, LBN_SETFOCUS).await;
+            }
+            0
+        }
+        Ok(WM::KILLFOCUS) => {
+            with_listbox(machine, hWnd, |lb| lb.focused = false);
+            invalidate(machine, hWnd);
+            if style & LBS_NOTIFY != 0 {
+                notify_parent(machine, hWnd, LBN_KILLFOCUS).await;
+            }
+            0
+        }
+        Ok(WM::GETDLGCODE) => DLGC_WANTARROWS | DLGC_WANTCHARS,
+        Err(msg) => {
+            let result = list_message(machine, hWnd, style, msg, wParam, lParam);
+            if let Some(result) = result {
+                return result;
+            }
+            def_control_proc(machine, hWnd, Err(msg), wParam, lParam).await
+        }
+        msg => def_control_proc(machine, hWnd, msg, wParam, lParam).await,
+    }
+}
+
+/// Handle the LB_* messages, returning None for other messages.
+fn list_message(
+    machine: &Machine,
+    hwnd: HWND,
+    style: u32,
+    msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> Option<u32> {
+    let data_only =
+        style & (LBS_OWNERDRAWFIXED | LBS_OWNERDRAWVARIABLE) != 0 && style & LBS_HASSTRINGS == 0;
+    let index = wParam as usize;
+    let page = page_size(machine, hwnd);
+    let result = match msg {
+        LB_ADDSTRING => {
+            let item = new_item(machine, data_only, lParam);
+            with_listbox(machine, hwnd, |lb| lb.list.add(item)) as u32
+        }
+        LB_INSERTSTRING => {
+            let item = new_item(machine, data_only, lParam);
+            with_listbox(machine, hwnd, |lb| {
+                let index = match wParam as i32 {
+                    -1 => lb.list.items.len(),
+                    i if i as usize > lb.list.items.len() => return LB_ERR,
+                    i => i as usize,
+                };
+                lb.list.insert(index, item);
+                index as u32
+            })
+        }
+        LB_DELETESTRING => with_listbox(machine, hwnd, |lb| {
+            if index >= lb.list.items.len() {
+                return LB_ERR;
+            }
+            lb.list.delete(index);
+            lb.list.items.len() as u32
+        }),
+        LB_RESETCONTENT => {
+            with_listbox(machine, hwnd, |lb| lb.list.clear());
+            0
+        }
+        LB_GETCOUNT => with_listbox(machine, hwnd, |lb| lb.list.items.len() as u32),
+        LB_SETCURSEL if !is_multi(style) => with_listbox(machine, hwnd, |lb| {
+            if index < lb.list.items.len() {
+                lb.list.cur = Some(index);
+                lb.list.show(index, page);
+                index as u32
+            } else {
+                // -1 clears the selection, but still returns LB_ERR.
+                lb.list.cur = None;
+                LB_ERR
+            }
+        }),
+        LB_GETCURSEL => with_listbox(machine, hwnd, |lb| {
+            lb.list.cur.map_or(LB_ERR, |cur| cur as u32)
+        }),
+        LB_SETSEL if is_multi(style) => with_listbox(machine, hwnd, |lb| {
+            // lParam is the index, with -1 meaning all items.
+            match lParam as i32 {
+                -1 => lb
+                    .list
+                    .items
+                    .iter_mut()
+                    .for_each(|i| i.selected = wParam != 0),
+                i => match lb.list.items.get_mut(i as usize) {
+                    Some(item) => item.selected = wParam != 0,
+                    None => return LB_ERR,
+                },
+            }
+            0
+        }),
+        LB_GETSEL => with_listbox(machine, hwnd, |lb| match lb.list.items.get(index) {
+            Some(item) if is_multi(style) => item.selected as u32,
+            Some(_) => (lb.list.cur == Some(index)) as u32,
+            None => LB_ERR,
+        }),
+        LB_GETSELCOUNT if is_multi(style) => with_listbox(machine, hwnd, |lb| {
+            lb.list.items.iter().filter(|i| i.selected).count() as u32
+        }),
+        LB_GETSELITEMS if is_multi(style) => {
+            let selected = with_listbox(machine, hwnd, |lb| {
+                lb.list
+                    .items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, item)| item.selected)
+                    .map(|(i, _)| i as u32)
+                    .take(wParam as usize)
+                    .collect::<Vec<_>>()
+            });
+            for (i, &index) in selected.iter().enumerate() {
+                machine.mem().put_pod::<u32>(lParam + i as u32 * 4, index);
+            }
+            selected.len() as u32
+        }
+        LB_SELITEMRANGE if is_multi(style) => with_listbox(machine, hwnd, |lb| {
+            let (first, last) = (lParam & 0xFFFF, lParam >> 16);
+            for item in lb
+                .list
+                .items
+                .iter_mut()
+                .take(last as usize + 1)
+                .skip(first as usize)
+            {
+                item.selected = wParam != 0;
+            }
+            0
+        }),
+        LB_GETTEXT => {
+            let item = with_listbox(machine, hwnd, |lb| lb.list.items.get(index).cloned());
+            match item {
+                Some(item) if data_only => {
+                    machine.mem().put_pod::<u32>(lParam, item.data);
+                    4
+                }
+                Some(item) => write_str(machine, lParam, &item.text),
+                None => LB_ERR,
+            }
+        }
+        LB_GETTEXTLEN => with_listbox(machine, hwnd, |lb| {
+            lb.list
+                .items
+                .get(index)
+                .map_or(LB_ERR, |item| item.text.len() as u32)
+        }),
+        LB_GETITEMDATA => with_listbox(machine, hwnd, |lb| {
+            lb.list.items.get(index).map_or(LB_ERR, |item| item.data)
+        }),
+        LB_SETITEMDATA => with_listbox(machine, hwnd, |lb| match lb.list.items.get_mut(index) {
+            Some(item) => {
+                item.data = lParam;
+                0
+            }
+            None => LB_ERR,
+        }),
+        LB_FINDSTRING | LB_FINDSTRINGEXACT | LB_SELECTSTRING => {
+            let text = read_str(machine, lParam);
+            let after = match wParam as i32 {
+                -1 => None,
+                i => Some(i as usize),
+            };
+            let found = with_listbox(machine, hwnd, |lb| {
+                lb.list.find(after, &text, msg == LB_FINDSTRINGEXACT)
+            });
+            match found {
+                Some(index) if msg == LB_SELECTSTRING => {
+                    with_listbox(machine, hwnd, |lb| {
+                        lb.list.cur = Some(index);
+                        lb.list.show(index, page);
+                    });
+                    index as u32
+                }
+                Some(index) => index as u32,
+                None => LB_ERR,
+            }
+        }
+        LB_GETTOPINDEX => with_listbox(machine, hwnd, |lb| lb.list.top as u32),
+        LB_SETTOPINDEX => with_listbox(machine, hwnd, |lb| {
+            if index >= lb.list.items.len() {
+                return LB_ERR;
+            }
+            lb.list.top = index;
+            0
+        }),
+        LB_SETCARETINDEX => with_listbox(machine, hwnd, |lb| {
+            if index >= lb.list.items.len() {
+                return LB_ERR;
+            }
+            lb.list.cur = Some(index);
+            lb.list.show(index, page);
+            0
+        }),
+        LB_GETCARETINDEX => with_listbox(machine, hwnd, |lb| lb.list.cur.unwrap_or(0) as u32),
+        LB_SETANCHORINDEX => with_listbox(machine, hwnd, |lb| {
+            lb.anchor = index;
+            0
+        }),
+        LB_GETANCHORINDEX => with_listbox(machine, hwnd, |lb| lb.anchor as u32),
+        LB_GETITEMHEIGHT => item_height(machine, hwnd) as u32,
+        LB_SETITEMHEIGHT => {
+            with_listbox(machine, hwnd, |lb| {
+                lb.item_height = Some(lParam as i32 & 0xFFFF)
+            });
+            0
+        }
+        LB_GETITEMRECT => {
+            let height = item_height(machine, hwnd);
+            let rect = items_rect(machine, hwnd);
+            let top = with_listbox(machine, hwnd, |lb| lb.list.top) as i32;
+            let y = rect.top + (index as i32 - top) * height;
+            machine.mem().put_pod::<RECT>(
+                lParam,
+                RECT {
+                    left: rect.left,
+                    top: y,
+                    right: rect.right,
+                    bottom: y + height,
+                },
+            );
+            1
+        }
+        LB_SETTABSTOPS | LB_SETHORIZONTALEXTENT => 1,
+        LB_DIR => {
+            log::warn!("LB_DIR unimplemented");
+            LB_ERR
+        }
+        _ => return None,
+    };
+    let read_only = matches!(
+        msg,
+        LB_GETCOUNT
+            | LB_GETCURSEL
+            | LB_GETSEL
+            | LB_GETSELCOUNT
+            | LB_GETSELITEMS
+            | LB_GETTEXT
+            | LB_GETTEXTLEN
+            | LB_GETITEMDATA
+            | LB_SETITEMDATA
+            | LB_FINDSTRING
+            | LB_FINDSTRINGEXACT
+            | LB_GETTOPINDEX
+            | LB_GETCARETINDEX
+            | LB_GETANCHORINDEX
+            | LB_GETITEMHEIGHT
+            | LB_GETITEMRECT
+    );
+    if !read_only {
+        invalidate(machine, hwnd);
+    }
+    Some(result)
+}
+
+fn item_at(machine: &Machine, hwnd: HWND, y: i32) -> Option<usize> {
+    let rect = items_rect(machine, hwnd);
+    if y < rect.top {
+        return None;
+    }
+    let height = item_height(machine, hwnd);
+    let (top, len) = with_listbox(machine, hwnd, |lb| (lb.list.top, lb.list.items.len()));
+    let index = top + ((y - rect.top) / height) as usize;
+    (index < len).then_some(index)
+}
+
+async fn paint(machine: &mut Machine, hwnd: HWND) {
+    let style = class_style(machine, hwnd);
+    let (window_style, ex_style) = window_style(machine, hwnd);
+    let height = item_height(machine, hwnd);
+    let (list, focused) = with_listbox(machine, hwnd, |lb| (lb.list.clone(), lb.focused));
+    let paint = super::begin_paint(
+        machine,
+        hwnd,
+        WM::CTLCOLORLISTBOX,
+        COLOR::WINDOW,
+        COLOR::WINDOWTEXT,
+    )
+    .await;
+    let rect = paint.border(machine, &paint.rect, window_style, ex_style);
+    let owner_draw = style & (LBS_OWNERDRAWFIXED | LBS_OWNERDRAWVARIABLE) != 0;
+    paint_items(
+        machine,
+        hwnd,
+        &paint,
+        &list,
+        &rect,
+        height,
+        is_multi(style),
+        focused,
+        owner_draw.then_some(ODT_LISTBOX),
+    )
+    .await;
+    super::end_paint(machine, hwnd, paint);
+}
+
+/// Draw the visible items of a list, for list boxes and combo boxes.
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn paint_items(
+    machine: &mut Machine,
+    hwnd: HWND,
+    paint: &Paint,
+    list: &List,
+    rect: &RECT,
+    height: i32,
+    multi: bool,
+    focused: bool,
+    owner_draw: Option<u32>,
+) {
+    for (index, item) in list.items.iter().enumerate().skip(list.top) {
+        let y = rect.top + (index - list.top) as i32 * height;
+        if y >= rect.bottom {
+            break;
+        }
+        let item_rect = RECT {
+            left: rect.left,
+            top: y,
+            right: rect.right,
+            bottom: std::cmp::min(y + height, rect.bottom),
+        };
+        let selected = if multi {
+            item.selected
+        } else {
+            list.cur == Some(index)
+        };
+        let has_focus = focused && list.cur == Some(index);
+        if let Some(typ) = owner_draw {
+            let state =
+                if selected { ODS_SELECTED } else { 0 } | if has_focus { ODS_FOCUS } else { 0 };
+            draw_item(
+                machine,
+                hwnd,
+                paint,
+                typ,
+                index as u32,
+                state,
+                item_rect,
+                item.data,
+            )
+            .await;
+            continue;
+        }
+        let text = item.text.chars().collect::<Vec<_>>();
+        let text_rect = RECT {
+            left: item_rect.left + 2,
+            ..item_rect
+        };
+        if selected {
+            paint.fill(machine, &item_rect, COLOR::HIGHLIGHT.to_colorref());
+            paint.text_color(machine, COLOR::HIGHLIGHTTEXT.to_colorref());
+            paint.bk_color(machine, COLOR::HIGHLIGHT.to_colorref());
+            paint.text(
+                machine,
+                &text,
+                &text_rect,
+                DT_SINGLELINE | DT_VCENTER | DT_NOPREFIX,
+            );
+            paint.text_color(machine, COLOR::WINDOWTEXT.to_colorref());
+            paint.bk_color(machine, paint.background);
+        } else {
+            paint.text(
+                machine,
+                &text,
+                &text_rect,
+                DT_SINGLELINE | DT_VCENTER | DT_NOPREFIX,
+            );
+        }
+        if has_focus {
+            paint.focus_rect(machine, &item_rect, &[]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str) -> ListItem {
+        ListItem {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_list() {
+        let mut list = List {
+            sorted: true,
+            ..Default::default()
+        };
+        assert_eq!(list.add(item("banana")), 0);
+        assert_eq!(list.add(item("Apple")), 0);
+        assert_eq!(list.add(item("cherry")), 2);
+        assert_eq!(list.add(item("apple")), 1);
+        list.cur = Some(2);
+        list.delete(0);
+        assert_eq!(list.cur, Some(1));
+        assert_eq!(list.items[list.cur.unwrap()].text, "banana");
+
+        assert_eq!(list.find(None, "CH", false), Some(2));
+        assert_eq!(list.find(Some(2), "ch", false), Some(2));
+        assert_eq!(list.find(Some(0), "apple", true), Some(0));
+        assert_eq!(list.find(None, "app le", false), None);
+
+        list.show(2, 2);
+        assert_eq!(list.top, 1);
+        list.show(0, 2);
+        assert_eq!(list.top, 0);
+    }
+}
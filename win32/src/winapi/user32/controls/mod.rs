@@ -0,0 +1,584 @@
+//! The predefined control classes ("Button", "Edit", etc.).
+//!
+//! Their window procedures are native, but otherwise controls are ordinary child
+//! windows: they paint into their own backing store through gdi32, and report to
+//! their parent with WM_COMMAND notifications.
+
+mod button;
+mod combobox;
+mod edit;
+mod listbox;
+mod scrollbar;
+mod static_;
+
+pub use button::*;
+pub use combobox::*;
+pub use edit::*;
+pub use listbox::*;
+pub use scrollbar::*;
+pub use static_::*;
+
+use super::{
+    def_window_proc, flush_window, register_class, SendMessageA, WndClass, COLOR, CS, DIALOG_CLASS,
+    WM, WS, WS_EX,
+};
+use crate::{
+    winapi::{
+        gdi32::{self, BkMode, DCTarget, COLORREF, DC, HDC, HGDIOBJ},
+        kernel32,
+        types::{HWND, POINT, RECT},
+    },
+    Machine,
+};
+use memory::ExtensionsMut;
+
+/// Per-window state of a control.
+pub struct Control {
+    /// Set by WM_SETFONT; null means the system font.
+    font: HGDIOBJ,
+    state: ControlState,
+}
+
+enum ControlState {
+    Button(Button),
+    Edit(Edit),
+    Static(Static),
+    ListBox(ListBox),
+    ComboBox(ComboBox),
+    ScrollBar(ScrollBar),
+}
+
+/// Register the dialog class and the control classes; called when user32 is loaded.
+pub fn register_system_classes(machine: &mut Machine) {
+    let classes = [
+        (DIALOG_CLASS, "DefDlgProcA"),
+        ("Button", "ButtonWndProc"),
+        ("Edit", "EditWndProc"),
+        ("Static", "StaticWndProc"),
+        ("ListBox", "ListBoxWndProc"),
+        ("ScrollBar", "ScrollBarWndProc"),
+        ("ComboBox", "ComboBoxWndProc"),
+    ];
+    for (name, wndproc) in classes {
+        // Controls paint their own background; dialogs are filled like buttons.
+        let background = if name == DIALOG_CLASS {
+            super::BrushOrColor::Color(COLOR::BTNFACE).to_brush(machine)
+        } else {
+            HGDIOBJ::null()
+        };
+        let wndclass = WndClass {
+            name: name.to_string(),
+            style: CS::DBLCLKS,
+            wndproc: kernel32::get_symbol(machine, "user32.dll", wndproc),
+            background,
+            menu: None,
+        };
+        register_class(machine, wndclass);
+    }
+}
+
+/// Create a control's state on its first message, given the class-specific style bits.
+/// Returns false if the window doesn't exist.
+fn init_control(machine: &Machine, hwnd: HWND, new: impl FnOnce(u32) -> ControlState) -> bool {
+    let Some(window) = machine.state.user32.windows.get(hwnd) else {
+        return false;
+    };
+    let mut window = window.borrow_mut();
+    if window.control.is_none() {
+        let state = new(window.style.bits() & 0xFFFF);
+        window.control = Some(Control {
+            font: HGDIOBJ::null(),
+            state,
+        });
+    }
+    true
+}
+
+/// The style bits specific to the control's class, e.g. BS_* for buttons.
+fn class_style(machine: &Machine, hwnd: HWND) -> u32 {
+    machine
+        .state
+        .user32
+        .windows
+        .get(hwnd)
+        .unwrap()
+        .borrow()
+        .style
+        .bits()
+        & 0xFFFF
+}
+
+fn window_style(machine: &Machine, hwnd: HWND) -> (WS, WS_EX) {
+    let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+    (window.style, window.ex_style)
+}
+
+fn invalidate(machine: &Machine, hwnd: HWND) {
+    if let Some(window) = machine.state.user32.windows.get(hwnd) {
+        window.borrow_mut().add_dirty(true, None);
+    }
+}
+
+/// The font controls draw with, per WM_SETFONT.
+fn control_font(machine: &Machine, hwnd: HWND) -> gdi32::Font {
+    let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+    let hfont = window.control.as_ref().map_or(HGDIOBJ::null(), |c| c.font);
+    match machine.state.gdi32.objects.get(hfont) {
+        Some(gdi32::Object::Font(font)) => font.clone(),
+        _ => gdi32::Font::new(&gdi32::font::SYSTEM),
+    }
+}
+
+/// Send a WM_COMMAND notification to the control's parent.
+async fn notify_parent(machine: &mut Machine, hwnd: HWND, code: u32) {
+    let (parent, id) = {
+        let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+        (window.parent, window.control_id)
+    };
+    if parent.is_null() {
+        return;
+    }
+    let wparam = (code << 16) | (id & 0xFFFF);
+    SendMessageA(machine, parent, WM::COMMAND as u32, wparam, hwnd.to_raw()).await;
+}
+
+/// Handle the messages all controls treat alike.
+async fn def_control_proc(
+    machine: &mut Machine,
+    hwnd: HWND,
+    msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    match msg {
+        Ok(WM::SETFONT) => {
+            let window = machine.state.user32.windows.get(hwnd).unwrap();
+            if let Some(control) = &mut window.borrow_mut().control {
+                control.font = HGDIOBJ::from_raw(wParam);
+            }
+            if lParam != 0 {
+                invalidate(machine, hwnd);
+            }
+            0
+        }
+        Ok(WM::GETFONT) => {
+            let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+            window.control.as_ref().map_or(0, |c| c.font.to_raw())
+        }
+        Ok(WM::ENABLE) => {
+            invalidate(machine, hwnd);
+            0
+        }
+        msg => def_window_proc(machine, hwnd, msg, wParam, lParam).await,
+    }
+}
+
+/// A DC for drawing a control's whole client area, set up with the control's font
+/// and the colors its parent picks in response to WM_CTLCOLOR*.
+struct Paint {
+    hdc: HDC,
+    rect: RECT,
+    background: COLORREF,
+    enabled: bool,
+}
+
+async fn begin_paint(
+    machine: &mut Machine,
+    hwnd: HWND,
+    ctlcolor: WM,
+    background: COLOR,
+    text: COLOR,
+) -> Paint {
+    let (bitmap, font, parent, rect, enabled) = {
+        let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+        (
+            window.bitmap().clone(),
+            window.control.as_ref().map_or(HGDIOBJ::null(), |c| c.font),
+            window.parent,
+            window.client_rect(),
+            !window.style.contains(WS::DISABLED),
+        )
+    };
+    // Draw directly into the backing store, flushing to the host once at the end.
+    let mut dc = DC::new(DCTarget::Memory(bitmap));
+    dc.font = font;
+    dc.text_color = text.to_colorref();
+    dc.bk_color = background.to_colorref();
+    dc.bk_mode = BkMode::OPAQUE;
+    let hdc = machine.state.gdi32.dcs.add_dc(dc);
+
+    let mut background = background.to_colorref();
+    if !parent.is_null() {
+        let hbrush = SendMessageA(
+            machine,
+            parent,
+            ctlcolor as u32,
+            hdc.to_raw(),
+            hwnd.to_raw(),
+        )
+        .await;
+        if let Some(gdi32::Object::Brush(brush)) =
+            machine.state.gdi32.objects.get(HGDIOBJ::from_raw(hbrush))
+        {
+            if let Some(color) = brush.color {
+                background = color;
+            }
+        }
+    }
+    gdi32::fill_rect(machine, hdc, &rect, background);
+    Paint {
+        hdc,
+        rect,
+        background,
+        enabled,
+    }
+}
+
+fn end_paint(machine: &mut Machine, hwnd: HWND, paint: Paint) {
+    machine.state.gdi32.dcs.remove(paint.hdc);
+    if let Some(window) = machine.state.user32.windows.get(hwnd) {
+        window.borrow_mut().dirty = None;
+        flush_window(machine, hwnd);
+    }
+}
+
+impl Paint {
+    fn fill(&self, machine: &mut Machine, rect: &RECT, color: COLORREF) {
+        gdi32::fill_rect(machine, self.hdc, rect, color);
+    }
+
+    /// A one pixel outline.
+    fn frame(&self, machine: &mut Machine, rect: &RECT, color: COLORREF) {
+        let RECT {
+            left,
+            top,
+            right,
+            bottom,
+        } = *rect;
+        for side in [
+            RECT {
+                left,
+                top,
+                right,
+                bottom: top + 1,
+            },
+            RECT {
+                left,
+                top: bottom - 1,
+                right,
+                bottom,
+            },
+            RECT {
+                left,
+                top,
+                right: left + 1,
+                bottom,
+            },
+            RECT {
+                left: right - 1,
+                top,
+                right,
+                bottom,
+            },
+        ] {
+            self.fill(machine, &side, color);
+        }
+    }
+
+    /// A one pixel bevel: light on the top and left, dark on the bottom and right.
+    fn bevel(&self, machine: &mut Machine, rect: &RECT, light: COLOR, dark: COLOR) {
+        let RECT {
+            left,
+            top,
+            right,
+            bottom,
+        } = *rect;
+        let light = light.to_colorref();
+        let dark = dark.to_colorref();
+        self.fill(
+            machine,
+            &RECT {
+                left,
+                top,
+                right: right - 1,
+                bottom: top + 1,
+            },
+            light,
+        );
+        self.fill(
+            machine,
+            &RECT {
+                left,
+                top,
+                right: left + 1,
+                bottom: bottom - 1,
+            },
+            light,
+        );
+        self.fill(
+            machine,
+            &RECT {
+                left,
+                top: bottom - 1,
+                right,
+                bottom,
+            },
+            dark,
+        );
+        self.fill(
+            machine,
+            &RECT {
+                left: right - 1,
+                top,
+                right,
+                bottom,
+            },
+            dark,
+        );
+    }
+
+    /// A two pixel 3D border, as DrawEdge draws.  Returns the interior.
+    fn edge(&self, machine: &mut Machine, rect: &RECT, edge: Edge) -> RECT {
+        let inner = inset(rect, 1);
+        match edge {
+            Edge::Raised => {
+                self.bevel(machine, rect, COLOR::BTNHIGHLIGHT, COLOR::DKSHADOW3D);
+                self.bevel(machine, &inner, COLOR::LIGHT3D, COLOR::BTNSHADOW);
+            }
+            Edge::Sunken => {
+                self.bevel(machine, rect, COLOR::BTNSHADOW, COLOR::BTNHIGHLIGHT);
+                self.bevel(machine, &inner, COLOR::DKSHADOW3D, COLOR::LIGHT3D);
+            }
+            Edge::Etched => {
+                self.bevel(machine, rect, COLOR::BTNSHADOW, COLOR::BTNHIGHLIGHT);
+                self.bevel(machine, &inner, COLOR::BTNHIGHLIGHT, COLOR::BTNSHADOW);
+            }
+        }
+        inset(rect, 2)
+    }
+
+    /// Draw the control's border, if any, and return the area within it.
+    fn border(&self, machine: &mut Machine, rect: &RECT, style: WS, ex_style: WS_EX) -> RECT {
+        match border_width(style, ex_style) {
+            2 => self.edge(machine, rect, Edge::Sunken),
+            1 => {
+                self.frame(machine, rect, COLOR::WINDOWFRAME.to_colorref());
+                inset(rect, 1)
+            }
+            _ => *rect,
+        }
+    }
+
+    fn text_color(&self, machine: &mut Machine, color: COLORREF) {
+        gdi32::SetTextColor(machine, self.hdc, color);
+    }
+
+    fn bk_color(&self, machine: &mut Machine, color: COLORREF) {
+        gdi32::SetBkColor(machine, self.hdc, color);
+    }
+
+    /// DrawText into a rectangle.
+    fn text(&self, machine: &mut Machine, text: &[char], rect: &RECT, format: u32) {
+        let mut rect = *rect;
+        super::draw_text(machine, self.hdc, text, &mut rect, format);
+    }
+
+    /// The dotted rectangle marking the focused control, around text if given.
+    fn focus_rect(&self, machine: &mut Machine, rect: &RECT, text: &[char]) {
+        let mut rect = *rect;
+        if !text.is_empty() {
+            let font = gdi32::dc_font(machine, self.hdc);
+            let height = font.height() as i32;
+            rect.top = (rect.top + rect.bottom - height) / 2 - 1;
+            rect.bottom = rect.top + height + 2;
+            rect.right = std::cmp::min(rect.right, rect.left + font.text_width(text) as i32 + 2);
+            rect.left -= 1;
+        }
+        let color = COLOR::WINDOWTEXT.to_colorref();
+        let dot = |x: i32, y: i32| RECT {
+            left: x,
+            top: y,
+            right: x + 1,
+            bottom: y + 1,
+        };
+        for x in (rect.left..rect.right).step_by(2) {
+            self.fill(machine, &dot(x, rect.top), color);
+            self.fill(machine, &dot(x, rect.bottom - 1), color);
+        }
+        for y in (rect.top..rect.bottom).step_by(2) {
+            self.fill(machine, &dot(rect.left, y), color);
+            self.fill(machine, &dot(rect.right - 1, y), color);
+        }
+    }
+
+    /// A solid triangle pointing in the given direction, centered in a rectangle.
+    fn arrow(&self, machine: &mut Machine, rect: &RECT, dir: Arrow, color: COLORREF) {
+        let size = std::cmp::max(
+            2,
+            std::cmp::min(rect.right - rect.left, rect.bottom - rect.top) / 4,
+        );
+        let cx = (rect.left + rect.right) / 2;
+        let cy = (rect.top + rect.bottom) / 2;
+        for i in 0..size {
+            let span = match dir {
+                Arrow::Up => RECT {
+                    left: cx - i,
+                    top: cy - size / 2 + i,
+                    right: cx + i + 1,
+                    bottom: cy - size / 2 + i + 1,
+                },
+                Arrow::Down => RECT {
+                    left: cx - i,
+                    top: cy + size / 2 - i,
+                    right: cx + i + 1,
+                    bottom: cy + size / 2 - i + 1,
+                },
+                Arrow::Left => RECT {
+                    left: cx - size / 2 + i,
+                    top: cy - i,
+                    right: cx - size / 2 + i + 1,
+                    bottom: cy + i + 1,
+                },
+                Arrow::Right => RECT {
+                    left: cx + size / 2 - i,
+                    top: cy - i,
+                    right: cx + size / 2 - i + 1,
+                    bottom: cy + i + 1,
+                },
+            };
+            self.fill(machine, &span, color);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Edge {
+    Raised,
+    Sunken,
+    Etched,
+}
+
+#[derive(Clone, Copy)]
+enum Arrow {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The client coordinates of a mouse message.
+fn mouse_pos(lParam: u32) -> POINT {
+    POINT {
+        x: lParam as i16 as i32,
+        y: (lParam >> 16) as i16 as i32,
+    }
+}
+
+fn inset(rect: &RECT, n: i32) -> RECT {
+    RECT {
+        left: rect.left + n,
+        top: rect.top + n,
+        right: rect.right - n,
+        bottom: rect.bottom - n,
+    }
+}
+
+/// The width of the border a control draws within its client area.
+// TODO: these belong in the non-client area.
+fn border_width(style: WS, ex_style: WS_EX) -> i32 {
+    if ex_style.contains(WS_EX::CLIENTEDGE) {
+        2
+    } else if style.contains(WS::BORDER) {
+        1
+    } else {
+        0
+    }
+}
+
+const DLGC_WANTARROWS: u32 = 0x0001;
+const DLGC_HASSETSEL: u32 = 0x0008;
+const DLGC_DEFPUSHBUTTON: u32 = 0x0010;
+const DLGC_UNDEFPUSHBUTTON: u32 = 0x0020;
+const DLGC_RADIOBUTTON: u32 = 0x0040;
+const DLGC_WANTCHARS: u32 = 0x0080;
+const DLGC_STATIC: u32 = 0x0100;
+const DLGC_BUTTON: u32 = 0x2000;
+
+const ODT_LISTBOX: u32 = 2;
+const ODT_COMBOBOX: u32 = 3;
+const ODT_BUTTON: u32 = 4;
+const ODT_STATIC: u32 = 5;
+
+const ODA_DRAWENTIRE: u32 = 1;
+
+const ODS_SELECTED: u32 = 0x0001;
+const ODS_DISABLED: u32 = 0x0004;
+const ODS_CHECKED: u32 = 0x0008;
+const ODS_FOCUS: u32 = 0x0010;
+
+#[repr(C)]
+#[derive(Clone)]
+struct DRAWITEMSTRUCT {
+    CtlType: u32,
+    CtlID: u32,
+    itemID: u32,
+    itemAction: u32,
+    itemState: u32,
+    hwndItem: HWND,
+    hDC: HDC,
+    rcItem: RECT,
+    itemData: u32,
+}
+unsafe impl memory::Pod for DRAWITEMSTRUCT {}
+
+/// Have the parent of an owner-drawn control draw (part of) it via WM_DRAWITEM.
+async fn draw_item(
+    machine: &mut Machine,
+    hwnd: HWND,
+    paint: &Paint,
+    typ: u32,
+    item: u32,
+    state: u32,
+    rect: RECT,
+    data: u32,
+) {
+    let (parent, id) = {
+        let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+        (window.parent, window.control_id)
+    };
+    let addr = machine.state.scratch.alloc(
+        machine.emu.memory.mem(),
+        std::mem::size_of::<DRAWITEMSTRUCT>() as u32,
+    );
+    machine.mem().put_pod::<DRAWITEMSTRUCT>(
+        addr,
+        DRAWITEMSTRUCT {
+            CtlType: typ,
+            CtlID: id,
+            itemID: item,
+            itemAction: ODA_DRAWENTIRE,
+            itemState: state | if paint.enabled { 0 } else { ODS_DISABLED },
+            hwndItem: hwnd,
+            hDC: paint.hdc,
+            rcItem: rect,
+            itemData: data,
+        },
+    );
+    SendMessageA(machine, parent, WM::DRAWITEM as u32, id, addr).await;
+    machine.state.scratch.free(machine.emu.memory.mem(), addr);
+}
+
+/// Copy a string argument of a message, e.g. LB_ADDSTRING's.
+fn read_str(machine: &Machine, addr: u32) -> String {
+    use memory::Extensions;
+    String::from_utf8_lossy(machine.mem().slicez(addr)).into_owned()
+}
+
+/// Copy a string to a message's output buffer, which the caller sized.
+/// Returns the length, not including the nul.
+fn write_str(machine: &Machine, addr: u32, text: &str) -> u32 {
+    let buf = machine.mem().sub32_mut(addr, text.len() as u32 + 1);
+    buf[..text.len()].copy_from_slice(text.as_bytes());
+    buf[text.len()] = 0;
+    text.len() as u32
+}
@@ -0,0 +1,476 @@
+//! The "ScrollBar" class.
+
+use super::{
+    class_style, def_control_proc, init_control, invalidate, mouse_pos, Arrow, ControlState, Edge,
+    Paint, DLGC_WANTARROWS,
+};
+use crate::{
+    winapi::{
+        types::{HWND, RECT},
+        user32::{
+            ReleaseCapture, SendMessageA, SetCapture, SetFocus, COLOR, VK_DOWN, VK_END, VK_HOME,
+            VK_LEFT, VK_NEXT, VK_PRIOR, VK_RIGHT, VK_UP, WM,
+        },
+    },
+    Machine,
+};
+use memory::{Extensions, ExtensionsMut};
+
+pub const SBS_HORZ: u32 = 0x0000;
+pub const SBS_VERT: u32 = 0x0001;
+pub const SBS_SIZEBOX: u32 = 0x0008;
+pub const SBS_SIZEGRIP: u32 = 0x0010;
+
+pub const SBM_SETPOS: u32 = 0x00E0;
+pub const SBM_GETPOS: u32 = 0x00E1;
+pub const SBM_SETRANGE: u32 = 0x00E2;
+pub const SBM_GETRANGE: u32 = 0x00E3;
+pub const SBM_ENABLE_ARROWS: u32 = 0x00E4;
+pub const SBM_SETRANGEREDRAW: u32 = 0x00E6;
+pub const SBM_SETSCROLLINFO: u32 = 0x00E9;
+pub const SBM_GETSCROLLINFO: u32 = 0x00EA;
+
+pub const SB_LINEUP: u32 = 0;
+pub const SB_LINEDOWN: u32 = 1;
+pub const SB_PAGEUP: u32 = 2;
+pub const SB_PAGEDOWN: u32 = 3;
+pub const SB_THUMBPOSITION: u32 = 4;
+pub const SB_THUMBTRACK: u32 = 5;
+pub const SB_TOP: u32 = 6;
+pub const SB_BOTTOM: u32 = 7;
+pub const SB_ENDSCROLL: u32 = 8;
+
+pub const SIF_RANGE: u32 = 0x0001;
+pub const SIF_PAGE: u32 = 0x0002;
+pub const SIF_POS: u32 = 0x0004;
+pub const SIF_TRACKPOS: u32 = 0x0010;
+
+#[repr(C)]
+#[derive(Clone, Debug, Default)]
+pub struct SCROLLINFO {
+    pub cbSize: u32,
+    pub fMask: u32,
+    pub nMin: i32,
+    pub nMax: i32,
+    pub nPage: u32,
+    pub nPos: i32,
+    pub nTrackPos: i32,
+}
+unsafe impl memory::Pod for SCROLLINFO {}
+
+/// The parts of a scroll bar, in order from top or left.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Part {
+    LineUp,
+    PageUp,
+    Thumb,
+    PageDown,
+    LineDown,
+}
+
+impl Part {
+    fn code(self) -> u32 {
+        match self {
+            Part::LineUp => SB_LINEUP,
+            Part::PageUp => SB_PAGEUP,
+            Part::Thumb => SB_THUMBTRACK,
+            Part::PageDown => SB_PAGEDOWN,
+            Part::LineDown => SB_LINEDOWN,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ScrollBar {
+    min: i32,
+    max: i32,
+    page: u32,
+    pos: i32,
+    /// The position the thumb is dragged to.
+    track_pos: i32,
+    /// The part the mouse went down on, while it is held.
+    pressed: Option<Part>,
+    /// Offset of the mouse within the thumb, while dragging it.
+    drag_ofs: i32,
+}
+
+fn with_scrollbar<T>(machine: &Machine, hwnd: HWND, f: impl FnOnce(&mut ScrollBar) -> T) -> T {
+    let mut window = machine.state.user32.windows.get(hwnd).unwrap().borrow_mut();
+    match &mut window.control.as_mut().unwrap().state {
+        ControlState::ScrollBar(sb) => f(sb),
+        _ => unreachable!("not a scroll bar"),
+    }
+}
+
+impl ScrollBar {
+    /// The largest position, given the page size.
+    fn max_pos(&self) -> i32 {
+        std::cmp::max(self.min, self.max - std::cmp::max(self.page as i32 - 1, 0))
+    }
+
+    fn set_pos(&mut self, pos: i32) -> i32 {
+        let prev = self.pos;
+        self.pos = pos.clamp(self.min, self.max_pos());
+        prev
+    }
+}
+
+/// Scroll bar geometry along its axis: positions from the top or left.
+struct Geometry {
+    /// The length of each arrow button.
+    arrow: i32,
+    /// The extent of the whole bar.
+    len: i32,
+    thumb_start: i32,
+    thumb_len: i32,
+}
+
+impl Geometry {
+    fn new(sb: &ScrollBar, len: i32, thickness: i32, pos: i32) -> Self {
+        let arrow = std::cmp::min(thickness, len / 2);
+        let track = len - 2 * arrow;
+        let range = sb.max - sb.min + 1;
+        let thumb_len = if sb.page > 0 && range > 0 {
+            (track * sb.page as i32 / range).clamp(8, track)
+        } else {
+            std::cmp::min(thickness, track)
+        };
+        let span = sb.max_pos() - sb.min;
+        let thumb_start = arrow
+            + if span > 0 {
+                (track - thumb_len) * (pos - sb.min) / span
+            } else {
+                0
+            };
+        Geometry {
+            arrow,
+            len,
+            thumb_start,
+            thumb_len,
+        }
+    }
+
+    fn part_at(&self, at: i32) -> Part {
+        if at < self.arrow {
+            Part::LineUp
+        } else if at >= self.len - self.arrow {
+            Part::LineDown
+        } else if at < self.thumb_start {
+            Part::PageUp
+        } else if at < self.thumb_start + self.thumb_len {
+            Part::Thumb
+        } else {
+            Part::PageDown
+        }
+    }
+
+    /// The position for the thumb to start at a given offset.
+    fn pos_at(&self, sb: &ScrollBar, at: i32) -> i32 {
+        let track = self.len - 2 * self.arrow - self.thumb_len;
+        if track <= 0 {
+            return sb.min;
+        }
+        let span = sb.max_pos() - sb.min;
+        (sb.min + (at - self.arrow) * span / track).clamp(sb.min, sb.max_pos())
+    }
+}
+
+/// The bar's (length, thickness), and whether it's vertical.
+fn dimensions(machine: &Machine, hwnd: HWND) -> (i32, i32, bool) {
+    let vert = class_style(machine, hwnd) & SBS_VERT != 0;
+    let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+    let (w, h) = (window.width as i32, window.height as i32);
+    if vert {
+        (h, w, true)
+    } else {
+        (w, h, false)
+    }
+}
+
+fn geometry(machine: &Machine, hwnd: HWND) -> Geometry {
+    let (len, thickness, _) = dimensions(machine, hwnd);
+    with_scrollbar(machine, hwnd, |sb| {
+        let pos = if sb.pressed == Some(Part::Thumb) {
+            sb.track_pos
+        } else {
+            sb.pos
+        };
+        Geometry::new(sb, len, thickness, pos)
+    })
+}
+
+/// Send WM_HSCROLL/WM_VSCROLL to the parent.
+async fn notify_scroll(machine: &mut Machine, hwnd: HWND, code: u32, pos: i32) {
+    let (_, _, vert) = dimensions(machine, hwnd);
+    let parent = machine
+        .state
+        .user32
+        .windows
+        .get(hwnd)
+        .unwrap()
+        .borrow()
+        .parent;
+    if parent.is_null() {
+        return;
+    }
+    let msg = if vert { WM::VSCROLL } else { WM::HSCROLL };
+    let wparam = ((pos as u32) << 16) | code;
+    SendMessageA(machine, parent, msg as u32, wparam, hwnd.to_raw()).await;
+}
+
+/// The window procedure of the "ScrollBar" class.
+#[win32_derive::dllexport]
+pub async fn ScrollBarWndProc(
+    machine: &mut Machine,
+    hWnd: HWND,
+    msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    if !init_control(machine, hWnd, |_| {
+        ControlState::ScrollBar(ScrollBar::default())
+    }) {
+        return 0;
+    }
+    let style = class_style(machine, hWnd);
+    let redraw = |machine: &Machine, redraw: bool| {
+        if redraw {
+            invalidate(machine, hWnd);
+        }
+    };
+    match msg {
+        Ok(WM::PAINT) => {
+            paint(machine, hWnd).await;
+            0
+        }
+        _ if style & (SBS_SIZEBOX | SBS_SIZEGRIP) != 0 => {
+            def_control_proc(machine, hWnd, msg, wParam, lParam).await
+        }
+        Ok(WM::LBUTTONDOWN | WM::LBUTTONDBLCLK) => {
+            SetFocus(machine, hWnd).await;
+            SetCapture(machine, hWnd);
+            let (_, _, vert) = dimensions(machine, hWnd);
+            let pt = mouse_pos(lParam);
+            let at = if vert { pt.y } else { pt.x };
+            let geometry = geometry(machine, hWnd);
+            let part = geometry.part_at(at);
+            let pos = with_scrollbar(machine, hWnd, |sb| {
+                sb.pressed = Some(part);
+                sb.track_pos = sb.pos;
+                sb.drag_ofs = at - geometry.thumb_start;
+                sb.pos
+            });
+            invalidate(machine, hWnd);
+            notify_scroll(
+                machine,
+                hWnd,
+                part.code(),
+                if part == Part::Thumb { pos } else { 0 },
+            )
+            .await;
+            0
+        }
+        Ok(WM::MOUSEMOVE) => {
+            let pressed = with_scrollbar(machine, hWnd, |sb| sb.pressed);
+            if pressed == Some(Part::Thumb) {
+                let (_, _, vert) = dimensions(machine, hWnd);
+                let pt = mouse_pos(lParam);
+                let at = if vert { pt.y } else { pt.x };
+                let geometry = geometry(machine, hWnd);
+                let (pos, moved) = with_scrollbar(machine, hWnd, |sb| {
+                    let pos = geometry.pos_at(sb, at - sb.drag_ofs);
+                    (pos, std::mem::replace(&mut sb.track_pos, pos) != pos)
+                });
+                if moved {
+                    invalidate(machine, hWnd);
+                    notify_scroll(machine, hWnd, SB_THUMBTRACK, pos).await;
+                }
+            }
+            0
+        }
+        Ok(WM::LBUTTONUP) => {
+            let Some((part, track_pos)) =
+                with_scrollbar(machine, hWnd, |sb| Some((sb.pressed.take()?, sb.track_pos)))
+            else {
+                return 0;
+            };
+            ReleaseCapture(machine);
+            invalidate(machine, hWnd);
+            if part == Part::Thumb {
+                notify_scroll(machine, hWnd, SB_THUMBPOSITION, track_pos).await;
+            }
+            notify_scroll(machine, hWnd, SB_ENDSCROLL, 0).await;
+            0
+        }
+        Ok(WM::KEYDOWN) => {
+            let code = match wParam as u8 {
+                VK_UP | VK_LEFT => SB_LINEUP,
+                VK_DOWN | VK_RIGHT => SB_LINEDOWN,
+                VK_PRIOR => SB_PAGEUP,
+                VK_NEXT => SB_PAGEDOWN,
+                VK_HOME => SB_TOP,
+                VK_END => SB_BOTTOM,
+                _ => return 0,
+            };
+            notify_scroll(machine, hWnd, code, 0).await;
+            notify_scroll(machine, hWnd, SB_ENDSCROLL, 0).await;
+            0
+        }
+        Ok(WM::GETDLGCODE) => DLGC_WANTARROWS,
+        Err(SBM_SETPOS) => {
+            let prev = with_scrollbar(machine, hWnd, |sb| sb.set_pos(wParam as i32));
+            redraw(machine, lParam != 0);
+            prev as u32
+        }
+        Err(SBM_GETPOS) => with_scrollbar(machine, hWnd, |sb| sb.pos as u32),
+        Err(msg @ (SBM_SETRANGE | SBM_SETRANGEREDRAW)) => {
+            let prev = with_scrollbar(machine, hWnd, |sb| {
+                sb.min = wParam as i32;
+                sb.max = lParam as i32;
+                let pos = sb.pos;
+                sb.set_pos(pos)
+            });
+            redraw(machine, msg == SBM_SETRANGEREDRAW);
+            prev as u32
+        }
+        Err(SBM_GETRANGE) => {
+            let (min, max) = with_scrollbar(machine, hWnd, |sb| (sb.min, sb.max));
+            if wParam != 0 {
+                machine.mem().put_pod::<i32>(wParam, min);
+            }
+            if lParam != 0 {
+                machine.mem().put_pod::<i32>(lParam, max);
+            }
+            0
+        }
+        Err(SBM_ENABLE_ARROWS) => 1,
+        Err(SBM_SETSCROLLINFO) => {
+            let info = machine.mem().get_pod::<SCROLLINFO>(lParam);
+            let pos = with_scrollbar(machine, hWnd, |sb| {
+                if info.fMask & SIF_RANGE != 0 {
+                    sb.min = info.nMin;
+                    sb.max = info.nMax;
+                }
+                if info.fMask & SIF_PAGE != 0 {
+                    sb.page = std::cmp::min(info.nPage, (sb.max - sb.min + 1).max(0) as u32);
+                }
+                let pos = if info.fMask & SIF_POS != 0 {
+                    info.nPos
+                } else {
+                    sb.pos
+                };
+                sb.set_pos(pos);
+                sb.pos
+            });
+            redraw(machine, wParam != 0);
+            pos as u32
+        }
+        Err(SBM_GETSCROLLINFO) => {
+            let mut info = machine.mem().get_pod::<SCROLLINFO>(lParam);
+            with_scrollbar(machine, hWnd, |sb| {
+                if info.fMask & SIF_RANGE != 0 {
+                    info.nMin = sb.min;
+                    info.nMax = sb.max;
+                }
+                if info.fMask & SIF_PAGE != 0 {
+                    info.nPage = sb.page;
+                }
+                if info.fMask & SIF_POS != 0 {
+                    info.nPos = sb.pos;
+                }
+                if info.fMask & SIF_TRACKPOS != 0 {
+                    info.nTrackPos = sb.track_pos;
+                }
+            });
+            machine.mem().put_pod::<SCROLLINFO>(lParam, info);
+            1
+        }
+        msg => def_control_proc(machine, hWnd, msg, wParam, lParam).await,
+    }
+}
+
+async fn paint(machine: &mut Machine, hwnd: HWND) {
+    let style = class_style(machine, hwnd);
+    let paint = super::begin_paint(
+        machine,
+        hwnd,
+        WM::CTLCOLORSCROLLBAR,
+        COLOR::SCROLLBAR,
+        COLOR::BTNTEXT,
+    )
+    .await;
+    if style & (SBS_SIZEBOX | SBS_SIZEGRIP) != 0 {
+        paint.fill(machine, &paint.rect, COLOR::BTNFACE.to_colorref());
+        super::end_paint(machine, hwnd, paint);
+        return;
+    }
+
+    let (_, _, vert) = dimensions(machine, hwnd);
+    let geometry = geometry(machine, hwnd);
+    let (pressed, scrollable) = with_scrollbar(machine, hwnd, |sb| {
+        (sb.pressed, paint.enabled && sb.max_pos() > sb.min)
+    });
+    let rect = paint.rect;
+    // Map a span along the bar's axis to a rectangle.
+    let span = |start: i32, len: i32| {
+        if vert {
+            RECT {
+                top: rect.top + start,
+                bottom: rect.top + start + len,
+                ..rect
+            }
+        } else {
+            RECT {
+                left: rect.left + start,
+                right: rect.left + start + len,
+                ..rect
+            }
+        }
+    };
+
+    let arrow_color = if scrollable {
+        COLOR::BTNTEXT
+    } else {
+        COLOR::GRAYTEXT
+    }
+    .to_colorref();
+    let (up, down) = if vert {
+        (Arrow::Up, Arrow::Down)
+    } else {
+        (Arrow::Left, Arrow::Right)
+    };
+    for (part, start, dir) in [
+        (Part::LineUp, 0, up),
+        (Part::LineDown, geometry.len - geometry.arrow, down),
+    ] {
+        let button = span(start, geometry.arrow);
+        paint_button(machine, &paint, &button, pressed == Some(part));
+        paint.arrow(machine, &button, dir, arrow_color);
+    }
+
+    if scrollable {
+        let pressed_page = match pressed {
+            Some(Part::PageUp) => Some(span(geometry.arrow, geometry.thumb_start - geometry.arrow)),
+            Some(Part::PageDown) => {
+                let start = geometry.thumb_start + geometry.thumb_len;
+                Some(span(start, geometry.len - geometry.arrow - start))
+            }
+            _ => None,
+        };
+        if let Some(page) = pressed_page {
+            paint.fill(machine, &page, COLOR::WINDOWFRAME.to_colorref());
+        }
+        let thumb = span(geometry.thumb_start, geometry.thumb_len);
+        paint_button(machine, &paint, &thumb, false);
+    }
+    super::end_paint(machine, hwnd, paint);
+}
+
+fn paint_button(machine: &mut Machine, paint: &Paint, rect: &RECT, pressed: bool) {
+    paint.fill(machine, rect, COLOR::BTNFACE.to_colorref());
+    if pressed {
+        paint.frame(machine, rect, COLOR::BTNSHADOW.to_colorref());
+    } else {
+        paint.edge(machine, rect, Edge::Raised);
+    }
+}
@@ -0,0 +1,226 @@
+//! The "Static" class: labels, images, and decorative rectangles.
+
+use super::{
+    class_style, def_control_proc, draw_item, init_control, invalidate, notify_parent,
+    ControlState, Edge, DLGC_STATIC, ODT_STATIC,
+};
+use crate::{
+    winapi::{
+        gdi32::{self, DCTarget, RasterOp, DC, HGDIOBJ},
+        types::{HWND, RECT},
+        user32::{
+            COLOR, DT_CENTER, DT_EXPANDTABS, DT_NOPREFIX, DT_RIGHT, DT_SINGLELINE, DT_VCENTER,
+            DT_WORDBREAK, WM,
+        },
+    },
+    Machine,
+};
+
+pub const SS_LEFT: u32 = 0x00;
+pub const SS_CENTER: u32 = 0x01;
+pub const SS_RIGHT: u32 = 0x02;
+pub const SS_ICON: u32 = 0x03;
+pub const SS_BLACKRECT: u32 = 0x04;
+pub const SS_GRAYRECT: u32 = 0x05;
+pub const SS_WHITERECT: u32 = 0x06;
+pub const SS_BLACKFRAME: u32 = 0x07;
+pub const SS_GRAYFRAME: u32 = 0x08;
+pub const SS_WHITEFRAME: u32 = 0x09;
+pub const SS_USERITEM: u32 = 0x0A;
+pub const SS_SIMPLE: u32 = 0x0B;
+pub const SS_LEFTNOWORDWRAP: u32 = 0x0C;
+pub const SS_OWNERDRAW: u32 = 0x0D;
+pub const SS_BITMAP: u32 = 0x0E;
+pub const SS_ENHMETAFILE: u32 = 0x0F;
+pub const SS_ETCHEDHORZ: u32 = 0x10;
+pub const SS_ETCHEDVERT: u32 = 0x11;
+pub const SS_ETCHEDFRAME: u32 = 0x12;
+pub const SS_TYPEMASK: u32 = 0x1F;
+pub const SS_NOPREFIX: u32 = 0x80;
+pub const SS_NOTIFY: u32 = 0x100;
+pub const SS_CENTERIMAGE: u32 = 0x200;
+pub const SS_SUNKEN: u32 = 0x1000;
+
+pub const STM_SETICON: u32 = 0x0170;
+pub const STM_GETICON: u32 = 0x0171;
+pub const STM_SETIMAGE: u32 = 0x0172;
+pub const STM_GETIMAGE: u32 = 0x0173;
+
+pub const STN_CLICKED: u32 = 0;
+pub const STN_DBLCLK: u32 = 1;
+
+pub const IMAGE_BITMAP: u32 = 0;
+pub const IMAGE_ICON: u32 = 1;
+
+#[derive(Default)]
+pub struct Static {
+    /// Set by STM_SETIMAGE/STM_SETICON.
+    image: HGDIOBJ,
+}
+
+fn with_static<T>(machine: &Machine, hwnd: HWND, f: impl FnOnce(&mut Static) -> T) -> T {
+    let mut window = machine.state.user32.windows.get(hwnd).unwrap().borrow_mut();
+    match &mut window.control.as_mut().unwrap().state {
+        ControlState::Static(s) => f(s),
+        _ => unreachable!("not a static"),
+    }
+}
+
+fn set_image(machine: &Machine, hwnd: HWND, image: HGDIOBJ) -> HGDIOBJ {
+    let prev = with_static(machine, hwnd, |s| std::mem::replace(&mut s.image, image));
+    invalidate(machine, hwnd);
+    prev
+}
+
+/// The window procedure of the "Static" class.
+#[win32_derive::dllexport]
+pub async fn StaticWndProc(
+    machine: &mut Machine,
+    hWnd: HWND,
+    msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    if !init_control(machine, hWnd, |_| ControlState::Static(Static::default())) {
+        return 0;
+    }
+    let style = class_style(machine, hWnd);
+    match msg {
+        Ok(WM::PAINT) => {
+            paint(machine, hWnd, style).await;
+            0
+        }
+        Ok(WM::LBUTTONDOWN) if style & SS_NOTIFY != 0 => {
+            notify_parent(machine, hWnd, STN_CLICKED).await;
+            0
+        }
+        Ok(WM::LBUTTONDBLCLK) if style & SS_NOTIFY != 0 => {
+            notify_parent(machine, hWnd, STN_DBLCLK).await;
+            0
+        }
+        Ok(WM::GETDLGCODE) => DLGC_STATIC,
+        Err(STM_SETIMAGE) if matches!(wParam, IMAGE_BITMAP | IMAGE_ICON) => {
+            set_image(machine, hWnd, HGDIOBJ::from_raw(lParam)).to_raw()
+        }
+        Err(STM_SETICON) => set_image(machine, hWnd, HGDIOBJ::from_raw(wParam)).to_raw(),
+        Err(STM_GETIMAGE | STM_GETICON) => with_static(machine, hWnd, |s| s.image.to_raw()),
+        msg => def_control_proc(machine, hWnd, msg, wParam, lParam).await,
+    }
+}
+
+async fn paint(machine: &mut Machine, hwnd: HWND, style: u32) {
+    let typ = style & SS_TYPEMASK;
+    let text = machine
+        .state
+        .user32
+        .windows
+        .get(hwnd)
+        .unwrap()
+        .borrow()
+        .text
+        .chars()
+        .collect::<Vec<_>>();
+    let image = with_static(machine, hwnd, |s| s.image);
+    let paint = super::begin_paint(
+        machine,
+        hwnd,
+        WM::CTLCOLORSTATIC,
+        COLOR::BTNFACE,
+        COLOR::WINDOWTEXT,
+    )
+    .await;
+    let mut rect = paint.rect;
+    if style & SS_SUNKEN != 0 {
+        paint.bevel(machine, &rect, COLOR::BTNSHADOW, COLOR::BTNHIGHLIGHT);
+        rect = super::inset(&rect, 1);
+    }
+    if !paint.enabled {
+        paint.text_color(machine, COLOR::GRAYTEXT.to_colorref());
+    }
+    paint.bk_color(machine, paint.background);
+
+    match typ {
+        SS_LEFT | SS_CENTER | SS_RIGHT | SS_SIMPLE | SS_LEFTNOWORDWRAP => {
+            let mut format = match typ {
+                SS_CENTER => DT_CENTER | DT_WORDBREAK | DT_EXPANDTABS,
+                SS_RIGHT => DT_RIGHT | DT_WORDBREAK | DT_EXPANDTABS,
+                SS_SIMPLE => DT_SINGLELINE,
+                SS_LEFTNOWORDWRAP => DT_EXPANDTABS,
+                _ => DT_WORDBREAK | DT_EXPANDTABS,
+            };
+            if style & SS_NOPREFIX != 0 {
+                format |= DT_NOPREFIX;
+            }
+            if style & SS_CENTERIMAGE != 0 {
+                format = (format & !DT_WORDBREAK) | DT_SINGLELINE | DT_VCENTER;
+            }
+            paint.text(machine, &text, &rect, format);
+        }
+        SS_BLACKRECT => paint.fill(machine, &rect, COLOR::WINDOWFRAME.to_colorref()),
+        SS_GRAYRECT => paint.fill(machine, &rect, COLOR::BTNSHADOW.to_colorref()),
+        SS_WHITERECT => paint.fill(machine, &rect, COLOR::WINDOW.to_colorref()),
+        SS_BLACKFRAME => paint.frame(machine, &rect, COLOR::WINDOWFRAME.to_colorref()),
+        SS_GRAYFRAME => paint.frame(machine, &rect, COLOR::BTNSHADOW.to_colorref()),
+        SS_WHITEFRAME => paint.frame(machine, &rect, COLOR::WINDOW.to_colorref()),
+        SS_ETCHEDHORZ => {
+            let line = RECT {
+                bottom: rect.top + 2,
+                ..rect
+            };
+            paint.bevel(machine, &line, COLOR::BTNSHADOW, COLOR::BTNHIGHLIGHT);
+        }
+        SS_ETCHEDVERT => {
+            let line = RECT {
+                right: rect.left + 2,
+                ..rect
+            };
+            paint.bevel(machine, &line, COLOR::BTNSHADOW, COLOR::BTNHIGHLIGHT);
+        }
+        SS_ETCHEDFRAME => {
+            paint.edge(machine, &rect, Edge::Etched);
+        }
+        SS_BITMAP => {
+            if let Some(gdi32::Object::Bitmap(bitmap)) = machine.state.gdi32.objects.get(image) {
+                let bitmap = bitmap.clone();
+                let (width, height) = {
+                    let bitmap = bitmap.borrow();
+                    (bitmap.width as i32, bitmap.height as i32)
+                };
+                let (x, y) = if style & SS_CENTERIMAGE != 0 {
+                    (
+                        (rect.left + rect.right - width) / 2,
+                        (rect.top + rect.bottom - height) / 2,
+                    )
+                } else {
+                    (rect.left, rect.top)
+                };
+                let src = machine
+                    .state
+                    .gdi32
+                    .dcs
+                    .add_dc(DC::new(DCTarget::Memory(bitmap)));
+                gdi32::BitBlt(
+                    machine,
+                    paint.hdc,
+                    x,
+                    y,
+                    width,
+                    height,
+                    src,
+                    0,
+                    0,
+                    RasterOp::SRCCOPY,
+                );
+                machine.state.gdi32.dcs.remove(src);
+            }
+        }
+        SS_ICON => {
+            // TODO: icons aren't loaded as drawable objects yet.
+        }
+        SS_OWNERDRAW => {
+            draw_item(machine, hwnd, &paint, ODT_STATIC, 0, 0, rect, 0).await;
+        }
+        _ => {}
+    }
+    super::end_paint(machine, hwnd, paint);
+}
@@ -1,5 +1,19 @@
+use super::{
+    dlgtemplate::{self, DialogTemplate},
+    is_push_button, load_menu,
+    window::{copy_text, def_window_proc, set_window_text, window_rect},
+    CreateWindowClassName, CreateWindowExW, DestroyWindow, EnableWindow, ShowWindow,
+    TranslateMessage, WindowType, BM_GETCHECK, BM_SETCHECK, CONTROL_CLASSES, DIALOG_CLASS,
+    HINSTANCE, HMENU, IMAGE_BITMAP, MSG, SS_BITMAP, SS_TYPEMASK, STM_SETIMAGE, SW, WM, WS, WS_EX,
+};
 use crate::{
-    winapi::{calling_convention::ArrayWithSizeMut, types::*},
+    pe,
+    winapi::{
+        calling_convention::ArrayWithSizeMut,
+        gdi32::{self, HGDIOBJ},
+        kernel32::{self, ResourceKey},
+        types::*,
+    },
     Machine,
 };
 
@@ -72,82 +86,700 @@ pub fn MessageBoxW(
     1 // IDOK
 }
 
+/// Dialog-specific state of a window created from a dialog template.
+pub struct DialogState {
+    pub dlgproc: u32,
+    /// Set by EndDialog.
+    pub result: Option<i32>,
+    /// DWL_MSGRESULT.
+    pub msg_result: u32,
+    /// DWL_USER.
+    pub user: u32,
+    /// Pixels per 4 horizontal and 8 vertical dialog units, from the dialog font.
+    pub base_units: (i32, i32),
+}
+
+const IDOK: u32 = 1;
+const IDCANCEL: u32 = 2;
+
+const VK_RETURN: u32 = 0x0D;
+const VK_ESCAPE: u32 = 0x1B;
+
+fn find_template(
+    machine: &Machine,
+    hInstance: HINSTANCE,
+    name: ResourceKey<&Str16>,
+) -> Option<DialogTemplate> {
+    let range = kernel32::find_resource(
+        &machine.state.kernel32,
+        machine.mem(),
+        hInstance,
+        ResourceKey::Id(pe::RT::DIALOG as u32),
+        &name,
+    )?;
+    dlgtemplate::parse(machine.mem().slice(range))
+}
+
+fn parse_template(machine: &Machine, addr: u32) -> Option<DialogTemplate> {
+    dlgtemplate::parse(machine.mem().slice(addr..))
+}
+
+/// The font a dialog template asks for, or the system font if none.
+fn template_font(template: &DialogTemplate) -> gdi32::Font {
+    match &template.font {
+        Some(font) => {
+            let face = gdi32::font::find_face(&font.name, gdi32::font::ANSI_CHARSET, 0);
+            let height = -(font.point_size as i32 * 96 / 72);
+            gdi32::Font::new(face).with_height(height)
+        }
+        None => gdi32::Font::new(&gdi32::font::SYSTEM),
+    }
+}
+
+/// Dialog templates are measured in dialog units, which derive from the dialog font's
+/// average character width and height.
+fn dialog_base_units(font: &gdi32::Font) -> (i32, i32) {
+    (font.char_width('x') as i32, font.height() as i32)
+}
+
+/// Create a dialog window and its controls, and deliver WM_INITDIALOG.
+async fn create_dialog(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    template: Option<DialogTemplate>,
+    hWndParent: HWND,
+    lpDialogFunc: u32,
+    dwInitParam: u32,
+) -> HWND {
+    let Some(template) = template else {
+        log::error!("failed to load dialog template");
+        return HWND::null();
+    };
+    let font = template_font(&template);
+    let (base_x, base_y) = dialog_base_units(&font);
+    let to_x = |x: i16| (x as i32 * base_x / 4) as u32;
+    let to_y = |y: i16| (y as i32 * base_y / 8) as u32;
+
+    let style = WS::from_bits_retain(template.style);
+    // The template size is the client size.
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: to_x(template.cx) as i32,
+        bottom: to_y(template.cy) as i32,
+    };
+    let ex_style = WS_EX::from_bits_truncate(template.ex_style);
+    window_rect(&mut rect, style, ex_style, template.menu.is_some());
+
+    let class_name = String16::from(match &template.class {
+        Some(ResourceKey::Name(name)) => name.as_str(),
+        _ => DIALOG_CLASS,
+    });
+    let title = String16::from(template.title.as_str());
+    let menu = match &template.menu {
+        Some(name) => {
+            let name = name.map_name(|name| String16::from(name));
+            load_menu(machine, hInstance, name.as_ref())
+        }
+        None => HMENU::null(),
+    };
+    let hdlg = CreateWindowExW(
+        machine,
+        Ok(ex_style),
+        CreateWindowClassName::Name(class_name.as_str16()),
+        Some(title.as_str16()),
+        Ok(style - WS::VISIBLE),
+        to_x(template.x),
+        to_y(template.y),
+        (rect.right - rect.left) as u32,
+        (rect.bottom - rect.top) as u32,
+        hWndParent,
+        menu.to_raw(),
+        hInstance,
+        0,
+    )
+    .await;
+    if hdlg.is_null() {
+        return hdlg;
+    }
+    machine
+        .state
+        .user32
+        .windows
+        .get(hdlg)
+        .unwrap()
+        .borrow_mut()
+        .dialog = Some(DialogState {
+        dlgproc: lpDialogFunc,
+        result: None,
+        msg_result: 0,
+        user: 0,
+        base_units: (base_x, base_y),
+    });
+
+    // Controls draw in the template font, if any.
+    let hfont = match &template.font {
+        Some(_) => machine.state.gdi32.objects.add(gdi32::Object::Font(font)),
+        None => HGDIOBJ::null(),
+    };
+
+    let mut focus = HWND::null();
+    for item in &template.items {
+        let class_name = match &item.class {
+            ResourceKey::Name(name) => String16::from(name.as_str()),
+            ResourceKey::Id(id) => match CONTROL_CLASSES.get((*id as usize).wrapping_sub(0x80)) {
+                Some(name) => String16::from(name),
+                None => {
+                    log::warn!("dialog item has unknown class {id:#x}");
+                    continue;
+                }
+            },
+        };
+        let title = String16::from(match &item.title {
+            ResourceKey::Name(title) => title.as_str(),
+            ResourceKey::Id(_) => "",
+        });
+        let style = WS::from_bits_retain(item.style) | WS::CHILD;
+        let hwnd = CreateWindowExW(
+            machine,
+            Ok(WS_EX::from_bits_truncate(item.ex_style)),
+            CreateWindowClassName::Name(class_name.as_str16()),
+            Some(title.as_str16()),
+            Ok(style),
+            to_x(item.x),
+            to_y(item.y),
+            to_x(item.cx),
+            to_y(item.cy),
+            hdlg,
+            item.id,
+            hInstance,
+            0, // TODO: creation data
+        )
+        .await;
+        if hwnd.is_null() {
+            continue;
+        }
+        if !hfont.is_null() {
+            super::SendMessageA(machine, hwnd, WM::SETFONT as u32, hfont.to_raw(), 0).await;
+        }
+        // Ordinal titles name a resource to show.
+        // TODO: icons, for SS_ICON.
+        if let &ResourceKey::Id(id) = &item.title {
+            if item.style & SS_TYPEMASK == SS_BITMAP {
+                let name = ResourceKey::Id(id);
+                if let Some(hbmp) = super::load_bitmap(machine, hInstance, name) {
+                    super::SendMessageA(machine, hwnd, STM_SETIMAGE, IMAGE_BITMAP, hbmp.to_raw())
+                        .await;
+                }
+            }
+        }
+        if focus.is_null() && style.contains(WS::TABSTOP) {
+            focus = hwnd;
+        }
+    }
+
+    let msg = MSG {
+        hwnd: hdlg,
+        message: WM::INITDIALOG as u32,
+        wParam: focus.to_raw(),
+        lParam: dwInitParam,
+        time: 0,
+        pt_x: 0,
+        pt_y: 0,
+    };
+    // A nonzero return means the dialog manager should set the focus.
+    if super::dispatch_message(machine, &msg).await != 0 && !focus.is_null() {
+        super::SetFocus(machine, focus).await;
+    }
+
+    if style.contains(WS::VISIBLE) {
+        ShowWindow(machine, hdlg, Ok(SW::SHOW)).await;
+    }
+    hdlg
+}
+
+/// Run a nested message loop until EndDialog is called, then destroy the dialog.
+async fn modal_loop(machine: &mut Machine, hdlg: HWND, hWndParent: HWND) -> i32 {
+    if hdlg.is_null() {
+        return -1;
+    }
+    if !hWndParent.is_null() {
+        EnableWindow(machine, hWndParent, false);
+    }
+
+    // Modal dialogs are shown even if the template lacks WS_VISIBLE,
+    // unless WM_INITDIALOG already ended them.
+    let ended = match machine.state.user32.windows.get(hdlg) {
+        Some(window) => window.borrow().dialog.as_ref().unwrap().result.is_some(),
+        None => true,
+    };
+    if !ended {
+        ShowWindow(machine, hdlg, Ok(SW::SHOW)).await;
+    }
+
+    let result = loop {
+        let Some(window) = machine.state.user32.windows.get(hdlg) else {
+            break 0; // destroyed without EndDialog
+        };
+        if let Some(result) = window.borrow().dialog.as_ref().unwrap().result {
+            break result;
+        }
+
+        let mut msg = MSG {
+            hwnd: HWND::null(),
+            message: 0,
+            wParam: 0,
+            lParam: 0,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        };
+        if super::get_message(machine, Some(&mut msg), HWND::null(), 0, 0).await == 0 {
+            // Leave WM_QUIT for the application's main loop.
+            super::PostQuitMessage(machine, msg.wParam as i32);
+            break 0;
+        }
+        if !is_dialog_message(machine, hdlg, &msg).await {
+            TranslateMessage(machine, Some(&msg));
+            if !msg.hwnd.is_null() {
+                super::dispatch_message(machine, &msg).await;
+            }
+        }
+    };
+
+    if !hWndParent.is_null() {
+        EnableWindow(machine, hWndParent, true);
+    }
+    DestroyWindow(machine, hdlg).await;
+    result
+}
+
+fn is_dialog_child(machine: &Machine, hDlg: HWND, mut hwnd: HWND) -> bool {
+    while !hwnd.is_null() {
+        if hwnd == hDlg {
+            return true;
+        }
+        let Some(window) = machine.state.user32.windows.get(hwnd) else {
+            return false;
+        };
+        let window = window.borrow();
+        if !matches!(window.typ, WindowType::Child) {
+            return false;
+        }
+        hwnd = window.parent;
+    }
+    false
+}
+
+/// Handle keyboard navigation for messages destined for the dialog,
+/// otherwise dispatching them as usual.
+async fn is_dialog_message(machine: &mut Machine, hDlg: HWND, msg: &MSG) -> bool {
+    if !is_dialog_child(machine, hDlg, msg.hwnd) {
+        return false;
+    }
+    // TODO: tab navigation, mnemonics, and DM_GETDEFID for the default button.
+    let command = match WM::try_from(msg.message) {
+        Ok(WM::KEYDOWN) if msg.wParam == VK_RETURN => {
+            // Enter presses the focused push button, otherwise the default.
+            let focus = machine.state.user32.focus;
+            let focused = machine.state.user32.windows.get(focus).map(|w| w.borrow());
+            Some(match focused {
+                Some(window) if window.parent == hDlg && is_push_button(&window) => {
+                    window.control_id
+                }
+                _ => IDOK,
+            })
+        }
+        Ok(WM::KEYDOWN) if msg.wParam == VK_ESCAPE => Some(IDCANCEL),
+        Ok(WM::CHAR) if msg.wParam == VK_RETURN || msg.wParam == VK_ESCAPE => return true,
+        _ => None,
+    };
+    let msg = match command {
+        Some(id) => MSG {
+            hwnd: hDlg,
+            message: WM::COMMAND as u32,
+            wParam: id,
+            lParam: GetDlgItem(machine, hDlg, id as i32).to_raw(),
+            ..msg.clone()
+        },
+        None => {
+            TranslateMessage(machine, Some(msg));
+            msg.clone()
+        }
+    };
+    super::dispatch_message(machine, &msg).await;
+    true
+}
+
 #[win32_derive::dllexport]
-pub fn DialogBoxParamA(
-    _machine: &mut Machine,
-    hInstance: u32,
-    lpTemplateName: u32,
+pub async fn IsDialogMessageA(machine: &mut Machine, hDlg: HWND, lpMsg: Option<&MSG>) -> bool {
+    let msg = lpMsg.unwrap().clone();
+    is_dialog_message(machine, hDlg, &msg).await
+}
+
+#[win32_derive::dllexport]
+pub async fn IsDialogMessageW(machine: &mut Machine, hDlg: HWND, lpMsg: Option<&MSG>) -> bool {
+    IsDialogMessageA(machine, hDlg, lpMsg).await
+}
+
+#[win32_derive::dllexport]
+pub async fn DialogBoxParamA(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpTemplateName: ResourceKey<&str>,
     hWndParent: HWND,
     lpDialogFunc: u32,
     dwInitParam: u32,
-) -> u32 {
-    log::warn!("TODO: not showing dialog");
-    // TODO: this should run a nested message loop that will call back into lpDialogFunc,
-    // which then will call EndDialog to end the nested message loop and return the value
-    // passed to EndDialog.
-    // Unfortunately we don't know what value to return here otherwise; it's application specific.
-    0
+) -> i32 {
+    let template = find_template(machine, hInstance, lpTemplateName.to_string16().as_ref());
+    let hdlg = create_dialog(
+        machine,
+        hInstance,
+        template,
+        hWndParent,
+        lpDialogFunc,
+        dwInitParam,
+    )
+    .await;
+    modal_loop(machine, hdlg, hWndParent).await
 }
 
 #[win32_derive::dllexport]
-pub fn DialogBoxParamW(
-    _machine: &mut Machine,
-    hInstance: u32,
-    lpTemplateName: u32,
+pub async fn DialogBoxParamW(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpTemplateName: ResourceKey<&Str16>,
     hWndParent: HWND,
     lpDialogFunc: u32,
     dwInitParam: u32,
-) -> u32 {
-    todo!();
+) -> i32 {
+    let template = find_template(machine, hInstance, lpTemplateName);
+    let hdlg = create_dialog(
+        machine,
+        hInstance,
+        template,
+        hWndParent,
+        lpDialogFunc,
+        dwInitParam,
+    )
+    .await;
+    modal_loop(machine, hdlg, hWndParent).await
 }
 
 #[win32_derive::dllexport]
-pub fn DialogBoxIndirectParamA(
-    _machine: &mut Machine,
-    hInstance: u32,
+pub async fn DialogBoxIndirectParamA(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
     hDialogTemplate: u32,
     hWndParent: HWND,
     lpDialogFunc: u32,
     dwInitParam: u32,
 ) -> i32 {
-    log::warn!("TODO: not showing dialog");
-    -1 // error
+    let template = parse_template(machine, hDialogTemplate);
+    let hdlg = create_dialog(
+        machine,
+        hInstance,
+        template,
+        hWndParent,
+        lpDialogFunc,
+        dwInitParam,
+    )
+    .await;
+    modal_loop(machine, hdlg, hWndParent).await
+}
+
+#[win32_derive::dllexport]
+pub async fn DialogBoxIndirectParamW(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    hDialogTemplate: u32,
+    hWndParent: HWND,
+    lpDialogFunc: u32,
+    dwInitParam: u32,
+) -> i32 {
+    DialogBoxIndirectParamA(
+        machine,
+        hInstance,
+        hDialogTemplate,
+        hWndParent,
+        lpDialogFunc,
+        dwInitParam,
+    )
+    .await
+}
+
+#[win32_derive::dllexport]
+pub async fn CreateDialogParamA(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpTemplateName: ResourceKey<&str>,
+    hWndParent: HWND,
+    lpDialogFunc: u32,
+    dwInitParam: u32,
+) -> HWND {
+    let template = find_template(machine, hInstance, lpTemplateName.to_string16().as_ref());
+    create_dialog(
+        machine,
+        hInstance,
+        template,
+        hWndParent,
+        lpDialogFunc,
+        dwInitParam,
+    )
+    .await
+}
+
+#[win32_derive::dllexport]
+pub async fn CreateDialogParamW(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpTemplateName: ResourceKey<&Str16>,
+    hWndParent: HWND,
+    lpDialogFunc: u32,
+    dwInitParam: u32,
+) -> HWND {
+    let template = find_template(machine, hInstance, lpTemplateName);
+    create_dialog(
+        machine,
+        hInstance,
+        template,
+        hWndParent,
+        lpDialogFunc,
+        dwInitParam,
+    )
+    .await
+}
+
+#[win32_derive::dllexport]
+pub async fn CreateDialogIndirectParamA(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpTemplate: u32,
+    hWndParent: HWND,
+    lpDialogFunc: u32,
+    dwInitParam: u32,
+) -> HWND {
+    let template = parse_template(machine, lpTemplate);
+    create_dialog(
+        machine,
+        hInstance,
+        template,
+        hWndParent,
+        lpDialogFunc,
+        dwInitParam,
+    )
+    .await
+}
+
+#[win32_derive::dllexport]
+pub async fn CreateDialogIndirectParamW(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpTemplate: u32,
+    hWndParent: HWND,
+    lpDialogFunc: u32,
+    dwInitParam: u32,
+) -> HWND {
+    CreateDialogIndirectParamA(
+        machine,
+        hInstance,
+        lpTemplate,
+        hWndParent,
+        lpDialogFunc,
+        dwInitParam,
+    )
+    .await
+}
+
+/// The window procedure of the dialog class, which calls the application's DLGPROC.
+async fn def_dlg_proc(
+    machine: &mut Machine,
+    hDlg: HWND,
+    Msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    let dlgproc = machine
+        .state
+        .user32
+        .windows
+        .get(hDlg)
+        .and_then(|w| Some(w.borrow().dialog.as_ref()?.dlgproc));
+    if let Some(dlgproc) = dlgproc.filter(|&p| p != 0) {
+        set_msg_result(machine, hDlg, 0);
+        let handled = machine
+            .call_x86(dlgproc, vec![hDlg.to_raw(), Msg, wParam, lParam])
+            .await;
+        if handled != 0 {
+            // Most messages return their result via DWL_MSGRESULT, but a few
+            // return the DLGPROC's return value directly.
+            const WM_CTLCOLORMSGBOX: u32 = 0x0132;
+            const WM_CTLCOLORSTATIC: u32 = 0x0138;
+            const WM_COMPAREITEM: u32 = 0x0039;
+            const WM_VKEYTOITEM: u32 = 0x002E;
+            const WM_CHARTOITEM: u32 = 0x002F;
+            const WM_QUERYDRAGICON: u32 = 0x0037;
+            return match Msg {
+                WM_CTLCOLORMSGBOX..=WM_CTLCOLORSTATIC
+                | WM_COMPAREITEM
+                | WM_VKEYTOITEM
+                | WM_CHARTOITEM
+                | WM_QUERYDRAGICON => handled,
+                _ if Msg == WM::INITDIALOG as u32 => handled,
+                _ => get_msg_result(machine, hDlg),
+            };
+        }
+    }
+
+    match WM::try_from(Msg) {
+        Ok(WM::CLOSE) => {
+            let cancel = GetDlgItem(machine, hDlg, IDCANCEL as i32);
+            super::PostMessageA(machine, hDlg, WM::COMMAND as u32, IDCANCEL, cancel.to_raw());
+            0
+        }
+        msg => def_window_proc(machine, hDlg, msg, wParam, lParam).await,
+    }
+}
+
+fn set_msg_result(machine: &mut Machine, hDlg: HWND, result: u32) {
+    if let Some(window) = machine.state.user32.windows.get(hDlg) {
+        if let Some(dialog) = &mut window.borrow_mut().dialog {
+            dialog.msg_result = result;
+        }
+    }
+}
+
+fn get_msg_result(machine: &Machine, hDlg: HWND) -> u32 {
+    machine
+        .state
+        .user32
+        .windows
+        .get(hDlg)
+        .and_then(|w| Some(w.borrow().dialog.as_ref()?.msg_result))
+        .unwrap_or(0)
+}
+
+#[win32_derive::dllexport]
+pub async fn DefDlgProcA(
+    machine: &mut Machine,
+    hDlg: HWND,
+    Msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    def_dlg_proc(machine, hDlg, Msg, wParam, lParam).await
+}
+
+#[win32_derive::dllexport]
+pub async fn DefDlgProcW(
+    machine: &mut Machine,
+    hDlg: HWND,
+    Msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    def_dlg_proc(machine, hDlg, Msg, wParam, lParam).await
+}
+
+#[win32_derive::dllexport]
+pub fn EndDialog(machine: &mut Machine, hDlg: HWND, nResult: i32) -> bool {
+    let Some(window) = machine.state.user32.windows.get(hDlg) else {
+        return false;
+    };
+    match &mut window.borrow_mut().dialog {
+        Some(dialog) => {
+            dialog.result = Some(nResult);
+            true
+        }
+        None => false,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetDlgItem(machine: &mut Machine, hDlg: HWND, nIDDlgItem: i32) -> HWND {
+    machine
+        .state
+        .user32
+        .windows
+        .iter()
+        .find(|(_, w)| {
+            let w = w.borrow();
+            w.parent == hDlg && w.control_id == nIDDlgItem as u32
+        })
+        .map(|(hwnd, _)| hwnd)
+        .unwrap_or(HWND::null())
+}
+
+#[win32_derive::dllexport]
+pub fn GetDlgCtrlID(machine: &mut Machine, hWnd: HWND) -> i32 {
+    match machine.state.user32.windows.get(hWnd) {
+        Some(window) => window.borrow().control_id as i32,
+        None => 0,
+    }
 }
 
 #[win32_derive::dllexport]
 pub fn SetDlgItemTextA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hDlg: HWND,
     nIDDlgItem: i32,
     lpString: Option<&str>,
 ) -> bool {
-    todo!();
+    let hwnd = GetDlgItem(machine, hDlg, nIDDlgItem);
+    set_window_text(machine, hwnd, lpString.unwrap_or_default().to_string())
 }
 
 #[win32_derive::dllexport]
 pub fn SetDlgItemTextW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hDlg: HWND,
     nIDDlgItem: i32,
     lpString: Option<&Str16>,
 ) -> bool {
-    todo!();
+    let hwnd = GetDlgItem(machine, hDlg, nIDDlgItem);
+    let text = lpString.map(|s| s.to_string()).unwrap_or_default();
+    set_window_text(machine, hwnd, text)
 }
 
 #[win32_derive::dllexport]
 pub fn SetDlgItemInt(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hDlg: HWND,
     nIDDlgItem: i32,
     uValue: u32,
-    _bSigned: bool,
+    bSigned: bool,
 ) -> bool {
-    todo!();
+    let hwnd = GetDlgItem(machine, hDlg, nIDDlgItem);
+    let text = if bSigned {
+        (uValue as i32).to_string()
+    } else {
+        uValue.to_string()
+    };
+    set_window_text(machine, hwnd, text)
+}
+
+fn dlg_item_text(machine: &mut Machine, hDlg: HWND, nIDDlgItem: i32) -> Option<String> {
+    let hwnd = GetDlgItem(machine, hDlg, nIDDlgItem);
+    let text = machine
+        .state
+        .user32
+        .windows
+        .get(hwnd)?
+        .borrow()
+        .text
+        .clone();
+    Some(text)
 }
 
 #[win32_derive::dllexport]
-pub fn GetDlgItem(machine: &mut Machine, hDlg: HWND, nIDDlgItem: i32) -> HWND {
-    todo!();
+pub fn GetDlgItemTextA(
+    machine: &mut Machine,
+    hDlg: HWND,
+    nIDDlgItem: i32,
+    lpString: ArrayWithSizeMut<u8>,
+) -> u32 {
+    let text = dlg_item_text(machine, hDlg, nIDDlgItem).unwrap_or_default();
+    copy_text(&text, lpString.unwrap())
 }
 
 #[win32_derive::dllexport]
@@ -157,42 +789,77 @@ pub fn GetDlgItemTextW(
     nIDDlgItem: i32,
     lpString: ArrayWithSizeMut<u16>,
 ) -> u32 {
-    todo!();
+    let text = dlg_item_text(machine, hDlg, nIDDlgItem).unwrap_or_default();
+    copy_text(&text, lpString.unwrap())
 }
 
 #[win32_derive::dllexport]
 pub fn GetDlgItemInt(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hDlg: HWND,
     nIDDlgItem: i32,
     lpTranslated: Option<&mut u32>,
     bSigned: bool,
 ) -> u32 {
-    todo!();
+    let text = dlg_item_text(machine, hDlg, nIDDlgItem).unwrap_or_default();
+    let text = text.trim();
+    let value = if bSigned {
+        text.parse::<i32>().ok().map(|v| v as u32)
+    } else {
+        text.parse::<u32>().ok()
+    };
+    if let Some(translated) = lpTranslated {
+        *translated = value.is_some() as u32;
+    }
+    value.unwrap_or(0)
 }
 
 #[win32_derive::dllexport]
-pub fn CheckRadioButton(
-    _machine: &mut Machine,
+pub async fn CheckRadioButton(
+    machine: &mut Machine,
     hDlg: HWND,
     nIDFirstButton: i32,
     nIDLastButton: i32,
     nIDCheckButton: i32,
 ) -> bool {
-    todo!();
+    for id in nIDFirstButton..=nIDLastButton {
+        let check = (id == nIDCheckButton) as u32;
+        super::SendDlgItemMessageA(machine, hDlg, id, BM_SETCHECK, check, 0).await;
+    }
+    true
 }
 
 #[win32_derive::dllexport]
-pub fn CheckDlgButton(_machine: &mut Machine, hDlg: HWND, nIDButton: i32, uCheck: u32) -> bool {
-    todo!();
+pub async fn CheckDlgButton(
+    machine: &mut Machine,
+    hDlg: HWND,
+    nIDButton: i32,
+    uCheck: u32,
+) -> bool {
+    super::SendDlgItemMessageA(machine, hDlg, nIDButton, BM_SETCHECK, uCheck, 0).await;
+    true
 }
 
 #[win32_derive::dllexport]
-pub fn IsDlgButtonChecked(_machine: &mut Machine, hDlg: HWND, nIDButton: i32) -> u32 {
-    todo!();
+pub async fn IsDlgButtonChecked(machine: &mut Machine, hDlg: HWND, nIDButton: i32) -> u32 {
+    super::SendDlgItemMessageA(machine, hDlg, nIDButton, BM_GETCHECK, 0, 0).await
 }
 
 #[win32_derive::dllexport]
-pub fn EndDialog(_machine: &mut Machine, hDlg: HWND, nResult: Option<&mut u32>) -> bool {
-    todo!();
+pub fn MapDialogRect(machine: &mut Machine, hDlg: HWND, lpRect: Option<&mut RECT>) -> bool {
+    let Some((base_x, base_y)) = machine
+        .state
+        .user32
+        .windows
+        .get(hDlg)
+        .and_then(|w| Some(w.borrow().dialog.as_ref()?.base_units))
+    else {
+        return false;
+    };
+    let rect = lpRect.unwrap();
+    rect.left = rect.left * base_x / 4;
+    rect.right = rect.right * base_x / 4;
+    rect.top = rect.top * base_y / 8;
+    rect.bottom = rect.bottom * base_y / 8;
+    true
 }
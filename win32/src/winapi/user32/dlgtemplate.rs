@@ -0,0 +1,305 @@
+//! Parsing of DLGTEMPLATE/DLGTEMPLATEEX, as found in RT_DIALOG resources.
+//! https://learn.microsoft.com/en-us/windows/win32/dlgbox/dlgtemplateex
+
+use super::ResourceKey;
+use memory::Extensions;
+
+/// DS_SETFONT dialog style: the template includes a font.
+pub const DS_SETFONT: u32 = 0x40;
+/// DS_SHELLFONT is DS_SETFONT | DS_FIXEDSYS.
+const DS_FIXEDSYS: u32 = 0x0008;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Font {
+    pub point_size: u16,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct DialogTemplate {
+    pub style: u32,
+    pub ex_style: u32,
+    /// Position and size, in dialog units.
+    pub x: i16,
+    pub y: i16,
+    pub cx: i16,
+    pub cy: i16,
+    pub menu: Option<ResourceKey<String>>,
+    pub class: Option<ResourceKey<String>>,
+    pub title: String,
+    pub font: Option<Font>,
+    pub items: Vec<DialogItem>,
+}
+
+#[derive(Debug)]
+pub struct DialogItem {
+    pub style: u32,
+    pub ex_style: u32,
+    /// Position and size, in dialog units.
+    pub x: i16,
+    pub y: i16,
+    pub cx: i16,
+    pub cy: i16,
+    pub id: u32,
+    /// Ordinals refer to the predefined control classes.
+    pub class: ResourceKey<String>,
+    /// Ordinals refer to resources, e.g. the icon of a static control.
+    pub title: ResourceKey<String>,
+}
+
+/// Cursor over template bytes.  Offsets are relative to the template start,
+/// which is DWORD-aligned in memory.
+/// Also used for menu templates, which share the string encoding.
+pub(super) struct Reader<'a> {
+    pub buf: &'a [u8],
+    pub ofs: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn read<T: memory::Pod + Copy>(&mut self) -> Option<T> {
+        let size = std::mem::size_of::<T>();
+        if self.ofs + size > self.buf.len() {
+            return None;
+        }
+        let val = self.buf.get_pod::<T>(self.ofs as u32);
+        self.ofs += size;
+        Some(val)
+    }
+
+    pub fn align(&mut self, n: usize) {
+        self.ofs = (self.ofs + n - 1) & !(n - 1);
+    }
+
+    /// A nul-terminated UTF-16 string.
+    pub fn sz(&mut self) -> Option<String> {
+        let mut chars = Vec::new();
+        loop {
+            match self.read::<u16>()? {
+                0 => break,
+                c => chars.push(c),
+            }
+        }
+        Some(String::from_utf16_lossy(&chars))
+    }
+
+    /// The "sz_Or_Ord" type: empty, an ordinal, or a string.
+    fn sz_or_ord(&mut self) -> Option<Option<ResourceKey<String>>> {
+        Some(match self.read::<u16>()? {
+            0 => None,
+            0xFFFF => Some(ResourceKey::Id(self.read::<u16>()? as u32)),
+            _ => {
+                self.ofs -= 2;
+                Some(ResourceKey::Name(self.sz()?))
+            }
+        })
+    }
+
+    fn rect(&mut self) -> Option<[i16; 4]> {
+        Some([self.read()?, self.read()?, self.read()?, self.read()?])
+    }
+}
+
+pub fn parse(buf: &[u8]) -> Option<DialogTemplate> {
+    let mut r = Reader { buf, ofs: 0 };
+    let extended = buf.get(2..4)? == [0xFF, 0xFF];
+
+    let (style, ex_style);
+    if extended {
+        let _version = r.read::<u16>()?;
+        let _signature = r.read::<u16>()?;
+        let _help_id = r.read::<u32>()?;
+        ex_style = r.read::<u32>()?;
+        style = r.read::<u32>()?;
+    } else {
+        style = r.read::<u32>()?;
+        ex_style = r.read::<u32>()?;
+    }
+    let count = r.read::<u16>()?;
+    let [x, y, cx, cy] = r.rect()?;
+    let menu = r.sz_or_ord()?;
+    let class = r.sz_or_ord()?;
+    let title = match r.sz_or_ord()? {
+        Some(ResourceKey::Name(title)) => title,
+        _ => String::new(),
+    };
+
+    let font = if style & (DS_SETFONT | DS_FIXEDSYS) != 0 {
+        let point_size = r.read::<u16>()?;
+        if extended {
+            let _weight = r.read::<u16>()?;
+            let _italic = r.read::<u8>()?;
+            let _charset = r.read::<u8>()?;
+        }
+        let name = r.sz()?;
+        Some(Font { point_size, name })
+    } else {
+        None
+    };
+
+    let mut items = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        r.align(4);
+        let (style, ex_style);
+        if extended {
+            let _help_id = r.read::<u32>()?;
+            ex_style = r.read::<u32>()?;
+            style = r.read::<u32>()?;
+        } else {
+            style = r.read::<u32>()?;
+            ex_style = r.read::<u32>()?;
+        }
+        let [x, y, cx, cy] = r.rect()?;
+        let id = if extended {
+            r.read::<u32>()?
+        } else {
+            r.read::<u16>()? as u32
+        };
+        let class = r.sz_or_ord()??;
+        let title = r
+            .sz_or_ord()?
+            .unwrap_or_else(|| ResourceKey::Name(String::new()));
+        // Skip the creation data, which would go to WM_CREATE via CREATESTRUCT.
+        let extra_len = r.read::<u16>()? as usize;
+        r.ofs += extra_len;
+        items.push(DialogItem {
+            style,
+            ex_style,
+            x,
+            y,
+            cx,
+            cy,
+            id,
+            class,
+            title,
+        });
+    }
+
+    Some(DialogTemplate {
+        style,
+        ex_style,
+        x,
+        y,
+        cx,
+        cy,
+        menu,
+        class,
+        title,
+        font,
+        items,
+    })
+}
+
+/// Builders for the templates parsed here and in menutemplate, for tests.
+#[cfg(test)]
+pub mod test_util {
+    /// Append a NUL-terminated UTF-16 string.
+    pub fn push_str(buf: &mut Vec<u8>, s: &str) {
+        for c in s.encode_utf16().chain(std::iter::once(0)) {
+            buf.extend_from_slice(&c.to_le_bytes());
+        }
+    }
+
+    pub fn push_u16s(buf: &mut Vec<u8>, vals: &[u16]) {
+        for v in vals {
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    pub fn push_u32s(buf: &mut Vec<u8>, vals: &[u32]) {
+        for v in vals {
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    /// Pad to a DWORD boundary.
+    pub fn align(buf: &mut Vec<u8>) {
+        buf.resize(buf.len().next_multiple_of(4), 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::*;
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(0x10C0_0000u32 | DS_SETFONT).to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        push_u16s(&mut buf, &[2, 10, 20, 200, 100]);
+        push_u16s(&mut buf, &[0, 0]); // no menu, default class
+        push_str(&mut buf, "Title");
+        push_u16s(&mut buf, &[8]);
+        push_str(&mut buf, "MS Sans Serif");
+
+        align(&mut buf);
+        buf.extend_from_slice(&0x5001_0001u32.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        push_u16s(&mut buf, &[5, 6, 50, 14, 1, 0xFFFF, 0x80]);
+        push_str(&mut buf, "OK");
+        push_u16s(&mut buf, &[0]);
+
+        align(&mut buf);
+        buf.extend_from_slice(&0x5000_0000u32.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        push_u16s(&mut buf, &[5, 30, 50, 14, 0xFFFF]);
+        push_str(&mut buf, "Custom");
+        push_u16s(&mut buf, &[0xFFFF, 7, 2, 0xABCD]);
+        push_u16s(&mut buf, &[0xFFFF]); // trailing garbage
+
+        let dlg = parse(&buf).unwrap();
+        assert_eq!(dlg.title, "Title");
+        assert_eq!((dlg.x, dlg.y, dlg.cx, dlg.cy), (10, 20, 200, 100));
+        assert!(dlg.menu.is_none() && dlg.class.is_none());
+        assert_eq!(
+            dlg.font,
+            Some(Font {
+                point_size: 8,
+                name: "MS Sans Serif".into()
+            })
+        );
+        assert_eq!(dlg.items.len(), 2);
+        let ok = &dlg.items[0];
+        assert_eq!(ok.id, 1);
+        assert!(matches!(ok.class, ResourceKey::Id(0x80)));
+        assert!(matches!(&ok.title, ResourceKey::Name(t) if t == "OK"));
+        let custom = &dlg.items[1];
+        assert_eq!(custom.id, 0xFFFF);
+        assert!(matches!(&custom.class, ResourceKey::Name(c) if c == "Custom"));
+        assert!(matches!(custom.title, ResourceKey::Id(7)));
+    }
+
+    #[test]
+    fn test_parse_ex() {
+        let mut buf = Vec::new();
+        push_u16s(&mut buf, &[1, 0xFFFF]);
+        buf.extend_from_slice(&0u32.to_le_bytes()); // help id
+        buf.extend_from_slice(&0u32.to_le_bytes()); // ex style
+        buf.extend_from_slice(&(0x80C0_0000u32 | DS_SETFONT).to_le_bytes());
+        push_u16s(&mut buf, &[1, 0, 0, 100, 50]);
+        push_u16s(&mut buf, &[0xFFFF, 3, 0]);
+        push_str(&mut buf, "Ex");
+        push_u16s(&mut buf, &[9, 400]);
+        buf.extend_from_slice(&[0, 1]);
+        push_str(&mut buf, "Tahoma");
+
+        align(&mut buf);
+        buf.extend_from_slice(&0u32.to_le_bytes()); // help id
+        buf.extend_from_slice(&0u32.to_le_bytes()); // ex style
+        buf.extend_from_slice(&0x5000_0000u32.to_le_bytes());
+        push_u16s(&mut buf, &[1, 2, 3, 4]);
+        buf.extend_from_slice(&1234u32.to_le_bytes());
+        push_u16s(&mut buf, &[0xFFFF, 0x81, 0, 0]);
+
+        let dlg = parse(&buf).unwrap();
+        assert_eq!(dlg.style & 0x8000_0000, 0x8000_0000);
+        assert!(matches!(dlg.menu, Some(ResourceKey::Id(3))));
+        assert_eq!(dlg.title, "Ex");
+        assert_eq!(dlg.font.as_ref().unwrap().name, "Tahoma");
+        assert_eq!(dlg.items.len(), 1);
+        assert_eq!(dlg.items[0].id, 1234);
+        assert_eq!((dlg.items[0].cx, dlg.items[0].cy), (3, 4));
+        assert!(matches!(dlg.items[0].class, ResourceKey::Id(0x81)));
+    }
+}
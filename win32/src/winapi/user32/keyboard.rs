@@ -0,0 +1,403 @@
+//! Keyboard input: virtual-key state and key -> character translation.
+
+use super::{MSG, WM};
+use crate::{host, Machine};
+use memory::ExtensionsMut;
+
+// Virtual-key codes.  Letters and digits are their ASCII uppercase values.
+pub const VK_BACK: u8 = 0x08;
+pub const VK_TAB: u8 = 0x09;
+pub const VK_RETURN: u8 = 0x0D;
+pub const VK_SHIFT: u8 = 0x10;
+pub const VK_CONTROL: u8 = 0x11;
+pub const VK_MENU: u8 = 0x12; // Alt
+pub const VK_CAPITAL: u8 = 0x14; // Caps Lock
+pub const VK_ESCAPE: u8 = 0x1B;
+pub const VK_SPACE: u8 = 0x20;
+pub const VK_PRIOR: u8 = 0x21; // Page Up
+pub const VK_NEXT: u8 = 0x22; // Page Down
+pub const VK_END: u8 = 0x23;
+pub const VK_HOME: u8 = 0x24;
+pub const VK_LEFT: u8 = 0x25;
+pub const VK_UP: u8 = 0x26;
+pub const VK_RIGHT: u8 = 0x27;
+pub const VK_DOWN: u8 = 0x28;
+pub const VK_INSERT: u8 = 0x2D;
+pub const VK_DELETE: u8 = 0x2E;
+pub const VK_NUMPAD0: u8 = 0x60;
+pub const VK_MULTIPLY: u8 = 0x6A;
+pub const VK_ADD: u8 = 0x6B;
+pub const VK_SUBTRACT: u8 = 0x6D;
+pub const VK_DECIMAL: u8 = 0x6E;
+pub const VK_DIVIDE: u8 = 0x6F;
+pub const VK_F1: u8 = 0x70;
+pub const VK_F10: u8 = 0x79;
+pub const VK_NUMLOCK: u8 = 0x90;
+pub const VK_SCROLL: u8 = 0x91;
+pub const VK_LSHIFT: u8 = 0xA0;
+pub const VK_RSHIFT: u8 = 0xA1;
+pub const VK_LCONTROL: u8 = 0xA2;
+pub const VK_RCONTROL: u8 = 0xA3;
+pub const VK_LMENU: u8 = 0xA4;
+pub const VK_RMENU: u8 = 0xA5;
+pub const VK_OEM_1: u8 = 0xBA; // ;:
+pub const VK_OEM_PLUS: u8 = 0xBB; // =+
+pub const VK_OEM_COMMA: u8 = 0xBC; // ,<
+pub const VK_OEM_MINUS: u8 = 0xBD; // -_
+pub const VK_OEM_PERIOD: u8 = 0xBE; // .>
+pub const VK_OEM_2: u8 = 0xBF; // /?
+pub const VK_OEM_3: u8 = 0xC0; // `~
+pub const VK_OEM_4: u8 = 0xDB; // [{
+pub const VK_OEM_5: u8 = 0xDC; // \|
+pub const VK_OEM_6: u8 = 0xDD; // ]}
+pub const VK_OEM_7: u8 = 0xDE; // '"
+
+/// Map a virtual-key code to its (US layout, set 1) scan code.
+/// Keys that need an E0 prefix on the wire are returned with 0xE000 set,
+/// as with MapVirtualKey's MAPVK_VK_TO_VSC_EX.  Returns 0 for unknown keys.
+pub fn vk_to_scancode(vk: u8) -> u16 {
+    const LETTERS: [u8; 26] = [
+        0x1E, 0x30, 0x2E, 0x20, 0x12, 0x21, 0x22, 0x23, 0x17, 0x24, 0x25, 0x26, 0x32, 0x31, 0x18,
+        0x19, 0x10, 0x13, 0x1F, 0x14, 0x16, 0x2F, 0x11, 0x2D, 0x15, 0x2C,
+    ];
+    const NUMPAD: [u8; 10] = [0x52, 0x4F, 0x50, 0x51, 0x4B, 0x4C, 0x4D, 0x47, 0x48, 0x49];
+    match vk {
+        b'A'..=b'Z' => LETTERS[(vk - b'A') as usize] as u16,
+        b'1'..=b'9' => (vk - b'1' + 0x02) as u16,
+        b'0' => 0x0B,
+        VK_NUMPAD0..=0x69 => NUMPAD[(vk - VK_NUMPAD0) as usize] as u16,
+        VK_F1..=VK_F10 => (vk - VK_F1 + 0x3B) as u16,
+        0x7A => 0x57, // F11
+        0x7B => 0x58, // F12
+        VK_ESCAPE => 0x01,
+        VK_OEM_MINUS => 0x0C,
+        VK_OEM_PLUS => 0x0D,
+        VK_BACK => 0x0E,
+        VK_TAB => 0x0F,
+        VK_OEM_4 => 0x1A,
+        VK_OEM_6 => 0x1B,
+        VK_RETURN => 0x1C,
+        VK_CONTROL => 0x1D,
+        VK_OEM_1 => 0x27,
+        VK_OEM_7 => 0x28,
+        VK_OEM_3 => 0x29,
+        VK_SHIFT => 0x2A,
+        VK_OEM_5 => 0x2B,
+        VK_OEM_COMMA => 0x33,
+        VK_OEM_PERIOD => 0x34,
+        VK_OEM_2 => 0x35,
+        VK_MULTIPLY => 0x37,
+        VK_MENU => 0x38,
+        VK_SPACE => 0x39,
+        VK_CAPITAL => 0x3A,
+        VK_NUMLOCK => 0x45,
+        VK_SCROLL => 0x46,
+        VK_SUBTRACT => 0x4A,
+        VK_ADD => 0x4E,
+        VK_DECIMAL => 0x53,
+        VK_DIVIDE => 0xE035,
+        VK_HOME => 0xE047,
+        VK_UP => 0xE048,
+        VK_PRIOR => 0xE049,
+        VK_LEFT => 0xE04B,
+        VK_RIGHT => 0xE04D,
+        VK_END => 0xE04F,
+        VK_DOWN => 0xE050,
+        VK_NEXT => 0xE051,
+        VK_INSERT => 0xE052,
+        VK_DELETE => 0xE053,
+        _ => 0,
+    }
+}
+
+/// The inverse of vk_to_scancode.  Returns 0 for unknown scan codes.
+fn scancode_to_vk(scancode: u16) -> u8 {
+    if scancode == 0 {
+        return 0; // also what vk_to_scancode returns for unmapped keys
+    }
+    (1..=255u8)
+        .find(|&vk| vk_to_scancode(vk) == scancode)
+        .unwrap_or(0)
+}
+
+/// Current state of the keyboard, as reflected by messages retrieved so far.
+pub struct Keyboard {
+    /// Indexed by virtual key, in GetKeyboardState format:
+    /// high bit set means key is down, low bit set means key is toggled.
+    keys: [u8; 256],
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Keyboard { keys: [0; 256] }
+    }
+}
+
+impl Keyboard {
+    pub fn is_down(&self, vk: u8) -> bool {
+        self.keys[vk as usize] & 0x80 != 0
+    }
+
+    fn is_toggled(&self, vk: u8) -> bool {
+        self.keys[vk as usize] & 0x01 != 0
+    }
+
+    /// Iterate the virtual keys currently held down.
+    pub fn iter_down(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=255u8).filter(|&vk| self.is_down(vk))
+    }
+
+    /// Record a key event and produce the corresponding message.
+    pub fn key_message(&mut self, key: &host::KeyMessage) -> MSG {
+        let alt = self.is_down(VK_MENU) || (key.vk == VK_MENU && key.down);
+        let state = &mut self.keys[key.vk as usize];
+        let was_down = *state & 0x80 != 0;
+        if key.down {
+            if !was_down {
+                *state ^= 0x01;
+            }
+            *state |= 0x80;
+        } else {
+            *state &= !0x80;
+        }
+
+        // F10 is the menu key, and so behaves like an alt combination.
+        let sys = alt || key.vk == VK_F10;
+        let message = match (key.down, sys) {
+            (true, false) => WM::KEYDOWN,
+            (false, false) => WM::KEYUP,
+            (true, true) => WM::SYSKEYDOWN,
+            (false, true) => WM::SYSKEYUP,
+        };
+
+        let mut lParam = 1 | ((key.scancode as u32 & 0xFF) << 16);
+        if key.scancode & 0xE000 == 0xE000 {
+            lParam |= 1 << 24; // extended key
+        }
+        if alt {
+            lParam |= 1 << 29; // context code
+        }
+        if !key.down || key.repeat || was_down {
+            lParam |= 1 << 30; // previous key state
+        }
+        if !key.down {
+            lParam |= 1 << 31; // transition state
+        }
+
+        MSG {
+            hwnd: Default::default(),
+            message: message as u32,
+            wParam: key.vk as u32,
+            lParam,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        }
+    }
+
+    /// Compute the character produced by a virtual key given the current modifier state,
+    /// using a US layout.
+    fn translate(&self, vk: u8) -> Option<u8> {
+        let shift = self.is_down(VK_SHIFT);
+        if self.is_down(VK_CONTROL) && !self.is_down(VK_MENU) {
+            return match vk {
+                b'A'..=b'Z' => Some(vk - b'A' + 1),
+                VK_BACK => Some(0x7F),
+                VK_RETURN => Some(b'\n'),
+                VK_ESCAPE => Some(0x1B),
+                _ => None,
+            };
+        }
+        const SHIFTED_DIGITS: &[u8; 10] = b")!@#$%^&*(";
+        Some(match vk {
+            b'A'..=b'Z' => {
+                if shift != self.is_toggled(VK_CAPITAL) {
+                    vk
+                } else {
+                    vk.to_ascii_lowercase()
+                }
+            }
+            b'0'..=b'9' if shift => SHIFTED_DIGITS[(vk - b'0') as usize],
+            b'0'..=b'9' => vk,
+            VK_NUMPAD0..=0x69 => vk - VK_NUMPAD0 + b'0',
+            VK_MULTIPLY => b'*',
+            VK_ADD => b'+',
+            VK_SUBTRACT => b'-',
+            VK_DECIMAL => b'.',
+            VK_DIVIDE => b'/',
+            VK_SPACE => b' ',
+            VK_RETURN => b'\r',
+            VK_BACK => 0x08,
+            VK_TAB => b'\t',
+            VK_ESCAPE => 0x1B,
+            VK_OEM_1..=VK_OEM_3 | VK_OEM_4..=VK_OEM_7 => {
+                let (plain, shifted) = match vk {
+                    VK_OEM_1 => (b';', b':'),
+                    VK_OEM_PLUS => (b'=', b'+'),
+                    VK_OEM_COMMA => (b',', b'<'),
+                    VK_OEM_MINUS => (b'-', b'_'),
+                    VK_OEM_PERIOD => (b'.', b'>'),
+                    VK_OEM_2 => (b'/', b'?'),
+                    VK_OEM_3 => (b'`', b'~'),
+                    VK_OEM_4 => (b'[', b'{'),
+                    VK_OEM_5 => (b'\\', b'|'),
+                    VK_OEM_6 => (b']', b'}'),
+                    VK_OEM_7 => (b'\'', b'"'),
+                    _ => return None,
+                };
+                if shift {
+                    shifted
+                } else {
+                    plain
+                }
+            }
+            _ => return None,
+        })
+    }
+
+    /// Produce the WM_CHAR/WM_SYSCHAR message for a key down message, if any.
+    pub fn translate_message(&self, msg: &MSG) -> Option<MSG> {
+        let message = match WM::try_from(msg.message) {
+            Ok(WM::KEYDOWN) => WM::CHAR,
+            Ok(WM::SYSKEYDOWN) => WM::SYSCHAR,
+            _ => return None,
+        };
+        let c = self.translate(msg.wParam as u8)?;
+        Some(MSG {
+            message: message as u32,
+            wParam: c as u32,
+            ..msg.clone()
+        })
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetKeyState(machine: &mut Machine, nVirtKey: u32) -> i32 {
+    let state = machine.state.user32.keyboard.keys[nVirtKey as usize & 0xFF];
+    // Returned as a SHORT: high bit for down, low bit for toggled.
+    (((state as u16 & 0x80) << 8) | (state as u16 & 0x01)) as i16 as i32
+}
+
+#[win32_derive::dllexport]
+pub fn GetAsyncKeyState(machine: &mut Machine, vKey: u32) -> i32 {
+    // Programs that poll this often don't pump messages, so pull in any pending input.
+    super::fetch_host_messages(machine);
+    if machine.state.user32.keyboard.is_down(vKey as u8) {
+        0x8000u16 as i16 as i32
+    } else {
+        0
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetKeyboardState(machine: &mut Machine, lpKeyState: u32) -> bool {
+    if lpKeyState == 0 {
+        return false;
+    }
+    machine
+        .mem()
+        .sub32_mut(lpKeyState, 256)
+        .copy_from_slice(&machine.state.user32.keyboard.keys);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn keybd_event(
+    _machine: &mut Machine,
+    bVk: u8,
+    bScan: u8,
+    dwFlags: u32, /* KEYBD_EVENT_FLAGS */
+    dwExtraInfo: u32,
+) {
+    todo!()
+}
+
+#[win32_derive::dllexport]
+pub fn MapVirtualKeyA(_machine: &mut Machine, uCode: u32, uMapType: u32) -> u32 {
+    map_virtual_key(uCode, uMapType)
+}
+
+/// Translate between virtual keys, scan codes, and characters, using the US layout.
+fn map_virtual_key(uCode: u32, uMapType: u32) -> u32 {
+    match uMapType {
+        0 /* MAPVK_VK_TO_VSC */ => vk_to_scancode(uCode as u8) as u32 & 0xFF,
+        1 /* MAPVK_VSC_TO_VK */ => scancode_to_vk(uCode as u8 as u16) as u32,
+        2 /* MAPVK_VK_TO_CHAR */ => {
+            // The unshifted character, except that letters are upper case.
+            let ch = Keyboard::default().translate(uCode as u8).unwrap_or(0);
+            ch.to_ascii_uppercase() as u32
+        }
+        3 /* MAPVK_VSC_TO_VK_EX */ => {
+            // Like MAPVK_VSC_TO_VK, but distinguishing left and right modifiers.
+            let vk = match uCode as u16 {
+                0x2A => VK_LSHIFT,
+                0x36 => VK_RSHIFT,
+                0x1D => VK_LCONTROL,
+                0xE01D => VK_RCONTROL,
+                0x38 => VK_LMENU,
+                0xE038 => VK_RMENU,
+                scancode => scancode_to_vk(scancode),
+            };
+            vk as u32
+        }
+        4 /* MAPVK_VK_TO_VSC_EX */ => vk_to_scancode(uCode as u8) as u32,
+        _ => {
+            log::warn!("MapVirtualKeyA: unknown type {uMapType}");
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(vk: u8, down: bool) -> host::KeyMessage {
+        host::KeyMessage {
+            vk,
+            scancode: vk_to_scancode(vk),
+            down,
+            repeat: false,
+        }
+    }
+
+    #[test]
+    fn shifted_char() {
+        let mut keyboard = Keyboard::default();
+        let down = keyboard.key_message(&key(b'A', true));
+        assert_eq!(down.message, WM::KEYDOWN as u32);
+        assert_eq!(down.lParam, 0x001E_0001);
+        let ch = keyboard.translate_message(&down).unwrap();
+        assert_eq!((ch.message, ch.wParam), (WM::CHAR as u32, b'a' as u32));
+
+        keyboard.key_message(&key(VK_SHIFT, true));
+        let ch = keyboard.translate_message(&down).unwrap();
+        assert_eq!(ch.wParam, b'A' as u32);
+
+        let up = keyboard.key_message(&key(b'A', false));
+        assert_eq!(up.lParam, 0xC01E_0001);
+        assert!(keyboard.translate_message(&up).is_none());
+    }
+
+    #[test]
+    fn alt_is_sys() {
+        let mut keyboard = Keyboard::default();
+        keyboard.key_message(&key(VK_MENU, true));
+        let down = keyboard.key_message(&key(b'F', true));
+        assert_eq!(down.message, WM::SYSKEYDOWN as u32);
+        assert_ne!(down.lParam & (1 << 29), 0);
+    }
+
+    #[test]
+    fn map_virtual_key_types() {
+        assert_eq!(map_virtual_key(b'A' as u32, 0), 0x1E);
+        assert_eq!(map_virtual_key(0x1E, 1), b'A' as u32);
+        assert_eq!(map_virtual_key(b'A' as u32, 2), b'A' as u32);
+        assert_eq!(map_virtual_key(b'1' as u32, 2), b'1' as u32);
+        assert_eq!(map_virtual_key(0x36, 3), VK_RSHIFT as u32);
+        assert_eq!(map_virtual_key(0xE01D, 3), VK_RCONTROL as u32);
+        assert_eq!(map_virtual_key(0x1E, 3), b'A' as u32);
+        assert_eq!(map_virtual_key(0, 1), 0);
+        assert_eq!(map_virtual_key(0, 99), 0);
+    }
+}
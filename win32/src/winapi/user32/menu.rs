@@ -1,112 +1,938 @@
+//! Menus: HMENU objects as loaded from resources or built by the Menu APIs,
+//! and accelerator tables.  The menu bar and popup menus are drawn and
+//! tracked in menutrack.rs.
+
+use super::{
+    keyboard::{VK_CONTROL, VK_MENU, VK_SHIFT},
+    menutemplate::{self, MenuTemplateItem},
+    menutrack, SendMessageA, HINSTANCE, MSG, WM,
+};
 use crate::{
-    winapi::types::{HWND, RECT},
+    pe,
+    str16::Str16,
+    winapi::{
+        handle::{Handles, HANDLE},
+        kernel32::{self, ResourceKey},
+        types::{HWND, RECT},
+    },
     Machine,
 };
 use bitflags::bitflags;
+use memory::{Extensions, ExtensionsMut};
 
-pub type HMENU = u32;
-
-#[win32_derive::dllexport]
-pub fn CreatePopupMenu(_machine: &mut Machine) -> HMENU {
-    0 // null
-}
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HMENUT;
+pub type HMENU = HANDLE<HMENUT>;
 
-#[win32_derive::dllexport]
-pub fn CheckMenuItem(_machine: &mut Machine, hMenu: HMENU, uIDCheckItem: u32, uCheck: u32) -> u32 {
-    0 // previous state: unchecked
-}
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HACCELT;
+pub type HACCEL = HANDLE<HACCELT>;
 
 bitflags! {
-    #[derive(win32_derive::TryFromBitflags)]
+    /// Menu item flags, as used by AppendMenu etc.  Besides BYPOSITION, these are
+    /// the type and state bits stored with each item.
+    #[derive(Default, win32_derive::TryFromBitflags)]
     pub struct MF: u32 {
         const BYCOMMAND = 0x00000000;
         const BYPOSITION = 0x00000400;
-        const DISABLED = 0x00000002;
         const ENABLED = 0x00000000;
         const GRAYED = 0x00000001;
+        const DISABLED = 0x00000002;
+        const STRING = 0x00000000;
+        const BITMAP = 0x00000004;
+        const UNCHECKED = 0x00000000;
+        const CHECKED = 0x00000008;
+        const POPUP = 0x00000010;
+        const MENUBARBREAK = 0x00000020;
+        const MENUBREAK = 0x00000040;
+        const HILITE = 0x00000080;
+        const OWNERDRAW = 0x00000100;
+        const RADIOCHECK = 0x00000200;
+        const SEPARATOR = 0x00000800;
+        const DEFAULT = 0x00001000;
+        const RIGHTJUSTIFY = 0x00004000;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MenuItem {
+    /// Command id, sent with WM_COMMAND.  Unused for items opening submenus.
+    pub id: u32,
+    /// The label, with '&' marking the mnemonic and '\t' preceding any accelerator text.
+    pub text: String,
+    pub flags: MF,
+    /// The submenu opened by the item, if any.
+    pub submenu: HMENU,
+    /// Application data, per SetMenuItemInfo.
+    pub data: u32,
+}
+
+impl MenuItem {
+    pub fn is_separator(&self) -> bool {
+        self.flags.contains(MF::SEPARATOR)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.flags.intersects(MF::GRAYED | MF::DISABLED)
+    }
+
+    /// The character marked by '&' in the label, lowercased.
+    pub fn mnemonic(&self) -> Option<char> {
+        let mut chars = self.text.chars();
+        while let Some(c) = chars.next() {
+            if c == '&' {
+                match chars.next() {
+                    Some('&') => continue,
+                    c => return c.map(|c| c.to_ascii_lowercase()),
+                }
+            }
+        }
+        None
+    }
+}
+
+#[derive(Default)]
+pub struct Menu {
+    pub items: Vec<MenuItem>,
+}
+
+/// Find an item by position or by command id.  Command ids are also searched for in submenus,
+/// so the result includes the menu where the item was found.
+pub fn find_item(
+    menus: &Handles<HMENU, Menu>,
+    hmenu: HMENU,
+    item: u32,
+    by_position: bool,
+) -> Option<(HMENU, usize)> {
+    let menu = menus.get(hmenu)?;
+    if by_position {
+        return ((item as usize) < menu.items.len()).then_some((hmenu, item as usize));
+    }
+    for (i, it) in menu.items.iter().enumerate() {
+        if it.submenu.is_null() {
+            if it.id == item && !it.is_separator() {
+                return Some((hmenu, i));
+            }
+        } else if let Some(found) = find_item(menus, it.submenu, item, false) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn with_item<T>(
+    machine: &mut Machine,
+    hmenu: HMENU,
+    item: u32,
+    flags: MF,
+    f: impl FnOnce(&mut MenuItem) -> T,
+) -> Option<T> {
+    let menus = &mut machine.state.user32.menus;
+    let (hmenu, index) = find_item(menus, hmenu, item, flags.contains(MF::BYPOSITION))?;
+    Some(f(&mut menus.get_mut(hmenu).unwrap().items[index]))
+}
+
+pub(super) fn destroy_menu(menus: &mut Handles<HMENU, Menu>, hmenu: HMENU) -> bool {
+    let Some(menu) = menus.remove(hmenu) else {
+        return false;
+    };
+    for item in menu.items {
+        if !item.submenu.is_null() {
+            destroy_menu(menus, item.submenu);
+        }
     }
+    true
+}
+
+fn create_from_template(menus: &mut Handles<HMENU, Menu>, items: Vec<MenuTemplateItem>) -> HMENU {
+    let items = items
+        .into_iter()
+        .map(|item| {
+            let submenu = match item.popup {
+                Some(popup) => create_from_template(menus, popup),
+                None => HMENU::null(),
+            };
+            MenuItem {
+                id: item.id,
+                text: item.text,
+                flags: MF::from_bits_truncate(item.flags),
+                submenu,
+                data: 0,
+            }
+        })
+        .collect();
+    menus.add(Menu { items })
+}
+
+pub fn load_menu(machine: &mut Machine, hInstance: HINSTANCE, name: ResourceKey<&Str16>) -> HMENU {
+    let Some(buf) = kernel32::find_resource(
+        &machine.state.kernel32,
+        machine.mem(),
+        hInstance,
+        ResourceKey::Id(pe::RT::MENU as u32),
+        &name,
+    ) else {
+        return HMENU::null();
+    };
+    let Some(items) = menutemplate::parse(machine.mem().slice(buf)) else {
+        log::error!("failed to parse menu template");
+        return HMENU::null();
+    };
+    create_from_template(&mut machine.state.user32.menus, items)
 }
 
 #[win32_derive::dllexport]
-pub fn EnableMenuItem(
-    _machine: &mut Machine,
+pub fn LoadMenuA(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpMenuName: ResourceKey<&str>,
+) -> HMENU {
+    load_menu(machine, hInstance, lpMenuName.to_string16().as_ref())
+}
+
+#[win32_derive::dllexport]
+pub fn LoadMenuW(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpMenuName: ResourceKey<&Str16>,
+) -> HMENU {
+    load_menu(machine, hInstance, lpMenuName)
+}
+
+#[win32_derive::dllexport]
+pub fn CreateMenu(machine: &mut Machine) -> HMENU {
+    machine.state.user32.menus.add(Menu::default())
+}
+
+#[win32_derive::dllexport]
+pub fn CreatePopupMenu(machine: &mut Machine) -> HMENU {
+    machine.state.user32.menus.add(Menu::default())
+}
+
+#[win32_derive::dllexport]
+pub fn DestroyMenu(machine: &mut Machine, hMenu: HMENU) -> bool {
+    destroy_menu(&mut machine.state.user32.menus, hMenu)
+}
+
+#[win32_derive::dllexport]
+pub fn IsMenu(machine: &mut Machine, hMenu: HMENU) -> bool {
+    machine.state.user32.menus.get(hMenu).is_some()
+}
+
+#[win32_derive::dllexport]
+pub fn GetMenu(machine: &mut Machine, hWnd: HWND) -> HMENU {
+    match machine.state.user32.windows.get(hWnd) {
+        Some(window) => window.borrow().menu(),
+        None => HMENU::null(),
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn SetMenu(machine: &mut Machine, hWnd: HWND, hMenu: HMENU) -> bool {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return false;
+    };
+    let mut window = window.borrow_mut();
+    if window.is_child() {
+        return false;
+    }
+    // Windows keeps the window size and shrinks the client area to make room for the bar;
+    // we instead keep the client size and grow the host window.
+    window.expect_toplevel_mut().menu = hMenu;
+    let (width, height) = (window.width, window.height);
+    window.set_client_size(&mut *machine.host, width, height);
+    drop(window);
+    menutrack::draw_menu_bar(machine, hWnd);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn DrawMenuBar(machine: &mut Machine, hWnd: HWND) -> bool {
+    if machine.state.user32.windows.get(hWnd).is_none() {
+        return false;
+    }
+    menutrack::draw_menu_bar(machine, hWnd);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetSystemMenu(_machine: &mut Machine, hWnd: HWND, bRevert: bool) -> HMENU {
+    // TODO: there is no system menu, as the host draws the window frame.
+    HMENU::null()
+}
+
+#[win32_derive::dllexport]
+pub fn GetSubMenu(machine: &mut Machine, hMenu: HMENU, nPos: i32) -> HMENU {
+    let Some(menu) = machine.state.user32.menus.get(hMenu) else {
+        return HMENU::null();
+    };
+    match menu.items.get(nPos as usize) {
+        Some(item) => item.submenu,
+        None => HMENU::null(),
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetMenuItemCount(machine: &mut Machine, hMenu: HMENU) -> i32 {
+    match machine.state.user32.menus.get(hMenu) {
+        Some(menu) => menu.items.len() as i32,
+        None => -1,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetMenuItemID(machine: &mut Machine, hMenu: HMENU, nPos: i32) -> u32 {
+    let Some(menu) = machine.state.user32.menus.get(hMenu) else {
+        return -1i32 as u32;
+    };
+    match menu.items.get(nPos as usize) {
+        Some(item) if item.submenu.is_null() => item.id,
+        _ => -1i32 as u32,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetMenuState(machine: &mut Machine, hMenu: HMENU, uId: u32, uFlags: Result<MF, u32>) -> u32 {
+    let flags = uFlags.unwrap_or_else(MF::from_bits_truncate);
+    let Some((hmenu, index)) = find_item(
+        &machine.state.user32.menus,
+        hMenu,
+        uId,
+        flags.contains(MF::BYPOSITION),
+    ) else {
+        return -1i32 as u32;
+    };
+    let menus = &machine.state.user32.menus;
+    let item = &menus.get(hmenu).unwrap().items[index];
+    match menus.get(item.submenu) {
+        // Submenus report their item count in the high byte.
+        Some(submenu) => ((submenu.items.len() as u32) << 8) | (item.flags.bits() & 0xFF),
+        None => item.flags.bits(),
+    }
+}
+
+fn get_menu_string(machine: &mut Machine, hMenu: HMENU, uIDItem: u32, flags: MF) -> Option<String> {
+    with_item(machine, hMenu, uIDItem, flags, |item| item.text.clone())
+}
+
+#[win32_derive::dllexport]
+pub fn GetMenuStringA(
+    machine: &mut Machine,
     hMenu: HMENU,
-    uIDEnableItem: u32,
-    uEnable: Result<MF, u32>,
+    uIDItem: u32,
+    lpString: u32,
+    cchMax: i32,
+    flags: Result<MF, u32>,
 ) -> i32 {
-    let previous_state = MF::ENABLED;
-    previous_state.bits() as i32
+    let flags = flags.unwrap_or_else(MF::from_bits_truncate);
+    let Some(text) = get_menu_string(machine, hMenu, uIDItem, flags) else {
+        return 0;
+    };
+    if lpString == 0 || cchMax <= 0 {
+        return text.len() as i32;
+    }
+    let buf = machine.mem().sub32_mut(lpString, cchMax as u32);
+    super::copy_text(&text, buf) as i32
+}
+
+/// Read the lpNewItem argument of AppendMenu etc., which is only a string for MF_STRING items.
+fn item_text(machine: &Machine, flags: MF, lpNewItem: u32, wide: bool) -> String {
+    if lpNewItem == 0 || flags.intersects(MF::SEPARATOR | MF::BITMAP | MF::OWNERDRAW) {
+        if flags.contains(MF::BITMAP) {
+            log::warn!("TODO: bitmap menu items");
+        }
+        return String::new();
+    }
+    if wide {
+        unsafe { Str16::from_nul_term_ptr(machine.mem(), lpNewItem) }
+            .unwrap()
+            .to_string()
+    } else {
+        String::from_utf8_lossy(machine.mem().slicez(lpNewItem)).into_owned()
+    }
+}
+
+fn new_item(machine: &Machine, flags: MF, uIDNewItem: u32, lpNewItem: u32, wide: bool) -> MenuItem {
+    let popup = flags.contains(MF::POPUP);
+    MenuItem {
+        id: if popup { 0 } else { uIDNewItem },
+        text: item_text(machine, flags, lpNewItem, wide),
+        flags: flags - MF::BYPOSITION,
+        submenu: if popup {
+            HMENU::from_raw(uIDNewItem)
+        } else {
+            HMENU::null()
+        },
+        data: 0,
+    }
+}
+
+/// Insert an item before the given item, or at the end if it doesn't exist.
+fn insert_item(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    before: Option<(u32, bool)>,
+    item: MenuItem,
+) -> bool {
+    let menus = &mut machine.state.user32.menus;
+    let (hmenu, index) =
+        match before.and_then(|(item, by_position)| find_item(menus, hMenu, item, by_position)) {
+            Some(found) => found,
+            None => match menus.get(hMenu) {
+                Some(menu) => (hMenu, menu.items.len()),
+                None => return false,
+            },
+        };
+    menus.get_mut(hmenu).unwrap().items.insert(index, item);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn AppendMenuA(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    uFlags: Result<MF, u32>,
+    uIDNewItem: u32,
+    lpNewItem: u32,
+) -> bool {
+    let flags = uFlags.unwrap_or_else(MF::from_bits_truncate);
+    let item = new_item(machine, flags, uIDNewItem, lpNewItem, false);
+    insert_item(machine, hMenu, None, item)
 }
 
 #[win32_derive::dllexport]
-pub fn GetMenu(_machine: &mut Machine, hWnd: HWND) -> HMENU {
-    0 // null
+pub fn AppendMenuW(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    uFlags: Result<MF, u32>,
+    uIDNewItem: u32,
+    lpNewItem: u32,
+) -> bool {
+    let flags = uFlags.unwrap_or_else(MF::from_bits_truncate);
+    let item = new_item(machine, flags, uIDNewItem, lpNewItem, true);
+    insert_item(machine, hMenu, None, item)
 }
 
 #[win32_derive::dllexport]
-pub fn GetSubMenu(_machine: &mut Machine, hMenu: HMENU, nPos: i32) -> HMENU {
-    0 // null
+pub fn InsertMenuA(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    uPosition: u32,
+    uFlags: Result<MF, u32>,
+    uIDNewItem: u32,
+    lpNewItem: u32,
+) -> bool {
+    let flags = uFlags.unwrap_or_else(MF::from_bits_truncate);
+    let item = new_item(machine, flags, uIDNewItem, lpNewItem, false);
+    let before = Some((uPosition, flags.contains(MF::BYPOSITION)));
+    insert_item(machine, hMenu, before, item)
+}
+
+#[win32_derive::dllexport]
+pub fn InsertMenuW(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    uPosition: u32,
+    uFlags: Result<MF, u32>,
+    uIDNewItem: u32,
+    lpNewItem: u32,
+) -> bool {
+    let flags = uFlags.unwrap_or_else(MF::from_bits_truncate);
+    let item = new_item(machine, flags, uIDNewItem, lpNewItem, true);
+    let before = Some((uPosition, flags.contains(MF::BYPOSITION)));
+    insert_item(machine, hMenu, before, item)
+}
+
+fn modify_item(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    uPosition: u32,
+    flags: MF,
+    new: MenuItem,
+) -> bool {
+    with_item(machine, hMenu, uPosition, flags, |item| {
+        *item = MenuItem {
+            data: item.data,
+            ..new
+        }
+    })
+    .is_some()
 }
 
 #[win32_derive::dllexport]
-pub fn LoadMenuA(_machine: &mut Machine, hInstance: u32, lpMenuName: u32) -> HMENU {
-    0 // null
+pub fn ModifyMenuA(
+    machine: &mut Machine,
+    hMnu: HMENU,
+    uPosition: u32,
+    uFlags: Result<MF, u32>,
+    uIDNewItem: u32,
+    lpNewItem: u32,
+) -> bool {
+    let flags = uFlags.unwrap_or_else(MF::from_bits_truncate);
+    let new = new_item(machine, flags, uIDNewItem, lpNewItem, false);
+    modify_item(machine, hMnu, uPosition, flags, new)
 }
 
 #[win32_derive::dllexport]
-pub fn SetMenu(_machine: &mut Machine, hWnd: HWND, hMenu: HMENU) -> bool {
-    true // success
+pub fn ModifyMenuW(
+    machine: &mut Machine,
+    hMnu: HMENU,
+    uPosition: u32,
+    uFlags: Result<MF, u32>,
+    uIDNewItem: u32,
+    lpNewItem: u32,
+) -> bool {
+    let flags = uFlags.unwrap_or_else(MF::from_bits_truncate);
+    let new = new_item(machine, flags, uIDNewItem, lpNewItem, true);
+    modify_item(machine, hMnu, uPosition, flags, new)
+}
+
+/// Remove an item, returning any submenu it opened.
+fn remove_item(machine: &mut Machine, hMenu: HMENU, uPosition: u32, flags: MF) -> Option<HMENU> {
+    let menus = &mut machine.state.user32.menus;
+    let (hmenu, index) = find_item(menus, hMenu, uPosition, flags.contains(MF::BYPOSITION))?;
+    Some(menus.get_mut(hmenu).unwrap().items.remove(index).submenu)
 }
 
 #[win32_derive::dllexport]
-pub fn SetMenuItemInfoA(
-    _machine: &mut Machine,
+pub fn RemoveMenu(
+    machine: &mut Machine,
     hMenu: HMENU,
-    item: u32,
-    fByPosition: bool,
-    lpmii: u32,
+    uPosition: u32,
+    uFlags: Result<MF, u32>,
 ) -> bool {
-    false // fail
+    let flags = uFlags.unwrap_or_else(MF::from_bits_truncate);
+    remove_item(machine, hMenu, uPosition, flags).is_some()
 }
 
 #[win32_derive::dllexport]
-pub fn GetSystemMenu(_machine: &mut Machine, hWnd: HWND, bRevert: bool) -> HMENU {
-    0 // null
+pub fn DeleteMenu(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    uPosition: u32,
+    uFlags: Result<MF, u32>,
+) -> bool {
+    let flags = uFlags.unwrap_or_else(MF::from_bits_truncate);
+    match remove_item(machine, hMenu, uPosition, flags) {
+        Some(submenu) => {
+            // Unlike RemoveMenu, this destroys the submenu.
+            destroy_menu(&mut machine.state.user32.menus, submenu);
+            true
+        }
+        None => false,
+    }
 }
 
 #[win32_derive::dllexport]
-pub fn AppendMenuA(
-    _machine: &mut Machine,
+pub fn CheckMenuItem(
+    machine: &mut Machine,
     hMenu: HMENU,
-    uFlags: u32,
-    uIDNewItem: u32,
-    lpNewItem: Option<&str>,
+    uIDCheckItem: u32,
+    uCheck: Result<MF, u32>,
+) -> u32 {
+    let flags = uCheck.unwrap_or_else(MF::from_bits_truncate);
+    with_item(machine, hMenu, uIDCheckItem, flags, |item| {
+        let previous = item.flags & MF::CHECKED;
+        item.flags.set(MF::CHECKED, flags.contains(MF::CHECKED));
+        previous.bits()
+    })
+    .unwrap_or(-1i32 as u32)
+}
+
+#[win32_derive::dllexport]
+pub fn CheckMenuRadioItem(
+    machine: &mut Machine,
+    hmenu: HMENU,
+    first: u32,
+    last: u32,
+    check: u32,
+    flags: Result<MF, u32>,
 ) -> bool {
-    false // fail
+    let by_position = flags
+        .unwrap_or_else(MF::from_bits_truncate)
+        .contains(MF::BYPOSITION);
+    let menus = &mut machine.state.user32.menus;
+    let (Some((menu, first)), Some((last_menu, last)), Some((check_menu, check))) = (
+        find_item(menus, hmenu, first, by_position),
+        find_item(menus, hmenu, last, by_position),
+        find_item(menus, hmenu, check, by_position),
+    ) else {
+        return false;
+    };
+    if menu != last_menu || menu != check_menu {
+        return false;
+    }
+    let menu = menus.get_mut(menu).unwrap();
+    for (i, item) in menu.items[first..=last].iter_mut().enumerate() {
+        if first + i == check {
+            item.flags |= MF::CHECKED | MF::RADIOCHECK;
+        } else {
+            item.flags.remove(MF::CHECKED);
+        }
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn EnableMenuItem(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    uIDEnableItem: u32,
+    uEnable: Result<MF, u32>,
+) -> i32 {
+    let flags = uEnable.unwrap_or_else(MF::from_bits_truncate);
+    with_item(machine, hMenu, uIDEnableItem, flags, |item| {
+        let previous = item.flags & (MF::GRAYED | MF::DISABLED);
+        item.flags.remove(MF::GRAYED | MF::DISABLED);
+        item.flags |= flags & (MF::GRAYED | MF::DISABLED);
+        previous.bits() as i32
+    })
+    .unwrap_or(-1)
 }
 
 #[win32_derive::dllexport]
 pub fn GetMenuItemRect(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hWnd: HWND,
     hMenu: HMENU,
     uItem: u32,
     lprcItem: Option<&mut RECT>,
 ) -> bool {
-    todo!();
+    match menutrack::item_rect(machine, hWnd, hMenu, uItem as usize) {
+        Some(rect) => {
+            *lprcItem.unwrap() = rect;
+            true
+        }
+        None => false,
+    }
+}
+
+const MIIM_STATE: u32 = 0x01;
+const MIIM_ID: u32 = 0x02;
+const MIIM_SUBMENU: u32 = 0x04;
+const MIIM_TYPE: u32 = 0x10;
+const MIIM_DATA: u32 = 0x20;
+const MIIM_STRING: u32 = 0x40;
+const MIIM_FTYPE: u32 = 0x100;
+
+/// The MFT_* type bits, as opposed to the MFS_* state bits.
+const MFT_MASK: u32 = 0x4000 | 0x2000 | 0x800 | 0x200 | 0x100 | 0x40 | 0x20 | 0x4;
+/// The MFS_* state bits.
+const MFS_MASK: u32 = 0x1000 | 0x80 | 0x8 | 0x3;
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MENUITEMINFOA {
+    cbSize: u32,
+    fMask: u32,
+    fType: u32,
+    fState: u32,
+    wID: u32,
+    hSubMenu: HMENU,
+    hbmpChecked: u32,
+    hbmpUnchecked: u32,
+    dwItemData: u32,
+    dwTypeData: u32,
+    cch: u32,
+}
+unsafe impl memory::Pod for MENUITEMINFOA {}
+
+/// Apply the fields of a MENUITEMINFO, per its fMask.
+fn set_item_info(machine: &Machine, item: &mut MenuItem, info: &MENUITEMINFOA) {
+    let mask = info.fMask;
+    if mask & (MIIM_TYPE | MIIM_FTYPE) != 0 {
+        let state = item.flags.bits() & !MFT_MASK;
+        item.flags = MF::from_bits_truncate(state | (info.fType & MFT_MASK));
+    }
+    if mask & (MIIM_TYPE | MIIM_STRING) != 0 && info.fType & MFT_MASK & !0x2000 == 0 {
+        item.text = item_text(machine, MF::STRING, info.dwTypeData, false);
+    }
+    if mask & MIIM_STATE != 0 {
+        let typ = item.flags.bits() & !MFS_MASK;
+        item.flags = MF::from_bits_truncate(typ | (info.fState & MFS_MASK));
+    }
+    if mask & MIIM_ID != 0 {
+        item.id = info.wID;
+    }
+    if mask & MIIM_SUBMENU != 0 {
+        item.submenu = info.hSubMenu;
+        item.flags.set(MF::POPUP, !info.hSubMenu.is_null());
+    }
+    if mask & MIIM_DATA != 0 {
+        item.data = info.dwItemData;
+    }
 }
 
 #[win32_derive::dllexport]
-pub fn DeleteMenu(
-    _machine: &mut Machine,
+pub fn SetMenuItemInfoA(
+    machine: &mut Machine,
     hMenu: HMENU,
-    uPosition: u32,
-    uFlags: u32, /* MENU_ITEM_FLAGS */
+    item: u32,
+    fByPosition: bool,
+    lpmii: Option<&MENUITEMINFOA>,
 ) -> bool {
-    false // fail
+    let info = lpmii.unwrap().clone();
+    let flags = if fByPosition {
+        MF::BYPOSITION
+    } else {
+        MF::BYCOMMAND
+    };
+    let Some(mut it) = with_item(machine, hMenu, item, flags, |it| it.clone()) else {
+        return false;
+    };
+    set_item_info(machine, &mut it, &info);
+    with_item(machine, hMenu, item, flags, |old| *old = it).is_some()
 }
 
 #[win32_derive::dllexport]
-pub fn DrawMenuBar(_machine: &mut Machine, hWnd: HWND) -> bool {
-    false // fail
+pub fn InsertMenuItemA(
+    machine: &mut Machine,
+    hmenu: HMENU,
+    item: u32,
+    fByPosition: bool,
+    lpmi: Option<&MENUITEMINFOA>,
+) -> bool {
+    let info = lpmi.unwrap().clone();
+    let mut it = MenuItem {
+        id: 0,
+        text: String::new(),
+        flags: MF::empty(),
+        submenu: HMENU::null(),
+        data: 0,
+    };
+    set_item_info(machine, &mut it, &info);
+    insert_item(machine, hmenu, Some((item, fByPosition)), it)
+}
+
+#[win32_derive::dllexport]
+pub fn GetMenuItemInfoA(
+    machine: &mut Machine,
+    hmenu: HMENU,
+    item: u32,
+    fByPosition: bool,
+    lpmii: Option<&mut MENUITEMINFOA>,
+) -> bool {
+    let flags = if fByPosition {
+        MF::BYPOSITION
+    } else {
+        MF::BYCOMMAND
+    };
+    let Some(it) = with_item(machine, hmenu, item, flags, |it| it.clone()) else {
+        return false;
+    };
+    let info = lpmii.unwrap();
+    let mask = info.fMask;
+    if mask & (MIIM_TYPE | MIIM_FTYPE) != 0 {
+        info.fType = it.flags.bits() & MFT_MASK;
+    }
+    if mask & MIIM_STATE != 0 {
+        info.fState = it.flags.bits() & MFS_MASK;
+    }
+    if mask & MIIM_ID != 0 {
+        info.wID = it.id;
+    }
+    if mask & MIIM_SUBMENU != 0 {
+        info.hSubMenu = it.submenu;
+    }
+    if mask & MIIM_DATA != 0 {
+        info.dwItemData = it.data;
+    }
+    if mask & (MIIM_TYPE | MIIM_STRING) != 0 {
+        if info.dwTypeData != 0 && info.cch > 0 {
+            let buf = machine.mem().sub32_mut(info.dwTypeData, info.cch);
+            info.cch = super::copy_text(&it.text, buf);
+        } else {
+            info.cch = it.text.len() as u32;
+        }
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn TrackPopupMenu(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    uFlags: u32,
+    x: i32,
+    y: i32,
+    nReserved: i32,
+    hWnd: HWND,
+    prcRect: Option<&RECT>,
+) -> u32 {
+    menutrack::track_popup_menu(machine, hMenu, uFlags, x, y, hWnd).await
+}
+
+#[win32_derive::dllexport]
+pub async fn TrackPopupMenuEx(
+    machine: &mut Machine,
+    hMenu: HMENU,
+    uFlags: u32,
+    x: i32,
+    y: i32,
+    hwnd: HWND,
+    lptpm: u32,
+) -> u32 {
+    menutrack::track_popup_menu(machine, hMenu, uFlags, x, y, hwnd).await
+}
+
+/// Deliver a command chosen from a menu or by an accelerator.
+pub async fn menu_command(machine: &mut Machine, hwnd: HWND, id: u32, from_accelerator: bool) {
+    if from_accelerator {
+        // Accelerators for disabled menu items do nothing, and the app gets a
+        // chance to update the item states first.
+        let hmenu = GetMenu(machine, hwnd);
+        if let Some((found, index)) = find_item(&machine.state.user32.menus, hmenu, id, false) {
+            SendMessageA(machine, hwnd, WM::INITMENU as u32, hmenu.to_raw(), 0).await;
+            let enabled = machine
+                .state
+                .user32
+                .menus
+                .get(found)
+                .and_then(|menu| menu.items.get(index))
+                .is_none_or(|item| item.is_enabled());
+            if !enabled {
+                return;
+            }
+        }
+        SendMessageA(machine, hwnd, WM::COMMAND as u32, (1 << 16) | id, 0).await;
+    } else {
+        super::PostMessageA(machine, hwnd, WM::COMMAND as u32, id, 0);
+    }
+}
+
+const FVIRTKEY: u8 = 0x01;
+const FSHIFT: u8 = 0x04;
+const FCONTROL: u8 = 0x08;
+const FALT: u8 = 0x10;
+/// Marks the last entry of an accelerator table resource.
+const ACCEL_END: u16 = 0x80;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ACCEL {
+    fVirt: u8,
+    key: u16,
+    cmd: u16,
+}
+unsafe impl memory::Pod for ACCEL {}
+
+fn load_accelerators(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    name: ResourceKey<&Str16>,
+) -> HACCEL {
+    let Some(buf) = kernel32::find_resource(
+        &machine.state.kernel32,
+        machine.mem(),
+        hInstance,
+        ResourceKey::Id(pe::RT::ACCELERATOR as u32),
+        &name,
+    ) else {
+        return HACCEL::null();
+    };
+    // Resource entries are four words: flags, key, command, and padding.
+    let buf = machine.mem().slice(buf);
+    let mut table = Vec::new();
+    for ofs in (0..buf.len() as u32).step_by(8) {
+        let flags = buf.get_pod::<u16>(ofs);
+        table.push(ACCEL {
+            fVirt: flags as u8 & !(ACCEL_END as u8),
+            key: buf.get_pod::<u16>(ofs + 2),
+            cmd: buf.get_pod::<u16>(ofs + 4),
+        });
+        if flags & ACCEL_END != 0 {
+            break;
+        }
+    }
+    machine.state.user32.accelerators.add(table)
+}
+
+#[win32_derive::dllexport]
+pub fn LoadAcceleratorsA(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpTableName: ResourceKey<&str>,
+) -> HACCEL {
+    load_accelerators(machine, hInstance, lpTableName.to_string16().as_ref())
+}
+
+#[win32_derive::dllexport]
+pub fn LoadAcceleratorsW(
+    machine: &mut Machine,
+    hInstance: HINSTANCE,
+    lpTableName: ResourceKey<&Str16>,
+) -> HACCEL {
+    load_accelerators(machine, hInstance, lpTableName)
+}
+
+#[win32_derive::dllexport]
+pub fn CreateAcceleratorTableA(machine: &mut Machine, paccel: u32, cAccel: i32) -> HACCEL {
+    let table = machine
+        .mem()
+        .iter_pod::<ACCEL>(paccel, cAccel as u32)
+        .collect();
+    machine.state.user32.accelerators.add(table)
+}
+
+#[win32_derive::dllexport]
+pub fn CreateAcceleratorTableW(machine: &mut Machine, paccel: u32, cAccel: i32) -> HACCEL {
+    CreateAcceleratorTableA(machine, paccel, cAccel)
+}
+
+#[win32_derive::dllexport]
+pub fn DestroyAcceleratorTable(machine: &mut Machine, hAccel: HACCEL) -> bool {
+    machine.state.user32.accelerators.remove(hAccel).is_some()
+}
+
+/// Find the command of the accelerator matching a key message, if any.
+fn find_accelerator(machine: &Machine, table: &[ACCEL], msg: &MSG) -> Option<u16> {
+    let keyboard = &machine.state.user32.keyboard;
+    let modifiers = |accel: &ACCEL| {
+        keyboard.is_down(VK_SHIFT) == (accel.fVirt & FSHIFT != 0)
+            && keyboard.is_down(VK_CONTROL) == (accel.fVirt & FCONTROL != 0)
+            && keyboard.is_down(VK_MENU) == (accel.fVirt & FALT != 0)
+    };
+    let accel = match WM::try_from(msg.message) {
+        Ok(WM::KEYDOWN | WM::SYSKEYDOWN) => table.iter().find(|accel| {
+            accel.fVirt & FVIRTKEY != 0 && accel.key as u32 == msg.wParam && modifiers(accel)
+        }),
+        Ok(WM::CHAR | WM::SYSCHAR) => {
+            let alt = msg.message == WM::SYSCHAR as u32;
+            table.iter().find(|accel| {
+                accel.fVirt & FVIRTKEY == 0
+                    && accel.key as u32 == msg.wParam
+                    && (accel.fVirt & FALT != 0) == alt
+            })
+        }
+        _ => None,
+    }?;
+    Some(accel.cmd)
+}
+
+#[win32_derive::dllexport]
+pub async fn TranslateAcceleratorA(
+    machine: &mut Machine,
+    hWnd: HWND,
+    hAccTable: HACCEL,
+    lpMsg: Option<&MSG>,
+) -> bool {
+    let msg = lpMsg.unwrap();
+    let Some(table) = machine.state.user32.accelerators.get(hAccTable) else {
+        return false;
+    };
+    let Some(cmd) = find_accelerator(machine, table, msg) else {
+        return false;
+    };
+    menu_command(machine, hWnd, cmd as u32, true).await;
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn TranslateAcceleratorW(
+    machine: &mut Machine,
+    hWnd: HWND,
+    hAccTable: HACCEL,
+    lpMsg: Option<&MSG>,
+) -> bool {
+    TranslateAcceleratorA(machine, hWnd, hAccTable, lpMsg).await
 }
@@ -0,0 +1,165 @@
+//! Parsing of MENU and MENUEX templates, as found in RT_MENU resources.
+//! https://learn.microsoft.com/en-us/windows/win32/menurc/resource-file-formats
+
+use super::dlgtemplate::Reader;
+
+/// MF_POPUP: the item opens a submenu, whose items follow it.
+const MF_POPUP: u32 = 0x10;
+/// MF_END: the last item of a (sub)menu.  The same bit is MF_HILITE at runtime.
+const MF_END: u32 = 0x80;
+/// MF_SEPARATOR.
+const MF_SEPARATOR: u32 = 0x800;
+
+/// MENUEX item bResInfo flags.
+const MENUEX_POPUP: u16 = 0x01;
+const MENUEX_END: u16 = 0x80;
+
+#[derive(Debug)]
+pub struct MenuTemplateItem {
+    /// MF_* type and state flags, e.g. MF_GRAYED or MF_SEPARATOR.
+    pub flags: u32,
+    pub id: u32,
+    pub text: String,
+    /// The items of the submenu opened by this item, if any.
+    pub popup: Option<Vec<MenuTemplateItem>>,
+}
+
+pub fn parse(buf: &[u8]) -> Option<Vec<MenuTemplateItem>> {
+    let mut r = Reader { buf, ofs: 0 };
+    let version = r.read::<u16>()?;
+    let offset = r.read::<u16>()? as usize;
+    match version {
+        0 => {
+            r.ofs += offset;
+            parse_items(&mut r)
+        }
+        1 => {
+            r.ofs = 4 + offset;
+            parse_items_ex(&mut r)
+        }
+        _ => {
+            log::warn!("unknown menu template version {version}");
+            None
+        }
+    }
+}
+
+fn parse_items(r: &mut Reader) -> Option<Vec<MenuTemplateItem>> {
+    let mut items = Vec::new();
+    loop {
+        let flags = r.read::<u16>()? as u32;
+        let popup = flags & MF_POPUP != 0;
+        let id = if popup { 0 } else { r.read::<u16>()? as u32 };
+        let text = r.sz()?;
+        let mut item = MenuTemplateItem {
+            flags: flags & !MF_END,
+            id,
+            text,
+            popup: None,
+        };
+        if popup {
+            item.popup = Some(parse_items(r)?);
+        } else if item.flags == 0 && id == 0 && item.text.is_empty() {
+            // MENUITEM SEPARATOR is compiled as an empty item.
+            item.flags = MF_SEPARATOR;
+        }
+        items.push(item);
+        if flags & MF_END != 0 {
+            return Some(items);
+        }
+    }
+}
+
+fn parse_items_ex(r: &mut Reader) -> Option<Vec<MenuTemplateItem>> {
+    let mut items = Vec::new();
+    loop {
+        r.align(4);
+        let typ = r.read::<u32>()?;
+        let state = r.read::<u32>()?;
+        let id = r.read::<u32>()?;
+        let res_info = r.read::<u16>()?;
+        let text = r.sz()?;
+        let mut item = MenuTemplateItem {
+            flags: typ | state,
+            id,
+            text,
+            popup: None,
+        };
+        if res_info & MENUEX_POPUP != 0 {
+            r.align(4);
+            let _help_id = r.read::<u32>()?;
+            item.flags |= MF_POPUP;
+            item.popup = Some(parse_items_ex(r)?);
+        }
+        items.push(item);
+        if res_info & MENUEX_END != 0 {
+            return Some(items);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dlgtemplate::test_util::*;
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mut buf = Vec::new();
+        push_u16s(&mut buf, &[0, 0]);
+        push_u16s(&mut buf, &[0x10]); // POPUP
+        push_str(&mut buf, "&Game");
+        push_u16s(&mut buf, &[0, 510]);
+        push_str(&mut buf, "&New\tF2");
+        push_u16s(&mut buf, &[0, 0]);
+        push_str(&mut buf, ""); // SEPARATOR
+        push_u16s(&mut buf, &[0x08 | 0x80, 521]); // CHECKED, END
+        push_str(&mut buf, "&Marks (?)");
+        push_u16s(&mut buf, &[0x80 | 0x01, 590]); // END, GRAYED
+        push_str(&mut buf, "&Help");
+
+        let items = parse(&buf).unwrap();
+        assert_eq!(items.len(), 2);
+        let game = &items[0];
+        assert_eq!(game.text, "&Game");
+        let popup = game.popup.as_ref().unwrap();
+        assert_eq!(popup.len(), 3);
+        assert_eq!((popup[0].id, popup[0].text.as_str()), (510, "&New\tF2"));
+        assert_eq!(popup[1].flags, MF_SEPARATOR);
+        assert_eq!((popup[2].id, popup[2].flags), (521, 0x08));
+        assert_eq!((items[1].id, items[1].flags), (590, 0x01));
+        assert!(items[1].popup.is_none());
+    }
+
+    #[test]
+    fn test_parse_ex() {
+        let mut buf = Vec::new();
+        push_u16s(&mut buf, &[1, 4]);
+        push_u32s(&mut buf, &[0]); // help id
+        push_u32s(&mut buf, &[0, 0, 0]);
+        push_u16s(&mut buf, &[MENUEX_POPUP | MENUEX_END]);
+        push_str(&mut buf, "&File");
+        align(&mut buf);
+        push_u32s(&mut buf, &[0]); // help id
+        push_u32s(&mut buf, &[0, 3, 100]); // MFS_GRAYED
+        push_u16s(&mut buf, &[0]);
+        push_str(&mut buf, "&Open");
+        align(&mut buf);
+        push_u32s(&mut buf, &[MF_SEPARATOR, 0, 0]);
+        push_u16s(&mut buf, &[0]);
+        push_str(&mut buf, "");
+        align(&mut buf);
+        push_u32s(&mut buf, &[0, 0, 101]);
+        push_u16s(&mut buf, &[MENUEX_END]);
+        push_str(&mut buf, "E&xit");
+
+        let items = parse(&buf).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].flags, MF_POPUP);
+        let popup = items[0].popup.as_ref().unwrap();
+        assert_eq!(popup.len(), 3);
+        assert_eq!((popup[0].id, popup[0].flags), (100, 3));
+        assert_eq!(popup[1].flags, MF_SEPARATOR);
+        assert_eq!((popup[2].id, popup[2].text.as_str()), (101, "E&xit"));
+    }
+}
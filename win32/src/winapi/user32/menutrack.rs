@@ -0,0 +1,1079 @@
+//! Drawing and tracking of menus: the menu bar above a top-level window's client area,
+//! and the popup menus opened from it or by TrackPopupMenu.
+//!
+//! As in Windows, an open menu runs a modal loop that takes the mouse capture and
+//! interprets input until a command is chosen or the menu is dismissed.  Popups aren't
+//! windows here; they are drawn over their top-level window whenever it is flushed.
+
+use super::{
+    create_backing_store, draw_text, flush_window, get_message, menu_command, toplevel_origin,
+    Menu, MenuItem, PostQuitMessage, SendMessageA, SetCapture, COLOR, DT_CENTER, DT_RIGHT,
+    DT_SINGLELINE, DT_VCENTER, HMENU, MENU_HEIGHT, MF, MSG, VK_DOWN, VK_ESCAPE, VK_F10, VK_LEFT,
+    VK_MENU, VK_RETURN, VK_RIGHT, VK_UP, WM,
+};
+use crate::{
+    winapi::{
+        bitmap::Bitmap,
+        gdi32::{self, BkMode, DCTarget, COLORREF, DC, HDC, HGDIOBJ},
+        types::{HWND, POINT, RECT},
+    },
+    Machine,
+};
+use memory::Mem;
+use std::{cell::RefCell, rc::Rc};
+
+/// Horizontal space around the label of a menu bar item.
+const BAR_PADDING: i32 = 8;
+/// Width of a popup's raised frame, including a pixel of space inside it.
+const BORDER: i32 = 3;
+/// Space left of popup labels, where check marks go.
+const CHECK_WIDTH: i32 = 16;
+/// Space right of popup labels, where submenu arrows go.
+const ARROW_WIDTH: i32 = 16;
+/// Minimum space between a popup label and its accelerator text.
+const ACCEL_GAP: i32 = 16;
+const SEPARATOR_HEIGHT: i32 = 8;
+
+const TPM_CENTERALIGN: u32 = 0x0004;
+const TPM_RIGHTALIGN: u32 = 0x0008;
+const TPM_VCENTERALIGN: u32 = 0x0010;
+const TPM_BOTTOMALIGN: u32 = 0x0020;
+const TPM_RETURNCMD: u32 = 0x0100;
+
+/// WM_MENUSELECT flag for items selected with the mouse.
+const MF_MOUSESELECT: u32 = 0x8000;
+
+/// An open menu: the menu bar or a popup.
+struct Level {
+    menu: HMENU,
+    /// Position in the top-level window's client coordinates, where the menu bar
+    /// has negative y.
+    rect: RECT,
+    /// Item positions, relative to rect.
+    items: Vec<RECT>,
+    hilite: Option<usize>,
+    /// Rendered pixels of a popup; the menu bar draws into its window's menu_bar.
+    bitmap: Option<Rc<RefCell<Bitmap>>>,
+}
+
+/// The menus open during a menu loop.
+pub struct MenuTracking {
+    /// The top-level window the menus are drawn over.
+    pub hwnd: HWND,
+    /// The window receiving WM_INITMENUPOPUP, WM_MENUSELECT and WM_COMMAND.
+    notify: HWND,
+    /// Whether levels[0] is the menu bar, as opposed to a TrackPopupMenu popup.
+    bar: bool,
+    /// The open menus, each opened from the highlighted item of the previous one.
+    levels: Vec<Level>,
+}
+
+impl MenuTracking {
+    /// Copy the open popups into a frame of the top-level window, which has the
+    /// menu bar's rows above the client area.
+    pub(super) fn composite_popups(
+        &self,
+        mem: Mem,
+        frame: &mut [u8],
+        frame_width: u32,
+        menu_height: u32,
+        clip: RECT,
+    ) {
+        for level in &self.levels {
+            let Some(bitmap) = &level.bitmap else {
+                continue;
+            };
+            let rect = level.rect;
+            let visible = rect.clip(&clip);
+            if visible.left >= visible.right || visible.top >= visible.bottom {
+                continue;
+            }
+            let bitmap = bitmap.borrow();
+            let pixels = bitmap.pixels.bytes(mem);
+            let row_len = (visible.right - visible.left) as usize * 4;
+            for y in visible.top..visible.bottom {
+                let src = ((y - rect.top) as usize * bitmap.width as usize
+                    + (visible.left - rect.left) as usize)
+                    * 4;
+                let dst = ((y + menu_height as i32) as usize * frame_width as usize
+                    + visible.left as usize)
+                    * 4;
+                frame[dst..][..row_len].copy_from_slice(&pixels[src..][..row_len]);
+            }
+        }
+    }
+
+    /// Find the open menu and item under a point, innermost first.
+    fn hit_test(&self, pt: POINT) -> Option<(usize, Option<usize>)> {
+        self.levels
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, level)| level.rect.contains(pt))
+            .map(|(l, level)| {
+                let pt = POINT {
+                    x: pt.x - level.rect.left,
+                    y: pt.y - level.rect.top,
+                };
+                (l, level.items.iter().position(|r| r.contains(pt)))
+            })
+    }
+}
+
+fn tracking(machine: &mut Machine) -> &mut MenuTracking {
+    machine.state.user32.menu_tracking.as_mut().unwrap()
+}
+
+fn menu_items(machine: &Machine, hmenu: HMENU) -> Vec<MenuItem> {
+    machine
+        .state
+        .user32
+        .menus
+        .get(hmenu)
+        .map_or_else(Vec::new, |menu: &Menu| menu.items.clone())
+}
+
+/// The font menus are drawn with.
+fn menu_font(machine: &mut Machine) -> (HGDIOBJ, gdi32::Font) {
+    let state = &mut machine.state;
+    let hfont = state.user32.menu_font;
+    if let Some(gdi32::Object::Font(font)) = state.gdi32.objects.get(hfont) {
+        return (hfont, font.clone());
+    }
+    let font = gdi32::Font::new(&gdi32::font::SANS_SERIF);
+    let hfont = state.gdi32.objects.add(gdi32::Object::Font(font.clone()));
+    state.user32.menu_font = hfont;
+    (hfont, font)
+}
+
+/// Split an item's text into its label and the accelerator text following a tab.
+fn split_text(text: &str) -> (Vec<char>, Vec<char>) {
+    let (label, accel) = text.split_once('\t').unwrap_or((text, ""));
+    (label.chars().collect(), accel.chars().collect())
+}
+
+/// Width of a label as drawn, where '&' marks the following character.
+fn label_width(font: &gdi32::Font, label: &[char]) -> i32 {
+    let mut chars = Vec::with_capacity(label.len());
+    let mut iter = label.iter();
+    while let Some(&c) = iter.next() {
+        match c {
+            '&' => chars.extend(iter.next()),
+            c => chars.push(c),
+        }
+    }
+    font.text_width(&chars) as i32
+}
+
+/// Lay out the items of a menu bar; rects are relative to the bar.
+fn bar_layout(machine: &mut Machine, hmenu: HMENU, width: u32) -> Vec<RECT> {
+    let (_, font) = menu_font(machine);
+    let items = menu_items(machine, hmenu);
+    let mut x = 0;
+    let mut rects = Vec::with_capacity(items.len());
+    for item in &items {
+        let (label, _) = split_text(&item.text);
+        let right = x + label_width(&font, &label) + BAR_PADDING * 2;
+        rects.push(RECT {
+            left: x,
+            top: 0,
+            right,
+            bottom: MENU_HEIGHT as i32,
+        });
+        x = right;
+    }
+    // Items from the first MF_RIGHTJUSTIFY one on are pushed to the right edge.
+    // TODO: wrap items onto multiple rows when the window is too narrow.
+    if let Some(first) = items
+        .iter()
+        .position(|item| item.flags.contains(MF::RIGHTJUSTIFY))
+    {
+        let shift = std::cmp::max(width as i32 - x, 0);
+        for rect in &mut rects[first..] {
+            rect.left += shift;
+            rect.right += shift;
+        }
+    }
+    rects
+}
+
+/// Lay out the items of a popup, returning its size and the item rects within it.
+fn popup_layout(machine: &mut Machine, hmenu: HMENU) -> (POINT, Vec<RECT>) {
+    let (_, font) = menu_font(machine);
+    let items = menu_items(machine, hmenu);
+    let item_height = font.height() as i32 + 5;
+    let mut label = 0;
+    let mut accel = 0;
+    for item in items.iter().filter(|item| !item.is_separator()) {
+        let (l, a) = split_text(&item.text);
+        label = std::cmp::max(label, label_width(&font, &l));
+        accel = std::cmp::max(accel, font.text_width(&a) as i32);
+    }
+    let mut width = CHECK_WIDTH + label + ARROW_WIDTH;
+    if accel > 0 {
+        width += ACCEL_GAP + accel;
+    }
+    // TODO: MF_MENUBREAK columns.
+    let mut y = BORDER;
+    let rects = items
+        .iter()
+        .map(|item| {
+            let top = y;
+            y += if item.is_separator() {
+                SEPARATOR_HEIGHT
+            } else {
+                item_height
+            };
+            RECT {
+                left: BORDER,
+                top,
+                right: BORDER + width,
+                bottom: y,
+            }
+        })
+        .collect();
+    let size = POINT {
+        x: width + BORDER * 2,
+        y: y + BORDER,
+    };
+    (size, rects)
+}
+
+/// Begin drawing a menu into a bitmap.
+fn begin_draw(machine: &mut Machine, bitmap: &Rc<RefCell<Bitmap>>) -> HDC {
+    let (hfont, _) = menu_font(machine);
+    let mut dc = DC::new(DCTarget::Memory(bitmap.clone()));
+    dc.font = hfont;
+    dc.bk_mode = BkMode::TRANSPARENT;
+    machine.state.gdi32.dcs.add_dc(dc)
+}
+
+fn set_text_color(machine: &Machine, hdc: HDC, color: COLOR) {
+    let dc = machine.state.gdi32.dcs.get(hdc).unwrap();
+    dc.borrow_mut().text_color = color.to_colorref();
+}
+
+/// Draw a small monochrome image, given as rows where '#' marks a pixel.
+fn draw_glyph(machine: &mut Machine, hdc: HDC, x: i32, y: i32, rows: &[&str], color: COLORREF) {
+    for (dy, row) in rows.iter().enumerate() {
+        for (dx, _) in row.bytes().enumerate().filter(|&(_, c)| c == b'#') {
+            let (x, y) = (x + dx as i32, y + dy as i32);
+            let rect = RECT {
+                left: x,
+                top: y,
+                right: x + 1,
+                bottom: y + 1,
+            };
+            gdi32::fill_rect(machine, hdc, &rect, color);
+        }
+    }
+}
+
+const CHECK_GLYPH: [&str; 7] = [
+    "......#", ".....##", "#...###", "##.###.", "#####..", ".###...", "..#....",
+];
+const RADIO_GLYPH: [&str; 5] = [".###.", "#####", "#####", "#####", ".###."];
+const ARROW_GLYPH: [&str; 7] = ["#...", "##..", "###.", "####", "###.", "##..", "#..."];
+
+/// Draw an item of the menu bar or of a popup.
+fn draw_item(
+    machine: &mut Machine,
+    hdc: HDC,
+    item: &MenuItem,
+    rect: RECT,
+    hilite: bool,
+    popup: bool,
+) {
+    if item.is_separator() {
+        let y = (rect.top + rect.bottom) / 2 - 1;
+        for (y, color) in [(y, COLOR::BTNSHADOW), (y + 1, COLOR::BTNHIGHLIGHT)] {
+            let line = RECT {
+                left: rect.left,
+                top: y,
+                right: rect.right,
+                bottom: y + 1,
+            };
+            gdi32::fill_rect(machine, hdc, &line, color.to_colorref());
+        }
+        return;
+    }
+    if item.flags.contains(MF::OWNERDRAW) {
+        log::warn!("TODO: owner-drawn menu items");
+    }
+
+    if hilite {
+        gdi32::fill_rect(machine, hdc, &rect, COLOR::HIGHLIGHT.to_colorref());
+    }
+    let color = if !item.is_enabled() {
+        COLOR::GRAYTEXT
+    } else if hilite {
+        COLOR::HIGHLIGHTTEXT
+    } else {
+        COLOR::MENUTEXT
+    };
+    set_text_color(machine, hdc, color);
+    let (label, accel) = split_text(&item.text);
+    if !popup {
+        let mut rect = rect;
+        draw_text(
+            machine,
+            hdc,
+            &label,
+            &mut rect,
+            DT_CENTER | DT_VCENTER | DT_SINGLELINE,
+        );
+        return;
+    }
+
+    let mut text_rect = RECT {
+        left: rect.left + CHECK_WIDTH,
+        right: rect.right - ARROW_WIDTH,
+        ..rect
+    };
+    let mut label_rect = text_rect;
+    draw_text(
+        machine,
+        hdc,
+        &label,
+        &mut label_rect,
+        DT_VCENTER | DT_SINGLELINE,
+    );
+    if !accel.is_empty() {
+        draw_text(
+            machine,
+            hdc,
+            &accel,
+            &mut text_rect,
+            DT_RIGHT | DT_VCENTER | DT_SINGLELINE,
+        );
+    }
+
+    let color = color.to_colorref();
+    let middle = (rect.top + rect.bottom) / 2;
+    if item.flags.contains(MF::CHECKED) {
+        let glyph: &[&str] = if item.flags.contains(MF::RADIOCHECK) {
+            &RADIO_GLYPH
+        } else {
+            &CHECK_GLYPH
+        };
+        let x = rect.left + (CHECK_WIDTH - glyph[0].len() as i32) / 2;
+        let y = middle - glyph.len() as i32 / 2;
+        draw_glyph(machine, hdc, x, y, glyph, color);
+    }
+    if !item.submenu.is_null() {
+        let x = rect.right - ARROW_WIDTH + (ARROW_WIDTH - 4) / 2;
+        let y = middle - ARROW_GLYPH.len() as i32 / 2;
+        draw_glyph(machine, hdc, x, y, &ARROW_GLYPH, color);
+    }
+}
+
+/// Draw a window's menu bar, as seen above its client area.
+pub fn draw_menu_bar(machine: &mut Machine, hwnd: HWND) {
+    let Some(window) = machine.state.user32.windows.get(hwnd) else {
+        return;
+    };
+    let (hmenu, width) = {
+        let window = window.borrow();
+        (window.menu(), window.width)
+    };
+    if hmenu.is_null() {
+        return;
+    }
+    let hilite = machine
+        .state
+        .user32
+        .menu_tracking
+        .as_ref()
+        .filter(|tracking| tracking.bar && tracking.hwnd == hwnd)
+        .and_then(|tracking| tracking.levels.first())
+        .and_then(|level| level.hilite);
+
+    let rects = bar_layout(machine, hmenu, width);
+    let bitmap = create_backing_store(width, MENU_HEIGHT);
+    let hdc = begin_draw(machine, &bitmap);
+    let bar = RECT {
+        left: 0,
+        top: 0,
+        right: width as i32,
+        bottom: MENU_HEIGHT as i32,
+    };
+    gdi32::fill_rect(machine, hdc, &bar, COLOR::MENU.to_colorref());
+    for (i, item) in menu_items(machine, hmenu).iter().enumerate() {
+        draw_item(machine, hdc, item, rects[i], hilite == Some(i), false);
+    }
+    machine.state.gdi32.dcs.remove(hdc);
+
+    let window = machine.state.user32.windows.get(hwnd).unwrap();
+    window.borrow_mut().expect_toplevel_mut().menu_bar = Some(bitmap);
+    flush_window(machine, hwnd);
+}
+
+/// Draw an open popup.
+fn draw_popup(machine: &mut Machine, level: usize) {
+    let (hmenu, rect, rects, hilite) = {
+        let level = &tracking(machine).levels[level];
+        (level.menu, level.rect, level.items.clone(), level.hilite)
+    };
+    let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+    let bitmap = create_backing_store(width as u32, height as u32);
+    let hdc = begin_draw(machine, &bitmap);
+
+    // A two pixel raised frame around the menu color.
+    let frames = [
+        (0, COLOR::LIGHT3D, COLOR::DKSHADOW3D),
+        (1, COLOR::BTNHIGHLIGHT, COLOR::BTNSHADOW),
+    ];
+    for (inset, light, dark) in frames {
+        let (left, top, right, bottom) = (inset, inset, width - inset, height - inset);
+        let sides = [
+            (
+                RECT {
+                    left,
+                    top,
+                    right,
+                    bottom: top + 1,
+                },
+                light,
+            ),
+            (
+                RECT {
+                    left,
+                    top,
+                    right: left + 1,
+                    bottom,
+                },
+                light,
+            ),
+            (
+                RECT {
+                    left,
+                    top: bottom - 1,
+                    right,
+                    bottom,
+                },
+                dark,
+            ),
+            (
+                RECT {
+                    left: right - 1,
+                    top,
+                    right,
+                    bottom,
+                },
+                dark,
+            ),
+        ];
+        for (side, color) in sides {
+            gdi32::fill_rect(machine, hdc, &side, color.to_colorref());
+        }
+    }
+    let inside = RECT {
+        left: 2,
+        top: 2,
+        right: width - 2,
+        bottom: height - 2,
+    };
+    gdi32::fill_rect(machine, hdc, &inside, COLOR::MENU.to_colorref());
+    for (i, item) in menu_items(machine, hmenu).iter().enumerate() {
+        if let Some(&rect) = rects.get(i) {
+            draw_item(machine, hdc, item, rect, hilite == Some(i), true);
+        }
+    }
+    machine.state.gdi32.dcs.remove(hdc);
+    tracking(machine).levels[level].bitmap = Some(bitmap);
+}
+
+/// Redraw an open menu after its highlight changed.
+fn redraw_level(machine: &mut Machine, level: usize) {
+    let tracking = tracking(machine);
+    let hwnd = tracking.hwnd;
+    if tracking.bar && level == 0 {
+        draw_menu_bar(machine, hwnd);
+    } else {
+        draw_popup(machine, level);
+        flush_window(machine, hwnd);
+    }
+}
+
+/// Close the menus opened from a level.
+fn close_after(machine: &mut Machine, level: usize) {
+    let tracking = tracking(machine);
+    if tracking.levels.len() > level + 1 {
+        tracking.levels.truncate(level + 1);
+        let hwnd = tracking.hwnd;
+        flush_window(machine, hwnd);
+    }
+}
+
+/// Highlight an item of an open menu, closing any menus opened from it.
+async fn set_hilite(machine: &mut Machine, level: usize, hilite: Option<usize>, mouse: bool) {
+    close_after(machine, level);
+    let tracking = tracking(machine);
+    let notify = tracking.notify;
+    let level_ = &mut tracking.levels[level];
+    if level_.hilite == hilite {
+        return;
+    }
+    level_.hilite = hilite;
+    let hmenu = level_.menu;
+    redraw_level(machine, level);
+
+    let Some(index) = hilite else {
+        return;
+    };
+    let Some(item) = menu_items(machine, hmenu).into_iter().nth(index) else {
+        return;
+    };
+    let mut flags = item.flags.bits() | MF::HILITE.bits();
+    if mouse {
+        flags |= MF_MOUSESELECT;
+    }
+    // Items opening submenus are identified by position.
+    let id = if item.submenu.is_null() {
+        item.id
+    } else {
+        flags |= MF::POPUP.bits();
+        index as u32
+    };
+    let wparam = (flags << 16) | (id & 0xFFFF);
+    SendMessageA(
+        machine,
+        notify,
+        WM::MENUSELECT as u32,
+        wparam,
+        hmenu.to_raw(),
+    )
+    .await;
+}
+
+/// Where to put a popup.
+enum Placement {
+    /// At a point, aligned per TPM_* flags.
+    Point(POINT, u32),
+    /// Below an item of the menu bar.
+    Below(RECT),
+    /// Beside the item of a popup, given as the popup's left and right edges and
+    /// the item's top and bottom.
+    Beside(RECT),
+}
+
+/// Open a popup, after letting the application update it.
+async fn open_popup(machine: &mut Machine, hmenu: HMENU, index: u32, placement: Placement) {
+    let notify = tracking(machine).notify;
+    SendMessageA(
+        machine,
+        notify,
+        WM::INITMENUPOPUP as u32,
+        hmenu.to_raw(),
+        index,
+    )
+    .await;
+
+    let (size, items) = popup_layout(machine, hmenu);
+    let hwnd = tracking(machine).hwnd;
+    let (client_width, client_height, menu_height) = {
+        let window = machine.state.user32.windows.get(hwnd).unwrap().borrow();
+        (
+            window.width as i32,
+            window.height as i32,
+            window.menu_height() as i32,
+        )
+    };
+    let (x, y) = match placement {
+        Placement::Point(pt, flags) => {
+            let x = if flags & TPM_RIGHTALIGN != 0 {
+                pt.x - size.x
+            } else if flags & TPM_CENTERALIGN != 0 {
+                pt.x - size.x / 2
+            } else {
+                pt.x
+            };
+            let y = if flags & TPM_BOTTOMALIGN != 0 {
+                pt.y - size.y
+            } else if flags & TPM_VCENTERALIGN != 0 {
+                pt.y - size.y / 2
+            } else {
+                pt.y
+            };
+            (x, y)
+        }
+        Placement::Below(item) => (item.left, item.bottom),
+        Placement::Beside(item) => {
+            let x = if item.right - BORDER + size.x <= client_width {
+                item.right - BORDER
+            } else {
+                item.left - size.x + BORDER
+            };
+            (x, item.top - BORDER)
+        }
+    };
+    // Keep the popup within the window, as that is all we can draw on.
+    // TODO: popups larger than the window are cut off.
+    let x = std::cmp::max(std::cmp::min(x, client_width - size.x), 0);
+    let y = std::cmp::max(std::cmp::min(y, client_height - size.y), -menu_height);
+    let rect = RECT {
+        left: x,
+        top: y,
+        right: x + size.x,
+        bottom: y + size.y,
+    };
+
+    let tracking = tracking(machine);
+    tracking.levels.push(Level {
+        menu: hmenu,
+        rect,
+        items,
+        hilite: None,
+        bitmap: None,
+    });
+    let level = tracking.levels.len() - 1;
+    draw_popup(machine, level);
+    flush_window(machine, hwnd);
+}
+
+/// Open the submenu of the highlighted item of an open menu, if it has one.
+/// Returns whether a submenu was opened.
+async fn open_submenu(machine: &mut Machine, level: usize) -> bool {
+    let tracking = tracking(machine);
+    let bar = tracking.bar && level == 0;
+    let level_ = &tracking.levels[level];
+    let Some(index) = level_.hilite else {
+        return false;
+    };
+    let parent = level_.rect;
+    let item_rect = level_.items[index];
+    let hmenu = level_.menu;
+    let Some(item) = menu_items(machine, hmenu).into_iter().nth(index) else {
+        return false;
+    };
+    if item.submenu.is_null() || !item.is_enabled() {
+        return false;
+    }
+    let placement = if bar {
+        Placement::Below(RECT {
+            left: parent.left + item_rect.left,
+            top: parent.top + item_rect.top,
+            right: parent.left + item_rect.right,
+            bottom: parent.top + item_rect.bottom,
+        })
+    } else {
+        Placement::Beside(RECT {
+            left: parent.left,
+            top: parent.top + item_rect.top,
+            right: parent.right,
+            bottom: parent.top + item_rect.bottom,
+        })
+    };
+    open_popup(machine, item.submenu, index as u32, placement).await;
+    machine
+        .state
+        .user32
+        .menu_tracking
+        .as_ref()
+        .is_some_and(|tracking| tracking.levels.len() > level + 1)
+}
+
+/// The next item that can be highlighted, skipping separators, wrapping around.
+fn next_item(items: &[MenuItem], from: Option<usize>, forward: bool) -> Option<usize> {
+    let len = items.len();
+    let mut i = from;
+    for _ in 0..len {
+        let next = match (i, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+        };
+        if !items[next].is_separator() {
+            return Some(next);
+        }
+        i = Some(next);
+    }
+    None
+}
+
+/// Highlight the first item of an open menu.
+async fn hilite_first(machine: &mut Machine, level: usize) {
+    let hmenu = tracking(machine).levels[level].menu;
+    let first = next_item(&menu_items(machine, hmenu), None, true);
+    set_hilite(machine, level, first, false).await;
+}
+
+/// Choose an item from the keyboard: open its submenu, or return its command.
+async fn activate(machine: &mut Machine, level: usize, index: usize) -> Option<u32> {
+    let hmenu = tracking(machine).levels[level].menu;
+    let item = menu_items(machine, hmenu).into_iter().nth(index)?;
+    if item.is_separator() || !item.is_enabled() {
+        return None;
+    }
+    set_hilite(machine, level, Some(index), false).await;
+    if item.submenu.is_null() {
+        return Some(item.id);
+    }
+    if open_submenu(machine, level).await {
+        hilite_first(machine, level + 1).await;
+    }
+    None
+}
+
+/// Move to the neighboring item of the menu bar, keeping a popup open if one was.
+async fn bar_neighbor(machine: &mut Machine, forward: bool) {
+    let tracking = tracking(machine);
+    let was_open = tracking.levels.len() > 1;
+    let (hmenu, hilite) = (tracking.levels[0].menu, tracking.levels[0].hilite);
+    let next = next_item(&menu_items(machine, hmenu), hilite, forward);
+    set_hilite(machine, 0, next, false).await;
+    if was_open && open_submenu(machine, 0).await {
+        hilite_first(machine, 1).await;
+    }
+}
+
+/// What ended a message during the menu loop.
+enum Outcome {
+    Continue,
+    Cancel,
+    Command(u32),
+}
+
+async fn handle_mouse(machine: &mut Machine, msg: &MSG, pressed: &mut bool) -> Outcome {
+    let pt = POINT {
+        x: msg.lParam as u16 as i16 as i32,
+        y: (msg.lParam >> 16) as u16 as i16 as i32,
+    };
+    let (bar, depth, hit) = {
+        let tracking = tracking(machine);
+        let hit = tracking
+            .hit_test(pt)
+            .map(|(level, item)| (level, tracking.levels[level].menu, item));
+        (tracking.bar, tracking.levels.len(), hit)
+    };
+    // Separators can't be highlighted.
+    let hit = hit.map(|(level, hmenu, item)| {
+        let items = menu_items(machine, hmenu);
+        (level, item.filter(|&i| !items[i].is_separator()))
+    });
+
+    match WM::try_from(msg.message) {
+        Ok(WM::MOUSEMOVE) => {
+            let Some((level, item)) = hit else {
+                return Outcome::Continue;
+            };
+            if bar && level == 0 {
+                // Hovering over the menu bar only switches between menus once one is open.
+                if item.is_some() && (depth > 1 || *pressed) && item != level_hilite(machine, 0) {
+                    set_hilite(machine, 0, item, true).await;
+                    open_submenu(machine, 0).await;
+                }
+            } else if item != level_hilite(machine, level) {
+                set_hilite(machine, level, item, true).await;
+                open_submenu(machine, level).await;
+            }
+        }
+        Ok(WM::LBUTTONDOWN | WM::RBUTTONDOWN) => {
+            *pressed = true;
+            let Some((level, item)) = hit else {
+                return Outcome::Cancel;
+            };
+            if bar && level == 0 {
+                // Clicking the item of the open menu closes it.
+                if item.is_some() && item == level_hilite(machine, 0) && depth > 1 {
+                    return Outcome::Cancel;
+                }
+            } else if item == level_hilite(machine, level) {
+                return Outcome::Continue;
+            }
+            set_hilite(machine, level, item, true).await;
+            open_submenu(machine, level).await;
+        }
+        Ok(WM::LBUTTONUP | WM::RBUTTONUP) => {
+            *pressed = false;
+            let Some((level, Some(index))) = hit else {
+                return Outcome::Continue;
+            };
+            if bar && level == 0 {
+                return Outcome::Continue;
+            }
+            let hmenu = tracking(machine).levels[level].menu;
+            if let Some(item) = menu_items(machine, hmenu).into_iter().nth(index) {
+                if item.submenu.is_null() && item.is_enabled() {
+                    return Outcome::Command(item.id);
+                }
+            }
+        }
+        _ => {}
+    }
+    Outcome::Continue
+}
+
+fn level_hilite(machine: &mut Machine, level: usize) -> Option<usize> {
+    tracking(machine).levels[level].hilite
+}
+
+async fn handle_key(machine: &mut Machine, msg: &MSG) -> Outcome {
+    let tracking = tracking(machine);
+    let bar = tracking.bar;
+    let level = tracking.levels.len() - 1;
+    let on_bar = bar && level == 0;
+    // Popups opened from other popups, as opposed to from the bar or by TrackPopupMenu.
+    let nested = level > bar as usize;
+    let hmenu = tracking.levels[level].menu;
+    let hilite = tracking.levels[level].hilite;
+    let items = menu_items(machine, hmenu);
+
+    match msg.wParam as u8 {
+        VK_ESCAPE => {
+            if level == 0 {
+                return Outcome::Cancel;
+            }
+            close_after(machine, level - 1);
+        }
+        VK_MENU | VK_F10 => return Outcome::Cancel,
+        VK_UP | VK_DOWN if on_bar => {
+            if open_submenu(machine, 0).await {
+                hilite_first(machine, 1).await;
+            }
+        }
+        VK_UP | VK_DOWN => {
+            let next = next_item(&items, hilite, msg.wParam as u8 == VK_DOWN);
+            set_hilite(machine, level, next, false).await;
+        }
+        VK_LEFT if on_bar => bar_neighbor(machine, false).await,
+        VK_LEFT if nested => close_after(machine, level - 1),
+        VK_LEFT if bar => bar_neighbor(machine, false).await,
+        VK_RIGHT if on_bar => bar_neighbor(machine, true).await,
+        VK_RIGHT => {
+            if open_submenu(machine, level).await {
+                hilite_first(machine, level + 1).await;
+            } else if bar {
+                bar_neighbor(machine, true).await;
+            }
+        }
+        VK_RETURN => {
+            if let Some(index) = hilite {
+                if let Some(id) = activate(machine, level, index).await {
+                    return Outcome::Command(id);
+                }
+            }
+        }
+        _ => {
+            // Items are chosen by their mnemonic characters.
+            let Some(char_msg) = machine.state.user32.keyboard.translate_message(msg) else {
+                return Outcome::Continue;
+            };
+            let c = char::from_u32(char_msg.wParam).map(|c| c.to_ascii_lowercase());
+            let found = items.iter().position(|item| item.mnemonic() == c);
+            if let Some(index) = found {
+                if let Some(id) = activate(machine, level, index).await {
+                    return Outcome::Command(id);
+                }
+            }
+        }
+    }
+    Outcome::Continue
+}
+
+/// Run the menu loop until a command is chosen or the menus are dismissed.
+async fn track(machine: &mut Machine, mut pressed: bool) -> Option<u32> {
+    loop {
+        let hwnd = tracking(machine).hwnd;
+        // The window may be destroyed by messages dispatched during the loop.
+        machine.state.user32.windows.get(hwnd)?;
+        let mut msg = MSG {
+            hwnd: HWND::null(),
+            message: 0,
+            wParam: 0,
+            lParam: 0,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        };
+        get_message(machine, Some(&mut msg), HWND::null(), 0, 0).await;
+        let outcome = match WM::try_from(msg.message) {
+            Ok(WM::QUIT) => {
+                // Leave it for the application's message loop.
+                PostQuitMessage(machine, msg.wParam as i32);
+                Outcome::Cancel
+            }
+            Ok(
+                WM::MOUSEMOVE | WM::LBUTTONDOWN | WM::LBUTTONUP | WM::RBUTTONDOWN | WM::RBUTTONUP,
+            ) if msg.hwnd == hwnd => handle_mouse(machine, &msg, &mut pressed).await,
+            Ok(WM::KEYDOWN | WM::SYSKEYDOWN) => handle_key(machine, &msg).await,
+            // Other input is swallowed while the menu is open.
+            Ok(WM::KEYUP | WM::SYSKEYUP | WM::CHAR | WM::SYSCHAR) => Outcome::Continue,
+            _ if (0x0200..=0x020D).contains(&msg.message) || msg.hwnd.is_null() => {
+                Outcome::Continue
+            }
+            _ => {
+                super::dispatch_message(machine, &msg).await;
+                Outcome::Continue
+            }
+        };
+        match outcome {
+            Outcome::Continue => {}
+            Outcome::Cancel => return None,
+            Outcome::Command(id) => return Some(id),
+        }
+    }
+}
+
+async fn begin_tracking(machine: &mut Machine, hwnd: HWND, notify: HWND, bar: bool) -> HWND {
+    machine.state.user32.menu_tracking = Some(MenuTracking {
+        hwnd,
+        notify,
+        bar,
+        levels: Vec::new(),
+    });
+    let capture = SetCapture(machine, hwnd);
+    SendMessageA(machine, notify, WM::ENTERMENULOOP as u32, !bar as u32, 0).await;
+    capture
+}
+
+async fn end_tracking(machine: &mut Machine, capture: HWND) {
+    let tracking = machine.state.user32.menu_tracking.take().unwrap();
+    machine.state.user32.capture = capture;
+    if tracking.bar {
+        draw_menu_bar(machine, tracking.hwnd);
+    } else {
+        flush_window(machine, tracking.hwnd);
+    }
+    let notify = tracking.notify;
+    SendMessageA(machine, notify, WM::MENUSELECT as u32, 0xFFFF << 16, 0).await;
+    let popup = !tracking.bar as u32;
+    SendMessageA(machine, notify, WM::EXITMENULOOP as u32, popup, 0).await;
+}
+
+/// How the menu bar was activated.
+pub enum MenuBarStart {
+    /// A click at a point in client coordinates.
+    Mouse(POINT),
+    /// The Alt or F10 key, possibly with a mnemonic character.
+    Key(Option<char>),
+}
+
+/// Track the menu bar of a top-level window, per WM_SYSCOMMAND's SC_MOUSEMENU and SC_KEYMENU.
+pub async fn track_menu_bar(machine: &mut Machine, hwnd: HWND, start: MenuBarStart) {
+    if machine.state.user32.menu_tracking.is_some() {
+        return;
+    }
+    let Some(window) = machine.state.user32.windows.get(hwnd) else {
+        return;
+    };
+    let (hmenu, width) = {
+        let window = window.borrow();
+        (window.menu(), window.width)
+    };
+    let items = menu_items(machine, hmenu);
+    if items.is_empty() {
+        return;
+    }
+    let mnemonic = match start {
+        MenuBarStart::Key(Some(c)) => {
+            let c = c.to_ascii_lowercase();
+            match items.iter().position(|item| item.mnemonic() == Some(c)) {
+                Some(index) => Some(index),
+                None => return,
+            }
+        }
+        _ => None,
+    };
+
+    let capture = begin_tracking(machine, hwnd, hwnd, true).await;
+    SendMessageA(machine, hwnd, WM::INITMENU as u32, hmenu.to_raw(), 0).await;
+    let items = bar_layout(machine, hmenu, width);
+    let rect = RECT {
+        left: 0,
+        top: -(MENU_HEIGHT as i32),
+        right: width as i32,
+        bottom: 0,
+    };
+    tracking(machine).levels.push(Level {
+        menu: hmenu,
+        rect,
+        items,
+        hilite: None,
+        bitmap: None,
+    });
+
+    let mut command = None;
+    let mut pressed = false;
+    let mut done = false;
+    match start {
+        MenuBarStart::Mouse(pt) => match tracking(machine).hit_test(pt) {
+            Some((0, Some(index))) => {
+                pressed = true;
+                set_hilite(machine, 0, Some(index), true).await;
+                open_submenu(machine, 0).await;
+            }
+            _ => done = true,
+        },
+        MenuBarStart::Key(_) => match mnemonic {
+            Some(index) => {
+                command = activate(machine, 0, index).await;
+                done = command.is_some();
+            }
+            None => hilite_first(machine, 0).await,
+        },
+    }
+    if !done {
+        command = track(machine, pressed).await;
+    }
+    end_tracking(machine, capture).await;
+    if let Some(id) = command {
+        menu_command(machine, hwnd, id, false).await;
+    }
+}
+
+/// TrackPopupMenu: show a popup at a point in screen coordinates, which are the
+/// client coordinates of the top-level window.
+pub async fn track_popup_menu(
+    machine: &mut Machine,
+    hmenu: HMENU,
+    flags: u32,
+    x: i32,
+    y: i32,
+    hwnd: HWND,
+) -> u32 {
+    if machine.state.user32.menu_tracking.is_some()
+        || machine.state.user32.menus.get(hmenu).is_none()
+    {
+        return 0;
+    }
+    let Some((top, _)) = toplevel_origin(&machine.state.user32.windows, hwnd) else {
+        return 0;
+    };
+    let capture = begin_tracking(machine, top, hwnd, false).await;
+    let pt = POINT { x, y };
+    open_popup(machine, hmenu, 0, Placement::Point(pt, flags)).await;
+    let command = track(machine, false).await;
+    end_tracking(machine, capture).await;
+    match command {
+        Some(id) if flags & TPM_RETURNCMD != 0 => id,
+        Some(id) => {
+            menu_command(machine, hwnd, id, false).await;
+            1
+        }
+        None => 0,
+    }
+}
+
+/// GetMenuItemRect: the position of an item of a window's menu bar or of an open popup,
+/// in screen coordinates.
+pub fn item_rect(machine: &mut Machine, hwnd: HWND, hmenu: HMENU, index: usize) -> Option<RECT> {
+    let window = machine.state.user32.windows.get(hwnd)?;
+    let (bar_menu, width) = {
+        let window = window.borrow();
+        (window.menu(), window.width)
+    };
+    let (origin, rect) = if !hmenu.is_null() && hmenu == bar_menu {
+        let origin = POINT {
+            x: 0,
+            y: -(MENU_HEIGHT as i32),
+        };
+        (origin, *bar_layout(machine, hmenu, width).get(index)?)
+    } else {
+        let tracking = machine.state.user32.menu_tracking.as_ref()?;
+        let level = tracking.levels.iter().find(|level| level.menu == hmenu)?;
+        (level.rect.origin(), *level.items.get(index)?)
+    };
+    Some(rect.add(origin))
+}
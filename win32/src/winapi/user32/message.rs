@@ -1,16 +1,25 @@
-use super::{Timers, Window};
+use super::{GetDlgItem, Keyboard, Window, WS};
 use crate::{
     host,
     winapi::{
         handle::Handles,
-        kernel32::{self, GetCurrentThreadId},
+        kernel32::{
+            self, current_thread, set_last_error, EventObject, GetCurrentThreadId, KernelObject,
+            HEVENT, HTHREAD,
+        },
         types::*,
+        winmm, ERROR,
     },
-    Host, Machine, MouseButton,
+    Machine, MouseButton,
 };
 use bitflags::bitflags;
 use memory::Extensions;
-use std::{cell::RefCell, ops::RangeInclusive, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    ops::RangeInclusive,
+    rc::Rc,
+};
 
 #[repr(C)]
 #[derive(Clone)]
@@ -49,20 +58,58 @@ impl std::fmt::Debug for MSG {
 pub enum WM {
     NULL = 0,
     CREATE = 0x0001,
+    DESTROY = 0x0002,
     MOVE = 0x0003,
     SIZE = 0x0005,
     ACTIVATE = 0x0006,
+    SETFOCUS = 0x0007,
+    KILLFOCUS = 0x0008,
+    ENABLE = 0x000A,
     SETTEXT = 0x000C,
+    GETTEXT = 0x000D,
+    GETTEXTLENGTH = 0x000E,
     PAINT = 0x000F,
     CLOSE = 0x0010,
     QUIT = 0x0012,
     ACTIVATEAPP = 0x001C,
+    DRAWITEM = 0x002B,
+    SETFONT = 0x0030,
+    GETFONT = 0x0031,
     WINDOWPOSCHANGED = 0x0047,
+    NOTIFY = 0x004E,
     SETICON = 0x0080,
+    GETDLGCODE = 0x0087,
+    NCCALCSIZE = 0x0083,
+    NCHITTEST = 0x0084,
+    NCPAINT = 0x0085,
+    NCMOUSEMOVE = 0x00A0,
+    NCLBUTTONDOWN = 0x00A1,
+    NCLBUTTONUP = 0x00A2,
+    NCLBUTTONDBLCLK = 0x00A3,
+    NCRBUTTONDOWN = 0x00A4,
+    NCRBUTTONUP = 0x00A5,
     KEYDOWN = 0x0100,
     KEYUP = 0x0101,
     CHAR = 0x0102,
+    SYSKEYDOWN = 0x0104,
+    SYSKEYUP = 0x0105,
+    SYSCHAR = 0x0106,
+    INITDIALOG = 0x0110,
+    COMMAND = 0x0111,
+    SYSCOMMAND = 0x0112,
     TIMER = 0x0113,
+    HSCROLL = 0x0114,
+    VSCROLL = 0x0115,
+    INITMENU = 0x0116,
+    INITMENUPOPUP = 0x0117,
+    MENUSELECT = 0x011F,
+    CTLCOLORMSGBOX = 0x0132,
+    CTLCOLOREDIT = 0x0133,
+    CTLCOLORLISTBOX = 0x0134,
+    CTLCOLORBTN = 0x0135,
+    CTLCOLORDLG = 0x0136,
+    CTLCOLORSCROLLBAR = 0x0137,
+    CTLCOLORSTATIC = 0x0138,
     MOUSEMOVE = 0x0200,
     LBUTTONDOWN = 0x0201,
     LBUTTONUP = 0x0202,
@@ -73,10 +120,12 @@ pub enum WM {
     MBUTTONDOWN = 0x0207,
     MBUTTONUP = 0x0208,
     MBUTTONDBLCLK = 0x0209,
+    ENTERMENULOOP = 0x0211,
+    EXITMENULOOP = 0x0212,
     USER = 0x0400,
 }
 
-fn msg_from_message(message: host::Message) -> MSG {
+fn msg_from_message(keyboard: &mut Keyboard, message: host::Message) -> MSG {
     let mut msg = MSG {
         hwnd: HWND::from_raw(message.hwnd),
         message: WM::QUIT as u32, // will be overwritten
@@ -106,106 +155,235 @@ fn msg_from_message(message: host::Message) -> MSG {
             msg.pt_x = mouse.x;
             msg.pt_y = mouse.y;
         }
+        host::MessageDetail::Key(key) => {
+            msg = MSG {
+                hwnd: msg.hwnd,
+                ..keyboard.key_message(key)
+            };
+        }
     }
 
     msg
 }
 
-/// A Windows message queue.
+/// Host input arrives for top-level windows; redirect it to the window that should
+/// receive it: the capture or the window under the mouse for mouse messages, and the
+/// focus for keyboard messages.
+/// Uncaptured mouse input outside the client area of the window under the mouse, such
+/// as over the menu bar or a child window's border, becomes WM_NC* messages.
+fn route_input(state: &super::State, msg: &mut MSG) {
+    if msg.hwnd.is_null() {
+        return;
+    }
+    match msg.message {
+        0x0200..=0x020D => {
+            // The host surface includes the menu bar, above the client area.
+            // Screen coordinates are those of the top-level window's client area.
+            let menu_height = match state.windows.get(msg.hwnd) {
+                Some(window) => window.borrow().menu_height() as i32,
+                None => 0,
+            };
+            let pt = POINT {
+                x: msg.lParam as u16 as i16 as i32,
+                y: (msg.lParam >> 16) as u16 as i16 as i32 - menu_height,
+            };
+            msg.pt_y = pt.y as u32;
+            let hwnd = match super::toplevel_origin(&state.windows, state.capture) {
+                Some((top, _)) if top == msg.hwnd => state.capture,
+                _ => {
+                    let hwnd = super::child_window_from_point(state, msg.hwnd, pt);
+                    // PeekMessage can't call window procedures, so rather than sending
+                    // WM_NCHITTEST this uses DefWindowProc's hit testing.
+                    let hit = super::nc_hit_test(state, hwnd, pt);
+                    if hit != super::HTCLIENT
+                        && hit != super::HTNOWHERE
+                        && msg.message <= WM::MBUTTONDBLCLK as u32
+                    {
+                        msg.message = msg.message - WM::MOUSEMOVE as u32 + WM::NCMOUSEMOVE as u32;
+                        msg.hwnd = hwnd;
+                        msg.wParam = hit;
+                        msg.lParam = ((pt.y as u16 as u32) << 16) | pt.x as u16 as u32;
+                        return;
+                    }
+                    hwnd
+                }
+            };
+            let (_, origin) = super::toplevel_origin(&state.windows, hwnd).unwrap();
+            let pt = pt.sub(origin);
+            msg.hwnd = hwnd;
+            msg.lParam = ((pt.y as u16 as u32) << 16) | pt.x as u16 as u32;
+        }
+        0x0100..=0x0109 => {
+            if let Some((top, _)) = super::toplevel_origin(&state.windows, state.focus) {
+                if top == msg.hwnd {
+                    msg.hwnd = state.focus;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A message sent to a window by another thread.  The window procedure must run on
+/// the window's own thread, so the message waits in that thread's queue until the
+/// thread next looks at it.
+struct SentMessage {
+    msg: MSG,
+    /// The sending thread, woken when the reply is ready.
+    sender: HTHREAD,
+    /// Receives the window procedure's result; None for SendNotifyMessage, which
+    /// doesn't wait for one.
+    reply: Option<Rc<Cell<Option<u32>>>>,
+}
+
+/// A Windows message queue, one per thread that uses messages.
 /// At a high level just a queue of MSG, but there are particulars around painting and timers.
 /// https://learn.microsoft.com/en-us/windows/win32/winmsg/about-messages-and-message-queues
-/// The basic approach is that there are a few get_* methods that each can either peek or remove
-/// the requested message.  Internally, paint/timers are never actually inserted into a queue
-/// but the caller doesn't need to be aware.
-///
-/// TODO: should be per-thread.
-/// TODO: this generally doesn't support multiple HWNDs either,
-/// and will need to be revisited to make that work.
-#[derive(Default)]
+/// Messages sent from other threads are handled first, and then messages are retrieved
+/// in priority order: posted messages, input, WM_QUIT, WM_PAINT, and WM_TIMER.
+/// Internally, paint/timers are never actually inserted into a queue but are synthesized
+/// when nothing else is pending, and the caller doesn't need to be aware.
 pub struct MessageQueue {
-    msgs: std::collections::VecDeque<MSG>,
+    /// Auto-reset event signaled whenever something arrives, which the thread blocks
+    /// on while it waits for messages.
+    event: HEVENT,
+    sent: VecDeque<SentMessage>,
+    posted: VecDeque<MSG>,
+    input: VecDeque<MSG>,
+    /// Exit code passed to PostQuitMessage.
+    quit: Option<u32>,
+    /// Kinds of messages that arrived since the thread last looked at the queue, which
+    /// are what wake WaitMessage and MsgWaitForMultipleObjects.
+    wake: QS,
 }
 
 impl MessageQueue {
-    fn push(&mut self, msg: MSG) {
-        self.msgs.push_back(msg);
-    }
-
-    /// Get any queued message matching the filter criteria.
-    fn get_queued(
-        &mut self,
-        hwnd: HWND,
-        filter: &RangeInclusive<u32>,
-        remove: bool,
-    ) -> Option<MSG> {
-        let pos = self.msgs.iter().position(|msg| {
-            if !hwnd.is_null() && (!msg.hwnd.is_null() && msg.hwnd != hwnd) {
-                return false;
-            }
-            filter.contains(&msg.message)
-        })?;
-        if remove {
-            self.msgs.remove(pos)
-        } else {
-            Some(self.msgs[pos].clone())
+    fn new(kernel32: &mut kernel32::State) -> Self {
+        let event = kernel32
+            .objects
+            .add(KernelObject::Event(EventObject::new(None, false, false)));
+        MessageQueue {
+            event: HEVENT::from_raw(event.to_raw()),
+            sent: Default::default(),
+            posted: Default::default(),
+            input: Default::default(),
+            quit: None,
+            wake: QS::empty(),
         }
     }
 
-    /// Get any pending WM_PAINT matching the filter criteria.
-    fn get_paint(
-        &mut self,
-        hwnd: HWND,
-        windows: &Handles<HWND, Rc<RefCell<Window>>>,
-        _remove: bool,
-    ) -> Option<MSG> {
-        // Note: remove is intentionally ignored because we never enqueue a WM_PAINT.
-        // This just accepts the flag to match the other get_* fns.
-        let hwnd = if hwnd.is_null() {
-            windows.iter().find(|(_, w)| w.borrow().is_dirty())?.0
-        } else {
-            if !windows.get(hwnd).unwrap().borrow().is_dirty() {
-                return None;
-            }
-            hwnd
-        };
-        Some(MSG {
-            hwnd,
-            message: WM::PAINT as u32,
-            wParam: 0,
-            lParam: 0,
-            time: 0,
-            pt_x: 0,
-            pt_y: 0,
-        })
+    fn push_posted(&mut self, msg: MSG) {
+        self.posted.push_back(msg);
+        self.wake |= QS::POSTMESSAGE | QS::ALLPOSTMESSAGE;
     }
 
-    /// Get any pending WM_TIMER matching the filter criteria.
-    /// Err(wait) returns indicate how long to block until the next timer.
-    fn get_timer(
-        &mut self,
-        host: &dyn Host,
-        timers: &mut Timers,
-        remove: bool,
-    ) -> Result<MSG, Option<u32>> {
-        if timers.is_empty() {
-            // No pending timers.
-            return Err(None);
-        }
+    fn push_input(&mut self, msg: MSG) {
+        self.wake |= input_kind(msg.message);
+        self.input.push_back(msg);
+    }
 
-        let now = host.ticks();
-        // TODO: support filtering by HWND.
-        if let Some(timer) = timers.find_next(HWND::null(), now) {
-            if !remove {
-                todo!("how does peeking timers work");
-            }
-            return Ok(timer.generate_wm_timer(now));
+    fn push_sent(&mut self, sent: SentMessage) {
+        self.sent.push_back(sent);
+        self.wake |= QS::SENDMESSAGE;
+    }
+}
+
+/// The GetQueueStatus category of an input message.
+fn input_kind(message: u32) -> QS {
+    match message {
+        0x0100..=0x0109 => QS::KEY,
+        0x0200 | 0x00A0 => QS::MOUSEMOVE,
+        0x0201..=0x020D | 0x00A1..=0x00A9 => QS::MOUSEBUTTON,
+        // The host's WM_QUIT.
+        _ => QS::POSTMESSAGE,
+    }
+}
+
+/// Get a thread's message queue, creating it on first use.
+fn queue(machine: &mut Machine, thread: HTHREAD) -> &mut MessageQueue {
+    machine
+        .state
+        .user32
+        .queues
+        .entry(thread)
+        .or_insert_with(|| MessageQueue::new(&mut machine.state.kernel32))
+}
+
+/// Add to a thread's message queue, waking the thread if it is waiting for messages.
+fn deliver(machine: &mut Machine, thread: HTHREAD, push: impl FnOnce(&mut MessageQueue)) {
+    let queue = queue(machine, thread);
+    push(queue);
+    let event = queue.event;
+    machine
+        .state
+        .kernel32
+        .objects
+        .get_event(event)
+        .unwrap()
+        .signaled
+        .set(true);
+    kernel32::wake_waiters(machine);
+}
+
+/// The thread that owns a window, and so receives its messages.
+pub(super) fn window_thread(state: &super::State, hwnd: HWND) -> Option<HTHREAD> {
+    Some(state.windows.get(hwnd)?.borrow().thread)
+}
+
+/// Whether a message for hwnd passes the hWnd filter of GetMessage/PeekMessage: null
+/// for any of the thread's messages, -1 for only those not associated with a window,
+/// or else a window, which also matches its descendants.
+fn hwnd_matches(windows: &Handles<HWND, Rc<RefCell<Window>>>, filter: HWND, hwnd: HWND) -> bool {
+    if filter.is_null() {
+        return true;
+    }
+    if filter.to_raw() == 0xFFFF_FFFF {
+        return hwnd.is_null();
+    }
+    let mut hwnd = hwnd;
+    while !hwnd.is_null() {
+        if hwnd == filter {
+            return true;
         }
+        hwnd = match windows.get(hwnd) {
+            Some(window) => window.borrow().parent,
+            None => break,
+        };
+    }
+    false
+}
 
-        let soonest = timers.soonest();
-        Err(Some(soonest))
+/// Get the first message matching the filter criteria, removing it if requested.
+fn take_message(
+    msgs: &mut VecDeque<MSG>,
+    matches: impl Fn(&MSG) -> bool,
+    remove: bool,
+) -> Option<MSG> {
+    let pos = msgs.iter().position(matches)?;
+    if remove {
+        msgs.remove(pos)
+    } else {
+        Some(msgs[pos].clone())
     }
 }
 
+/// Find a window of the given thread that needs a WM_PAINT.
+fn find_paint(
+    windows: &Handles<HWND, Rc<RefCell<Window>>>,
+    thread: HTHREAD,
+    matches: impl Fn(HWND) -> bool,
+) -> Option<HWND> {
+    let (hwnd, _) = windows.iter().find(|&(hwnd, w)| {
+        let w = w.borrow();
+        // Hidden child windows aren't painted until shown.
+        w.thread == thread
+            && w.is_dirty()
+            && (!w.is_child() || w.style.contains(WS::VISIBLE))
+            && matches(hwnd)
+    })?;
+    Some(hwnd)
+}
+
 /// Retrieves the next available message without blocking.
 /// Returns Err(wait) if we need to wait.
 fn poll_message(
@@ -214,56 +392,183 @@ fn poll_message(
     filter: Option<RangeInclusive<u32>>,
     remove: bool,
 ) -> Result<MSG, Option<u32>> {
+    // Sound callbacks may post messages.
+    winmm::pump_audio(machine);
+    fetch_host_messages(machine);
+
     let filter = filter.unwrap_or(0..=0xFFFF_FFFF);
-    if let Some(msg) = machine
-        .state
-        .user32
-        .messages
-        .get_queued(hwnd, &filter, remove)
-    {
+    let thread = current_thread(machine);
+    let now = machine.host.ticks();
+    queue(machine, thread).wake = QS::empty();
+    let state = &mut machine.state.user32;
+    let queue = state.queues.get_mut(&thread).unwrap();
+    let windows = &state.windows;
+    let matches =
+        |msg: &MSG| hwnd_matches(windows, hwnd, msg.hwnd) && filter.contains(&msg.message);
+
+    if let Some(msg) = take_message(&mut queue.posted, matches, remove) {
+        return Ok(msg);
+    }
+    if let Some(msg) = take_message(&mut queue.input, matches, remove) {
         return Ok(msg);
     }
 
-    // TODO: obey filter here.
-    if let Some(msg) = machine.host.get_message() {
-        let msg = msg_from_message(msg);
-        if !remove {
-            machine.state.user32.messages.push(msg.clone());
+    // WM_QUIT is retrieved regardless of the filters, once nothing else is queued.
+    if let Some(code) = queue.quit {
+        if remove {
+            queue.quit = None;
         }
-        return Ok(msg);
+        return Ok(MSG {
+            hwnd: HWND::null(),
+            message: WM::QUIT as u32,
+            wParam: code,
+            lParam: 0,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        });
     }
 
+    // Note: remove is intentionally ignored for WM_PAINT, which stays pending until
+    // the window is validated.
     if filter.contains(&(WM::PAINT as u32)) {
-        if let Some(msg) =
-            machine
-                .state
-                .user32
-                .messages
-                .get_paint(hwnd, &machine.state.user32.windows, remove)
-        {
-            return Ok(msg);
+        if let Some(hwnd) = find_paint(windows, thread, |h| hwnd_matches(windows, hwnd, h)) {
+            return Ok(MSG {
+                hwnd,
+                message: WM::PAINT as u32,
+                wParam: 0,
+                lParam: 0,
+                time: 0,
+                pt_x: 0,
+                pt_y: 0,
+            });
         }
     }
 
-    if filter.contains(&(WM::TIMER as u32)) {
-        return machine.state.user32.messages.get_timer(
-            &*machine.host,
-            &mut machine.state.user32.timers,
-            remove,
-        );
-    } else {
-        Err(None) // block
+    if !filter.contains(&(WM::TIMER as u32)) {
+        return Err(None); // block
     }
+    let timers = &mut state.timers;
+    if let Some(timer) = timers.find_next(thread, |h| hwnd_matches(windows, hwnd, h), now) {
+        return Ok(timer.generate_wm_timer(now, remove));
+    }
+    Err(timers.soonest(thread))
 }
 
-#[cfg(feature = "x86-emu")]
-async fn await_message(machine: &mut Machine, wait: Option<u32>) {
-    machine.emu.x86.cpu_mut().block(wait).await;
+/// Move all pending host messages into the message queues of the threads that own
+/// their windows, updating input state.
+/// Used by APIs that observe input without retrieving messages, and by waits, as host
+/// input can wake a thread other than the one that will read it.
+pub fn fetch_host_messages(machine: &mut Machine) {
+    while let Some(msg) = machine.host.get_message() {
+        let Some(msg) = kernel32::route_host_message(machine, msg) else {
+            continue;
+        };
+        let mut msg = msg_from_message(&mut machine.state.user32.keyboard, msg);
+        route_input(&machine.state.user32, &mut msg);
+        let thread = window_thread(&machine.state.user32, msg.hwnd)
+            .unwrap_or_else(|| current_thread(machine));
+        deliver(machine, thread, |queue| queue.push_input(msg));
+    }
 }
 
-#[cfg(not(feature = "x86-emu"))]
+/// Call the window procedures for messages other threads sent to this one, replying
+/// to the senders.
+async fn receive_sent_messages(machine: &mut Machine) {
+    let thread = current_thread(machine);
+    while let Some(sent) = queue(machine, thread).sent.pop_front() {
+        let result = dispatch_message(machine, &sent.msg).await;
+        if let Some(reply) = sent.reply {
+            reply.set(Some(result));
+            deliver(machine, sent.sender, |_| {});
+        }
+    }
+    queue(machine, thread).wake.remove(QS::SENDMESSAGE);
+}
+
+/// Send a message to a window, running its window procedure on the thread that owns it.
+/// Returns None if the timeout (in host ticks) passes before the other thread replies.
+async fn send_message(
+    machine: &mut Machine,
+    msg: &MSG,
+    until: Option<u32>,
+    block: bool,
+) -> Option<u32> {
+    let thread = current_thread(machine);
+    let target = window_thread(&machine.state.user32, msg.hwnd).unwrap_or(thread);
+    if target == thread {
+        return Some(dispatch_message(machine, msg).await);
+    }
+
+    let reply = Rc::new(Cell::new(None));
+    let sent = SentMessage {
+        msg: msg.clone(),
+        sender: thread,
+        reply: Some(reply.clone()),
+    };
+    deliver(machine, target, |queue| queue.push_sent(sent));
+    loop {
+        // The receiver may send back to this thread before replying, so handle those
+        // while waiting, unless asked not to.
+        if !block {
+            receive_sent_messages(machine).await;
+        }
+        if let Some(result) = reply.get() {
+            return Some(result);
+        }
+        if until.is_some_and(|until| machine.host.ticks() >= until) {
+            return None;
+        }
+        let event = queue(machine, thread).event;
+        kernel32::block_on_objects(machine, &[HANDLE::from_raw(event.to_raw())], false, until)
+            .await;
+    }
+}
+
+/// The kinds of messages that wake a thread waiting for new ones: whatever arrived
+/// since it last looked at its queue, plus any pending WM_PAINT or WM_TIMER.
+fn wake_status(machine: &mut Machine) -> QS {
+    fetch_host_messages(machine);
+    let thread = current_thread(machine);
+    let status = queue_status(machine, thread);
+    queue(machine, thread).wake | (status & (QS::PAINT | QS::TIMER))
+}
+
+/// The kinds of messages a thread has waiting, as reported by GetQueueStatus.
+fn queue_status(machine: &mut Machine, thread: HTHREAD) -> QS {
+    let now = machine.host.ticks();
+    let queue = queue(machine, thread);
+    let mut status = QS::empty();
+    if !queue.sent.is_empty() {
+        status |= QS::SENDMESSAGE;
+    }
+    if !queue.posted.is_empty() || queue.quit.is_some() {
+        status |= QS::POSTMESSAGE | QS::ALLPOSTMESSAGE;
+    }
+    for msg in &queue.input {
+        status |= input_kind(msg.message);
+    }
+    let state = &mut machine.state.user32;
+    if find_paint(&state.windows, thread, |_| true).is_some() {
+        status |= QS::PAINT;
+    }
+    if state.timers.find_next(thread, |_| true, now).is_some() {
+        status |= QS::TIMER;
+    }
+    status
+}
+
+/// Block until something arrives in the current thread's queue or until the given time.
 async fn await_message(machine: &mut Machine, wait: Option<u32>) {
-    machine.host.block(wait);
+    // Wake up in time to keep sound playing.
+    let wait = match (wait, winmm::audio_due(machine)) {
+        (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+        (a, b) => a.or(b),
+    };
+    let thread = current_thread(machine);
+    let event = queue(machine, thread).event;
+    kernel32::block_on_objects(machine, &[HANDLE::from_raw(event.to_raw())], false, wait).await;
+    winmm::deliver_callbacks(machine).await;
 }
 
 bitflags! {
@@ -276,7 +581,7 @@ bitflags! {
 }
 
 #[win32_derive::dllexport]
-pub fn PeekMessageA(
+pub async fn PeekMessageA(
     machine: &mut Machine,
     lpMsg: Option<&mut MSG>,
     hWnd: HWND,
@@ -291,8 +596,12 @@ pub fn PeekMessageA(
     } else {
         Some(wMsgFilterMin..=wMsgFilterMax)
     };
-    let remove = wRemoveMsg.unwrap().contains(RemoveMsg::PM_REMOVE);
+    // The PM_QS_* flags, which further filter the kinds of messages, are ignored.
+    let remove = wRemoveMsg
+        .unwrap_or_else(RemoveMsg::from_bits_truncate)
+        .contains(RemoveMsg::PM_REMOVE);
 
+    receive_sent_messages(machine).await;
     if let Ok(msg) = poll_message(machine, hWnd, filter, remove) {
         *lpMsg = msg;
         return true;
@@ -301,7 +610,7 @@ pub fn PeekMessageA(
 }
 
 #[win32_derive::dllexport]
-pub fn PeekMessageW(
+pub async fn PeekMessageW(
     machine: &mut Machine,
     lpMsg: Option<&mut MSG>,
     hWnd: HWND,
@@ -317,9 +626,10 @@ pub fn PeekMessageW(
         wMsgFilterMax,
         wRemoveMsg,
     )
+    .await
 }
 
-async fn get_message(
+pub(super) async fn get_message(
     machine: &mut Machine,
     lpMsg: Option<&mut MSG>,
     hWnd: HWND,
@@ -328,6 +638,7 @@ async fn get_message(
 ) -> i32 {
     let msg: MSG;
     loop {
+        receive_sent_messages(machine).await;
         let filter = if wMsgFilterMin == 0 && wMsgFilterMax == 0 {
             None
         } else {
@@ -374,13 +685,18 @@ pub async fn GetMessageW(
     get_message(machine, lpMsg, hWnd, wMsgFilterMin, wMsgFilterMax).await
 }
 
+/// Note: like Windows, this waits for messages that arrived since the queue was last
+/// checked, so messages that a PeekMessage already saw but left don't end the wait.
 #[win32_derive::dllexport]
 pub async fn WaitMessage(machine: &mut Machine) -> bool {
+    let thread = current_thread(machine);
     loop {
-        match poll_message(machine, HWND::null(), None, false) {
-            Ok(_) => break,
-            Err(wait_until) => await_message(machine, wait_until).await,
+        receive_sent_messages(machine).await;
+        if !wake_status(machine).is_empty() {
+            break;
         }
+        let wait = machine.state.user32.timers.soonest(thread);
+        await_message(machine, wait).await;
     }
     true
 }
@@ -398,41 +714,50 @@ bitflags::bitflags! {
         const SENDMESSAGE = 0x0040;
         const HOTKEY = 0x0080;
         const ALLPOSTMESSAGE = 0x0100;
+        // Part of QS_ALLINPUT, though we never have raw input.
+        const RAWINPUT = 0x0400;
 
-        // const RAWINPUT = 0x0400;
         // const TOUCH = 0x0800;
         // const POINTER = 0x1000;
     }
 }
 
 #[win32_derive::dllexport]
-pub fn GetQueueStatus(_machine: &mut Machine, flags: Result<QS, u32>) -> u32 {
-    todo!()
+pub fn GetQueueStatus(machine: &mut Machine, flags: Result<QS, u32>) -> u32 {
+    let flags = flags.unwrap_or_else(QS::from_bits_truncate);
+    // The high word is the kinds of messages new since the last check.
+    let new = wake_status(machine) & flags;
+    let thread = current_thread(machine);
+    let status = queue_status(machine, thread) & flags;
+    queue(machine, thread).wake.remove(flags);
+    (new.bits() << 16) | status.bits()
 }
 
 #[win32_derive::dllexport]
-pub fn TranslateMessage(_machine: &mut Machine, lpMsg: Option<&MSG>) -> bool {
-    // TODO: translate key-related messages into enqueuing a WM_CHAR.
-    false // no message translated
+pub fn TranslateMessage(machine: &mut Machine, lpMsg: Option<&MSG>) -> bool {
+    let msg = lpMsg.unwrap();
+    match WM::try_from(msg.message) {
+        Ok(WM::KEYDOWN | WM::KEYUP | WM::SYSKEYDOWN | WM::SYSKEYUP) => {}
+        _ => return false,
+    }
+    if let Some(char_msg) = machine.state.user32.keyboard.translate_message(msg) {
+        let thread = current_thread(machine);
+        deliver(machine, thread, |queue| queue.push_posted(char_msg));
+    }
+    true
 }
 
 pub async fn dispatch_message(machine: &mut Machine, msg: &MSG) -> u32 {
     assert!(!msg.hwnd.is_null());
-    let wndproc = machine
-        .state
-        .user32
-        .windows
-        .get(msg.hwnd)
-        .unwrap()
-        .borrow()
-        .wndclass
-        .borrow()
-        .wndproc;
+    let Some(window) = machine.state.user32.windows.get(msg.hwnd) else {
+        log::warn!("dispatch to nonexistent window {:?}", msg.hwnd);
+        return 0;
+    };
+    let wndproc = window.borrow().wndproc;
     if wndproc == 0 {
         log::error!("window has no wndproc, skipping message dispatch");
         return 0;
     }
-    // TODO: SetWindowLong can change the wndproc.
     machine
         .call_x86(
             wndproc,
@@ -443,9 +768,7 @@ pub async fn dispatch_message(machine: &mut Machine, msg: &MSG) -> u32 {
                 msg.lParam,
             ],
         )
-        .await;
-    // TODO: copy eax to return value
-    0
+        .await
 }
 
 #[win32_derive::dllexport]
@@ -472,20 +795,90 @@ pub async fn DispatchMessageW(machine: &mut Machine, lpMsg: Option<&MSG>) -> u32
 
 #[win32_derive::dllexport]
 pub fn PostQuitMessage(machine: &mut Machine, nExitCode: i32) {
-    machine.state.user32.messages.push(MSG {
-        hwnd: HWND::null(),
-        message: WM::QUIT as u32,
-        wParam: 0,
-        lParam: 0,
-        time: 0,
-        pt_x: 0,
-        pt_y: 0,
+    let thread = current_thread(machine);
+    deliver(machine, thread, |queue| {
+        queue.quit = Some(nExitCode as u32);
+        queue.wake |= QS::POSTMESSAGE | QS::ALLPOSTMESSAGE;
+    });
+}
+
+#[win32_derive::dllexport]
+pub fn PostMessageA(machine: &mut Machine, hWnd: HWND, Msg: u32, wParam: u32, lParam: u32) -> bool {
+    if hWnd.is_null() {
+        let thread_id = GetCurrentThreadId(machine);
+        return PostThreadMessageA(machine, thread_id, Msg, wParam, lParam);
+    }
+    let Some(thread) = window_thread(&machine.state.user32, hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    deliver(machine, thread, |queue| {
+        queue.push_posted(MSG {
+            hwnd: hWnd,
+            message: Msg,
+            wParam,
+            lParam,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        })
     });
+    true
 }
 
 #[win32_derive::dllexport]
 pub fn PostMessageW(machine: &mut Machine, hWnd: HWND, Msg: u32, wParam: u32, lParam: u32) -> bool {
-    machine.state.user32.messages.push(MSG {
+    PostMessageA(machine, hWnd, Msg, wParam, lParam)
+}
+
+#[win32_derive::dllexport]
+pub fn PostThreadMessageA(
+    machine: &mut Machine,
+    idThread: u32,
+    Msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> bool {
+    let thread = HTHREAD::from_raw(idThread);
+    // Like Windows, fail for threads that haven't used messages yet.
+    if thread != current_thread(machine) && !machine.state.user32.queues.contains_key(&thread) {
+        set_last_error(machine, ERROR::INVALID_THREAD_ID);
+        return false;
+    }
+    deliver(machine, thread, |queue| {
+        queue.push_posted(MSG {
+            hwnd: HWND::null(),
+            message: Msg,
+            wParam,
+            lParam,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        })
+    });
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn PostThreadMessageW(
+    machine: &mut Machine,
+    idThread: u32,
+    Msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> bool {
+    PostThreadMessageA(machine, idThread, Msg, wParam, lParam)
+}
+
+#[win32_derive::dllexport]
+pub async fn SendMessageA(
+    machine: &mut Machine,
+    hWnd: HWND,
+    Msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    let msg = MSG {
         hwnd: hWnd,
         message: Msg,
         wParam,
@@ -493,51 +886,147 @@ pub fn PostMessageW(machine: &mut Machine, hWnd: HWND, Msg: u32, wParam: u32, lP
         time: 0,
         pt_x: 0,
         pt_y: 0,
-    });
-    true
+    };
+    send_message(machine, &msg, None, false).await.unwrap()
 }
 
 #[win32_derive::dllexport]
-pub fn TranslateAcceleratorW(
-    _machine: &mut Machine,
+pub async fn SendMessageW(
+    machine: &mut Machine,
     hWnd: HWND,
-    hAccTable: u32,
-    lpMsg: Option<&MSG>,
+    Msg: u32,
+    wParam: u32,
+    lParam: u32,
 ) -> u32 {
-    0 // success
+    // TODO: messages carrying strings need conversion.
+    SendMessageA(machine, hWnd, Msg, wParam, lParam).await
+}
+
+bitflags! {
+    #[derive(win32_derive::TryFromBitflags)]
+    pub struct SMTO: u32 {
+        const NORMAL = 0x0000;
+        const BLOCK = 0x0001;
+        const ABORTIFHUNG = 0x0002;
+        const NOTIMEOUTIFNOTHUNG = 0x0008;
+        const ERRORONEXIT = 0x0020;
+    }
 }
 
 #[win32_derive::dllexport]
-pub async fn SendMessageA(
+pub async fn SendMessageTimeoutA(
     machine: &mut Machine,
     hWnd: HWND,
-    Msg: Result<WM, u32>,
+    Msg: u32,
     wParam: u32,
     lParam: u32,
+    fuFlags: Result<SMTO, u32>,
+    uTimeout: u32,
+    lpdwResult: Option<&mut u32>,
 ) -> u32 {
+    if window_thread(&machine.state.user32, hWnd).is_none() {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return 0;
+    }
+    let flags = fuFlags.unwrap_or_else(SMTO::from_bits_truncate);
+    // Threads never hang as far as we can tell, so this flag means no timeout.
+    let until = if flags.contains(SMTO::NOTIMEOUTIFNOTHUNG) {
+        None
+    } else {
+        Some(machine.host.ticks() + uTimeout)
+    };
     let msg = MSG {
         hwnd: hWnd,
-        message: Msg.unwrap() as u32,
+        message: Msg,
         wParam,
         lParam,
         time: 0,
         pt_x: 0,
         pt_y: 0,
     };
-    dispatch_message(machine, &msg).await
+    match send_message(machine, &msg, until, flags.contains(SMTO::BLOCK)).await {
+        Some(result) => {
+            if let Some(lpdwResult) = lpdwResult {
+                *lpdwResult = result;
+            }
+            1
+        }
+        None => {
+            set_last_error(machine, ERROR::TIMEOUT);
+            0
+        }
+    }
 }
 
 #[win32_derive::dllexport]
-pub async fn SendMessageW(
+pub async fn SendMessageTimeoutW(
     machine: &mut Machine,
     hWnd: HWND,
-    Msg: Result<WM, u32>,
+    Msg: u32,
     wParam: u32,
     lParam: u32,
+    fuFlags: Result<SMTO, u32>,
+    uTimeout: u32,
+    lpdwResult: Option<&mut u32>,
 ) -> u32 {
-    todo!()
+    // TODO: messages carrying strings need conversion.
+    SendMessageTimeoutA(
+        machine, hWnd, Msg, wParam, lParam, fuFlags, uTimeout, lpdwResult,
+    )
+    .await
 }
 
+/// Like SendMessage within a thread, but messages to other threads' windows are queued
+/// without waiting for the result.
+#[win32_derive::dllexport]
+pub async fn SendNotifyMessageA(
+    machine: &mut Machine,
+    hWnd: HWND,
+    Msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> bool {
+    let Some(target) = window_thread(&machine.state.user32, hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    let msg = MSG {
+        hwnd: hWnd,
+        message: Msg,
+        wParam,
+        lParam,
+        time: 0,
+        pt_x: 0,
+        pt_y: 0,
+    };
+    let thread = current_thread(machine);
+    if target == thread {
+        dispatch_message(machine, &msg).await;
+    } else {
+        let sent = SentMessage {
+            msg,
+            sender: thread,
+            reply: None,
+        };
+        deliver(machine, target, |queue| queue.push_sent(sent));
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn SendNotifyMessageW(
+    machine: &mut Machine,
+    hWnd: HWND,
+    Msg: u32,
+    wParam: u32,
+    lParam: u32,
+) -> bool {
+    // TODO: messages carrying strings need conversion.
+    SendNotifyMessageA(machine, hWnd, Msg, wParam, lParam).await
+}
+
+/// Note: like WaitMessage, only messages that arrived since the queue was last checked
+/// count as input for dwWakeMask.
 #[win32_derive::dllexport]
 pub async fn MsgWaitForMultipleObjects(
     machine: &mut Machine,
@@ -551,70 +1040,101 @@ pub async fn MsgWaitForMultipleObjects(
         .mem()
         .iter_pod::<HANDLE<()>>(pHandles, nCount)
         .collect::<Vec<_>>();
-    let mask = dwWakeMask.unwrap();
-    if !mask.is_empty() {
-        log::warn!("MsgWaitForMultipleObjects: ignoring dwWakeMask");
-        // TODO: e.g. handles.push(msgqueueevent)
-    }
+    let mask = dwWakeMask.unwrap_or_else(QS::from_bits_truncate);
+    let thread = current_thread(machine);
+    let until = kernel32::wait_from_milliseconds(machine, dwMilliseconds);
+    // The queue's event follows the handles, as the queue's result is WAIT_OBJECT_0 + nCount.
+    let mut wait_handles = handles.clone();
+    wait_handles.push(HANDLE::from_raw(queue(machine, thread).event.to_raw()));
 
-    kernel32::wait_for_objects(machine, &handles, fWaitAll, dwMilliseconds).await
+    loop {
+        receive_sent_messages(machine).await;
+        let input = wake_status(machine).intersects(mask);
+        if fWaitAll {
+            // Satisfied once all the objects are signaled and there is input.
+            if input {
+                let result = kernel32::wait_for_objects(machine, &handles, true, 0).await;
+                if result != kernel32::WAIT_TIMEOUT {
+                    return result;
+                }
+            }
+        } else {
+            let result = kernel32::wait_for_objects(machine, &handles, false, 0).await;
+            if result != kernel32::WAIT_TIMEOUT {
+                return result;
+            }
+            if input {
+                return kernel32::WAIT_OBJECT_0 + nCount;
+            }
+        }
+
+        let Some(until) = until else {
+            return kernel32::WAIT_TIMEOUT; // no waiting at all
+        };
+        if until.is_some_and(|until| machine.host.ticks() >= until) {
+            return kernel32::WAIT_TIMEOUT;
+        }
+        let timer_due = if mask.contains(QS::TIMER) {
+            machine.state.user32.timers.soonest(thread)
+        } else {
+            None
+        };
+        let block_until = [until, timer_due, winmm::audio_due(machine)]
+            .into_iter()
+            .flatten()
+            .min();
+        if fWaitAll {
+            let event = wait_handles[handles.len()];
+            if input {
+                // Only the objects are outstanding, so wait for (and acquire) all of them,
+                // while still waking for messages sent to this thread.
+                if let Some(result) =
+                    kernel32::block_on_all_objects_or(machine, &handles, event, block_until).await
+                {
+                    return result;
+                }
+            } else {
+                // Blocking on the objects would acquire them before there is input, so
+                // only arriving messages wake the wait, which then rechecks the objects.
+                kernel32::block_on_objects(machine, &[event], false, block_until).await;
+            }
+        } else if let Some(result) =
+            kernel32::block_on_objects(machine, &wait_handles, false, block_until).await
+        {
+            if result != kernel32::WAIT_OBJECT_0 + nCount {
+                return result;
+            }
+        }
+        winmm::deliver_callbacks(machine).await;
+    }
 }
 
 #[win32_derive::dllexport]
-pub fn CallWindowProcA(
-    _machine: &mut Machine,
+pub async fn CallWindowProcA(
+    machine: &mut Machine,
     lpPrevWndFunc: u32, /* WNDPROC */
     hWnd: HWND,
     Msg: u32,
     wParam: u32,
     lParam: u32,
 ) -> u32 {
-    todo!()
-}
-
-#[win32_derive::dllexport]
-pub fn PostMessageA(machine: &mut Machine, hWnd: HWND, Msg: u32, wParam: u32, lParam: u32) -> bool {
-    if hWnd.is_null() {
-        let thread_id = GetCurrentThreadId(machine);
-        return PostThreadMessageA(machine, thread_id, Msg, wParam, lParam);
-    }
-    todo!()
+    machine
+        .call_x86(lpPrevWndFunc, vec![hWnd.to_raw(), Msg, wParam, lParam])
+        .await
 }
 
 #[win32_derive::dllexport]
-pub fn PostThreadMessageA(
+pub async fn SendDlgItemMessageA(
     machine: &mut Machine,
-    idThread: u32,
-    Msg: u32,
-    wParam: u32,
-    lParam: u32,
-) -> bool {
-    let thread_id = GetCurrentThreadId(machine);
-    if idThread != thread_id {
-        // TODO: per-thread queues
-        todo!();
-    }
-
-    machine.state.user32.messages.push(MSG {
-        hwnd: HWND::null(),
-        message: Msg,
-        wParam,
-        lParam,
-        time: 0,
-        pt_x: 0,
-        pt_y: 0,
-    });
-    true
-}
-
-#[win32_derive::dllexport]
-pub fn SendDlgItemMessageA(
-    _machine: &mut Machine,
     hDlg: HWND,
     nIDDlgItem: i32,
     Msg: u32,
     wParam: u32,
     lParam: u32,
 ) -> u32 {
-    todo!()
+    let hwnd = GetDlgItem(machine, hDlg, nIDDlgItem);
+    if hwnd.is_null() {
+        return 0;
+    }
+    SendMessageA(machine, hwnd, Msg, wParam, lParam).await
 }
@@ -1,7 +1,9 @@
+use super::COLOR;
 use crate::{
     str16::Str16,
     winapi::{
         calling_convention::VarArgs,
+        gdi32::COLORREF,
         types::{HWND, POINT, RECT},
     },
     Machine,
@@ -142,8 +144,11 @@ pub fn GetSystemMetrics(_machine: &mut Machine, nIndex: Result<SM, u32>) -> u32
 }
 
 #[win32_derive::dllexport]
-pub fn GetSysColor(_machine: &mut Machine, nIndex: i32) -> u32 {
-    todo!();
+pub fn GetSysColor(_machine: &mut Machine, nIndex: Result<COLOR, u32>) -> COLORREF {
+    match nIndex {
+        Ok(color) => color.to_colorref(),
+        Err(_) => COLORREF::from_rgb(0, 0, 0),
+    }
 }
 
 #[win32_derive::dllexport(cdecl)]
@@ -223,11 +228,6 @@ pub fn wsprintfW(machine: &mut Machine, buf: u32, fmt: Option<&Str16>, args: Var
     todo!()
 }
 
-#[win32_derive::dllexport]
-pub fn GetKeyState(_machine: &mut Machine, nVirtKey: u32) -> u32 {
-    0
-}
-
 #[win32_derive::dllexport]
 pub fn IsIconic(_machine: &mut Machine, hwnd: HWND) -> bool {
     false
@@ -254,22 +254,6 @@ pub fn SetCursorPos(_machine: &mut Machine, x: i32, y: i32) -> bool {
     todo!();
 }
 
-#[win32_derive::dllexport]
-pub fn GetKeyboardState(_machine: &mut Machine, lpKeyState: Option<&mut u8>) -> bool {
-    todo!()
-}
-
-#[win32_derive::dllexport]
-pub fn keybd_event(
-    _machine: &mut Machine,
-    bVk: u8,
-    bScan: u8,
-    dwFlags: u32, /* KEYBD_EVENT_FLAGS */
-    dwExtraInfo: u32,
-) {
-    todo!()
-}
-
 #[derive(Debug, win32_derive::TryFromEnum)]
 pub enum SPI {
     GETBEEP = 0x0001,
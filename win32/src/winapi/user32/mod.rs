@@ -2,10 +2,16 @@
 #![allow(non_camel_case_types)]
 
 mod builtin;
+mod controls;
 mod dialog;
+mod dlgtemplate;
+mod keyboard;
 mod menu;
+mod menutemplate;
+mod menutrack;
 mod message;
 mod misc;
+mod nonclient;
 mod paint;
 mod rect;
 mod resource;
@@ -13,19 +19,25 @@ mod timer;
 mod window;
 mod wndclass;
 
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 pub use builtin::DLL;
 
+use super::gdi32::HGDIOBJ;
 use super::handle::Handles;
+use super::kernel32::HTHREAD;
 use super::types::HWND;
 
-pub use super::gdi32::HDC;
+pub use super::gdi32::{HDC, HRGN};
 pub use super::kernel32::ResourceKey;
+pub use controls::*;
 pub use dialog::*;
+pub use keyboard::*;
 pub use menu::*;
+pub use menutrack::*;
 pub use message::*;
 pub use misc::*;
+pub use nonclient::*;
 pub use paint::*;
 pub use rect::*;
 pub use resource::*;
@@ -41,6 +53,23 @@ pub struct State {
     wndclasses: Vec<Rc<RefCell<WndClass>>>,
     pub user_window_message_count: u32,
     pub windows: Handles<HWND, Rc<RefCell<Window>>>,
-    messages: MessageQueue,
+    /// All windows, bottom-most first.  Only the order among siblings matters.
+    z_order: Vec<HWND>,
+    /// Message queues of the threads that have used messages.
+    queues: HashMap<HTHREAD, MessageQueue>,
     timers: Timers,
+    pub keyboard: Keyboard,
+    /// Window receiving keyboard input, per SetFocus.
+    pub focus: HWND,
+    /// Window receiving all mouse input, per SetCapture.
+    pub capture: HWND,
+    pub menus: Handles<HMENU, Menu>,
+    pub accelerators: Handles<HACCEL, Vec<ACCEL>>,
+    /// Set while a menu is open, so the popups are drawn over their window.
+    pub menu_tracking: Option<MenuTracking>,
+    /// The font menus are drawn with, created on first use.
+    menu_font: HGDIOBJ,
+    /// Set by an Alt press, and cleared by any other key, so that releasing Alt
+    /// by itself activates the menu bar.
+    menu_alt: bool,
 }
@@ -0,0 +1,386 @@
+//! The non-client area: the border and caption around a window's client area.
+//!
+//! The host draws the frames of top-level windows, apart from the menu bar,
+//! so only child windows have non-client areas of their own here.
+
+use super::*;
+use crate::{
+    winapi::{
+        gdi32::{self, BkMode, DCTarget, DC, HDC},
+        types::{HWND, POINT, RECT},
+    },
+    Machine,
+};
+use memory::{Extensions, ExtensionsMut};
+
+/// Hit test codes, as returned by WM_NCHITTEST and passed with WM_NC* mouse messages.
+pub const HTNOWHERE: u32 = 0;
+pub const HTCLIENT: u32 = 1;
+pub const HTCAPTION: u32 = 2;
+pub const HTMENU: u32 = 5;
+pub const HTBORDER: u32 = 18;
+
+/// The parts of a child window's non-client area, from the outside in.
+struct FrameLayout {
+    border: i32,
+    caption: i32,
+    edge: i32,
+}
+
+fn frame_layout(style: WS, ex_style: WS_EX) -> FrameLayout {
+    let border = if style.contains(WS::THICKFRAME) {
+        4
+    } else if style.contains(WS::DLGFRAME) || ex_style.contains(WS_EX::DLGMODALFRAME) {
+        3
+    } else if style.contains(WS::BORDER) {
+        1
+    } else {
+        0
+    };
+    // WS_CAPTION is WS_BORDER | WS_DLGFRAME.
+    let caption = if style.contains(WS::BORDER | WS::DLGFRAME) {
+        18
+    } else {
+        0
+    };
+    let edge = if ex_style.contains(WS_EX::CLIENTEDGE) {
+        2
+    } else if ex_style.contains(WS_EX::STATICEDGE) {
+        1
+    } else {
+        0
+    };
+    FrameLayout {
+        border,
+        caption,
+        edge,
+    }
+}
+
+/// The thickness of the non-client area on each side of the client area,
+/// as DefWindowProc computes it for WM_NCCALCSIZE.
+pub fn nc_insets(style: WS, ex_style: WS_EX, menu: bool) -> RECT {
+    if style.contains(WS::CHILD) {
+        let FrameLayout {
+            border,
+            caption,
+            edge,
+        } = frame_layout(style, ex_style);
+        let side = border + edge;
+        return RECT {
+            left: side,
+            top: side + caption,
+            right: side,
+            bottom: side,
+        };
+    }
+
+    // Top-level windows, as the host draws them.
+    const CAPTION: i32 = 19;
+    let mut side = 0;
+    if style.contains(WS::BORDER) {
+        side += 1;
+    }
+    if style.contains(WS::THICKFRAME) {
+        side += 4;
+    }
+    let menu = if menu { MENU_HEIGHT as i32 } else { 0 };
+    RECT {
+        left: side,
+        top: CAPTION + menu + side,
+        right: side,
+        bottom: side,
+    }
+}
+
+/// The size of a window, including its non-client area.
+pub(super) fn window_size(window: &Window) -> (u32, u32) {
+    let mut rect = window.client_rect();
+    if window.is_child() {
+        rect = window.rect();
+    } else {
+        window_rect(
+            &mut rect,
+            window.style,
+            window.ex_style,
+            !window.menu().is_null(),
+        );
+    }
+    (
+        (rect.right - rect.left) as u32,
+        (rect.bottom - rect.top) as u32,
+    )
+}
+
+/// DefWindowProc's WM_NCCALCSIZE: shrink the window rectangle at addr to the client area.
+pub(super) fn nc_calc_size(machine: &mut Machine, hwnd: HWND, addr: u32) {
+    let Some(window) = machine.state.user32.windows.get(hwnd) else {
+        return;
+    };
+    let nc = {
+        let window = window.borrow();
+        nc_insets(window.style, window.ex_style, !window.menu().is_null())
+    };
+    // When wParam is set, addr is an NCCALCSIZE_PARAMS, which starts with the same rectangle.
+    let mut rect = machine.mem().get_pod::<RECT>(addr);
+    rect.left += nc.left;
+    rect.top += nc.top;
+    rect.right = std::cmp::max(rect.right - nc.right, rect.left);
+    rect.bottom = std::cmp::max(rect.bottom - nc.bottom, rect.top);
+    machine.mem().put_pod::<RECT>(addr, rect);
+}
+
+/// Resize a window to a given window size, asking it via WM_NCCALCSIZE where its
+/// client area lies within.
+pub(super) async fn calc_client_area(machine: &mut Machine, hwnd: HWND, width: u32, height: u32) {
+    let Some(window) = machine.state.user32.windows.get(hwnd) else {
+        return;
+    };
+    let (x, y) = {
+        let window = window.borrow();
+        (window.x, window.y)
+    };
+    let rect = RECT {
+        left: x,
+        top: y,
+        right: x + width as i32,
+        bottom: y + height as i32,
+    };
+    let addr = machine
+        .state
+        .scratch
+        .alloc(machine.emu.memory.mem(), std::mem::size_of::<RECT>() as u32);
+    machine.mem().put_pod::<RECT>(addr, rect);
+    let msg = MSG {
+        hwnd,
+        message: WM::NCCALCSIZE as u32,
+        wParam: 0,
+        lParam: addr,
+        time: 0,
+        pt_x: 0,
+        pt_y: 0,
+    };
+    dispatch_message(machine, &msg).await;
+    let client = machine.mem().get_pod::<RECT>(addr);
+    machine.state.scratch.free(machine.emu.memory.mem(), addr);
+
+    let Some(window) = machine.state.user32.windows.get(hwnd) else {
+        return;
+    };
+    let mut window = window.borrow_mut();
+    let (client_width, client_height) = if window.is_child() {
+        window.nc = RECT {
+            left: client.left - rect.left,
+            top: client.top - rect.top,
+            right: rect.right - client.right,
+            bottom: rect.bottom - client.bottom,
+        };
+        window.frame = None;
+        (
+            std::cmp::max(client.right - client.left, 0) as u32,
+            std::cmp::max(client.bottom - client.top, 0) as u32,
+        )
+    } else {
+        toplevel_client_size(client.right - client.left, client.bottom - client.top)
+    };
+    if (client_width, client_height) != (window.width, window.height) {
+        window.set_client_size(&mut *machine.host, client_width, client_height);
+        drop(window);
+        draw_menu_bar(machine, hwnd);
+    }
+}
+
+/// DefWindowProc's WM_NCHITTEST: find the part of a window at a point in screen coordinates.
+pub fn nc_hit_test(state: &State, hwnd: HWND, pt: POINT) -> u32 {
+    let Some((_, origin)) = toplevel_origin(&state.windows, hwnd) else {
+        return HTNOWHERE;
+    };
+    let window = state.windows.get(hwnd).unwrap().borrow();
+    let pt = pt.sub(origin);
+    let client = window.client_rect();
+    if client.contains(pt) {
+        return HTCLIENT;
+    }
+    if !window.is_child() {
+        let bar = RECT {
+            top: -(window.menu_height() as i32),
+            bottom: 0,
+            ..client
+        };
+        return if bar.contains(pt) { HTMENU } else { HTNOWHERE };
+    }
+
+    // The window rectangle, relative to the client area.
+    let rect = RECT {
+        left: -window.nc.left,
+        top: -window.nc.top,
+        right: client.right + window.nc.right,
+        bottom: client.bottom + window.nc.bottom,
+    };
+    if !rect.contains(pt) {
+        return HTNOWHERE;
+    }
+    let layout = frame_layout(window.style, window.ex_style);
+    let caption = RECT {
+        left: rect.left + layout.border,
+        top: rect.top + layout.border,
+        right: rect.right - layout.border,
+        bottom: rect.top + layout.border + layout.caption,
+    };
+    if caption.contains(pt) {
+        HTCAPTION
+    } else {
+        HTBORDER
+    }
+}
+
+fn shrink(rect: RECT, n: i32) -> RECT {
+    RECT {
+        left: rect.left + n,
+        top: rect.top + n,
+        right: rect.right - n,
+        bottom: rect.bottom - n,
+    }
+}
+
+/// A one pixel bevel: light on the top and left, dark on the bottom and right.
+fn bevel(machine: &mut Machine, hdc: HDC, rect: RECT, light: COLOR, dark: COLOR) {
+    let RECT {
+        left,
+        top,
+        right,
+        bottom,
+    } = rect;
+    let sides = [
+        (
+            RECT {
+                left,
+                top,
+                right: right - 1,
+                bottom: top + 1,
+            },
+            light,
+        ),
+        (
+            RECT {
+                left,
+                top,
+                right: left + 1,
+                bottom: bottom - 1,
+            },
+            light,
+        ),
+        (
+            RECT {
+                left,
+                top: bottom - 1,
+                right,
+                bottom,
+            },
+            dark,
+        ),
+        (
+            RECT {
+                left: right - 1,
+                top,
+                right,
+                bottom,
+            },
+            dark,
+        ),
+    ];
+    for (side, color) in sides {
+        gdi32::fill_rect(machine, hdc, &side, color.to_colorref());
+    }
+}
+
+/// DefWindowProc's WM_NCPAINT: draw a child window's border and caption.
+pub(super) fn paint_frame(machine: &mut Machine, hwnd: HWND) {
+    let Some(window) = machine.state.user32.windows.get(hwnd).cloned() else {
+        return;
+    };
+    let (layout, text, (width, height)) = {
+        let window = window.borrow();
+        if !window.has_frame() {
+            return;
+        }
+        (
+            frame_layout(window.style, window.ex_style),
+            window.text.chars().collect::<Vec<_>>(),
+            window_size(&window),
+        )
+    };
+    let bitmap = create_backing_store(width, height);
+    let mut dc = DC::new(DCTarget::Memory(bitmap.clone()));
+    dc.bk_mode = BkMode::TRANSPARENT;
+    dc.text_color = COLOR::CAPTIONTEXT.to_colorref();
+    let hdc = machine.state.gdi32.dcs.add_dc(dc);
+
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: width as i32,
+        bottom: height as i32,
+    };
+    gdi32::fill_rect(machine, hdc, &rect, COLOR::BTNFACE.to_colorref());
+    match layout.border {
+        0 => {}
+        1 => bevel(machine, hdc, rect, COLOR::WINDOWFRAME, COLOR::WINDOWFRAME),
+        _ => {
+            bevel(machine, hdc, rect, COLOR::LIGHT3D, COLOR::DKSHADOW3D);
+            let inner = shrink(rect, 1);
+            bevel(machine, hdc, inner, COLOR::BTNHIGHLIGHT, COLOR::BTNSHADOW);
+        }
+    }
+    rect = shrink(rect, layout.border);
+    if layout.caption > 0 {
+        // The caption bar, with a line of face color below it.
+        let caption = RECT {
+            bottom: rect.top + layout.caption - 1,
+            ..rect
+        };
+        gdi32::fill_rect(machine, hdc, &caption, COLOR::ACTIVECAPTION.to_colorref());
+        let mut text_rect = RECT {
+            left: caption.left + 2,
+            ..caption
+        };
+        let format = DT_SINGLELINE | DT_VCENTER | DT_NOPREFIX;
+        draw_text(machine, hdc, &text, &mut text_rect, format);
+        rect.top += layout.caption;
+    }
+    match layout.edge {
+        0 => {}
+        1 => bevel(machine, hdc, rect, COLOR::BTNSHADOW, COLOR::BTNHIGHLIGHT),
+        _ => {
+            bevel(machine, hdc, rect, COLOR::BTNSHADOW, COLOR::BTNHIGHLIGHT);
+            let inner = shrink(rect, 1);
+            bevel(machine, hdc, inner, COLOR::DKSHADOW3D, COLOR::LIGHT3D);
+        }
+    }
+    machine.state.gdi32.dcs.remove(hdc);
+
+    window.borrow_mut().frame = Some(bitmap);
+    flush_window(machine, hwnd);
+}
+
+/// Send WM_NCPAINT if a window's frame needs painting, as BeginPaint does.
+pub(super) async fn update_frame(machine: &mut Machine, hwnd: HWND) {
+    let needed = machine.state.user32.windows.get(hwnd).is_some_and(|w| {
+        let w = w.borrow();
+        w.has_frame() && w.frame.is_none()
+    });
+    if !needed {
+        return;
+    }
+    // A wParam of 1 means the whole frame.
+    let msg = MSG {
+        hwnd,
+        message: WM::NCPAINT as u32,
+        wParam: 1,
+        lParam: 0,
+        time: 0,
+        pt_x: 0,
+        pt_y: 0,
+    };
+    dispatch_message(machine, &msg).await;
+}
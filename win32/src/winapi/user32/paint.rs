@@ -1,10 +1,10 @@
-use super::{WindowType, HBRUSH, HDC};
+use super::{flush_window, invalidate, update_frame, visible_region, HBRUSH, HDC, HRGN, WS};
 use crate::str16::Str16;
 use crate::{
     winapi::{
         calling_convention::FromArg,
-        gdi32::{self, COLORREF, HGDIOBJ},
-        types::{HWND, RECT},
+        gdi32::{self, Region, COLORREF, HGDIOBJ, RGN},
+        types::{CStr, HWND, RECT},
     },
     Machine,
 };
@@ -16,22 +16,17 @@ pub fn InvalidateRect(
     lpRect: Option<&RECT>,
     bErase: bool,
 ) -> bool {
-    let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
-    window.add_dirty(bErase);
+    let region = lpRect.map(|rect| Region::from_rect(*rect));
+    invalidate(&machine.state.user32, hWnd, bErase, region.as_ref(), false);
     true // success
 }
 
 #[win32_derive::dllexport]
 pub fn ValidateRect(machine: &mut Machine, hWnd: HWND, lpRect: Option<&RECT>) -> bool {
+    let region = lpRect.map(|rect| Region::from_rect(*rect));
     let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
-    let window = window.expect_toplevel_mut();
-    match lpRect {
-        Some(_rect) => {
-            // TODO: ignored.
-        }
-        None => window.dirty = None,
-    }
-    false // fail
+    window.remove_dirty(region.as_ref());
+    true // success
 }
 
 #[win32_derive::dllexport]
@@ -41,37 +36,57 @@ pub fn GetUpdateRect(
     lpRect: Option<&mut RECT>,
     bErase: bool,
 ) -> bool {
-    let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
-    let top = window.expect_toplevel_mut();
-
-    if let Some(dirty) = &top.dirty {
-        if let Some(rect) = lpRect {
-            *rect = RECT {
-                left: 0,
-                top: 0,
-                right: window.width as i32,
-                bottom: window.height as i32,
-            };
-        }
-        if bErase {
-            todo!(); // need to erase background(?!)
-        }
-        true
-    } else {
-        false
+    let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+    let bounds = match &window.dirty {
+        Some(dirty) => dirty.region.bounds(),
+        None => RECT::default(),
+    };
+    if let Some(rect) = lpRect {
+        *rect = bounds;
+    }
+    if window.dirty.is_none() {
+        return false;
+    }
+    drop(window);
+    if bErase {
+        erase_update_region(machine, hWnd);
     }
+    true
 }
 
-/// Handle to a region.
-pub type HRGN = HGDIOBJ;
+#[win32_derive::dllexport]
+pub fn GetUpdateRgn(machine: &mut Machine, hWnd: HWND, hRgn: HRGN, bErase: bool) -> i32 {
+    let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+    let region = window
+        .dirty
+        .as_ref()
+        .map(|dirty| dirty.region.clone())
+        .unwrap_or_default();
+    drop(window);
+    if bErase {
+        erase_update_region(machine, hWnd);
+    }
+    match machine.state.gdi32.objects.get_mut(hRgn) {
+        Some(gdi32::Object::Region(dst)) => {
+            *dst = region;
+            dst.complexity()
+        }
+        _ => gdi32::RGN_ERROR,
+    }
+}
 
 #[win32_derive::dllexport]
 pub fn InvalidateRgn(machine: &mut Machine, hWnd: HWND, hRgn: HRGN, bErase: bool) -> bool {
-    if !hRgn.is_null() {
-        todo!("invalidate specific region");
-    }
+    let region = gdi32::get_region(machine, hRgn);
+    invalidate(&machine.state.user32, hWnd, bErase, region.as_ref(), false);
+    true // success
+}
+
+#[win32_derive::dllexport]
+pub fn ValidateRgn(machine: &mut Machine, hWnd: HWND, hRgn: HRGN) -> bool {
+    let region = gdi32::get_region(machine, hRgn);
     let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
-    window.add_dirty(bErase);
+    window.remove_dirty(region.as_ref());
     true // success
 }
 
@@ -88,42 +103,25 @@ pub struct PAINTSTRUCT {
 unsafe impl memory::Pod for PAINTSTRUCT {}
 
 #[win32_derive::dllexport]
-pub fn BeginPaint(machine: &mut Machine, hWnd: HWND, lpPaint: Option<&mut PAINTSTRUCT>) -> HDC {
-    let rcwindow = machine.state.user32.windows.get(hWnd).unwrap();
-    let window = rcwindow.borrow();
-    // TODO: take from update region
-    let dirty_rect = RECT {
-        left: 0,
-        top: 0,
-        right: window.width as i32,
-        bottom: window.height as i32,
+pub async fn BeginPaint(
+    machine: &mut Machine,
+    hWnd: HWND,
+    lpPaint: Option<&mut PAINTSTRUCT>,
+) -> HDC {
+    update_frame(machine, hWnd).await;
+    let hdc = update_dc(machine, hWnd);
+    let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+    let (dirty_rect, erase_background) = match &window.dirty {
+        Some(dirty) => (dirty.region.bounds(), dirty.erase_background),
+        None => (RECT::default(), false),
     };
+    drop(window);
 
-    let mut background_drawn = false;
-
-    let WindowType::TopLevel(toplevel) = &window.typ else {
-        log::warn!("TODO: BeginPaint for child windows");
-        return HDC::null();
-    };
-    let hdc = machine.state.gdi32.new_window_dc(rcwindow.clone());
-    let update = toplevel.dirty.as_ref().unwrap();
-
-    if update.erase_background {
-        let background = window.wndclass.borrow().background;
-        if let Some(hbrush) = background.clone().to_option() {
-            if let gdi32::Object::Brush(brush) = machine.state.gdi32.objects.get(hbrush).unwrap() {
-                if let Some(color) = brush.color {
-                    drop(window);
-                    gdi32::fill_rect(machine, hdc, &dirty_rect, color);
-                    background_drawn = true;
-                }
-            }
-        }
-    }
+    let background_drawn = erase_background && fill_background(machine, hWnd, hdc);
 
     *lpPaint.unwrap() = PAINTSTRUCT {
         hdc,
-        fErase: (!background_drawn).into(),
+        fErase: (erase_background && !background_drawn).into(),
         rcPaint: dirty_rect,
         fRestore: 0,          // reserved
         fIncUpdate: 0,        // reserved
@@ -132,24 +130,83 @@ pub fn BeginPaint(machine: &mut Machine, hWnd: HWND, lpPaint: Option<&mut PAINTS
     hdc
 }
 
+/// Create a DC for painting a window, limited to its update region within the part
+/// of the window not clipped away by its ancestors, children or siblings.
+fn update_dc(machine: &mut Machine, hWnd: HWND) -> HDC {
+    let rcwindow = machine.state.user32.windows.get(hWnd).unwrap();
+    let window = rcwindow.borrow();
+    let hdc = machine.state.gdi32.new_window_dc(rcwindow.clone());
+    let region = match &window.dirty {
+        Some(dirty) => dirty.region.clone(),
+        None => Region::default(),
+    };
+    let region = if window.is_child() || window.style.contains(WS::CLIPCHILDREN) {
+        region.combine(&visible_region(&machine.state.user32, hWnd), RGN::AND)
+    } else {
+        region
+    };
+    let dc = machine.state.gdi32.dcs.get(hdc).unwrap();
+    dc.borrow_mut().visible = Some(region);
+    hdc
+}
+
+/// Fill the update region with the window class's background brush, our stand-in for
+/// WM_ERASEBKGND's default handling.  Returns false if the class has no brush to
+/// erase with, in which case the background still needs erasing.
+fn fill_background(machine: &mut Machine, hWnd: HWND, hdc: HDC) -> bool {
+    let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+    let Some(dirty) = &window.dirty else {
+        return false;
+    };
+    let dirty_rect = dirty.region.bounds();
+    let Some(hbrush) = window.wndclass.borrow().background.to_option() else {
+        return false;
+    };
+    let Some(gdi32::Object::Brush(brush)) = machine.state.gdi32.objects.get(hbrush) else {
+        return false;
+    };
+    let Some(color) = brush.color else {
+        return false;
+    };
+    drop(window);
+    gdi32::fill_rect(machine, hdc, &dirty_rect, color);
+    let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
+    window.dirty.as_mut().unwrap().erase_background = false;
+    true
+}
+
+/// Erase the background of the update region, if requested, outside of BeginPaint.
+fn erase_update_region(machine: &mut Machine, hWnd: HWND) {
+    let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+    if !window
+        .dirty
+        .as_ref()
+        .is_some_and(|dirty| dirty.erase_background)
+    {
+        return;
+    }
+    drop(window);
+    let hdc = update_dc(machine, hWnd);
+    if fill_background(machine, hWnd, hdc) {
+        flush_window(machine, hWnd);
+    } else {
+        log::warn!("GetUpdateRect/GetUpdateRgn: no class brush to erase {hWnd:?} with");
+    }
+    machine.state.gdi32.dcs.remove(hdc);
+}
+
 #[win32_derive::dllexport]
 pub fn EndPaint(machine: &mut Machine, hWnd: HWND, lpPaint: Option<&PAINTSTRUCT>) -> bool {
     let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
-    match &mut window.typ {
-        WindowType::TopLevel(toplevel) => {
-            toplevel.dirty = None;
-            window.flush_backing_store(machine.emu.memory.mem());
-        }
-        _ => {
-            log::warn!("TODO: EndPaint for child windows");
-        }
-    }
+    window.dirty = None;
+    drop(window);
+    flush_window(machine, hWnd);
 
     true
 }
 
 /// COLOR_xxx for GetSysColor etc.
-#[derive(Debug, Eq, PartialEq, win32_derive::TryFromEnum)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, win32_derive::TryFromEnum)]
 pub enum COLOR {
     SCROLLBAR = 0,
     BACKGROUND = 1,
@@ -172,6 +229,44 @@ pub enum COLOR {
     BTNTEXT = 18,
     INACTIVECAPTIONTEXT = 19,
     BTNHIGHLIGHT = 20,
+    DKSHADOW3D = 21,
+    LIGHT3D = 22,
+    INFOTEXT = 23,
+    INFOBK = 24,
+}
+
+impl COLOR {
+    /// The classic Windows 95 color scheme.
+    pub fn to_colorref(&self) -> COLORREF {
+        let (r, g, b) = match self {
+            COLOR::SCROLLBAR => (0xc0, 0xc0, 0xc0),
+            COLOR::BACKGROUND => (0x00, 0x80, 0x80),
+            COLOR::ACTIVECAPTION => (0x00, 0x00, 0x80),
+            COLOR::INACTIVECAPTION => (0x80, 0x80, 0x80),
+            COLOR::MENU => (0xc0, 0xc0, 0xc0),
+            COLOR::WINDOW => (0xff, 0xff, 0xff),
+            COLOR::WINDOWFRAME => (0x00, 0x00, 0x00),
+            COLOR::MENUTEXT => (0x00, 0x00, 0x00),
+            COLOR::WINDOWTEXT => (0x00, 0x00, 0x00),
+            COLOR::CAPTIONTEXT => (0xff, 0xff, 0xff),
+            COLOR::ACTIVEBORDER => (0xc0, 0xc0, 0xc0),
+            COLOR::INACTIVEBORDER => (0xc0, 0xc0, 0xc0),
+            COLOR::APPWORKSPACE => (0x80, 0x80, 0x80),
+            COLOR::HIGHLIGHT => (0x00, 0x00, 0x80),
+            COLOR::HIGHLIGHTTEXT => (0xff, 0xff, 0xff),
+            COLOR::BTNFACE => (0xc0, 0xc0, 0xc0),
+            COLOR::BTNSHADOW => (0x80, 0x80, 0x80),
+            COLOR::GRAYTEXT => (0x80, 0x80, 0x80),
+            COLOR::BTNTEXT => (0x00, 0x00, 0x00),
+            COLOR::INACTIVECAPTIONTEXT => (0xc0, 0xc0, 0xc0),
+            COLOR::BTNHIGHLIGHT => (0xff, 0xff, 0xff),
+            COLOR::DKSHADOW3D => (0x00, 0x00, 0x00),
+            COLOR::LIGHT3D => (0xc0, 0xc0, 0xc0),
+            COLOR::INFOTEXT => (0x00, 0x00, 0x00),
+            COLOR::INFOBK => (0xff, 0xff, 0xe1),
+        };
+        COLORREF::from_rgb(r, g, b)
+    }
 }
 
 #[derive(Debug)]
@@ -195,17 +290,13 @@ impl BrushOrColor {
         match self {
             BrushOrColor::Brush(hbr) => *hbr,
             BrushOrColor::Color(c) => {
-                let color = match c {
-                    COLOR::WINDOW => Some(COLORREF::from_rgb(0xc0, 0xc0, 0xc0)),
-                    COLOR::MENU => Some(COLORREF::from_rgb(0xc0, 0xc0, 0xc0)),
-                    COLOR::APPWORKSPACE => Some(COLORREF::from_rgb(0x80, 0x80, 0x80)),
-                    _ => todo!("{c:?}"),
-                };
                 machine
                     .state
                     .gdi32
                     .objects
-                    .add(gdi32::Object::Brush(gdi32::Brush { color }))
+                    .add(gdi32::Object::Brush(gdi32::Brush::new(Some(
+                        c.to_colorref(),
+                    ))))
             }
         }
     }
@@ -232,17 +323,210 @@ pub fn FrameRect(_machine: &mut Machine, hDC: HDC, lprc: Option<&RECT>, hbr: HBR
     true
 }
 
+pub const DT_CENTER: u32 = 0x1;
+pub const DT_RIGHT: u32 = 0x2;
+pub const DT_VCENTER: u32 = 0x4;
+pub const DT_BOTTOM: u32 = 0x8;
+pub const DT_WORDBREAK: u32 = 0x10;
+pub const DT_SINGLELINE: u32 = 0x20;
+pub const DT_EXPANDTABS: u32 = 0x40;
+pub const DT_NOCLIP: u32 = 0x100;
+pub const DT_CALCRECT: u32 = 0x400;
+pub const DT_NOPREFIX: u32 = 0x800;
+
+/// Text prepared for DrawText: tabs expanded, '&' prefixes removed, and split into lines.
+struct TextLayout {
+    text: Vec<char>,
+    /// Index in text of the character marked by an '&' prefix.
+    underline: Option<usize>,
+    lines: Vec<std::ops::Range<usize>>,
+}
+
+impl TextLayout {
+    fn new(font: &gdi32::Font, raw: &[char], format: u32, max_width: i32) -> Self {
+        let mut text = Vec::with_capacity(raw.len());
+        let mut underline = None;
+        let mut i = 0;
+        while i < raw.len() {
+            let c = raw[i];
+            i += 1;
+            match c {
+                '&' if format & DT_NOPREFIX == 0 => {
+                    if raw.get(i) == Some(&'&') {
+                        text.push('&');
+                        i += 1;
+                    } else {
+                        // The next character is underlined.
+                        underline.get_or_insert(text.len());
+                    }
+                }
+                '\r' => {}
+                '\n' if format & DT_SINGLELINE != 0 => {}
+                '\t' if format & DT_EXPANDTABS != 0 => {
+                    let col =
+                        text.len() - text.iter().rposition(|&c| c == '\n').map_or(0, |i| i + 1);
+                    text.extend(std::iter::repeat_n(' ', 8 - col % 8));
+                }
+                '\t' => text.push(' '),
+                c => text.push(c),
+            }
+        }
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for end in (0..text.len())
+            .filter(|&i| text[i] == '\n')
+            .chain([text.len()])
+        {
+            if format & DT_WORDBREAK != 0 {
+                start = Self::wrap(font, &text, start..end, max_width, &mut lines);
+            }
+            lines.push(start..end);
+            start = end + 1;
+        }
+        TextLayout {
+            text,
+            underline,
+            lines,
+        }
+    }
+
+    /// Break full lines off the front of a line, at spaces, until the rest fits in
+    /// max_width.  Returns the start of the remainder.
+    fn wrap(
+        font: &gdi32::Font,
+        text: &[char],
+        line: std::ops::Range<usize>,
+        max_width: i32,
+        lines: &mut Vec<std::ops::Range<usize>>,
+    ) -> usize {
+        let mut start = line.start;
+        let mut width = 0;
+        let mut last_space = None;
+        let mut i = start;
+        while i < line.end {
+            width += font.char_width(text[i]) as i32;
+            if text[i] == ' ' {
+                last_space = Some(i);
+            } else if width > max_width {
+                if let Some(space) = last_space {
+                    lines.push(start..space);
+                    start = space + 1;
+                    last_space = None;
+                    width = 0;
+                    i = start;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+        start
+    }
+}
+
+/// DrawText on already-decoded text.
+pub fn draw_text(
+    machine: &mut Machine,
+    hdc: HDC,
+    text: &[char],
+    rect: &mut RECT,
+    format: u32,
+) -> i32 {
+    let font = gdi32::dc_font(machine, hdc);
+    let bounds = *rect;
+    let layout = TextLayout::new(&font, text, format, bounds.right - bounds.left);
+
+    let line_height = font.height() as i32;
+    let text_height = layout.lines.len() as i32 * line_height;
+    let mut y = bounds.top;
+    if format & DT_SINGLELINE != 0 {
+        if format & DT_VCENTER != 0 {
+            y += (bounds.bottom - bounds.top - line_height) / 2;
+        } else if format & DT_BOTTOM != 0 {
+            y = bounds.bottom - line_height;
+        }
+    }
+
+    if format & DT_CALCRECT != 0 {
+        let width = layout
+            .lines
+            .iter()
+            .map(|line| font.text_width(&layout.text[line.clone()]) as i32)
+            .max()
+            .unwrap_or(0);
+        rect.right = bounds.left + width;
+        rect.bottom = bounds.top + text_height;
+        return text_height;
+    }
+
+    let clip = if format & DT_NOCLIP != 0 {
+        None
+    } else {
+        Some(&bounds)
+    };
+    for (i, line) in layout.lines.iter().enumerate() {
+        let chars = &layout.text[line.clone()];
+        let width = font.text_width(chars) as i32;
+        let x = if format & DT_CENTER != 0 {
+            bounds.left + (bounds.right - bounds.left - width) / 2
+        } else if format & DT_RIGHT != 0 {
+            bounds.right - width
+        } else {
+            bounds.left
+        };
+        let underline = layout
+            .underline
+            .filter(|u| line.contains(u))
+            .map(|u| u - line.start);
+        gdi32::draw_text(
+            machine,
+            hdc,
+            x,
+            y + i as i32 * line_height,
+            chars,
+            underline,
+            clip,
+        );
+    }
+    y - bounds.top + text_height
+}
+
+#[win32_derive::dllexport]
+pub fn DrawTextA(
+    machine: &mut Machine,
+    hDC: HDC,
+    lpString: Option<&CStr>,
+    nCount: i32,
+    lpRect: Option<&mut RECT>,
+    uFormat: u32,
+) -> i32 {
+    let (Some(text), Some(rect)) = (lpString, lpRect) else {
+        return 0;
+    };
+    let mut text = text.to_bytes();
+    if nCount >= 0 {
+        text = &text[..std::cmp::min(nCount as usize, text.len())];
+    }
+    draw_text(machine, hDC, &gdi32::chars_a(text), rect, uFormat)
+}
+
 #[win32_derive::dllexport]
 pub fn DrawTextW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hDC: HDC,
     lpString: Option<&Str16>,
     nCount: i32,
-    lpRect: Option<&RECT>,
+    lpRect: Option<&mut RECT>,
     uFormat: u32,
 ) -> i32 {
-    log::info!("DrawTextW: {:?}", lpString);
-    0
+    let (Some(text), Some(rect)) = (lpString, lpRect) else {
+        return 0;
+    };
+    let mut text = text.buf();
+    if nCount >= 0 {
+        text = &text[..std::cmp::min(nCount as usize, text.len())];
+    }
+    draw_text(machine, hDC, &gdi32::chars_w(text), rect, uFormat)
 }
 
 #[win32_derive::dllexport]
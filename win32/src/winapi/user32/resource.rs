@@ -1,12 +1,7 @@
-use super::{HINSTANCE, HMENU};
+use super::HINSTANCE;
 use crate::{
     pe,
-    winapi::{
-        bitmap::{Bitmap, BITMAPFILEHEADER},
-        gdi32::HGDIOBJ,
-        kernel32::ResourceKey,
-        types::*,
-    },
+    winapi::{bitmap::Bitmap, gdi32::HGDIOBJ, kernel32::ResourceKey, types::*},
     FileOptions, Machine,
 };
 use bitflags::bitflags;
@@ -142,16 +137,11 @@ fn load_image(
 
     match typ {
         IMAGE::BITMAP => {
-            let buf = if load_from_file {
-                let file_header = buf.get_pod::<BITMAPFILEHEADER>(0);
-                assert!(file_header.bfType == 0x4D42); // "BM"
-
-                // Rest of the header doesn't seem useful (?).
-                &buf[14..]
+            let bmp = if load_from_file {
+                Bitmap::parse_file(buf)
             } else {
-                buf
+                Bitmap::parse(buf, None)
             };
-            let bmp = Bitmap::parse(buf, None);
             machine.state.gdi32.objects.add_bitmap(bmp)
         }
         typ => {
@@ -203,7 +193,7 @@ pub fn LoadImageW(
     )
 }
 
-fn load_bitmap(
+pub(super) fn load_bitmap(
     machine: &mut Machine,
     hInstance: HINSTANCE,
     name: ResourceKey<&Str16>,
@@ -304,13 +294,3 @@ pub fn LoadStringW(
         copy_len as u32
     }
 }
-
-#[win32_derive::dllexport]
-pub fn LoadMenuW(_machine: &mut Machine, hInstance: u32, lpMenuName: u32) -> HMENU {
-    0
-}
-
-#[win32_derive::dllexport]
-pub fn LoadAcceleratorsW(_machine: &mut Machine, hInstance: u32, lpTableName: u32) -> HMENU {
-    0
-}
@@ -1,4 +1,10 @@
-use crate::{winapi::types::*, Machine};
+use crate::{
+    winapi::{
+        kernel32::{current_thread, HTHREAD},
+        types::*,
+    },
+    Machine,
+};
 
 use super::{MSG, WM};
 
@@ -7,6 +13,9 @@ pub struct Timer {
     id: u32,
     /// Associated window, if any.
     hwnd: HWND,
+    /// Thread whose message queue receives the WM_TIMERs: the window's, or else
+    /// the thread that called SetTimer.
+    thread: HTHREAD,
     /// Milliseconds between ticks.
     period: u32,
     /// Time when timer should fire next.
@@ -16,8 +25,12 @@ pub struct Timer {
 }
 
 impl Timer {
-    pub fn generate_wm_timer(&mut self, now: u32) -> MSG {
-        self.next = now + self.period;
+    /// Generate the WM_TIMER for a ready timer.  Removing it from the queue schedules
+    /// the next one, while peeking leaves the timer ready.
+    pub fn generate_wm_timer(&mut self, now: u32, remove: bool) -> MSG {
+        if remove {
+            self.next = now + self.period;
+        }
         MSG {
             hwnd: self.hwnd,
             message: WM::TIMER as u32,
@@ -38,26 +51,41 @@ impl Timers {
         self.0.is_empty()
     }
 
-    /// Find the soonest ready to fire timer matching the given hwnd.
-    pub fn find_next(&mut self, hwnd: HWND, now: u32) -> Option<&mut Timer> {
+    /// Find the soonest ready to fire timer of the given thread whose hwnd matches.
+    pub fn find_next(
+        &mut self,
+        thread: HTHREAD,
+        matches: impl Fn(HWND) -> bool,
+        now: u32,
+    ) -> Option<&mut Timer> {
         self.0
             .iter_mut()
-            .filter(|t| (hwnd.is_null() || t.hwnd == hwnd) && t.next <= now)
+            .filter(|t| t.thread == thread && t.next <= now && matches(t.hwnd))
             .min_by_key(|t| t.next)
-        // TODO: do we need to consider hwnd == null timers too?
     }
 
-    pub fn soonest(&self) -> u32 {
-        self.0.iter().map(|t| t.next).min().unwrap_or(0)
+    /// When the given thread's next timer fires, if it has any.
+    pub fn soonest(&self, thread: HTHREAD) -> Option<u32> {
+        self.0
+            .iter()
+            .filter(|t| t.thread == thread)
+            .map(|t| t.next)
+            .min()
     }
 }
 
+/// The thread whose queue receives the given window's timers.
+fn timer_thread(machine: &Machine, hwnd: HWND) -> HTHREAD {
+    super::window_thread(&machine.state.user32, hwnd).unwrap_or_else(|| current_thread(machine))
+}
+
 #[win32_derive::dllexport]
 pub fn KillTimer(machine: &mut Machine, hWnd: HWND, uIDEvent: u32) -> bool {
+    let thread = timer_thread(machine, hWnd);
     let timers = &mut machine.state.user32.timers.0;
     let index = timers
         .iter()
-        .position(|t| t.hwnd == hWnd && t.id == uIDEvent);
+        .position(|t| t.hwnd == hWnd && t.thread == thread && t.id == uIDEvent);
 
     if let Some(index) = index {
         timers.swap_remove(index);
@@ -83,13 +111,14 @@ pub fn SetTimer(
         log::warn!("timer callbacks unimplemented");
     }
 
+    let thread = timer_thread(machine, hWnd);
     let id = match machine
         .state
         .user32
         .timers
         .0
         .iter_mut()
-        .find(|t| t.hwnd == hWnd && t.id == nIDEvent)
+        .find(|t| t.hwnd == hWnd && t.thread == thread && t.id == nIDEvent)
     {
         Some(timer) => {
             timer.period = uElapse;
@@ -102,6 +131,7 @@ pub fn SetTimer(
             let timer = Timer {
                 id,
                 hwnd: hWnd,
+                thread,
                 period: uElapse,
                 next: machine.host.ticks() + uElapse,
                 func: lpTimerFunc,
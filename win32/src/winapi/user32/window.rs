@@ -3,8 +3,9 @@ use crate::{
     host,
     winapi::{
         bitmap::{Bitmap, PixelData, PixelFormat},
-        calling_convention::ArrayWithSize,
-        gdi32::HDC,
+        calling_convention::ArrayWithSizeMut,
+        gdi32::{self, Region, HDC, RGN},
+        kernel32::{self, HTHREAD},
         types::{Str16, String16, HWND, POINT, RECT},
     },
     Host, Machine, SurfaceOptions,
@@ -66,11 +67,28 @@ bitflags! {
     }
 }
 
+impl WS {
+    /// The low word of a style holds class-specific bits (BS_*, ES_*, etc.),
+    /// which must survive alongside the WS_* flags.
+    pub fn from_bits_retain(bits: u32) -> WS {
+        // Safety: bitflags 1.x's "unchecked" just means retaining unknown bits.
+        unsafe { WS::from_bits_unchecked(bits) }
+    }
+}
+
 bitflags! {
     /// Extended window styles.
     #[derive(win32_derive::TryFromBitflags)]
     pub struct WS_EX: u32 {
-        // todo
+        const DLGMODALFRAME = 0x00000001;
+        const TOPMOST       = 0x00000008;
+        const TRANSPARENT   = 0x00000020;
+        const TOOLWINDOW    = 0x00000080;
+        const WINDOWEDGE    = 0x00000100;
+        const CLIENTEDGE    = 0x00000200;
+        const CONTROLPARENT = 0x00010000;
+        const STATICEDGE    = 0x00020000;
+        const APPWINDOW     = 0x00040000;
     }
 }
 
@@ -79,14 +97,50 @@ pub struct Window {
     // TODO: make create_surface a method on Window and remove this.
     pub id: u32,
     pub typ: WindowType,
+    /// Position of the window, including its non-client area, relative to the parent's
+    /// client area; always 0,0 for top-level windows.
+    pub x: i32,
+    pub y: i32,
     /// Client area width (not total window width).
     pub width: u32,
     /// Client area height (not total window height).
     pub height: u32,
+    /// Thickness of a child window's non-client area (border, caption) on each side of
+    /// its client area, per WM_NCCALCSIZE.  The host draws top-level windows' frames.
+    pub nc: RECT,
     pub wndclass: Rc<RefCell<WndClass>>,
+    /// Initially the class wndproc, but SetWindowLong can replace it.
+    pub wndproc: u32,
     pub style: WS,
+    pub ex_style: WS_EX,
     /// The current show state of the window.
     pub show_cmd: SW,
+    /// The window text, e.g. the title of top-level windows or the label of a button.
+    pub text: String,
+    /// Parent window, for child windows.
+    pub parent: HWND,
+    /// Owner window, for top-level windows.  Owned windows are destroyed with their owner.
+    pub owner: HWND,
+    /// Thread that created the window, whose message queue receives its messages.
+    pub thread: HTHREAD,
+    /// Control id of child windows, passed as hMenu on creation.
+    pub control_id: u32,
+    /// GWL_USERDATA.
+    pub user_data: u32,
+    /// Set for windows created from dialog templates.
+    pub dialog: Option<DialogState>,
+    /// Set for instances of the predefined control classes.
+    pub control: Option<Control>,
+    // TODO: CS_OWNDC windows do own a DC, but otherwise they don't.
+    // pub hdc: HDC,
+    /// Backing store.  Child windows have their own, which are composited onto their
+    /// top-level window's when it is flushed to the host.
+    /// Rc so it can be shared within drawing functions.
+    backing_store: Rc<RefCell<Bitmap>>,
+    pub dirty: Option<Dirty>,
+    /// The non-client area's pixels, the size of the whole window, as drawn by
+    /// WM_NCPAINT.  None if it needs painting.
+    pub frame: Option<Rc<RefCell<Bitmap>>>,
 }
 
 pub enum WindowType {
@@ -98,14 +152,15 @@ pub enum WindowType {
 pub struct WindowTopLevel {
     pub host: Box<dyn host::Window>,
     surface: Box<dyn host::Surface>,
-    // TODO: CS_OWNDC windows do own a DC, but otherwise they don't.
-    // pub hdc: HDC,
-    /// Backing store.
-    /// Rc so it can be shared within drawing functions.
-    backing_store: Rc<RefCell<Bitmap>>,
-    pub dirty: Option<Dirty>,
+    /// The menu bar's menu, per SetMenu.
+    pub menu: HMENU,
+    /// The menu bar's pixels, drawn above the client area.
+    pub menu_bar: Option<Rc<RefCell<Bitmap>>>,
 }
 
+/// Height of the menu bar, which the host surface has in addition to the client area.
+pub const MENU_HEIGHT: u32 = 19;
+
 impl Window {
     // TODO: expect_toplevel was added to introduce child windows,
     // but many callers just need to handle child windows instead of calling these.
@@ -117,64 +172,139 @@ impl Window {
     }
 
     pub fn bitmap(&self) -> &Rc<RefCell<Bitmap>> {
+        &self.backing_store
+    }
+
+    /// The menu bar's menu, or null for child windows.
+    pub fn menu(&self) -> HMENU {
         match &self.typ {
-            WindowType::TopLevel(win) => &win.backing_store,
-            _ => panic!("expected top-level window, see TODO"),
+            WindowType::TopLevel(w) => w.menu,
+            WindowType::Child => HMENU::null(),
+        }
+    }
+
+    /// Height of the menu bar above the client area, if any.
+    pub fn menu_height(&self) -> u32 {
+        if self.menu().is_null() {
+            0
+        } else {
+            MENU_HEIGHT
         }
     }
 
     pub fn set_client_size(&mut self, host: &mut dyn Host, width: u32, height: u32) {
         self.width = width;
         self.height = height;
-        match &mut self.typ {
-            WindowType::TopLevel(w) => {
-                w.set_size(host, self.id, width, height);
-            }
-            _ => {}
+        let menu_height = self.menu_height();
+        if let WindowType::TopLevel(w) = &mut self.typ {
+            w.set_size(host, self.id, width, height + menu_height);
         }
+        self.backing_store = create_backing_store(width, height);
+        self.frame = None;
+        self.add_dirty(true, None);
     }
 
     pub fn is_dirty(&self) -> bool {
-        match &self.typ {
-            WindowType::TopLevel(top) => top.dirty.is_some(),
-            _ => false, // TODO
+        self.dirty.is_some()
+    }
+
+    pub fn is_child(&self) -> bool {
+        matches!(self.typ, WindowType::Child)
+    }
+
+    pub fn client_rect(&self) -> RECT {
+        RECT {
+            left: 0,
+            top: 0,
+            right: self.width as i32,
+            bottom: self.height as i32,
         }
     }
 
-    // TODO: update region
-    pub fn add_dirty(&mut self, erase: bool) {
-        match &mut self.typ {
-            WindowType::TopLevel(top) => {
-                let prev = top
-                    .dirty
-                    .as_ref()
-                    .map(|d| d.erase_background)
-                    .unwrap_or(false);
-                top.dirty = Some(Dirty {
-                    erase_background: prev || erase,
+    /// The window's rectangle, including its non-client area, in its parent's client coordinates.
+    pub fn rect(&self) -> RECT {
+        RECT {
+            left: self.x,
+            top: self.y,
+            right: self.x + self.nc.left + self.width as i32 + self.nc.right,
+            bottom: self.y + self.nc.top + self.height as i32 + self.nc.bottom,
+        }
+    }
+
+    /// Position of the client area in its parent's client coordinates.
+    pub fn client_origin(&self) -> POINT {
+        POINT {
+            x: self.x + self.nc.left,
+            y: self.y + self.nc.top,
+        }
+    }
+
+    pub fn has_frame(&self) -> bool {
+        self.nc != RECT::default()
+    }
+
+    /// Add to the update region; None means the whole client area.
+    pub fn add_dirty(&mut self, erase: bool, region: Option<&Region>) {
+        let client = self.client_rect();
+        let region = match region {
+            Some(region) => region.intersect_rect(client),
+            None => Region::from_rect(client),
+        };
+        match &mut self.dirty {
+            Some(dirty) => {
+                dirty.erase_background |= erase;
+                dirty.region = dirty.region.combine(&region, RGN::OR);
+            }
+            None if region.is_empty() => {}
+            None => {
+                self.dirty = Some(Dirty {
+                    erase_background: erase,
+                    region,
                 });
             }
-            _ => todo!(),
         }
     }
 
-    pub fn flush_backing_store(&mut self, mem: Mem) {
-        match self.typ {
-            WindowType::TopLevel(ref mut top) => {
-                top.flush_backing_store(mem);
-            }
-            _ => {}
+    /// Remove from the update region; None means the whole client area.
+    pub fn remove_dirty(&mut self, region: Option<&Region>) {
+        let (Some(dirty), Some(region)) = (&mut self.dirty, region) else {
+            self.dirty = None;
+            return;
+        };
+        dirty.region = dirty.region.combine(region, RGN::DIFF);
+        if dirty.region.is_empty() {
+            self.dirty = None;
         }
     }
 }
 
 pub struct Dirty {
     pub erase_background: bool,
-    // TODO: region
+    /// The update region, in client coordinates.
+    pub region: Region,
+}
+
+pub(super) fn create_backing_store(width: u32, height: u32) -> Rc<RefCell<Bitmap>> {
+    Rc::new(RefCell::new(Bitmap {
+        width,
+        height,
+        format: PixelFormat::RGBA32,
+        stride: width * 4,
+        bottom_up: false,
+        pixels: PixelData::new_owned((width * height * 4) as usize),
+    }))
 }
 
 impl WindowTopLevel {
-    fn new(host: &mut dyn Host, hwnd: HWND, title: &str, width: u32, height: u32) -> Self {
+    /// The height includes the menu bar, if any.
+    fn new(
+        host: &mut dyn Host,
+        hwnd: HWND,
+        title: &str,
+        menu: HMENU,
+        width: u32,
+        height: u32,
+    ) -> Self {
         let host_win = host.create_window(hwnd.to_raw());
         host_win.set_title(title);
         host_win.set_size(width, height);
@@ -191,15 +321,15 @@ impl WindowTopLevel {
         WindowTopLevel {
             host: host_win,
             surface,
-            backing_store: Self::create_backing_store(width, height),
-            dirty: Some(Dirty {
-                erase_background: true,
-            }),
+            menu,
+            menu_bar: None,
         }
     }
 
     fn set_size(&mut self, host: &mut dyn Host, id: u32, width: u32, height: u32) {
         self.host.set_size(width, height);
+        // The menu bar must be redrawn at the new width.
+        self.menu_bar = None;
         self.surface = host.create_surface(
             id,
             &host::SurfaceOptions {
@@ -209,27 +339,323 @@ impl WindowTopLevel {
                 primary: true,
             },
         );
-        self.backing_store = WindowTopLevel::create_backing_store(width, height);
     }
+}
 
-    fn create_backing_store(width: u32, height: u32) -> Rc<RefCell<Bitmap>> {
-        Rc::new(RefCell::new(Bitmap {
-            width,
-            height,
-            format: PixelFormat::RGBA32,
-            pixels: PixelData::new_owned((width * height * 4) as usize),
-        }))
+/// The children of a window, including hidden ones, bottom-most first.
+/// The children of the null window are the top-level windows.
+pub fn window_children(state: &State, hwnd: HWND) -> Vec<HWND> {
+    state
+        .z_order
+        .iter()
+        .copied()
+        .filter(|&child| {
+            state
+                .windows
+                .get(child)
+                .is_some_and(|w| w.borrow().parent == hwnd)
+        })
+        .collect()
+}
+
+/// The visible child windows of a window, bottom-most first.
+pub fn child_windows(state: &State, hwnd: HWND) -> Vec<(HWND, Rc<RefCell<Window>>)> {
+    window_children(state, hwnd)
+        .into_iter()
+        .map(|child| (child, state.windows.get(child).unwrap().clone()))
+        .filter(|(_, w)| {
+            let w = w.borrow();
+            w.is_child() && w.style.contains(WS::VISIBLE)
+        })
+        .collect()
+}
+
+/// Move a window within its siblings' z-order, per SetWindowPos's hWndInsertAfter:
+/// HWND_TOP, HWND_BOTTOM, or just below another window.
+fn set_z_order(state: &mut State, hwnd: HWND, insert_after: HWND) {
+    const HWND_BOTTOM: u32 = 1;
+    const HWND_TOPMOST: u32 = -1i32 as u32;
+    const HWND_NOTOPMOST: u32 = -2i32 as u32;
+    state.z_order.retain(|&w| w != hwnd);
+    let index = match insert_after.to_raw() {
+        // TODO: topmost windows stay above the others.
+        0 | HWND_TOPMOST | HWND_NOTOPMOST => state.z_order.len(),
+        HWND_BOTTOM => 0,
+        _ => match state.z_order.iter().position(|&w| w == insert_after) {
+            Some(index) => index,
+            None => state.z_order.len(),
+        },
+    };
+    state.z_order.insert(index, hwnd);
+}
+
+/// Find the top-level window containing a window, and the offset of the window's
+/// client area within the top-level window's.
+pub fn toplevel_origin(
+    windows: &Handles<HWND, Rc<RefCell<Window>>>,
+    mut hwnd: HWND,
+) -> Option<(HWND, POINT)> {
+    let mut origin = POINT { x: 0, y: 0 };
+    loop {
+        let window = windows.get(hwnd)?.borrow();
+        if !window.is_child() {
+            return Some((hwnd, origin));
+        }
+        origin = origin.add(window.client_origin());
+        hwnd = window.parent;
+    }
+}
+
+/// The position of a window's client area in screen coordinates.
+/// Screen coordinates are those of the top-level window's client area.
+fn screen_origin(machine: &Machine, hwnd: HWND) -> POINT {
+    match toplevel_origin(&machine.state.user32.windows, hwnd) {
+        Some((_, origin)) => origin,
+        None => POINT { x: 0, y: 0 },
+    }
+}
+
+/// Find the deepest visible, enabled descendant of a window whose rectangle contains
+/// a point, given in the window's client coordinates.
+pub fn child_window_from_point(state: &State, hwnd: HWND, pt: POINT) -> HWND {
+    for (child_hwnd, child) in child_windows(state, hwnd).into_iter().rev() {
+        let child = child.borrow();
+        if child.style.contains(WS::DISABLED) || !child.rect().contains(pt) {
+            continue;
+        }
+        let pt = pt.sub(child.client_origin());
+        if !child.client_rect().contains(pt) {
+            // Within the child's non-client area.
+            return child_hwnd;
+        }
+        drop(child);
+        return child_window_from_point(state, child_hwnd, pt);
+    }
+    hwnd
+}
+
+/// The part of a window's client area that drawing may touch: within its ancestors'
+/// client areas, and outside its children and the siblings above it as requested by
+/// WS_CLIPCHILDREN and WS_CLIPSIBLINGS.  In client coordinates.
+pub fn visible_region(state: &State, hwnd: HWND) -> Region {
+    let window = state.windows.get(hwnd).unwrap().borrow();
+    let mut region = Region::from_rect(window.client_rect());
+    if window.style.contains(WS::CLIPCHILDREN) {
+        for (_, child) in child_windows(state, hwnd) {
+            let rect = Region::from_rect(child.borrow().rect());
+            region = region.combine(&rect, RGN::DIFF);
+        }
+    }
+    drop(window);
+
+    // Walk up the tree, with origin tracking the window's client area within the
+    // current ancestor's.
+    let mut origin = POINT { x: 0, y: 0 };
+    let mut hwnd = hwnd;
+    loop {
+        let window = state.windows.get(hwnd).unwrap().borrow();
+        if !window.is_child() {
+            break;
+        }
+        origin = origin.add(window.client_origin());
+        let to_client = POINT {
+            x: -origin.x,
+            y: -origin.y,
+        };
+        if window.style.contains(WS::CLIPSIBLINGS) {
+            let siblings = child_windows(state, window.parent);
+            let above = siblings.iter().skip_while(|(h, _)| *h != hwnd).skip(1);
+            for (_, sibling) in above {
+                let rect = sibling.borrow().rect().add(to_client);
+                region = region.combine(&Region::from_rect(rect), RGN::DIFF);
+            }
+        }
+        let parent = window.parent;
+        drop(window);
+        let Some(parent_window) = state.windows.get(parent) else {
+            break;
+        };
+        region = region.intersect_rect(parent_window.borrow().client_rect().add(to_client));
+        hwnd = parent;
     }
+    region
+}
+
+/// Add to the update region of a window and, unless it has WS_CLIPCHILDREN (or
+/// all_children is set), the parts of its children it overlaps.
+/// The region is in client coordinates; None means the whole client area.
+pub fn invalidate(
+    state: &State,
+    hwnd: HWND,
+    erase: bool,
+    region: Option<&Region>,
+    all_children: bool,
+) {
+    let Some(window) = state.windows.get(hwnd) else {
+        return;
+    };
+    let mut window = window.borrow_mut();
+    window.add_dirty(erase, region);
+    if !all_children && window.style.contains(WS::CLIPCHILDREN) {
+        return;
+    }
+    let region = match region {
+        Some(region) => region.intersect_rect(window.client_rect()),
+        None => Region::from_rect(window.client_rect()),
+    };
+    drop(window);
+    for (child_hwnd, child) in child_windows(state, hwnd) {
+        let origin = child.borrow().client_origin();
+        let mut region = region.clone();
+        region.offset(-origin.x, -origin.y);
+        invalidate(state, child_hwnd, erase, Some(&region), all_children);
+    }
+}
 
-    fn flush_backing_store(&mut self, mem: Mem) {
-        let backing_store = self.backing_store.borrow();
-        let bytes = backing_store.pixels.bytes(mem);
-        self.surface.write_pixels(bytes);
+/// Copy the visible part of a bitmap, positioned at rect, into a frame.
+fn blit(mem: Mem, bitmap: &Bitmap, frame: &mut [u8], frame_width: u32, rect: RECT, visible: RECT) {
+    if visible.left >= visible.right || visible.top >= visible.bottom {
+        return;
+    }
+    let pixels = bitmap.pixels.bytes(mem);
+    let row_len = (visible.right - visible.left) as usize * 4;
+    for y in visible.top..visible.bottom {
+        let src = ((y - rect.top) as usize * bitmap.width as usize
+            + (visible.left - rect.left) as usize)
+            * 4;
+        let dst = (y as usize * frame_width as usize + visible.left as usize) * 4;
+        frame[dst..][..row_len].copy_from_slice(&pixels[src..][..row_len]);
+    }
+}
 
-        self.surface.show();
+/// Copy the pixels of a window's descendants into a frame, which is the size of its client area.
+fn composite_children(
+    state: &State,
+    mem: Mem,
+    hwnd: HWND,
+    frame: &mut [u8],
+    frame_width: u32,
+    clip: RECT,
+    origin: POINT,
+) {
+    for (child_hwnd, child) in child_windows(state, hwnd) {
+        let child = child.borrow();
+        if let Some(bitmap) = &child.frame {
+            let rect = child.rect().add(origin);
+            blit(
+                mem,
+                &bitmap.borrow(),
+                frame,
+                frame_width,
+                rect,
+                rect.clip(&clip),
+            );
+        }
+        let client = child.client_rect().add(origin.add(child.client_origin()));
+        let visible = client.clip(&clip);
+        blit(
+            mem,
+            &child.backing_store.borrow(),
+            frame,
+            frame_width,
+            client,
+            visible,
+        );
+        drop(child);
+        composite_children(
+            state,
+            mem,
+            child_hwnd,
+            frame,
+            frame_width,
+            visible,
+            client.origin(),
+        );
     }
 }
 
+/// Display a window on the host, by way of its top-level window.
+pub fn flush_window(machine: &Machine, hwnd: HWND) {
+    let windows = &machine.state.user32.windows;
+    let Some((top_hwnd, _)) = toplevel_origin(windows, hwnd) else {
+        return;
+    };
+    let mem = machine.emu.memory.mem();
+    let window = windows.get(top_hwnd).unwrap();
+    let (backing_store, menu_bar, menu_height) = {
+        let window = window.borrow();
+        let WindowType::TopLevel(top) = &window.typ else {
+            unreachable!();
+        };
+        (
+            window.backing_store.clone(),
+            top.menu_bar.clone(),
+            window.menu_height(),
+        )
+    };
+    let backing_store = backing_store.borrow();
+    let bytes = backing_store.pixels.bytes(mem);
+    let tracking = machine
+        .state
+        .user32
+        .menu_tracking
+        .as_ref()
+        .filter(|tracking| tracking.hwnd == top_hwnd);
+
+    // Composite the menu bar, child windows and any open popup menus over a copy of
+    // the top-level window's pixels.
+    let frame = if menu_height == 0
+        && tracking.is_none()
+        && child_windows(&machine.state.user32, top_hwnd).is_empty()
+    {
+        None
+    } else {
+        let width = backing_store.width;
+        let bar_len = (width * menu_height * 4) as usize;
+        let mut frame = vec![0; bar_len];
+        if let Some(bar) = &menu_bar {
+            let bar = bar.borrow();
+            let pixels = bar.pixels.bytes(mem);
+            let len = std::cmp::min(bar_len, pixels.len());
+            frame[..len].copy_from_slice(&pixels[..len]);
+        }
+        frame.extend_from_slice(bytes);
+        let clip = RECT {
+            left: 0,
+            top: 0,
+            right: width as i32,
+            bottom: backing_store.height as i32,
+        };
+        let origin = POINT { x: 0, y: 0 };
+        composite_children(
+            &machine.state.user32,
+            mem,
+            top_hwnd,
+            &mut frame[bar_len..],
+            width,
+            clip,
+            origin,
+        );
+        if let Some(tracking) = tracking {
+            let clip = RECT {
+                left: 0,
+                top: -(menu_height as i32),
+                right: width as i32,
+                bottom: backing_store.height as i32,
+            };
+            tracking.composite_popups(mem, &mut frame, width, menu_height, clip);
+        }
+        Some(frame)
+    };
+
+    let mut window = window.borrow_mut();
+    let WindowType::TopLevel(top) = &mut window.typ else {
+        unreachable!();
+    };
+    top.surface.write_pixels(frame.as_deref().unwrap_or(bytes));
+    top.surface.show();
+}
+
 #[derive(Debug)]
 pub enum CreateWindowClassName<'a, Str: ?Sized> {
     Atom(u16),
@@ -313,63 +739,104 @@ pub async fn CreateWindowExW(
     hInstance: u32,
     lpParam: u32,
 ) -> HWND {
-    let class_name = match lpClassName {
-        CreateWindowClassName::Atom(_) => unimplemented!(),
-        CreateWindowClassName::Name(name) => name.to_string(),
+    let Some(wndclass) = find_class(machine, lpClassName) else {
+        log::error!("CreateWindowEx: unknown window class");
+        return HWND::null();
     };
-    let wndclass = machine
-        .state
-        .user32
-        .wndclasses
-        .iter()
-        .find(|c| c.borrow().name == class_name)
-        .unwrap()
-        .clone();
+    let wndproc = wndclass.borrow().wndproc;
 
-    let style = dwStyle.unwrap();
+    let style = dwStyle.unwrap_or_else(WS::from_bits_retain);
+    let ex_style = dwExStyle.unwrap_or_else(WS_EX::from_bits_truncate);
     const CW_USEDEFAULT: u32 = 0x8000_0000;
 
     // hInstance is only relevant when multiple DLLs register classes:
     //   https://devblogs.microsoft.com/oldnewthing/20050418-59/?p=35873
 
     let hwnd = machine.state.user32.windows.reserve();
+    let thread = kernel32::current_thread(machine);
     let width = if nWidth == CW_USEDEFAULT { 640 } else { nWidth };
     let height = if nHeight == CW_USEDEFAULT {
         480
     } else {
         nHeight
     };
+    let text = lpWindowName.map(|s| s.to_string()).unwrap_or_default();
+    let (window_width, window_height) = (width, height);
 
-    let style = dwStyle.unwrap();
-    let menu = false; // TODO
-    let (width, height) = client_size_from_window_size(style, menu, width, height);
-
-    let typ = if style.contains(WS::CHILD) {
-        WindowType::Child
+    let (typ, x, y, width, height) = if style.contains(WS::CHILD) {
+        let x = if X == CW_USEDEFAULT { 0 } else { X as i32 };
+        let y = if Y == CW_USEDEFAULT { 0 } else { Y as i32 };
+        (WindowType::Child, x, y, width, height)
     } else {
-        WindowType::TopLevel(WindowTopLevel::new(
+        let menu = if hMenu != 0 {
+            HMENU::from_raw(hMenu)
+        } else {
+            match &wndclass.borrow().menu {
+                Some(name) => load_menu(machine, hInstance, name.as_ref()),
+                None => HMENU::null(),
+            }
+        };
+        let (width, height) =
+            client_size_from_window_size(style, ex_style, !menu.is_null(), width, height);
+        let menu_height = if menu.is_null() { 0 } else { MENU_HEIGHT };
+        let top = WindowTopLevel::new(
             &mut *machine.host,
             hwnd,
-            &lpWindowName.unwrap().to_string(),
+            &text,
+            menu,
             width,
-            height,
-        ))
+            height + menu_height,
+        );
+        (WindowType::TopLevel(top), 0, 0, width, height)
     };
 
     let window = Window {
         id: hwnd.to_raw(),
         typ,
+        x,
+        y,
         width,
         height,
+        nc: RECT::default(),
         wndclass,
+        wndproc,
         style,
+        ex_style,
         show_cmd: SW::HIDE,
+        text,
+        // For top-level windows, hWndParent is the owner.
+        parent: if style.contains(WS::CHILD) {
+            hWndParent
+        } else {
+            HWND::null()
+        },
+        owner: if style.contains(WS::CHILD) {
+            HWND::null()
+        } else {
+            hWndParent
+        },
+        thread,
+        control_id: if style.contains(WS::CHILD) { hMenu } else { 0 },
+        user_data: 0,
+        dialog: None,
+        control: None,
+        backing_store: create_backing_store(width, height),
+        dirty: Some(Dirty {
+            erase_background: true,
+            region: Region::from_rect(RECT {
+                left: 0,
+                top: 0,
+                right: width as i32,
+                bottom: height as i32,
+            }),
+        }),
+        frame: None,
     };
-    machine
-        .state
-        .user32
-        .windows
-        .set(hwnd, Rc::new(RefCell::new(window)));
+    let state = &mut machine.state.user32;
+    state.windows.set(hwnd, Rc::new(RefCell::new(window)));
+    state.z_order.push(hwnd);
+    draw_menu_bar(machine, hwnd);
+    calc_client_area(machine, hwnd, window_width, window_height).await;
 
     // Synchronously dispatch WM_CREATE.
     let msg = MSG {
@@ -387,7 +854,61 @@ pub async fn CreateWindowExW(
 }
 
 #[win32_derive::dllexport]
-pub fn DestroyWindow(_machine: &mut Machine, hWnd: HWND) -> bool {
+pub async fn DestroyWindow(machine: &mut Machine, hWnd: HWND) -> bool {
+    let windows = &machine.state.user32.windows;
+    if windows.get(hWnd).is_none() {
+        return false;
+    }
+    // Destroying a window also destroys its children and owned windows,
+    // after the parent receives its WM_DESTROY.
+    let mut doomed = vec![hWnd];
+    let mut i = 0;
+    while i < doomed.len() {
+        let parent = doomed[i];
+        doomed.extend(
+            windows
+                .iter()
+                .filter(|(_, w)| {
+                    let w = w.borrow();
+                    w.parent == parent || w.owner == parent
+                })
+                .map(|(hwnd, _)| hwnd),
+        );
+        i += 1;
+    }
+
+    for &hwnd in &doomed {
+        let msg = MSG {
+            hwnd,
+            message: WM::DESTROY as u32,
+            wParam: 0,
+            lParam: 0,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        };
+        dispatch_message(machine, &msg).await;
+    }
+    let state = &mut machine.state.user32;
+    let parent = state.windows.get(hWnd).unwrap().borrow().parent;
+    state.z_order.retain(|hwnd| !doomed.contains(hwnd));
+    for hwnd in doomed {
+        if let Some(window) = state.windows.remove(hwnd) {
+            // A window's menu bar is destroyed along with it.
+            let menu = window.borrow().menu();
+            destroy_menu(&mut state.menus, menu);
+        }
+        if state.focus == hwnd {
+            state.focus = HWND::null();
+        }
+        if state.capture == hwnd {
+            state.capture = HWND::null();
+        }
+    }
+    // Reveal whatever the window covered.
+    if machine.state.user32.windows.get(parent).is_some() {
+        flush_window(machine, parent);
+    }
     true // success
 }
 
@@ -438,17 +959,234 @@ pub fn FindWindowA(
 }
 
 #[win32_derive::dllexport]
-pub async fn UpdateWindow(machine: &mut Machine, hWnd: HWND) -> bool {
-    let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
-    match &window.typ {
-        WindowType::TopLevel(top) => {
-            if top.dirty.is_none() {
-                return true;
-            }
+pub fn IsWindow(machine: &mut Machine, hWnd: HWND) -> bool {
+    machine.state.user32.windows.get(hWnd).is_some()
+}
+
+#[win32_derive::dllexport]
+pub fn GetWindowThreadProcessId(
+    machine: &mut Machine,
+    hWnd: HWND,
+    lpdwProcessId: Option<&mut u32>,
+) -> u32 {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return 0;
+    };
+    let thread = window.borrow().thread;
+    if let Some(process_id) = lpdwProcessId {
+        *process_id = machine.state.kernel32.process_id;
+    }
+    thread.to_raw()
+}
+
+#[win32_derive::dllexport]
+pub fn GetParent(machine: &mut Machine, hWnd: HWND) -> HWND {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return HWND::null();
+    };
+    let window = window.borrow();
+    if window.is_child() {
+        window.parent
+    } else if window.style.contains(WS::POPUP) {
+        window.owner
+    } else {
+        HWND::null()
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn SetParent(machine: &mut Machine, hWndChild: HWND, hWndNewParent: HWND) -> HWND {
+    let state = &mut machine.state.user32;
+    let Some(window) = state.windows.get(hWndChild).cloned() else {
+        return HWND::null();
+    };
+    let mut window = window.borrow_mut();
+    if !window.is_child() || state.windows.get(hWndNewParent).is_none() {
+        // This would mean creating or destroying the host window.
+        log::warn!("SetParent: moving windows to or from the top level is unimplemented");
+        return HWND::null();
+    }
+    let old_parent = std::mem::replace(&mut window.parent, hWndNewParent);
+    drop(window);
+    // The window goes on top of its new siblings.
+    set_z_order(state, hWndChild, HWND::null());
+    flush_window(machine, old_parent);
+    flush_window(machine, hWndNewParent);
+    old_parent
+}
+
+#[win32_derive::dllexport]
+pub fn IsChild(machine: &mut Machine, hWndParent: HWND, hWnd: HWND) -> bool {
+    let mut hwnd = hWnd;
+    while let Some(window) = machine.state.user32.windows.get(hwnd) {
+        let window = window.borrow();
+        if !window.is_child() {
+            break;
         }
-        _ => {
-            log::warn!("TODO: UpdateWindow for child windows");
+        if window.parent == hWndParent {
+            return true;
         }
+        hwnd = window.parent;
+    }
+    false
+}
+
+/// GetWindow relationships.
+#[derive(Debug, win32_derive::TryFromEnum)]
+pub enum GW {
+    HWNDFIRST = 0,
+    HWNDLAST = 1,
+    HWNDNEXT = 2,
+    HWNDPREV = 3,
+    OWNER = 4,
+    CHILD = 5,
+    ENABLEDPOPUP = 6,
+}
+
+#[win32_derive::dllexport]
+pub fn GetWindow(machine: &mut Machine, hWnd: HWND, uCmd: Result<GW, u32>) -> HWND {
+    let state = &machine.state.user32;
+    let Some(window) = state.windows.get(hWnd) else {
+        return HWND::null();
+    };
+    let (parent, owner) = {
+        let window = window.borrow();
+        (window.parent, window.owner)
+    };
+    // Siblings, top-most first.
+    let mut siblings = window_children(state, parent);
+    siblings.reverse();
+    let index = siblings.iter().position(|&w| w == hWnd);
+    let found = match uCmd {
+        Ok(GW::HWNDFIRST) => siblings.first().copied(),
+        Ok(GW::HWNDLAST) => siblings.last().copied(),
+        Ok(GW::HWNDNEXT) => index.and_then(|i| siblings.get(i + 1).copied()),
+        Ok(GW::HWNDPREV) => index.and_then(|i| i.checked_sub(1)).map(|i| siblings[i]),
+        Ok(GW::OWNER) => Some(owner),
+        Ok(GW::CHILD) => window_children(state, hWnd).last().copied(),
+        Ok(GW::ENABLEDPOPUP) | Err(_) => {
+            log::warn!("GetWindow({uCmd:?}) unimplemented");
+            None
+        }
+    };
+    found.unwrap_or_default()
+}
+
+#[win32_derive::dllexport]
+pub fn GetTopWindow(machine: &mut Machine, hWnd: HWND) -> HWND {
+    GetWindow(machine, hWnd, Ok(GW::CHILD))
+}
+
+#[win32_derive::dllexport]
+pub fn BringWindowToTop(machine: &mut Machine, hWnd: HWND) -> bool {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return false;
+    };
+    let parent = window.borrow().parent;
+    set_z_order(&mut machine.state.user32, hWnd, HWND::null());
+    flush_window(machine, if parent.is_null() { hWnd } else { parent });
+    true
+}
+
+/// Call an EnumWindows-style callback on each window, until it returns false.
+async fn enum_windows(machine: &mut Machine, hwnds: Vec<HWND>, callback: u32, lParam: u32) {
+    for hwnd in hwnds {
+        // The callback may have destroyed later windows.
+        if machine.state.user32.windows.get(hwnd).is_none() {
+            continue;
+        }
+        if machine
+            .call_x86(callback, vec![hwnd.to_raw(), lParam])
+            .await
+            == 0
+        {
+            break;
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub async fn EnumWindows(machine: &mut Machine, lpEnumFunc: u32, lParam: u32) -> bool {
+    let mut hwnds = window_children(&machine.state.user32, HWND::null());
+    hwnds.reverse();
+    enum_windows(machine, hwnds, lpEnumFunc, lParam).await;
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn EnumChildWindows(
+    machine: &mut Machine,
+    hWndParent: HWND,
+    lpEnumFunc: u32,
+    lParam: u32,
+) -> bool {
+    if hWndParent.is_null() {
+        return EnumWindows(machine, lpEnumFunc, lParam).await;
+    }
+    // All descendants, each child top-most first and followed by its own descendants.
+    fn descendants(state: &State, hwnd: HWND, hwnds: &mut Vec<HWND>) {
+        for child in window_children(state, hwnd).into_iter().rev() {
+            hwnds.push(child);
+            descendants(state, child, hwnds);
+        }
+    }
+    let mut hwnds = Vec::new();
+    descendants(&machine.state.user32, hWndParent, &mut hwnds);
+    enum_windows(machine, hwnds, lpEnumFunc, lParam).await;
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn WindowFromPoint(machine: &mut Machine, Point: POINT) -> HWND {
+    let state = &machine.state.user32;
+    let toplevel = window_children(state, HWND::null())
+        .into_iter()
+        .rev()
+        .find(|&hwnd| {
+            let window = state.windows.get(hwnd).unwrap().borrow();
+            // The top-level window's surface includes the menu bar, above the client area.
+            let rect = RECT {
+                top: -(window.menu_height() as i32),
+                ..window.client_rect()
+            };
+            !window.is_child() && window.style.contains(WS::VISIBLE) && rect.contains(Point)
+        });
+    match toplevel {
+        Some(hwnd) => child_window_from_point(state, hwnd, Point),
+        None => HWND::null(),
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn ChildWindowFromPoint(machine: &mut Machine, hWndParent: HWND, Point: POINT) -> HWND {
+    let state = &machine.state.user32;
+    let Some(parent) = state.windows.get(hWndParent) else {
+        return HWND::null();
+    };
+    if !parent.borrow().client_rect().contains(Point) {
+        return HWND::null();
+    }
+    // Unlike WindowFromPoint, this considers only immediate children, hidden or not.
+    window_children(state, hWndParent)
+        .into_iter()
+        .rev()
+        .find(|&hwnd| {
+            state
+                .windows
+                .get(hwnd)
+                .unwrap()
+                .borrow()
+                .rect()
+                .contains(Point)
+        })
+        .unwrap_or(hWndParent)
+}
+
+#[win32_derive::dllexport]
+pub async fn UpdateWindow(machine: &mut Machine, hWnd: HWND) -> bool {
+    let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+    if !window.is_dirty() {
+        return true;
     }
     drop(window);
 
@@ -497,26 +1235,42 @@ pub async fn RedrawWindow(
     hrgnUpdate: HRGN,
     flags: Result<RDW, u32>,
 ) -> bool {
-    if lprcUpdate.is_some() || !hrgnUpdate.is_null() {
-        todo!();
-    }
+    let region = match lprcUpdate {
+        Some(rect) => Some(Region::from_rect(*rect)),
+        None => gdi32::get_region(machine, hrgnUpdate),
+    };
 
     // TODO: this function has a million flags, ugh.
     // Seems like it's three steps: invalidate/validate, update.
 
-    let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
     let flags = flags.unwrap();
-
+    let window = machine.state.user32.windows.get(hWnd).unwrap();
     if flags.contains(RDW::INVALIDATE) {
-        window.add_dirty(flags.contains(RDW::ERASE));
+        let erase = flags.contains(RDW::ERASE);
+        if flags.contains(RDW::FRAME) {
+            window.borrow_mut().frame = None;
+        }
+        if flags.contains(RDW::NOCHILDREN) {
+            window.borrow_mut().add_dirty(erase, region.as_ref());
+        } else {
+            let all_children = flags.contains(RDW::ALLCHILDREN);
+            invalidate(
+                &machine.state.user32,
+                hWnd,
+                erase,
+                region.as_ref(),
+                all_children,
+            );
+        }
     } else if flags.contains(RDW::VALIDATE) {
-        todo!();
+        window.borrow_mut().remove_dirty(region.as_ref());
     }
-    drop(window);
 
-    if flags.contains(RDW::ERASENOW) {
-        todo!();
-    } else if flags.contains(RDW::UPDATENOW) {
+    // Backgrounds are erased within BeginPaint, so erasing now only affects the frame.
+    if flags.intersects(RDW::ERASENOW | RDW::UPDATENOW) {
+        update_frame(machine, hWnd).await;
+    }
+    if flags.contains(RDW::UPDATENOW) {
         let msg = MSG {
             hwnd: hWnd,
             message: WM::PAINT as u32,
@@ -551,15 +1305,22 @@ pub enum SW {
 
 #[win32_derive::dllexport]
 pub async fn ShowWindow(machine: &mut Machine, hWnd: HWND, nCmdShow: Result<SW, u32>) -> bool {
+    let nCmdShow = nCmdShow.unwrap();
+    let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
+    let previously_visible = window.style.contains(WS::VISIBLE);
+    window.style.set(WS::VISIBLE, !matches!(nCmdShow, SW::HIDE));
     // Store the show command for returning from GetWindowPlacement.
-    machine
-        .state
-        .user32
-        .windows
-        .get(hWnd)
-        .unwrap()
-        .borrow_mut()
-        .show_cmd = nCmdShow.unwrap();
+    window.show_cmd = nCmdShow;
+    if window.is_child() {
+        // Child windows are drawn as part of their top-level window, so showing
+        // one just means painting it.
+        window.add_dirty(true, None);
+        let parent = window.parent;
+        drop(window);
+        flush_window(machine, parent);
+        return previously_visible;
+    }
+    drop(window);
 
     dispatch_message(
         machine,
@@ -607,26 +1368,58 @@ pub async fn ShowWindow(machine: &mut Machine, hWnd: HWND, nCmdShow: Result<SW,
     )
     .await;
 
-    let previously_visible = true;
     previously_visible
 }
 
 #[win32_derive::dllexport]
-pub fn SetFocus(_machine: &mut Machine, hWnd: HWND) -> HWND {
-    let prev_focused = HWND::null();
+pub async fn SetFocus(machine: &mut Machine, hWnd: HWND) -> HWND {
+    let prev_focused = machine.state.user32.focus;
+    if prev_focused == hWnd {
+        return prev_focused;
+    }
+    machine.state.user32.focus = hWnd;
+    if machine.state.user32.windows.get(prev_focused).is_some() {
+        let msg = MSG {
+            hwnd: prev_focused,
+            message: WM::KILLFOCUS as u32,
+            wParam: hWnd.to_raw(),
+            lParam: 0,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        };
+        dispatch_message(machine, &msg).await;
+    }
+    if machine.state.user32.windows.get(hWnd).is_some() {
+        let msg = MSG {
+            hwnd: hWnd,
+            message: WM::SETFOCUS as u32,
+            wParam: prev_focused.to_raw(),
+            lParam: 0,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        };
+        dispatch_message(machine, &msg).await;
+    }
     prev_focused
 }
 
 #[win32_derive::dllexport]
 pub fn GetFocus(machine: &mut Machine) -> HWND {
+    let focus = machine.state.user32.focus;
+    if !focus.is_null() {
+        return focus;
+    }
+    // Nothing has explicitly taken focus; assume the main window has it.
     machine.state.user32.windows.iter().next().unwrap().0
 }
 
-async fn def_window_proc(
+pub(super) async fn def_window_proc(
     machine: &mut Machine,
     hWnd: HWND,
     msg: Result<WM, u32>,
-    _wParam: u32,
+    wParam: u32,
     lParam: u32,
 ) -> u32 {
     let msg = match msg {
@@ -635,13 +1428,68 @@ async fn def_window_proc(
     };
     match msg {
         WM::PAINT => {
+            update_frame(machine, hWnd).await;
             let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
-            let window = window.expect_toplevel_mut();
-            window.dirty = None;
+            window.remove_dirty(None);
+        }
+        WM::NCCALCSIZE => {
+            nc_calc_size(machine, hWnd, lParam);
+        }
+        WM::NCHITTEST => {
+            let pt = POINT {
+                x: lParam as u16 as i16 as i32,
+                y: (lParam >> 16) as u16 as i16 as i32,
+            };
+            return nc_hit_test(&machine.state.user32, hWnd, pt);
+        }
+        WM::NCPAINT => {
+            paint_frame(machine, hWnd);
+        }
+        WM::SETTEXT => {
+            let text = String::from_utf8_lossy(machine.mem().slicez(lParam)).into_owned();
+            return set_window_text(machine, hWnd, text) as u32;
+        }
+        WM::GETTEXT => {
+            let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+            let buf = machine.mem().sub32_mut(lParam, wParam);
+            return copy_text(&window.text, buf);
+        }
+        WM::GETTEXTLENGTH => {
+            let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+            return window.text.len() as u32;
+        }
+        WM::NCLBUTTONDOWN if wParam == HTMENU => {
+            SendMessageA(machine, hWnd, WM::SYSCOMMAND as u32, SC_MOUSEMENU, lParam).await;
+        }
+        WM::KEYDOWN | WM::SYSKEYDOWN => {
+            // Alt activates the menu bar only if released without pressing another key.
+            machine.state.user32.menu_alt = wParam == VK_MENU as u32;
+        }
+        WM::SYSKEYUP => {
+            let alt = std::mem::take(&mut machine.state.user32.menu_alt);
+            if (wParam == VK_MENU as u32 && alt) || wParam == VK_F10 as u32 {
+                send_syscommand(machine, hWnd, SC_KEYMENU, 0).await;
+            }
+        }
+        WM::SYSCHAR => {
+            send_syscommand(machine, hWnd, SC_KEYMENU, wParam).await;
+        }
+        WM::SYSCOMMAND => {
+            let start = match wParam & 0xFFF0 {
+                SC_MOUSEMENU => MenuBarStart::Mouse(POINT {
+                    x: lParam as u16 as i16 as i32,
+                    y: (lParam >> 16) as u16 as i16 as i32,
+                }),
+                SC_KEYMENU => MenuBarStart::Key(char::from_u32(lParam).filter(|&c| c != '\0')),
+                _ => return 0,
+            };
+            track_menu_bar(machine, hWnd, start).await;
         }
         WM::WINDOWPOSCHANGED => {
-            let Window { width, height, .. } =
-                *machine.state.user32.windows.get(hWnd).unwrap().borrow();
+            let (width, height, origin) = {
+                let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+                (window.width, window.height, window.client_origin())
+            };
             let WINDOWPOS { flags, .. } = machine.mem().get_pod::<WINDOWPOS>(lParam);
 
             if !flags.contains(SWP::NOSIZE) {
@@ -659,13 +1507,11 @@ async fn def_window_proc(
             }
 
             if !flags.contains(SWP::NOMOVE) {
-                let x = 0; // TODO
-                let y = 0;
                 let msg = MSG {
                     hwnd: hWnd,
                     message: WM::MOVE as u32,
                     wParam: 0,
-                    lParam: (y << 16) | x,
+                    lParam: ((origin.y as u16 as u32) << 16) | origin.x as u16 as u32,
                     time: 0,
                     pt_x: 0,
                     pt_y: 0,
@@ -678,6 +1524,17 @@ async fn def_window_proc(
     0
 }
 
+/// WM_SYSCOMMAND codes.
+const SC_MOUSEMENU: u32 = 0xF090;
+const SC_KEYMENU: u32 = 0xF100;
+
+/// Send WM_SYSCOMMAND to the top-level window containing a window, which owns the menu bar.
+async fn send_syscommand(machine: &mut Machine, hwnd: HWND, code: u32, lParam: u32) {
+    if let Some((top, _)) = toplevel_origin(&machine.state.user32.windows, hwnd) {
+        SendMessageA(machine, top, WM::SYSCOMMAND as u32, code, lParam).await;
+    }
+}
+
 #[win32_derive::dllexport]
 pub async fn DefWindowProcA(
     machine: &mut Machine,
@@ -701,37 +1558,34 @@ pub async fn DefWindowProcW(
 }
 
 /// Compute window rectangle from client rectangle.
-fn window_rect(rect: &mut RECT, style: WS, menu: bool) {
-    const CAPTION: i32 = 19;
-    rect.top = rect.top - CAPTION;
-    rect.left = rect.left;
-    rect.right = rect.right;
-    rect.bottom = rect.bottom;
-    if menu {
-        rect.top -= 19;
-    }
-    if style.contains(WS::BORDER) {
-        const BORDER: i32 = 1;
-        rect.top -= BORDER;
-        rect.left -= BORDER;
-        rect.right += BORDER;
-        rect.bottom += BORDER;
-    }
-    if style.contains(WS::THICKFRAME) {
-        const FRAME: i32 = 4;
-        rect.top -= FRAME;
-        rect.left -= FRAME;
-        rect.right += FRAME;
-        rect.bottom += FRAME;
-    }
-}
-
-fn client_size_from_window_size(style: WS, menu: bool, width: u32, height: u32) -> (u32, u32) {
-    let mut r = RECT::default();
-    window_rect(&mut r, style, menu);
+pub(super) fn window_rect(rect: &mut RECT, style: WS, ex_style: WS_EX, menu: bool) {
+    let nc = nc_insets(style, ex_style, menu);
+    rect.left -= nc.left;
+    rect.top -= nc.top;
+    rect.right += nc.right;
+    rect.bottom += nc.bottom;
+}
+
+/// The client size of a top-level window, which has a minimum size.
+pub(super) fn toplevel_client_size(width: i32, height: i32) -> (u32, u32) {
     (
-        std::cmp::max(width as i32 - (r.right - r.left), 64) as u32,
-        std::cmp::max(height as i32 - (r.bottom - r.top), 64) as u32,
+        std::cmp::max(width, 64) as u32,
+        std::cmp::max(height, 64) as u32,
+    )
+}
+
+fn client_size_from_window_size(
+    style: WS,
+    ex_style: WS_EX,
+    menu: bool,
+    width: u32,
+    height: u32,
+) -> (u32, u32) {
+    let mut r = RECT::default();
+    window_rect(&mut r, style, ex_style, menu);
+    toplevel_client_size(
+        width as i32 - (r.right - r.left),
+        height as i32 - (r.bottom - r.top),
     )
 }
 
@@ -753,7 +1607,12 @@ pub fn AdjustWindowRectEx(
     bMenu: bool,
     dwExStyle: Result<WS_EX, u32>,
 ) -> bool {
-    window_rect(lpRect.unwrap(), dwStyle.unwrap(), bMenu);
+    window_rect(
+        lpRect.unwrap(),
+        dwStyle.unwrap(),
+        dwExStyle.unwrap_or_else(WS_EX::from_bits_truncate),
+        bMenu,
+    );
     true
 }
 
@@ -802,6 +1661,27 @@ pub async fn SetWindowPos(
     cy: i32,
     uFlags: Result<SWP, u32>,
 ) -> bool {
+    let flags = uFlags.unwrap();
+    let Some(window) = machine.state.user32.windows.get(hWnd).cloned() else {
+        return false;
+    };
+    if !flags.contains(SWP::NOZORDER) {
+        set_z_order(&mut machine.state.user32, hWnd, hWndInsertAfter);
+    }
+    if !flags.contains(SWP::NOMOVE) && window.borrow().is_child() {
+        let mut window = window.borrow_mut();
+        window.x = X;
+        window.y = Y;
+    }
+    if !flags.contains(SWP::NOSIZE) {
+        calc_client_area(machine, hWnd, cx as u32, cy as u32).await;
+    } else if flags.contains(SWP::FRAMECHANGED) {
+        let (width, height) = window_size(&window.borrow());
+        calc_client_area(machine, hWnd, width, height).await;
+    }
+    let parent = window.borrow().parent;
+    flush_window(machine, if parent.is_null() { hWnd } else { parent });
+
     let windowpos_addr = machine.state.scratch.alloc(
         machine.emu.memory.mem(),
         std::mem::size_of::<WINDOWPOS>() as u32,
@@ -815,7 +1695,7 @@ pub async fn SetWindowPos(
             y: Y,
             cx,
             cy,
-            flags: uFlags.unwrap(),
+            flags,
         },
     );
 
@@ -834,30 +1714,39 @@ pub async fn SetWindowPos(
         pt_y: 0,
     };
     dispatch_message(machine, &msg).await;
-
-    let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
-    let menu = true; // TODO
-    let (width, height) = client_size_from_window_size(window.style, menu, cx as u32, cy as u32);
-    window.set_client_size(&mut *machine.host, width, height);
+    machine
+        .state
+        .scratch
+        .free(machine.emu.memory.mem(), windowpos_addr);
 
     true
 }
 
 #[win32_derive::dllexport]
-pub fn MoveWindow(
+pub async fn MoveWindow(
     machine: &mut Machine,
     hWnd: HWND,
-    X: u32,
-    Y: u32,
-    nWidth: u32,
-    nHeight: u32,
+    X: i32,
+    Y: i32,
+    nWidth: i32,
+    nHeight: i32,
     bRepaint: bool,
 ) -> bool {
-    let mut window = machine.state.user32.windows.get(hWnd).unwrap().borrow_mut();
-    let menu = true; // TODO
-    let (width, height) = client_size_from_window_size(window.style, menu, nWidth, nHeight);
-    window.set_client_size(&mut *machine.host, width, height);
-    true // success
+    let mut flags = SWP::NOZORDER | SWP::NOACTIVATE;
+    if !bRepaint {
+        flags |= SWP::NOREDRAW;
+    }
+    SetWindowPos(
+        machine,
+        hWnd,
+        HWND::null(),
+        X,
+        Y,
+        nWidth,
+        nHeight,
+        Ok(flags),
+    )
+    .await
 }
 
 #[win32_derive::dllexport]
@@ -876,6 +1765,10 @@ pub fn GetClientRect(machine: &mut Machine, hWnd: HWND, lpRect: Option<&mut RECT
 #[win32_derive::dllexport]
 pub fn GetWindowRect(machine: &mut Machine, hWnd: HWND, lpRect: Option<&mut RECT>) -> bool {
     let window = machine.state.user32.windows.get(hWnd).unwrap().borrow();
+    if window.is_child() {
+        *lpRect.unwrap() = window.rect().add(screen_origin(machine, window.parent));
+        return true;
+    }
 
     let mut result = RECT {
         left: 0,
@@ -884,8 +1777,12 @@ pub fn GetWindowRect(machine: &mut Machine, hWnd: HWND, lpRect: Option<&mut RECT
         bottom: window.height as i32,
     };
 
-    let menu = true; // TODO
-    window_rect(&mut result, window.style, menu);
+    window_rect(
+        &mut result,
+        window.style,
+        window.ex_style,
+        !window.menu().is_null(),
+    );
 
     // TODO: this pretends that the window is at 0,0
     let offset_x = -result.left;
@@ -941,43 +1838,147 @@ pub fn GetWindowPlacement(
 }
 
 #[win32_derive::dllexport]
-pub fn ClientToScreen(_machine: &mut Machine, hWnd: HWND, lpPoint: Option<&mut POINT>) -> bool {
+pub fn ClientToScreen(machine: &mut Machine, hWnd: HWND, lpPoint: Option<&mut POINT>) -> bool {
+    let Some(point) = lpPoint else {
+        return false;
+    };
+    *point = point.add(screen_origin(machine, hWnd));
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn ScreenToClient(machine: &mut Machine, hWnd: HWND, lpPoint: Option<&mut POINT>) -> bool {
+    let Some(point) = lpPoint else {
+        return false;
+    };
+    *point = point.sub(screen_origin(machine, hWnd));
     true
 }
 
 #[win32_derive::dllexport]
-pub fn GetWindowDC(_machine: &mut Machine, hWnd: HWND) -> HDC {
-    HDC::null()
+pub fn GetWindowDC(machine: &mut Machine, hWnd: HWND) -> HDC {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return HDC::null();
+    };
+    let mut window = window.borrow_mut();
+    if !window.has_frame() {
+        // Top-level windows' frames belong to the host, so just draw on the client area.
+        drop(window);
+        return GetDC(machine, hWnd);
+    }
+    let (width, height) = window_size(&window);
+    let bitmap = window
+        .frame
+        .get_or_insert_with(|| create_backing_store(width, height))
+        .clone();
+    machine
+        .state
+        .gdi32
+        .dcs
+        .add_dc(gdi32::DC::new(gdi32::DCTarget::Memory(bitmap)))
 }
 
 #[win32_derive::dllexport]
 pub fn ReleaseDC(machine: &mut Machine, hwnd: HWND, hdc: HDC) -> bool {
     // Note: there is also DeleteDC; this one is specifically for GetWindowDC/GetDC.
     // TODO: there is a separate refcount for ReleaseDC, but we don't track it.
+    // Drawing on the frame via GetWindowDC isn't displayed until now.
+    if machine.state.user32.windows.get(hwnd).is_some() {
+        flush_window(machine, hwnd);
+    }
     true
 }
 
+const GWL_WNDPROC: i32 = -4;
+const GWL_HWNDPARENT: i32 = -8;
+const GWL_ID: i32 = -12;
+const GWL_STYLE: i32 = -16;
+const GWL_EXSTYLE: i32 = -20;
+const GWL_USERDATA: i32 = -21;
+
+// Dialog windows have extra fields at nonnegative indices.
+const DWL_MSGRESULT: i32 = 0;
+const DWL_DLGPROC: i32 = 4;
+const DWL_USER: i32 = 8;
+
+/// Get a mutable reference to a field of a window, as indexed by GetWindowLong/SetWindowLong.
+fn window_long(window: &mut Window, nIndex: i32) -> Option<&mut u32> {
+    if let Some(dialog) = &mut window.dialog {
+        match nIndex {
+            DWL_MSGRESULT => return Some(&mut dialog.msg_result),
+            DWL_DLGPROC => return Some(&mut dialog.dlgproc),
+            DWL_USER => return Some(&mut dialog.user),
+            _ => {}
+        }
+    }
+    Some(match nIndex {
+        GWL_WNDPROC => &mut window.wndproc,
+        GWL_ID => &mut window.control_id,
+        GWL_USERDATA => &mut window.user_data,
+        _ => return None,
+    })
+}
+
 #[win32_derive::dllexport]
-pub fn GetWindowLongA(_machine: &mut Machine, hWnd: HWND, nIndex: i32) -> i32 {
+pub fn GetWindowLongA(machine: &mut Machine, hWnd: HWND, nIndex: i32) -> i32 {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return 0;
+    };
+    let mut window = window.borrow_mut();
     match nIndex {
-        // GWL_STYLE
-        -16 => WS::empty().bits() as i32,
-
-        // GWL_EXSTYLE
-        -20 => WS_EX::empty().bits() as i32,
-
-        _ => todo!("GetWindowLong({nIndex})"),
+        GWL_STYLE => window.style.bits() as i32,
+        GWL_EXSTYLE => window.ex_style.bits() as i32,
+        GWL_HWNDPARENT if window.is_child() => window.parent.to_raw() as i32,
+        GWL_HWNDPARENT => window.owner.to_raw() as i32,
+        _ => match window_long(&mut window, nIndex) {
+            Some(val) => *val as i32,
+            None => todo!("GetWindowLong({nIndex})"),
+        },
     }
 }
 
+#[win32_derive::dllexport]
+pub fn GetWindowLongW(machine: &mut Machine, hWnd: HWND, nIndex: i32) -> i32 {
+    GetWindowLongA(machine, hWnd, nIndex)
+}
+
 #[win32_derive::dllexport]
 pub fn SetWindowLongA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hWnd: HWND,
-    nIndex: u32, /* WINDOW_LONG_PTR_INDEX */
+    nIndex: i32, /* WINDOW_LONG_PTR_INDEX */
     dwNewLong: i32,
 ) -> i32 {
-    todo!()
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return 0;
+    };
+    let mut window = window.borrow_mut();
+    match nIndex {
+        GWL_STYLE => {
+            // TODO: some style changes require SetWindowPos to take effect.
+            let new = WS::from_bits_retain(dwNewLong as u32);
+            std::mem::replace(&mut window.style, new).bits() as i32
+        }
+        GWL_HWNDPARENT => {
+            // Despite the name, this sets the owner of top-level windows.
+            let new = HWND::from_raw(dwNewLong as u32);
+            let field = if window.is_child() {
+                &mut window.parent
+            } else {
+                &mut window.owner
+            };
+            std::mem::replace(field, new).to_raw() as i32
+        }
+        _ => match window_long(&mut window, nIndex) {
+            Some(val) => std::mem::replace(val, dwNewLong as u32) as i32,
+            None => todo!("SetWindowLong({nIndex})"),
+        },
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn SetWindowLongW(machine: &mut Machine, hWnd: HWND, nIndex: i32, dwNewLong: i32) -> i32 {
+    SetWindowLongA(machine, hWnd, nIndex, dwNewLong)
 }
 
 #[win32_derive::dllexport]
@@ -985,14 +1986,23 @@ pub fn GetDC(machine: &mut Machine, hWnd: HWND) -> HDC {
     match hWnd.to_option() {
         Some(hwnd) => {
             let rcwindow = machine.state.user32.windows.get(hwnd).unwrap();
-            let window = rcwindow.borrow();
-            match &window.typ {
-                WindowType::TopLevel(_) => machine.state.gdi32.new_window_dc(rcwindow.clone()),
-                _ => {
-                    log::warn!("GetDC for non-top-level window");
-                    HDC::null()
-                }
+            let clipped = {
+                let window = rcwindow.borrow();
+                window.is_child() || window.style.contains(WS::CLIPCHILDREN)
+            };
+            let hdc = machine.state.gdi32.new_window_dc(rcwindow.clone());
+            if clipped {
+                let region = visible_region(&machine.state.user32, hwnd);
+                machine
+                    .state
+                    .gdi32
+                    .dcs
+                    .get(hdc)
+                    .unwrap()
+                    .borrow_mut()
+                    .visible = Some(region);
             }
+            hdc
         }
         None => machine.state.gdi32.screen_dc,
     }
@@ -1000,46 +2010,87 @@ pub fn GetDC(machine: &mut Machine, hWnd: HWND) -> HDC {
 
 #[win32_derive::dllexport]
 pub fn MapWindowPoints(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hWndFrom: HWND,
     hWndTo: HWND,
-    lpPoints: ArrayWithSize<POINT>,
+    lpPoints: ArrayWithSizeMut<POINT>,
 ) -> i32 {
-    if !(hWndFrom.is_null() || hWndTo.is_null()) {
-        todo!()
+    // The null window is the desktop, whose client coordinates are screen coordinates.
+    let delta = screen_origin(machine, hWndFrom).sub(screen_origin(machine, hWndTo));
+    for point in lpPoints.to_option().unwrap_or_default() {
+        *point = point.add(delta);
     }
-    // Mapping a window to/from desktop coords.
-    let delta_x = 0;
-    let delta_y = 0;
-    (delta_y << 16) | delta_x
+    ((delta.y as u16 as i32) << 16) | delta.x as u16 as i32
 }
 
 #[win32_derive::dllexport]
-pub fn SetCapture(_machine: &mut Machine, hwnd: HWND) -> HWND {
-    HWND::null()
+pub fn SetCapture(machine: &mut Machine, hwnd: HWND) -> HWND {
+    std::mem::replace(&mut machine.state.user32.capture, hwnd)
 }
 
 #[win32_derive::dllexport]
-pub fn ReleaseCapture(_machine: &mut Machine) -> bool {
+pub fn ReleaseCapture(machine: &mut Machine) -> bool {
+    machine.state.user32.capture = HWND::null();
     true
 }
 
-#[win32_derive::dllexport]
-pub fn SetWindowTextA(machine: &mut Machine, hWnd: HWND, lpString: Option<&str>) -> bool {
-    match machine.state.user32.windows.get_mut(hWnd) {
-        Some(window) => {
-            let mut window = window.borrow_mut();
-            window
-                .expect_toplevel_mut()
-                .host
-                .set_title(lpString.unwrap());
-            true
-        }
-        None => {
-            log::error!("SetWindowText of non-window?");
-            false
+pub(super) fn set_window_text(machine: &mut Machine, hWnd: HWND, text: String) -> bool {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        log::error!("SetWindowText of non-window?");
+        return false;
+    };
+    let mut window = window.borrow_mut();
+    match &window.typ {
+        WindowType::TopLevel(top) => top.host.set_title(&text),
+        // Controls generally display their text, and other windows in their caption.
+        WindowType::Child => {
+            window.frame = None;
+            window.add_dirty(true, None);
         }
     }
+    window.text = text;
+    true
+}
+
+/// Copy window text into a buffer as a nul-terminated string, truncating as needed.
+/// Returns the number of characters copied, not including the nul.
+pub(super) fn copy_text<T: From<u8> + Copy>(text: &str, buf: &mut [T]) -> u32 {
+    let Some(max) = buf.len().checked_sub(1) else {
+        return 0;
+    };
+    let len = std::cmp::min(text.len(), max);
+    for (dst, &src) in buf.iter_mut().zip(&text.as_bytes()[..len]) {
+        *dst = T::from(src);
+    }
+    buf[len] = T::from(0);
+    len as u32
+}
+
+#[win32_derive::dllexport]
+pub fn SetWindowTextA(machine: &mut Machine, hWnd: HWND, lpString: Option<&str>) -> bool {
+    set_window_text(machine, hWnd, lpString.unwrap_or_default().to_string())
+}
+
+#[win32_derive::dllexport]
+pub fn SetWindowTextW(machine: &mut Machine, hWnd: HWND, lpString: Option<&Str16>) -> bool {
+    let text = lpString.map(|s| s.to_string()).unwrap_or_default();
+    set_window_text(machine, hWnd, text)
+}
+
+#[win32_derive::dllexport]
+pub fn GetWindowTextA(machine: &mut Machine, hWnd: HWND, lpString: ArrayWithSizeMut<u8>) -> i32 {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return 0;
+    };
+    copy_text(&window.borrow().text, lpString.unwrap()) as i32
+}
+
+#[win32_derive::dllexport]
+pub fn GetWindowTextW(machine: &mut Machine, hWnd: HWND, lpString: ArrayWithSizeMut<u16>) -> i32 {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return 0;
+    };
+    copy_text(&window.borrow().text, lpString.unwrap()) as i32
 }
 
 /// RegisterWindowMessage returns a unique message ID starting at this value.
@@ -1061,15 +2112,28 @@ pub fn RegisterWindowMessageW(machine: &mut Machine, lpString: Option<&Str16>) -
 
 #[win32_derive::dllexport]
 pub fn GetCapture(machine: &mut Machine) -> HWND {
-    todo!();
+    machine.state.user32.capture
 }
 
 #[win32_derive::dllexport]
-pub fn EnableWindow(_machine: &mut Machine, hWnd: HWND, bEnable: bool) -> bool {
-    todo!();
+pub fn EnableWindow(machine: &mut Machine, hWnd: HWND, bEnable: bool) -> bool {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        return false;
+    };
+    let mut window = window.borrow_mut();
+    let was_disabled = window.style.contains(WS::DISABLED);
+    window.style.set(WS::DISABLED, !bEnable);
+    if window.is_child() && was_disabled == bEnable {
+        // Controls draw differently when disabled.
+        window.add_dirty(true, None);
+    }
+    was_disabled
 }
 
 #[win32_derive::dllexport]
-pub fn IsWindowVisible(_machine: &mut Machine, hWnd: HWND) -> bool {
-    true
+pub fn IsWindowVisible(machine: &mut Machine, hWnd: HWND) -> bool {
+    match machine.state.user32.windows.get(hWnd) {
+        Some(window) => window.borrow().style.contains(WS::VISIBLE),
+        None => false,
+    }
 }
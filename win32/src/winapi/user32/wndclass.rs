@@ -1,6 +1,6 @@
-use super::{BrushOrColor, HBRUSH, HCURSOR, HICON, HINSTANCE};
+use super::{BrushOrColor, CreateWindowClassName, ResourceKey, HBRUSH, HCURSOR, HICON, HINSTANCE};
 use crate::{
-    str16::{expect_ascii, Str16},
+    str16::{expect_ascii, Str16, String16},
     winapi::{calling_convention::FromArg, types::HWND},
     Machine,
 };
@@ -33,9 +33,27 @@ pub struct WndClass {
     pub style: CS,
     pub wndproc: u32,
     pub background: HBRUSH,
+    /// The menu resource given as lpszMenuName, for top-level windows created without a menu.
+    pub menu: Option<ResourceKey<String16>>,
 }
 
-fn register_class(machine: &mut Machine, wndclass: WndClass) -> u32 {
+fn menu_name_a(machine: &Machine, lpszMenuName: u32) -> Option<ResourceKey<String16>> {
+    if lpszMenuName == 0 {
+        return None;
+    }
+    let name = unsafe { <ResourceKey<&str>>::from_arg(machine.mem(), lpszMenuName) };
+    Some(name.to_string16())
+}
+
+fn menu_name_w(machine: &Machine, lpszMenuName: u32) -> Option<ResourceKey<String16>> {
+    if lpszMenuName == 0 {
+        return None;
+    }
+    let name = unsafe { <ResourceKey<&Str16>>::from_arg(machine.mem(), lpszMenuName) };
+    Some(name.map_name(|name| String16(name.buf().to_vec())))
+}
+
+pub(super) fn register_class(machine: &mut Machine, wndclass: WndClass) -> u32 {
     let atom = machine.state.user32.wndclasses.len() as u32 + 1;
     machine
         .state
@@ -45,6 +63,40 @@ fn register_class(machine: &mut Machine, wndclass: WndClass) -> u32 {
     atom
 }
 
+/// The predefined control classes, in the order of the ordinals (starting at 0x80)
+/// that dialog templates use to refer to them.
+pub const CONTROL_CLASSES: [&str; 6] = [
+    "Button",
+    "Edit",
+    "Static",
+    "ListBox",
+    "ScrollBar",
+    "ComboBox",
+];
+
+/// The class of dialog boxes.
+pub const DIALOG_CLASS: &str = "#32770";
+
+/// Look up a class by atom or by (case-insensitive) name.
+pub fn find_class(
+    machine: &Machine,
+    name: CreateWindowClassName<'_, Str16>,
+) -> Option<Rc<RefCell<WndClass>>> {
+    let wndclasses = &machine.state.user32.wndclasses;
+    match name {
+        CreateWindowClassName::Atom(atom) => {
+            wndclasses.get((atom as usize).checked_sub(1)?).cloned()
+        }
+        CreateWindowClassName::Name(name) => {
+            let name = name.to_string();
+            wndclasses
+                .iter()
+                .find(|c| c.borrow().name.eq_ignore_ascii_case(&name))
+                .cloned()
+        }
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Debug)]
 pub struct WNDCLASSA {
@@ -93,6 +145,7 @@ pub fn RegisterClassW(machine: &mut Machine, lpWndClass: Option<&WNDCLASSA>) ->
         style: CS::from_bits(lpWndClass.style).unwrap(),
         wndproc: lpWndClass.lpfnWndProc,
         background: background.to_brush(machine),
+        menu: menu_name_w(machine, lpWndClass.lpszMenuName),
     };
     register_class(machine, wndclass)
 }
@@ -143,6 +196,7 @@ pub fn RegisterClassExA(machine: &mut Machine, lpWndClassEx: Option<&WNDCLASSEXA
         wndproc: lpWndClassEx.lpfnWndProc,
         background: unsafe { BrushOrColor::from_arg(machine.mem(), lpWndClassEx.hbrBackground) }
             .to_brush(machine),
+        menu: menu_name_a(machine, lpWndClassEx.lpszMenuName),
     };
     register_class(machine, wndclass)
 }
@@ -159,6 +213,7 @@ pub fn RegisterClassExW(machine: &mut Machine, lpWndClassEx: Option<&WNDCLASSEXW
         wndproc: lpWndClassEx.lpfnWndProc,
         background: unsafe { BrushOrColor::from_arg(machine.mem(), lpWndClassEx.hbrBackground) }
             .to_brush(machine),
+        menu: menu_name_w(machine, lpWndClassEx.lpszMenuName),
     };
     register_class(machine, wndclass)
 }
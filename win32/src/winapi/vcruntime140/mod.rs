@@ -4,7 +4,7 @@ mod builtin;
 
 pub use builtin::DLL;
 
-use crate::machine::Machine;
+use crate::{machine::Machine, winapi::kernel32};
 use memory::{Extensions, ExtensionsMut};
 
 #[win32_derive::dllexport(cdecl)]
@@ -35,6 +35,15 @@ pub fn memcmp(machine: &mut Machine, lhs: u32, rhs: u32, len: u32) -> u32 {
 }
 
 #[win32_derive::dllexport(cdecl)]
-pub fn _CxxThrowException(_machine: &mut Machine, pExceptionObject: u32, pThrowInfo: u32) -> u32 {
-    panic!("exception");
+pub fn _CxxThrowException(machine: &mut Machine, pExceptionObject: u32, pThrowInfo: u32) -> u32 {
+    // The magic number identifies the MSVC version of the exception object layout.
+    const EH_MAGIC_NUMBER1: u32 = 0x1993_0520;
+    kernel32::raise_from_shim(
+        machine,
+        kernel32::STATUS_CXX_EXCEPTION,
+        kernel32::EXCEPTION_NONCONTINUABLE,
+        &[EH_MAGIC_NUMBER1, pExceptionObject, pThrowInfo],
+        0,
+    );
+    0
 }
@@ -12,15 +12,51 @@ mod wrappers {
     };
     use ::memory::Extensions;
     use winapi::winmm::*;
-    pub unsafe fn PlaySoundW(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn PlaySoundA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
-        let pszSound = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+        let pszSound = <u32>::from_stack(mem, stack_args + 0u32);
         let hmod = <HMODULE>::from_stack(mem, stack_args + 4u32);
         let fdwSound = <u32>::from_stack(mem, stack_args + 8u32);
-        let __trace_record = if crate::trace::enabled("winmm/misc") {
+        let __trace_record = if crate::trace::enabled("winmm/playsound") {
+            crate::trace::Record::new(
+                winapi::winmm::PlaySoundA_pos,
+                "winmm/playsound",
+                "PlaySoundA",
+                &[
+                    ("pszSound", &pszSound),
+                    ("hmod", &hmod),
+                    ("fdwSound", &fdwSound),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::winmm::PlaySoundA(machine, pszSound, hmod, fdwSound).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn PlaySoundW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let pszSound = <u32>::from_stack(mem, stack_args + 0u32);
+        let hmod = <HMODULE>::from_stack(mem, stack_args + 4u32);
+        let fdwSound = <u32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("winmm/playsound") {
             crate::trace::Record::new(
                 winapi::winmm::PlaySoundW_pos,
-                "winmm/misc",
+                "winmm/playsound",
                 "PlaySoundW",
                 &[
                     ("pszSound", &pszSound),
@@ -32,11 +68,15 @@ mod wrappers {
         } else {
             None
         };
-        let result = winapi::winmm::PlaySoundW(machine, pszSound, hmod, fdwSound);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::winmm::PlaySoundW(machine, pszSound, hmod, fdwSound).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn joyGetDevCapsA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
@@ -103,52 +143,67 @@ mod wrappers {
     pub unsafe fn mciGetErrorStringA(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let mcierr = <u32>::from_stack(mem, stack_args + 0u32);
-        let pszText = <Option<&str>>::from_stack(mem, stack_args + 4u32);
-        let cchText = <u32>::from_stack(mem, stack_args + 8u32);
+        let pszText = <ArrayWithSizeMut<'_, u8>>::from_stack(mem, stack_args + 4u32);
         let __trace_record = if crate::trace::enabled("winmm/mci") {
             crate::trace::Record::new(
                 winapi::winmm::mciGetErrorStringA_pos,
                 "winmm/mci",
                 "mciGetErrorStringA",
-                &[
-                    ("mcierr", &mcierr),
-                    ("pszText", &pszText),
-                    ("cchText", &cchText),
-                ],
+                &[("mcierr", &mcierr), ("pszText", &pszText)],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::winmm::mciGetErrorStringA(machine, mcierr, pszText, cchText);
+        let result = winapi::winmm::mciGetErrorStringA(machine, mcierr, pszText);
         if let Some(mut __trace_record) = __trace_record {
             __trace_record.exit(&result);
         }
         result.into_abireturn()
     }
-    pub unsafe fn mciSendCommandA(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn mciSendCommandA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
-        let __trace_record = if crate::trace::enabled("winmm/misc") {
+        let IDDevice = <u32>::from_stack(mem, stack_args + 0u32);
+        let uMsg = <u32>::from_stack(mem, stack_args + 4u32);
+        let fdwCommand = <u32>::from_stack(mem, stack_args + 8u32);
+        let dwParam = <u32>::from_stack(mem, stack_args + 12u32);
+        let __trace_record = if crate::trace::enabled("winmm/mci") {
             crate::trace::Record::new(
                 winapi::winmm::mciSendCommandA_pos,
-                "winmm/misc",
+                "winmm/mci",
                 "mciSendCommandA",
-                &[],
+                &[
+                    ("IDDevice", &IDDevice),
+                    ("uMsg", &uMsg),
+                    ("fdwCommand", &fdwCommand),
+                    ("dwParam", &dwParam),
+                ],
             )
             .enter()
         } else {
             None
         };
-        let result = winapi::winmm::mciSendCommandA(machine);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result =
+                winapi::winmm::mciSendCommandA(machine, IDDevice, uMsg, fdwCommand, dwParam).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
-    pub unsafe fn mciSendStringA(machine: &mut Machine, stack_args: u32) -> u64 {
+    pub unsafe fn mciSendStringA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
         let mem = machine.mem().detach();
         let lpstrCommand = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-        let lpstrReturnString = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+        let lpstrReturnString = <ArrayWithSizeMut<'_, u8>>::from_stack(mem, stack_args + 4u32);
         let hwndCallback = <HWND>::from_stack(mem, stack_args + 12u32);
         let __trace_record = if crate::trace::enabled("winmm/mci") {
             crate::trace::Record::new(
@@ -165,12 +220,21 @@ mod wrappers {
         } else {
             None
         };
-        let result =
-            winapi::winmm::mciSendStringA(machine, lpstrCommand, lpstrReturnString, hwndCallback);
-        if let Some(mut __trace_record) = __trace_record {
-            __trace_record.exit(&result);
-        }
-        result.into_abireturn()
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::winmm::mciSendStringA(
+                machine,
+                lpstrCommand,
+                lpstrReturnString,
+                hwndCallback,
+            )
+            .await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
     }
     pub unsafe fn midiOutClose(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
@@ -237,6 +301,28 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn midiOutLongMsg(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hmo = <HMIDIOUT>::from_stack(mem, stack_args + 0u32);
+        let pmh = <u32>::from_stack(mem, stack_args + 4u32);
+        let cbmh = <u32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiOutLongMsg_pos,
+                "winmm/midi",
+                "midiOutLongMsg",
+                &[("hmo", &hmo), ("pmh", &pmh), ("cbmh", &cbmh)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiOutLongMsg(machine, hmo, pmh, cbmh);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn midiOutOpen(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let phmo = <Option<&mut HMIDIOUT>>::from_stack(mem, stack_args + 0u32);
@@ -268,6 +354,28 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn midiOutPrepareHeader(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hmo = <HMIDIOUT>::from_stack(mem, stack_args + 0u32);
+        let pmh = <u32>::from_stack(mem, stack_args + 4u32);
+        let cbmh = <u32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiOutPrepareHeader_pos,
+                "winmm/midi",
+                "midiOutPrepareHeader",
+                &[("hmo", &hmo), ("pmh", &pmh), ("cbmh", &cbmh)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiOutPrepareHeader(machine, hmo, pmh, cbmh);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn midiOutReset(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hmo = <HMIDIOUT>::from_stack(mem, stack_args + 0u32);
@@ -330,6 +438,190 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn midiOutUnprepareHeader(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hmo = <HMIDIOUT>::from_stack(mem, stack_args + 0u32);
+        let pmh = <u32>::from_stack(mem, stack_args + 4u32);
+        let cbmh = <u32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiOutUnprepareHeader_pos,
+                "winmm/midi",
+                "midiOutUnprepareHeader",
+                &[("hmo", &hmo), ("pmh", &pmh), ("cbmh", &cbmh)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiOutUnprepareHeader(machine, hmo, pmh, cbmh);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn midiStreamClose(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hms = <HMIDISTRM>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiStreamClose_pos,
+                "winmm/midi",
+                "midiStreamClose",
+                &[("hms", &hms)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiStreamClose(machine, hms);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn midiStreamOpen(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let phms = <Option<&mut HMIDISTRM>>::from_stack(mem, stack_args + 0u32);
+        let puDeviceID = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+        let cMidi = <u32>::from_stack(mem, stack_args + 8u32);
+        let dwCallback = <u32>::from_stack(mem, stack_args + 12u32);
+        let dwInstance = <u32>::from_stack(mem, stack_args + 16u32);
+        let fdwOpen = <u32>::from_stack(mem, stack_args + 20u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiStreamOpen_pos,
+                "winmm/midi",
+                "midiStreamOpen",
+                &[
+                    ("phms", &phms),
+                    ("puDeviceID", &puDeviceID),
+                    ("cMidi", &cMidi),
+                    ("dwCallback", &dwCallback),
+                    ("dwInstance", &dwInstance),
+                    ("fdwOpen", &fdwOpen),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiStreamOpen(
+            machine, phms, puDeviceID, cMidi, dwCallback, dwInstance, fdwOpen,
+        );
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn midiStreamOut(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hms = <HMIDISTRM>::from_stack(mem, stack_args + 0u32);
+        let pmh = <u32>::from_stack(mem, stack_args + 4u32);
+        let cbmh = <u32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiStreamOut_pos,
+                "winmm/midi",
+                "midiStreamOut",
+                &[("hms", &hms), ("pmh", &pmh), ("cbmh", &cbmh)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiStreamOut(machine, hms, pmh, cbmh);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn midiStreamPause(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hms = <HMIDISTRM>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiStreamPause_pos,
+                "winmm/midi",
+                "midiStreamPause",
+                &[("hms", &hms)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiStreamPause(machine, hms);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn midiStreamProperty(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hms = <HMIDISTRM>::from_stack(mem, stack_args + 0u32);
+        let lppropdata = <u32>::from_stack(mem, stack_args + 4u32);
+        let dwProperty = <u32>::from_stack(mem, stack_args + 8u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiStreamProperty_pos,
+                "winmm/midi",
+                "midiStreamProperty",
+                &[
+                    ("hms", &hms),
+                    ("lppropdata", &lppropdata),
+                    ("dwProperty", &dwProperty),
+                ],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiStreamProperty(machine, hms, lppropdata, dwProperty);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn midiStreamRestart(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hms = <HMIDISTRM>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiStreamRestart_pos,
+                "winmm/midi",
+                "midiStreamRestart",
+                &[("hms", &hms)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiStreamRestart(machine, hms);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
+    pub unsafe fn midiStreamStop(machine: &mut Machine, stack_args: u32) -> u64 {
+        let mem = machine.mem().detach();
+        let hms = <HMIDISTRM>::from_stack(mem, stack_args + 0u32);
+        let __trace_record = if crate::trace::enabled("winmm/midi") {
+            crate::trace::Record::new(
+                winapi::winmm::midiStreamStop_pos,
+                "winmm/midi",
+                "midiStreamStop",
+                &[("hms", &hms)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let result = winapi::winmm::midiStreamStop(machine, hms);
+        if let Some(mut __trace_record) = __trace_record {
+            __trace_record.exit(&result);
+        }
+        result.into_abireturn()
+    }
     pub unsafe fn mixerClose(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let hmx = <u32>::from_stack(mem, stack_args + 0u32);
@@ -474,6 +766,62 @@ mod wrappers {
         }
         result.into_abireturn()
     }
+    pub unsafe fn sndPlaySoundA(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let lpszSound = <u32>::from_stack(mem, stack_args + 0u32);
+        let fuSound = <u32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("winmm/playsound") {
+            crate::trace::Record::new(
+                winapi::winmm::sndPlaySoundA_pos,
+                "winmm/playsound",
+                "sndPlaySoundA",
+                &[("lpszSound", &lpszSound), ("fuSound", &fuSound)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::winmm::sndPlaySoundA(machine, lpszSound, fuSound).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
+    pub unsafe fn sndPlaySoundW(
+        machine: &mut Machine,
+        stack_args: u32,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u64>>> {
+        let mem = machine.mem().detach();
+        let lpszSound = <u32>::from_stack(mem, stack_args + 0u32);
+        let fuSound = <u32>::from_stack(mem, stack_args + 4u32);
+        let __trace_record = if crate::trace::enabled("winmm/playsound") {
+            crate::trace::Record::new(
+                winapi::winmm::sndPlaySoundW_pos,
+                "winmm/playsound",
+                "sndPlaySoundW",
+                &[("lpszSound", &lpszSound), ("fuSound", &fuSound)],
+            )
+            .enter()
+        } else {
+            None
+        };
+        let machine: *mut Machine = machine;
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine };
+            let result = winapi::winmm::sndPlaySoundW(machine, lpszSound, fuSound).await;
+            if let Some(mut __trace_record) = __trace_record {
+                __trace_record.exit(&result);
+            }
+            result.into_abireturn()
+        })
+    }
     pub unsafe fn timeBeginPeriod(machine: &mut Machine, stack_args: u32) -> u64 {
         let mem = machine.mem().detach();
         let uPeriod = <u32>::from_stack(mem, stack_args + 0u32);
@@ -895,10 +1243,14 @@ mod wrappers {
         result.into_abireturn()
     }
 }
-const SHIMS: [Shim; 39usize] = [
+const SHIMS: [Shim; 52usize] = [
+    Shim {
+        name: "PlaySoundA",
+        func: Handler::Async(wrappers::PlaySoundA),
+    },
     Shim {
         name: "PlaySoundW",
-        func: Handler::Sync(wrappers::PlaySoundW),
+        func: Handler::Async(wrappers::PlaySoundW),
     },
     Shim {
         name: "joyGetDevCapsA",
@@ -918,11 +1270,11 @@ const SHIMS: [Shim; 39usize] = [
     },
     Shim {
         name: "mciSendCommandA",
-        func: Handler::Sync(wrappers::mciSendCommandA),
+        func: Handler::Async(wrappers::mciSendCommandA),
     },
     Shim {
         name: "mciSendStringA",
-        func: Handler::Sync(wrappers::mciSendStringA),
+        func: Handler::Async(wrappers::mciSendStringA),
     },
     Shim {
         name: "midiOutClose",
@@ -936,10 +1288,18 @@ const SHIMS: [Shim; 39usize] = [
         name: "midiOutGetNumDevs",
         func: Handler::Sync(wrappers::midiOutGetNumDevs),
     },
+    Shim {
+        name: "midiOutLongMsg",
+        func: Handler::Sync(wrappers::midiOutLongMsg),
+    },
     Shim {
         name: "midiOutOpen",
         func: Handler::Sync(wrappers::midiOutOpen),
     },
+    Shim {
+        name: "midiOutPrepareHeader",
+        func: Handler::Sync(wrappers::midiOutPrepareHeader),
+    },
     Shim {
         name: "midiOutReset",
         func: Handler::Sync(wrappers::midiOutReset),
@@ -952,6 +1312,38 @@ const SHIMS: [Shim; 39usize] = [
         name: "midiOutShortMsg",
         func: Handler::Sync(wrappers::midiOutShortMsg),
     },
+    Shim {
+        name: "midiOutUnprepareHeader",
+        func: Handler::Sync(wrappers::midiOutUnprepareHeader),
+    },
+    Shim {
+        name: "midiStreamClose",
+        func: Handler::Sync(wrappers::midiStreamClose),
+    },
+    Shim {
+        name: "midiStreamOpen",
+        func: Handler::Sync(wrappers::midiStreamOpen),
+    },
+    Shim {
+        name: "midiStreamOut",
+        func: Handler::Sync(wrappers::midiStreamOut),
+    },
+    Shim {
+        name: "midiStreamPause",
+        func: Handler::Sync(wrappers::midiStreamPause),
+    },
+    Shim {
+        name: "midiStreamProperty",
+        func: Handler::Sync(wrappers::midiStreamProperty),
+    },
+    Shim {
+        name: "midiStreamRestart",
+        func: Handler::Sync(wrappers::midiStreamRestart),
+    },
+    Shim {
+        name: "midiStreamStop",
+        func: Handler::Sync(wrappers::midiStreamStop),
+    },
     Shim {
         name: "mixerClose",
         func: Handler::Sync(wrappers::mixerClose),
@@ -976,6 +1368,14 @@ const SHIMS: [Shim; 39usize] = [
         name: "mixerSetControlDetails",
         func: Handler::Sync(wrappers::mixerSetControlDetails),
     },
+    Shim {
+        name: "sndPlaySoundA",
+        func: Handler::Async(wrappers::sndPlaySoundA),
+    },
+    Shim {
+        name: "sndPlaySoundW",
+        func: Handler::Async(wrappers::sndPlaySoundW),
+    },
     Shim {
         name: "timeBeginPeriod",
         func: Handler::Sync(wrappers::timeBeginPeriod),
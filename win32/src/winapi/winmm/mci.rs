@@ -1,29 +1,1569 @@
+//! Media Control Interface: the command string and command message interfaces to
+//! waveaudio (WAV files), sequencer (MIDI files) and cdaudio devices.
+//!
+//! CD audio is emulated by a directory of per-track WAV files, named track01.wav,
+//! track02.wav etc.  Missing tracks are treated as data tracks.
+
+use super::{
+    midi::{self, Callback},
+    output, smf,
+    smf::Sequence,
+    synth::Synth,
+    wav::{self, Clip},
+};
 use crate::{
-    winapi::{calling_convention::ArrayWithSizeMut, types::HWND},
-    Machine,
+    host::{FileOptions, Host},
+    machine::Machine,
+    winapi::{
+        calling_convention::ArrayWithSizeMut, dsound::mixer::Format, kernel32, types::HWND,
+        user32::PostMessageA,
+    },
 };
+use memory::{Extensions, ExtensionsMut};
+use std::{collections::BTreeMap, io::Read};
+use typed_path::WindowsPath;
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MCIERR {
+    INVALID_DEVICE_ID = 257,
+    UNRECOGNIZED_KEYWORD = 259,
+    UNRECOGNIZED_COMMAND = 261,
+    INVALID_DEVICE_NAME = 263,
+    DEVICE_OPEN = 265,
+    MISSING_COMMAND_STRING = 267,
+    PARAM_OVERFLOW = 268,
+    BAD_INTEGER = 270,
+    MISSING_PARAMETER = 273,
+    UNSUPPORTED_FUNCTION = 274,
+    FILE_NOT_FOUND = 275,
+    DEVICE_NOT_READY = 276,
+    OUTOFRANGE = 282,
+    DEVICE_TYPE_REQUIRED = 287,
+    DUPLICATE_ALIAS = 289,
+    BAD_CONSTANT = 290,
+    BAD_TIME_FORMAT = 293,
+    NO_CLOSING_QUOTE = 294,
+    INVALID_FILE = 296,
+    NULL_PARAMETER_BLOCK = 297,
+    MISSING_DEVICE_NAME = 292,
+    DEVICE_NOT_INSTALLED = 306,
+    SEQ_PORT_INUSE = 337,
+}
+
+impl MCIERR {
+    fn from_raw(code: u32) -> Option<Self> {
+        use MCIERR::*;
+        [
+            INVALID_DEVICE_ID,
+            UNRECOGNIZED_KEYWORD,
+            UNRECOGNIZED_COMMAND,
+            INVALID_DEVICE_NAME,
+            DEVICE_OPEN,
+            MISSING_COMMAND_STRING,
+            PARAM_OVERFLOW,
+            BAD_INTEGER,
+            MISSING_PARAMETER,
+            UNSUPPORTED_FUNCTION,
+            FILE_NOT_FOUND,
+            DEVICE_NOT_READY,
+            OUTOFRANGE,
+            DEVICE_TYPE_REQUIRED,
+            DUPLICATE_ALIAS,
+            BAD_CONSTANT,
+            BAD_TIME_FORMAT,
+            NO_CLOSING_QUOTE,
+            INVALID_FILE,
+            NULL_PARAMETER_BLOCK,
+            MISSING_DEVICE_NAME,
+            DEVICE_NOT_INSTALLED,
+            SEQ_PORT_INUSE,
+        ]
+        .into_iter()
+        .find(|&err| err as u32 == code)
+    }
+
+    /// The text Windows uses for the error.
+    fn message(self) -> &'static str {
+        use MCIERR::*;
+        match self {
+            INVALID_DEVICE_ID => {
+                "Invalid device ID. Use the ID given to this device when the device was opened."
+            }
+            UNRECOGNIZED_KEYWORD => "The driver cannot recognize the specified command parameter.",
+            UNRECOGNIZED_COMMAND => "The driver cannot recognize the specified command.",
+            INVALID_DEVICE_NAME => "The specified device is not open or is not recognized by MCI.",
+            DEVICE_OPEN => {
+                "The device name is already being used as an alias by this application. Use a unique alias."
+            }
+            MISSING_COMMAND_STRING => "No command was specified.",
+            PARAM_OVERFLOW => {
+                "The output string was too large to fit in the return buffer. Increase the size of the buffer."
+            }
+            BAD_INTEGER => "The specified integer is invalid for this command.",
+            MISSING_PARAMETER => {
+                "The specified command requires a parameter. Please supply one."
+            }
+            UNSUPPORTED_FUNCTION => {
+                "The MCI device you are using does not support the specified command."
+            }
+            FILE_NOT_FOUND => {
+                "Cannot find the specified file. Make sure the path and filename are correct."
+            }
+            DEVICE_NOT_READY => "The device driver is not ready.",
+            OUTOFRANGE => "The specified parameter is out of range for the specified command.",
+            DEVICE_TYPE_REQUIRED => {
+                "The specified device cannot be found on this system. Check that the device is installed and the device name is spelled correctly."
+            }
+            DUPLICATE_ALIAS => {
+                "The specified alias is already being used in this application. Use a unique alias."
+            }
+            BAD_CONSTANT => "The specified parameter value is unknown for this command.",
+            BAD_TIME_FORMAT => "The specified MCI time format is invalid.",
+            NO_CLOSING_QUOTE => {
+                "A closing quotation mark is missing from the parameter value. Please supply one."
+            }
+            INVALID_FILE => {
+                "The specified file cannot be played on the specified MCI device. The file may be corrupt, not in the correct format, or no file handler available for this format."
+            }
+            NULL_PARAMETER_BLOCK => "A null parameter block was passed to MCI.",
+            MISSING_DEVICE_NAME => "The specified command requires an alias, file, driver, or device name. Please supply one.",
+            DEVICE_NOT_INSTALLED => {
+                "The specified device is not installed on the system. Use the Drivers option in Control Panel to install the device."
+            }
+            SEQ_PORT_INUSE => {
+                "The specified MIDI port is already in use. Wait until it is free; then try again."
+            }
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, MCIERR>;
+
+const MCI_ALL_DEVICE_ID: u32 = 0xFFFF;
+
+const MM_MCINOTIFY: u32 = 0x3B9;
+const MCI_NOTIFY_SUCCESSFUL: u32 = 0x0001;
+const MCI_NOTIFY_SUPERSEDED: u32 = 0x0002;
+const MCI_NOTIFY_ABORTED: u32 = 0x0004;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DeviceType {
+    CdAudio = 516,
+    WaveAudio = 522,
+    Sequencer = 523,
+}
+
+impl DeviceType {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "cdaudio" => DeviceType::CdAudio,
+            "waveaudio" => DeviceType::WaveAudio,
+            "sequencer" => DeviceType::Sequencer,
+            _ => return None,
+        })
+    }
+
+    fn from_id(id: u32) -> Option<Self> {
+        [
+            DeviceType::CdAudio,
+            DeviceType::WaveAudio,
+            DeviceType::Sequencer,
+        ]
+        .into_iter()
+        .find(|&t| t as u32 == id & 0xFFFF)
+    }
+
+    fn from_extension(path: &str) -> Option<Self> {
+        let (_, ext) = path.rsplit_once('.')?;
+        Some(match ext.to_ascii_lowercase().as_str() {
+            "wav" => DeviceType::WaveAudio,
+            "mid" | "midi" | "rmi" => DeviceType::Sequencer,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            DeviceType::CdAudio => "cdaudio",
+            DeviceType::WaveAudio => "waveaudio",
+            DeviceType::Sequencer => "sequencer",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TimeFormat {
+    Milliseconds = 0,
+    Msf = 2,
+    Bytes = 8,
+    Samples = 9,
+    Tmsf = 10,
+}
+
+impl TimeFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "milliseconds" | "ms" => TimeFormat::Milliseconds,
+            "msf" => TimeFormat::Msf,
+            "bytes" => TimeFormat::Bytes,
+            "samples" => TimeFormat::Samples,
+            "tmsf" => TimeFormat::Tmsf,
+            _ => return None,
+        })
+    }
+
+    fn from_id(id: u32) -> Option<Self> {
+        [
+            TimeFormat::Milliseconds,
+            TimeFormat::Msf,
+            TimeFormat::Bytes,
+            TimeFormat::Samples,
+            TimeFormat::Tmsf,
+        ]
+        .into_iter()
+        .find(|&t| t as u32 == id)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TimeFormat::Milliseconds => "milliseconds",
+            TimeFormat::Msf => "msf",
+            TimeFormat::Bytes => "bytes",
+            TimeFormat::Samples => "samples",
+            TimeFormat::Tmsf => "tmsf",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    NotReady = 524,
+    Stopped = 525,
+    Playing = 526,
+    Paused = 529,
+}
+
+impl Mode {
+    fn name(self) -> &'static str {
+        match self {
+            Mode::NotReady => "not ready",
+            Mode::Stopped => "stopped",
+            Mode::Playing => "playing",
+            Mode::Paused => "paused",
+        }
+    }
+}
+
+struct Track {
+    path: String,
+    /// None for data tracks.
+    format: Option<Format>,
+    length_ms: f64,
+}
+
+enum Media {
+    Wave(Clip),
+    Sequencer(Sequence),
+    CdAudio {
+        tracks: Vec<Track>,
+        /// The currently loaded track, by index.
+        loaded: Option<(usize, Clip)>,
+    },
+}
+
+struct Device {
+    device_type: DeviceType,
+    alias: String,
+    media: Media,
+    time_format: TimeFormat,
+    mode: Mode,
+    /// Play position for wave and CD devices; sequences track their own.
+    position_ms: f64,
+    /// Where the current play command stops.
+    to_ms: f64,
+    /// Window awaiting MM_MCINOTIFY for the current play command.
+    notify: Option<HWND>,
+}
+
+/// Milliseconds to a CD frame count; there are 75 frames per second.
+fn ms_to_frames(ms: f64) -> u32 {
+    (ms * 75.0 / 1000.0) as u32
+}
+
+fn pack_msf(ms: f64) -> u32 {
+    let frames = ms_to_frames(ms);
+    let (m, s, f) = (frames / 75 / 60, frames / 75 % 60, frames % 75);
+    m | s << 8 | f << 16
+}
+
+fn unpack_msf(m: u32, s: u32, f: u32) -> f64 {
+    (m * 60 + s) as f64 * 1000.0 + f as f64 * 1000.0 / 75.0
+}
+
+impl Device {
+    fn new(device_type: DeviceType, alias: String, media: Media) -> Self {
+        Device {
+            device_type,
+            alias,
+            media,
+            time_format: match device_type {
+                DeviceType::CdAudio => TimeFormat::Msf,
+                _ => TimeFormat::Milliseconds,
+            },
+            mode: Mode::Stopped,
+            position_ms: 0.0,
+            to_ms: 0.0,
+            notify: None,
+        }
+    }
+
+    fn tracks(&self) -> &[Track] {
+        match &self.media {
+            Media::CdAudio { tracks, .. } => tracks,
+            _ => &[],
+        }
+    }
+
+    fn track_start_ms(&self, track: usize) -> f64 {
+        self.tracks()[..track].iter().map(|t| t.length_ms).sum()
+    }
+
+    /// The index of the track containing a position.
+    fn track_at(&self, ms: f64) -> Option<usize> {
+        let mut start = 0.0;
+        for (i, track) in self.tracks().iter().enumerate() {
+            start += track.length_ms;
+            if ms < start {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    fn length_ms(&self) -> f64 {
+        match &self.media {
+            Media::Wave(clip) => clip.length_ms(),
+            Media::Sequencer(seq) => seq.length_ms,
+            Media::CdAudio { tracks, .. } => tracks.iter().map(|t| t.length_ms).sum(),
+        }
+    }
+
+    fn position_ms(&self) -> f64 {
+        match &self.media {
+            Media::Sequencer(seq) => seq.position_ms,
+            _ => self.position_ms,
+        }
+    }
+
+    fn wave_format(&self) -> Result<Format> {
+        match &self.media {
+            Media::Wave(clip) => Ok(clip.format),
+            _ => Err(MCIERR::BAD_TIME_FORMAT),
+        }
+    }
+
+    fn set_time_format(&mut self, format: TimeFormat) -> Result<()> {
+        let ok = match format {
+            TimeFormat::Milliseconds => true,
+            TimeFormat::Msf | TimeFormat::Tmsf => self.device_type == DeviceType::CdAudio,
+            TimeFormat::Bytes | TimeFormat::Samples => self.device_type == DeviceType::WaveAudio,
+        };
+        if !ok {
+            return Err(MCIERR::BAD_TIME_FORMAT);
+        }
+        self.time_format = format;
+        Ok(())
+    }
+
+    /// Convert a time in the device's time format to milliseconds.
+    fn time_to_ms(&self, value: u32) -> Result<f64> {
+        let [b0, b1, b2, b3] = value.to_le_bytes().map(|b| b as u32);
+        Ok(match self.time_format {
+            TimeFormat::Milliseconds => value as f64,
+            TimeFormat::Msf => unpack_msf(b0, b1, b2),
+            TimeFormat::Tmsf => {
+                if b0 == 0 || b0 as usize > self.tracks().len() {
+                    return Err(MCIERR::OUTOFRANGE);
+                }
+                self.track_start_ms(b0 as usize - 1) + unpack_msf(b1, b2, b3)
+            }
+            TimeFormat::Samples => value as f64 * 1000.0 / self.wave_format()?.sample_rate as f64,
+            TimeFormat::Bytes => {
+                let format = self.wave_format()?;
+                (value / format.block_align()) as f64 * 1000.0 / format.sample_rate as f64
+            }
+        })
+    }
+
+    /// Convert milliseconds to the device's time format.
+    fn ms_to_time(&self, ms: f64) -> u32 {
+        match self.time_format {
+            TimeFormat::Milliseconds => ms as u32,
+            TimeFormat::Msf => pack_msf(ms),
+            TimeFormat::Tmsf => {
+                let track = self
+                    .track_at(ms)
+                    .unwrap_or(self.tracks().len().saturating_sub(1));
+                let offset = (ms - self.track_start_ms(track)).max(0.0);
+                (track as u32 + 1) | pack_msf(offset) << 8
+            }
+            TimeFormat::Samples => {
+                let format = self.wave_format().unwrap();
+                (ms * format.sample_rate as f64 / 1000.0) as u32
+            }
+            TimeFormat::Bytes => {
+                let format = self.wave_format().unwrap();
+                (ms * format.sample_rate as f64 / 1000.0) as u32 * format.block_align()
+            }
+        }
+    }
+
+    /// Format a time value as the string interface reports it.
+    fn format_time(&self, value: u32, format: TimeFormat) -> String {
+        let [b0, b1, b2, b3] = value.to_le_bytes();
+        match format {
+            TimeFormat::Msf => format!("{b0:02}:{b1:02}:{b2:02}"),
+            TimeFormat::Tmsf => format!("{b0:02}:{b1:02}:{b2:02}:{b3:02}"),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Parse a time in the device's format from the string interface.
+    fn parse_time(&self, text: &str) -> Result<u32> {
+        let parse = |s: &str| s.parse::<u32>().map_err(|_| MCIERR::BAD_INTEGER);
+        match self.time_format {
+            TimeFormat::Msf | TimeFormat::Tmsf => {
+                let mut packed = 0;
+                for (i, part) in text.split(':').enumerate() {
+                    let n = parse(part)?;
+                    if i >= 4 || n > 0xFF {
+                        return Err(MCIERR::BAD_INTEGER);
+                    }
+                    packed |= n << (i * 8);
+                }
+                Ok(packed)
+            }
+            _ => parse(text),
+        }
+    }
+
+    /// Mix wave or CD playback into out.  Returns true when playback reached its end.
+    fn mix(&mut self, host: &dyn Host, mut out: &mut [[f32; 2]], rate: u32) -> bool {
+        loop {
+            if self.position_ms >= self.to_ms {
+                return true;
+            }
+            let Some(track) = self.track_at(self.position_ms) else {
+                // A wave device, or a CD past its last track.
+                return match &self.media {
+                    Media::Wave(clip) => {
+                        clip.mix(out, &mut self.position_ms, self.to_ms, rate);
+                        self.position_ms >= self.to_ms
+                    }
+                    _ => true,
+                };
+            };
+
+            let start = self.track_start_ms(track);
+            let end = start + self.tracks()[track].length_ms;
+            let Media::CdAudio { tracks, loaded } = &mut self.media else {
+                unreachable!()
+            };
+            if loaded.as_ref().map(|(i, _)| *i) != Some(track) {
+                *loaded = load_track(host, &tracks[track]).map(|clip| (track, clip));
+            }
+            let Some((_, clip)) = loaded else {
+                // Data track, or failed to load: skip it.
+                self.position_ms = end;
+                continue;
+            };
+            let mut offset = self.position_ms - start;
+            let n = clip.mix(out, &mut offset, self.to_ms.min(end) - start, rate);
+            self.position_ms = start + offset;
+            if n == out.len() {
+                return self.position_ms >= self.to_ms;
+            }
+            // Track finished; continue with the next.
+            self.position_ms = self.position_ms.max(end);
+            out = &mut out[n..];
+        }
+    }
+}
+
+fn read_file(host: &dyn Host, path: &str) -> Result<Vec<u8>> {
+    let mut file = host
+        .open(WindowsPath::new(path), FileOptions::read())
+        .map_err(|_| MCIERR::FILE_NOT_FOUND)?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)
+        .map_err(|_| MCIERR::FILE_NOT_FOUND)?;
+    Ok(buf)
+}
+
+/// Find the per-track files standing in for an audio CD.
+/// Only the start of each is read, to get its format and length.
+fn scan_tracks(host: &dyn Host, dir: &str) -> Vec<Track> {
+    let mut tracks = Vec::new();
+    for n in 1..=99 {
+        let path = format!("{dir}\\track{n:02}.wav");
+        let mut track = Track {
+            path,
+            format: None,
+            length_ms: 0.0,
+        };
+        if let Ok(mut file) = host.open(WindowsPath::new(&track.path), FileOptions::read()) {
+            let mut header = Vec::new();
+            _ = (&mut *file).take(4096).read_to_end(&mut header);
+            if let Some(wav) = wav::parse(&header) {
+                track.format = Some(wav.format);
+                track.length_ms = wav.length_ms();
+            }
+        }
+        tracks.push(track);
+    }
+    // Trailing missing tracks aren't on the disc.
+    while tracks.last().is_some_and(|t| t.format.is_none()) {
+        tracks.pop();
+    }
+    tracks
+}
+
+fn load_track(host: &dyn Host, track: &Track) -> Option<Clip> {
+    track.format?;
+    let buf = read_file(host, &track.path).ok()?;
+    Some(wav::parse(&buf)?.decode())
+}
+
+pub struct State {
+    devices: BTreeMap<u32, Device>,
+    next_id: u32,
+    /// Devices that finished playing, awaiting notification.
+    finished: Vec<u32>,
+    /// Directory of trackNN.wav files that cdaudio plays.
+    pub cd_audio_dir: String,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            devices: BTreeMap::new(),
+            next_id: 1,
+            finished: Vec::new(),
+            cd_audio_dir: "cdaudio".into(),
+        }
+    }
+}
+
+impl State {
+    pub fn is_playing(&self) -> bool {
+        self.devices.values().any(|d| d.mode == Mode::Playing)
+    }
+
+    fn playing_sequences(&mut self) -> impl Iterator<Item = (u32, &mut Sequence, f64)> {
+        self.devices
+            .iter_mut()
+            .filter_map(|(&id, dev)| match &mut dev.media {
+                Media::Sequencer(seq) if dev.mode == Mode::Playing => Some((id, seq, dev.to_ms)),
+                _ => None,
+            })
+    }
+
+    /// Play due sequencer events, returning the number of frames until the next one.
+    pub fn run_sequencers(&mut self, synth: &mut Synth, rate: u32) -> Option<usize> {
+        self.playing_sequences()
+            .map(|(_, seq, to_ms)| {
+                let until_end = to_ms - seq.position_ms;
+                let ms = seq.run(synth).map_or(until_end, |ms| ms.min(until_end));
+                (ms * rate as f64 / 1000.0).ceil().max(1.0) as usize
+            })
+            .min()
+    }
+
+    pub fn advance_sequencers(&mut self, frames: usize, rate: u32) {
+        let ms = frames as f64 * 1000.0 / rate as f64;
+        let mut finished = Vec::new();
+        for (id, seq, to_ms) in self.playing_sequences() {
+            seq.advance(ms);
+            if seq.position_ms >= to_ms {
+                finished.push(id);
+            }
+        }
+        for id in finished {
+            self.devices.get_mut(&id).unwrap().mode = Mode::Stopped;
+            self.finished.push(id);
+        }
+    }
+
+    /// Mix playing wave and CD devices into out.
+    pub fn mix(&mut self, host: &dyn Host, out: &mut [[f32; 2]], rate: u32) {
+        for (&id, dev) in self.devices.iter_mut() {
+            if dev.mode != Mode::Playing || matches!(dev.media, Media::Sequencer(_)) {
+                continue;
+            }
+            if dev.mix(host, out, rate) {
+                dev.mode = Mode::Stopped;
+                self.finished.push(id);
+            }
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<u32> {
+        self.devices
+            .iter()
+            .find(|(_, dev)| dev.alias.eq_ignore_ascii_case(name))
+            .map(|(&id, _)| id)
+    }
+}
+
+fn post_notify(machine: &mut Machine, hwnd: HWND, flag: u32, id: u32) {
+    if hwnd.is_null() {
+        return;
+    }
+    PostMessageA(machine, hwnd, MM_MCINOTIFY, flag, id);
+}
+
+/// Send MM_MCINOTIFY for devices that finished playing.
+pub fn notify_finished(machine: &mut Machine) {
+    let finished = std::mem::take(&mut machine.state.winmm.mci.finished);
+    for id in finished {
+        let Some(dev) = machine.state.winmm.mci.devices.get_mut(&id) else {
+            continue;
+        };
+        if let Some(hwnd) = dev.notify.take() {
+            post_notify(machine, hwnd, MCI_NOTIFY_SUCCESSFUL, id);
+        }
+        if let Some(midi) = machine.state.winmm.midi.as_mut() {
+            if dev_is_sequencer(&machine.state.winmm.mci, id) {
+                midi::all_notes_off(&mut midi.synth);
+            }
+        }
+    }
+}
+
+fn dev_is_sequencer(mci: &State, id: u32) -> bool {
+    mci.devices
+        .get(&id)
+        .is_some_and(|d| d.device_type == DeviceType::Sequencer)
+}
+
+#[derive(Debug)]
+enum SeekTo {
+    Start,
+    End,
+    Position(u32),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum StatusItem {
+    Length,
+    Position,
+    NumberOfTracks,
+    Mode,
+    MediaPresent,
+    TimeFormat,
+    Ready,
+    CurrentTrack,
+    TrackType,
+}
+
+/// The arguments of an open, from either the string or the message interface.
+/// Open is handled apart from other commands, as it has no device yet.
+#[derive(Debug)]
+struct OpenArgs {
+    device_type: Option<DeviceType>,
+    element: Option<String>,
+    alias: Option<String>,
+}
+
+/// A command on an open device, from either the string or the message interface.
+#[derive(Debug)]
+enum Command {
+    Close,
+    Play {
+        from: Option<u32>,
+        to: Option<u32>,
+    },
+    Stop,
+    Pause,
+    Resume,
+    Seek(SeekTo),
+    Set {
+        time_format: Option<TimeFormat>,
+    },
+    Status {
+        item: StatusItem,
+        track: Option<u32>,
+    },
+}
+
+/// A command's result, reported as a string or a DWORD depending on the interface.
+enum Value {
+    None,
+    Integer(u32),
+    Bool(bool),
+    Mode(Mode),
+    TimeFormat(TimeFormat),
+    Time { value: u32, text: String },
+    TrackType { audio: bool },
+}
+
+impl Value {
+    fn to_text(&self) -> String {
+        match self {
+            Value::None => String::new(),
+            Value::Integer(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Mode(mode) => mode.name().into(),
+            Value::TimeFormat(format) => format.name().into(),
+            Value::Time { text, .. } => text.clone(),
+            Value::TrackType { audio: true } => "audio".into(),
+            Value::TrackType { audio: false } => "other".into(),
+        }
+    }
+
+    fn to_dword(&self) -> u32 {
+        const MCI_CDA_TRACK_AUDIO: u32 = 1088;
+        const MCI_CDA_TRACK_OTHER: u32 = 1089;
+        match *self {
+            Value::None => 0,
+            Value::Integer(n) => n,
+            Value::Bool(b) => b as u32,
+            Value::Mode(mode) => mode as u32,
+            Value::TimeFormat(format) => format as u32,
+            Value::Time { value, .. } => value,
+            Value::TrackType { audio: true } => MCI_CDA_TRACK_AUDIO,
+            Value::TrackType { audio: false } => MCI_CDA_TRACK_OTHER,
+        }
+    }
+}
+
+fn open(machine: &mut Machine, args: OpenArgs) -> Result<u32> {
+    let OpenArgs {
+        device_type,
+        element,
+        alias,
+    } = args;
+    if !machine.state.winmm.audio_enabled {
+        return Err(MCIERR::DEVICE_NOT_INSTALLED);
+    }
+    let device_type = device_type
+        .or_else(|| DeviceType::from_extension(element.as_deref()?))
+        .ok_or(MCIERR::DEVICE_TYPE_REQUIRED)?;
+    let explicit_alias = alias.is_some();
+    let alias = alias
+        .or_else(|| element.clone())
+        .unwrap_or_else(|| device_type.name().into());
+    if machine.state.winmm.mci.find(&alias).is_some() {
+        return Err(if explicit_alias {
+            MCIERR::DUPLICATE_ALIAS
+        } else {
+            MCIERR::DEVICE_OPEN
+        });
+    }
+
+    let media = match device_type {
+        DeviceType::WaveAudio => {
+            let element = element.ok_or(MCIERR::MISSING_PARAMETER)?;
+            let buf = read_file(&*machine.host, &element)?;
+            let wav = wav::parse(&buf).ok_or(MCIERR::INVALID_FILE)?;
+            Media::Wave(wav.decode())
+        }
+        DeviceType::Sequencer => {
+            let element = element.ok_or(MCIERR::MISSING_PARAMETER)?;
+            let buf = read_file(&*machine.host, &element)?;
+            let seq = smf::parse(&buf).ok_or(MCIERR::INVALID_FILE)?;
+            // The sequencer plays through the MIDI device, so it can't be in use.
+            if machine.state.winmm.midi.is_some() {
+                return Err(MCIERR::SEQ_PORT_INUSE);
+            }
+            match midi::open(machine, 0, Callback::None, None) {
+                super::MMRESULT::MMSYSERR_NOERROR => {}
+                _ => return Err(MCIERR::DEVICE_NOT_INSTALLED),
+            }
+            Media::Sequencer(seq)
+        }
+        DeviceType::CdAudio => {
+            let dir = machine.state.winmm.mci.cd_audio_dir.clone();
+            Media::CdAudio {
+                tracks: scan_tracks(&*machine.host, &dir),
+                loaded: None,
+            }
+        }
+    };
+    output::ensure_audio(machine);
+
+    let mci = &mut machine.state.winmm.mci;
+    let id = mci.next_id;
+    mci.next_id += 1;
+    mci.devices
+        .insert(id, Device::new(device_type, alias, media));
+    Ok(id)
+}
+
+/// Stop playback, e.g. for stop, seek or close, notifying any pending play as aborted.
+fn stop(machine: &mut Machine, id: u32) {
+    output::render(machine);
+    let winmm = &mut machine.state.winmm;
+    let dev = winmm.mci.devices.get_mut(&id).unwrap();
+    if dev.mode == Mode::Playing || dev.mode == Mode::Paused {
+        dev.mode = Mode::Stopped;
+        if dev.device_type == DeviceType::Sequencer {
+            midi::all_notes_off(&mut winmm.midi.as_mut().unwrap().synth);
+        }
+    }
+    if let Some(hwnd) = dev.notify.take() {
+        post_notify(machine, hwnd, MCI_NOTIFY_ABORTED, id);
+    }
+}
+
+/// Move the play position of a device.
+fn seek(machine: &mut Machine, id: u32, ms: f64) {
+    let winmm = &mut machine.state.winmm;
+    let dev = winmm.mci.devices.get_mut(&id).unwrap();
+    let ms = ms.clamp(0.0, dev.length_ms());
+    match &mut dev.media {
+        Media::Sequencer(seq) => seq.seek(ms, &mut winmm.midi.as_mut().unwrap().synth),
+        _ => dev.position_ms = ms,
+    }
+}
+
+fn execute(
+    machine: &mut Machine,
+    id: u32,
+    command: Command,
+    notify: Option<HWND>,
+) -> Result<Value> {
+    if id == MCI_ALL_DEVICE_ID {
+        let Command::Close = command else {
+            return Err(MCIERR::UNSUPPORTED_FUNCTION);
+        };
+        let ids = machine
+            .state
+            .winmm
+            .mci
+            .devices
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        for id in ids {
+            execute(machine, id, Command::Close, None)?;
+        }
+        return Ok(Value::None);
+    }
+    if !machine.state.winmm.mci.devices.contains_key(&id) {
+        return Err(MCIERR::INVALID_DEVICE_ID);
+    }
+    output::render(machine);
+
+    let value = match command {
+        Command::Close => {
+            stop(machine, id);
+            let dev = machine.state.winmm.mci.devices.remove(&id).unwrap();
+            if dev.device_type == DeviceType::Sequencer {
+                machine.state.winmm.midi = None;
+            }
+            Value::None
+        }
+        Command::Play { from, to } => {
+            let dev = &machine.state.winmm.mci.devices[&id];
+            if dev.device_type == DeviceType::CdAudio && dev.tracks().is_empty() {
+                return Err(MCIERR::DEVICE_NOT_READY);
+            }
+            let from = from.map(|t| dev.time_to_ms(t)).transpose()?;
+            let to = match to {
+                Some(t) => dev.time_to_ms(t)?,
+                None => dev.length_ms(),
+            };
+            if let Some(from) = from {
+                seek(machine, id, from);
+            }
+            let dev = machine.state.winmm.mci.devices.get_mut(&id).unwrap();
+            dev.to_ms = to.min(dev.length_ms());
+            dev.mode = Mode::Playing;
+            if let Some(hwnd) = std::mem::replace(&mut dev.notify, notify) {
+                post_notify(machine, hwnd, MCI_NOTIFY_SUPERSEDED, id);
+            }
+            // Notification of play comes when it finishes.
+            return Ok(Value::None);
+        }
+        Command::Stop => {
+            stop(machine, id);
+            Value::None
+        }
+        Command::Pause => {
+            let winmm = &mut machine.state.winmm;
+            let dev = winmm.mci.devices.get_mut(&id).unwrap();
+            if dev.mode == Mode::Playing {
+                dev.mode = Mode::Paused;
+                if dev.device_type == DeviceType::Sequencer {
+                    midi::all_notes_off(&mut winmm.midi.as_mut().unwrap().synth);
+                }
+            }
+            Value::None
+        }
+        Command::Resume => {
+            let dev = machine.state.winmm.mci.devices.get_mut(&id).unwrap();
+            if dev.mode == Mode::Paused {
+                dev.mode = Mode::Playing;
+            }
+            Value::None
+        }
+        Command::Seek(to) => {
+            stop(machine, id);
+            let dev = &machine.state.winmm.mci.devices[&id];
+            let ms = match to {
+                SeekTo::Start => 0.0,
+                SeekTo::End => dev.length_ms(),
+                SeekTo::Position(t) => dev.time_to_ms(t)?,
+            };
+            seek(machine, id, ms);
+            Value::None
+        }
+        Command::Set { time_format } => {
+            let dev = machine.state.winmm.mci.devices.get_mut(&id).unwrap();
+            if let Some(format) = time_format {
+                dev.set_time_format(format)?;
+            }
+            Value::None
+        }
+        Command::Status { item, track } => {
+            let dev = &machine.state.winmm.mci.devices[&id];
+            let track = match track {
+                Some(0) => return Err(MCIERR::OUTOFRANGE),
+                Some(t) if t as usize > dev.tracks().len() => return Err(MCIERR::OUTOFRANGE),
+                Some(t) => Some(t as usize - 1),
+                None => None,
+            };
+            // Lengths are never reported with a track number.
+            let length_format = match dev.time_format {
+                TimeFormat::Tmsf => TimeFormat::Msf,
+                format => format,
+            };
+            let time = |value: u32, format: TimeFormat| Value::Time {
+                value,
+                text: dev.format_time(value, format),
+            };
+            match item {
+                StatusItem::Length => {
+                    let ms = match track {
+                        Some(t) => dev.tracks()[t].length_ms,
+                        None => dev.length_ms(),
+                    };
+                    let value = match length_format {
+                        TimeFormat::Msf => pack_msf(ms),
+                        _ => dev.ms_to_time(ms),
+                    };
+                    time(value, length_format)
+                }
+                StatusItem::Position => {
+                    let ms = match track {
+                        Some(t) => dev.track_start_ms(t),
+                        None => dev.position_ms(),
+                    };
+                    time(dev.ms_to_time(ms), dev.time_format)
+                }
+                StatusItem::NumberOfTracks => Value::Integer(match dev.device_type {
+                    DeviceType::CdAudio => dev.tracks().len() as u32,
+                    _ => 1,
+                }),
+                StatusItem::Mode => Value::Mode(
+                    if dev.device_type == DeviceType::CdAudio && dev.tracks().is_empty() {
+                        Mode::NotReady
+                    } else {
+                        dev.mode
+                    },
+                ),
+                StatusItem::MediaPresent => {
+                    Value::Bool(dev.device_type != DeviceType::CdAudio || !dev.tracks().is_empty())
+                }
+                StatusItem::TimeFormat => Value::TimeFormat(dev.time_format),
+                StatusItem::Ready => Value::Bool(true),
+                StatusItem::CurrentTrack => Value::Integer(
+                    dev.track_at(dev.position_ms())
+                        .map_or(dev.tracks().len().max(1), |t| t + 1) as u32,
+                ),
+                StatusItem::TrackType => {
+                    let t = track.ok_or(MCIERR::MISSING_PARAMETER)?;
+                    Value::TrackType {
+                        audio: dev.tracks()[t].format.is_some(),
+                    }
+                }
+            }
+        }
+    };
+    if let Some(hwnd) = notify {
+        post_notify(machine, hwnd, MCI_NOTIFY_SUCCESSFUL, id);
+    }
+    Ok(value)
+}
+
+/// Block until a device stops playing, for the "wait" flag.
+async fn wait_for(machine: &mut Machine, id: u32) {
+    loop {
+        output::render(machine);
+        let playing = machine
+            .state
+            .winmm
+            .mci
+            .devices
+            .get(&id)
+            .is_some_and(|d| d.mode == Mode::Playing);
+        if !playing {
+            break;
+        }
+        kernel32::Sleep(machine, 10).await;
+    }
+}
+
+/// Split a command string into words, honoring double quotes.
+fn tokenize(cmd: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = cmd.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(c) = chars.next() else {
+            break;
+        };
+        let mut token = String::new();
+        if c == '"' {
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => token.push(c),
+                    None => return Err(MCIERR::NO_CLOSING_QUOTE),
+                }
+            }
+        } else {
+            token.push(c);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                token.push(c);
+            }
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// A parsed command string, before the device is resolved.
+#[derive(Debug)]
+struct CommandString {
+    verb: String,
+    device: String,
+    args: Vec<String>,
+    notify: bool,
+    wait: bool,
+}
+
+fn parse_command_string(cmd: &str) -> Result<CommandString> {
+    let mut tokens = tokenize(cmd)?.into_iter();
+    let verb = tokens
+        .next()
+        .ok_or(MCIERR::MISSING_COMMAND_STRING)?
+        .to_ascii_lowercase();
+    let device = tokens.next().ok_or(MCIERR::MISSING_DEVICE_NAME)?;
+    let mut parsed = CommandString {
+        verb,
+        device,
+        args: Vec::new(),
+        notify: false,
+        wait: false,
+    };
+    for token in tokens {
+        match token.to_ascii_lowercase().as_str() {
+            "notify" => parsed.notify = true,
+            "wait" => parsed.wait = true,
+            _ => parsed.args.push(token),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parse a command's arguments, given the device it applies to.
+fn parse_args(verb: &str, args: &[String], dev: &Device) -> Result<Command> {
+    let lower = args
+        .iter()
+        .map(|a| a.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    let words = lower.iter().map(String::as_str).collect::<Vec<_>>();
+
+    Ok(match verb {
+        "close" => Command::Close,
+        "stop" => Command::Stop,
+        "pause" => Command::Pause,
+        "resume" => Command::Resume,
+        "play" => {
+            let (mut from, mut to) = (None, None);
+            for (i, pair) in words.chunks(2).enumerate() {
+                let [word, _] = pair else {
+                    return Err(MCIERR::MISSING_PARAMETER);
+                };
+                let value = Some(dev.parse_time(&args[i * 2 + 1])?);
+                match *word {
+                    "from" => from = value,
+                    "to" => to = value,
+                    _ => return Err(MCIERR::UNRECOGNIZED_KEYWORD),
+                }
+            }
+            Command::Play { from, to }
+        }
+        "seek" => match words[..] {
+            ["to", "start"] => Command::Seek(SeekTo::Start),
+            ["to", "end"] => Command::Seek(SeekTo::End),
+            ["to", _] => Command::Seek(SeekTo::Position(dev.parse_time(&args[1])?)),
+            _ => return Err(MCIERR::MISSING_PARAMETER),
+        },
+        "set" => {
+            let mut time_format = None;
+            match words[..] {
+                ["time", "format", format] => {
+                    time_format =
+                        Some(TimeFormat::from_name(format).ok_or(MCIERR::BAD_TIME_FORMAT)?)
+                }
+                // Not meaningful here, but harmless to accept.
+                ["door", "open" | "closed"] | ["audio", "all" | "left" | "right", "on" | "off"] => {
+                }
+                [] => return Err(MCIERR::MISSING_PARAMETER),
+                _ => return Err(MCIERR::UNRECOGNIZED_KEYWORD),
+            }
+            Command::Set { time_format }
+        }
+        "status" => {
+            let (item, track) = match words[..] {
+                ["length"] => (StatusItem::Length, None),
+                ["length", "track", n] => (StatusItem::Length, Some(n)),
+                ["position"] => (StatusItem::Position, None),
+                ["position", "track", n] => (StatusItem::Position, Some(n)),
+                ["number", "of", "tracks"] => (StatusItem::NumberOfTracks, None),
+                ["mode"] => (StatusItem::Mode, None),
+                ["media", "present"] => (StatusItem::MediaPresent, None),
+                ["time", "format"] => (StatusItem::TimeFormat, None),
+                ["ready"] => (StatusItem::Ready, None),
+                ["current", "track"] => (StatusItem::CurrentTrack, None),
+                ["type", "track", n] => (StatusItem::TrackType, Some(n)),
+                [] => return Err(MCIERR::MISSING_PARAMETER),
+                _ => return Err(MCIERR::UNRECOGNIZED_KEYWORD),
+            };
+            let track = track
+                .map(|n| n.parse::<u32>().map_err(|_| MCIERR::BAD_INTEGER))
+                .transpose()?;
+            Command::Status { item, track }
+        }
+        _ => return Err(MCIERR::UNRECOGNIZED_COMMAND),
+    })
+}
+
+/// Parse the arguments of an open command string.
+fn parse_open(device: &str, args: &[String]) -> Result<OpenArgs> {
+    // The device is a device type, a file, or "type!file".
+    let (mut device_type, mut element) = match device.split_once('!') {
+        Some((t, e)) => (
+            Some(DeviceType::from_name(t).ok_or(MCIERR::INVALID_DEVICE_NAME)?),
+            Some(e.to_string()),
+        ),
+        None => match DeviceType::from_name(device) {
+            Some(t) => (Some(t), None),
+            None => (None, Some(device.to_string())),
+        },
+    };
+    if element.as_deref() == Some("new") {
+        // Recording.
+        return Err(MCIERR::UNSUPPORTED_FUNCTION);
+    }
+    let mut alias = None;
+    let mut args = args.iter();
+    while let Some(word) = args.next() {
+        match word.to_ascii_lowercase().as_str() {
+            "type" => {
+                let name = args.next().ok_or(MCIERR::MISSING_PARAMETER)?;
+                device_type = Some(DeviceType::from_name(name).ok_or(MCIERR::INVALID_DEVICE_NAME)?);
+            }
+            "alias" => alias = Some(args.next().ok_or(MCIERR::MISSING_PARAMETER)?.clone()),
+            "shareable" => {}
+            _ => return Err(MCIERR::UNRECOGNIZED_KEYWORD),
+        }
+    }
+    if device_type.is_none() && element.is_none() {
+        element = Some(device.to_string());
+    }
+    Ok(OpenArgs {
+        device_type,
+        element,
+        alias,
+    })
+}
+
+async fn send_string(machine: &mut Machine, cmd: &str, hwnd: HWND) -> Result<String> {
+    let parsed = parse_command_string(cmd)?;
+    let notify = if parsed.notify { Some(hwnd) } else { None };
+
+    if parsed.verb == "open" {
+        let id = open(machine, parse_open(&parsed.device, &parsed.args)?)?;
+        if let Some(hwnd) = notify {
+            post_notify(machine, hwnd, MCI_NOTIFY_SUCCESSFUL, id);
+        }
+        return Ok(id.to_string());
+    }
+
+    let id = if parsed.device.eq_ignore_ascii_case("all") {
+        MCI_ALL_DEVICE_ID
+    } else {
+        match machine.state.winmm.mci.find(&parsed.device) {
+            Some(id) => id,
+            None => {
+                // Commands on unopened devices open them automatically.
+                open(machine, parse_open(&parsed.device, &[])?)
+                    .map_err(|_| MCIERR::INVALID_DEVICE_NAME)?
+            }
+        }
+    };
+    let command = match machine.state.winmm.mci.devices.get(&id) {
+        Some(dev) => parse_args(&parsed.verb, &parsed.args, dev)?,
+        // The "all" device only supports close.
+        None if parsed.verb == "close" => Command::Close,
+        None => return Err(MCIERR::UNSUPPORTED_FUNCTION),
+    };
+    let value = execute(machine, id, command, notify)?;
+    if parsed.wait {
+        wait_for(machine, id).await;
+    }
+    Ok(value.to_text())
+}
 
 #[win32_derive::dllexport]
 pub fn mciGetErrorStringA(
     _machine: &mut Machine,
     mcierr: u32,
-    pszText: Option<&str>,
-    cchText: u32,
+    pszText: ArrayWithSizeMut<'_, u8>,
 ) -> bool {
-    todo!()
+    let text = match mcierr {
+        0 => "The specified command was carried out.",
+        err => match MCIERR::from_raw(err) {
+            Some(err) => err.message(),
+            None => return false,
+        },
+    };
+    let Some(buf) = pszText.to_option() else {
+        return false;
+    };
+    if buf.is_empty() {
+        return false;
+    }
+    let len = text.len().min(buf.len() - 1);
+    buf[..len].copy_from_slice(&text.as_bytes()[..len]);
+    buf[len] = 0;
+    true
 }
 
 #[win32_derive::dllexport]
-pub fn mciSendStringA(
-    _machine: &mut Machine,
+pub async fn mciSendStringA(
+    machine: &mut Machine,
     lpstrCommand: Option<&str>,
-    lpstrReturnString: ArrayWithSizeMut<u8>,
+    lpstrReturnString: ArrayWithSizeMut<'_, u8>,
     hwndCallback: HWND,
 ) -> u32 {
-    let cmd = lpstrCommand.unwrap();
-    log::info!("mci: {:?}", cmd);
-    if cmd.find("notify").is_some() {
-        todo!("mci notify not implemented");
+    let Some(cmd) = lpstrCommand else {
+        return MCIERR::MISSING_COMMAND_STRING as u32;
+    };
+    let text = match send_string(machine, cmd, hwndCallback).await {
+        Ok(text) => text,
+        Err(err) => return err as u32,
+    };
+    if let Some(buf) = lpstrReturnString.to_option() {
+        if text.len() >= buf.len() {
+            return MCIERR::PARAM_OVERFLOW as u32;
+        }
+        buf[..text.len()].copy_from_slice(text.as_bytes());
+        buf[text.len()] = 0;
+    }
+    0
+}
+
+const MCI_OPEN: u32 = 0x0803;
+const MCI_CLOSE: u32 = 0x0804;
+const MCI_PLAY: u32 = 0x0806;
+const MCI_SEEK: u32 = 0x0807;
+const MCI_STOP: u32 = 0x0808;
+const MCI_PAUSE: u32 = 0x0809;
+const MCI_SET: u32 = 0x080D;
+const MCI_STATUS: u32 = 0x0814;
+const MCI_RESUME: u32 = 0x0855;
+
+const MCI_NOTIFY: u32 = 0x0000_0001;
+const MCI_WAIT: u32 = 0x0000_0002;
+const MCI_FROM: u32 = 0x0000_0004;
+const MCI_TO: u32 = 0x0000_0008;
+const MCI_TRACK: u32 = 0x0000_0010;
+
+const MCI_OPEN_ELEMENT: u32 = 0x0000_0200;
+const MCI_OPEN_ALIAS: u32 = 0x0000_0400;
+const MCI_OPEN_TYPE_ID: u32 = 0x0000_1000;
+const MCI_OPEN_TYPE: u32 = 0x0000_2000;
+
+const MCI_SEEK_TO_START: u32 = 0x0000_0100;
+const MCI_SEEK_TO_END: u32 = 0x0000_0200;
+
+const MCI_SET_TIME_FORMAT: u32 = 0x0000_0400;
+
+const MCI_STATUS_ITEM: u32 = 0x0000_0100;
+const MCI_CDA_STATUS_TYPE_TRACK: u32 = 0x0000_4001;
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MCI_OPEN_PARMSA {
+    pub dwCallback: u32,
+    pub wDeviceID: u32,
+    pub lpstrDeviceType: u32,
+    pub lpstrElementName: u32,
+    pub lpstrAlias: u32,
+}
+unsafe impl memory::Pod for MCI_OPEN_PARMSA {}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MCI_PLAY_PARMS {
+    pub dwCallback: u32,
+    pub dwFrom: u32,
+    pub dwTo: u32,
+}
+unsafe impl memory::Pod for MCI_PLAY_PARMS {}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MCI_SEEK_PARMS {
+    pub dwCallback: u32,
+    pub dwTo: u32,
+}
+unsafe impl memory::Pod for MCI_SEEK_PARMS {}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MCI_SET_PARMS {
+    pub dwCallback: u32,
+    pub dwTimeFormat: u32,
+    pub dwAudio: u32,
+}
+unsafe impl memory::Pod for MCI_SET_PARMS {}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MCI_STATUS_PARMS {
+    pub dwCallback: u32,
+    pub dwReturn: u32,
+    pub dwItem: u32,
+    pub dwTrack: u32,
+}
+unsafe impl memory::Pod for MCI_STATUS_PARMS {}
+
+async fn send_command(
+    machine: &mut Machine,
+    IDDevice: u32,
+    uMsg: u32,
+    fdwCommand: u32,
+    dwParam: u32,
+) -> Result<()> {
+    let mem = machine.emu.memory.mem();
+    // All the parameter structs start with dwCallback.
+    let notify = if fdwCommand & MCI_NOTIFY != 0 && dwParam != 0 {
+        Some(HWND::from_raw(mem.get_pod::<u32>(dwParam) & 0xFFFF))
+    } else {
+        None
+    };
+    let read_str = |addr: u32| String::from_utf8_lossy(mem.slicez(addr)).into_owned();
+
+    let command = match uMsg {
+        MCI_OPEN => {
+            if dwParam == 0 {
+                return Err(MCIERR::NULL_PARAMETER_BLOCK);
+            }
+            let parms = mem.get_pod::<MCI_OPEN_PARMSA>(dwParam);
+            let mut element = None;
+            if fdwCommand & MCI_OPEN_ELEMENT != 0 {
+                element = Some(read_str(parms.lpstrElementName));
+            }
+            let device_type = if fdwCommand & MCI_OPEN_TYPE_ID != 0 {
+                Some(
+                    DeviceType::from_id(parms.lpstrDeviceType)
+                        .ok_or(MCIERR::INVALID_DEVICE_NAME)?,
+                )
+            } else if fdwCommand & MCI_OPEN_TYPE != 0 {
+                let args = parse_open(&read_str(parms.lpstrDeviceType), &[])?;
+                element = element.or(args.element);
+                args.device_type
+            } else {
+                None
+            };
+            let alias = if fdwCommand & MCI_OPEN_ALIAS != 0 {
+                Some(read_str(parms.lpstrAlias))
+            } else {
+                None
+            };
+            let id = open(
+                machine,
+                OpenArgs {
+                    device_type,
+                    element,
+                    alias,
+                },
+            )?;
+            machine.mem().put_pod::<u32>(dwParam + 4, id);
+            if let Some(hwnd) = notify {
+                post_notify(machine, hwnd, MCI_NOTIFY_SUCCESSFUL, id);
+            }
+            return Ok(());
+        }
+        MCI_CLOSE => Command::Close,
+        MCI_STOP => Command::Stop,
+        MCI_PAUSE => Command::Pause,
+        MCI_RESUME => Command::Resume,
+        MCI_PLAY => {
+            let parms = if dwParam != 0 {
+                mem.get_pod::<MCI_PLAY_PARMS>(dwParam)
+            } else {
+                MCI_PLAY_PARMS {
+                    dwCallback: 0,
+                    dwFrom: 0,
+                    dwTo: 0,
+                }
+            };
+            Command::Play {
+                from: (fdwCommand & MCI_FROM != 0).then_some(parms.dwFrom),
+                to: (fdwCommand & MCI_TO != 0).then_some(parms.dwTo),
+            }
+        }
+        MCI_SEEK => {
+            if fdwCommand & MCI_SEEK_TO_START != 0 {
+                Command::Seek(SeekTo::Start)
+            } else if fdwCommand & MCI_SEEK_TO_END != 0 {
+                Command::Seek(SeekTo::End)
+            } else if fdwCommand & MCI_TO != 0 && dwParam != 0 {
+                Command::Seek(SeekTo::Position(
+                    mem.get_pod::<MCI_SEEK_PARMS>(dwParam).dwTo,
+                ))
+            } else {
+                return Err(MCIERR::MISSING_PARAMETER);
+            }
+        }
+        MCI_SET => {
+            if dwParam == 0 {
+                return Err(MCIERR::NULL_PARAMETER_BLOCK);
+            }
+            let parms = mem.get_pod::<MCI_SET_PARMS>(dwParam);
+            let time_format = if fdwCommand & MCI_SET_TIME_FORMAT != 0 {
+                Some(TimeFormat::from_id(parms.dwTimeFormat).ok_or(MCIERR::BAD_TIME_FORMAT)?)
+            } else {
+                None
+            };
+            Command::Set { time_format }
+        }
+        MCI_STATUS => {
+            if dwParam == 0 {
+                return Err(MCIERR::NULL_PARAMETER_BLOCK);
+            }
+            if fdwCommand & MCI_STATUS_ITEM == 0 {
+                return Err(MCIERR::MISSING_PARAMETER);
+            }
+            let parms = mem.get_pod::<MCI_STATUS_PARMS>(dwParam);
+            let item = match parms.dwItem {
+                1 => StatusItem::Length,
+                2 => StatusItem::Position,
+                3 => StatusItem::NumberOfTracks,
+                4 => StatusItem::Mode,
+                5 => StatusItem::MediaPresent,
+                6 => StatusItem::TimeFormat,
+                7 => StatusItem::Ready,
+                8 => StatusItem::CurrentTrack,
+                MCI_CDA_STATUS_TYPE_TRACK => StatusItem::TrackType,
+                _ => return Err(MCIERR::BAD_CONSTANT),
+            };
+            let track = (fdwCommand & MCI_TRACK != 0).then_some(parms.dwTrack);
+            let value = execute(machine, IDDevice, Command::Status { item, track }, notify)?;
+            machine.mem().put_pod::<u32>(
+                dwParam + std::mem::offset_of!(MCI_STATUS_PARMS, dwReturn) as u32,
+                value.to_dword(),
+            );
+            return Ok(());
+        }
+        _ => {
+            log::warn!("mciSendCommand: unhandled message {uMsg:#x}");
+            return Err(MCIERR::UNRECOGNIZED_COMMAND);
+        }
+    };
+    execute(machine, IDDevice, command, notify)?;
+    if fdwCommand & MCI_WAIT != 0 {
+        wait_for(machine, IDDevice).await;
+    }
+    Ok(())
+}
+
+#[win32_derive::dllexport]
+pub async fn mciSendCommandA(
+    machine: &mut Machine,
+    IDDevice: u32,
+    uMsg: u32,
+    fdwCommand: u32,
+    dwParam: u32,
+) -> u32 {
+    match send_command(machine, IDDevice, uMsg, fdwCommand, dwParam).await {
+        Ok(()) => 0,
+        Err(err) => err as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command_string() {
+        let cmd =
+            parse_command_string(r#"open "c:\my music\song.mid" type sequencer alias song notify"#)
+                .unwrap();
+        assert_eq!(cmd.verb, "open");
+        assert_eq!(cmd.device, r"c:\my music\song.mid");
+        assert!(cmd.notify && !cmd.wait);
+        let open = parse_open(&cmd.device, &cmd.args).unwrap();
+        assert_eq!(open.device_type, Some(DeviceType::Sequencer));
+        assert_eq!(open.element.as_deref(), Some(r"c:\my music\song.mid"));
+        assert_eq!(open.alias.as_deref(), Some("song"));
+
+        assert_eq!(
+            parse_command_string(r#"open "foo"#).unwrap_err(),
+            MCIERR::NO_CLOSING_QUOTE
+        );
+    }
+
+    #[test]
+    fn test_cd_times() {
+        let track = |secs: f64| Track {
+            path: String::new(),
+            format: Some(Format::DEFAULT_PRIMARY),
+            length_ms: secs * 1000.0,
+        };
+        let mut dev = Device::new(
+            DeviceType::CdAudio,
+            "cdaudio".into(),
+            Media::CdAudio {
+                tracks: vec![track(10.0), track(65.0), track(30.0)],
+                loaded: None,
+            },
+        );
+        dev.set_time_format(TimeFormat::Tmsf).unwrap();
+        let from = dev.parse_time("2").unwrap();
+        assert_eq!(dev.time_to_ms(from).unwrap(), 10_000.0);
+        let pos = dev.parse_time("2:01:02:15").unwrap();
+        assert_eq!(dev.time_to_ms(pos).unwrap(), 10_000.0 + 62_200.0);
+        assert_eq!(
+            dev.format_time(dev.ms_to_time(72_200.0), TimeFormat::Tmsf),
+            "02:01:02:15"
+        );
+        assert!(dev.time_to_ms(dev.parse_time("4").unwrap()).is_err());
+
+        dev.set_time_format(TimeFormat::Msf).unwrap();
+        assert_eq!(
+            dev.format_time(dev.ms_to_time(75_000.0), TimeFormat::Msf),
+            "01:15:00"
+        );
+        assert!(dev.set_time_format(TimeFormat::Samples).is_err());
     }
-    0 // success
 }
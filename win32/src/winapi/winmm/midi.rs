@@ -1,52 +1,567 @@
-use crate::Machine;
+//! MIDI output, played by the software synthesizer in synth.rs.
+//!
+//! There is a single output device, which can be opened either directly (midiOut*) or as
+//! a stream (midiStream*).  Its output is rendered by output.rs.
+
+use super::{output, smf, synth::Synth, MMRESULT};
+use crate::{
+    machine::Machine,
+    winapi::{
+        kernel32::{SetEvent, HEVENT},
+        types::HWND,
+        user32::{PostMessageA, PostThreadMessageA},
+    },
+};
+use memory::{Extensions, ExtensionsMut, Mem};
+use std::collections::VecDeque;
 
 pub type HMIDIOUT = u32;
+pub type HMIDISTRM = u32;
+
+/// The handle of the one device, whether opened as midiOut or midiStream.
+const HMIDI: u32 = 1;
+const MIDI_MAPPER: u32 = 0xFFFF_FFFF;
+
+const MM_MOM_OPEN: u32 = 0x3C7;
+const MM_MOM_CLOSE: u32 = 0x3C8;
+const MM_MOM_DONE: u32 = 0x3C9;
+const MM_MOM_POSITIONCB: u32 = 0x3CA;
+
+const CALLBACK_TYPEMASK: u32 = 0x0007_0000;
+const CALLBACK_NULL: u32 = 0x0000_0000;
+const CALLBACK_WINDOW: u32 = 0x0001_0000;
+const CALLBACK_THREAD: u32 = 0x0002_0000;
+const CALLBACK_FUNCTION: u32 = 0x0003_0000;
+const CALLBACK_EVENT: u32 = 0x0005_0000;
+
+/// How the application wants to hear about MOM_* events.
+pub(super) enum Callback {
+    None,
+    Window(HWND),
+    Thread(u32),
+    Function { func: u32, instance: u32 },
+    Event(HEVENT),
+}
+
+impl Callback {
+    pub(super) fn new(dwCallback: u32, dwInstance: u32, fdwOpen: u32) -> Self {
+        match fdwOpen & CALLBACK_TYPEMASK {
+            CALLBACK_NULL => Callback::None,
+            CALLBACK_WINDOW => Callback::Window(HWND::from_raw(dwCallback)),
+            CALLBACK_THREAD => Callback::Thread(dwCallback),
+            CALLBACK_FUNCTION => Callback::Function {
+                func: dwCallback,
+                instance: dwInstance,
+            },
+            CALLBACK_EVENT => Callback::Event(HEVENT::from_raw(dwCallback)),
+            flags => todo!("midi callback {flags:#x}"),
+        }
+    }
+}
+
+pub struct MidiOut {
+    pub(super) synth: Synth,
+    callback: Callback,
+    stream: Option<Stream>,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MIDIHDR {
+    pub lpData: u32,
+    pub dwBufferLength: u32,
+    pub dwBytesRecorded: u32,
+    pub dwUser: u32,
+    pub dwFlags: u32,
+    pub lpNext: u32,
+    pub reserved: u32,
+    pub dwOffset: u32,
+    pub dwReserved: [u32; 8],
+}
+unsafe impl memory::Pod for MIDIHDR {}
+
+const MHDR_DONE: u32 = 0x0000_0001;
+const MHDR_PREPARED: u32 = 0x0000_0002;
+const MHDR_INQUEUE: u32 = 0x0000_0004;
+
+/// Update a header's flags in place.  Note we only write the fields we change, because
+/// some callers allocate the smaller pre-stream version of MIDIHDR.
+fn set_header_flags(mem: Mem, hdr: u32, set: u32, clear: u32) {
+    let addr = hdr + std::mem::offset_of!(MIDIHDR, dwFlags) as u32;
+    let flags = mem.get_pod::<u32>(addr);
+    mem.put_pod::<u32>(addr, (flags | set) & !clear);
+}
+
+const MEVT_F_LONG: u32 = 0x8000_0000;
+const MEVT_F_CALLBACK: u32 = 0x4000_0000;
+const MEVT_SHORTMSG: u8 = 0x00;
+const MEVT_TEMPO: u8 = 0x01;
+const MEVT_LONGMSG: u8 = 0x80;
+
+/// Playback state of midiStream buffers.
+pub(super) struct Stream {
+    headers: VecDeque<u32>,
+    /// Byte offset of the next event in the front header.
+    offset: u32,
+    playing: bool,
+    /// Ticks per quarter note, or an SMPTE time division.
+    time_div: u32,
+    /// Microseconds per quarter note.
+    tempo: u32,
+    /// Ticks elapsed since the previous event.
+    ticks: f64,
+}
+
+impl Stream {
+    fn new() -> Self {
+        Stream {
+            headers: VecDeque::new(),
+            offset: 0,
+            playing: false,
+            time_div: 96,
+            tempo: 500_000,
+            ticks: 0.0,
+        }
+    }
+
+    fn ticks_per_frame(&self, sample_rate: u32) -> f64 {
+        // The time division is in the low word of MIDIPROP_TIMEDIV.
+        let ticks_per_second = smf::smpte_ticks_per_second(self.time_div as u16)
+            .unwrap_or(self.time_div as f64 * 1_000_000.0 / self.tempo as f64);
+        ticks_per_second / sample_rate as f64
+    }
+
+    /// Play all events that are due, collecting MOM_* notifications.
+    /// Returns the number of ticks until the next event, if any.
+    fn run(&mut self, mem: Mem, synth: &mut Synth, notes: &mut Vec<(u32, u32)>) -> Option<f64> {
+        loop {
+            let &hdr_addr = self.headers.front()?;
+            let hdr = mem.get_pod::<MIDIHDR>(hdr_addr);
+            if self.offset + 12 > hdr.dwBytesRecorded {
+                set_header_flags(mem, hdr_addr, MHDR_DONE, MHDR_INQUEUE);
+                notes.push((MM_MOM_DONE, hdr_addr));
+                self.headers.pop_front();
+                self.offset = 0;
+                continue;
+            }
+
+            // MIDIEVENT: dwDeltaTime, dwStreamID, dwEvent, then dwParms for long events.
+            let event_addr = hdr.lpData + self.offset;
+            let delta = mem.get_pod::<u32>(event_addr) as f64;
+            if self.ticks < delta {
+                return Some(delta - self.ticks);
+            }
+            self.ticks -= delta;
+            let event = mem.get_pod::<u32>(event_addr + 8);
+            let param = event & 0x00FF_FFFF;
+            let mut len = 12;
+            if event & MEVT_F_LONG != 0 {
+                len += (param + 3) & !3;
+            }
+            match (event >> 24) as u8 & !(MEVT_F_CALLBACK >> 24) as u8 {
+                MEVT_SHORTMSG => synth.short_msg(param),
+                MEVT_TEMPO => self.tempo = param,
+                MEVT_LONGMSG => synth.sysex(mem.sub32(event_addr + 12, param)),
+                // MEVT_NOP, MEVT_COMMENT, MEVT_VERSION etc.
+                _ => {}
+            }
+            if event & MEVT_F_CALLBACK != 0 {
+                mem.put_pod::<u32>(
+                    hdr_addr + std::mem::offset_of!(MIDIHDR, dwOffset) as u32,
+                    self.offset,
+                );
+                notes.push((MM_MOM_POSITIONCB, hdr_addr));
+            }
+            self.offset += len;
+        }
+    }
+
+    /// Return all queued buffers to the application, as done.
+    fn flush(&mut self, mem: Mem, notes: &mut Vec<(u32, u32)>) {
+        for hdr in self.headers.drain(..) {
+            set_header_flags(mem, hdr, MHDR_DONE, MHDR_INQUEUE);
+            notes.push((MM_MOM_DONE, hdr));
+        }
+        self.offset = 0;
+        self.ticks = 0.0;
+    }
+}
+
+/// Send a MOM_* message to the application's callback.
+pub(super) fn notify(machine: &mut Machine, msg: u32, param1: u32) {
+    let Some(midi) = machine.state.winmm.midi.as_ref() else {
+        return;
+    };
+    match midi.callback {
+        Callback::None => {}
+        Callback::Window(hwnd) => {
+            PostMessageA(machine, hwnd, msg, HMIDI, param1);
+        }
+        Callback::Thread(thread) => {
+            PostThreadMessageA(machine, thread, msg, HMIDI, param1);
+        }
+        Callback::Function { func, instance } => {
+            // Function callbacks run the next time we're somewhere we can call into x86.
+            let args = vec![HMIDI, msg, instance, param1, 0];
+            machine.state.winmm.pending_callbacks.push((func, args));
+        }
+        Callback::Event(event) => {
+            SetEvent(machine, event);
+        }
+    }
+}
+
+/// Run any function callbacks queued by notify().
+pub async fn deliver_callbacks(machine: &mut Machine) {
+    while !machine.state.winmm.pending_callbacks.is_empty() {
+        let (func, args) = machine.state.winmm.pending_callbacks.remove(0);
+        machine.call_x86(func, args).await;
+    }
+}
+
+impl MidiOut {
+    /// Play due stream events, returning the number of frames until the next one.
+    pub(super) fn run_stream(
+        &mut self,
+        mem: Mem,
+        sample_rate: u32,
+        notes: &mut Vec<(u32, u32)>,
+    ) -> Option<usize> {
+        let stream = self.stream.as_mut().filter(|s| s.playing)?;
+        let ticks = stream.run(mem, &mut self.synth, notes)?;
+        Some(
+            (ticks / stream.ticks_per_frame(sample_rate))
+                .ceil()
+                .max(1.0) as usize,
+        )
+    }
+
+    pub(super) fn advance_stream(&mut self, frames: usize, sample_rate: u32) {
+        if let Some(stream) = self.stream.as_mut() {
+            // Time only passes while there's something queued to play.
+            if stream.playing && !stream.headers.is_empty() {
+                stream.ticks += frames as f64 * stream.ticks_per_frame(sample_rate);
+            }
+        }
+    }
+
+    /// Whether there's anything to render.
+    pub(super) fn is_sounding(&self) -> bool {
+        self.stream.as_ref().is_some_and(|s| s.playing) || !self.synth.is_silent()
+    }
+}
+
+pub(super) fn open(
+    machine: &mut Machine,
+    uDeviceID: u32,
+    callback: Callback,
+    stream: Option<Stream>,
+) -> MMRESULT {
+    if !machine.state.winmm.audio_enabled {
+        return MMRESULT::MMSYSERR_NOTENABLED;
+    }
+    if uDeviceID != 0 && uDeviceID != MIDI_MAPPER {
+        return MMRESULT::MMSYSERR_BADDEVICEID;
+    }
+    if machine.state.winmm.midi.is_some() {
+        return MMRESULT::MMSYSERR_ALLOCATED;
+    }
+
+    output::ensure_audio(machine);
+    let winmm = &mut machine.state.winmm;
+    winmm.midi = Some(MidiOut {
+        synth: Synth::new(winmm.audio_rate),
+        callback,
+        stream,
+    });
+    notify(machine, MM_MOM_OPEN, 0);
+    MMRESULT::MMSYSERR_NOERROR
+}
+
+/// Get the open device, or fail with MMSYSERR_INVALHANDLE.
+macro_rules! midi_out {
+    ($machine:expr, $handle:expr) => {
+        match $machine.state.winmm.midi.as_mut() {
+            Some(midi) if $handle == HMIDI => midi,
+            _ => return MMRESULT::MMSYSERR_INVALHANDLE,
+        }
+    };
+}
+
+/// Get the open device's stream, or fail with MMSYSERR_INVALHANDLE.
+macro_rules! midi_stream {
+    ($machine:expr, $handle:expr) => {
+        match midi_out!($machine, $handle).stream.as_mut() {
+            Some(stream) => stream,
+            None => return MMRESULT::MMSYSERR_INVALHANDLE,
+        }
+    };
+}
 
 #[win32_derive::dllexport]
 pub fn midiOutOpen(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     phmo: Option<&mut HMIDIOUT>,
     uDeviceID: u32,
     dwCallback: u32,
     dwInstance: u32,
     fdwOpen: u32, /* MIDI_WAVE_OPEN_TYPE */
-) -> u32 {
-    todo!()
+) -> MMRESULT {
+    let callback = Callback::new(dwCallback, dwInstance, fdwOpen);
+    let result = open(machine, uDeviceID, callback, None);
+    if let MMRESULT::MMSYSERR_NOERROR = result {
+        *phmo.unwrap() = HMIDI;
+    }
+    result
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct MIDIOUTCAPSA {
+    pub wMid: u16,
+    pub wPid: u16,
+    pub vDriverVersion: u32,
+    pub szPname: [u8; 32],
+    pub wTechnology: u16,
+    pub wVoices: u16,
+    pub wNotes: u16,
+    pub wChannelMask: u16,
+    pub dwSupport: u32,
 }
+unsafe impl memory::Pod for MIDIOUTCAPSA {}
 
-pub type MIDIOUTCAPSA = u32; // TODO
+const MOD_SWSYNTH: u16 = 7;
+const MIDICAPS_VOLUME: u32 = 0x0001;
+const MIDICAPS_STREAM: u32 = 0x0008;
 
 #[win32_derive::dllexport]
 pub fn midiOutGetDevCapsA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     uDeviceID: u32,
     pmoc: Option<&mut MIDIOUTCAPSA>,
     cbmoc: u32,
-) -> u32 {
-    todo!()
+) -> MMRESULT {
+    if !machine.state.winmm.audio_enabled || (uDeviceID != 0 && uDeviceID != MIDI_MAPPER) {
+        return MMRESULT::MMSYSERR_BADDEVICEID;
+    }
+    assert_eq!(cbmoc, std::mem::size_of::<MIDIOUTCAPSA>() as u32);
+
+    let mut szPname = [0; 32];
+    let name = b"retrowin32 synth";
+    szPname[..name.len()].copy_from_slice(name);
+    *pmoc.unwrap() = MIDIOUTCAPSA {
+        wMid: 0,
+        wPid: 0,
+        vDriverVersion: 1,
+        szPname,
+        wTechnology: MOD_SWSYNTH,
+        wVoices: 32,
+        wNotes: 32,
+        wChannelMask: 0xFFFF,
+        dwSupport: MIDICAPS_VOLUME | MIDICAPS_STREAM,
+    };
+    MMRESULT::MMSYSERR_NOERROR
+}
+
+#[win32_derive::dllexport]
+pub fn midiOutGetNumDevs(machine: &mut Machine) -> u32 {
+    if machine.state.winmm.audio_enabled {
+        1
+    } else {
+        0
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn midiOutReset(machine: &mut Machine, hmo: HMIDIOUT) -> MMRESULT {
+    output::render(machine);
+    let mem = machine.emu.memory.mem();
+    let midi = midi_out!(machine, hmo);
+    midi.synth.reset();
+    let mut notes = Vec::new();
+    if let Some(stream) = midi.stream.as_mut() {
+        stream.playing = false;
+        stream.flush(mem, &mut notes);
+    }
+    for (msg, hdr) in notes {
+        notify(machine, msg, hdr);
+    }
+    MMRESULT::MMSYSERR_NOERROR
 }
 
 #[win32_derive::dllexport]
-pub fn midiOutGetNumDevs(_machine: &mut Machine) -> u32 {
-    todo!()
+pub fn midiOutClose(machine: &mut Machine, hmo: HMIDIOUT) -> MMRESULT {
+    output::render(machine);
+    let midi = midi_out!(machine, hmo);
+    if midi.stream.as_ref().is_some_and(|s| !s.headers.is_empty()) {
+        return MMRESULT::MIDIERR_STILLPLAYING;
+    }
+    notify(machine, MM_MOM_CLOSE, 0);
+    machine.state.winmm.midi = None;
+    MMRESULT::MMSYSERR_NOERROR
 }
 
 #[win32_derive::dllexport]
-pub fn midiOutReset(_machine: &mut Machine, hmo: HMIDIOUT) -> u32 {
-    todo!()
+pub fn midiOutSetVolume(machine: &mut Machine, hmo: HMIDIOUT, dwVolume: u32) -> MMRESULT {
+    output::render(machine);
+    let midi = midi_out!(machine, hmo);
+    // Low word is the left channel, high word the right; we only play mono.
+    let left = dwVolume & 0xFFFF;
+    let right = dwVolume >> 16;
+    midi.synth.volume = (left + right) as f32 / (2 * 0xFFFF) as f32;
+    MMRESULT::MMSYSERR_NOERROR
 }
 
 #[win32_derive::dllexport]
-pub fn midiOutClose(_machine: &mut Machine, hmo: HMIDIOUT) -> u32 {
-    todo!()
+pub fn midiOutShortMsg(machine: &mut Machine, hmo: HMIDIOUT, dwMsg: u32) -> MMRESULT {
+    output::render(machine);
+    midi_out!(machine, hmo).synth.short_msg(dwMsg);
+    MMRESULT::MMSYSERR_NOERROR
 }
 
 #[win32_derive::dllexport]
-pub fn midiOutSetVolume(_machine: &mut Machine, hmo: HMIDIOUT, dwVolume: u32) -> u32 {
-    todo!()
+pub fn midiOutLongMsg(machine: &mut Machine, hmo: HMIDIOUT, pmh: u32, cbmh: u32) -> MMRESULT {
+    output::render(machine);
+    let mem = machine.emu.memory.mem();
+    let hdr = mem.get_pod::<MIDIHDR>(pmh);
+    midi_out!(machine, hmo)
+        .synth
+        .sysex(mem.sub32(hdr.lpData, hdr.dwBufferLength));
+    set_header_flags(mem, pmh, MHDR_DONE, 0);
+    notify(machine, MM_MOM_DONE, pmh);
+    MMRESULT::MMSYSERR_NOERROR
 }
 
 #[win32_derive::dllexport]
-pub fn midiOutShortMsg(_machine: &mut Machine, hmo: HMIDIOUT, dwMsg: u32) -> u32 {
-    todo!()
+pub fn midiOutPrepareHeader(machine: &mut Machine, hmo: HMIDIOUT, pmh: u32, cbmh: u32) -> MMRESULT {
+    midi_out!(machine, hmo);
+    set_header_flags(machine.emu.memory.mem(), pmh, MHDR_PREPARED, 0);
+    MMRESULT::MMSYSERR_NOERROR
+}
+
+#[win32_derive::dllexport]
+pub fn midiOutUnprepareHeader(
+    machine: &mut Machine,
+    hmo: HMIDIOUT,
+    pmh: u32,
+    cbmh: u32,
+) -> MMRESULT {
+    midi_out!(machine, hmo);
+    let mem = machine.emu.memory.mem();
+    let flags = mem.get_pod::<u32>(pmh + std::mem::offset_of!(MIDIHDR, dwFlags) as u32);
+    if flags & MHDR_INQUEUE != 0 {
+        return MMRESULT::MIDIERR_STILLPLAYING;
+    }
+    set_header_flags(mem, pmh, 0, MHDR_PREPARED);
+    MMRESULT::MMSYSERR_NOERROR
+}
+
+#[win32_derive::dllexport]
+pub fn midiStreamOpen(
+    machine: &mut Machine,
+    phms: Option<&mut HMIDISTRM>,
+    puDeviceID: Option<&mut u32>,
+    cMidi: u32,
+    dwCallback: u32,
+    dwInstance: u32,
+    fdwOpen: u32,
+) -> MMRESULT {
+    assert_eq!(cMidi, 1);
+    let callback = Callback::new(dwCallback, dwInstance, fdwOpen);
+    let device = puDeviceID.map_or(MIDI_MAPPER, |id| *id);
+    let result = open(machine, device, callback, Some(Stream::new()));
+    if let MMRESULT::MMSYSERR_NOERROR = result {
+        *phms.unwrap() = HMIDI;
+    }
+    result
+}
+
+#[win32_derive::dllexport]
+pub fn midiStreamClose(machine: &mut Machine, hms: HMIDISTRM) -> MMRESULT {
+    midiOutClose(machine, hms)
+}
+
+#[win32_derive::dllexport]
+pub fn midiStreamOut(machine: &mut Machine, hms: HMIDISTRM, pmh: u32, cbmh: u32) -> MMRESULT {
+    let mem = machine.emu.memory.mem();
+    let stream = midi_stream!(machine, hms);
+    set_header_flags(mem, pmh, MHDR_INQUEUE, MHDR_DONE);
+    stream.headers.push_back(pmh);
+    MMRESULT::MMSYSERR_NOERROR
+}
+
+#[win32_derive::dllexport]
+pub fn midiStreamRestart(machine: &mut Machine, hms: HMIDISTRM) -> MMRESULT {
+    output::render(machine);
+    midi_stream!(machine, hms).playing = true;
+    MMRESULT::MMSYSERR_NOERROR
+}
+
+/// Release all notes without resetting controllers, as pausing or stopping a stream does.
+pub(super) fn all_notes_off(synth: &mut Synth) {
+    for channel in 0..16 {
+        synth.short_msg(0x7B_B0 | channel);
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn midiStreamPause(machine: &mut Machine, hms: HMIDISTRM) -> MMRESULT {
+    output::render(machine);
+    let midi = midi_out!(machine, hms);
+    let Some(stream) = midi.stream.as_mut() else {
+        return MMRESULT::MMSYSERR_INVALHANDLE;
+    };
+    stream.playing = false;
+    all_notes_off(&mut midi.synth);
+    MMRESULT::MMSYSERR_NOERROR
+}
+
+#[win32_derive::dllexport]
+pub fn midiStreamStop(machine: &mut Machine, hms: HMIDISTRM) -> MMRESULT {
+    output::render(machine);
+    let mem = machine.emu.memory.mem();
+    let midi = midi_out!(machine, hms);
+    let Some(stream) = midi.stream.as_mut() else {
+        return MMRESULT::MMSYSERR_INVALHANDLE;
+    };
+    stream.playing = false;
+    let mut notes = Vec::new();
+    stream.flush(mem, &mut notes);
+    all_notes_off(&mut midi.synth);
+    for (msg, hdr) in notes {
+        notify(machine, msg, hdr);
+    }
+    MMRESULT::MMSYSERR_NOERROR
+}
+
+const MIDIPROP_SET: u32 = 0x8000_0000;
+const MIDIPROP_GET: u32 = 0x4000_0000;
+const MIDIPROP_TIMEDIV: u32 = 0x0000_0001;
+const MIDIPROP_TEMPO: u32 = 0x0000_0002;
+
+#[win32_derive::dllexport]
+pub fn midiStreamProperty(
+    machine: &mut Machine,
+    hms: HMIDISTRM,
+    lppropdata: u32,
+    dwProperty: u32,
+) -> MMRESULT {
+    output::render(machine);
+    let mem = machine.emu.memory.mem();
+    let stream = midi_stream!(machine, hms);
+    // Both MIDIPROPTIMEDIV and MIDIPROPTEMPO are { cbStruct, value }.
+    let value_addr = lppropdata + 4;
+    let field = match dwProperty & !(MIDIPROP_SET | MIDIPROP_GET) {
+        MIDIPROP_TIMEDIV => &mut stream.time_div,
+        MIDIPROP_TEMPO => &mut stream.tempo,
+        _ => return MMRESULT::MMSYSERR_INVALPARAM,
+    };
+    if dwProperty & MIDIPROP_SET != 0 {
+        *field = mem.get_pod::<u32>(value_addr);
+    } else if dwProperty & MIDIPROP_GET != 0 {
+        mem.put_pod::<u32>(value_addr, *field);
+    } else {
+        return MMRESULT::MMSYSERR_INVALPARAM;
+    }
+    MMRESULT::MMSYSERR_NOERROR
 }
@@ -6,9 +6,13 @@ mod builtin;
 mod joy;
 mod mci;
 mod midi;
-mod misc;
 mod mixer;
+mod output;
+mod playsound;
+mod smf;
+mod synth;
 mod time;
+mod wav;
 mod wave;
 
 pub use builtin::DLL;
@@ -16,17 +20,23 @@ pub use builtin::DLL;
 pub use joy::*;
 pub use mci::*;
 pub use midi::*;
-pub use misc::*;
 pub use mixer::*;
+pub use playsound::*;
 pub use time::*;
 pub use wave::*;
 
-use crate::host;
+use crate::{host, machine::Machine, winapi::dsound};
+use std::collections::VecDeque;
 
 #[derive(Copy, Clone, Debug)]
 pub enum MMRESULT {
     MMSYSERR_NOERROR = 0,
+    MMSYSERR_BADDEVICEID = 2,
     MMSYSERR_NOTENABLED = 3,
+    MMSYSERR_ALLOCATED = 4,
+    MMSYSERR_INVALHANDLE = 5,
+    MMSYSERR_INVALPARAM = 11,
+    MIDIERR_STILLPLAYING = 65,
 }
 impl super::calling_convention::ABIReturn for MMRESULT {
     fn into_abireturn(self) -> u64 {
@@ -38,4 +48,30 @@ impl super::calling_convention::ABIReturn for MMRESULT {
 pub struct State {
     pub audio_enabled: bool,
     pub audio: Option<Box<dyn host::Audio>>,
+    /// Sample rate `audio` was opened with.
+    pub audio_rate: u32,
+    pub midi: Option<MidiOut>,
+    /// Tick count that audio has been rendered up to; see output::render().
+    pub rendered_until: u32,
+    pub mci: mci::State,
+    pub sound: Option<playsound::Sound>,
+    /// waveOut samples waiting to be mixed with MIDI and MCI output.
+    pub wave_queue: VecDeque<i16>,
+    /// Function callbacks (address, args) waiting for a chance to call into x86.
+    pub pending_callbacks: Vec<(u32, Vec<u32>)>,
+}
+
+/// Advance sound output to the current time.  Called wherever the emulator waits, so
+/// that sound keeps playing and position notifications fire.
+pub fn pump_audio(machine: &mut Machine) {
+    dsound::mix(machine);
+    output::render(machine);
+}
+
+/// Tick count by which pump_audio() should next run, if anything is playing.
+pub fn audio_due(machine: &Machine) -> Option<u32> {
+    [dsound::mix_due(machine), output::render_due(machine)]
+        .into_iter()
+        .flatten()
+        .min()
 }
@@ -0,0 +1,112 @@
+//! The host audio stream, shared by waveOut, MIDI, MCI and PlaySound.
+//!
+//! waveOut data normally goes straight to the host.  While MIDI, MCI or PlaySound
+//! output is playing, their output is synthesized and mixed here instead, along with any waveOut
+//! data, which is queued until it can be mixed.  Rendering happens lazily, whenever
+//! the emulator waits or a winmm function is called.
+
+use super::{mci, midi, State};
+use crate::machine::Machine;
+
+/// Sample rate used when waveOut hasn't opened the host audio stream.
+const DEFAULT_SAMPLE_RATE: u32 = 22050;
+/// Render at least this often while anything is sounding.
+const RENDER_INTERVAL_MS: u32 = 10;
+/// If we haven't rendered for longer than this, drop the backlog rather than catching up.
+const MAX_RENDER_MS: u32 = 250;
+
+/// Open the host audio stream, if waveOut hasn't already.
+pub fn ensure_audio(machine: &mut Machine) {
+    let winmm = &mut machine.state.winmm;
+    if winmm.audio.is_none() {
+        winmm.audio = Some(machine.host.init_audio(DEFAULT_SAMPLE_RATE));
+        winmm.audio_rate = DEFAULT_SAMPLE_RATE;
+        winmm.rendered_until = machine.host.ticks();
+    }
+}
+
+/// Whether waveOut data needs to be queued for mixing rather than sent to the host.
+pub fn is_mixing(winmm: &State) -> bool {
+    winmm.midi.is_some() || winmm.mci.is_playing() || winmm.sound.is_some()
+}
+
+fn is_sounding(winmm: &State) -> bool {
+    winmm.midi.as_ref().is_some_and(|m| m.is_sounding())
+        || winmm.mci.is_playing()
+        || winmm.sound.is_some()
+        || !winmm.wave_queue.is_empty()
+}
+
+/// Render output up to the current time and send it to the host, then deliver any
+/// notifications for things that finished playing.
+pub fn render(machine: &mut Machine) {
+    let now = machine.host.ticks();
+    let winmm = &mut machine.state.winmm;
+    if !is_sounding(winmm) {
+        // Nothing to play; don't build up a backlog of silence.
+        winmm.rendered_until = now;
+        return;
+    }
+    let rate = winmm.audio_rate;
+    let elapsed = now.wrapping_sub(winmm.rendered_until).min(MAX_RENDER_MS);
+    let frames = (elapsed as u64 * rate as u64 / 1000) as usize;
+    if frames == 0 {
+        return;
+    }
+    winmm.rendered_until = now;
+
+    let mem = machine.emu.memory.mem();
+    let mut synth_out = vec![0.0f32; frames];
+    let mut notes = Vec::new();
+    if let Some(midi) = winmm.midi.as_mut() {
+        midi.synth.set_sample_rate(rate);
+        // Render the synth in chunks between events, so events land on time.
+        let mut pos = 0;
+        while pos < frames {
+            let mut chunk = frames - pos;
+            if let Some(n) = midi.run_stream(mem, rate, &mut notes) {
+                chunk = chunk.min(n);
+            }
+            if let Some(n) = winmm.mci.run_sequencers(&mut midi.synth, rate) {
+                chunk = chunk.min(n);
+            }
+            midi.synth.render(&mut synth_out[pos..pos + chunk]);
+            midi.advance_stream(chunk, rate);
+            winmm.mci.advance_sequencers(chunk, rate);
+            pos += chunk;
+        }
+    }
+
+    let mut out = synth_out.iter().map(|&s| [s, s]).collect::<Vec<_>>();
+    winmm.mci.mix(&*machine.host, &mut out, rate);
+    if let Some(sound) = winmm.sound.as_mut() {
+        if sound.mix(&mut out, rate) {
+            winmm.sound = None;
+        }
+    }
+    for frame in out.iter_mut() {
+        let Some(sample) = winmm.wave_queue.pop_front() else {
+            break;
+        };
+        let sample = sample as f32 / 32768.0;
+        frame[0] += sample;
+        frame[1] += sample;
+    }
+    let samples = crate::winapi::dsound::mixer::to_host_samples(&out);
+    winmm.audio.as_mut().unwrap().write(&samples);
+
+    for (msg, hdr) in notes {
+        midi::notify(machine, msg, hdr);
+    }
+    mci::notify_finished(machine);
+}
+
+/// Tick count by which render() should next run, if anything is sounding.
+pub fn render_due(machine: &Machine) -> Option<u32> {
+    let winmm = &machine.state.winmm;
+    if is_sounding(winmm) {
+        Some(winmm.rendered_until + RENDER_INTERVAL_MS)
+    } else {
+        None
+    }
+}
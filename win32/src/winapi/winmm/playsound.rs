@@ -0,0 +1,221 @@
+//! PlaySound and sndPlaySound: fire-and-forget playback of WAV files and resources.
+
+pub use crate::winapi::kernel32::HMODULE;
+
+use super::{output, wav, wav::Clip};
+use crate::{
+    host::FileOptions,
+    machine::Machine,
+    str16::{Str16, String16},
+    winapi::kernel32::{self, ResourceKey},
+};
+use memory::Extensions;
+use std::io::Read;
+use typed_path::WindowsPath;
+
+const SND_ASYNC: u32 = 0x0001;
+const SND_NODEFAULT: u32 = 0x0002;
+const SND_MEMORY: u32 = 0x0004;
+const SND_LOOP: u32 = 0x0008;
+const SND_NOSTOP: u32 = 0x0010;
+const SND_PURGE: u32 = 0x0040;
+const SND_ALIAS_ID: u32 = 0x0011_0000;
+const SND_FILENAME: u32 = 0x0002_0000;
+const SND_RESOURCE: u32 = 0x0004_0004;
+
+/// The sound currently playing.  PlaySound only plays one sound at a time.
+pub struct Sound {
+    clip: Clip,
+    position_ms: f64,
+    looping: bool,
+}
+
+impl Sound {
+    /// Mix into out.  Returns true when the sound has finished.
+    pub(super) fn mix(&mut self, mut out: &mut [[f32; 2]], rate: u32) -> bool {
+        let length_ms = self.clip.length_ms();
+        loop {
+            let n = self.clip.mix(out, &mut self.position_ms, length_ms, rate);
+            if self.position_ms < length_ms {
+                return false;
+            }
+            if !self.looping || length_ms == 0.0 {
+                return true;
+            }
+            self.position_ms = 0.0;
+            out = &mut out[n..];
+            if out.is_empty() {
+                return false;
+            }
+        }
+    }
+}
+
+/// Where the sound data comes from, per the SND_* flags.
+enum Source {
+    /// A file name, or a sound alias.
+    Name(String),
+    Resource(ResourceKey<String16>),
+    Memory(u32),
+}
+
+fn read_file(machine: &Machine, path: &str) -> Option<Vec<u8>> {
+    let mut file = machine
+        .host
+        .open(WindowsPath::new(path), FileOptions::read())
+        .ok()?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).ok()?;
+    Some(buf)
+}
+
+fn load(machine: &Machine, source: &Source, hmod: HMODULE) -> Option<Clip> {
+    let mem = machine.mem();
+    let buf = match source {
+        Source::Name(name) => read_file(machine, name)?,
+        Source::Resource(name) => {
+            let hinstance = if hmod.is_null() {
+                machine.state.kernel32.image_base
+            } else {
+                hmod.to_raw()
+            };
+            let typ = String16::from("WAVE");
+            let range = kernel32::find_resource(
+                &machine.state.kernel32,
+                mem,
+                hinstance,
+                ResourceKey::Name(typ.as_str16()),
+                &name.as_ref(),
+            )?;
+            mem.slice(range).to_vec()
+        }
+        Source::Memory(addr) => {
+            // The size of in-memory sounds comes from the RIFF header.
+            let len = mem.get_pod::<u32>(addr + 4) + 8;
+            mem.sub32(*addr, len).to_vec()
+        }
+    };
+    Some(wav::parse(&buf)?.decode())
+}
+
+fn stop(machine: &mut Machine) {
+    output::render(machine);
+    machine.state.winmm.sound = None;
+}
+
+async fn play_sound(
+    machine: &mut Machine,
+    source: Option<Source>,
+    hmod: HMODULE,
+    flags: u32,
+) -> bool {
+    let Some(source) = source else {
+        stop(machine);
+        return true;
+    };
+    if flags & SND_PURGE != 0 {
+        stop(machine);
+        return true;
+    }
+    if flags & SND_NOSTOP != 0 && machine.state.winmm.sound.is_some() {
+        return false;
+    }
+    if !machine.state.winmm.audio_enabled {
+        return false;
+    }
+    let Some(clip) = load(machine, &source, hmod) else {
+        // There are no system sounds to fall back on, regardless of SND_NODEFAULT.
+        log::warn!(
+            "PlaySound: failed to load sound (nodefault={})",
+            flags & SND_NODEFAULT != 0
+        );
+        return false;
+    };
+
+    stop(machine);
+    output::ensure_audio(machine);
+    let asynchronous = flags & SND_ASYNC != 0;
+    machine.state.winmm.sound = Some(Sound {
+        clip,
+        position_ms: 0.0,
+        // SND_LOOP is only meaningful with SND_ASYNC.
+        looping: asynchronous && flags & SND_LOOP != 0,
+    });
+    if !asynchronous {
+        while machine.state.winmm.sound.is_some() {
+            kernel32::Sleep(machine, 10).await;
+            output::render(machine);
+        }
+    }
+    true
+}
+
+/// Interpret the pszSound argument according to the flags.
+fn source(pszSound: u32, flags: u32, read_str: impl Fn(u32) -> String) -> Option<Source> {
+    if pszSound == 0 {
+        return None;
+    }
+    Some(if flags & SND_RESOURCE == SND_RESOURCE {
+        Source::Resource(if pszSound >> 16 == 0 {
+            ResourceKey::Id(pszSound)
+        } else {
+            ResourceKey::Name(String16::from(&read_str(pszSound)))
+        })
+    } else if flags & SND_MEMORY != 0 {
+        Source::Memory(pszSound)
+    } else if flags & SND_ALIAS_ID == SND_ALIAS_ID {
+        // A system sound id; we have no system sounds.
+        Source::Name(String::new())
+    } else {
+        let name = read_str(pszSound);
+        if flags & SND_FILENAME == 0 {
+            // Probably an alias like "SystemAsterisk", but possibly a file name.
+            log::debug!("PlaySound: treating {name:?} as a file name");
+        }
+        Source::Name(name)
+    })
+}
+
+fn read_str_a(machine: &Machine) -> impl Fn(u32) -> String + '_ {
+    |addr| String::from_utf8_lossy(machine.mem().slicez(addr)).into_owned()
+}
+
+fn read_str_w(machine: &Machine) -> impl Fn(u32) -> String + '_ {
+    |addr| {
+        unsafe { Str16::from_nul_term_ptr(machine.mem(), addr) }
+            .unwrap()
+            .to_string()
+    }
+}
+
+#[win32_derive::dllexport]
+pub async fn PlaySoundA(
+    machine: &mut Machine,
+    pszSound: u32,
+    hmod: HMODULE,
+    fdwSound: u32,
+) -> bool {
+    let source = source(pszSound, fdwSound, read_str_a(machine));
+    play_sound(machine, source, hmod, fdwSound).await
+}
+
+#[win32_derive::dllexport]
+pub async fn PlaySoundW(
+    machine: &mut Machine,
+    pszSound: u32,
+    hmod: HMODULE,
+    fdwSound: u32,
+) -> bool {
+    let source = source(pszSound, fdwSound, read_str_w(machine));
+    play_sound(machine, source, hmod, fdwSound).await
+}
+
+#[win32_derive::dllexport]
+pub async fn sndPlaySoundA(machine: &mut Machine, lpszSound: u32, fuSound: u32) -> bool {
+    PlaySoundA(machine, lpszSound, HMODULE::null(), fuSound).await
+}
+
+#[win32_derive::dllexport]
+pub async fn sndPlaySoundW(machine: &mut Machine, lpszSound: u32, fuSound: u32) -> bool {
+    PlaySoundW(machine, lpszSound, HMODULE::null(), fuSound).await
+}
@@ -0,0 +1,284 @@
+//! Standard MIDI Files, flattened into a list of timed events for playback.
+
+use super::synth::Synth;
+
+enum Msg {
+    Short(u32),
+    Sysex(Vec<u8>),
+}
+
+struct Event {
+    /// Time from the start of the sequence, in milliseconds.
+    ms: f64,
+    msg: Msg,
+}
+
+/// A parsed MIDI file and a play position within it.
+pub struct Sequence {
+    events: Vec<Event>,
+    pub length_ms: f64,
+    /// Index of the next event to play.
+    next: usize,
+    pub position_ms: f64,
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    ofs: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn u8(&mut self) -> Option<u8> {
+        let b = *self.buf.get(self.ofs)?;
+        self.ofs += 1;
+        Some(b)
+    }
+
+    fn bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let bytes = self.buf.get(self.ofs..self.ofs + n)?;
+        self.ofs += n;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    /// A variable-length quantity: 7 bits per byte, high bit set on all but the last.
+    fn varlen(&mut self) -> Option<u32> {
+        let mut n = 0u32;
+        loop {
+            let b = self.u8()?;
+            n = (n << 7) | (b & 0x7F) as u32;
+            if b & 0x80 == 0 {
+                return Some(n);
+            }
+        }
+    }
+}
+
+enum TrackEvent {
+    Tempo(u32),
+    Msg(Msg),
+}
+
+/// Parse one MTrk chunk body into (tick, event) pairs, plus the tick of its end.
+fn parse_track(buf: &[u8], out: &mut Vec<(u32, TrackEvent)>) -> Option<u32> {
+    let mut r = Reader { buf, ofs: 0 };
+    let mut tick = 0u32;
+    let mut running_status = 0u8;
+    while r.ofs < buf.len() {
+        tick += r.varlen()?;
+        let mut status = r.u8()?;
+        if status < 0x80 {
+            // Running status: this byte is the first data byte.
+            r.ofs -= 1;
+            status = running_status;
+        }
+        match status {
+            0xFF => {
+                let kind = r.u8()?;
+                let len = r.varlen()? as usize;
+                let data = r.bytes(len)?;
+                match kind {
+                    0x2F => break, // end of track
+                    0x51 if len == 3 => {
+                        let tempo = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+                        out.push((tick, TrackEvent::Tempo(tempo)));
+                    }
+                    _ => {}
+                }
+            }
+            0xF0 | 0xF7 => {
+                let len = r.varlen()? as usize;
+                let mut data = Vec::with_capacity(len + 1);
+                if status == 0xF0 {
+                    data.push(0xF0);
+                }
+                data.extend_from_slice(r.bytes(len)?);
+                out.push((tick, TrackEvent::Msg(Msg::Sysex(data))));
+            }
+            0x80..=0xEF => {
+                running_status = status;
+                let data1 = r.u8()?;
+                let data2 = match status & 0xF0 {
+                    0xC0 | 0xD0 => 0,
+                    _ => r.u8()?,
+                };
+                let msg = status as u32 | (data1 as u32) << 8 | (data2 as u32) << 16;
+                out.push((tick, TrackEvent::Msg(Msg::Short(msg))));
+            }
+            _ => return None,
+        }
+    }
+    Some(tick)
+}
+
+/// Parse a Standard MIDI File, or an RMID (RIFF-wrapped) one.
+/// For an SMPTE time division (high bit set), ticks per second: frames per second
+/// (as a negative byte) times ticks per frame.  Otherwise the division is in ticks
+/// per quarter note, and the rate depends on the tempo.
+pub fn smpte_ticks_per_second(division: u16) -> Option<f64> {
+    if division & 0x8000 == 0 {
+        return None;
+    }
+    let fps = -((division >> 8) as i8) as f64;
+    let ticks_per_frame = (division & 0xFF) as f64;
+    Some(fps * ticks_per_frame)
+}
+
+pub fn parse(buf: &[u8]) -> Option<Sequence> {
+    let buf = if buf.starts_with(b"RIFF") && buf.get(8..12) == Some(b"RMID") {
+        let pos = buf.windows(4).position(|w| w == b"MThd")?;
+        &buf[pos..]
+    } else {
+        buf
+    };
+
+    let mut r = Reader { buf, ofs: 0 };
+    if r.bytes(4)? != b"MThd" {
+        return None;
+    }
+    let header_len = r.u32()? as usize;
+    let header = r.bytes(header_len)?;
+    let mut h = Reader {
+        buf: header,
+        ofs: 0,
+    };
+    let _format = h.u16()?;
+    let track_count = h.u16()?;
+    let division = h.u16()?;
+
+    let mut events = Vec::new();
+    let mut end_tick = 0;
+    for _ in 0..track_count {
+        let id = r.bytes(4)?;
+        let len = r.u32()? as usize;
+        let body = r.bytes(len)?;
+        if id == b"MTrk" {
+            end_tick = end_tick.max(parse_track(body, &mut events)?);
+        }
+    }
+    // Merge tracks; the sort is stable so events at the same tick keep track order.
+    events.sort_by_key(|&(tick, _)| tick);
+
+    // Convert ticks to milliseconds, following tempo changes.
+    let ms_per_tick = |tempo: u32| -> f64 {
+        if let Some(ticks_per_second) = smpte_ticks_per_second(division) {
+            1000.0 / ticks_per_second
+        } else {
+            tempo as f64 / 1000.0 / division as f64
+        }
+    };
+    let mut tempo = 500_000;
+    let mut last_tick = 0;
+    let mut ms = 0.0;
+    let mut seq = Vec::new();
+    for (tick, event) in events {
+        ms += (tick - last_tick) as f64 * ms_per_tick(tempo);
+        last_tick = tick;
+        match event {
+            TrackEvent::Tempo(t) => tempo = t,
+            TrackEvent::Msg(msg) => seq.push(Event { ms, msg }),
+        }
+    }
+    let length_ms = ms + (end_tick.max(last_tick) - last_tick) as f64 * ms_per_tick(tempo);
+
+    Some(Sequence {
+        events: seq,
+        length_ms,
+        next: 0,
+        position_ms: 0.0,
+    })
+}
+
+impl Sequence {
+    fn send(synth: &mut Synth, msg: &Msg) {
+        match msg {
+            Msg::Short(msg) => synth.short_msg(*msg),
+            Msg::Sysex(data) => synth.sysex(data),
+        }
+    }
+
+    /// Play events that are due at the current position.
+    /// Returns the milliseconds until the next event, if any.
+    pub fn run(&mut self, synth: &mut Synth) -> Option<f64> {
+        while let Some(event) = self.events.get(self.next) {
+            if event.ms > self.position_ms {
+                return Some(event.ms - self.position_ms);
+            }
+            Self::send(synth, &event.msg);
+            self.next += 1;
+        }
+        None
+    }
+
+    pub fn advance(&mut self, ms: f64) {
+        self.position_ms = (self.position_ms + ms).min(self.length_ms);
+    }
+
+    /// Move the play position.  The synth is reset and all events other than notes
+    /// before the new position are replayed, so instruments and controllers are right.
+    pub fn seek(&mut self, ms: f64, synth: &mut Synth) {
+        synth.reset();
+        self.next = 0;
+        self.position_ms = ms.min(self.length_ms);
+        while let Some(event) = self.events.get(self.next) {
+            if event.ms >= self.position_ms {
+                break;
+            }
+            let is_note = matches!(event.msg, Msg::Short(msg) if matches!(msg & 0xF0, 0x80 | 0x90));
+            if !is_note {
+                Self::send(synth, &event.msg);
+            }
+            self.next += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(b"MThd\0\0\0\x06\0\x01\0\x02\0\x60"); // format 1, 2 tracks, 96 ppq
+                                                                    // Tempo track: 120bpm, then 60bpm after one beat.
+        let tempo = [
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // 500000us
+            0x60, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // 1000000us
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        buf.extend_from_slice(b"MTrk\0\0\0");
+        buf.push(tempo.len() as u8);
+        buf.extend_from_slice(&tempo);
+        // Note on at 0, running-status note on at beat 1, note off at beat 2.
+        let notes = [
+            0x00, 0x90, 0x3C, 0x40, //
+            0x60, 0x3E, 0x40, //
+            0x60, 0x80, 0x3C, 0x00, //
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        buf.extend_from_slice(b"MTrk\0\0\0");
+        buf.push(notes.len() as u8);
+        buf.extend_from_slice(&notes);
+
+        let seq = parse(&buf).unwrap();
+        let times: Vec<f64> = seq.events.iter().map(|e| e.ms).collect();
+        assert_eq!(times, [0.0, 500.0, 1500.0]);
+        assert!(matches!(seq.events[1].msg, Msg::Short(0x40_3E_90)));
+        assert_eq!(seq.length_ms, 1500.0);
+    }
+
+    #[test]
+    fn test_smpte_division() {
+        // 25fps (-25 = 0xE7), 40 ticks per frame: millisecond resolution.
+        assert_eq!(smpte_ticks_per_second(0xE728), Some(1000.0));
+        assert_eq!(smpte_ticks_per_second(96), None);
+    }
+}
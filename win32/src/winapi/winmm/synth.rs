@@ -0,0 +1,393 @@
+//! A small General MIDI software synthesizer.
+//!
+//! Rather than bundling sample data, each GM instrument family is approximated by a
+//! few additive harmonics and an ADSR envelope, and the percussion channel by tuned
+//! sine sweeps and noise.  It won't fool anyone, but it plays the right notes.
+
+use std::f32::consts::TAU;
+
+/// Simultaneous voices; the oldest voice is stolen beyond this.
+const MAX_VOICES: usize = 32;
+/// The GM percussion channel (channel 10, zero-based).
+const DRUM_CHANNEL: usize = 9;
+/// Headroom so a handful of loud voices don't clip.
+const MASTER_GAIN: f32 = 0.2;
+
+/// The timbre of an instrument family.
+struct Instrument {
+    /// Amplitudes of the first few harmonics.
+    harmonics: [f32; 4],
+    /// Envelope times in seconds and sustain level.
+    attack: f32,
+    decay: f32,
+    sustain: f32,
+    release: f32,
+}
+
+/// One instrument per group of 8 GM programs.
+#[rustfmt::skip]
+const INSTRUMENTS: [Instrument; 16] = [
+    // piano
+    Instrument { harmonics: [1.0, 0.5, 0.25, 0.12], attack: 0.002, decay: 0.8, sustain: 0.0, release: 0.3 },
+    // chromatic percussion
+    Instrument { harmonics: [1.0, 0.0, 0.3, 0.0], attack: 0.001, decay: 0.5, sustain: 0.0, release: 0.4 },
+    // organ
+    Instrument { harmonics: [1.0, 0.7, 0.5, 0.3], attack: 0.01, decay: 0.1, sustain: 0.9, release: 0.05 },
+    // guitar
+    Instrument { harmonics: [1.0, 0.6, 0.3, 0.2], attack: 0.002, decay: 0.6, sustain: 0.1, release: 0.2 },
+    // bass
+    Instrument { harmonics: [1.0, 0.4, 0.1, 0.0], attack: 0.005, decay: 0.4, sustain: 0.4, release: 0.1 },
+    // strings
+    Instrument { harmonics: [1.0, 0.5, 0.33, 0.25], attack: 0.08, decay: 0.2, sustain: 0.8, release: 0.3 },
+    // ensemble
+    Instrument { harmonics: [1.0, 0.5, 0.33, 0.25], attack: 0.1, decay: 0.3, sustain: 0.7, release: 0.4 },
+    // brass
+    Instrument { harmonics: [1.0, 0.8, 0.6, 0.4], attack: 0.03, decay: 0.2, sustain: 0.7, release: 0.1 },
+    // reed
+    Instrument { harmonics: [1.0, 0.1, 0.5, 0.1], attack: 0.03, decay: 0.2, sustain: 0.7, release: 0.1 },
+    // pipe
+    Instrument { harmonics: [1.0, 0.2, 0.05, 0.0], attack: 0.04, decay: 0.1, sustain: 0.8, release: 0.15 },
+    // synth lead
+    Instrument { harmonics: [1.0, 0.5, 0.33, 0.25], attack: 0.005, decay: 0.1, sustain: 0.8, release: 0.1 },
+    // synth pad
+    Instrument { harmonics: [1.0, 0.3, 0.2, 0.1], attack: 0.3, decay: 0.5, sustain: 0.7, release: 0.8 },
+    // synth effects
+    Instrument { harmonics: [1.0, 0.0, 0.4, 0.2], attack: 0.1, decay: 0.5, sustain: 0.5, release: 0.6 },
+    // ethnic
+    Instrument { harmonics: [1.0, 0.5, 0.2, 0.1], attack: 0.003, decay: 0.5, sustain: 0.2, release: 0.2 },
+    // percussive
+    Instrument { harmonics: [1.0, 0.3, 0.0, 0.2], attack: 0.001, decay: 0.3, sustain: 0.0, release: 0.2 },
+    // sound effects
+    Instrument { harmonics: [1.0, 0.0, 0.0, 0.5], attack: 0.05, decay: 0.5, sustain: 0.3, release: 0.3 },
+];
+
+#[derive(Clone, Copy)]
+struct Channel {
+    program: u8,
+    volume: u8,
+    expression: u8,
+    /// Pitch bend, in semitones.
+    bend: f32,
+    sustain: bool,
+}
+
+impl Default for Channel {
+    fn default() -> Self {
+        Channel {
+            program: 0,
+            volume: 100,
+            expression: 127,
+            bend: 0.0,
+            sustain: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Stage {
+    Attack,
+    Decay,
+    Release,
+}
+
+enum Sound {
+    Tone(&'static Instrument),
+    /// A sine whose pitch falls, for kicks and toms.
+    Drum {
+        decay: f32,
+    },
+    /// Noise, for snares, hats and cymbals.
+    Noise {
+        decay: f32,
+    },
+}
+
+struct Voice {
+    channel: usize,
+    note: u8,
+    velocity: f32,
+    sound: Sound,
+    /// Oscillator phase, in cycles.
+    phase: f32,
+    /// Seconds since the note started.
+    time: f32,
+    env: f32,
+    stage: Stage,
+    /// Key released while the sustain pedal was down.
+    held_by_pedal: bool,
+}
+
+fn note_freq(note: f32) -> f32 {
+    440.0 * 2f32.powf((note - 69.0) / 12.0)
+}
+
+/// Per-sample multiplier that decays by 1/e over `secs`.
+fn decay_coef(secs: f32, rate: f32) -> f32 {
+    (-1.0 / (secs * rate)).exp()
+}
+
+pub struct Synth {
+    rate: f32,
+    channels: [Channel; 16],
+    voices: Vec<Voice>,
+    /// Output volume, from midiOutSetVolume.
+    pub volume: f32,
+    noise: u32,
+}
+
+impl Synth {
+    pub fn new(sample_rate: u32) -> Self {
+        Synth {
+            rate: sample_rate as f32,
+            channels: Default::default(),
+            voices: Vec::new(),
+            volume: 1.0,
+            noise: 0x1234_5678,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.rate = sample_rate as f32;
+    }
+
+    /// Silence everything and reset controllers, as for midiOutReset or a GM reset.
+    pub fn reset(&mut self) {
+        self.voices.clear();
+        self.channels = Default::default();
+    }
+
+    /// Handle a packed short message, as passed to midiOutShortMsg.
+    pub fn short_msg(&mut self, msg: u32) {
+        let [status, data1, data2, _] = msg.to_le_bytes();
+        let channel = (status & 0xF) as usize;
+        match status & 0xF0 {
+            0x80 => self.note_off(channel, data1),
+            0x90 if data2 == 0 => self.note_off(channel, data1),
+            0x90 => self.note_on(channel, data1, data2),
+            0xB0 => self.control_change(channel, data1, data2),
+            0xC0 => self.channels[channel].program = data1 & 0x7F,
+            0xE0 => {
+                let bend = ((data2 as i32) << 7 | data1 as i32) - 0x2000;
+                // The default pitch bend range is +/- 2 semitones.
+                self.channels[channel].bend = bend as f32 / 0x2000 as f32 * 2.0;
+            }
+            // Aftertouch and system messages don't affect this synth.
+            _ => {}
+        }
+    }
+
+    /// Handle a system exclusive message, as passed to midiOutLongMsg.
+    pub fn sysex(&mut self, data: &[u8]) {
+        const GM_RESET: [u8; 6] = [0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7];
+        if data.starts_with(&GM_RESET) {
+            self.reset();
+        }
+    }
+
+    fn note_on(&mut self, channel: usize, note: u8, velocity: u8) {
+        // Retriggering a note replaces it.
+        self.voices
+            .retain(|v| !(v.channel == channel && v.note == note));
+        if self.voices.len() >= MAX_VOICES {
+            self.voices.remove(0);
+        }
+        let sound = if channel == DRUM_CHANNEL {
+            match note {
+                // Kicks and toms.
+                35 | 36 | 41 | 43 | 45 | 47 | 48 | 50 => Sound::Drum { decay: 0.15 },
+                // Open hats and cymbals ring longer.
+                46 | 49 | 51 | 52 | 55 | 57 | 59 => Sound::Noise { decay: 0.4 },
+                _ => Sound::Noise { decay: 0.06 },
+            }
+        } else {
+            let program = self.channels[channel].program;
+            Sound::Tone(&INSTRUMENTS[program as usize / 8])
+        };
+        self.voices.push(Voice {
+            channel,
+            note,
+            velocity: velocity as f32 / 127.0,
+            sound,
+            phase: 0.0,
+            time: 0.0,
+            env: 0.0,
+            stage: Stage::Attack,
+            held_by_pedal: false,
+        });
+    }
+
+    fn note_off(&mut self, channel: usize, note: u8) {
+        let sustain = self.channels[channel].sustain;
+        for v in self.voices.iter_mut() {
+            if v.channel == channel && v.note == note && v.stage != Stage::Release {
+                if sustain {
+                    v.held_by_pedal = true;
+                } else {
+                    v.stage = Stage::Release;
+                }
+            }
+        }
+    }
+
+    fn control_change(&mut self, channel: usize, controller: u8, value: u8) {
+        let ch = &mut self.channels[channel];
+        match controller {
+            7 => ch.volume = value,
+            11 => ch.expression = value,
+            64 => {
+                ch.sustain = value >= 64;
+                if !ch.sustain {
+                    for v in self.voices.iter_mut() {
+                        if v.channel == channel && v.held_by_pedal {
+                            v.stage = Stage::Release;
+                        }
+                    }
+                }
+            }
+            // All sound off.
+            120 => self.voices.retain(|v| v.channel != channel),
+            // Reset all controllers.
+            121 => {
+                *ch = Channel {
+                    program: ch.program,
+                    ..Default::default()
+                }
+            }
+            // All notes off.
+            123 => {
+                for v in self.voices.iter_mut() {
+                    if v.channel == channel {
+                        v.stage = Stage::Release;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn next_noise(&mut self) -> f32 {
+        // xorshift32
+        let mut x = self.noise;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.noise = x;
+        x as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    /// Add the synthesized output to `out`, mono samples in [-1, 1].
+    pub fn render(&mut self, out: &mut [f32]) {
+        let rate = self.rate;
+        let mut voices = std::mem::take(&mut self.voices);
+        for v in voices.iter_mut() {
+            let ch = self.channels[v.channel];
+            let gain = v.velocity
+                * (ch.volume as f32 / 127.0)
+                * (ch.expression as f32 / 127.0)
+                * self.volume
+                * MASTER_GAIN;
+            for sample in out.iter_mut() {
+                let s = match v.sound {
+                    Sound::Tone(inst) => {
+                        match v.stage {
+                            Stage::Attack => {
+                                v.env += 1.0 / (inst.attack * rate);
+                                if v.env >= 1.0 {
+                                    v.env = 1.0;
+                                    v.stage = Stage::Decay;
+                                }
+                            }
+                            Stage::Decay => {
+                                v.env = inst.sustain
+                                    + (v.env - inst.sustain) * decay_coef(inst.decay, rate);
+                            }
+                            Stage::Release => v.env *= decay_coef(inst.release / 4.0, rate),
+                        }
+                        let s = inst
+                            .harmonics
+                            .iter()
+                            .enumerate()
+                            .map(|(i, amp)| amp * (TAU * v.phase * (i + 1) as f32).sin())
+                            .sum::<f32>();
+                        v.phase += note_freq(v.note as f32 + ch.bend) / rate;
+                        s / 2.0
+                    }
+                    Sound::Drum { decay } => {
+                        v.env = (-v.time / decay).exp();
+                        // Sweep down towards a pitch set by the note.
+                        let base = 40.0 + (v.note as f32 - 35.0) * 8.0;
+                        v.phase += base * (1.0 + 2.0 * v.env) / rate;
+                        (TAU * v.phase).sin()
+                    }
+                    Sound::Noise { decay } => {
+                        v.env = (-v.time / decay).exp();
+                        self.next_noise() * 0.5
+                    }
+                };
+                v.phase = v.phase.fract();
+                v.time += 1.0 / rate;
+                *sample += s * v.env * gain;
+            }
+        }
+        // Drop voices that have faded out.
+        voices.retain(|v| match v.sound {
+            Sound::Tone(_) => !(v.stage == Stage::Release && v.env < 1e-4),
+            _ => v.env >= 1e-4,
+        });
+        self.voices = voices;
+    }
+
+    pub fn is_silent(&self) -> bool {
+        self.voices.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peak(synth: &mut Synth, frames: usize) -> f32 {
+        let mut out = vec![0.0; frames];
+        synth.render(&mut out);
+        out.iter().fold(0.0, |a, s| a.max(s.abs()))
+    }
+
+    #[test]
+    fn test_note_on_off() {
+        let mut synth = Synth::new(22050);
+        assert_eq!(peak(&mut synth, 100), 0.0);
+
+        // Organ: sustains while held.
+        synth.short_msg(0x10_C0);
+        synth.short_msg(0x7F_3C_90);
+        assert!(peak(&mut synth, 2205) > 0.1);
+        assert!(peak(&mut synth, 22050) > 0.1);
+
+        // Note on with velocity 0 is a note off; the release fades to nothing.
+        synth.short_msg(0x00_3C_90);
+        peak(&mut synth, 22050);
+        assert!(synth.is_silent());
+    }
+
+    #[test]
+    fn test_sustain_pedal() {
+        let mut synth = Synth::new(22050);
+        synth.short_msg(0x10_C0);
+        synth.short_msg(0x7F_40_B0); // pedal down
+        synth.short_msg(0x7F_3C_90);
+        synth.short_msg(0x00_3C_80);
+        peak(&mut synth, 22050);
+        assert!(!synth.is_silent());
+        synth.short_msg(0x00_40_B0); // pedal up
+        peak(&mut synth, 22050);
+        assert!(synth.is_silent());
+    }
+
+    #[test]
+    fn test_gm_reset() {
+        let mut synth = Synth::new(22050);
+        synth.short_msg(0x7F_3C_90);
+        synth.sysex(&[0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7]);
+        assert!(synth.is_silent());
+    }
+}
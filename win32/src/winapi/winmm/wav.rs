@@ -0,0 +1,316 @@
+//! Parsing of RIFF WAVE files.
+
+use crate::winapi::dsound::mixer::{self, Format};
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IMA_ADPCM: u16 = 0x11;
+
+pub enum Encoding {
+    Pcm,
+    ImaAdpcm { block_align: u16 },
+}
+
+pub struct Wav<'a> {
+    /// Format of the decoded samples.
+    pub format: Format,
+    pub encoding: Encoding,
+    /// Sample data; may be shorter than data_len if the buffer was truncated.
+    pub data: &'a [u8],
+    /// Length of the sample data according to the file.
+    pub data_len: u32,
+}
+
+fn read_u16(buf: &[u8], ofs: usize) -> u16 {
+    u16::from_le_bytes([buf[ofs], buf[ofs + 1]])
+}
+
+fn read_u32(buf: &[u8], ofs: usize) -> u32 {
+    u32::from_le_bytes(buf[ofs..ofs + 4].try_into().unwrap())
+}
+
+/// Parse a PCM or IMA ADPCM WAVE file.  Tolerates a truncated buffer, so the format and
+/// length can be read from just the start of a file.
+pub fn parse(buf: &[u8]) -> Option<Wav<'_>> {
+    if buf.len() < 12 || &buf[0..4] != b"RIFF" || &buf[8..12] != b"WAVE" {
+        return None;
+    }
+    let mut format = None;
+    let mut ofs = 12;
+    while ofs + 8 <= buf.len() {
+        let id = &buf[ofs..ofs + 4];
+        let len = read_u32(buf, ofs + 4);
+        let body = ofs + 8;
+        match id {
+            b"fmt " => {
+                if body + 16 > buf.len() {
+                    return None;
+                }
+                let channels = read_u16(buf, body + 2);
+                let sample_rate = read_u32(buf, body + 4);
+                let block_align = read_u16(buf, body + 12);
+                let bits_per_sample = read_u16(buf, body + 14);
+                format = Some(match read_u16(buf, body) {
+                    WAVE_FORMAT_PCM => (
+                        Format {
+                            channels,
+                            sample_rate,
+                            bits_per_sample,
+                        },
+                        Encoding::Pcm,
+                    ),
+                    WAVE_FORMAT_IMA_ADPCM
+                        if bits_per_sample == 4
+                            && (1..=2).contains(&channels)
+                            && block_align > 4 * channels =>
+                    {
+                        (
+                            Format {
+                                channels,
+                                sample_rate,
+                                bits_per_sample: 16,
+                            },
+                            Encoding::ImaAdpcm { block_align },
+                        )
+                    }
+                    tag => {
+                        log::warn!("wav: unsupported format {tag:#x}");
+                        return None;
+                    }
+                });
+            }
+            b"data" => {
+                let (format, encoding) = format?;
+                let end = buf.len().min(body + len as usize);
+                return Some(Wav {
+                    format,
+                    encoding,
+                    data: &buf[body..end],
+                    data_len: len,
+                });
+            }
+            _ => {}
+        }
+        // Chunks are padded to even sizes.
+        ofs = body + ((len as usize + 1) & !1);
+    }
+    None
+}
+
+/// Number of sample frames in an IMA ADPCM block of the given length.
+fn adpcm_block_frames(len: usize, channels: usize) -> usize {
+    let header = 4 * channels;
+    if len < header {
+        return 0;
+    }
+    // The header holds one sample; each remaining byte holds two.
+    1 + (len - header) * 2 / channels
+}
+
+impl<'a> Wav<'a> {
+    /// Length in sample frames, according to the file.
+    pub fn frames(&self) -> u32 {
+        match self.encoding {
+            Encoding::Pcm => self.data_len / self.format.block_align(),
+            Encoding::ImaAdpcm { block_align } => {
+                let channels = self.format.channels as usize;
+                let block_align = block_align as usize;
+                let len = self.data_len as usize;
+                let full = len / block_align * adpcm_block_frames(block_align, channels);
+                (full + adpcm_block_frames(len % block_align, channels)) as u32
+            }
+        }
+    }
+
+    pub fn length_ms(&self) -> f64 {
+        self.frames() as f64 * 1000.0 / self.format.sample_rate as f64
+    }
+
+    /// Decode the sample data to PCM.
+    pub fn decode(&self) -> Clip {
+        let data = match self.encoding {
+            Encoding::Pcm => self.data.to_vec(),
+            Encoding::ImaAdpcm { block_align } => {
+                let channels = self.format.channels as usize;
+                let mut out = Vec::new();
+                for block in self.data.chunks(block_align as usize) {
+                    decode_adpcm_block(block, channels, &mut out);
+                }
+                out
+            }
+        };
+        Clip {
+            format: self.format,
+            data,
+        }
+    }
+}
+
+const ADPCM_INDEX_TABLE: [i32; 8] = [-1, -1, -1, -1, 2, 4, 6, 8];
+
+const ADPCM_STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+    73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449,
+    494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272,
+    2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493,
+    10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
+];
+
+struct AdpcmChannel {
+    predictor: i32,
+    index: i32,
+}
+
+impl AdpcmChannel {
+    fn decode(&mut self, nibble: u8) -> i16 {
+        let step = ADPCM_STEP_TABLE[self.index as usize];
+        let mut diff = step >> 3;
+        if nibble & 1 != 0 {
+            diff += step >> 2;
+        }
+        if nibble & 2 != 0 {
+            diff += step >> 1;
+        }
+        if nibble & 4 != 0 {
+            diff += step;
+        }
+        if nibble & 8 != 0 {
+            diff = -diff;
+        }
+        self.predictor = (self.predictor + diff).clamp(i16::MIN as i32, i16::MAX as i32);
+        self.index = (self.index + ADPCM_INDEX_TABLE[(nibble & 7) as usize]).clamp(0, 88);
+        self.predictor as i16
+    }
+}
+
+/// Decode one IMA ADPCM block, appending 16-bit samples to out.
+fn decode_adpcm_block(block: &[u8], channels: usize, out: &mut Vec<u8>) {
+    let frames = adpcm_block_frames(block.len(), channels);
+    if frames == 0 {
+        return;
+    }
+    let mut state = (0..channels)
+        .map(|c| AdpcmChannel {
+            predictor: read_u16(block, c * 4) as i16 as i32,
+            index: (block[c * 4 + 2] as i32).clamp(0, 88),
+        })
+        .collect::<Vec<_>>();
+    let mut samples = vec![0i16; frames * channels];
+    for (c, ch) in state.iter().enumerate() {
+        samples[c] = ch.predictor as i16;
+    }
+    // After the headers, each channel's data comes in interleaved 4-byte (8-sample) groups,
+    // low nibble first.
+    let data = &block[4 * channels..];
+    for (i, group) in data.chunks(4).enumerate() {
+        let c = i % channels;
+        let first = 1 + (i / channels) * 8;
+        for (j, nibble) in group.iter().flat_map(|b| [b & 0xF, b >> 4]).enumerate() {
+            let frame = first + j;
+            if frame < frames {
+                samples[frame * channels + c] = state[c].decode(nibble);
+            }
+        }
+    }
+    out.extend(samples.iter().flat_map(|s| s.to_le_bytes()));
+}
+
+/// Decoded PCM audio.
+pub struct Clip {
+    pub format: Format,
+    pub data: Vec<u8>,
+}
+
+impl Clip {
+    pub fn frames(&self) -> usize {
+        self.data.len() / self.format.block_align() as usize
+    }
+
+    pub fn length_ms(&self) -> f64 {
+        self.frames() as f64 * 1000.0 / self.format.sample_rate as f64
+    }
+
+    /// Mix from *pos_ms up to end_ms into out, advancing *pos_ms.
+    /// Returns the number of output frames that were used.
+    pub fn mix(&self, out: &mut [[f32; 2]], pos_ms: &mut f64, end_ms: f64, rate: u32) -> usize {
+        let src_rate = self.format.sample_rate as f64;
+        let end_frame = ((end_ms * src_rate / 1000.0) as usize).min(self.frames());
+        let mut cursor = *pos_ms * src_rate / 1000.0;
+        let step = src_rate / rate as f64;
+        let remaining = ((end_frame as f64 - cursor) / step).ceil().max(0.0) as usize;
+        let n = remaining.min(out.len());
+        if n > 0 {
+            let voice = mixer::Voice {
+                data: &self.data[..end_frame * self.format.block_align() as usize],
+                format: self.format,
+                step,
+                gains: [1.0, 1.0],
+                looping: false,
+            };
+            mixer::mix_voice(&mut out[..n], &voice, &mut cursor);
+        }
+        *pos_ms = if n == remaining {
+            end_ms
+        } else {
+            cursor * 1000.0 / src_rate
+        };
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(b"RIFF\0\0\0\0WAVE");
+        buf.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        buf.extend_from_slice(b"fmt \x10\0\0\0");
+        buf.extend_from_slice(&[1, 0, 2, 0]);
+        buf.extend_from_slice(&22050u32.to_le_bytes());
+        buf.extend_from_slice(&(22050u32 * 4).to_le_bytes());
+        buf.extend_from_slice(&[4, 0, 16, 0]);
+        buf.extend_from_slice(b"data\x08\0\0\0");
+        buf.extend_from_slice(&[1, 2, 3, 4]);
+
+        let wav = parse(&buf).unwrap();
+        assert_eq!(
+            wav.format,
+            Format {
+                channels: 2,
+                sample_rate: 22050,
+                bits_per_sample: 16
+            }
+        );
+        assert_eq!(wav.data, &[1, 2, 3, 4]);
+        assert_eq!(wav.data_len, 8);
+
+        assert!(parse(&buf[..20]).is_none());
+    }
+
+    #[test]
+    fn test_ima_adpcm() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(b"RIFF\0\0\0\0WAVE");
+        buf.extend_from_slice(b"fmt \x14\0\0\0");
+        buf.extend_from_slice(&[0x11, 0, 1, 0]);
+        buf.extend_from_slice(&8000u32.to_le_bytes());
+        buf.extend_from_slice(&4000u32.to_le_bytes());
+        buf.extend_from_slice(&[5, 0, 4, 0, 2, 0, 3, 0]);
+        // Two blocks: predictor 0, step index 0, then nibbles 7 and 0.
+        buf.extend_from_slice(b"data\x0a\0\0\0");
+        buf.extend_from_slice(&[0, 0, 0, 0, 0x07, 0, 0, 0, 0, 0x07]);
+
+        let wav = parse(&buf).unwrap();
+        assert_eq!(wav.format.bits_per_sample, 16);
+        assert_eq!(wav.frames(), 6);
+        let clip = wav.decode();
+        let samples: Vec<i16> = clip
+            .data
+            .chunks_exact(2)
+            .map(|s| i16::from_le_bytes([s[0], s[1]]))
+            .collect();
+        assert_eq!(samples, [0, 11, 13, 0, 11, 13]);
+    }
+}
@@ -99,6 +99,7 @@ pub fn waveOutOpen(
 
     let fmt = pwfx.unwrap();
     machine.state.winmm.audio = Some(machine.host.init_audio(fmt.nSamplesPerSec));
+    machine.state.winmm.audio_rate = fmt.nSamplesPerSec;
 
     MMRESULT::MMSYSERR_NOERROR
 }
@@ -289,7 +290,16 @@ pub fn waveOutWrite(
         .memory
         .mem()
         .sub32(hdr.lpData, hdr.dwBufferLength);
-    machine.state.winmm.audio.as_mut().unwrap().write(buf);
+    let winmm = &mut machine.state.winmm;
+    if super::output::is_mixing(winmm) {
+        // MIDI or MCI output owns the stream; queue samples to mix in.
+        winmm.wave_queue.extend(
+            buf.chunks_exact(2)
+                .map(|sample| i16::from_le_bytes([sample[0], sample[1]])),
+        );
+    } else {
+        winmm.audio.as_mut().unwrap().write(buf);
+    }
     MMRESULT::MMSYSERR_NOERROR
 }
 
@@ -118,7 +118,7 @@ pub fn dump_fpu_state(cpu: &crate::CPU) {
     }
     println!("fpu status: {:04x}", cpu.fpu.status());
     for i in cpu.fpu.st_top..8 {
-        let value = cpu.fpu.st[i];
+        let value = crate::FpuFloat::to_f64(cpu.fpu.st[i]);
         println!("st{}: {:20.16}", i - cpu.fpu.st_top, value);
     }
 }
@@ -0,0 +1,765 @@
+//! Software implementation of the x87 80-bit extended precision format,
+//! used for the FPU stack when the "f80" feature is enabled.
+//!
+//! Arithmetic is computed exactly (or with enough extra bits plus a sticky bit)
+//! and then rounded once, per the control word's precision and rounding fields.
+//! Transcendental functions are computed via f64.
+
+use crate::fpu::{Class, Control, FpuFloat, Rounding, Status};
+
+const EXP_MAX: i32 = 0x7FFF;
+const BIAS: i32 = 16383;
+const INT_BIT: u64 = 1 << 63;
+const QUIET_BIT: u64 = 1 << 62;
+
+#[derive(Clone, Copy, Default)]
+pub struct F80 {
+    /// Sign bit and 15-bit biased exponent, as laid out in memory.
+    se: u16,
+    /// Significand, including the explicit integer bit.
+    mant: u64,
+}
+
+/// The "real indefinite" QNaN produced by invalid operations.
+const INDEFINITE: F80 = F80 {
+    se: 0xFFFF,
+    mant: 0xC000_0000_0000_0000,
+};
+
+/// Parameters of a binary floating point format, for rounding.
+struct Format {
+    /// Significand bits, including the integer bit.
+    precision: u32,
+    bias: i32,
+    /// The all-ones exponent used for infinities and NaNs.
+    exp_max: i32,
+}
+
+const DOUBLE: Format = Format {
+    precision: 53,
+    bias: 1023,
+    exp_max: 0x7FF,
+};
+
+const SINGLE: Format = Format {
+    precision: 24,
+    bias: 127,
+    exp_max: 0xFF,
+};
+
+/// Shift right, or'ing any bits shifted out into the lowest bit.
+fn shift_right_jam(x: u128, n: u32) -> u128 {
+    if n == 0 {
+        x
+    } else if n >= 128 {
+        (x != 0) as u128
+    } else {
+        (x >> n) | ((x << (128 - n)) != 0) as u128
+    }
+}
+
+/// Decide whether to round a truncated magnitude up, given the bits that were dropped.
+fn should_round_up(sign: bool, kept: u128, rem: u128, half: u128, rounding: Rounding) -> bool {
+    match rounding {
+        Rounding::Nearest => rem > half || (rem == half && kept & 1 == 1),
+        Rounding::Down => sign && rem != 0,
+        Rounding::Up => !sign && rem != 0,
+        Rounding::Zero => false,
+    }
+}
+
+/// Round the value (-1)^sign * sig * 2^exp into the given format.
+/// Returns the biased exponent and the significand with the integer bit at bit 63;
+/// the exponent is 0 for zeros and denormals and fmt.exp_max for infinity.
+fn round(
+    sign: bool,
+    exp: i32,
+    sig: u128,
+    fmt: &Format,
+    rounding: Rounding,
+    status: &mut Status,
+) -> (i32, u64) {
+    if sig == 0 {
+        return (0, 0);
+    }
+    let lz = sig.leading_zeros();
+    let mut sig = sig << lz;
+    // The leading bit, now at bit 127, has value 2^(exp - lz + 127).
+    let mut e = exp - lz as i32 + 127 + fmt.bias;
+    let tiny = e < 1;
+    if tiny {
+        sig = shift_right_jam(sig, (1 - e).min(128) as u32);
+        e = 1;
+    }
+
+    let drop = 128 - fmt.precision;
+    let half = 1u128 << (drop - 1);
+    let rem = sig & ((1u128 << drop) - 1);
+    let mut kept = sig >> drop;
+    let up = should_round_up(sign, kept, rem, half, rounding);
+    status.set(Status::C1, up);
+    if up {
+        kept += 1;
+        if kept >> fmt.precision != 0 {
+            kept >>= 1;
+            e += 1;
+        }
+    }
+
+    let mant = (kept << (64 - fmt.precision)) as u64;
+    if rem != 0 {
+        status.insert(Status::PE);
+        if tiny && mant & INT_BIT == 0 {
+            status.insert(Status::UE);
+        }
+    }
+
+    if e >= fmt.exp_max {
+        status.insert(Status::OE | Status::PE);
+        let to_inf = match rounding {
+            Rounding::Nearest => true,
+            Rounding::Down => sign,
+            Rounding::Up => !sign,
+            Rounding::Zero => false,
+        };
+        status.set(Status::C1, to_inf);
+        return if to_inf {
+            (fmt.exp_max, INT_BIT)
+        } else {
+            (fmt.exp_max - 1, !0u64 << (64 - fmt.precision))
+        };
+    }
+
+    if mant & INT_BIT == 0 {
+        (0, mant)
+    } else {
+        (e, mant)
+    }
+}
+
+impl F80 {
+    fn new(sign: bool, exp: i32, mant: u64) -> Self {
+        F80 {
+            se: (sign as u16) << 15 | exp as u16,
+            mant,
+        }
+    }
+
+    fn zero(sign: bool) -> Self {
+        F80::new(sign, 0, 0)
+    }
+
+    fn inf(sign: bool) -> Self {
+        F80::new(sign, EXP_MAX, INT_BIT)
+    }
+
+    fn exp(self) -> i32 {
+        (self.se & 0x7FFF) as i32
+    }
+
+    pub fn is_sign_negative(self) -> bool {
+        self.se & 0x8000 != 0
+    }
+
+    pub fn abs(self) -> Self {
+        F80 {
+            se: self.se & 0x7FFF,
+            mant: self.mant,
+        }
+    }
+
+    pub fn is_nan(self) -> bool {
+        matches!(self.class(), Class::NaN | Class::Unsupported)
+    }
+
+    fn is_snan(self) -> bool {
+        self.class() == Class::NaN && self.mant & QUIET_BIT == 0
+    }
+
+    fn is_inf(self) -> bool {
+        self.class() == Class::Infinity
+    }
+
+    fn is_zero(self) -> bool {
+        self.class() == Class::Zero
+    }
+
+    /// Round (-1)^sign * sig * 2^exp per the control word.
+    fn pack(sign: bool, exp: i32, sig: u128, ctl: Control, status: &mut Status) -> Self {
+        let fmt = Format {
+            precision: ctl.precision().bits(),
+            bias: BIAS,
+            exp_max: EXP_MAX,
+        };
+        let (e, mant) = round(sign, exp, sig, &fmt, ctl.rounding(), status);
+        F80::new(sign, e, mant)
+    }
+
+    /// Break a finite nonzero value into (sign, exp, sig), where the value is
+    /// (-1)^sign * sig * 2^exp.  Sets DE for denormal operands.
+    fn unpack(self, status: &mut Status) -> (bool, i32, u64) {
+        let mut exp = self.exp();
+        if exp == 0 {
+            status.insert(Status::DE);
+            exp = 1;
+        }
+        (self.is_sign_negative(), exp - BIAS - 63, self.mant)
+    }
+
+    /// Like unpack, but shifted so the significand's integer bit is set.
+    fn unpack_normalized(self, status: &mut Status) -> (bool, i32, u64) {
+        let (sign, exp, sig) = self.unpack(status);
+        let lz = sig.leading_zeros();
+        (sign, exp - lz as i32, sig << lz)
+    }
+
+    /// Handle NaN and unsupported operands, returning the result if there were any.
+    fn propagate_nan(self, y: Self, status: &mut Status) -> Option<Self> {
+        let classes = (self.class(), y.class());
+        if classes.0 == Class::Unsupported || classes.1 == Class::Unsupported {
+            status.insert(Status::IE);
+            return Some(INDEFINITE);
+        }
+        if self.is_snan() || y.is_snan() {
+            status.insert(Status::IE);
+        }
+        let quiet = |x: F80| F80 {
+            se: x.se,
+            mant: x.mant | QUIET_BIT,
+        };
+        match classes {
+            (Class::NaN, Class::NaN) => {
+                // Real hardware picks the NaN with the larger significand.
+                Some(if y.mant > self.mant {
+                    quiet(y)
+                } else {
+                    quiet(self)
+                })
+            }
+            (Class::NaN, _) => Some(quiet(self)),
+            (_, Class::NaN) => Some(quiet(y)),
+            _ => None,
+        }
+    }
+
+    fn invalid(status: &mut Status) -> Self {
+        status.insert(Status::IE);
+        INDEFINITE
+    }
+
+    /// Round a finite value to an integer, returning the integer magnitude.
+    fn round_to_integer(self, rounding: Rounding, status: &mut Status) -> u128 {
+        if self.is_zero() {
+            return 0;
+        }
+        let (sign, exp, sig) = self.unpack(status);
+        if exp >= 0 {
+            // Already an integer; may not fit, but callers check the range.
+            return if exp > 64 {
+                u128::MAX
+            } else {
+                (sig as u128) << exp
+            };
+        }
+        let shift = (-exp) as u32;
+        let (kept, rem, half) = if shift > 64 {
+            // Magnitude is below 1/2: keep nothing, and treat the remainder as below half.
+            (0, 1, 2)
+        } else {
+            let sig = sig as u128;
+            (sig >> shift, sig & ((1 << shift) - 1), 1 << (shift - 1))
+        };
+        let up = should_round_up(sign, kept, rem, half, rounding);
+        status.set(Status::C1, up);
+        if rem != 0 {
+            status.insert(Status::PE);
+        }
+        kept + up as u128
+    }
+
+    fn from_bits_f64(bits: u64) -> Self {
+        let sign = bits >> 63 != 0;
+        let exp = ((bits >> 52) & 0x7FF) as i32;
+        let frac = bits & ((1 << 52) - 1);
+        match exp {
+            0x7FF => F80::new(sign, EXP_MAX, INT_BIT | frac << 11),
+            0 if frac == 0 => F80::zero(sign),
+            0 => {
+                // Denormal doubles are normal in extended precision.
+                let lz = frac.leading_zeros();
+                F80::new(sign, 63 - lz as i32 - 1074 + BIAS, frac << lz)
+            }
+            _ => F80::new(sign, exp - 1023 + BIAS, INT_BIT | frac << 11),
+        }
+    }
+
+    /// Round to a smaller format, returning (sign, biased exponent, significand at bit 63).
+    fn round_to(self, fmt: &Format, ctl: Control, status: &mut Status) -> (bool, i32, u64) {
+        let sign = self.is_sign_negative();
+        match self.class() {
+            Class::Zero => (sign, 0, 0),
+            Class::Infinity => (sign, fmt.exp_max, INT_BIT),
+            Class::NaN | Class::Unsupported => {
+                if self.class() == Class::Unsupported || self.is_snan() {
+                    status.insert(Status::IE);
+                }
+                let nan = if self.class() == Class::Unsupported {
+                    INDEFINITE
+                } else {
+                    self
+                };
+                (nan.is_sign_negative(), fmt.exp_max, nan.mant | QUIET_BIT)
+            }
+            _ => {
+                let (sign, exp, sig) = self.unpack(status);
+                let (e, mant) = round(sign, exp, sig as u128, fmt, ctl.rounding(), status);
+                (sign, e, mant)
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for F80 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} ({:04x}:{:016x})",
+            self.to_f64(),
+            self.se,
+            self.mant
+        )
+    }
+}
+
+impl std::fmt::Display for F80 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.to_f64(), f)
+    }
+}
+
+impl PartialEq for F80 {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(std::cmp::Ordering::Equal)
+    }
+}
+
+impl PartialOrd for F80 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        if self.is_nan() || other.is_nan() {
+            return None;
+        }
+        // Exponent and significand together order magnitudes; zeros of either sign are equal.
+        let key = |x: &F80| {
+            let mag = ((x.exp() as i128) << 64) | x.mant as i128;
+            if x.is_sign_negative() {
+                -mag
+            } else {
+                mag
+            }
+        };
+        Some(key(self).cmp(&key(other)))
+    }
+}
+
+impl FpuFloat for F80 {
+    fn from_f64(x: f64) -> Self {
+        F80::from_bits_f64(x.to_bits())
+    }
+
+    fn from_f32(x: f32) -> Self {
+        // f32 to f64 is exact.
+        F80::from_f64(x as f64)
+    }
+
+    fn from_i64(x: i64) -> Self {
+        if x == 0 {
+            return F80::zero(false);
+        }
+        let mag = x.unsigned_abs();
+        let lz = mag.leading_zeros();
+        F80::new(x < 0, 63 - lz as i32 + BIAS, mag << lz)
+    }
+
+    fn from_m80(bytes: [u8; 10]) -> Self {
+        F80 {
+            mant: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            se: u16::from_le_bytes(bytes[8..].try_into().unwrap()),
+        }
+    }
+
+    fn to_m80(self) -> [u8; 10] {
+        let mut bytes = [0u8; 10];
+        bytes[..8].copy_from_slice(&self.mant.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.se.to_le_bytes());
+        bytes
+    }
+
+    fn to_f64(self) -> f64 {
+        self.round_f64(Control::FINIT, &mut Status::empty())
+    }
+
+    fn round_f64(self, ctl: Control, status: &mut Status) -> f64 {
+        let (sign, exp, mant) = self.round_to(&DOUBLE, ctl, status);
+        let bits = (sign as u64) << 63 | (exp as u64) << 52 | (mant >> 11) & ((1 << 52) - 1);
+        f64::from_bits(bits)
+    }
+
+    fn round_f32(self, ctl: Control, status: &mut Status) -> f32 {
+        let (sign, exp, mant) = self.round_to(&SINGLE, ctl, status);
+        let bits = (sign as u32) << 31 | (exp as u32) << 23 | ((mant >> 40) as u32) & 0x7F_FFFF;
+        f32::from_bits(bits)
+    }
+
+    fn round_i64(self, ctl: Control, status: &mut Status) -> Option<i64> {
+        match self.class() {
+            Class::Zero => return Some(0),
+            Class::Normal | Class::Denormal => {}
+            _ => return None,
+        }
+        let mag = self.round_to_integer(ctl.rounding(), status);
+        if self.is_sign_negative() {
+            if mag > 1 << 63 {
+                return None;
+            }
+            Some((mag as i128).wrapping_neg() as i64)
+        } else {
+            i64::try_from(mag).ok()
+        }
+    }
+
+    fn class(self) -> Class {
+        let exp = self.exp();
+        let int_bit = self.mant & INT_BIT != 0;
+        if exp == EXP_MAX {
+            if !int_bit {
+                // pseudo-infinity / pseudo-NaN
+                Class::Unsupported
+            } else if self.mant << 1 == 0 {
+                Class::Infinity
+            } else {
+                Class::NaN
+            }
+        } else if exp == 0 {
+            if self.mant == 0 {
+                Class::Zero
+            } else {
+                Class::Denormal
+            }
+        } else if int_bit {
+            Class::Normal
+        } else {
+            // unnormal
+            Class::Unsupported
+        }
+    }
+
+    fn sign(self) -> bool {
+        self.is_sign_negative()
+    }
+
+    fn fchs(self) -> Self {
+        F80 {
+            se: self.se ^ 0x8000,
+            mant: self.mant,
+        }
+    }
+
+    fn fabs(self) -> Self {
+        self.abs()
+    }
+
+    fn fadd(self, y: Self, ctl: Control, status: &mut Status) -> Self {
+        if let Some(nan) = self.propagate_nan(y, status) {
+            return nan;
+        }
+        let (sx, sy) = (self.is_sign_negative(), y.is_sign_negative());
+        match (self.is_inf(), y.is_inf()) {
+            (true, true) if sx != sy => return F80::invalid(status),
+            (true, _) => return self,
+            (_, true) => return y,
+            _ => {}
+        }
+        let exact_zero = F80::zero(ctl.rounding() == Rounding::Down);
+        match (self.is_zero(), y.is_zero()) {
+            (true, true) => return if sx == sy { self } else { exact_zero },
+            (true, false) => {
+                let (sign, exp, sig) = y.unpack(status);
+                return F80::pack(sign, exp, sig as u128, ctl, status);
+            }
+            (false, true) => {
+                let (sign, exp, sig) = self.unpack(status);
+                return F80::pack(sign, exp, sig as u128, ctl, status);
+            }
+            _ => {}
+        }
+
+        let (mut sa, mut ea, ma) = self.unpack(status);
+        let (mut sb, mut eb, mb) = y.unpack(status);
+        // Leave headroom above for a carry, and 62 guard bits below.
+        let (mut a, mut b) = ((ma as u128) << 62, (mb as u128) << 62);
+        if ea < eb {
+            std::mem::swap(&mut sa, &mut sb);
+            std::mem::swap(&mut ea, &mut eb);
+            std::mem::swap(&mut a, &mut b);
+        }
+        let b = shift_right_jam(b, (ea - eb).min(128) as u32);
+        let (sign, sig) = if sa == sb {
+            (sa, a + b)
+        } else if a >= b {
+            (sa, a - b)
+        } else {
+            (sb, b - a)
+        };
+        if sig == 0 {
+            return exact_zero;
+        }
+        F80::pack(sign, ea - 62, sig, ctl, status)
+    }
+
+    fn fmul(self, y: Self, ctl: Control, status: &mut Status) -> Self {
+        if let Some(nan) = self.propagate_nan(y, status) {
+            return nan;
+        }
+        let sign = self.is_sign_negative() != y.is_sign_negative();
+        match (self.class(), y.class()) {
+            (Class::Infinity, Class::Zero) | (Class::Zero, Class::Infinity) => {
+                return F80::invalid(status)
+            }
+            (Class::Infinity, _) | (_, Class::Infinity) => return F80::inf(sign),
+            (Class::Zero, _) | (_, Class::Zero) => return F80::zero(sign),
+            _ => {}
+        }
+        let (_, ea, ma) = self.unpack(status);
+        let (_, eb, mb) = y.unpack(status);
+        F80::pack(sign, ea + eb, ma as u128 * mb as u128, ctl, status)
+    }
+
+    fn fdiv(self, y: Self, ctl: Control, status: &mut Status) -> Self {
+        if let Some(nan) = self.propagate_nan(y, status) {
+            return nan;
+        }
+        let sign = self.is_sign_negative() != y.is_sign_negative();
+        match (self.class(), y.class()) {
+            (Class::Infinity, Class::Infinity) | (Class::Zero, Class::Zero) => {
+                return F80::invalid(status)
+            }
+            (Class::Infinity, _) => return F80::inf(sign),
+            (_, Class::Infinity) => return F80::zero(sign),
+            (Class::Zero, _) => return F80::zero(sign),
+            (_, Class::Zero) => {
+                status.insert(Status::ZE);
+                return F80::inf(sign);
+            }
+            _ => {}
+        }
+        let (_, ea, ma) = self.unpack_normalized(status);
+        let (_, eb, mb) = y.unpack_normalized(status);
+        // Long division in two 64-bit steps, producing ~128 quotient bits.
+        let (ma, mb) = (ma as u128, mb as u128);
+        let q1 = (ma << 63) / mb;
+        let r1 = (ma << 63) % mb;
+        let q2 = (r1 << 64) / mb;
+        let r2 = (r1 << 64) % mb;
+        let sig = (q1 << 64) | q2 | (r2 != 0) as u128;
+        F80::pack(sign, ea - eb - 127, sig, ctl, status)
+    }
+
+    fn fsqrt(self, ctl: Control, status: &mut Status) -> Self {
+        if let Some(nan) = self.propagate_nan(self, status) {
+            return nan;
+        }
+        match self.class() {
+            Class::Zero => return self,
+            _ if self.is_sign_negative() => return F80::invalid(status),
+            Class::Infinity => return self,
+            _ => {}
+        }
+        let (_, mut exp, sig) = self.unpack_normalized(status);
+        let mut sig = sig as u128;
+        if exp.rem_euclid(2) == 1 {
+            sig <<= 1;
+            exp -= 1;
+        }
+        // Digit-by-digit square root of sig * 2^128, two radicand bits per step.
+        let bit = |k: u32| if k >= 128 { (sig >> (k - 128)) & 1 } else { 0 };
+        let (mut root, mut rem) = (0u128, 0u128);
+        for i in (0..98).rev() {
+            rem = (rem << 2) | (bit(2 * i + 1) << 1) | bit(2 * i);
+            let trial = (root << 2) | 1;
+            root <<= 1;
+            if rem >= trial {
+                rem -= trial;
+                root |= 1;
+            }
+        }
+        let sig = (root << 1) | (rem != 0) as u128;
+        F80::pack(false, (exp - 128) / 2 - 1, sig, ctl, status)
+    }
+
+    fn frndint(self, ctl: Control, status: &mut Status) -> Self {
+        if let Some(nan) = self.propagate_nan(self, status) {
+            return nan;
+        }
+        match self.class() {
+            Class::Zero | Class::Infinity => return self,
+            _ => {}
+        }
+        let sign = self.is_sign_negative();
+        let mag = self.round_to_integer(ctl.rounding(), status);
+        if mag == 0 {
+            return F80::zero(sign);
+        }
+        if mag == u128::MAX {
+            return self;
+        }
+        let c1 = status.contains(Status::C1);
+        // Exact, since the result has no more bits than the input.
+        let extended = Control(ctl.0 | 0x300);
+        let result = F80::pack(sign, 0, mag, extended, status);
+        status.set(Status::C1, c1);
+        result
+    }
+
+    fn fscale(self, n: i32, ctl: Control, status: &mut Status) -> Self {
+        if let Some(nan) = self.propagate_nan(self, status) {
+            return nan;
+        }
+        match self.class() {
+            Class::Zero | Class::Infinity => return self,
+            _ => {}
+        }
+        let (sign, exp, sig) = self.unpack(status);
+        // Clamp so the exponent arithmetic can't overflow; anything this large
+        // over/underflows regardless.
+        let n = n.clamp(-0x10000, 0x10000);
+        F80::pack(sign, exp + n, sig as u128, ctl, status)
+    }
+
+    fn fprem(self, y: Self, nearest: bool, status: &mut Status) -> (Self, u64, bool) {
+        if let Some(nan) = self.propagate_nan(y, status) {
+            return (nan, 0, true);
+        }
+        match (self.class(), y.class()) {
+            (Class::Infinity, _) | (_, Class::Zero) => return (F80::invalid(status), 0, true),
+            (Class::Zero, _) | (_, Class::Infinity) => return (self, 0, true),
+            _ => {}
+        }
+        let (sx, ex, mx) = self.unpack_normalized(status);
+        let (_, ey, my) = y.unpack_normalized(status);
+        let exact = Control::FINIT;
+        let d = ex - ey;
+
+        if d >= 64 {
+            // Partial remainder: reduce the exponent difference by N=63 bits per step.
+            // The result is x - q*y*2^(d-63) for an integer q.
+            let r = ((mx as u128) << 63) % my as u128;
+            let result = if r == 0 {
+                F80::zero(sx)
+            } else {
+                F80::pack(sx, ex - 63, r, exact, status)
+            };
+            return (result, 0, false);
+        }
+
+        if d < -1 || (d == -1 && !nearest) {
+            return (self, 0, true);
+        }
+
+        // Work in units of y/2 so that d == -1 (possible only for fprem1) is handled too.
+        let num = (mx as u128) << (d + 1);
+        let den = (my as u128) << 1;
+        let mut q = num / den;
+        let mut r = num % den;
+        let mut sign = sx;
+        if nearest && (r * 2 > den || (r * 2 == den && q & 1 == 1)) {
+            r = den - r;
+            q += 1;
+            sign = !sign;
+        }
+        let result = if r == 0 {
+            F80::zero(sx)
+        } else {
+            F80::pack(sign, ey - 1, r, exact, status)
+        };
+        (result, q as u64, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctl(precision: u16, rounding: u16) -> Control {
+        Control(0x7F | precision << 8 | rounding << 10)
+    }
+
+    fn f(x: f64) -> F80 {
+        F80::from_f64(x)
+    }
+
+    #[test]
+    fn round_trip_f64() {
+        for x in [0.0, -0.0, 1.0, -2.5, 1e300, 1e-310, f64::INFINITY, f64::MAX] {
+            assert_eq!(f(x).to_f64().to_bits(), x.to_bits());
+        }
+        assert!(f(f64::NAN).to_f64().is_nan());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let mut status = Status::empty();
+        let ext = ctl(3, 0);
+        assert_eq!(f(1.5).fadd(f(2.25), ext, &mut status).to_f64(), 3.75);
+        assert_eq!(f(1.5).fsub(f(2.25), ext, &mut status).to_f64(), -0.75);
+        assert_eq!(f(1.5).fmul(f(-4.0), ext, &mut status).to_f64(), -6.0);
+        assert_eq!(f(1.0).fdiv(f(8.0), ext, &mut status).to_f64(), 0.125);
+        assert_eq!(f(2.25).fsqrt(ext, &mut status).to_f64(), 1.5);
+        assert_eq!(status & Status::EXCEPTIONS, Status::empty());
+
+        // 1/3 in extended precision differs from the double.
+        let third = f(1.0).fdiv(f(3.0), ext, &mut status);
+        assert_eq!(third.mant, 0xAAAA_AAAA_AAAA_AAAB);
+        assert!(status.contains(Status::PE));
+        // ... but matches it when the control word asks for double precision.
+        let third = f(1.0).fdiv(f(3.0), ctl(2, 0), &mut status);
+        assert_eq!(third.to_f64(), 1.0 / 3.0);
+        assert_eq!(third.mant, (1.0f64 / 3.0).to_bits() << 11 | INT_BIT);
+
+        let mut status = Status::empty();
+        assert!(f(1.0).fdiv(f(0.0), ext, &mut status).is_inf());
+        assert!(status.contains(Status::ZE));
+        assert!(f(-1.0).fsqrt(ext, &mut status).is_nan());
+        assert!(status.contains(Status::IE));
+    }
+
+    #[test]
+    fn rounding_modes() {
+        let mut status = Status::empty();
+        let cases = [
+            (2.5, [2.0, 2.0, 3.0, 2.0]),
+            (-2.5, [-2.0, -3.0, -2.0, -2.0]),
+        ];
+        for (x, expected) in cases {
+            for (rc, want) in expected.into_iter().enumerate() {
+                let r = f(x).frndint(ctl(3, rc as u16), &mut status);
+                assert_eq!(r.to_f64(), want, "{x} rc={rc}");
+            }
+        }
+        assert_eq!(f(3.5).round_i64(ctl(3, 0), &mut status), Some(4));
+        assert_eq!(f(1e30).round_i64(ctl(3, 0), &mut status), None);
+    }
+
+    #[test]
+    fn fprem() {
+        let mut status = Status::empty();
+        let (r, q, done) = f(7.0).fprem(f(2.0), false, &mut status);
+        assert_eq!((r.to_f64(), q, done), (1.0, 3, true));
+        let (r, q, done) = f(7.0).fprem(f(2.0), true, &mut status);
+        assert_eq!((r.to_f64(), q, done), (-1.0, 4, true));
+        let (r, _, done) = f(1e30).fprem(f(3.0), false, &mut status);
+        assert!(!done);
+        let (r, _, done) = r.fprem(f(3.0), false, &mut status);
+        assert!(done);
+        assert_eq!(r.to_f64(), 1e30 % 3.0);
+    }
+}
@@ -4,36 +4,313 @@ use bitflags::bitflags;
 
 bitflags! {
     pub struct Status: u16 {
+        /// invalid operation
+        const IE = 1 << 0;
+        /// denormalized operand
+        const DE = 1 << 1;
+        /// zero divide
+        const ZE = 1 << 2;
+        /// overflow
+        const OE = 1 << 3;
+        /// underflow
+        const UE = 1 << 4;
+        /// precision (inexact result)
+        const PE = 1 << 5;
+        /// stack fault
+        const SF = 1 << 6;
+
         const C3 = 1 << 14;
         const C2 = 1 << 10;
         const C1 = 1 << 9;
         const C0 = 1 << 8;
+
+        /// The exception flags, which are sticky until cleared by fclex/finit.
+        const EXCEPTIONS = 0x7F;
+    }
+}
+
+/// Rounding mode, from the control word RC field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Nearest,
+    Down,
+    Up,
+    Zero,
+}
+
+/// Significand precision, from the control word PC field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precision {
+    Single,
+    Double,
+    Extended,
+}
+
+impl Precision {
+    /// Number of significand bits, including the integer bit.
+    pub fn bits(self) -> u32 {
+        match self {
+            Precision::Single => 24,
+            Precision::Double => 53,
+            Precision::Extended => 64,
+        }
+    }
+}
+
+/// FPU control word.  Kept as raw bits because reserved bits must round trip through
+/// fldcw/fnstcw.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Control(pub u16);
+
+impl Control {
+    /// Value after finit: all exceptions masked, 64-bit precision, round to nearest.
+    pub const FINIT: Control = Control(0x037F);
+    /// Value Windows starts processes with, which is like FINIT but with 53-bit precision.
+    pub const WINDOWS: Control = Control(0x027F);
+
+    pub fn rounding(self) -> Rounding {
+        match (self.0 >> 10) & 3 {
+            0 => Rounding::Nearest,
+            1 => Rounding::Down,
+            2 => Rounding::Up,
+            3 => Rounding::Zero,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn precision(self) -> Precision {
+        match (self.0 >> 8) & 3 {
+            0 => Precision::Single,
+            // 1 is reserved; treat it like double.
+            1 | 2 => Precision::Double,
+            3 => Precision::Extended,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Classification of a value as reported by fxam, in C3/C2/C0 order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    Unsupported = 0b000,
+    NaN = 0b001,
+    Normal = 0b010,
+    Infinity = 0b011,
+    Zero = 0b100,
+    Empty = 0b101,
+    Denormal = 0b110,
+}
+
+/// The operations the FPU needs from its register representation.
+///
+/// Operations that round take the control word, and accumulate exception flags
+/// (and C1, the round-up indicator) into the given status.
+pub trait FpuFloat: Copy + PartialOrd + std::fmt::Debug {
+    fn from_f64(x: f64) -> Self;
+    fn from_f32(x: f32) -> Self;
+    fn from_i64(x: i64) -> Self;
+    fn from_m80(bytes: [u8; 10]) -> Self;
+    fn to_m80(self) -> [u8; 10];
+
+    /// Convert to f64 with round to nearest, for transcendental functions and display.
+    fn to_f64(self) -> f64;
+    /// Convert for storing to an m64fp.
+    fn round_f64(self, ctl: Control, status: &mut Status) -> f64;
+    /// Convert for storing to an m32fp.
+    fn round_f32(self, ctl: Control, status: &mut Status) -> f32;
+    /// Convert to an integer, honoring the rounding mode.
+    /// Returns None for NaNs and values out of i64 range.
+    fn round_i64(self, ctl: Control, status: &mut Status) -> Option<i64>;
+
+    fn class(self) -> Class;
+    /// True if the sign bit is set.
+    fn sign(self) -> bool;
+    fn fchs(self) -> Self;
+    fn fabs(self) -> Self;
+
+    fn fadd(self, y: Self, ctl: Control, status: &mut Status) -> Self;
+    fn fsub(self, y: Self, ctl: Control, status: &mut Status) -> Self {
+        self.fadd(y.fchs(), ctl, status)
+    }
+    fn fmul(self, y: Self, ctl: Control, status: &mut Status) -> Self;
+    fn fdiv(self, y: Self, ctl: Control, status: &mut Status) -> Self;
+    fn fsqrt(self, ctl: Control, status: &mut Status) -> Self;
+    fn frndint(self, ctl: Control, status: &mut Status) -> Self;
+    /// Multiply by 2^n.
+    fn fscale(self, n: i32, ctl: Control, status: &mut Status) -> Self;
+    /// fprem (nearest=false) and fprem1 (nearest=true).
+    /// Returns the partial remainder, the low bits of the quotient, and whether the
+    /// reduction is complete.
+    fn fprem(self, y: Self, nearest: bool, status: &mut Status) -> (Self, u64, bool);
+}
+
+/// The representation of values on the FPU stack.
+#[cfg(not(feature = "f80"))]
+pub type Float = f64;
+#[cfg(feature = "f80")]
+pub type Float = crate::f80::F80;
+
+/// Round a finite f64 to an integer-valued f64 per the rounding mode.
+fn round_f64_int(x: f64, rounding: Rounding) -> f64 {
+    match rounding {
+        Rounding::Nearest => x.round_ties_even(),
+        Rounding::Down => x.floor(),
+        Rounding::Up => x.ceil(),
+        Rounding::Zero => x.trunc(),
+    }
+}
+
+/// Best-effort exception flags for the f64 representation, derived from the operands
+/// and result rather than tracked through the computation.
+fn check_f64(result: f64, x: f64, y: f64, status: &mut Status) -> f64 {
+    if result.is_nan() && !x.is_nan() && !y.is_nan() {
+        status.insert(Status::IE);
+    } else if result.is_infinite() && x.is_finite() && y.is_finite() {
+        status.insert(Status::OE | Status::PE);
     }
+    result
 }
 
+impl FpuFloat for f64 {
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+    fn from_f32(x: f32) -> Self {
+        x as f64
+    }
+    fn from_i64(x: i64) -> Self {
+        x as f64
+    }
+    fn from_m80(bytes: [u8; 10]) -> Self {
+        extended::Extended::from_le_bytes(bytes).to_f64()
+    }
+    fn to_m80(self) -> [u8; 10] {
+        extended::Extended::from(self).to_le_bytes()
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn round_f64(self, _ctl: Control, _status: &mut Status) -> f64 {
+        self
+    }
+    fn round_f32(self, _ctl: Control, _status: &mut Status) -> f32 {
+        self as f32
+    }
+    fn round_i64(self, ctl: Control, status: &mut Status) -> Option<i64> {
+        if !self.is_finite() {
+            return None;
+        }
+        let r = round_f64_int(self, ctl.rounding());
+        if r != self {
+            status.insert(Status::PE);
+        }
+        // i64::MIN (-2^63) is exactly representable as f64, while i64::MAX is not.
+        let limit = -(i64::MIN as f64);
+        if r >= limit || r < -limit {
+            return None;
+        }
+        Some(r as i64)
+    }
+
+    fn class(self) -> Class {
+        match self.classify() {
+            std::num::FpCategory::Nan => Class::NaN,
+            std::num::FpCategory::Infinite => Class::Infinity,
+            std::num::FpCategory::Zero => Class::Zero,
+            // f64 subnormals are normal numbers in the FPU's format.
+            std::num::FpCategory::Subnormal | std::num::FpCategory::Normal => Class::Normal,
+        }
+    }
+    fn sign(self) -> bool {
+        self.is_sign_negative()
+    }
+    fn fchs(self) -> Self {
+        -self
+    }
+    fn fabs(self) -> Self {
+        self.abs()
+    }
+
+    fn fadd(self, y: Self, _ctl: Control, status: &mut Status) -> Self {
+        check_f64(self + y, self, y, status)
+    }
+    fn fsub(self, y: Self, _ctl: Control, status: &mut Status) -> Self {
+        check_f64(self - y, self, y, status)
+    }
+    fn fmul(self, y: Self, _ctl: Control, status: &mut Status) -> Self {
+        check_f64(self * y, self, y, status)
+    }
+    fn fdiv(self, y: Self, _ctl: Control, status: &mut Status) -> Self {
+        if y == 0.0 && self.is_finite() && self != 0.0 {
+            status.insert(Status::ZE);
+            return self / y;
+        }
+        check_f64(self / y, self, y, status)
+    }
+    fn fsqrt(self, _ctl: Control, status: &mut Status) -> Self {
+        check_f64(self.sqrt(), self, 0.0, status)
+    }
+    fn frndint(self, ctl: Control, status: &mut Status) -> Self {
+        if !self.is_finite() {
+            return self;
+        }
+        let r = round_f64_int(self, ctl.rounding());
+        if r != self {
+            status.insert(Status::PE);
+        }
+        r
+    }
+    fn fscale(self, n: i32, _ctl: Control, status: &mut Status) -> Self {
+        check_f64(self * 2f64.powi(n), self, 0.0, status)
+    }
+    fn fprem(self, y: Self, nearest: bool, status: &mut Status) -> (Self, u64, bool) {
+        if self.is_nan() || y.is_nan() {
+            return (self + y, 0, true);
+        }
+        if self.is_infinite() || y == 0.0 {
+            status.insert(Status::IE);
+            return (f64::NAN, 0, true);
+        }
+        let q = (self / y).trunc();
+        let mut r = self % y;
+        let mut q = q.abs() as u64;
+        if nearest {
+            let half = y.abs() / 2.0;
+            if r.abs() > half || (r.abs() == half && q & 1 == 1) {
+                r -= y.abs().copysign(r);
+                q = q.wrapping_add(1);
+            }
+        }
+        (r, q, true)
+    }
+}
+
+#[derive(Clone)]
 pub struct FPU {
     /// FPU ST0 through ST7 registers.
-    pub st: [f64; 8],
+    pub st: [Float; 8],
     /// Index of top of FPU stack; 8 when stack empty.
     pub st_top: usize,
     /// FPU status word, without st_top included.
     pub status: Status,
+    pub control: Control,
 }
 
 impl Default for FPU {
     fn default() -> Self {
         Self {
-            st: [0.; 8],
+            st: [Float::from_f64(0.); 8],
             st_top: 8,
             status: Status::empty(),
+            control: Control::WINDOWS,
         }
     }
 }
 
 impl FPU {
-    fn exception(_msg: &str) {
-        // TODO: modify state bits etc.
-        // At least ignoring these may allow programs to make some progress.
+    fn exception(&mut self, _msg: &str) {
+        // Exceptions are always handled as if masked: we record the flag and continue.
         // See note in https://github.com/joncampbell123/dosbox-x/issues/94 ,
         // "I've seen DOSBox SVN bail out on perfectly good demoscene programs because
         // of [not allowing underflow]."
@@ -41,14 +318,31 @@ impl FPU {
         // log::warn!("{}", msg);
     }
 
+    /// Record a stack overflow (push onto a full stack) or underflow (read of an empty register).
+    fn stack_fault(&mut self, overflow: bool, msg: &str) {
+        self.status.insert(Status::IE | Status::SF);
+        self.status.set(Status::C1, overflow);
+        self.exception(msg);
+    }
+
+    pub fn reset(&mut self) {
+        self.st_top = 8;
+        self.status = Status::empty();
+        self.control = Control::FINIT;
+    }
+
     /// Get st(0), the current top of the FPU stack.
-    pub fn st0(&mut self) -> &mut f64 {
+    pub fn st0(&mut self) -> &mut Float {
+        if self.st_top == 8 {
+            self.stack_fault(false, "fpu stack underflow");
+            return &mut self.st[7];
+        }
         &mut self.st[self.st_top]
     }
 
-    pub fn push(&mut self, val: f64) {
+    pub fn push(&mut self, val: Float) {
         if self.st_top == 0 {
-            Self::exception("fpu stack overflow");
+            self.stack_fault(true, "fpu stack overflow");
             return;
         }
         self.st_top -= 1;
@@ -57,14 +351,14 @@ impl FPU {
 
     pub fn pop(&mut self) {
         if self.st_top == 8 {
-            Self::exception("fpu stack underflow");
+            self.stack_fault(false, "fpu stack underflow");
             return;
         }
         self.st_top += 1;
     }
 
     /// Index in self.st for a given ST0, ST1 etc reg.
-    fn st_offset(&self, reg: iced_x86::Register) -> usize {
+    fn st_offset(&mut self, reg: iced_x86::Register) -> usize {
         let ofs = match reg {
             iced_x86::Register::ST0 => 0,
             iced_x86::Register::ST1 => 1,
@@ -78,7 +372,7 @@ impl FPU {
         };
         let new = self.st_top + ofs;
         if new >= 8 {
-            Self::exception("fpu stack underflow");
+            self.stack_fault(false, "fpu stack underflow");
             return 7;
         }
         new
@@ -90,14 +384,60 @@ impl FPU {
         self.st.swap(o1, o2);
     }
 
-    pub fn get(&mut self, reg: iced_x86::Register) -> &mut f64 {
-        &mut self.st[self.st_offset(reg)]
+    pub fn get(&mut self, reg: iced_x86::Register) -> &mut Float {
+        let ofs = self.st_offset(reg);
+        &mut self.st[ofs]
+    }
+
+    /// Classify st(0) as fxam does, which includes reporting an empty stack.
+    pub fn class(&self) -> Class {
+        if self.st_top == 8 {
+            return Class::Empty;
+        }
+        self.st[self.st_top].class()
     }
 
     pub fn status(&self) -> u16 {
         // Our status register impl doesn't include st_top so include it here.
         let mut status = self.status.bits();
         status |= (self.st_top as u16 & 0b111) << 11;
+        // Error summary (ES, and B which mirrors it) is set when there are unmasked exceptions.
+        let unmasked = self.status.bits() & !self.control.0 & Status::EXCEPTIONS.bits();
+        if unmasked != 0 {
+            status |= 1 << 7 | 1 << 15;
+        }
         status
     }
+
+    /// The tag word, two bits per physical register:
+    /// 00 valid, 01 zero, 10 special (NaN, infinity, denormal), 11 empty.
+    pub fn tag_word(&self) -> u16 {
+        let mut tags = 0;
+        for (i, value) in self.st.iter().enumerate() {
+            let tag = if i < self.st_top {
+                0b11
+            } else {
+                match value.class() {
+                    Class::Normal => 0b00,
+                    Class::Zero => 0b01,
+                    _ => 0b10,
+                }
+            };
+            tags |= tag << (i * 2);
+        }
+        tags
+    }
+
+    /// Load the status and tag words, as from fldenv.
+    /// We model the stack as running from st_top to the last register, so the tags only
+    /// tell us whether that stack is empty.
+    pub fn load_env(&mut self, status: u16, tags: u16) {
+        self.status = Status::from_bits_truncate(status);
+        let top = ((status >> 11) & 0b111) as usize;
+        self.st_top = if (tags >> (top * 2)) & 0b11 == 0b11 {
+            8
+        } else {
+            top
+        };
+    }
 }
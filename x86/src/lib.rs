@@ -1,10 +1,14 @@
 pub mod debug;
+#[cfg(feature = "f80")]
+mod f80;
 mod fpu;
 mod icache;
 pub mod ops;
 mod registers;
 mod x86;
 
-pub use crate::x86::{CPUState, CPU, X86};
+pub use crate::x86::{CPUState, Exception, CPU, X86};
+pub use fpu::{Float, FpuFloat};
 pub use iced_x86::Register;
 pub use ops::set_edx_eax;
+pub use registers::Flags;
@@ -1,6 +1,10 @@
-use crate::{registers::Flags, x86::CPU, CPUState};
+use crate::{
+    registers::Flags,
+    x86::{Exception, CPU},
+    CPUState,
+};
 use iced_x86::{Instruction, Register};
-use memory::{Extensions, ExtensionsMut, Mem};
+use memory::Mem;
 
 use super::helpers::*;
 
@@ -13,8 +17,13 @@ pub fn enterd_imm16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn leaved(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
-    cpu.regs.set32(Register::ESP, cpu.regs.get32(Register::EBP));
-    let ebp = pop(cpu, mem);
+    // Read before modifying esp, in case it faults.
+    let esp = cpu.regs.get32(Register::EBP);
+    let ebp = read::<u32>(cpu, mem, esp);
+    if faulted(cpu) {
+        return;
+    }
+    cpu.regs.set32(Register::ESP, esp.wrapping_add(4));
     cpu.regs.set32(Register::EBP, ebp);
 }
 
@@ -58,16 +67,35 @@ pub fn popd_r16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     cpu.regs.set16(instr.op0_register(), value as u16);
 }
 
+/// Pop to memory.  The destination address is computed with esp after the pop, but
+/// esp is only updated once the write succeeds, in case it faults.
+fn pop_mem<T: Clone + memory::Pod + Default>(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let esp = cpu.regs.get32(Register::ESP);
+    let value = read::<T>(cpu, mem, esp);
+    let new_esp = esp.wrapping_add(std::mem::size_of::<T>() as u32);
+    cpu.regs.set32(Register::ESP, new_esp);
+    let addr = x86_addr(cpu, instr);
+    cpu.regs.set32(Register::ESP, esp);
+    write::<T>(cpu, mem, addr, value);
+    if !faulted(cpu) {
+        cpu.regs.set32(Register::ESP, new_esp);
+    }
+}
+
 pub fn pop_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    if instr.op0_kind() == iced_x86::OpKind::Memory {
+        return pop_mem::<u32>(cpu, mem, instr);
+    }
     let value = pop(cpu, mem);
-    let x = rm32(cpu, mem, instr);
-    x.set(value);
+    cpu.regs.set32(instr.op0_register(), value);
 }
 
 pub fn pop_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    if instr.op0_kind() == iced_x86::OpKind::Memory {
+        return pop_mem::<u16>(cpu, mem, instr);
+    }
     let value = pop16(cpu, mem);
-    let x = rm16(cpu, mem, instr);
-    x.set(value);
+    cpu.regs.set16(instr.op0_register(), value);
 }
 
 pub fn mov_rm32_imm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -122,7 +150,7 @@ pub fn mov_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 
 pub fn mov_moffs8_al(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let addr = x86_addr(cpu, instr);
-    mem.put_pod::<u8>(addr, cpu.regs.get8(Register::AL));
+    write::<u8>(cpu, mem, addr, cpu.regs.get8(Register::AL));
 }
 
 pub fn mov_r32m16_sreg(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -132,7 +160,7 @@ pub fn mov_r32m16_sreg(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
         iced_x86::OpKind::Register => cpu.regs.set32(instr.op0_register(), y as u32),
         iced_x86::OpKind::Memory => {
             let addr = x86_addr(cpu, instr);
-            mem.put_pod::<u16>(addr, y)
+            write::<u16>(cpu, mem, addr, y)
         }
         _ => unimplemented!(),
     }
@@ -143,7 +171,7 @@ pub fn mov_sreg_r32m16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     // TODO: this is supposed to do segment selector validation stuff.
     let y = match instr.op1_kind() {
         iced_x86::OpKind::Register => cpu.regs.get32(instr.op1_register()) as u16,
-        iced_x86::OpKind::Memory => mem.get_pod::<u16>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Memory => read::<u16>(cpu, mem, x86_addr(cpu, instr)),
         _ => unimplemented!(),
     };
     cpu.regs.set16(instr.op0_register(), y);
@@ -234,10 +262,10 @@ pub fn cmpxchg_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
         iced_x86::OpKind::Register => todo!(),
         iced_x86::OpKind::Memory => {
             let addr = x86_addr(cpu, instr);
-            let x = mem.get_pod::<u32>(addr);
+            let x = read::<u32>(cpu, mem, addr);
             if cpu.regs.get32(Register::EAX) == x {
                 cpu.flags.insert(Flags::ZF);
-                mem.put_pod::<u32>(addr, y);
+                write::<u32>(cpu, mem, addr, y);
             } else {
                 cpu.flags.remove(Flags::ZF);
                 cpu.regs.set32(Register::EAX, y);
@@ -249,13 +277,13 @@ pub fn cmpxchg_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 
 pub fn cmpxchg8b_m64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let addr = x86_addr(cpu, instr);
-    let m64 = mem.get_pod::<u64>(addr);
+    let m64 = read::<u64>(cpu, mem, addr);
     let test = get_edx_eax(cpu);
     if test == m64 {
         cpu.flags.insert(Flags::ZF);
         let val =
             ((cpu.regs.get32(Register::ECX) as u64) << 32) | (cpu.regs.get32(Register::EBX) as u64);
-        mem.put_pod::<u64>(addr, val);
+        write::<u64>(cpu, mem, addr, val);
     } else {
         cpu.flags.remove(Flags::ZF);
         set_edx_eax(cpu, m64);
@@ -350,6 +378,10 @@ pub fn sets_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 
 pub fn pushad(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
     let esp = cpu.regs.get32(Register::ESP); // get before any pushes
+                                             // Check the whole range first, so a fault doesn't leave it partly pushed.
+    if !check_addr::<[u32; 8]>(cpu, mem, esp.wrapping_sub(32), true) {
+        return;
+    }
 
     push(cpu, mem, cpu.regs.get32(Register::EAX));
     push(cpu, mem, cpu.regs.get32(Register::ECX));
@@ -362,6 +394,9 @@ pub fn pushad(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
 }
 
 pub fn popad(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
+    if !check_addr::<[u32; 8]>(cpu, mem, cpu.regs.get32(Register::ESP), false) {
+        return;
+    }
     let edi = pop(cpu, mem);
     cpu.regs.set32(Register::EDI, edi);
     let esi = pop(cpu, mem);
@@ -460,8 +495,7 @@ pub fn int3(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
 }
 
 pub fn ud2(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    cpu.err("ud: undefined instruction".into());
-    cpu.regs.eip -= 2;
+    cpu.fault(Exception::InvalidOpcode);
 }
 
 pub fn sysenter(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
@@ -482,7 +516,8 @@ pub fn bswap_r32(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
 
 pub fn xlat_m8(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
     let addr = cpu.regs.get32(Register::EBX) + (cpu.regs.get8(Register::AL) as u32);
-    cpu.regs.set8(Register::AL, mem.get_pod::<u8>(addr));
+    let value = read::<u8>(cpu, mem, addr);
+    cpu.regs.set8(Register::AL, value);
 }
 
 pub fn bts_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
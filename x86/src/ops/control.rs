@@ -85,10 +85,16 @@ pub fn jns(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn jp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    // Note: the decoder ensures we only see a jp directly after a sahf,
-    // so we know the PF flag directly depends on ax.
-    let pf = ((cpu.regs.get32(Register::EAX) >> 10) & 1) == 1;
-    if pf {
+    // Note: the decoder ensures we only see a jp directly after an instruction
+    // that sets PF (sahf or comiss and friends), as we don't track PF elsewhere.
+    if cpu.flags.contains(Flags::PF) {
+        cpu.jmp(mem, instr.near_branch32());
+    }
+}
+
+pub fn jnp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    // See note in jp.
+    if !cpu.flags.contains(Flags::PF) {
         cpu.jmp(mem, instr.near_branch32());
     }
 }
@@ -6,6 +6,9 @@ use memory::Mem;
 bitflags! {
     pub struct EDXFeatures: u32 {
         const MMX = 1 << 23;
+        const FXSR = 1 << 24;
+        const SSE = 1 << 25;
+        const SSE2 = 1 << 26;
     }
 }
 
@@ -22,9 +25,15 @@ pub fn cpuid(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
         1 => {
             // CPUID_GETFEATURES
             // Just enough to convince heaven7 that we support MMX.
+            #[allow(unused_mut)]
+            let mut features = EDXFeatures::MMX;
+            #[cfg(feature = "sse")]
+            {
+                features |= EDXFeatures::FXSR | EDXFeatures::SSE | EDXFeatures::SSE2;
+            }
             cpu.regs.set32(Register::EAX, 0);
             cpu.regs.set32(Register::ECX, 0);
-            cpu.regs.set32(Register::EDX, EDXFeatures::MMX.bits());
+            cpu.regs.set32(Register::EDX, features.bits());
         }
         0x8000_0000 => {
             // maximum extended function
@@ -1,16 +1,74 @@
 use super::helpers::*;
-use crate::{fpu, registers::Flags, x86::CPU};
-use extended::Extended;
+use crate::{
+    fpu::{self, Control, Float, FpuFloat, Status},
+    registers::Flags,
+    x86::CPU,
+};
 use iced_x86::{Instruction, Register};
-use memory::{Extensions, ExtensionsMut, Mem};
+use memory::{Mem, Pod};
+
+/// A binary arithmetic operation, like FpuFloat::fadd.
+type BinOp = fn(Float, Float, Control, &mut Status) -> Float;
+
+fn fsubr(x: Float, y: Float, ctl: Control, status: &mut Status) -> Float {
+    y.fsub(x, ctl, status)
+}
+
+fn fdivr(x: Float, y: Float, ctl: Control, status: &mut Status) -> Float {
+    y.fdiv(x, ctl, status)
+}
+
+/// Compute reg = op(reg, y).
+fn arith(cpu: &mut CPU, reg: Register, y: Float, op: BinOp) {
+    let x = *cpu.fpu.get(reg);
+    let value = op(x, y, cpu.fpu.control, &mut cpu.fpu.status);
+    *cpu.fpu.get(reg) = value;
+}
+
+/// Compute op0 = op(op0, op1) for two stack registers.
+fn arith_sti(cpu: &mut CPU, instr: &Instruction, op: BinOp) {
+    let y = *cpu.fpu.get(instr.op1_register());
+    arith(cpu, instr.op0_register(), y, op);
+}
+
+/// Apply a transcendental function, which we compute in f64 regardless of representation.
+fn arith_f64(cpu: &mut CPU, f: impl FnOnce(f64) -> f64) {
+    let x = cpu.fpu.st0();
+    *x = Float::from_f64(f(x.to_f64()));
+}
+
+fn m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Float {
+    Float::from_f64(read::<f64>(cpu, mem, x86_addr(cpu, instr)))
+}
+
+fn m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Float {
+    Float::from_f32(read::<f32>(cpu, mem, x86_addr(cpu, instr)))
+}
+
+fn m64int(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Float {
+    Float::from_i64(read::<i64>(cpu, mem, x86_addr(cpu, instr)))
+}
+
+fn m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Float {
+    Float::from_i64(read::<i32>(cpu, mem, x86_addr(cpu, instr)) as i64)
+}
+
+fn m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Float {
+    Float::from_i64(read::<i16>(cpu, mem, x86_addr(cpu, instr)) as i64)
+}
 
 pub fn finit(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    cpu.fpu.st_top = 8;
-    cpu.fpu.status = fpu::Status::empty();
+    cpu.fpu.reset();
+}
+
+pub fn fnclex(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    cpu.fpu.status.remove(Status::EXCEPTIONS);
 }
 
 /// Compare two values and set floating-point comparison flags.
-fn fcom<T: std::cmp::PartialOrd>(cpu: &mut CPU, x: T, y: T) {
+/// Unordered comparisons are an invalid operation unless quiet (fucom and friends).
+fn fcom(cpu: &mut CPU, x: Float, y: Float, quiet: bool) {
+    cpu.fpu.status.set(fpu::Status::C1, false);
     if x > y {
         cpu.fpu.status.set(fpu::Status::C3, false);
         cpu.fpu.status.set(fpu::Status::C2, false);
@@ -27,23 +85,38 @@ fn fcom<T: std::cmp::PartialOrd>(cpu: &mut CPU, x: T, y: T) {
         cpu.fpu.status.set(fpu::Status::C3, true);
         cpu.fpu.status.set(fpu::Status::C2, true);
         cpu.fpu.status.set(fpu::Status::C0, true);
+        if !quiet {
+            cpu.fpu.status.insert(Status::IE);
+        }
     };
 }
 
+// These are the values of the x87's internal constants rounded to nearest,
+// as (sign/exponent, significand).
+const PI: (u16, u64) = (0x4000, 0xC90F_DAA2_2168_C235);
+const L2E: (u16, u64) = (0x3FFF, 0xB8AA_3B29_5C17_F0BC);
+
+fn constant((se, mant): (u16, u64)) -> Float {
+    let mut bytes = [0u8; 10];
+    bytes[..8].copy_from_slice(&mant.to_le_bytes());
+    bytes[8..].copy_from_slice(&se.to_le_bytes());
+    Float::from_m80(bytes)
+}
+
 pub fn fld1(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    cpu.fpu.push(1.0);
+    cpu.fpu.push(Float::from_f64(1.0));
 }
 
 pub fn fldz(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    cpu.fpu.push(0.0);
+    cpu.fpu.push(Float::from_f64(0.0));
 }
 
 pub fn fldpi(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    cpu.fpu.push(std::f64::consts::PI);
+    cpu.fpu.push(constant(PI));
 }
 
 pub fn fldl2e(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    cpu.fpu.push(std::f64::consts::LOG2_E);
+    cpu.fpu.push(constant(L2E));
 }
 
 pub fn fld_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
@@ -52,42 +125,93 @@ pub fn fld_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
 }
 
 pub fn fld_m80fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x_bytes = mem.get_pod::<[u8; 10]>(x86_addr(cpu, instr));
-    cpu.fpu.push(Extended::from_le_bytes(x_bytes).to_f64());
+    let x_bytes = read::<[u8; 10]>(cpu, mem, x86_addr(cpu, instr));
+    cpu.fpu.push(Float::from_m80(x_bytes));
 }
 
 pub fn fld_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu.push(mem.get_pod::<f64>(x86_addr(cpu, instr)));
+    let x = m64fp(cpu, mem, instr);
+    cpu.fpu.push(x);
 }
 
 pub fn fld_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu
-        .push(mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64);
+    let x = m32fp(cpu, mem, instr);
+    cpu.fpu.push(x);
 }
 
 pub fn fild_m64int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu
-        .push(mem.get_pod::<i64>(x86_addr(cpu, instr)) as f64);
+    let x = m64int(cpu, mem, instr);
+    cpu.fpu.push(x);
 }
 
 pub fn fild_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu
-        .push(mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64);
+    let x = m32int(cpu, mem, instr);
+    cpu.fpu.push(x);
 }
 
 pub fn fild_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu
-        .push(mem.get_pod::<i16>(x86_addr(cpu, instr)) as f64);
+    let x = m16int(cpu, mem, instr);
+    cpu.fpu.push(x);
+}
+
+pub fn fbld_m80bcd(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let bytes = read::<[u8; 10]>(cpu, mem, x86_addr(cpu, instr));
+    // 18 packed BCD digits, least significant byte first, then a sign byte.
+    let mut value = 0i64;
+    for &b in bytes[..9].iter().rev() {
+        value = value * 100 + ((b >> 4) * 10 + (b & 0xF)) as i64;
+    }
+    let mut x = Float::from_i64(value);
+    if bytes[9] & 0x80 != 0 {
+        x = x.fchs();
+    }
+    cpu.fpu.push(x);
+}
+
+pub fn fbstp_m80bcd(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    let value = x
+        .round_i64(cpu.fpu.control, &mut cpu.fpu.status)
+        .filter(|v| v.unsigned_abs() < 1_000_000_000_000_000_000);
+    let bytes = match value {
+        Some(value) => {
+            let mut bytes = [0u8; 10];
+            let mut digits = value.unsigned_abs();
+            for b in &mut bytes[..9] {
+                *b = (((digits / 10 % 10) << 4) | (digits % 10)) as u8;
+                digits /= 100;
+            }
+            if x.sign() {
+                bytes[9] = 0x80;
+            }
+            bytes
+        }
+        None => {
+            cpu.fpu.status.insert(Status::IE);
+            // packed BCD indefinite
+            [0, 0, 0, 0, 0, 0, 0, 0xC0, 0xFF, 0xFF]
+        }
+    };
+    write::<[u8; 10]>(cpu, mem, x86_addr(cpu, instr), bytes);
+    cpu.fpu.pop();
 }
 
 pub fn fst_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let f = *cpu.fpu.st0();
-    mem.put_pod::<f64>(x86_addr(cpu, instr), f);
+    let x = *cpu.fpu.st0();
+    let f = x.round_f64(cpu.fpu.control, &mut cpu.fpu.status);
+    write::<f64>(cpu, mem, x86_addr(cpu, instr), f);
 }
 
 pub fn fst_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let f = *cpu.fpu.st0();
-    mem.put_pod::<f32>(x86_addr(cpu, instr), f as f32);
+    let x = *cpu.fpu.st0();
+    let f = x.round_f32(cpu.fpu.control, &mut cpu.fpu.status);
+    write::<f32>(cpu, mem, x86_addr(cpu, instr), f);
+}
+
+pub fn fstp_m80fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    write::<[u8; 10]>(cpu, mem, x86_addr(cpu, instr), x.to_m80());
+    cpu.fpu.pop();
 }
 
 pub fn fstp_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -106,15 +230,27 @@ pub fn fstp_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     cpu.fpu.pop();
 }
 
+/// Store st(0) as an integer, rounding per the control word.
+/// Out of range values store the "integer indefinite" value, which is T::MIN.
+fn fist<T: Clone + Pod + TryFrom<i64>>(cpu: &mut CPU, mem: Mem, instr: &Instruction, min: T) {
+    let x = *cpu.fpu.st0();
+    let value = x
+        .round_i64(cpu.fpu.control, &mut cpu.fpu.status)
+        .and_then(|v| T::try_from(v).ok());
+    let value = value.unwrap_or_else(|| {
+        cpu.fpu.status.insert(Status::IE);
+        min
+    });
+    write::<T>(cpu, mem, x86_addr(cpu, instr), value);
+}
+
 pub fn fistp_m64int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let f = *cpu.fpu.st0();
-    mem.put_pod::<i64>(x86_addr(cpu, instr), f as i64);
+    fist::<i64>(cpu, mem, instr, i64::MIN);
     cpu.fpu.pop();
 }
 
 pub fn fist_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let f = *cpu.fpu.st0();
-    mem.put_pod::<i32>(x86_addr(cpu, instr), f as i32);
+    fist::<i32>(cpu, mem, instr, i32::MIN);
 }
 
 pub fn fistp_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -122,52 +258,50 @@ pub fn fistp_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     cpu.fpu.pop();
 }
 
+pub fn fist_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    fist::<i16>(cpu, mem, instr, i16::MIN);
+}
+
 pub fn fistp_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let f = *cpu.fpu.st0();
-    mem.put_pod::<i16>(x86_addr(cpu, instr), f as i16);
+    fist_m16int(cpu, mem, instr);
     cpu.fpu.pop();
 }
 
 pub fn fchs(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    *cpu.fpu.st0() = -*cpu.fpu.st0();
+    *cpu.fpu.st0() = cpu.fpu.st0().fchs();
 }
 
 pub fn fabs(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    *cpu.fpu.st0() = cpu.fpu.st0().abs();
+    *cpu.fpu.st0() = cpu.fpu.st0().fabs();
 }
 
 pub fn fcos(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let reg = cpu.fpu.st0();
-    *reg = reg.cos();
+    arith_f64(cpu, f64::cos);
 }
 
 pub fn fsin(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let reg = cpu.fpu.st0();
-    *reg = reg.sin();
+    arith_f64(cpu, f64::sin);
 }
 
 pub fn fsincos(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let val = *cpu.fpu.st0();
-    *cpu.fpu.st0() = val.sin();
-    cpu.fpu.push(val.cos());
+    let val = cpu.fpu.st0().to_f64();
+    *cpu.fpu.st0() = Float::from_f64(val.sin());
+    cpu.fpu.push(Float::from_f64(val.cos()));
 }
 
 pub fn fpatan(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let x = *cpu.fpu.st0();
+    let x = cpu.fpu.st0().to_f64();
     cpu.fpu.pop();
-    let reg = cpu.fpu.st0();
-    *reg = reg.atan2(x);
+    arith_f64(cpu, |y| y.atan2(x));
 }
 
 pub fn fsqrt(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let reg = cpu.fpu.st0();
-    *reg = reg.sqrt();
+    let x = *cpu.fpu.st0();
+    *cpu.fpu.st0() = x.fsqrt(cpu.fpu.control, &mut cpu.fpu.status);
 }
 
 pub fn fadd_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
-    let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x += y;
+    arith_sti(cpu, instr, Float::fadd);
 }
 
 pub fn faddp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -176,41 +310,37 @@ pub fn faddp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn fadd_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    *cpu.fpu.st0() += y;
+    let y = m64fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fadd);
 }
 
 pub fn fadd_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() += y;
+    let y = m32fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fadd);
 }
 
 pub fn fiadd_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() += y;
+    let y = m32int(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fadd);
 }
 
 pub fn fiadd_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i16>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() += y;
+    let y = m16int(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fadd);
 }
 
 pub fn fsub_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    let x = cpu.fpu.st0();
-    *x -= y;
+    let y = m64fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fsub);
 }
 
 pub fn fsub_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x -= y;
+    let y = m32fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fsub);
 }
 
 pub fn fsub_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
-    let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x -= y;
+    arith_sti(cpu, instr, Float::fsub);
 }
 
 pub fn fsubp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -219,27 +349,22 @@ pub fn fsubp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn fisub_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x = *x - y;
+    let y = m32int(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fsub);
 }
 
 pub fn fsubr_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    let x = cpu.fpu.st0();
-    *x = y - *x;
+    let y = m64fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, fsubr);
 }
 
 pub fn fsubr_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x = y - *x;
+    let y = m32fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, fsubr);
 }
 
 pub fn fsubr_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
-    let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x = y - *x;
+    arith_sti(cpu, instr, fsubr);
 }
 
 pub fn fsubrp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -248,29 +373,27 @@ pub fn fsubrp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn fmul_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    *cpu.fpu.st0() *= y;
+    let y = m64fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fmul);
 }
 
 pub fn fmul_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() *= y;
+    let y = m32fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fmul);
 }
 
 pub fn fimul_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() *= y;
+    let y = m32int(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fmul);
 }
 
 pub fn fimul_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i16>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() *= y;
+    let y = m16int(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fmul);
 }
 
 pub fn fmul_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
-    let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x *= y;
+    arith_sti(cpu, instr, Float::fmul);
 }
 
 pub fn fmulp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -279,37 +402,34 @@ pub fn fmulp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn f2xm1(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let x = cpu.fpu.st0();
-    *x = 2.0_f64.powf(*x) - 1.0;
+    arith_f64(cpu, |x| 2.0_f64.powf(x) - 1.0);
 }
 
 pub fn fyl2x(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let x = *cpu.fpu.st0();
+    let x = cpu.fpu.st0().to_f64();
     cpu.fpu.pop();
-    let y = cpu.fpu.st0();
-    *y = *y * x.log2();
+    arith_f64(cpu, |y| y * x.log2());
 }
 
 pub fn fscale(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let y = *cpu.fpu.get(iced_x86::Register::ST1);
-    let x = cpu.fpu.st0();
-    *x = *x * 2f64.powf(y.round());
+    // Truncated toward zero; the conversion saturates for huge values.
+    let n = cpu.fpu.get(iced_x86::Register::ST1).to_f64().trunc() as i32;
+    let x = *cpu.fpu.st0();
+    *cpu.fpu.st0() = x.fscale(n, cpu.fpu.control, &mut cpu.fpu.status);
 }
 
 pub fn fdiv_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    *cpu.fpu.st0() /= y;
+    let y = m64fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fdiv);
 }
 
 pub fn fdiv_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() /= y;
+    let y = m32fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fdiv);
 }
 
 pub fn fdiv_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
-    let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x = *x / y;
+    arith_sti(cpu, instr, Float::fdiv);
 }
 
 pub fn fdivp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -318,31 +438,27 @@ pub fn fdivp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn fidiv_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() /= y;
+    let y = m32int(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fdiv);
 }
 
 pub fn fidiv_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i16>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() /= y;
+    let y = m16int(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, Float::fdiv);
 }
 
 pub fn fdivr_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    let x = cpu.fpu.st0();
-    *x = y / *x;
+    let y = m64fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, fdivr);
 }
 
 pub fn fdivr_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x = y / *x;
+    let y = m32fp(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, fdivr);
 }
 
 pub fn fdivr_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
-    let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x = y / *x;
+    arith_sti(cpu, instr, fdivr);
 }
 
 pub fn fdivrp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -351,36 +467,62 @@ pub fn fdivrp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn fidivr_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x = y / *x;
+    let y = m32int(cpu, mem, instr);
+    arith(cpu, Register::ST0, y, fdivr);
 }
 
-pub fn fprem(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+fn fprem_common(cpu: &mut CPU, nearest: bool) {
     let y = *cpu.fpu.get(iced_x86::Register::ST1);
-    let x = cpu.fpu.st0();
-    *x = *x % y;
+    let x = *cpu.fpu.st0();
+    let (r, q, complete) = x.fprem(y, nearest, &mut cpu.fpu.status);
+    *cpu.fpu.st0() = r;
+    // C2 is set while reduction is incomplete; once complete,
+    // C0, C3, C1 hold the low three bits of the quotient.
+    let status = &mut cpu.fpu.status;
+    status.set(fpu::Status::C2, !complete);
+    status.set(fpu::Status::C0, complete && q & 0b100 != 0);
+    status.set(fpu::Status::C3, complete && q & 0b010 != 0);
+    status.set(fpu::Status::C1, complete && q & 0b001 != 0);
+}
+
+pub fn fprem(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    fprem_common(cpu, false);
+}
+
+pub fn fprem1(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    fprem_common(cpu, true);
 }
 
 pub fn fxch_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     cpu.fpu.swap(instr.op0_register(), instr.op1_register());
 }
 
+pub fn fxam(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let class = cpu.fpu.class() as u16;
+    // The sign is reported even for empty registers.
+    let sign = cpu.fpu.st[cpu.fpu.st_top & 7].sign();
+    let status = &mut cpu.fpu.status;
+    status.set(fpu::Status::C3, class & 0b100 != 0);
+    status.set(fpu::Status::C2, class & 0b010 != 0);
+    status.set(fpu::Status::C0, class & 0b001 != 0);
+    status.set(fpu::Status::C1, sign);
+}
+
 pub fn ftst(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
     let x = *cpu.fpu.st0();
-    fcom(cpu, x, 0.0);
+    fcom(cpu, x, Float::from_f64(0.0), false);
 }
 
 pub fn fcom_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = *cpu.fpu.st0();
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    fcom(cpu, x, y);
+    let y = m64fp(cpu, mem, instr);
+    fcom(cpu, x, y, false);
 }
 
 pub fn fcom_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = *cpu.fpu.st0();
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    fcom(cpu, x, y);
+    let y = m32fp(cpu, mem, instr);
+    fcom(cpu, x, y, false);
 }
 
 pub fn fcomp_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -396,45 +538,60 @@ pub fn fcomp_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 pub fn fcomp_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let x = *cpu.fpu.st0();
     let y = *cpu.fpu.get(instr.op1_register());
-    fcom(cpu, x, y);
+    fcom(cpu, x, y, false);
     cpu.fpu.pop();
 }
 
 pub fn fcompp(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
     let x = *cpu.fpu.st0();
     let y = *cpu.fpu.get(iced_x86::Register::ST1);
-    fcom(cpu, x, y);
+    fcom(cpu, x, y, false);
     cpu.fpu.pop();
     cpu.fpu.pop();
 }
 
-pub fn fucomp_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    fcomp_st0_sti(cpu, mem, instr);
-    // TODO: raise the invalid-arithmetic-operand exception when appropriate.
+pub fn fucomp_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    let y = *cpu.fpu.get(instr.op1_register());
+    fcom(cpu, x, y, true);
+    cpu.fpu.pop();
 }
 
-pub fn fcomi_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+fn fcomi(cpu: &mut CPU, instr: &Instruction, quiet: bool) {
     let x = *cpu.fpu.st0();
     let y = *cpu.fpu.get(instr.op1_register());
-    //cpu.flags.set(Flags::PF, false);
+    cpu.flags.set(Flags::PF, false);
     if x > y {
         cpu.flags.set(Flags::ZF, false);
         cpu.flags.set(Flags::CF, false);
     } else if x < y {
         cpu.flags.set(Flags::ZF, false);
         cpu.flags.set(Flags::CF, true);
-    } else {
+    } else if x == y {
         cpu.flags.set(Flags::ZF, true);
         cpu.flags.set(Flags::CF, false);
+    } else {
+        cpu.flags.insert(Flags::ZF | Flags::PF | Flags::CF);
+        if !quiet {
+            cpu.fpu.status.insert(Status::IE);
+        }
     }
 
     cpu.flags.set(Flags::OF, false);
     cpu.flags.set(Flags::SF, false);
 }
 
-pub fn fucomi_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+pub fn fcomi_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    fcomi(cpu, instr, false);
+}
+
+pub fn fcomip_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     fcomi_st0_sti(cpu, mem, instr);
-    // TODO: raise the invalid-arithmetic-operand exception when appropriate.
+    cpu.fpu.pop();
+}
+
+pub fn fucomi_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    fcomi(cpu, instr, true);
 }
 
 pub fn fucomip_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -443,29 +600,81 @@ pub fn fucomip_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn frndint(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let x = cpu.fpu.st0();
-    *x = x.round();
+    let x = *cpu.fpu.st0();
+    *cpu.fpu.st0() = x.frndint(cpu.fpu.control, &mut cpu.fpu.status);
 }
 
 pub fn fnstsw_ax(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    cpu.regs.set32(Register::EAX, cpu.fpu.status() as u32);
+    cpu.regs.set16(Register::AX, cpu.fpu.status());
 }
 
 pub fn fnstsw_m2byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let addr = x86_addr(cpu, instr);
-    mem.put_pod::<u16>(addr, cpu.fpu.status() as u16);
+    write::<u16>(cpu, mem, addr, cpu.fpu.status());
 }
 
 pub fn fnstcw_m2byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    // TODO: control word
-    let cw = 0x37u16; // default value
     let addr = x86_addr(cpu, instr);
-    mem.put_pod::<u16>(addr, cw);
+    write::<u16>(cpu, mem, addr, cpu.fpu.control.0);
 }
 
 pub fn fldcw_m2byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    // TODO: control word
-    mem.get_pod::<u16>(x86_addr(cpu, instr));
+    cpu.fpu.control = Control(read::<u16>(cpu, mem, x86_addr(cpu, instr)));
+}
+
+/// Size of the 32-bit protected mode FPU environment, as used by fnstenv/fnsave.
+const ENV_SIZE: u32 = 28;
+
+fn store_env(cpu: &mut CPU, mem: Mem, addr: u32) {
+    // Reserved upper halves read as ones on real hardware.
+    // We don't track the last instruction/operand pointers, so those are zero.
+    write::<u32>(cpu, mem, addr, 0xFFFF_0000 | cpu.fpu.control.0 as u32);
+    write::<u32>(cpu, mem, addr + 4, 0xFFFF_0000 | cpu.fpu.status() as u32);
+    write::<u32>(cpu, mem, addr + 8, 0xFFFF_0000 | cpu.fpu.tag_word() as u32);
+    for ofs in (12..ENV_SIZE).step_by(4) {
+        write::<u32>(cpu, mem, addr + ofs, 0);
+    }
+}
+
+fn load_env(cpu: &mut CPU, mem: Mem, addr: u32) {
+    cpu.fpu.control = Control(read::<u16>(cpu, mem, addr));
+    let status = read::<u16>(cpu, mem, addr + 4);
+    let tags = read::<u16>(cpu, mem, addr + 8);
+    cpu.fpu.load_env(status, tags);
+}
+
+pub fn fnstenv_m28byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let addr = x86_addr(cpu, instr);
+    store_env(cpu, mem, addr);
+    // fnstenv also masks all exceptions.
+    cpu.fpu.control.0 |= Status::EXCEPTIONS.bits() & 0x3F;
+}
+
+pub fn fldenv_m28byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let addr = x86_addr(cpu, instr);
+    load_env(cpu, mem, addr);
+}
+
+pub fn fnsave_m108byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let addr = x86_addr(cpu, instr);
+    store_env(cpu, mem, addr);
+    // Registers follow in stack order, st(0) first.
+    let top = cpu.fpu.st_top & 7;
+    for i in 0..8 {
+        let bytes = cpu.fpu.st[(top + i) & 7].to_m80();
+        write::<[u8; 10]>(cpu, mem, addr + ENV_SIZE + i as u32 * 10, bytes);
+    }
+    cpu.fpu.reset();
+}
+
+pub fn frstor_m108byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let addr = x86_addr(cpu, instr);
+    load_env(cpu, mem, addr);
+    let top = cpu.fpu.st_top & 7;
+    for i in 0..8 {
+        let bytes = read::<[u8; 10]>(cpu, mem, addr + ENV_SIZE + i as u32 * 10);
+        cpu.fpu.st[(top + i) & 7] = Float::from_m80(bytes);
+    }
 }
 
 pub fn fcmovnbe_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
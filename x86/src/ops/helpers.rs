@@ -1,7 +1,46 @@
 //! Functions for common behaviors across all operations.
 
 use crate::{x86::CPU, Register};
-use memory::{Extensions, ExtensionsMut, Mem};
+use memory::{Extensions, ExtensionsMut, Mem, Pod};
+
+/// Whether an access of a T at addr falls within memory.
+fn in_bounds<T>(mem: Mem, addr: u32) -> bool {
+    addr.checked_add(std::mem::size_of::<T>() as u32)
+        .is_some_and(|end| end <= mem.len())
+}
+
+/// Check that an access of a T at addr falls within memory, raising an access
+/// violation if not.  The faulting instruction still runs to completion, after which
+/// the CPU restores the registers as of the fault, so callers need only avoid touching
+/// memory and may produce any value.  Instructions must not modify registers before
+/// an access that may fault.
+pub fn check_addr<T>(cpu: &mut CPU, mem: Mem, addr: u32, write: bool) -> bool {
+    let ok = in_bounds::<T>(mem, addr);
+    if !ok {
+        cpu.access_violation(addr, write);
+    }
+    ok
+}
+
+/// Whether the current instruction has faulted.
+pub fn faulted(cpu: &CPU) -> bool {
+    matches!(cpu.state, crate::CPUState::Exception(_))
+}
+
+/// Read a T from x86 memory.  Out of bounds reads fault and return zero.
+pub fn read<T: Clone + Pod + Default>(cpu: &mut CPU, mem: Mem, addr: u32) -> T {
+    if !check_addr::<T>(cpu, mem, addr, false) {
+        return T::default();
+    }
+    mem.get_pod::<T>(addr)
+}
+
+/// Write a T to x86 memory.  Out of bounds writes fault and are dropped.
+pub fn write<T: Clone + Pod>(cpu: &mut CPU, mem: Mem, addr: u32, value: T) {
+    if check_addr::<T>(cpu, mem, addr, true) {
+        mem.put_pod::<T>(addr, value);
+    }
+}
 
 // TODO: maybe there are no 64-bit memory reads needed (?)
 pub fn rm64_x(
@@ -19,9 +58,33 @@ pub fn rm64_x(
         }
         iced_x86::OpKind::Memory => {
             let addr = x86_addr(cpu, instr);
-            let x = mem.get_pod::<u64>(addr);
+            let x = read::<u64>(cpu, mem, addr);
+            let value = op(cpu, x);
+            write::<u64>(cpu, mem, addr, value);
+        }
+        _ => unimplemented!(),
+    }
+}
+
+#[cfg(feature = "sse")]
+pub fn rm128_x(
+    cpu: &mut CPU,
+    mem: Mem,
+    instr: &iced_x86::Instruction,
+    op: impl FnOnce(&mut CPU, u128) -> u128,
+) {
+    match instr.op0_kind() {
+        iced_x86::OpKind::Register => {
+            let reg = instr.op0_register();
+            let x = cpu.regs.get128(reg);
+            let value = op(cpu, x);
+            cpu.regs.set128(reg, value);
+        }
+        iced_x86::OpKind::Memory => {
+            let addr = x86_addr(cpu, instr);
+            let x = read::<u128>(cpu, mem, addr);
             let value = op(cpu, x);
-            mem.put_pod::<u64>(addr, value);
+            write::<u128>(cpu, mem, addr, value);
         }
         _ => unimplemented!(),
     }
@@ -38,7 +101,25 @@ pub fn rm64_x(
 /// about lifetimes anyway.
 pub struct Arg<T>(*mut T);
 
-impl<T> Arg<T> {
+impl<T: Pod> Arg<T> {
+    /// Point at a T in x86 memory.  Out of bounds accesses fault and instead use a
+    /// scratch location in the CPU, whose contents are discarded.
+    fn mem(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction, addr: u32) -> Self {
+        if in_bounds::<T>(mem, addr) {
+            return Arg(mem.get_ptr_mut::<T>(addr));
+        }
+        // The instruction determines whether the operand is written.
+        let write = !matches!(
+            iced_x86::InstructionInfoFactory::new()
+                .info(instr)
+                .op0_access(),
+            iced_x86::OpAccess::Read | iced_x86::OpAccess::CondRead
+        );
+        cpu.access_violation(addr, write);
+        const { assert!(std::mem::size_of::<T>() <= std::mem::size_of::<u32>()) };
+        Arg(&mut cpu.fault_sink as *mut u32 as *mut T)
+    }
+
     pub fn get(&self) -> T {
         unsafe { std::ptr::read_unaligned(self.0) }
     }
@@ -56,7 +137,7 @@ pub fn rm32<'a>(cpu: &'a mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Ar
         }
         iced_x86::OpKind::Memory => {
             let addr = x86_addr(cpu, instr);
-            Arg(mem.get_ptr_mut::<u32>(addr))
+            Arg::mem(cpu, mem, instr, addr)
         }
         _ => unimplemented!(),
     }
@@ -70,7 +151,7 @@ pub fn rm16<'a>(cpu: &'a mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Ar
         }
         iced_x86::OpKind::Memory => {
             let addr = x86_addr(cpu, instr);
-            Arg(mem.get_ptr_mut::<u16>(addr))
+            Arg::mem(cpu, mem, instr, addr)
         }
         _ => unimplemented!(),
     }
@@ -83,12 +164,8 @@ pub fn rm8<'a>(cpu: &'a mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Arg
             Arg(cpu.regs.get8_mut(reg))
         }
         iced_x86::OpKind::Memory => {
-            let mut addr = x86_addr(cpu, instr);
-            if mem.is_oob::<u8>(addr) {
-                cpu.err(format!("oob at {addr:x}"));
-                addr = 0;
-            }
-            Arg(mem.get_ptr_mut::<u8>(addr))
+            let addr = x86_addr(cpu, instr);
+            Arg::mem(cpu, mem, instr, addr)
         }
         _ => unimplemented!(),
     }
@@ -99,11 +176,7 @@ pub fn op1_rm32(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u32 {
         iced_x86::OpKind::Register => cpu.regs.get32(instr.op1_register()),
         iced_x86::OpKind::Memory => {
             let addr = x86_addr(cpu, instr);
-            if mem.is_oob::<u32>(addr) {
-                cpu.err(format!("oob at {addr:x}"));
-                return 0;
-            }
-            mem.get_pod::<u32>(addr)
+            read::<u32>(cpu, mem, addr)
         }
         _ => unreachable!(),
     }
@@ -112,7 +185,10 @@ pub fn op1_rm32(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u32 {
 pub fn op1_rm16(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u16 {
     match instr.op1_kind() {
         iced_x86::OpKind::Register => cpu.regs.get16(instr.op1_register()),
-        iced_x86::OpKind::Memory => mem.get_pod::<u16>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Memory => {
+            let addr = x86_addr(cpu, instr);
+            read::<u16>(cpu, mem, addr)
+        }
         _ => unreachable!(),
     }
 }
@@ -120,39 +196,54 @@ pub fn op1_rm16(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u16 {
 pub fn op1_rm8(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u8 {
     match instr.op1_kind() {
         iced_x86::OpKind::Register => cpu.regs.get8(instr.op1_register()),
-        iced_x86::OpKind::Memory => mem.get_pod::<u8>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Memory => {
+            let addr = x86_addr(cpu, instr);
+            read::<u8>(cpu, mem, addr)
+        }
         _ => unreachable!(),
     }
 }
 
 /// Push a u32 on the x86 stack.
 pub fn push(cpu: &mut CPU, mem: Mem, value: u32) {
-    let esp = cpu.regs.get32_mut(Register::ESP);
-    *esp -= 4;
-    mem.put_pod::<u32>(*esp, value);
+    push_pod(cpu, mem, value);
 }
 
 /// Push a u16 on the x86 stack.
 pub fn push16(cpu: &mut CPU, mem: Mem, value: u16) {
-    let esp = cpu.regs.get32_mut(Register::ESP);
-    *esp -= 2;
-    mem.put_pod::<u16>(*esp, value);
+    push_pod(cpu, mem, value);
 }
 
 /// Pop a u32 from the x86 stack.
 pub fn pop(cpu: &mut CPU, mem: Mem) -> u32 {
-    let esp = cpu.regs.get32_mut(Register::ESP);
-    let value = mem.get_pod::<u32>(*esp);
-    *esp += 4;
-    value
+    pop_pod(cpu, mem)
 }
 
 /// Pop a u16 from the x86 stack.
 pub fn pop16(cpu: &mut CPU, mem: Mem) -> u16 {
-    let esp = cpu.regs.get32_mut(Register::ESP);
-    let value = mem.get_pod::<u16>(*esp);
-    *esp += 2;
-    value
+    pop_pod(cpu, mem)
+}
+
+/// esp is only updated if the access succeeds, so a faulting push/pop can be retried.
+fn push_pod<T: Clone + Pod>(cpu: &mut CPU, mem: Mem, value: T) {
+    let esp = cpu
+        .regs
+        .get32(Register::ESP)
+        .wrapping_sub(std::mem::size_of::<T>() as u32);
+    if check_addr::<T>(cpu, mem, esp, true) {
+        mem.put_pod::<T>(esp, value);
+        cpu.regs.set32(Register::ESP, esp);
+    }
+}
+
+fn pop_pod<T: Clone + Pod + Default>(cpu: &mut CPU, mem: Mem) -> T {
+    let esp = cpu.regs.get32(Register::ESP);
+    if !check_addr::<T>(cpu, mem, esp, false) {
+        return T::default();
+    }
+    cpu.regs
+        .set32(Register::ESP, esp + std::mem::size_of::<T>() as u32);
+    mem.get_pod::<T>(esp)
 }
 
 /// Compute the address found in instructions that reference memory, e.g.
@@ -1,5 +1,8 @@
 use super::helpers::*;
-use crate::{registers::Flags, x86::CPU};
+use crate::{
+    registers::Flags,
+    x86::{Exception, CPU},
+};
 use iced_x86::{Instruction, Register};
 use memory::Mem;
 use num_traits::{
@@ -863,16 +866,30 @@ pub fn imul_r16_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 pub fn idiv_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = get_edx_eax(cpu) as i64;
     let y = rm32(cpu, mem, instr).get() as i32 as i64;
-    cpu.regs.set32(Register::EAX, (x / y) as i32 as u32);
+    if y == 0 {
+        cpu.fault(Exception::DivideError);
+        return;
+    }
+    let quotient = x / y;
+    if quotient > i32::MAX as i64 || quotient < i32::MIN as i64 {
+        cpu.fault(Exception::DivideError);
+        return;
+    }
+    cpu.regs.set32(Register::EAX, quotient as i32 as u32);
     cpu.regs.set32(Register::EDX, (x % y) as i32 as u32);
 }
 
 pub fn idiv_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = get_dx_ax(cpu) as i32;
     let y = rm16(cpu, mem, instr).get() as i16 as i32;
+    if y == 0 {
+        cpu.fault(Exception::DivideError);
+        return;
+    }
     let quotient = x / y;
     if quotient > 0x7FFF || quotient < -0x8000 {
-        panic!("divide error");
+        cpu.fault(Exception::DivideError);
+        return;
     }
     cpu.regs.set16(Register::AX, quotient as i16 as u16);
     cpu.regs.set16(Register::DX, (x % y) as u16);
@@ -881,9 +898,14 @@ pub fn idiv_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 pub fn idiv_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = cpu.regs.get16(Register::AX) as i16;
     let y = rm8(cpu, mem, instr).get() as i8 as i16;
+    if y == 0 {
+        cpu.fault(Exception::DivideError);
+        return;
+    }
     let quotient = x / y;
     if quotient > 0x7F || quotient < -0x80 {
-        panic!("divide error");
+        cpu.fault(Exception::DivideError);
+        return;
     }
     let rem = x % y;
     cpu.regs
@@ -893,6 +915,10 @@ pub fn idiv_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 pub fn div_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = get_edx_eax(cpu);
     let y = rm32(cpu, mem, instr).get() as u64;
+    if y == 0 || x / y > u32::MAX as u64 {
+        cpu.fault(Exception::DivideError);
+        return;
+    }
     cpu.regs.set32(Register::EAX, (x / y) as u32);
     cpu.regs.set32(Register::EDX, (x % y) as u32);
     // No flags.
@@ -901,6 +927,10 @@ pub fn div_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 pub fn div_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = get_dx_ax(cpu);
     let y = rm16(cpu, mem, instr).get() as u32;
+    if y == 0 || x / y > u16::MAX as u32 {
+        cpu.fault(Exception::DivideError);
+        return;
+    }
     cpu.regs.set32(Register::EAX, ((x / y) as u16) as u32);
     cpu.regs.set32(Register::EDX, ((x % y) as u16) as u32);
     // No flags.
@@ -909,6 +939,10 @@ pub fn div_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 pub fn div_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = cpu.regs.get16(Register::AX);
     let y = rm8(cpu, mem, instr).get() as u16;
+    if y == 0 || x / y > u8::MAX as u16 {
+        cpu.fault(Exception::DivideError);
+        return;
+    }
     cpu.regs
         .set32(Register::EAX, (((x % y) as u32) << 16) | ((x / y) as u32));
     // No flags.
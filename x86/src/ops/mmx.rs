@@ -1,12 +1,12 @@
 use super::helpers::*;
 use crate::CPU;
 use iced_x86::Instruction;
-use memory::{Extensions, Mem};
+use memory::Mem;
 
 fn op1_mmm64(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u64 {
     match instr.op1_kind() {
         iced_x86::OpKind::Register => cpu.regs.get64(instr.op1_register()),
-        iced_x86::OpKind::Memory => mem.get_pod::<u64>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Memory => read::<u64>(cpu, mem, x86_addr(cpu, instr)),
         _ => unreachable!(),
     }
 }
@@ -14,7 +14,7 @@ fn op1_mmm64(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u64 {
 fn op1_mmm32(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u32 {
     match instr.op1_kind() {
         iced_x86::OpKind::Register => cpu.regs.get64(instr.op1_register()) as u32,
-        iced_x86::OpKind::Memory => mem.get_pod::<u32>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Memory => read::<u32>(cpu, mem, x86_addr(cpu, instr)),
         _ => unreachable!(),
     }
 }
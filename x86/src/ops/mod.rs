@@ -5,6 +5,8 @@ mod fpu;
 mod helpers;
 mod math;
 mod mmx;
+#[cfg(feature = "sse")]
+mod sse;
 mod string;
 mod table;
 mod test;
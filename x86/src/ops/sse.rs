@@ -0,0 +1,1106 @@
+//! SSE and SSE2 operations.
+//!
+//! SSE floating point exceptions are always treated as masked, and only the rounding
+//! control bits of MXCSR are obeyed.
+
+use super::helpers::*;
+use crate::{registers::Flags, CPU};
+use iced_x86::{Instruction, OpKind};
+use memory::Mem;
+
+fn op1_xmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> u128 {
+    match instr.op1_kind() {
+        OpKind::Register => cpu.regs.get128(instr.op1_register()),
+        OpKind::Memory => read::<u128>(cpu, mem, x86_addr(cpu, instr)),
+        _ => unreachable!(),
+    }
+}
+
+fn op1_xmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> u64 {
+    match instr.op1_kind() {
+        OpKind::Register => cpu.regs.get128(instr.op1_register()) as u64,
+        OpKind::Memory => read::<u64>(cpu, mem, x86_addr(cpu, instr)),
+        _ => unreachable!(),
+    }
+}
+
+fn op1_xmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> u32 {
+    match instr.op1_kind() {
+        OpKind::Register => cpu.regs.get128(instr.op1_register()) as u32,
+        OpKind::Memory => read::<u32>(cpu, mem, x86_addr(cpu, instr)),
+        _ => unreachable!(),
+    }
+}
+
+/// A value packed into an XMM register.
+trait Lane: Copy {
+    const SIZE: usize;
+    fn read(bytes: &[u8]) -> Self;
+    fn write(self, bytes: &mut [u8]);
+}
+
+macro_rules! lane {
+    ($($t:ty),*) => {$(
+        impl Lane for $t {
+            const SIZE: usize = std::mem::size_of::<$t>();
+            fn read(bytes: &[u8]) -> Self {
+                <$t>::from_le_bytes(bytes[..Self::SIZE].try_into().unwrap())
+            }
+            fn write(self, bytes: &mut [u8]) {
+                bytes[..Self::SIZE].copy_from_slice(&self.to_le_bytes());
+            }
+        }
+    )*};
+}
+lane!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
+fn unpack<T: Lane, const N: usize>(x: u128) -> [T; N] {
+    let bytes = x.to_le_bytes();
+    std::array::from_fn(|i| T::read(&bytes[i * T::SIZE..]))
+}
+
+fn pack<T: Lane, const N: usize>(lanes: [T; N]) -> u128 {
+    let mut bytes = [0u8; 16];
+    for (i, lane) in lanes.into_iter().enumerate() {
+        lane.write(&mut bytes[i * T::SIZE..]);
+    }
+    u128::from_le_bytes(bytes)
+}
+
+/// Apply f lane-wise to x and y.
+fn map2<T: Lane, const N: usize>(x: u128, y: u128, f: impl Fn(T, T) -> T) -> u128 {
+    let x: [T; N] = unpack(x);
+    let y: [T; N] = unpack(y);
+    pack::<T, N>(std::array::from_fn(|i| f(x[i], y[i])))
+}
+
+/// Apply f lane-wise to x.
+fn map1<T: Lane, const N: usize>(x: u128, f: impl Fn(T) -> T) -> u128 {
+    let x: [T; N] = unpack(x);
+    pack::<T, N>(std::array::from_fn(|i| f(x[i])))
+}
+
+/// Binary op on packed operands: xmm op= xmm/m128.
+fn packed<T: Lane, const N: usize>(
+    cpu: &mut CPU,
+    mem: Mem,
+    instr: &Instruction,
+    f: impl Fn(T, T) -> T,
+) {
+    let y = op1_xmm128(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| map2::<T, N>(x, y, f));
+}
+
+/// Binary op on the low f32 lane: xmm op= xmm/m32, leaving upper lanes untouched.
+fn scalar_f32(cpu: &mut CPU, mem: Mem, instr: &Instruction, f: impl Fn(f32, f32) -> f32) {
+    let y = f32::from_bits(op1_xmm32(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let value = f(f32::from_bits(x as u32), y);
+        (x & !(u32::MAX as u128)) | value.to_bits() as u128
+    });
+}
+
+/// Binary op on the low f64 lane: xmm op= xmm/m64, leaving the upper lane untouched.
+fn scalar_f64(cpu: &mut CPU, mem: Mem, instr: &Instruction, f: impl Fn(f64, f64) -> f64) {
+    let y = f64::from_bits(op1_xmm64(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let value = f(f64::from_bits(x as u64), y);
+        (x & !(u64::MAX as u128)) | value.to_bits() as u128
+    });
+}
+
+// SSE min/max return the second operand if either is NaN or both are zero.
+fn min<T: PartialOrd>(x: T, y: T) -> T {
+    if x < y {
+        x
+    } else {
+        y
+    }
+}
+fn max<T: PartialOrd>(x: T, y: T) -> T {
+    if x > y {
+        x
+    } else {
+        y
+    }
+}
+
+// Moves.
+
+/// movaps/movups/movdqa etc: full 128-bit move, to or from memory.
+pub fn mov_xmmm128_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm128(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, _x| y);
+}
+
+pub fn movss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm32(cpu, mem, instr) as u128;
+    rm128_x(cpu, mem, instr, |_cpu, x| match instr.op1_kind() {
+        OpKind::Register => (x & !(u32::MAX as u128)) | y,
+        _ => y, // loads from memory clear the upper lanes
+    });
+}
+
+pub fn movss_xmmm32_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u32;
+    match instr.op0_kind() {
+        OpKind::Memory => write::<u32>(cpu, mem, x86_addr(cpu, instr), y),
+        _ => rm128_x(cpu, mem, instr, |_cpu, x| {
+            (x & !(u32::MAX as u128)) | y as u128
+        }),
+    }
+}
+
+pub fn movsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm64(cpu, mem, instr) as u128;
+    rm128_x(cpu, mem, instr, |_cpu, x| match instr.op1_kind() {
+        OpKind::Register => (x & !(u64::MAX as u128)) | y,
+        _ => y, // loads from memory clear the upper lane
+    });
+}
+
+pub fn movsd_xmmm64_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u64;
+    match instr.op0_kind() {
+        OpKind::Memory => write::<u64>(cpu, mem, x86_addr(cpu, instr), y),
+        _ => rm128_x(cpu, mem, instr, |_cpu, x| {
+            (x & !(u64::MAX as u128)) | y as u128
+        }),
+    }
+}
+
+pub fn movq_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm64(cpu, mem, instr) as u128; // zero extend
+    rm128_x(cpu, mem, instr, |_cpu, _x| y);
+}
+
+pub fn movq_xmmm64_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u64;
+    match instr.op0_kind() {
+        OpKind::Memory => write::<u64>(cpu, mem, x86_addr(cpu, instr), y),
+        _ => rm128_x(cpu, mem, instr, |_cpu, _x| y as u128),
+    }
+}
+
+pub fn movd_xmm_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_rm32(cpu, mem, instr) as u128; // zero extend
+    rm128_x(cpu, mem, instr, |_cpu, _x| y);
+}
+
+pub fn movd_rm32_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u32;
+    let x = rm32(cpu, mem, instr);
+    x.set(y);
+}
+
+/// movlps/movlpd xmm,m64: replace the low 64 bits.
+pub fn movlps_xmm_m64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm64(cpu, mem, instr) as u128;
+    rm128_x(cpu, mem, instr, |_cpu, x| (x & !(u64::MAX as u128)) | y);
+}
+
+/// movhps/movhpd xmm,m64: replace the high 64 bits.
+pub fn movhps_xmm_m64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm64(cpu, mem, instr) as u128;
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        (x & u64::MAX as u128) | (y << 64)
+    });
+}
+
+/// movlps/movlpd m64,xmm: store the low 64 bits.
+pub fn movlps_m64_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u64;
+    write::<u64>(cpu, mem, x86_addr(cpu, instr), y);
+}
+
+/// movhps/movhpd m64,xmm: store the high 64 bits.
+pub fn movhps_m64_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = (cpu.regs.get128(instr.op1_register()) >> 64) as u64;
+    write::<u64>(cpu, mem, x86_addr(cpu, instr), y);
+}
+
+pub fn movhlps_xmm_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm128(cpu, mem, instr) >> 64;
+    rm128_x(cpu, mem, instr, |_cpu, x| (x & !(u64::MAX as u128)) | y);
+}
+
+pub fn movlhps_xmm_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm128(cpu, mem, instr) << 64;
+    rm128_x(cpu, mem, instr, |_cpu, x| (x & u64::MAX as u128) | y);
+}
+
+pub fn movmskps_r32_xmm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y: [u32; 4] = unpack(cpu.regs.get128(instr.op1_register()));
+    let mask = (0..4).fold(0, |mask, i| mask | ((y[i] >> 31) << i));
+    cpu.regs.set32(instr.op0_register(), mask);
+}
+
+pub fn movmskpd_r32_xmm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y: [u64; 2] = unpack(cpu.regs.get128(instr.op1_register()));
+    let mask = (0..2).fold(0, |mask, i| mask | ((y[i] >> 63) << i)) as u32;
+    cpu.regs.set32(instr.op0_register(), mask);
+}
+
+pub fn pmovmskb_r32_xmm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y: [u8; 16] = unpack(cpu.regs.get128(instr.op1_register()));
+    let mask = (0..16).fold(0, |mask, i| mask | (((y[i] >> 7) as u32) << i));
+    cpu.regs.set32(instr.op0_register(), mask);
+}
+
+// Packed/scalar float arithmetic.
+
+pub fn addps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f32, 4>(cpu, mem, instr, |x, y| x + y);
+}
+pub fn addpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f64, 2>(cpu, mem, instr, |x, y| x + y);
+}
+pub fn addss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f32(cpu, mem, instr, |x, y| x + y);
+}
+pub fn addsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f64(cpu, mem, instr, |x, y| x + y);
+}
+
+pub fn subps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f32, 4>(cpu, mem, instr, |x, y| x - y);
+}
+pub fn subpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f64, 2>(cpu, mem, instr, |x, y| x - y);
+}
+pub fn subss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f32(cpu, mem, instr, |x, y| x - y);
+}
+pub fn subsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f64(cpu, mem, instr, |x, y| x - y);
+}
+
+pub fn mulps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f32, 4>(cpu, mem, instr, |x, y| x * y);
+}
+pub fn mulpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f64, 2>(cpu, mem, instr, |x, y| x * y);
+}
+pub fn mulss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f32(cpu, mem, instr, |x, y| x * y);
+}
+pub fn mulsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f64(cpu, mem, instr, |x, y| x * y);
+}
+
+pub fn divps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f32, 4>(cpu, mem, instr, |x, y| x / y);
+}
+pub fn divpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f64, 2>(cpu, mem, instr, |x, y| x / y);
+}
+pub fn divss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f32(cpu, mem, instr, |x, y| x / y);
+}
+pub fn divsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f64(cpu, mem, instr, |x, y| x / y);
+}
+
+pub fn minps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f32, 4>(cpu, mem, instr, min);
+}
+pub fn minpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f64, 2>(cpu, mem, instr, min);
+}
+pub fn minss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f32(cpu, mem, instr, min);
+}
+pub fn minsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f64(cpu, mem, instr, min);
+}
+
+pub fn maxps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f32, 4>(cpu, mem, instr, max);
+}
+pub fn maxpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f64, 2>(cpu, mem, instr, max);
+}
+pub fn maxss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f32(cpu, mem, instr, max);
+}
+pub fn maxsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f64(cpu, mem, instr, max);
+}
+
+pub fn sqrtps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f32, 4>(cpu, mem, instr, |_x, y| y.sqrt());
+}
+pub fn sqrtpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f64, 2>(cpu, mem, instr, |_x, y| y.sqrt());
+}
+pub fn sqrtss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f32(cpu, mem, instr, |_x, y| y.sqrt());
+}
+pub fn sqrtsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f64(cpu, mem, instr, |_x, y| y.sqrt());
+}
+
+// rcp/rsqrt are approximations on real hardware; exact results are within spec.
+pub fn rcpps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f32, 4>(cpu, mem, instr, |_x, y| 1.0 / y);
+}
+pub fn rcpss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f32(cpu, mem, instr, |_x, y| 1.0 / y);
+}
+pub fn rsqrtps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<f32, 4>(cpu, mem, instr, |_x, y| 1.0 / y.sqrt());
+}
+pub fn rsqrtss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_f32(cpu, mem, instr, |_x, y| 1.0 / y.sqrt());
+}
+
+// Bitwise ops, shared by the ps/pd/integer variants.
+
+pub fn and_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm128(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| x & y);
+}
+pub fn andn_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm128(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| !x & y);
+}
+pub fn or_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm128(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| x | y);
+}
+pub fn xor_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm128(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| x ^ y);
+}
+
+// Comparisons.
+
+/// Set ZF/PF/CF as comiss/ucomiss do.  We don't distinguish the two since
+/// we don't raise floating point exceptions.
+fn comis<T: PartialOrd>(cpu: &mut CPU, x: T, y: T) {
+    cpu.flags
+        .remove(Flags::ZF | Flags::PF | Flags::CF | Flags::OF | Flags::SF);
+    match x.partial_cmp(&y) {
+        None => cpu.flags.insert(Flags::ZF | Flags::PF | Flags::CF),
+        Some(std::cmp::Ordering::Less) => cpu.flags.insert(Flags::CF),
+        Some(std::cmp::Ordering::Equal) => cpu.flags.insert(Flags::ZF),
+        Some(std::cmp::Ordering::Greater) => {}
+    }
+}
+
+pub fn comiss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = f32::from_bits(op1_xmm32(cpu, mem, instr));
+    let x = f32::from_bits(cpu.regs.get128(instr.op0_register()) as u32);
+    comis(cpu, x, y);
+}
+
+pub fn comisd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = f64::from_bits(op1_xmm64(cpu, mem, instr));
+    let x = f64::from_bits(cpu.regs.get128(instr.op0_register()) as u64);
+    comis(cpu, x, y);
+}
+
+/// Evaluate a cmpps-style predicate (imm8 0..=7).
+// The negated predicates are true for unordered (NaN) operands.
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
+fn cmp_predicate<T: PartialOrd>(x: T, y: T, imm: u8) -> bool {
+    let unordered = x.partial_cmp(&y).is_none();
+    match imm & 7 {
+        0 => x == y,
+        1 => x < y,
+        2 => x <= y,
+        3 => unordered,
+        4 => x != y,
+        5 => !(x < y),
+        6 => !(x <= y),
+        7 => !unordered,
+        _ => unreachable!(),
+    }
+}
+
+pub fn cmpps_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    packed::<f32, 4>(cpu, mem, instr, |x, y| {
+        f32::from_bits(if cmp_predicate(x, y, imm) { !0 } else { 0 })
+    });
+}
+pub fn cmppd_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    packed::<f64, 2>(cpu, mem, instr, |x, y| {
+        f64::from_bits(if cmp_predicate(x, y, imm) { !0 } else { 0 })
+    });
+}
+pub fn cmpss_xmm_xmmm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    scalar_f32(cpu, mem, instr, |x, y| {
+        f32::from_bits(if cmp_predicate(x, y, imm) { !0 } else { 0 })
+    });
+}
+pub fn cmpsd_xmm_xmmm64_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    scalar_f64(cpu, mem, instr, |x, y| {
+        f64::from_bits(if cmp_predicate(x, y, imm) { !0 } else { 0 })
+    });
+}
+
+// Shuffles.
+
+pub fn shufps_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    let y: [u32; 4] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let x: [u32; 4] = unpack(x);
+        let sel = |i: usize| (imm >> (i * 2)) as usize & 3;
+        pack([x[sel(0)], x[sel(1)], y[sel(2)], y[sel(3)]])
+    });
+}
+
+pub fn shufpd_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    let y: [u64; 2] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let x: [u64; 2] = unpack(x);
+        pack([x[imm as usize & 1], y[(imm >> 1) as usize & 1]])
+    });
+}
+
+pub fn pshufd_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    let y: [u32; 4] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, _x| {
+        pack::<u32, 4>(std::array::from_fn(|i| y[(imm >> (i * 2)) as usize & 3]))
+    });
+}
+
+pub fn pshuflw_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    let y: [u16; 8] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, _x| {
+        pack::<u16, 8>(std::array::from_fn(|i| match i {
+            0..=3 => y[(imm >> (i * 2)) as usize & 3],
+            _ => y[i],
+        }))
+    });
+}
+
+pub fn pshufhw_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    let y: [u16; 8] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, _x| {
+        pack::<u16, 8>(std::array::from_fn(|i| match i {
+            0..=3 => y[i],
+            _ => y[4 + ((imm >> ((i - 4) * 2)) as usize & 3)],
+        }))
+    });
+}
+
+/// Interleave lanes from the low (or high) halves of x and y.
+fn unpck<T: Lane + Default, const N: usize>(
+    cpu: &mut CPU,
+    mem: Mem,
+    instr: &Instruction,
+    high: bool,
+) {
+    let y: [T; N] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let x: [T; N] = unpack(x);
+        let base = if high { N / 2 } else { 0 };
+        pack::<T, N>(std::array::from_fn(|i| {
+            let src = if i % 2 == 0 { &x } else { &y };
+            src[base + i / 2]
+        }))
+    });
+}
+
+pub fn unpcklps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u32, 4>(cpu, mem, instr, false);
+}
+pub fn unpckhps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u32, 4>(cpu, mem, instr, true);
+}
+pub fn punpcklbw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u8, 16>(cpu, mem, instr, false);
+}
+pub fn punpckhbw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u8, 16>(cpu, mem, instr, true);
+}
+pub fn punpcklwd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u16, 8>(cpu, mem, instr, false);
+}
+pub fn punpckhwd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u16, 8>(cpu, mem, instr, true);
+}
+pub fn punpckldq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u32, 4>(cpu, mem, instr, false);
+}
+pub fn punpckhdq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u32, 4>(cpu, mem, instr, true);
+}
+/// Also unpcklpd.
+pub fn punpcklqdq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u64, 2>(cpu, mem, instr, false);
+}
+/// Also unpckhpd.
+pub fn punpckhqdq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    unpck::<u64, 2>(cpu, mem, instr, true);
+}
+
+// Conversions.
+
+/// Round according to the MXCSR rounding control.
+fn round_mxcsr(cpu: &CPU, x: f64) -> f64 {
+    match (cpu.regs.mxcsr >> 13) & 3 {
+        0 => x.round_ties_even(),
+        1 => x.floor(),
+        2 => x.ceil(),
+        3 => x.trunc(),
+        _ => unreachable!(),
+    }
+}
+
+/// Convert an already-rounded float to i32, producing the "integer indefinite"
+/// value for out of range inputs.
+fn to_i32(x: f64) -> i32 {
+    if x.is_nan() || x < i32::MIN as f64 || x > i32::MAX as f64 {
+        i32::MIN
+    } else {
+        x as i32
+    }
+}
+
+pub fn cvtsi2ss_xmm_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_rm32(cpu, mem, instr) as i32 as f32;
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        (x & !(u32::MAX as u128)) | y.to_bits() as u128
+    });
+}
+
+pub fn cvtsi2sd_xmm_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_rm32(cpu, mem, instr) as i32 as f64;
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        (x & !(u64::MAX as u128)) | y.to_bits() as u128
+    });
+}
+
+pub fn cvtss2sd_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = f32::from_bits(op1_xmm32(cpu, mem, instr)) as f64;
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        (x & !(u64::MAX as u128)) | y.to_bits() as u128
+    });
+}
+
+pub fn cvtsd2ss_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = f64::from_bits(op1_xmm64(cpu, mem, instr)) as f32;
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        (x & !(u32::MAX as u128)) | y.to_bits() as u128
+    });
+}
+
+pub fn cvtss2si_r32_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = f32::from_bits(op1_xmm32(cpu, mem, instr)) as f64;
+    let value = to_i32(round_mxcsr(cpu, y));
+    cpu.regs.set32(instr.op0_register(), value as u32);
+}
+
+pub fn cvttss2si_r32_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = f32::from_bits(op1_xmm32(cpu, mem, instr)) as f64;
+    cpu.regs
+        .set32(instr.op0_register(), to_i32(y.trunc()) as u32);
+}
+
+pub fn cvtsd2si_r32_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = f64::from_bits(op1_xmm64(cpu, mem, instr));
+    let value = to_i32(round_mxcsr(cpu, y));
+    cpu.regs.set32(instr.op0_register(), value as u32);
+}
+
+pub fn cvttsd2si_r32_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = f64::from_bits(op1_xmm64(cpu, mem, instr));
+    cpu.regs
+        .set32(instr.op0_register(), to_i32(y.trunc()) as u32);
+}
+
+pub fn cvtdq2ps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [i32; 4] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, _x| pack(y.map(|y| y as f32)));
+}
+
+pub fn cvtps2dq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [f32; 4] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |cpu, _x| {
+        pack(y.map(|y| to_i32(round_mxcsr(cpu, y as f64))))
+    });
+}
+
+pub fn cvttps2dq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [f32; 4] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, _x| {
+        pack(y.map(|y| to_i32((y as f64).trunc())))
+    });
+}
+
+pub fn cvtdq2pd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm64(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, _x| {
+        pack([y as i32 as f64, (y >> 32) as i32 as f64])
+    });
+}
+
+pub fn cvtpd2dq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [f64; 2] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |cpu, _x| {
+        let [a, b] = y.map(|y| to_i32(round_mxcsr(cpu, y)));
+        pack([a, b, 0, 0])
+    });
+}
+
+pub fn cvttpd2dq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [f64; 2] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, _x| {
+        let [a, b] = y.map(|y| to_i32(y.trunc()));
+        pack([a, b, 0, 0])
+    });
+}
+
+pub fn cvtps2pd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = op1_xmm64(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, _x| {
+        pack([
+            f32::from_bits(y as u32) as f64,
+            f32::from_bits((y >> 32) as u32) as f64,
+        ])
+    });
+}
+
+pub fn cvtpd2ps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [f64; 2] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, _x| {
+        pack([y[0] as f32, y[1] as f32, 0.0, 0.0])
+    });
+}
+
+// Packed integer arithmetic.
+
+pub fn paddb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u8, 16>(cpu, mem, instr, u8::wrapping_add);
+}
+pub fn paddw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u16, 8>(cpu, mem, instr, u16::wrapping_add);
+}
+pub fn paddd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u32, 4>(cpu, mem, instr, u32::wrapping_add);
+}
+pub fn paddq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u64, 2>(cpu, mem, instr, u64::wrapping_add);
+}
+pub fn psubb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u8, 16>(cpu, mem, instr, u8::wrapping_sub);
+}
+pub fn psubw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u16, 8>(cpu, mem, instr, u16::wrapping_sub);
+}
+pub fn psubd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u32, 4>(cpu, mem, instr, u32::wrapping_sub);
+}
+pub fn psubq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u64, 2>(cpu, mem, instr, u64::wrapping_sub);
+}
+pub fn paddusb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u8, 16>(cpu, mem, instr, u8::saturating_add);
+}
+pub fn paddusw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u16, 8>(cpu, mem, instr, u16::saturating_add);
+}
+pub fn paddsb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i8, 16>(cpu, mem, instr, i8::saturating_add);
+}
+pub fn paddsw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i16, 8>(cpu, mem, instr, i16::saturating_add);
+}
+pub fn psubusb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u8, 16>(cpu, mem, instr, u8::saturating_sub);
+}
+pub fn psubusw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u16, 8>(cpu, mem, instr, u16::saturating_sub);
+}
+pub fn psubsb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i8, 16>(cpu, mem, instr, i8::saturating_sub);
+}
+pub fn psubsw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i16, 8>(cpu, mem, instr, i16::saturating_sub);
+}
+
+pub fn pmullw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u16, 8>(cpu, mem, instr, u16::wrapping_mul);
+}
+pub fn pmulhw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i16, 8>(cpu, mem, instr, |x, y| ((x as i32 * y as i32) >> 16) as i16);
+}
+pub fn pmulhuw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u16, 8>(cpu, mem, instr, |x, y| ((x as u32 * y as u32) >> 16) as u16);
+}
+pub fn pmuludq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u64, 2>(cpu, mem, instr, |x, y| {
+        (x as u32 as u64) * (y as u32 as u64)
+    });
+}
+
+pub fn pmaddwd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [i16; 8] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let x: [i16; 8] = unpack(x);
+        pack::<i32, 4>(std::array::from_fn(|i| {
+            let lo = x[i * 2] as i32 * y[i * 2] as i32;
+            let hi = x[i * 2 + 1] as i32 * y[i * 2 + 1] as i32;
+            lo.wrapping_add(hi)
+        }))
+    });
+}
+
+pub fn pminub_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u8, 16>(cpu, mem, instr, std::cmp::min);
+}
+pub fn pmaxub_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u8, 16>(cpu, mem, instr, std::cmp::max);
+}
+pub fn pminsw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i16, 8>(cpu, mem, instr, std::cmp::min);
+}
+pub fn pmaxsw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i16, 8>(cpu, mem, instr, std::cmp::max);
+}
+pub fn pavgb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u8, 16>(cpu, mem, instr, |x, y| {
+        ((x as u16 + y as u16 + 1) >> 1) as u8
+    });
+}
+pub fn pavgw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u16, 8>(cpu, mem, instr, |x, y| {
+        ((x as u32 + y as u32 + 1) >> 1) as u16
+    });
+}
+
+pub fn psadbw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [u8; 16] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let x: [u8; 16] = unpack(x);
+        let sad = |range: std::ops::Range<usize>| -> u64 {
+            range.map(|i| x[i].abs_diff(y[i]) as u64).sum()
+        };
+        pack([sad(0..8), sad(8..16)])
+    });
+}
+
+pub fn pcmpeqb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u8, 16>(cpu, mem, instr, |x, y| if x == y { !0 } else { 0 });
+}
+pub fn pcmpeqw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u16, 8>(cpu, mem, instr, |x, y| if x == y { !0 } else { 0 });
+}
+pub fn pcmpeqd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<u32, 4>(cpu, mem, instr, |x, y| if x == y { !0 } else { 0 });
+}
+pub fn pcmpgtb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i8, 16>(cpu, mem, instr, |x, y| if x > y { !0 } else { 0 });
+}
+pub fn pcmpgtw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i16, 8>(cpu, mem, instr, |x, y| if x > y { !0 } else { 0 });
+}
+pub fn pcmpgtd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed::<i32, 4>(cpu, mem, instr, |x, y| if x > y { !0 } else { 0 });
+}
+
+fn saturate_i8(x: i16) -> i8 {
+    x.clamp(i8::MIN as i16, i8::MAX as i16) as i8
+}
+fn saturate_u8(x: i16) -> u8 {
+    x.clamp(0, u8::MAX as i16) as u8
+}
+fn saturate_i16(x: i32) -> i16 {
+    x.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+}
+
+pub fn packsswb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [i16; 8] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let x: [i16; 8] = unpack(x);
+        pack::<i8, 16>(std::array::from_fn(|i| {
+            saturate_i8(if i < 8 { x[i] } else { y[i - 8] })
+        }))
+    });
+}
+
+pub fn packuswb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [i16; 8] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let x: [i16; 8] = unpack(x);
+        pack::<u8, 16>(std::array::from_fn(|i| {
+            saturate_u8(if i < 8 { x[i] } else { y[i - 8] })
+        }))
+    });
+}
+
+pub fn packssdw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y: [i32; 4] = unpack(op1_xmm128(cpu, mem, instr));
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let x: [i32; 4] = unpack(x);
+        pack::<i16, 8>(std::array::from_fn(|i| {
+            saturate_i16(if i < 4 { x[i] } else { y[i - 4] })
+        }))
+    });
+}
+
+pub fn pextrw_r32_xmm_imm8(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y: [u16; 8] = unpack(cpu.regs.get128(instr.op1_register()));
+    let value = y[instr.immediate8() as usize & 7];
+    cpu.regs.set32(instr.op0_register(), value as u32);
+}
+
+pub fn pinsrw_xmm_r32m16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = match instr.op1_kind() {
+        OpKind::Register => cpu.regs.get32(instr.op1_register()) as u16,
+        _ => read::<u16>(cpu, mem, x86_addr(cpu, instr)),
+    };
+    let index = instr.immediate8() as usize & 7;
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        let mut x: [u16; 8] = unpack(x);
+        x[index] = y;
+        pack(x)
+    });
+}
+
+// Shifts.  The count comes either from an immediate or from the low 64 bits of an
+// xmm/m128 operand; counts past the lane width zero the lane (or fill with sign bits
+// for arithmetic shifts).
+
+fn shift_count(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> u64 {
+    match instr.op1_kind() {
+        OpKind::Immediate8 => instr.immediate8() as u64,
+        _ => op1_xmm128(cpu, mem, instr) as u64,
+    }
+}
+
+pub fn psllw_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        map1::<u16, 8>(x, |x| if count > 15 { 0 } else { x << count })
+    });
+}
+pub fn pslld_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        map1::<u32, 4>(x, |x| if count > 31 { 0 } else { x << count })
+    });
+}
+pub fn psllq_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        map1::<u64, 2>(x, |x| if count > 63 { 0 } else { x << count })
+    });
+}
+pub fn psrlw_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        map1::<u16, 8>(x, |x| if count > 15 { 0 } else { x >> count })
+    });
+}
+pub fn psrld_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        map1::<u32, 4>(x, |x| if count > 31 { 0 } else { x >> count })
+    });
+}
+pub fn psrlq_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, mem, instr);
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        map1::<u64, 2>(x, |x| if count > 63 { 0 } else { x >> count })
+    });
+}
+pub fn psraw_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, mem, instr).min(15);
+    rm128_x(cpu, mem, instr, |_cpu, x| map1::<i16, 8>(x, |x| x >> count));
+}
+pub fn psrad_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, mem, instr).min(31);
+    rm128_x(cpu, mem, instr, |_cpu, x| map1::<i32, 4>(x, |x| x >> count));
+}
+
+pub fn pslldq_xmm_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = instr.immediate8() as u32;
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        x.checked_shl(count * 8).unwrap_or(0)
+    });
+}
+pub fn psrldq_xmm_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = instr.immediate8() as u32;
+    rm128_x(cpu, mem, instr, |_cpu, x| {
+        x.checked_shr(count * 8).unwrap_or(0)
+    });
+}
+
+// Control.
+
+pub fn ldmxcsr_m32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    cpu.regs.mxcsr = read::<u32>(cpu, mem, x86_addr(cpu, instr));
+}
+
+pub fn stmxcsr_m32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    write::<u32>(cpu, mem, x86_addr(cpu, instr), cpu.regs.mxcsr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced_x86::Register::{EAX, XMM0, XMM1};
+
+    /// Decode a single instruction, which must operate only on registers.
+    fn decode(bytes: &[u8]) -> Instruction {
+        let mut decoder = iced_x86::Decoder::new(32, bytes, iced_x86::DecoderOptions::NONE);
+        let instr = decoder.decode();
+        assert!(!instr.is_invalid());
+        instr
+    }
+
+    fn run(cpu: &mut CPU, op: fn(&mut CPU, Mem, &Instruction), bytes: &[u8]) {
+        op(cpu, Mem::from_slice(&[]), &decode(bytes));
+    }
+
+    /// Run a conversion to an integer of xmm0's low lane into eax.
+    fn cvt(cpu: &mut CPU, op: fn(&mut CPU, Mem, &Instruction), bytes: &[u8], x: u128) -> u32 {
+        cpu.regs.set128(XMM0, x);
+        run(cpu, op, bytes);
+        cpu.regs.get32(EAX)
+    }
+
+    #[test]
+    fn cvtt_truncates() {
+        let mut cpu = CPU::default();
+        let cvttsd2si = |cpu: &mut CPU, x: f64| {
+            // cvttsd2si eax, xmm0
+            cvt(
+                cpu,
+                cvttsd2si_r32_xmmm64,
+                &[0xF2, 0x0F, 0x2C, 0xC0],
+                x.to_bits() as u128,
+            )
+        };
+        assert_eq!(cvttsd2si(&mut cpu, 2.9), 2);
+        assert_eq!(cvttsd2si(&mut cpu, -2.9), -2i32 as u32);
+        assert_eq!(cvttsd2si(&mut cpu, 3e9), 0x8000_0000);
+        assert_eq!(cvttsd2si(&mut cpu, -3e9), 0x8000_0000);
+        assert_eq!(cvttsd2si(&mut cpu, f64::NAN), 0x8000_0000);
+        assert_eq!(cvttsd2si(&mut cpu, f64::INFINITY), 0x8000_0000);
+
+        let cvttss2si = |cpu: &mut CPU, x: f32| {
+            // cvttss2si eax, xmm0
+            cvt(
+                cpu,
+                cvttss2si_r32_xmmm32,
+                &[0xF3, 0x0F, 0x2C, 0xC0],
+                x.to_bits() as u128,
+            )
+        };
+        assert_eq!(cvttss2si(&mut cpu, 7.5), 7);
+        assert_eq!(cvttss2si(&mut cpu, -0.5), 0);
+        assert_eq!(cvttss2si(&mut cpu, 2147483648.0), 0x8000_0000);
+        assert_eq!(cvttss2si(&mut cpu, f32::NAN), 0x8000_0000);
+    }
+
+    #[test]
+    fn cvt_rounds_per_mxcsr() {
+        let mut cpu = CPU::default();
+        let cvtsd2si = |cpu: &mut CPU, x: f64| {
+            // cvtsd2si eax, xmm0
+            cvt(
+                cpu,
+                cvtsd2si_r32_xmmm64,
+                &[0xF2, 0x0F, 0x2D, 0xC0],
+                x.to_bits() as u128,
+            )
+        };
+        let rc = |mode: u32| crate::registers::MXCSR_DEFAULT | mode << 13;
+
+        // Round to nearest even, the default.
+        assert_eq!(cvtsd2si(&mut cpu, 2.5), 2);
+        assert_eq!(cvtsd2si(&mut cpu, 3.5), 4);
+        assert_eq!(cvtsd2si(&mut cpu, -2.5), -2i32 as u32);
+        // Round down.
+        cpu.regs.mxcsr = rc(1);
+        assert_eq!(cvtsd2si(&mut cpu, 2.5), 2);
+        assert_eq!(cvtsd2si(&mut cpu, -2.5), -3i32 as u32);
+        // Round up.
+        cpu.regs.mxcsr = rc(2);
+        assert_eq!(cvtsd2si(&mut cpu, 2.1), 3);
+        assert_eq!(cvtsd2si(&mut cpu, -2.9), -2i32 as u32);
+        // Truncate.
+        cpu.regs.mxcsr = rc(3);
+        assert_eq!(cvtsd2si(&mut cpu, 2.9), 2);
+        assert_eq!(cvtsd2si(&mut cpu, -2.9), -2i32 as u32);
+        assert_eq!(cvtsd2si(&mut cpu, f64::NAN), 0x8000_0000);
+    }
+
+    #[test]
+    fn cmpps_predicates() {
+        let mut cpu = CPU::default();
+        let x = pack([1.0f32, 2.0, f32::NAN, 1.0]);
+        let y = pack([1.0f32, 1.0, 1.0, f32::NAN]);
+        // Lane masks for each predicate, for the lanes (equal, greater, NaN, NaN).
+        let expected: [[bool; 4]; 8] = [
+            [true, false, false, false],  // eq
+            [false, false, false, false], // lt
+            [true, false, false, false],  // le
+            [false, false, true, true],   // unord
+            [false, true, true, true],    // neq
+            [true, true, true, true],     // nlt
+            [false, true, true, true],    // nle
+            [true, true, false, false],   // ord
+        ];
+        for (imm, want) in expected.iter().enumerate() {
+            cpu.regs.set128(XMM0, x);
+            cpu.regs.set128(XMM1, y);
+            // cmpps xmm0, xmm1, imm
+            run(
+                &mut cpu,
+                cmpps_xmm_xmmm128_imm8,
+                &[0x0F, 0xC2, 0xC1, imm as u8],
+            );
+            let got: [u32; 4] = unpack(cpu.regs.get128(XMM0));
+            assert_eq!(got, want.map(|b| if b { !0 } else { 0 }), "predicate {imm}");
+        }
+    }
+
+    #[test]
+    fn shuffles() {
+        let mut cpu = CPU::default();
+        let x = pack([10u32, 11, 12, 13]);
+        let y = pack([20u32, 21, 22, 23]);
+
+        cpu.regs.set128(XMM0, x);
+        cpu.regs.set128(XMM1, y);
+        // shufps xmm0, xmm1, 0b00_01_10_11
+        run(&mut cpu, shufps_xmm_xmmm128_imm8, &[0x0F, 0xC6, 0xC1, 0x1B]);
+        assert_eq!(unpack::<u32, 4>(cpu.regs.get128(XMM0)), [13, 12, 21, 20]);
+
+        cpu.regs.set128(XMM0, x);
+        // shufps xmm0, xmm1, 0b11_10_01_00
+        run(&mut cpu, shufps_xmm_xmmm128_imm8, &[0x0F, 0xC6, 0xC1, 0xE4]);
+        assert_eq!(unpack::<u32, 4>(cpu.regs.get128(XMM0)), [10, 11, 22, 23]);
+
+        cpu.regs.set128(XMM0, x);
+        // pshufd xmm0, xmm1, 0b00_01_10_11
+        run(
+            &mut cpu,
+            pshufd_xmm_xmmm128_imm8,
+            &[0x66, 0x0F, 0x70, 0xC1, 0x1B],
+        );
+        assert_eq!(unpack::<u32, 4>(cpu.regs.get128(XMM0)), [23, 22, 21, 20]);
+
+        // pshufd xmm0, xmm1, 0 broadcasts the low lane.
+        run(
+            &mut cpu,
+            pshufd_xmm_xmmm128_imm8,
+            &[0x66, 0x0F, 0x70, 0xC1, 0x00],
+        );
+        assert_eq!(unpack::<u32, 4>(cpu.regs.get128(XMM0)), [20, 20, 20, 20]);
+    }
+}
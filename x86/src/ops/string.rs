@@ -1,9 +1,10 @@
 //! Ops that tend to loop with 'rep' prefix, e.g. movs, stos.
 
+use super::helpers::*;
 use super::math::sub;
 use crate::{registers::Flags, x86::CPU};
 use iced_x86::{Instruction, Register};
-use memory::{Extensions, ExtensionsMut, Mem};
+use memory::Mem;
 
 /// Width of an operation, e.g. movsb/w/d.
 #[derive(Clone, Copy)]
@@ -48,9 +49,14 @@ impl Rep {
 /// Looping logic of various 'rep' prefixes, generalized for different instructions.
 /// Note: some instructions do not have varying Reps and it is important to treat them
 /// as plain REP; e.g. "REPNE MOVS" just means "REP MOVS".
+/// On a memory fault, the registers are left as they were before the faulting
+/// iteration, so that the instruction can be restarted.
 fn rep(cpu: &mut CPU, mem: Mem, rep: Rep, size: Size, func: impl Fn(&mut CPU, Mem, Size)) {
     while cpu.regs.get32(Register::ECX) > 0 {
         func(cpu, mem, size);
+        if faulted(cpu) {
+            break;
+        }
         *cpu.regs.get32_mut(Register::ECX) -= 1;
         match rep {
             Rep::REPE if !cpu.flags.contains(Flags::ZF) => break,
@@ -63,21 +69,24 @@ fn rep(cpu: &mut CPU, mem: Mem, rep: Rep, size: Size, func: impl Fn(&mut CPU, Me
 fn cmps_single(cpu: &mut CPU, mem: Mem, size: Size) {
     match size {
         Size::Dword => {
-            let x = mem.get_pod::<u32>(cpu.regs.get32(Register::ESI));
-            let y = mem.get_pod::<u32>(cpu.regs.get32(Register::EDI));
+            let x = read::<u32>(cpu, mem, cpu.regs.get32(Register::ESI));
+            let y = read::<u32>(cpu, mem, cpu.regs.get32(Register::EDI));
             sub(x, y, &mut cpu.flags);
         }
         Size::Word => {
-            let x = mem.get_pod::<u16>(cpu.regs.get32(Register::ESI));
-            let y = mem.get_pod::<u16>(cpu.regs.get32(Register::EDI));
+            let x = read::<u16>(cpu, mem, cpu.regs.get32(Register::ESI));
+            let y = read::<u16>(cpu, mem, cpu.regs.get32(Register::EDI));
             sub(x, y, &mut cpu.flags);
         }
         Size::Byte => {
-            let x = mem.get_pod::<u8>(cpu.regs.get32(Register::ESI));
-            let y = mem.get_pod::<u8>(cpu.regs.get32(Register::EDI));
+            let x = read::<u8>(cpu, mem, cpu.regs.get32(Register::ESI));
+            let y = read::<u8>(cpu, mem, cpu.regs.get32(Register::EDI));
             sub(x, y, &mut cpu.flags);
         }
     }
+    if faulted(cpu) {
+        return;
+    }
     if cpu.flags.contains(Flags::DF) {
         *cpu.regs.get32_mut(Register::EDI) -= size as u32;
         *cpu.regs.get32_mut(Register::ESI) -= size as u32;
@@ -110,18 +119,21 @@ pub fn cmpsb(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 fn movs_single(cpu: &mut CPU, mem: Mem, size: Size) {
     match size {
         Size::Dword => {
-            let src = mem.get_pod::<u32>(cpu.regs.get32(Register::ESI));
-            mem.put_pod::<u32>(cpu.regs.get32(Register::EDI), src);
+            let src = read::<u32>(cpu, mem, cpu.regs.get32(Register::ESI));
+            write::<u32>(cpu, mem, cpu.regs.get32(Register::EDI), src);
         }
         Size::Word => {
-            let src = mem.get_pod::<u16>(cpu.regs.get32(Register::ESI));
-            mem.put_pod::<u16>(cpu.regs.get32(Register::EDI), src);
+            let src = read::<u16>(cpu, mem, cpu.regs.get32(Register::ESI));
+            write::<u16>(cpu, mem, cpu.regs.get32(Register::EDI), src);
         }
         Size::Byte => {
-            let src = mem.get_pod::<u8>(cpu.regs.get32(Register::ESI));
-            mem.put_pod::<u8>(cpu.regs.get32(Register::EDI), src);
+            let src = read::<u8>(cpu, mem, cpu.regs.get32(Register::ESI));
+            write::<u8>(cpu, mem, cpu.regs.get32(Register::EDI), src);
         }
     }
+    if faulted(cpu) {
+        return;
+    }
     if cpu.flags.contains(Flags::DF) {
         *cpu.regs.get32_mut(Register::EDI) -= size as u32;
         *cpu.regs.get32_mut(Register::ESI) -= size as u32;
@@ -154,18 +166,21 @@ pub fn movsb(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 fn scas_single(cpu: &mut CPU, mem: Mem, size: Size) {
     match size {
         Size::Dword => {
-            let src = mem.get_pod::<u32>(cpu.regs.get32(Register::EDI));
+            let src = read::<u32>(cpu, mem, cpu.regs.get32(Register::EDI));
             sub(cpu.regs.get32(Register::EAX), src, &mut cpu.flags);
         }
         Size::Word => {
-            let src = mem.get_pod::<u16>(cpu.regs.get32(Register::EDI));
+            let src = read::<u16>(cpu, mem, cpu.regs.get32(Register::EDI));
             sub(cpu.regs.get32(Register::EAX) as u16, src, &mut cpu.flags);
         }
         Size::Byte => {
-            let src = mem.get_pod::<u8>(cpu.regs.get32(Register::EDI));
+            let src = read::<u8>(cpu, mem, cpu.regs.get32(Register::EDI));
             sub(cpu.regs.get32(Register::EAX) as u8, src, &mut cpu.flags);
         }
     }
+    if faulted(cpu) {
+        return;
+    }
     if cpu.flags.contains(Flags::DF) {
         *cpu.regs.get32_mut(Register::EDI) -= size as u32;
     } else {
@@ -194,18 +209,15 @@ pub fn scasb(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 fn stos_single(cpu: &mut CPU, mem: Mem, size: Size) {
+    let addr = cpu.regs.get32(Register::EDI);
+    let value = cpu.regs.get32(Register::EAX);
     match size {
-        Size::Byte => mem.put_pod::<u8>(
-            cpu.regs.get32(Register::EDI),
-            cpu.regs.get32(Register::EAX) as u8,
-        ),
-        Size::Word => mem.put_pod::<u16>(
-            cpu.regs.get32(Register::EDI),
-            cpu.regs.get32(Register::EAX) as u16,
-        ),
-        Size::Dword => {
-            mem.put_pod::<u32>(cpu.regs.get32(Register::EDI), cpu.regs.get32(Register::EAX))
-        }
+        Size::Byte => write::<u8>(cpu, mem, addr, value as u8),
+        Size::Word => write::<u16>(cpu, mem, addr, value as u16),
+        Size::Dword => write::<u32>(cpu, mem, addr, value),
+    }
+    if faulted(cpu) {
+        return;
     }
     if cpu.flags.contains(Flags::DF) {
         *cpu.regs.get32_mut(Register::EDI) -= size as u32;
@@ -237,20 +249,21 @@ pub fn stosb(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 fn lods_single(cpu: &mut CPU, mem: Mem, size: Size) {
     match size {
         Size::Byte => {
-            let value = mem.get_pod::<u8>(cpu.regs.get32(Register::ESI));
+            let value = read::<u8>(cpu, mem, cpu.regs.get32(Register::ESI));
             cpu.regs.set8(iced_x86::Register::AL, value)
         }
         Size::Word => {
-            let value = mem.get_pod::<u16>(cpu.regs.get32(Register::ESI));
+            let value = read::<u16>(cpu, mem, cpu.regs.get32(Register::ESI));
             cpu.regs.set16(iced_x86::Register::AX, value)
         }
         Size::Dword => {
-            cpu.regs.set32(
-                Register::EAX,
-                mem.get_pod::<u32>(cpu.regs.get32(Register::ESI)),
-            );
+            let value = read::<u32>(cpu, mem, cpu.regs.get32(Register::ESI));
+            cpu.regs.set32(Register::EAX, value)
         }
     }
+    if faulted(cpu) {
+        return;
+    }
     if cpu.flags.contains(Flags::DF) {
         *cpu.regs.get32_mut(Register::ESI) -= size as u32;
     } else {
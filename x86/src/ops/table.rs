@@ -7,8 +7,8 @@ use memory::Mem;
 /// The type of all operations defined in the ops module.
 pub type Op = fn(&mut CPU, Mem, &Instruction);
 
-const OP_TAB: [Option<Op>; 2553] = {
-    let mut tab: [Option<Op>; 2553] = [None; 2553];
+const OP_TAB: [Option<Op>; 2554] = {
+    let mut tab: [Option<Op>; 2554] = [None; 2554];
 
     use super::basic::*;
     use super::control::*;
@@ -16,6 +16,8 @@ const OP_TAB: [Option<Op>; 2553] = {
     use super::fpu::*;
     use super::math::*;
     use super::mmx::*;
+    #[cfg(feature = "sse")]
+    use super::sse::*;
     use super::string::*;
     use super::test::*;
 
@@ -44,6 +46,9 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Jns_rel32_32 as usize] = Some(jns);
     tab[iced_x86::Code::Jns_rel8_32 as usize] = Some(jns);
     tab[iced_x86::Code::Jp_rel8_32 as usize] = Some(jp);
+    tab[iced_x86::Code::Jp_rel32_32 as usize] = Some(jp);
+    tab[iced_x86::Code::Jnp_rel8_32 as usize] = Some(jnp);
+    tab[iced_x86::Code::Jnp_rel32_32 as usize] = Some(jnp);
     tab[iced_x86::Code::Jg_rel32_32 as usize] = Some(jg);
     tab[iced_x86::Code::Jg_rel8_32 as usize] = Some(jg);
     tab[iced_x86::Code::Jge_rel32_32 as usize] = Some(jge);
@@ -350,6 +355,7 @@ const OP_TAB: [Option<Op>; 2553] = {
 
     tab[iced_x86::Code::Fld_sti as usize] = Some(fld_sti);
     tab[iced_x86::Code::Fld_m80fp as usize] = Some(fld_m80fp);
+    tab[iced_x86::Code::Fbld_m80bcd as usize] = Some(fbld_m80bcd);
     tab[iced_x86::Code::Fld_m64fp as usize] = Some(fld_m64fp);
     tab[iced_x86::Code::Fld_m32fp as usize] = Some(fld_m32fp);
     tab[iced_x86::Code::Fild_m64int as usize] = Some(fild_m64int);
@@ -357,15 +363,17 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Fild_m16int as usize] = Some(fild_m16int);
     tab[iced_x86::Code::Fst_m64fp as usize] = Some(fst_m64fp);
     tab[iced_x86::Code::Fst_m32fp as usize] = Some(fst_m32fp);
+    tab[iced_x86::Code::Fstp_m80fp as usize] = Some(fstp_m80fp);
     tab[iced_x86::Code::Fstp_m64fp as usize] = Some(fstp_m64fp);
     tab[iced_x86::Code::Fstp_m32fp as usize] = Some(fstp_m32fp);
     tab[iced_x86::Code::Fstp_sti as usize] = Some(fstp_sti);
+    tab[iced_x86::Code::Fbstp_m80bcd as usize] = Some(fbstp_m80bcd);
     tab[iced_x86::Code::Fistp_m64int as usize] = Some(fistp_m64int);
     tab[iced_x86::Code::Fistp_m32int as usize] = Some(fistp_m32int);
     tab[iced_x86::Code::Fistp_m16int as usize] = Some(fistp_m16int);
     // tab[iced_x86::Code::Fisp_m64int as usize] = Some(fist_m64int);
     tab[iced_x86::Code::Fist_m32int as usize] = Some(fist_m32int);
-    // tab[iced_x86::Code::Fist_m16int as usize] = Some(fist_m16int);
+    tab[iced_x86::Code::Fist_m16int as usize] = Some(fist_m16int);
 
     tab[iced_x86::Code::Fchs as usize] = Some(fchs);
     tab[iced_x86::Code::Fabs as usize] = Some(fabs);
@@ -422,10 +430,12 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Fidivr_m32int as usize] = Some(fidivr_m32int);
 
     tab[iced_x86::Code::Fprem as usize] = Some(fprem);
+    tab[iced_x86::Code::Fprem1 as usize] = Some(fprem1);
 
     tab[iced_x86::Code::Fxch_st0_sti as usize] = Some(fxch_st0_sti);
+    tab[iced_x86::Code::Fxam as usize] = Some(fxam);
     tab[iced_x86::Code::Ftst as usize] = Some(ftst);
-    tab[iced_x86::Code::Fcom_m64fp as usize] = Some(fcom_m32fp);
+    tab[iced_x86::Code::Fcom_m64fp as usize] = Some(fcom_m64fp);
     tab[iced_x86::Code::Fcom_m32fp as usize] = Some(fcom_m32fp);
     tab[iced_x86::Code::Fcomp_m32fp as usize] = Some(fcomp_m32fp);
     tab[iced_x86::Code::Fcomp_m64fp as usize] = Some(fcomp_m64fp);
@@ -433,6 +443,7 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Fcompp as usize] = Some(fcompp);
     tab[iced_x86::Code::Fucomp_st0_sti as usize] = Some(fucomp_st0_sti);
     tab[iced_x86::Code::Fcomi_st0_sti as usize] = Some(fcomi_st0_sti);
+    tab[iced_x86::Code::Fcomip_st0_sti as usize] = Some(fcomip_st0_sti);
     tab[iced_x86::Code::Fucomi_st0_sti as usize] = Some(fucomi_st0_sti);
     tab[iced_x86::Code::Fucomip_st0_sti as usize] = Some(fucomip_st0_sti);
 
@@ -441,8 +452,14 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Fnstsw_m2byte as usize] = Some(fnstsw_m2byte);
     tab[iced_x86::Code::Fnstcw_m2byte as usize] = Some(fnstcw_m2byte);
     tab[iced_x86::Code::Fldcw_m2byte as usize] = Some(fldcw_m2byte);
-    tab[iced_x86::Code::Fclex as usize] = Some(nop);
-    tab[iced_x86::Code::Fnclex as usize] = Some(nop);
+    tab[iced_x86::Code::Fclex as usize] = Some(fnclex);
+    tab[iced_x86::Code::Fnclex as usize] = Some(fnclex);
+    tab[iced_x86::Code::Fstenv_m28byte as usize] = Some(fnstenv_m28byte);
+    tab[iced_x86::Code::Fnstenv_m28byte as usize] = Some(fnstenv_m28byte);
+    tab[iced_x86::Code::Fldenv_m28byte as usize] = Some(fldenv_m28byte);
+    tab[iced_x86::Code::Fsave_m108byte as usize] = Some(fnsave_m108byte);
+    tab[iced_x86::Code::Fnsave_m108byte as usize] = Some(fnsave_m108byte);
+    tab[iced_x86::Code::Frstor_m108byte as usize] = Some(frstor_m108byte);
 
     tab[iced_x86::Code::Fcmovnbe_st0_sti as usize] = Some(fcmovnbe_st0_sti);
 
@@ -506,6 +523,206 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Bts_rm32_r32 as usize] = Some(bts_rm32_r32);
     tab[iced_x86::Code::Tzcnt_r32_rm32 as usize] = Some(tzcnt_r32_rm32);
 
+    #[cfg(feature = "sse")]
+    {
+        tab[iced_x86::Code::Movaps_xmm_xmmm128 as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movaps_xmmm128_xmm as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movups_xmm_xmmm128 as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movups_xmmm128_xmm as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movapd_xmm_xmmm128 as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movapd_xmmm128_xmm as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movupd_xmm_xmmm128 as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movupd_xmmm128_xmm as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movdqa_xmm_xmmm128 as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movdqa_xmmm128_xmm as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movdqu_xmm_xmmm128 as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movdqu_xmmm128_xmm as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movntps_m128_xmm as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movntpd_m128_xmm as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movntdq_m128_xmm as usize] = Some(mov_xmmm128_xmmm128);
+        tab[iced_x86::Code::Movnti_m32_r32 as usize] = Some(mov_rm32_r32);
+        tab[iced_x86::Code::Movss_xmm_xmmm32 as usize] = Some(movss_xmm_xmmm32);
+        tab[iced_x86::Code::Movss_xmmm32_xmm as usize] = Some(movss_xmmm32_xmm);
+        tab[iced_x86::Code::Movsd_xmm_xmmm64 as usize] = Some(movsd_xmm_xmmm64);
+        tab[iced_x86::Code::Movsd_xmmm64_xmm as usize] = Some(movsd_xmmm64_xmm);
+        tab[iced_x86::Code::Movq_xmm_xmmm64 as usize] = Some(movq_xmm_xmmm64);
+        tab[iced_x86::Code::Movq_xmmm64_xmm as usize] = Some(movq_xmmm64_xmm);
+        tab[iced_x86::Code::Movd_xmm_rm32 as usize] = Some(movd_xmm_rm32);
+        tab[iced_x86::Code::Movd_rm32_xmm as usize] = Some(movd_rm32_xmm);
+        tab[iced_x86::Code::Movlps_xmm_m64 as usize] = Some(movlps_xmm_m64);
+        tab[iced_x86::Code::Movlpd_xmm_m64 as usize] = Some(movlps_xmm_m64);
+        tab[iced_x86::Code::Movhps_xmm_m64 as usize] = Some(movhps_xmm_m64);
+        tab[iced_x86::Code::Movhpd_xmm_m64 as usize] = Some(movhps_xmm_m64);
+        tab[iced_x86::Code::Movlps_m64_xmm as usize] = Some(movlps_m64_xmm);
+        tab[iced_x86::Code::Movlpd_m64_xmm as usize] = Some(movlps_m64_xmm);
+        tab[iced_x86::Code::Movhps_m64_xmm as usize] = Some(movhps_m64_xmm);
+        tab[iced_x86::Code::Movhpd_m64_xmm as usize] = Some(movhps_m64_xmm);
+        tab[iced_x86::Code::Movhlps_xmm_xmm as usize] = Some(movhlps_xmm_xmm);
+        tab[iced_x86::Code::Movlhps_xmm_xmm as usize] = Some(movlhps_xmm_xmm);
+        tab[iced_x86::Code::Movmskps_r32_xmm as usize] = Some(movmskps_r32_xmm);
+        tab[iced_x86::Code::Movmskpd_r32_xmm as usize] = Some(movmskpd_r32_xmm);
+        tab[iced_x86::Code::Pmovmskb_r32_xmm as usize] = Some(pmovmskb_r32_xmm);
+
+        tab[iced_x86::Code::Addps_xmm_xmmm128 as usize] = Some(addps_xmm_xmmm128);
+        tab[iced_x86::Code::Addpd_xmm_xmmm128 as usize] = Some(addpd_xmm_xmmm128);
+        tab[iced_x86::Code::Addss_xmm_xmmm32 as usize] = Some(addss_xmm_xmmm32);
+        tab[iced_x86::Code::Addsd_xmm_xmmm64 as usize] = Some(addsd_xmm_xmmm64);
+        tab[iced_x86::Code::Subps_xmm_xmmm128 as usize] = Some(subps_xmm_xmmm128);
+        tab[iced_x86::Code::Subpd_xmm_xmmm128 as usize] = Some(subpd_xmm_xmmm128);
+        tab[iced_x86::Code::Subss_xmm_xmmm32 as usize] = Some(subss_xmm_xmmm32);
+        tab[iced_x86::Code::Subsd_xmm_xmmm64 as usize] = Some(subsd_xmm_xmmm64);
+        tab[iced_x86::Code::Mulps_xmm_xmmm128 as usize] = Some(mulps_xmm_xmmm128);
+        tab[iced_x86::Code::Mulpd_xmm_xmmm128 as usize] = Some(mulpd_xmm_xmmm128);
+        tab[iced_x86::Code::Mulss_xmm_xmmm32 as usize] = Some(mulss_xmm_xmmm32);
+        tab[iced_x86::Code::Mulsd_xmm_xmmm64 as usize] = Some(mulsd_xmm_xmmm64);
+        tab[iced_x86::Code::Divps_xmm_xmmm128 as usize] = Some(divps_xmm_xmmm128);
+        tab[iced_x86::Code::Divpd_xmm_xmmm128 as usize] = Some(divpd_xmm_xmmm128);
+        tab[iced_x86::Code::Divss_xmm_xmmm32 as usize] = Some(divss_xmm_xmmm32);
+        tab[iced_x86::Code::Divsd_xmm_xmmm64 as usize] = Some(divsd_xmm_xmmm64);
+        tab[iced_x86::Code::Minps_xmm_xmmm128 as usize] = Some(minps_xmm_xmmm128);
+        tab[iced_x86::Code::Minpd_xmm_xmmm128 as usize] = Some(minpd_xmm_xmmm128);
+        tab[iced_x86::Code::Minss_xmm_xmmm32 as usize] = Some(minss_xmm_xmmm32);
+        tab[iced_x86::Code::Minsd_xmm_xmmm64 as usize] = Some(minsd_xmm_xmmm64);
+        tab[iced_x86::Code::Maxps_xmm_xmmm128 as usize] = Some(maxps_xmm_xmmm128);
+        tab[iced_x86::Code::Maxpd_xmm_xmmm128 as usize] = Some(maxpd_xmm_xmmm128);
+        tab[iced_x86::Code::Maxss_xmm_xmmm32 as usize] = Some(maxss_xmm_xmmm32);
+        tab[iced_x86::Code::Maxsd_xmm_xmmm64 as usize] = Some(maxsd_xmm_xmmm64);
+        tab[iced_x86::Code::Sqrtps_xmm_xmmm128 as usize] = Some(sqrtps_xmm_xmmm128);
+        tab[iced_x86::Code::Sqrtpd_xmm_xmmm128 as usize] = Some(sqrtpd_xmm_xmmm128);
+        tab[iced_x86::Code::Sqrtss_xmm_xmmm32 as usize] = Some(sqrtss_xmm_xmmm32);
+        tab[iced_x86::Code::Sqrtsd_xmm_xmmm64 as usize] = Some(sqrtsd_xmm_xmmm64);
+        tab[iced_x86::Code::Rcpps_xmm_xmmm128 as usize] = Some(rcpps_xmm_xmmm128);
+        tab[iced_x86::Code::Rcpss_xmm_xmmm32 as usize] = Some(rcpss_xmm_xmmm32);
+        tab[iced_x86::Code::Rsqrtps_xmm_xmmm128 as usize] = Some(rsqrtps_xmm_xmmm128);
+        tab[iced_x86::Code::Rsqrtss_xmm_xmmm32 as usize] = Some(rsqrtss_xmm_xmmm32);
+
+        tab[iced_x86::Code::Andps_xmm_xmmm128 as usize] = Some(and_xmm_xmmm128);
+        tab[iced_x86::Code::Andpd_xmm_xmmm128 as usize] = Some(and_xmm_xmmm128);
+        tab[iced_x86::Code::Pand_xmm_xmmm128 as usize] = Some(and_xmm_xmmm128);
+        tab[iced_x86::Code::Andnps_xmm_xmmm128 as usize] = Some(andn_xmm_xmmm128);
+        tab[iced_x86::Code::Andnpd_xmm_xmmm128 as usize] = Some(andn_xmm_xmmm128);
+        tab[iced_x86::Code::Pandn_xmm_xmmm128 as usize] = Some(andn_xmm_xmmm128);
+        tab[iced_x86::Code::Orps_xmm_xmmm128 as usize] = Some(or_xmm_xmmm128);
+        tab[iced_x86::Code::Orpd_xmm_xmmm128 as usize] = Some(or_xmm_xmmm128);
+        tab[iced_x86::Code::Por_xmm_xmmm128 as usize] = Some(or_xmm_xmmm128);
+        tab[iced_x86::Code::Xorps_xmm_xmmm128 as usize] = Some(xor_xmm_xmmm128);
+        tab[iced_x86::Code::Xorpd_xmm_xmmm128 as usize] = Some(xor_xmm_xmmm128);
+        tab[iced_x86::Code::Pxor_xmm_xmmm128 as usize] = Some(xor_xmm_xmmm128);
+
+        tab[iced_x86::Code::Comiss_xmm_xmmm32 as usize] = Some(comiss_xmm_xmmm32);
+        tab[iced_x86::Code::Ucomiss_xmm_xmmm32 as usize] = Some(comiss_xmm_xmmm32);
+        tab[iced_x86::Code::Comisd_xmm_xmmm64 as usize] = Some(comisd_xmm_xmmm64);
+        tab[iced_x86::Code::Ucomisd_xmm_xmmm64 as usize] = Some(comisd_xmm_xmmm64);
+        tab[iced_x86::Code::Cmpps_xmm_xmmm128_imm8 as usize] = Some(cmpps_xmm_xmmm128_imm8);
+        tab[iced_x86::Code::Cmppd_xmm_xmmm128_imm8 as usize] = Some(cmppd_xmm_xmmm128_imm8);
+        tab[iced_x86::Code::Cmpss_xmm_xmmm32_imm8 as usize] = Some(cmpss_xmm_xmmm32_imm8);
+        tab[iced_x86::Code::Cmpsd_xmm_xmmm64_imm8 as usize] = Some(cmpsd_xmm_xmmm64_imm8);
+
+        tab[iced_x86::Code::Shufps_xmm_xmmm128_imm8 as usize] = Some(shufps_xmm_xmmm128_imm8);
+        tab[iced_x86::Code::Shufpd_xmm_xmmm128_imm8 as usize] = Some(shufpd_xmm_xmmm128_imm8);
+        tab[iced_x86::Code::Pshufd_xmm_xmmm128_imm8 as usize] = Some(pshufd_xmm_xmmm128_imm8);
+        tab[iced_x86::Code::Pshuflw_xmm_xmmm128_imm8 as usize] = Some(pshuflw_xmm_xmmm128_imm8);
+        tab[iced_x86::Code::Pshufhw_xmm_xmmm128_imm8 as usize] = Some(pshufhw_xmm_xmmm128_imm8);
+        tab[iced_x86::Code::Unpcklps_xmm_xmmm128 as usize] = Some(unpcklps_xmm_xmmm128);
+        tab[iced_x86::Code::Unpckhps_xmm_xmmm128 as usize] = Some(unpckhps_xmm_xmmm128);
+        tab[iced_x86::Code::Unpcklpd_xmm_xmmm128 as usize] = Some(punpcklqdq_xmm_xmmm128);
+        tab[iced_x86::Code::Unpckhpd_xmm_xmmm128 as usize] = Some(punpckhqdq_xmm_xmmm128);
+        tab[iced_x86::Code::Punpcklbw_xmm_xmmm128 as usize] = Some(punpcklbw_xmm_xmmm128);
+        tab[iced_x86::Code::Punpckhbw_xmm_xmmm128 as usize] = Some(punpckhbw_xmm_xmmm128);
+        tab[iced_x86::Code::Punpcklwd_xmm_xmmm128 as usize] = Some(punpcklwd_xmm_xmmm128);
+        tab[iced_x86::Code::Punpckhwd_xmm_xmmm128 as usize] = Some(punpckhwd_xmm_xmmm128);
+        tab[iced_x86::Code::Punpckldq_xmm_xmmm128 as usize] = Some(punpckldq_xmm_xmmm128);
+        tab[iced_x86::Code::Punpckhdq_xmm_xmmm128 as usize] = Some(punpckhdq_xmm_xmmm128);
+        tab[iced_x86::Code::Punpcklqdq_xmm_xmmm128 as usize] = Some(punpcklqdq_xmm_xmmm128);
+        tab[iced_x86::Code::Punpckhqdq_xmm_xmmm128 as usize] = Some(punpckhqdq_xmm_xmmm128);
+
+        tab[iced_x86::Code::Cvtsi2ss_xmm_rm32 as usize] = Some(cvtsi2ss_xmm_rm32);
+        tab[iced_x86::Code::Cvtsi2sd_xmm_rm32 as usize] = Some(cvtsi2sd_xmm_rm32);
+        tab[iced_x86::Code::Cvtss2sd_xmm_xmmm32 as usize] = Some(cvtss2sd_xmm_xmmm32);
+        tab[iced_x86::Code::Cvtsd2ss_xmm_xmmm64 as usize] = Some(cvtsd2ss_xmm_xmmm64);
+        tab[iced_x86::Code::Cvtss2si_r32_xmmm32 as usize] = Some(cvtss2si_r32_xmmm32);
+        tab[iced_x86::Code::Cvttss2si_r32_xmmm32 as usize] = Some(cvttss2si_r32_xmmm32);
+        tab[iced_x86::Code::Cvtsd2si_r32_xmmm64 as usize] = Some(cvtsd2si_r32_xmmm64);
+        tab[iced_x86::Code::Cvttsd2si_r32_xmmm64 as usize] = Some(cvttsd2si_r32_xmmm64);
+        tab[iced_x86::Code::Cvtdq2ps_xmm_xmmm128 as usize] = Some(cvtdq2ps_xmm_xmmm128);
+        tab[iced_x86::Code::Cvtps2dq_xmm_xmmm128 as usize] = Some(cvtps2dq_xmm_xmmm128);
+        tab[iced_x86::Code::Cvttps2dq_xmm_xmmm128 as usize] = Some(cvttps2dq_xmm_xmmm128);
+        tab[iced_x86::Code::Cvtdq2pd_xmm_xmmm64 as usize] = Some(cvtdq2pd_xmm_xmmm64);
+        tab[iced_x86::Code::Cvtpd2dq_xmm_xmmm128 as usize] = Some(cvtpd2dq_xmm_xmmm128);
+        tab[iced_x86::Code::Cvttpd2dq_xmm_xmmm128 as usize] = Some(cvttpd2dq_xmm_xmmm128);
+        tab[iced_x86::Code::Cvtps2pd_xmm_xmmm64 as usize] = Some(cvtps2pd_xmm_xmmm64);
+        tab[iced_x86::Code::Cvtpd2ps_xmm_xmmm128 as usize] = Some(cvtpd2ps_xmm_xmmm128);
+
+        tab[iced_x86::Code::Paddb_xmm_xmmm128 as usize] = Some(paddb_xmm_xmmm128);
+        tab[iced_x86::Code::Paddw_xmm_xmmm128 as usize] = Some(paddw_xmm_xmmm128);
+        tab[iced_x86::Code::Paddd_xmm_xmmm128 as usize] = Some(paddd_xmm_xmmm128);
+        tab[iced_x86::Code::Paddq_xmm_xmmm128 as usize] = Some(paddq_xmm_xmmm128);
+        tab[iced_x86::Code::Psubb_xmm_xmmm128 as usize] = Some(psubb_xmm_xmmm128);
+        tab[iced_x86::Code::Psubw_xmm_xmmm128 as usize] = Some(psubw_xmm_xmmm128);
+        tab[iced_x86::Code::Psubd_xmm_xmmm128 as usize] = Some(psubd_xmm_xmmm128);
+        tab[iced_x86::Code::Psubq_xmm_xmmm128 as usize] = Some(psubq_xmm_xmmm128);
+        tab[iced_x86::Code::Paddusb_xmm_xmmm128 as usize] = Some(paddusb_xmm_xmmm128);
+        tab[iced_x86::Code::Paddusw_xmm_xmmm128 as usize] = Some(paddusw_xmm_xmmm128);
+        tab[iced_x86::Code::Paddsb_xmm_xmmm128 as usize] = Some(paddsb_xmm_xmmm128);
+        tab[iced_x86::Code::Paddsw_xmm_xmmm128 as usize] = Some(paddsw_xmm_xmmm128);
+        tab[iced_x86::Code::Psubusb_xmm_xmmm128 as usize] = Some(psubusb_xmm_xmmm128);
+        tab[iced_x86::Code::Psubusw_xmm_xmmm128 as usize] = Some(psubusw_xmm_xmmm128);
+        tab[iced_x86::Code::Psubsb_xmm_xmmm128 as usize] = Some(psubsb_xmm_xmmm128);
+        tab[iced_x86::Code::Psubsw_xmm_xmmm128 as usize] = Some(psubsw_xmm_xmmm128);
+        tab[iced_x86::Code::Pmullw_xmm_xmmm128 as usize] = Some(pmullw_xmm_xmmm128);
+        tab[iced_x86::Code::Pmulhw_xmm_xmmm128 as usize] = Some(pmulhw_xmm_xmmm128);
+        tab[iced_x86::Code::Pmulhuw_xmm_xmmm128 as usize] = Some(pmulhuw_xmm_xmmm128);
+        tab[iced_x86::Code::Pmuludq_xmm_xmmm128 as usize] = Some(pmuludq_xmm_xmmm128);
+        tab[iced_x86::Code::Pmaddwd_xmm_xmmm128 as usize] = Some(pmaddwd_xmm_xmmm128);
+        tab[iced_x86::Code::Pminub_xmm_xmmm128 as usize] = Some(pminub_xmm_xmmm128);
+        tab[iced_x86::Code::Pmaxub_xmm_xmmm128 as usize] = Some(pmaxub_xmm_xmmm128);
+        tab[iced_x86::Code::Pminsw_xmm_xmmm128 as usize] = Some(pminsw_xmm_xmmm128);
+        tab[iced_x86::Code::Pmaxsw_xmm_xmmm128 as usize] = Some(pmaxsw_xmm_xmmm128);
+        tab[iced_x86::Code::Pavgb_xmm_xmmm128 as usize] = Some(pavgb_xmm_xmmm128);
+        tab[iced_x86::Code::Pavgw_xmm_xmmm128 as usize] = Some(pavgw_xmm_xmmm128);
+        tab[iced_x86::Code::Psadbw_xmm_xmmm128 as usize] = Some(psadbw_xmm_xmmm128);
+        tab[iced_x86::Code::Pcmpeqb_xmm_xmmm128 as usize] = Some(pcmpeqb_xmm_xmmm128);
+        tab[iced_x86::Code::Pcmpeqw_xmm_xmmm128 as usize] = Some(pcmpeqw_xmm_xmmm128);
+        tab[iced_x86::Code::Pcmpeqd_xmm_xmmm128 as usize] = Some(pcmpeqd_xmm_xmmm128);
+        tab[iced_x86::Code::Pcmpgtb_xmm_xmmm128 as usize] = Some(pcmpgtb_xmm_xmmm128);
+        tab[iced_x86::Code::Pcmpgtw_xmm_xmmm128 as usize] = Some(pcmpgtw_xmm_xmmm128);
+        tab[iced_x86::Code::Pcmpgtd_xmm_xmmm128 as usize] = Some(pcmpgtd_xmm_xmmm128);
+        tab[iced_x86::Code::Packsswb_xmm_xmmm128 as usize] = Some(packsswb_xmm_xmmm128);
+        tab[iced_x86::Code::Packuswb_xmm_xmmm128 as usize] = Some(packuswb_xmm_xmmm128);
+        tab[iced_x86::Code::Packssdw_xmm_xmmm128 as usize] = Some(packssdw_xmm_xmmm128);
+        tab[iced_x86::Code::Pextrw_r32_xmm_imm8 as usize] = Some(pextrw_r32_xmm_imm8);
+        tab[iced_x86::Code::Pinsrw_xmm_r32m16_imm8 as usize] = Some(pinsrw_xmm_r32m16_imm8);
+
+        tab[iced_x86::Code::Psllw_xmm_imm8 as usize] = Some(psllw_xmm);
+        tab[iced_x86::Code::Psllw_xmm_xmmm128 as usize] = Some(psllw_xmm);
+        tab[iced_x86::Code::Pslld_xmm_imm8 as usize] = Some(pslld_xmm);
+        tab[iced_x86::Code::Pslld_xmm_xmmm128 as usize] = Some(pslld_xmm);
+        tab[iced_x86::Code::Psllq_xmm_imm8 as usize] = Some(psllq_xmm);
+        tab[iced_x86::Code::Psllq_xmm_xmmm128 as usize] = Some(psllq_xmm);
+        tab[iced_x86::Code::Psrlw_xmm_imm8 as usize] = Some(psrlw_xmm);
+        tab[iced_x86::Code::Psrlw_xmm_xmmm128 as usize] = Some(psrlw_xmm);
+        tab[iced_x86::Code::Psrld_xmm_imm8 as usize] = Some(psrld_xmm);
+        tab[iced_x86::Code::Psrld_xmm_xmmm128 as usize] = Some(psrld_xmm);
+        tab[iced_x86::Code::Psrlq_xmm_imm8 as usize] = Some(psrlq_xmm);
+        tab[iced_x86::Code::Psrlq_xmm_xmmm128 as usize] = Some(psrlq_xmm);
+        tab[iced_x86::Code::Psraw_xmm_imm8 as usize] = Some(psraw_xmm);
+        tab[iced_x86::Code::Psraw_xmm_xmmm128 as usize] = Some(psraw_xmm);
+        tab[iced_x86::Code::Psrad_xmm_imm8 as usize] = Some(psrad_xmm);
+        tab[iced_x86::Code::Psrad_xmm_xmmm128 as usize] = Some(psrad_xmm);
+        tab[iced_x86::Code::Pslldq_xmm_imm8 as usize] = Some(pslldq_xmm_imm8);
+        tab[iced_x86::Code::Psrldq_xmm_imm8 as usize] = Some(psrldq_xmm_imm8);
+
+        tab[iced_x86::Code::Ldmxcsr_m32 as usize] = Some(ldmxcsr_m32);
+        tab[iced_x86::Code::Stmxcsr_m32 as usize] = Some(stmxcsr_m32);
+        tab[iced_x86::Code::Sfence as usize] = Some(nop);
+        tab[iced_x86::Code::Lfence as usize] = Some(nop);
+        tab[iced_x86::Code::Mfence as usize] = Some(nop);
+        tab[iced_x86::Code::Prefetchnta_m8 as usize] = Some(nop);
+        tab[iced_x86::Code::Prefetcht0_m8 as usize] = Some(nop);
+        tab[iced_x86::Code::Prefetcht1_m8 as usize] = Some(nop);
+        tab[iced_x86::Code::Prefetcht2_m8 as usize] = Some(nop);
+    }
+
     tab[iced_x86::Code::Cpuid as usize] = Some(cpuid);
 
     // Code to print the necessary size of the table:
@@ -520,8 +737,19 @@ const OP_TAB: [Option<Op>; 2553] = {
 pub fn decode(instr: &Instruction, ops: &[crate::icache::Op]) -> Option<Op> {
     // The implementation of 'jp' requires a specific instruction to precede it.
     match instr.mnemonic() {
-        iced_x86::Mnemonic::Jp => {
-            assert!(ops.last().unwrap().instr.code() == iced_x86::Code::Sahf);
+        iced_x86::Mnemonic::Jp | iced_x86::Mnemonic::Jnp => {
+            assert!(matches!(
+                ops.last().unwrap().instr.mnemonic(),
+                iced_x86::Mnemonic::Sahf
+                    | iced_x86::Mnemonic::Comiss
+                    | iced_x86::Mnemonic::Fcomi
+                    | iced_x86::Mnemonic::Fcomip
+                    | iced_x86::Mnemonic::Fucomi
+                    | iced_x86::Mnemonic::Fucomip
+                    | iced_x86::Mnemonic::Ucomiss
+                    | iced_x86::Mnemonic::Comisd
+                    | iced_x86::Mnemonic::Ucomisd
+            ));
         }
         _ => {}
     }
@@ -1,7 +1,7 @@
 use super::math::{and, sub};
 use crate::{registers::Flags, x86::CPU};
 use iced_x86::Instruction;
-use memory::{Extensions, Mem};
+use memory::Mem;
 
 use super::helpers::*;
 
@@ -63,7 +63,7 @@ pub fn cmp_r8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = cpu.regs.get8(instr.op0_register());
     let y = match instr.op1_kind() {
         iced_x86::OpKind::Register => cpu.regs.get8(instr.op1_register()),
-        iced_x86::OpKind::Memory => mem.get_pod::<u8>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Memory => read::<u8>(cpu, mem, x86_addr(cpu, instr)),
         _ => unreachable!(),
     };
     sub(x, y, &mut cpu.flags);
@@ -6,6 +6,8 @@ bitflags! {
     pub struct Flags: u32 {
         /// carry
         const CF = 1 << 0;
+        /// parity; only tracked by sahf and comiss/ucomiss, see ops::decode
+        const PF = 1 << 2;
         /// zero
         const ZF = 1 << 6;
         /// sign
@@ -22,7 +24,7 @@ bitflags! {
     }
 }
 
-#[derive(Default)]
+#[derive(Clone)]
 pub struct Registers {
     /// 32-bit registers, in order:
     ///   eax ecx edx ebx esp ebp esi edi,
@@ -52,8 +54,31 @@ pub struct Registers {
     /// MMX registers.
     // TODO: officially these should alias the FPU registers(!).
     mm: [u64; 8],
+
+    /// SSE registers.
+    xmm: [u128; 8],
+
+    /// SSE control/status register.
+    pub mxcsr: u32,
 }
 
+impl Default for Registers {
+    fn default() -> Self {
+        Registers {
+            r32: Default::default(),
+            eip: 0,
+            segment: Default::default(),
+            fs_addr: 0,
+            mm: Default::default(),
+            xmm: Default::default(),
+            mxcsr: MXCSR_DEFAULT,
+        }
+    }
+}
+
+/// Power-on value of MXCSR: all exceptions masked, round to nearest.
+pub const MXCSR_DEFAULT: u32 = 0x1F80;
+
 #[allow(dead_code)]
 const fn assert_enums_as_expected() {
     assert!(ECX as u8 == EAX as u8 + 1);
@@ -89,6 +114,14 @@ const fn assert_enums_as_expected() {
     assert!(MM5 as u8 == MM0 as u8 + 5);
     assert!(MM6 as u8 == MM0 as u8 + 6);
     assert!(MM7 as u8 == MM0 as u8 + 7);
+
+    assert!(XMM1 as u8 == XMM0 as u8 + 1);
+    assert!(XMM2 as u8 == XMM0 as u8 + 2);
+    assert!(XMM3 as u8 == XMM0 as u8 + 3);
+    assert!(XMM4 as u8 == XMM0 as u8 + 4);
+    assert!(XMM5 as u8 == XMM0 as u8 + 5);
+    assert!(XMM6 as u8 == XMM0 as u8 + 6);
+    assert!(XMM7 as u8 == XMM0 as u8 + 7);
 }
 const _: () = assert_enums_as_expected();
 
@@ -222,4 +255,20 @@ impl Registers {
         }
         self.mm[index] = value;
     }
+
+    pub fn get128(&self, reg: Register) -> u128 {
+        let index = reg as usize - XMM0 as usize;
+        if index >= 8 {
+            unreachable!("{reg:?}");
+        }
+        self.xmm[index]
+    }
+
+    pub fn set128(&mut self, reg: Register, value: u128) {
+        let index = reg as usize - XMM0 as usize;
+        if index >= 8 {
+            unreachable!("{reg:?}");
+        }
+        self.xmm[index] = value;
+    }
 }
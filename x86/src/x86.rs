@@ -12,6 +12,18 @@ use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
+/// A CPU fault that the OS is expected to handle (e.g. by dispatching to an
+/// exception handler), as opposed to an Error, which stops emulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exception {
+    /// #DE: division by zero, or a quotient too large for the destination.
+    DivideError,
+    /// #UD: undefined instruction.
+    InvalidOpcode,
+    /// Memory access (or jump) to an address outside of valid memory.
+    AccessViolation { addr: u32, write: bool },
+}
+
 #[derive(Debug, Default, PartialEq, Eq)]
 pub enum CPUState {
     #[default]
@@ -20,6 +32,7 @@ pub enum CPUState {
     DebugBreak,
     SysCall,
     Error(String),
+    Exception(Exception),
     Free,
 }
 
@@ -48,7 +61,24 @@ pub struct CPU {
 
     /// If eip==MAGIC_ADDR, then the next step is to poll a future rather than
     /// executing a basic block.
-    futures: Vec<BoxFuture<()>>,
+    futures: Vec<AsyncFrame>,
+
+    /// Registers as of the first memory fault in the current instruction, which the
+    /// instruction may go on to clobber.  Restored once the instruction completes, so
+    /// the fault is reported (and resumable) as if the instruction never ran.
+    fault_state: Option<Box<(Registers, Flags, FPU)>>,
+    /// Stands in for the memory operand of an instruction whose access faulted.
+    pub(crate) fault_sink: u32,
+}
+
+/// A pending future, along with the esp at the time it was started.
+/// While the future is live, any x86 code it calls runs below that esp;
+/// if esp is found above it, the x86 frames it was waiting on were unwound
+/// (e.g. by an exception handler jumping to an __except block) and it will
+/// never complete.
+struct AsyncFrame {
+    esp: u32,
+    future: BoxFuture<()>,
 }
 
 impl CPU {
@@ -56,14 +86,31 @@ impl CPU {
         self.state = CPUState::Error(msg);
     }
 
+    /// Raise a CPU fault.  Like err(), this stops execution at the faulting instruction,
+    /// but the fault is reported to the OS layer rather than treated as fatal.
+    pub fn fault(&mut self, exception: Exception) {
+        self.state = CPUState::Exception(exception);
+    }
+
+    /// Raise an access violation for a memory access by the current instruction.
+    pub fn access_violation(&mut self, addr: u32, write: bool) {
+        if matches!(self.state, CPUState::Exception(_)) {
+            return; // only the first fault counts
+        }
+        self.fault_state = Some(Box::new((self.regs.clone(), self.flags, self.fpu.clone())));
+        self.fault(Exception::AccessViolation { addr, write });
+    }
+
     /// Jump to an address, verifying it's within valid bounds.
     pub fn jmp(&mut self, mem: Mem, addr: u32) {
         if addr < 0x1000 {
-            self.err(format!("jmp to null page addr={addr:x}"));
+            log::warn!("jmp to null page addr={addr:x}");
+            self.fault(Exception::AccessViolation { addr, write: false });
             return;
         }
         if mem.is_oob::<u8>(addr) && addr != MAGIC_ADDR {
-            self.err(format!("jmp to oob addr={addr:x}"));
+            log::warn!("jmp to oob addr={addr:x}");
+            self.fault(Exception::AccessViolation { addr, write: false });
             return;
         }
         self.regs.eip = addr;
@@ -87,15 +134,18 @@ impl CPU {
         self.regs.set32(Register::ECX, 0);
         self.regs.set32(Register::EDX, 0);
 
-        X86Future { cpu: self, esp }
+        X86Future {
+            cpu: self,
+            esp,
+            args_esp: esp - (args.len() as u32 * 4),
+        }
     }
 
     /// Set up the CPU such that we are making an x86->async call, enqueuing a Future
     /// that is polled the next time the CPU executes.
     pub fn call_async(&mut self, future: BoxFuture<u64>, return_address: u32) {
-        self.regs.eip = MAGIC_ADDR;
         let cpu = self as *mut CPU;
-        self.futures.push(Box::pin(async move {
+        self.spawn_async(Box::pin(async move {
             let cpu = unsafe { &mut *cpu };
             let ret = future.await;
             cpu.regs.set32(Register::EAX, ret as u32);
@@ -104,14 +154,34 @@ impl CPU {
         }));
     }
 
+    /// Set up the CPU such that the next step polls the given future, which is responsible
+    /// for leaving the CPU in the state to resume from (including eip) when it completes.
+    /// Used for e.g. exception dispatch, which resumes at an arbitrary context rather than
+    /// returning to a caller.
+    pub fn spawn_async(&mut self, future: BoxFuture<()>) {
+        self.regs.eip = MAGIC_ADDR;
+        self.drop_unwound_futures();
+        let esp = self.regs.get32(Register::ESP);
+        self.futures.push(AsyncFrame { esp, future });
+    }
+
+    /// Discard futures whose x86 frames have been unwound past; see AsyncFrame.
+    fn drop_unwound_futures(&mut self) {
+        let esp = self.regs.get32(Register::ESP);
+        while let Some(frame) = self.futures.last() {
+            if esp <= frame.esp {
+                break;
+            }
+            self.futures.pop();
+        }
+    }
+
     fn async_executor(&mut self) {
-        let future = self.futures.last_mut().unwrap();
-        // TODO: we don't use the waker at all.  Rust doesn't like us passing a random null pointer
-        // here but it seems like nothing accesses it(?).
-        //let c = unsafe { std::task::Context::from_waker(&Waker::from_raw(std::task::RawWaker::)) };
-        #[allow(deref_nullptr)]
-        let context: &mut std::task::Context = unsafe { &mut *std::ptr::null_mut() };
-        let poll = future.as_mut().poll(context);
+        self.drop_unwound_futures();
+        let future = &mut self.futures.last_mut().unwrap().future;
+        // We don't use the waker at all; the CPU polls whenever it reaches MAGIC_ADDR.
+        let mut context = Context::from_waker(std::task::Waker::noop());
+        let poll = future.as_mut().poll(&mut context);
         match poll {
             Poll::Ready(()) => {
                 self.futures.pop();
@@ -144,8 +214,11 @@ impl CPU {
             (op.op)(self, mem, &op.instr);
             match self.state {
                 CPUState::Running => continue,
-                CPUState::Error(_) => {
-                    // Point the debugger at the failed instruction.
+                CPUState::Error(_) | CPUState::Exception(_) => {
+                    if let Some(state) = self.fault_state.take() {
+                        (self.regs, self.flags, self.fpu) = *state;
+                    }
+                    // Point the debugger (or exception handler) at the failed instruction.
                     self.regs.eip = prev_ip;
                     break;
                 }
@@ -160,7 +233,11 @@ pub struct X86Future {
     // We assume the CPU is around for the duration of the future execution.
     // https://github.com/rust-lang/futures-rs/issues/316
     cpu: *mut CPU,
+    /// esp before the call.
     esp: u32,
+    /// esp after the args were pushed.  Once the call returns, esp is at or above this,
+    /// depending on whether the callee popped the args (stdcall) or not (cdecl).
+    args_esp: u32,
 }
 impl Future for X86Future {
     type Output = u32;
@@ -168,7 +245,9 @@ impl Future for X86Future {
     fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
         let cpu = self.cpu;
         let cpu = unsafe { &mut *cpu };
-        if cpu.regs.get32(Register::ESP) == self.esp {
+        if cpu.regs.get32(Register::ESP) >= self.args_esp {
+            // Clean up the args in the cdecl case.
+            cpu.regs.set32(Register::ESP, self.esp);
             Poll::Ready(cpu.regs.get32(Register::EAX))
         } else {
             Poll::Pending
@@ -254,7 +333,10 @@ impl X86 {
         for i in 0..self.cpus.len() {
             let i = (self.cur_cpu + i + 1) % self.cpus.len();
             match self.cpus[i].state {
-                CPUState::Running | CPUState::SysCall | CPUState::Error(_) => {
+                CPUState::Running
+                | CPUState::SysCall
+                | CPUState::Error(_)
+                | CPUState::Exception(_) => {
                     self.cur_cpu = i;
                     return;
                 }
@@ -267,14 +349,23 @@ impl X86 {
         for (i, cpu) in self.cpus.iter().enumerate() {
             match cpu.state {
                 CPUState::Running | CPUState::Free => {}
-                CPUState::DebugBreak | CPUState::Error(_) | CPUState::SysCall => {
+                CPUState::DebugBreak
+                | CPUState::Error(_)
+                | CPUState::Exception(_)
+                | CPUState::SysCall => {
                     self.cur_cpu = i;
                     return;
                 }
                 CPUState::Blocked(wait) => match soonest {
                     None => soonest = Some((i, wait)),
                     Some((_, soonest_wait)) => {
-                        if wait < soonest_wait {
+                        // A wait of None means forever, so it sorts after any deadline.
+                        let sooner = match (wait, soonest_wait) {
+                            (Some(wait), Some(soonest_wait)) => wait < soonest_wait,
+                            (Some(_), None) => true,
+                            (None, _) => false,
+                        };
+                        if sooner {
                             soonest = Some((i, wait));
                         }
                     }